@@ -0,0 +1,671 @@
+// 网格上传、渲染通道录制与整帧提交的基准测试
+// 运行：cargo bench --bench render
+#[cfg(not(target_arch = "wasm32"))]
+mod benches {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use criterion::{ Criterion, Throughput };
+    use wgpu::util::DeviceExt;
+
+    use learn_wgpu::mesh::{ MeshData, Vertex };
+    use learn_wgpu::mesh_upload::MeshUploader;
+    use learn_wgpu::primitives;
+    use learn_wgpu::render_bundle_cache::RenderBundleCache;
+    use learn_wgpu::render_queue::{ layer, DrawCall, RecordStats, RenderQueue };
+    use learn_wgpu::render_state::RenderPassStateTracker;
+    use learn_wgpu::secondary_command_buffer::BundleTarget;
+    use learn_wgpu::texture::Texture;
+    use learn_wgpu::triple_buffer::TripleBuffer;
+
+    const WIDTH: u32 = 512;
+    const HEIGHT: u32 = 512;
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    const DRAW_CALLS: u32 = 1000;
+    const UPLOAD_VERTICES: usize = 100_000;
+    const QUEUE_OBJECTS: u32 = 500;
+    const STATIC_DRAW_CALLS: u32 = 10_000;
+    const PIPELINES: usize = 4;
+    const MATERIALS: usize = 16;
+    const UNIFORM_OBJECTS: usize = 1000;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    struct SceneUniform {
+        view_proj: [[f32; 4]; 4],
+        light_dir: [f32; 4],
+    }
+
+    // 每个物体一份 uniform，通过动态偏移绑定
+    struct UniformScene {
+        pipeline: wgpu::RenderPipeline,
+        // 朴素做法：每帧都写入同一个缓冲区
+        single_buffer: wgpu::Buffer,
+        single_bind_group: wgpu::BindGroup,
+        triple_buffer: TripleBuffer<SceneUniform>,
+        triple_bind_groups: [wgpu::BindGroup; 3],
+    }
+
+    // 不依赖窗口的渲染状态，结构与 State 中的前向通道一致
+    struct Headless {
+        device: Arc<wgpu::Device>,
+        queue: wgpu::Queue,
+        pipelines: Vec<wgpu::RenderPipeline>,
+        bind_groups: Vec<wgpu::BindGroup>,
+        color: Texture,
+        depth: Texture,
+        vertex_buffer: wgpu::Buffer,
+        index_buffer: wgpu::Buffer,
+        index_count: u32,
+    }
+
+    impl Headless {
+        async fn new() -> Option<Self> {
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends: wgpu::Backends::all(),
+                ..Default::default()
+            });
+            let adapter = match instance.request_adapter(&wgpu::RequestAdapterOptions::default()).await {
+                Some(adapter) => adapter,
+                None => instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        force_fallback_adapter: true,
+                        ..Default::default()
+                    })
+                    .await?,
+            };
+            let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor::default(), None).await.ok()?;
+
+            let scene_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Bench Scene"),
+                contents: bytemuck::cast_slice(&[[0.0f32; 4]; 5]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("bench_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+            // 渲染队列基准里每个绑定组代表一种材质
+            let bind_groups = (0..MATERIALS)
+                .map(|_| {
+                    device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("bench_bind_group"),
+                        layout: &bind_group_layout,
+                        entries: &[wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: scene_buffer.as_entire_binding(),
+                        }],
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Bench Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("../tests/render/lit.wgsl").into()),
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Bench Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+            let create_pipeline = || device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Bench Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+            let pipelines = (0..PIPELINES).map(|_| create_pipeline()).collect::<Vec<_>>();
+
+            let color = Texture::create_render_target(&device, WIDTH, HEIGHT, FORMAT, wgpu::TextureUsages::empty(), "Bench Color");
+            let depth = Texture::create_render_target(
+                &device,
+                WIDTH,
+                HEIGHT,
+                Texture::DEPTH_FORMAT,
+                wgpu::TextureUsages::empty(),
+                "Bench Depth",
+            );
+            let cube = primitives::cube(0.5);
+            let (vertex_buffer, index_buffer) = cube.upload(&device, &queue);
+
+            Some(Self {
+                device: Arc::new(device),
+                queue,
+                pipelines,
+                bind_groups,
+                color,
+                depth,
+                vertex_buffer,
+                index_buffer,
+                index_count: cube.index_count(),
+            })
+        }
+
+        fn record_frame(&self) -> wgpu::CommandBuffer {
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Encoder"),
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Discard,
+                        }),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(&self.pipelines[0]);
+                render_pass.set_bind_group(0, &self.bind_groups[0], &[]);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                for instance in 0..DRAW_CALLS {
+                    render_pass.draw_indexed(0..self.index_count, 0, instance..instance + 1);
+                }
+            }
+            encoder.finish()
+        }
+
+        fn uniform_scene(&self) -> UniformScene {
+            let device = &self.device;
+            let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("bench_dynamic_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<SceneUniform>() as u64),
+                    },
+                    count: None,
+                }],
+            });
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Bench Uniform Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("../tests/render/lit.wgsl").into()),
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Bench Uniform Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Bench Uniform Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[Vertex::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+
+            let triple_buffer = TripleBuffer::new(device, UNIFORM_OBJECTS, "Bench Triple Uniform Buffer");
+            let triple_bind_groups = triple_buffer.create_bind_groups(device, &bind_group_layout, 0, "bench_triple_bind_group");
+            let single_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Bench Single Uniform Buffer"),
+                size: triple_buffer.stride() * UNIFORM_OBJECTS as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let single_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("bench_single_bind_group"),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &single_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<SceneUniform>() as u64),
+                    }),
+                }],
+            });
+
+            UniformScene { pipeline, single_buffer, single_bind_group, triple_buffer, triple_bind_groups }
+        }
+
+        // 每帧更新 1000 个物体的 uniform 并逐个以动态偏移绘制，提交后不等待 GPU
+        fn draw_uniform_scene(&self, scene: &mut UniformScene, triple: bool, frame: u32) {
+            let uniform = |object: usize| {
+                let mut view_proj = [[0.0; 4]; 4];
+                for (i, column) in view_proj.iter_mut().enumerate() {
+                    column[i] = 0.1;
+                }
+                view_proj[3] = [object as f32 / UNIFORM_OBJECTS as f32 - 0.5, 0.0, 0.5, 1.0];
+                SceneUniform { view_proj, light_dir: [0.0, 1.0, frame as f32, 0.0] }
+            };
+            let offsets = (0..UNIFORM_OBJECTS)
+                .map(|object| {
+                    if triple {
+                        scene.triple_buffer.write(&self.queue, object, &uniform(object))
+                    } else {
+                        let offset = scene.triple_buffer.stride() * object as u64;
+                        self.queue.write_buffer(&scene.single_buffer, offset, bytemuck::bytes_of(&uniform(object)));
+                        offset as u32
+                    }
+                })
+                .collect::<Vec<_>>();
+            let bind_group = if triple {
+                &scene.triple_bind_groups[scene.triple_buffer.current_index()]
+            } else {
+                &scene.single_bind_group
+            };
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Uniform Encoder"),
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Uniform Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations::default()),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(&scene.pipeline);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                for offset in offsets {
+                    render_pass.set_bind_group(0, bind_group, &[offset]);
+                    render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+                }
+            }
+            self.queue.submit(std::iter::once(encoder.finish()));
+            self.device.poll(wgpu::Maintain::Poll);
+            if triple {
+                scene.triple_buffer.advance();
+            }
+        }
+
+        fn static_queue(&self, draws: u32) -> RenderQueue<'_> {
+            let mut queue = RenderQueue::new();
+            for instance in 0..draws {
+                queue.push(
+                    DrawCall::new(&self.pipelines[0])
+                        .with_bind_group(0, &self.bind_groups[instance as usize % MATERIALS])
+                        .with_vertex_buffer(0, &self.vertex_buffer, ..)
+                        .with_index_buffer(&self.index_buffer, .., wgpu::IndexFormat::Uint16)
+                        .with_elements(0..self.index_count)
+                        .with_instances(instance..instance + 1),
+                );
+            }
+            queue.sort();
+            queue
+        }
+
+        // 静态场景：cache 为 Some 时复用渲染包，否则每帧重新录制所有绘制
+        fn record_static_scene(&self, cache: Option<&mut RenderBundleCache>) -> wgpu::CommandBuffer {
+            let queue = self.static_queue(STATIC_DRAW_CALLS);
+            let target = BundleTarget::new(&[FORMAT], Some(Texture::DEPTH_FORMAT));
+            let bundle = cache.map(|cache| cache.get_or_record("static", &self.device, &target, &queue));
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Static Encoder"),
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Static Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations::default()),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                match bundle {
+                    Some(bundle) => render_pass.execute_bundles(std::iter::once(bundle)),
+                    None => {
+                        queue.record_layers(&mut render_pass, layer::OPAQUE..=layer::DEBUG);
+                    }
+                }
+            }
+            encoder.finish()
+        }
+
+        // 与 record_frame 相同的 1000 次绘制，分成若干份在多个线程上录制成渲染包
+        fn record_frame_parallel(&self, partitions: usize) -> wgpu::CommandBuffer {
+            let mut queue = RenderQueue::new();
+            for instance in 0..DRAW_CALLS {
+                queue.push(
+                    DrawCall::new(&self.pipelines[0])
+                        .with_bind_group(0, &self.bind_groups[0])
+                        .with_vertex_buffer(0, &self.vertex_buffer, ..)
+                        .with_index_buffer(&self.index_buffer, .., wgpu::IndexFormat::Uint16)
+                        .with_elements(0..self.index_count)
+                        .with_instances(instance..instance + 1),
+                );
+            }
+            queue.sort();
+            let target = BundleTarget::new(&[FORMAT], Some(Texture::DEPTH_FORMAT));
+            let bundles = queue.record_parallel(&self.device, &target, layer::OPAQUE..=layer::DEBUG, partitions);
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Parallel Encoder"),
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Parallel Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations::default()),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.execute_bundles(bundles.iter());
+            }
+            encoder.finish()
+        }
+
+        // 1000 个物体，每个物体都设置自己的管线与材质；相邻物体大多相同，tracked 时冗余的调用被跳过
+        fn record_objects(&self, tracked: bool) -> (wgpu::CommandBuffer, RecordStats) {
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Objects Encoder"),
+            });
+            let stats = {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Objects Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations::default()),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                let object = |instance: u32| {
+                    let pipeline = &self.pipelines[(instance as usize * PIPELINES) / DRAW_CALLS as usize];
+                    let bind_group = &self.bind_groups[(instance as usize * MATERIALS) / DRAW_CALLS as usize];
+                    (pipeline, bind_group)
+                };
+                if tracked {
+                    let mut tracker = RenderPassStateTracker::new(&mut render_pass);
+                    for instance in 0..DRAW_CALLS {
+                        let (pipeline, bind_group) = object(instance);
+                        tracker.set_pipeline(pipeline);
+                        tracker.set_bind_group(0, bind_group, &[]);
+                        tracker.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                        tracker.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                        tracker.draw_indexed(0..self.index_count, 0, instance..instance + 1);
+                    }
+                    tracker.stats()
+                } else {
+                    for instance in 0..DRAW_CALLS {
+                        let (pipeline, bind_group) = object(instance);
+                        render_pass.set_pipeline(pipeline);
+                        render_pass.set_bind_group(0, bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                        render_pass.draw_indexed(0..self.index_count, 0, instance..instance + 1);
+                    }
+                    RecordStats {
+                        draws: DRAW_CALLS,
+                        pipeline_switches: DRAW_CALLS,
+                        bind_group_switches: DRAW_CALLS,
+                    }
+                }
+            };
+            (encoder.finish(), stats)
+        }
+
+        // 500 个物体随机分配管线、材质与深度，按 queue 的排序方式录制
+        fn record_queue(&self, material_sort: bool) -> (wgpu::CommandBuffer, RecordStats) {
+            let mut queue = RenderQueue::new();
+            if !material_sort {
+                queue = queue.without_material_sort();
+            }
+            // 固定种子的线性同余生成器，保证每次运行场景相同
+            let mut seed = 0x2545_f491_u32;
+            let mut next = move || {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                seed >> 8
+            };
+            for instance in 0..QUEUE_OBJECTS {
+                queue.push(
+                    DrawCall::new(&self.pipelines[next() as usize % PIPELINES])
+                        .with_bind_group(0, &self.bind_groups[next() as usize % MATERIALS])
+                        .with_vertex_buffer(0, &self.vertex_buffer, ..)
+                        .with_index_buffer(&self.index_buffer, .., wgpu::IndexFormat::Uint16)
+                        .with_elements(0..self.index_count)
+                        .with_instances(instance..instance + 1)
+                        .with_depth((next() % 1000) as f32 / 10.0),
+                );
+            }
+            queue.sort();
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bench Queue Encoder"),
+            });
+            let stats = {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Bench Queue Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &self.color.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &self.depth.view,
+                        depth_ops: Some(wgpu::Operations::default()),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                queue.record_layers(&mut render_pass, layer::OPAQUE..=layer::DEBUG)
+            };
+            (encoder.finish(), stats)
+        }
+    }
+
+    // u16 索引只能引用前 65536 个顶点，这里只关心上传的字节量
+    fn large_mesh() -> Arc<MeshData> {
+        let vertices = (0..UPLOAD_VERTICES)
+            .map(|i| Vertex {
+                position: [i as f32, 0.0, 0.0],
+                normal: [0.0, 1.0, 0.0],
+                tex_coords: [0.0, 0.0],
+                uv1: [0.0, 0.0],
+            })
+            .collect();
+        let indices = (0..UPLOAD_VERTICES).map(|i| (i % u16::MAX as usize) as u16).collect();
+        Arc::new(MeshData { vertices, indices })
+    }
+
+    pub fn render_benches(c: &mut Criterion) {
+        let runtime = tokio::runtime::Runtime::new().expect("failed to build tokio runtime");
+        let Some(headless) = runtime.block_on(Headless::new()) else {
+            eprintln!("no GPU adapter available, skipping render benchmarks");
+            return;
+        };
+
+        let mesh = large_mesh();
+        let mut uploader = MeshUploader::new(headless.device.clone());
+        let mut group = c.benchmark_group("mesh_upload");
+        group.throughput(Throughput::Bytes(
+            (std::mem::size_of_val(mesh.vertices.as_slice()) + std::mem::size_of_val(mesh.indices.as_slice())) as u64,
+        ));
+        group.bench_function("staging_100k_vertices", |b| {
+            let _guard = runtime.enter();
+            b.iter(|| {
+                uploader.upload(mesh.clone());
+                let mut commands = Vec::new();
+                while uploader.pending() > 0 {
+                    let (mut ready, _meshes) = uploader.collect();
+                    commands.append(&mut ready);
+                    std::thread::yield_now();
+                }
+                headless.queue.submit(commands);
+                headless.device.poll(wgpu::Maintain::Wait);
+            });
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("render_pass");
+        group.throughput(Throughput::Elements(DRAW_CALLS as u64));
+        group.bench_function("record_1000_draws", |b| {
+            b.iter(|| headless.record_frame());
+        });
+        group.bench_function("record_1000_draws_4_threads", |b| {
+            b.iter(|| headless.record_frame_parallel(4));
+        });
+        for (name, tracked) in [("redundant_state_1000_objects", false), ("tracked_state_1000_objects", true)] {
+            let (_, stats) = headless.record_objects(tracked);
+            eprintln!(
+                "render_pass/{name}: {} pipeline switches, {} bind group switches",
+                stats.pipeline_switches, stats.bind_group_switches,
+            );
+            group.bench_function(name, |b| {
+                b.iter(|| headless.record_objects(tracked));
+            });
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group("render_queue");
+        group.throughput(Throughput::Elements(QUEUE_OBJECTS as u64));
+        for (name, material_sort) in [("depth_sorted_500", false), ("material_sorted_500", true)] {
+            let (_, stats) = headless.record_queue(material_sort);
+            eprintln!(
+                "render_queue/{name}: {} pipeline switches, {} bind group switches",
+                stats.pipeline_switches, stats.bind_group_switches,
+            );
+            group.bench_function(name, |b| {
+                b.iter(|| headless.record_queue(material_sort));
+            });
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group("static_scene");
+        group.throughput(Throughput::Elements(STATIC_DRAW_CALLS as u64));
+        group.bench_function("rerecord_10k_draws", |b| {
+            b.iter(|| headless.record_static_scene(None));
+        });
+        let mut cache = RenderBundleCache::new();
+        group.bench_function("cached_bundle_10k_draws", |b| {
+            b.iter(|| headless.record_static_scene(Some(&mut cache)));
+        });
+        eprintln!("static_scene: {} bundle cache hits, {} misses", cache.hits(), cache.misses());
+        group.finish();
+
+        let mut group = c.benchmark_group("uniform_upload");
+        group.throughput(Throughput::Elements(UNIFORM_OBJECTS as u64));
+        let mut scene = headless.uniform_scene();
+        for (name, triple) in [("single_buffer_1000_objects", false), ("triple_buffer_1000_objects", true)] {
+            let mut frame = 0;
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    headless.draw_uniform_scene(&mut scene, triple, frame);
+                    frame += 1;
+                });
+            });
+            headless.device.poll(wgpu::Maintain::Wait);
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group("frame");
+        group.throughput(Throughput::Elements(1));
+        group.measurement_time(Duration::from_secs(10));
+        group.bench_function("record_submit_wait", |b| {
+            b.iter(|| {
+                headless.queue.submit(std::iter::once(headless.record_frame()));
+                headless.device.poll(wgpu::Maintain::Wait);
+            });
+        });
+        group.finish();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_group!(render, benches::render_benches);
+#[cfg(not(target_arch = "wasm32"))]
+criterion::criterion_main!(render);
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
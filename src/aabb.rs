@@ -0,0 +1,81 @@
+use crate::mesh::Vertex;
+use crate::ray_cast::Ray;
+
+// 轴对齐包围盒；min 大于 max 表示空盒
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    pub const EMPTY: Self = Self {
+        min: [f32::INFINITY; 3],
+        max: [f32::NEG_INFINITY; 3],
+    };
+
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        vertices.iter().fold(Self::EMPTY, |aabb, vertex| aabb.including(vertex.position))
+    }
+
+    pub fn including(&self, point: [f32; 3]) -> Self {
+        Self {
+            min: [0, 1, 2].map(|axis| self.min[axis].min(point[axis])),
+            max: [0, 1, 2].map(|axis| self.max[axis].max(point[axis])),
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: [0, 1, 2].map(|axis| self.min[axis].min(other.min[axis])),
+            max: [0, 1, 2].map(|axis| self.max[axis].max(other.max[axis])),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min[axis] > self.max[axis])
+    }
+
+    pub fn center(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| (self.min[axis] + self.max[axis]) * 0.5)
+    }
+
+    pub fn size(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| self.max[axis] - self.min[axis])
+    }
+
+    // 局部包围盒经模型矩阵变换后的世界包围盒（Arvo 1990）：
+    // 对每个输出轴，按矩阵元素的符号分别取 min 或 max，比变换 8 个角更快，结果相同
+    // mat 为列主序，与 cgmath::Matrix4 转换得到的数组一致
+    pub fn transformed(&self, mat: &[[f32; 4]; 4]) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let mut min = [mat[3][0], mat[3][1], mat[3][2]];
+        let mut max = min;
+        for (column, axis) in mat.iter().take(3).enumerate() {
+            for row in 0..3 {
+                let a = axis[row] * self.min[column];
+                let b = axis[row] * self.max[column];
+                min[row] += a.min(b);
+                max[row] += a.max(b);
+            }
+        }
+        Self { min, max }
+    }
+
+    // 射线与包围盒的最近交点参数（slab 方法），起点在盒内时返回 0
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let origin: [f32; 3] = ray.origin.into();
+        let direction: [f32; 3] = ray.direction.into();
+        let (mut t_min, mut t_max) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            let inverse = 1.0 / direction[axis];
+            let t0 = (self.min[axis] - origin[axis]) * inverse;
+            let t1 = (self.max[axis] - origin[axis]) * inverse;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+        (t_min <= t_max).then_some(t_min)
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::mesh::MeshData;
+
+// GPU 资源的 CPU 端镜像，设备丢失后据此重新上传
+#[derive(Default, Clone)]
+pub struct AssetCache {
+    meshes: HashMap<String, Arc<MeshData>>,
+    images: HashMap<String, Arc<image::DynamicImage>>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert_mesh(&mut self, name: impl Into<String>, mesh: MeshData) -> Arc<MeshData> {
+        let mesh = Arc::new(mesh);
+        self.meshes.insert(name.into(), mesh.clone());
+        mesh
+    }
+
+    pub fn insert_image(&mut self, name: impl Into<String>, image: image::DynamicImage) -> Arc<image::DynamicImage> {
+        let image = Arc::new(image);
+        self.images.insert(name.into(), image.clone());
+        image
+    }
+
+    pub fn mesh(&self, name: &str) -> Option<Arc<MeshData>> {
+        self.meshes.get(name).cloned()
+    }
+
+    pub fn image(&self, name: &str) -> Option<Arc<image::DynamicImage>> {
+        self.images.get(name).cloned()
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = (&str, &Arc<image::DynamicImage>)> {
+        self.images.iter().map(|(name, image)| (name.as_str(), image))
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::mpsc::{ self, Receiver, Sender };
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use rodio::{ Decoder, OutputStream, Sink, Source };
+
+enum AudioCommand {
+    Play { name: String, bytes: Arc<Vec<u8>>, volume: f32, looping: bool },
+    Stop { name: String },
+    Shutdown,
+}
+
+// rodio 的 OutputStream 不是 Send，所以放在独立的音频线程里，
+// 主线程只持有命令发送端，AudioManager 因此可以放进事件循环闭包
+pub struct AudioManager {
+    sender: Sender<AudioCommand>,
+    sounds: HashMap<String, Arc<Vec<u8>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AudioManager {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("audio".into())
+            .spawn(move || run_audio_thread(receiver))
+            .expect("failed to spawn audio thread");
+
+        Self {
+            sender,
+            sounds: HashMap::new(),
+            thread: Some(thread),
+        }
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, bytes: Vec<u8>) {
+        self.sounds.insert(name.into(), Arc::new(bytes));
+    }
+
+    pub fn load(&mut self, name: impl Into<String>, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let bytes = std::fs::read(path)?;
+        self.insert(name, bytes);
+        Ok(())
+    }
+
+    pub fn play(&self, name: &str, volume: f32, looping: bool) {
+        let Some(bytes) = self.sounds.get(name) else {
+            tracing::warn!("audio: sound `{name}` is not loaded");
+            return;
+        };
+        let _ = self.sender.send(AudioCommand::Play {
+            name: name.to_string(),
+            bytes: bytes.clone(),
+            volume,
+            looping,
+        });
+    }
+
+    pub fn stop(&self, name: &str) {
+        let _ = self.sender.send(AudioCommand::Stop { name: name.to_string() });
+    }
+}
+
+impl Default for AudioManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for AudioManager {
+    fn drop(&mut self) {
+        let _ = self.sender.send(AudioCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// 让 Decoder 直接读取缓存中的字节，无需复制
+struct SoundBytes(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SoundBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+fn run_audio_thread(receiver: Receiver<AudioCommand>) {
+    let (_stream, handle) = match OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::warn!("audio: no output device available: {e}");
+            // 继续接收命令，避免发送端阻塞或报错
+            while let Ok(command) = receiver.recv() {
+                if let AudioCommand::Shutdown = command {
+                    break;
+                }
+            }
+            return;
+        }
+    };
+
+    // 每个声音名对应一个 Sink，非循环声音在同一个 Sink 中排队播放
+    let mut sinks: HashMap<String, Sink> = HashMap::new();
+
+    while let Ok(command) = receiver.recv() {
+        match command {
+            AudioCommand::Play { name, bytes, volume, looping } => {
+                let source = match Decoder::new(Cursor::new(SoundBytes(bytes))) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        tracing::error!("audio: failed to decode `{name}`: {e}");
+                        continue;
+                    }
+                };
+
+                let reuse = !looping && sinks.get(&name).is_some_and(|sink| !sink.empty());
+                if !reuse {
+                    let sink = match Sink::try_new(&handle) {
+                        Ok(sink) => sink,
+                        Err(e) => {
+                            tracing::error!("audio: failed to create sink for `{name}`: {e}");
+                            continue;
+                        }
+                    };
+                    if let Some(old) = sinks.insert(name.clone(), sink) {
+                        old.stop();
+                    }
+                }
+
+                let sink = &sinks[&name];
+                sink.set_volume(volume);
+                if looping {
+                    sink.append(source.repeat_infinite());
+                } else {
+                    sink.append(source);
+                }
+            }
+            AudioCommand::Stop { name } => {
+                if let Some(sink) = sinks.remove(&name) {
+                    sink.stop();
+                }
+            }
+            AudioCommand::Shutdown => break,
+        }
+    }
+}
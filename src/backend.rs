@@ -0,0 +1,80 @@
+// 根据适配器判断运行的图形后端，决定请求哪些特性与限制、渲染路径能用哪些功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Native,
+    WebGpu,
+    // 浏览器中的 WebGL2：没有计算着色器与存储缓冲区，限制取 downlevel_webgl2_defaults
+    WebGl2,
+}
+
+impl Backend {
+    pub fn detect(info: &wgpu::AdapterInfo) -> Self {
+        match info.backend {
+            wgpu::Backend::BrowserWebGpu => Backend::WebGpu,
+            // 原生的 GL 后端（如 llvmpipe）仍按原生处理，能力由 downlevel 标志决定
+            wgpu::Backend::Gl if cfg!(target_arch = "wasm32") => Backend::WebGl2,
+            _ => Backend::Native,
+        }
+    }
+}
+
+// 协商后的设备能力：请求设备时使用 features 与 limits，渲染路径按其余字段选择回退实现
+#[derive(Debug, Clone)]
+pub struct RenderCapabilities {
+    pub backend: Backend,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub compute_shaders: bool,
+    pub storage_buffers: bool,
+    pub multi_draw_indirect: bool,
+    // 因后端不支持而关闭的功能，用于日志
+    pub disabled: Vec<&'static str>,
+}
+
+impl RenderCapabilities {
+    // requested 为渲染路径希望启用的可选特性，适配器不支持的会被去掉
+    pub fn negotiate(adapter: &wgpu::Adapter, requested: wgpu::Features) -> Self {
+        let backend = Backend::detect(&adapter.get_info());
+        let downlevel = adapter.get_downlevel_capabilities();
+        let mut features = adapter.features() & requested;
+        let mut disabled = Vec::new();
+
+        let limits = match backend {
+            // WebGL2 上 Limits::default() 会请求失败；分辨率相关的限制取适配器实际值
+            Backend::WebGl2 => wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+            Backend::WebGpu | Backend::Native => wgpu::Limits::default(),
+        };
+
+        let compute_shaders = backend != Backend::WebGl2 && downlevel.flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+        if !compute_shaders {
+            disabled.push("compute shaders");
+        }
+        let storage_buffers = compute_shaders && limits.max_storage_buffers_per_shader_stage > 0;
+        if !storage_buffers {
+            disabled.push("storage buffers");
+        }
+
+        let indirect = wgpu::Features::MULTI_DRAW_INDIRECT | wgpu::Features::INDIRECT_FIRST_INSTANCE;
+        if backend == Backend::WebGl2 || !downlevel.flags.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION) {
+            features -= indirect;
+        }
+        let multi_draw_indirect = features.contains(indirect);
+        if requested.contains(indirect) && !multi_draw_indirect {
+            disabled.push("multi-draw-indirect");
+        }
+
+        if !disabled.is_empty() {
+            tracing::warn!("backend: {backend:?} adapter, disabled {}; using fallback paths", disabled.join(", "));
+        }
+
+        Self {
+            backend,
+            features,
+            limits,
+            compute_shaders,
+            storage_buffers,
+            multi_draw_indirect,
+            disabled,
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use std::fmt;
+use std::io;
+use std::path::{ Path, PathBuf };
+use std::sync::mpsc;
+
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::cubemap::{ Cubemap, CubemapCapture, CUBEMAP_FORMAT };
+use crate::shader_preprocessor::ShaderPreprocessor;
+
+pub const BAKED_FACE_SIZE: u32 = 128;
+// 最后一级对应粗糙度 1
+const MAX_MIP_LEVELS: u32 = 6;
+const PREFILTER_SAMPLES: u32 = 128;
+const WORKGROUP_SIZE: u32 = 8;
+// rgba16float
+const TEXEL_SIZE: u64 = 8;
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n'];
+const VK_FORMAT_R16G16B16A16_SFLOAT: u32 = 97;
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_SIZE: usize = 24;
+// 场景哈希以十六进制字符串存放在键值数据中
+const SCENE_HASH_KEY: &str = "learn-wgpu.sceneHash";
+
+#[derive(Debug)]
+pub enum BakedEnvMapError {
+    Io { path: PathBuf, source: io::Error },
+    Parse(ktx2::ParseError),
+    // 格式、面数或层级大小与烘焙结果不符
+    Corrupt(String),
+    // 文件中记录的场景哈希与当前场景不同，需要重新烘焙
+    Stale { expected: u64, found: Option<u64> },
+    Readback(wgpu::BufferAsyncError),
+}
+
+impl fmt::Display for BakedEnvMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BakedEnvMapError::Io { path, source } => write!(f, "baked env map {}: {source}", path.display()),
+            BakedEnvMapError::Parse(e) => write!(f, "failed to parse baked env map: {e}"),
+            BakedEnvMapError::Corrupt(message) => write!(f, "corrupt baked env map: {message}"),
+            BakedEnvMapError::Stale { expected, found: Some(found) } => {
+                write!(f, "baked env map is stale: scene hash {found:016x}, expected {expected:016x}")
+            }
+            BakedEnvMapError::Stale { expected, found: None } => {
+                write!(f, "baked env map has no scene hash, expected {expected:016x}")
+            }
+            BakedEnvMapError::Readback(e) => write!(f, "failed to read back prefiltered env map: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BakedEnvMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BakedEnvMapError::Io { source, .. } => Some(source),
+            BakedEnvMapError::Readback(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// 缓存文件的位置与当前场景的哈希，场景文件变化后旧的缓存不再使用
+#[derive(Debug, Clone)]
+pub struct EnvMapCache {
+    pub path: PathBuf,
+    pub scene_hash: u64,
+}
+
+impl EnvMapCache {
+    // scene_files 为场景引用的 OBJ/glTF 等文件
+    pub fn new(path: impl Into<PathBuf>, scene_files: &[impl AsRef<Path>]) -> Self {
+        Self {
+            path: path.into(),
+            scene_hash: scene_hash(scene_files),
+        }
+    }
+}
+
+// 对文件路径与修改时间做 FNV-1a，结果不依赖标准库哈希算法的版本；不存在的文件按修改时间 0 计入
+pub fn scene_hash(scene_files: &[impl AsRef<Path>]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    for file in scene_files {
+        let file = file.as_ref();
+        let modified = std::fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+        feed(file.as_os_str().as_encoded_bytes());
+        feed(&modified.to_le_bytes());
+    }
+    hash
+}
+
+// 启动时烘焙一次的环境贴图：六个面渲染后按 GGX 预过滤出各级 mip（第 n 级粗糙度为 n / (级数 - 1)），
+// 保存为 KTX2 立方体贴图；之后的运行直接读取文件上传，不再渲染
+pub struct BakedEnvMap {
+    pub texture: wgpu::Texture,
+    // Cube 视图，包含全部 mip，可直接用于 LightProbeSystem 的绑定组布局
+    pub view: wgpu::TextureView,
+    pub face_size: u32,
+    pub mip_level_count: u32,
+}
+
+impl BakedEnvMap {
+    pub fn load(device: &wgpu::Device, queue: &wgpu::Queue, cache: &EnvMapCache) -> Result<Self, BakedEnvMapError> {
+        let bytes = std::fs::read(&cache.path).map_err(|source| BakedEnvMapError::Io { path: cache.path.clone(), source })?;
+        let reader = ktx2::Reader::new(bytes.as_slice()).map_err(BakedEnvMapError::Parse)?;
+        let found = read_scene_hash(&bytes);
+        if found != Some(cache.scene_hash) {
+            return Err(BakedEnvMapError::Stale { expected: cache.scene_hash, found });
+        }
+
+        let header = reader.header();
+        if header.format != Some(ktx2::Format::R16G16B16A16_SFLOAT) || header.supercompression_scheme.is_some() {
+            return Err(BakedEnvMapError::Corrupt(format!("unexpected format {:?}", header.format)));
+        }
+        if header.face_count != 6 || header.layer_count > 1 || header.pixel_width != header.pixel_height {
+            return Err(BakedEnvMapError::Corrupt("not a square cubemap".to_owned()));
+        }
+        let face_size = header.pixel_width;
+        let levels: Vec<&[u8]> = reader.levels().collect();
+        for (level, data) in levels.iter().enumerate() {
+            if data.len() as u64 != level_byte_size(face_size, level as u32) {
+                return Err(BakedEnvMapError::Corrupt(format!("level {level} has {} bytes", data.len())));
+            }
+        }
+
+        Ok(Self::upload(device, queue, face_size, &levels))
+    }
+
+    // 从 position 处渲染场景并预过滤，结果写入 cache.path 后上传；绘制回调的要求见 CubemapCapture
+    pub fn capture<S>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        capture: &CubemapCapture,
+        cache: &EnvMapCache,
+        position: [f32; 3],
+        scene: &S,
+        scene_drawer: impl for<'p> FnMut(&mut wgpu::RenderPass<'p>, &'p S, &Camera),
+    ) -> Result<Self, BakedEnvMapError> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Env Map Bake Encoder"),
+        });
+        let source = capture.record(device, &mut encoder, position, BAKED_FACE_SIZE, scene, scene_drawer);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let data = prefilter(device, queue, &source, BAKED_FACE_SIZE)?;
+        let levels = split_levels(&data, BAKED_FACE_SIZE);
+        let bytes = encode_ktx2(BAKED_FACE_SIZE, &levels, cache.scene_hash);
+
+        let io_error = |source| BakedEnvMapError::Io { path: cache.path.clone(), source };
+        if let Some(parent) = cache.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        std::fs::write(&cache.path, bytes).map_err(io_error)?;
+        tracing::info!(path = %cache.path.display(), scene_hash = cache.scene_hash, "baked env map");
+
+        Ok(Self::upload(device, queue, BAKED_FACE_SIZE, &levels))
+    }
+
+    // 缓存缺失或过期时重新烘焙，其他读取错误记录日志后同样重新烘焙
+    pub fn load_or_capture<S>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        capture: &CubemapCapture,
+        cache: &EnvMapCache,
+        position: [f32; 3],
+        scene: &S,
+        scene_drawer: impl for<'p> FnMut(&mut wgpu::RenderPass<'p>, &'p S, &Camera),
+    ) -> Result<Self, BakedEnvMapError> {
+        match Self::load(device, queue, cache) {
+            Ok(env_map) => return Ok(env_map),
+            Err(BakedEnvMapError::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => {}
+            Err(e @ BakedEnvMapError::Stale { .. }) => tracing::info!("{e}, baking again"),
+            Err(e) => tracing::warn!("baked env map: {e}, baking again"),
+        }
+        Self::capture(device, queue, capture, cache, position, scene, scene_drawer)
+    }
+
+    fn upload(device: &wgpu::Device, queue: &wgpu::Queue, face_size: u32, levels: &[&[u8]]) -> Self {
+        let mip_level_count = levels.len() as u32;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Baked Env Map"),
+            size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBEMAP_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (level, data) in levels.iter().enumerate() {
+            let size = level_size(face_size, level as u32);
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size * TEXEL_SIZE as u32),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 6,
+                },
+            );
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Baked Env Map View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        Self { texture, view, face_size, mip_level_count }
+    }
+}
+
+fn mip_level_count(face_size: u32) -> u32 {
+    (face_size.ilog2() + 1).min(MAX_MIP_LEVELS)
+}
+
+fn level_size(face_size: u32, level: u32) -> u32 {
+    (face_size >> level).max(1)
+}
+
+fn level_byte_size(face_size: u32, level: u32) -> u64 {
+    let size = level_size(face_size, level) as u64;
+    6 * size * size * TEXEL_SIZE
+}
+
+fn split_levels(data: &[u8], face_size: u32) -> Vec<&[u8]> {
+    let mut offset = 0;
+    (0..mip_level_count(face_size))
+        .map(|level| {
+            let length = level_byte_size(face_size, level) as usize;
+            offset += length;
+            &data[offset - length..offset]
+        })
+        .collect()
+}
+
+// 在计算着色器中把各级结果直接写成 KTX2 的层级数据再读回，避免逐层复制立方体纹理
+fn prefilter(device: &wgpu::Device, queue: &wgpu::Queue, source: &wgpu::Texture, face_size: u32) -> Result<Vec<u8>, BakedEnvMapError> {
+    let mut preprocessor = ShaderPreprocessor::new();
+    Cubemap::register_includes(&mut preprocessor);
+    let shader = preprocessor.compile(device, "Env Map Prefilter Shader", include_str!("env_map_prefilter.wgsl"));
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("env_map_prefilter_bind_group_layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Env Map Prefilter Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Env Map Prefilter Pipeline"),
+        layout: Some(&layout),
+        module: &shader,
+        entry_point: "cs_main",
+    });
+
+    let source_view = source.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("Env Map Prefilter Source"),
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        ..Default::default()
+    });
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Env Map Prefilter Sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let level_count = mip_level_count(face_size);
+    let total_size: u64 = (0..level_count).map(|level| level_byte_size(face_size, level)).sum();
+    let output = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Env Map Prefilter Output"),
+        size: total_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Env Map Prefilter Readback"),
+        size: total_size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut offset = 0u32;
+    let bind_groups: Vec<(u32, wgpu::BindGroup)> = (0..level_count)
+        .map(|level| {
+            let size = level_size(face_size, level);
+            let roughness = if level_count > 1 { level as f32 / (level_count - 1) as f32 } else { 0.0 };
+            let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Env Map Prefilter Params"),
+                contents: bytemuck::cast_slice(&[size, offset, roughness.to_bits(), PREFILTER_SAMPLES]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            offset += 6 * size * size;
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("env_map_prefilter_bind_group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: output.as_entire_binding(),
+                    },
+                ],
+            });
+            (size, bind_group)
+        })
+        .collect();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Env Map Prefilter Encoder"),
+    });
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Env Map Prefilter Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline);
+        for (size, bind_group) in &bind_groups {
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            let workgroups = size.div_ceil(WORKGROUP_SIZE);
+            compute_pass.dispatch_workgroups(workgroups, workgroups, 6);
+        }
+    }
+    encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, total_size);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let (sender, receiver) = mpsc::channel();
+    readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver
+        .recv()
+        .expect("map_async callback runs during poll")
+        .map_err(BakedEnvMapError::Readback)?;
+    let data = readback.slice(..).get_mapped_range().to_vec();
+    readback.unmap();
+    Ok(data)
+}
+
+// 非超压缩的 KTX2：头、层级索引、数据格式描述、键值数据，层级数据从最小一级开始存放
+fn encode_ktx2(face_size: u32, levels: &[&[u8]], scene_hash: u64) -> Vec<u8> {
+    let dfd = basic_data_format_descriptor();
+    let kvd = key_value_entry(SCENE_HASH_KEY, &format!("{scene_hash:016x}"));
+    let dfd_offset = KTX2_HEADER_SIZE + KTX2_LEVEL_INDEX_SIZE * levels.len();
+    let kvd_offset = dfd_offset + dfd.len();
+    // 层级数据按 lcm(纹素大小, 4) 对齐
+    let data_offset = (kvd_offset + kvd.len()).next_multiple_of(TEXEL_SIZE as usize);
+
+    let mut level_offsets = vec![0; levels.len()];
+    let mut offset = data_offset;
+    for (level, data) in levels.iter().enumerate().rev() {
+        level_offsets[level] = offset;
+        offset += data.len();
+    }
+
+    let mut bytes = Vec::with_capacity(offset);
+    bytes.extend_from_slice(&KTX2_IDENTIFIER);
+    let header = [VK_FORMAT_R16G16B16A16_SFLOAT, 2, face_size, face_size, 0, 0, 6, levels.len() as u32, 0];
+    for value in header {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in [dfd_offset, dfd.len(), kvd_offset, kvd.len()] {
+        bytes.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+    // 没有超压缩全局数据
+    bytes.extend_from_slice(&[0; 16]);
+    for (data, level_offset) in levels.iter().zip(&level_offsets) {
+        for value in [*level_offset, data.len(), data.len()] {
+            bytes.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+    }
+    bytes.extend_from_slice(&dfd);
+    bytes.extend_from_slice(&kvd);
+    bytes.resize(data_offset, 0);
+    for data in levels.iter().rev() {
+        bytes.extend_from_slice(data);
+    }
+    bytes
+}
+
+// Khronos 基本数据格式描述块：线性 RGBA，每个通道为 16 位有符号浮点
+fn basic_data_format_descriptor() -> Vec<u8> {
+    const SAMPLE_FLOAT_SIGNED: u8 = 0xC0;
+    let block_size = 24 + 16 * 4;
+    let mut dfd = Vec::with_capacity(4 + block_size);
+    dfd.extend_from_slice(&(4 + block_size as u32).to_le_bytes());
+    // vendorId 与 descriptorType 均为 0
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    dfd.extend_from_slice(&2u16.to_le_bytes());
+    dfd.extend_from_slice(&(block_size as u16).to_le_bytes());
+    // 颜色模型 RGBSDA，原色 BT.709，线性传递函数，非预乘
+    dfd.extend_from_slice(&[1, 1, 1, 0]);
+    // 纹素块 1×1×1×1，平面 0 每块 8 字节
+    dfd.extend_from_slice(&[0, 0, 0, 0]);
+    dfd.extend_from_slice(&[TEXEL_SIZE as u8, 0, 0, 0, 0, 0, 0, 0]);
+    for (index, channel) in [0u8, 1, 2, 15].into_iter().enumerate() {
+        dfd.extend_from_slice(&(index as u16 * 16).to_le_bytes());
+        dfd.push(15);
+        dfd.push(channel | SAMPLE_FLOAT_SIGNED);
+        dfd.extend_from_slice(&0u32.to_le_bytes());
+        dfd.extend_from_slice(&(-1.0f32).to_bits().to_le_bytes());
+        dfd.extend_from_slice(&1.0f32.to_bits().to_le_bytes());
+    }
+    dfd
+}
+
+fn key_value_entry(key: &str, value: &str) -> Vec<u8> {
+    let length = key.len() + value.len() + 2;
+    let mut entry = Vec::with_capacity(4 + length.next_multiple_of(4));
+    entry.extend_from_slice(&(length as u32).to_le_bytes());
+    entry.extend_from_slice(key.as_bytes());
+    entry.push(0);
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(0);
+    entry.resize(4 + length.next_multiple_of(4), 0);
+    entry
+}
+
+// ktx2 不公开键值数据，按头部中的偏移自行解析
+fn read_scene_hash(bytes: &[u8]) -> Option<u64> {
+    let word = |offset: usize| Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?) as usize);
+    let kvd_offset = word(56)?;
+    let mut kvd = bytes.get(kvd_offset..kvd_offset + word(60)?)?;
+    while kvd.len() >= 4 {
+        let length = u32::from_le_bytes(kvd[..4].try_into().ok()?) as usize;
+        let entry = kvd.get(4..4 + length)?;
+        let mut parts = entry.splitn(2, |byte| *byte == 0);
+        if parts.next()? == SCENE_HASH_KEY.as_bytes() {
+            let value = parts.next()?.strip_suffix(&[0])?;
+            return u64::from_str_radix(std::str::from_utf8(value).ok()?, 16).ok();
+        }
+        kvd = kvd.get(4 + length.next_multiple_of(4)..).unwrap_or(&[]);
+    }
+    None
+}
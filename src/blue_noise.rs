@@ -0,0 +1,249 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use wgpu::util::DeviceExt;
+
+pub const BLUE_NOISE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+// 排序每个像素一步，全部由一个工作组串行推进，尺寸再大生成时间难以接受
+pub const MAX_BLUE_NOISE_SIZE: u32 = 128;
+// 与 blue_noise.wgsl 中的 STEPS_PER_DISPATCH 一致
+const STEPS_PER_DISPATCH: u32 = 32;
+const WORKGROUP_SIZE: u32 = 256;
+// 初始随机点占像素的比例
+const INITIAL_DENSITY: f32 = 0.1;
+const SIGMA: f32 = 1.5;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BlueNoiseParams {
+    size: u32,
+    initial_ones: u32,
+    max_swaps: u32,
+    sigma: f32,
+}
+
+// 用 void-and-cluster（Ulichney 1993）生成可平铺的蓝噪声阈值图：
+// 随机撒点后反复把最紧密团簇中的点移到最大空洞直到收敛，再逐点移除 / 填入得到每个像素的排序，
+// 排序归一化后写入单通道纹理。填入阶段全程寻找最大空洞，没有按原论文在过半后改为反相寻找团簇。
+// 交换与排序循环在计算着色器中分多次调度推进，结果以 PNG 缓存在可执行文件旁，之后直接读取
+pub struct BlueNoiseGenerator;
+
+impl BlueNoiseGenerator {
+    // size 被限制在 4..=MAX_BLUE_NOISE_SIZE；需要计算着色器，缓存命中时除外
+    pub fn generate(device: &wgpu::Device, queue: &wgpu::Queue, size: u32) -> wgpu::Texture {
+        let size = size.clamp(4, MAX_BLUE_NOISE_SIZE);
+        let cache_path = Self::cache_path(size);
+        let cached = cache_path.as_ref().and_then(|path| match image::open(path) {
+            Ok(image) if image.width() == size && image.height() == size => Some(image.to_luma8().into_raw()),
+            Ok(_) => {
+                tracing::warn!("blue noise: cached {} has the wrong size, regenerating", path.display());
+                None
+            }
+            Err(_) => None,
+        });
+
+        let pixels = match cached {
+            Some(pixels) => pixels,
+            None => {
+                let pixels: Vec<u8> = match Self::compute(device, queue, size) {
+                    // 每个灰度级分到相同数量的像素
+                    Ok(ranks) => ranks.iter().map(|&rank| (rank as u64 * 256 / (size * size) as u64) as u8).collect(),
+                    Err(e) => {
+                        tracing::warn!("blue noise: readback failed ({e}), falling back to the initial white noise");
+                        (0..size * size).map(|index| (hash(index) >> 24) as u8).collect()
+                    }
+                };
+                if let Some(path) = &cache_path {
+                    if let Err(e) = image::save_buffer(path, &pixels, size, size, image::ColorType::L8) {
+                        tracing::warn!("blue noise: failed to cache {}: {e}", path.display());
+                    }
+                }
+                pixels
+            }
+        };
+
+        device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Blue Noise"),
+                size: wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: BLUE_NOISE_FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            &pixels,
+        )
+    }
+
+    fn cache_path(size: u32) -> Option<PathBuf> {
+        let exe = std::env::current_exe().ok()?;
+        Some(exe.parent()?.join(format!("blue_noise_{size}.png")))
+    }
+
+    // 返回每个像素的排序 0..size²
+    fn compute(device: &wgpu::Device, queue: &wgpu::Queue, size: u32) -> Result<Vec<u32>, wgpu::BufferAsyncError> {
+        let count = size * size;
+        let initial_ones = ((count as f32 * INITIAL_DENSITY) as u32).max(1);
+        // 确定性的随机初始图案：按哈希值取前 initial_ones 个像素
+        let mut order: Vec<u32> = (0..count).collect();
+        order.sort_by_key(|&index| hash(index));
+        let mut bits = vec![0u32; count as usize];
+        for &index in &order[..initial_ones as usize] {
+            bits[index as usize] = 1;
+        }
+
+        let params = BlueNoiseParams {
+            size,
+            initial_ones,
+            max_swaps: count,
+            sigma: SIGMA,
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Blue Noise Params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bits_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Blue Noise Bits"),
+            contents: bytemuck::cast_slice(&bits),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let buffer_size = (count as usize * std::mem::size_of::<u32>()) as wgpu::BufferAddress;
+        let storage = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: buffer_size,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+                mapped_at_creation: false,
+            })
+        };
+        let energy = storage("Blue Noise Energy", wgpu::BufferUsages::empty());
+        let prototype_bits = storage("Blue Noise Prototype Bits", wgpu::BufferUsages::empty());
+        let prototype_energy = storage("Blue Noise Prototype Energy", wgpu::BufferUsages::empty());
+        let ranks = storage("Blue Noise Ranks", wgpu::BufferUsages::COPY_SRC);
+        // 全零即从松弛阶段开始
+        let state = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Blue Noise State"),
+            size: 2 * std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Blue Noise Readback"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("blue_noise_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1),
+                storage_entry(2),
+                storage_entry(3),
+                storage_entry(4),
+                storage_entry(5),
+                storage_entry(6),
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("blue_noise_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: bits_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: energy.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: prototype_bits.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: prototype_energy.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 5, resource: ranks.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 6, resource: state.as_entire_binding() },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blue Noise Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("blue_noise.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blue Noise Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let create_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+        let energy_pipeline = create_pipeline("Blue Noise Energy Pipeline", "cs_energy");
+        let step_pipeline = create_pipeline("Blue Noise Step Pipeline", "cs_step");
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Blue Noise Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Blue Noise Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.set_pipeline(&energy_pipeline);
+            compute_pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE), 1, 1);
+            // 松弛至多 max_swaps 步，两个排序阶段合计 count 步
+            compute_pass.set_pipeline(&step_pipeline);
+            for _ in 0..(params.max_swaps + count).div_ceil(STEPS_PER_DISPATCH) {
+                compute_pass.dispatch_workgroups(1, 1, 1);
+            }
+        }
+        encoder.copy_buffer_to_buffer(&ranks, 0, &readback, 0, buffer_size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = mpsc::channel();
+        readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv().expect("map_async callback runs during poll")?;
+        let ranks = bytemuck::cast_slice(&readback.slice(..).get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(ranks)
+    }
+}
+
+// 整数哈希（lowbias32），用于确定性的初始图案
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
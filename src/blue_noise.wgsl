@@ -0,0 +1,179 @@
+// void-and-cluster 的每一步都依赖上一步的全局结果，由单个工作组推进：像素 p 由 p % WORKGROUP_SIZE 号线程负责，
+// 每一步先归约出全局的最紧密团簇 / 最大空洞，再由各线程更新自己像素的能量。
+// 一次调度只推进 STEPS_PER_DISPATCH 步，进度保存在 state 中，避免单次调度运行过久
+const WORKGROUP_SIZE: u32 = 256u;
+const STEPS_PER_DISPATCH: u32 = 32u;
+const NONE: u32 = 0xffffffffu;
+
+const PHASE_RELAX: u32 = 0u;
+const PHASE_REMOVE: u32 = 1u;
+const PHASE_FILL: u32 = 2u;
+const PHASE_DONE: u32 = 3u;
+
+struct Params {
+    size: u32,
+    initial_ones: u32,
+    max_swaps: u32,
+    sigma: f32,
+};
+
+struct State {
+    phase: u32,
+    // 松弛阶段为已交换次数，排序阶段为下一个序号
+    counter: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+// 初始为 CPU 生成的随机点，随后由着色器原地修改
+@group(0) @binding(1)
+var<storage, read_write> bits: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> energy: array<f32>;
+// 松弛后的原型图案，排序的两个阶段都从它出发
+@group(0) @binding(3)
+var<storage, read_write> prototype_bits: array<u32>;
+@group(0) @binding(4)
+var<storage, read_write> prototype_energy: array<f32>;
+@group(0) @binding(5)
+var<storage, read_write> ranks: array<u32>;
+@group(0) @binding(6)
+var<storage, read_write> state: State;
+
+var<workgroup> best_value: array<f32, WORKGROUP_SIZE>;
+var<workgroup> best_index: array<u32, WORKGROUP_SIZE>;
+
+// 环绕（可平铺）距离下的高斯权重
+fn weight(a: u32, b: u32) -> f32 {
+    let size = i32(params.size);
+    let delta = abs(vec2<i32>(i32(a % params.size), i32(a / params.size)) - vec2<i32>(i32(b % params.size), i32(b / params.size)));
+    let d = vec2f(min(delta, vec2<i32>(size) - delta));
+    return exp(-dot(d, d) / (2.0 * params.sigma * params.sigma));
+}
+
+// 每个线程计算一个像素的初始能量
+@compute @workgroup_size(256)
+fn cs_energy(@builtin(global_invocation_id) id: vec3<u32>) {
+    let count = params.size * params.size;
+    let p = id.x;
+    if p >= count {
+        return;
+    }
+    var sum = 0.0;
+    for (var q = 0u; q < count; q++) {
+        if bits[q] == 1u {
+            sum += weight(p, q);
+        }
+    }
+    energy[p] = sum;
+}
+
+fn better(value: f32, index: u32, other_value: f32, other_index: u32, find_max: bool) -> bool {
+    if other_index == NONE {
+        return index != NONE;
+    }
+    if index == NONE {
+        return false;
+    }
+    if value == other_value {
+        return index < other_index;
+    }
+    return select(value < other_value, value > other_value, find_max);
+}
+
+// 在 bits 等于 bit 的像素中找能量最大（find_max）或最小的一个，没有时返回 NONE
+fn find_extreme(thread: u32, bit: u32, find_max: bool) -> u32 {
+    var value = 0.0;
+    var index = NONE;
+    for (var p = thread; p < params.size * params.size; p += WORKGROUP_SIZE) {
+        if bits[p] == bit && better(energy[p], p, value, index, find_max) {
+            value = energy[p];
+            index = p;
+        }
+    }
+    best_value[thread] = value;
+    best_index[thread] = index;
+    workgroupBarrier();
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        if thread < stride && better(best_value[thread + stride], best_index[thread + stride], best_value[thread], best_index[thread], find_max) {
+            best_value[thread] = best_value[thread + stride];
+            best_index[thread] = best_index[thread + stride];
+        }
+        workgroupBarrier();
+    }
+    let result = best_index[0];
+    // 下一次归约覆盖共享数组之前，所有线程都要读到结果
+    workgroupBarrier();
+    return result;
+}
+
+// 把像素 pixel 置为 bit，并更新本线程负责的像素的能量
+fn set_bit(thread: u32, pixel: u32, bit: u32) {
+    let sign = select(-1.0, 1.0, bit == 1u);
+    for (var p = thread; p < params.size * params.size; p += WORKGROUP_SIZE) {
+        energy[p] += sign * weight(p, pixel);
+        if p == pixel {
+            bits[p] = bit;
+        }
+    }
+}
+
+@compute @workgroup_size(256)
+fn cs_step(@builtin(local_invocation_index) thread: u32) {
+    let count = params.size * params.size;
+    // 所有线程持有相同的进度，结束时由 0 号线程写回
+    var phase = state.phase;
+    var counter = state.counter;
+    workgroupBarrier();
+
+    for (var step = 0u; step < STEPS_PER_DISPATCH; step++) {
+        if phase == PHASE_RELAX {
+            // 把最紧密团簇中的点移到最大空洞，直到移走的点又落回原处
+            let cluster = find_extreme(thread, 1u, true);
+            set_bit(thread, cluster, 0u);
+            let hole = find_extreme(thread, 0u, false);
+            set_bit(thread, hole, 1u);
+            counter += 1u;
+            if hole == cluster || counter >= params.max_swaps {
+                for (var p = thread; p < count; p += WORKGROUP_SIZE) {
+                    prototype_bits[p] = bits[p];
+                    prototype_energy[p] = energy[p];
+                }
+                phase = PHASE_REMOVE;
+                counter = params.initial_ones;
+            }
+        } else if phase == PHASE_REMOVE {
+            // 逐个移走最紧密团簇，序号从 initial_ones - 1 递减
+            let cluster = find_extreme(thread, 1u, true);
+            set_bit(thread, cluster, 0u);
+            counter -= 1u;
+            if cluster % WORKGROUP_SIZE == thread {
+                ranks[cluster] = counter;
+            }
+            if counter == 0u {
+                for (var p = thread; p < count; p += WORKGROUP_SIZE) {
+                    bits[p] = prototype_bits[p];
+                    energy[p] = prototype_energy[p];
+                }
+                phase = PHASE_FILL;
+                counter = params.initial_ones;
+            }
+        } else if phase == PHASE_FILL {
+            // 从原型出发逐个填入最大空洞，直到填满
+            let hole = find_extreme(thread, 0u, false);
+            set_bit(thread, hole, 1u);
+            if hole % WORKGROUP_SIZE == thread {
+                ranks[hole] = counter;
+            }
+            counter += 1u;
+            if counter == count {
+                phase = PHASE_DONE;
+            }
+        }
+    }
+
+    if thread == 0u {
+        state.phase = phase;
+        state.counter = counter;
+    }
+}
@@ -0,0 +1,151 @@
+use wgpu::util::DeviceExt;
+
+pub const BRICK_PATTERN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BrickParams {
+    brick_color: [f32; 3],
+    color_variation: f32,
+    mortar_color: [f32; 3],
+    mortar_width: f32,
+    size: u32,
+    columns: u32,
+    rows: u32,
+    seed: u32,
+}
+
+// 砖墙纹理：计算着色器按砖块大小铺砖，奇数行错开半块，砖块之间留 mortar_width 宽的灰缝，
+// 每块砖的亮度按 seed 随机浮动 color_variation。每行砖块数与行数取整（行数取偶数），
+// 实际砖块大小可能与请求的略有不同，但纹理可以无缝重复，直接作为漫反射贴图（线性颜色）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BrickPatternTexture {
+    pub brick_color: [f32; 3],
+    pub mortar_color: [f32; 3],
+    pub color_variation: f32,
+    pub seed: u32,
+}
+
+impl Default for BrickPatternTexture {
+    fn default() -> Self {
+        Self {
+            brick_color: [0.45, 0.16, 0.1],
+            mortar_color: [0.6, 0.58, 0.55],
+            color_variation: 0.15,
+            seed: 0,
+        }
+    }
+}
+
+impl BrickPatternTexture {
+    // 尺寸均以纹素为单位；需要计算着色器
+    pub fn generate(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: u32,
+        brick_width: u32,
+        brick_height: u32,
+        mortar_width: u32,
+    ) -> wgpu::Texture {
+        let size = size.clamp(1, device.limits().max_texture_dimension_2d);
+        let columns = (size as f32 / brick_width.max(1) as f32).round().clamp(1.0, size as f32) as u32;
+        let rows = ((size as f32 / brick_height.max(1) as f32 / 2.0).round() as u32 * 2).clamp(2, size.max(2));
+
+        let params = BrickParams {
+            brick_color: self.brick_color,
+            color_variation: self.color_variation.clamp(0.0, 1.0),
+            mortar_color: self.mortar_color,
+            mortar_width: mortar_width as f32,
+            size,
+            columns,
+            rows,
+            seed: self.seed,
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Brick Pattern Params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Brick Pattern"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: BRICK_PATTERN_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("brick_pattern_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: BRICK_PATTERN_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("brick_pattern_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&view) },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Brick Pattern Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("brick_pattern.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Brick Pattern Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Brick Pattern Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Brick Pattern Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Brick Pattern Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(size.div_ceil(WORKGROUP_SIZE), size.div_ceil(WORKGROUP_SIZE), 1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        texture
+    }
+}
@@ -0,0 +1,57 @@
+// 砖墙图案：奇数行错开半块砖，砖块之间填灰缝
+
+struct BrickParams {
+    brick_color: vec3f,
+    // 每块砖亮度的随机浮动幅度
+    color_variation: f32,
+    mortar_color: vec3f,
+    // 灰缝宽度，单位为纹素
+    mortar_width: f32,
+    size: u32,
+    // 每行的砖块数与行数，砖块大小为 size 除以它们，行数为偶数时上下边缘衔接
+    columns: u32,
+    rows: u32,
+    seed: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: BrickParams;
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// 整数哈希（lowbias32），与 worley_noise.rs 相同
+fn hash(value: u32) -> u32 {
+    var x = value;
+    x ^= x >> 16u;
+    x *= 0x7feb352du;
+    x ^= x >> 15u;
+    x *= 0x846ca68bu;
+    x ^= x >> 16u;
+    return x;
+}
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= vec2u(params.size)) {
+        return;
+    }
+    let brick_size = f32(params.size) / vec2f(f32(params.columns), f32(params.rows));
+    let position = vec2f(id.xy) + 0.5;
+    let row = u32(position.y / brick_size.y);
+    // 奇数行错开半块砖；砖块序号按列数取模，跨过右边缘的砖与左边缘是同一块
+    let shifted = position.x + f32(row & 1u) * brick_size.x * 0.5;
+    let column = u32(shifted / brick_size.x) % params.columns;
+    let local = vec2f(shifted % brick_size.x, position.y % brick_size.y);
+
+    // 灰缝平分在砖块两侧
+    let half_mortar = params.mortar_width * 0.5;
+    let edge = min(local, brick_size - local);
+    if min(edge.x, edge.y) < half_mortar {
+        textureStore(output, id.xy, vec4f(params.mortar_color, 1.0));
+        return;
+    }
+
+    let random = f32(hash(params.seed ^ hash(row * params.columns + column)) >> 8u) / 16777216.0;
+    let brightness = 1.0 + params.color_variation * (random * 2.0 - 1.0);
+    textureStore(output, id.xy, vec4f(saturate(params.brick_color * brightness), 1.0));
+}
@@ -0,0 +1,93 @@
+use cgmath::{ InnerSpace, Matrix4, Point3, Vector3 };
+
+// cgmath 的投影矩阵针对 OpenGL（z 范围 -1..1），wgpu 的 NDC z 范围是 0..1
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub aspect: f32,
+    // 垂直视场角，单位为度
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+impl Camera {
+    pub fn build_view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn build_projection_matrix(&self) -> Matrix4<f32> {
+        OPENGL_TO_WGPU_MATRIX * cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar)
+    }
+
+    pub fn build_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.build_projection_matrix() * self.build_view_matrix()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+    // 裁剪平面 (法线, 距离)，dot(p, n) + d < 0 的片元被丢弃；全零表示不裁剪
+    pub clip_plane: [f32; 4],
+}
+
+impl CameraUniform {
+    pub fn new() -> Self {
+        use cgmath::SquareMatrix;
+        Self {
+            view_proj: Matrix4::identity().into(),
+            clip_plane: [0.0; 4],
+        }
+    }
+
+    pub fn update_view_proj(&mut self, camera: &Camera) {
+        self.view_proj = camera.build_view_projection_matrix().into();
+    }
+
+    // 法线会被归一化，distance 按归一化后的法线解释
+    pub fn set_clip_plane(&mut self, normal: Vector3<f32>, distance: f32) {
+        let normal = normal.normalize();
+        self.clip_plane = [normal.x, normal.y, normal.z, distance];
+    }
+
+    pub fn clear_clip_plane(&mut self) {
+        self.clip_plane = [0.0; 4];
+    }
+
+    // 片元着色器要读取裁剪平面
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("camera_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+        })
+    }
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
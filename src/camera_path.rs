@@ -0,0 +1,68 @@
+use cgmath::Point3;
+
+use crate::camera::Camera;
+use crate::spline::{ CatmullRomSpline, Lerp };
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKeyframe {
+    pub position: Point3<f32>,
+    pub target: Point3<f32>,
+    pub fov: f32,
+}
+
+impl Lerp for CameraKeyframe {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            target: self.target.lerp(&other.target, t),
+            fov: self.fov.lerp(&other.fov, t),
+        }
+    }
+}
+
+// 沿样条播放的相机动画
+pub struct CameraPath {
+    pub spline: CatmullRomSpline<CameraKeyframe>,
+    // 每秒经过的关键帧数
+    pub playback_speed: f32,
+    pub looping: bool,
+    progress: f32,
+}
+
+impl CameraPath {
+    pub fn new(keyframes: Vec<CameraKeyframe>, playback_speed: f32) -> Self {
+        Self {
+            spline: CatmullRomSpline::new(keyframes),
+            playback_speed,
+            looping: true,
+            progress: 0.0,
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.progress >= 1.0
+    }
+
+    pub fn update(&mut self, dt: f32, camera: &mut Camera) {
+        let segments = self.spline.segment_count();
+        if segments == 0 {
+            return;
+        }
+
+        self.progress += dt * self.playback_speed / segments as f32;
+        self.progress = if self.looping {
+            self.progress.rem_euclid(1.0)
+        } else {
+            self.progress.min(1.0)
+        };
+
+        let keyframe = self.spline.sample(self.progress);
+        camera.eye = keyframe.position;
+        camera.target = keyframe.target;
+        camera.fovy = keyframe.fov;
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::{ Duration, Instant };
+
+// 帧时钟：默认跟随墙上时间；启用固定步长后每次 tick 恰好前进 dt，用于回放与自动化测试
+pub struct DeterministicClock {
+    start: Instant,
+    elapsed: Duration,
+    last_tick: Duration,
+    fixed_dt: Option<Duration>,
+}
+
+impl DeterministicClock {
+    pub fn real_time() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Duration::ZERO,
+            last_tick: Duration::ZERO,
+            fixed_dt: None,
+        }
+    }
+
+    pub fn fixed(dt: Duration) -> Self {
+        Self { fixed_dt: Some(dt), ..Self::real_time() }
+    }
+
+    // 设置 FIXED_FPS 环境变量时按该帧率固定步进
+    pub fn from_env() -> Self {
+        match std::env::var("FIXED_FPS").ok().and_then(|fps| fps.parse::<f64>().ok()) {
+            Some(fps) if fps > 0.0 => Self::fixed(Duration::from_secs_f64(1.0 / fps)),
+            _ => Self::real_time(),
+        }
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.fixed_dt.is_some()
+    }
+
+    // 固定步长模式下与墙上时间无关
+    pub fn now(&self) -> Instant {
+        match self.fixed_dt {
+            Some(_) => self.start + self.elapsed,
+            None => Instant::now(),
+        }
+    }
+
+    // 返回距上次 tick 经过的时间
+    pub fn tick(&mut self) -> Duration {
+        self.elapsed = match self.fixed_dt {
+            Some(dt) => self.elapsed + dt,
+            None => self.start.elapsed(),
+        };
+        let delta = self.elapsed - self.last_tick;
+        self.last_tick = self.elapsed;
+        delta
+    }
+
+    // 时钟创建以来累计的时间
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
@@ -0,0 +1,251 @@
+use cgmath::SquareMatrix;
+
+use crate::camera::Camera;
+use crate::shader_preprocessor::ShaderPreprocessor;
+
+pub use crate::lighting::PointLight;
+
+// 与 clustered_lighting_common.wgsl 中的常量一致
+pub const CLUSTER_X: u32 = 16;
+pub const CLUSTER_Y: u32 = 9;
+pub const CLUSTER_Z: u32 = 24;
+pub const CLUSTER_COUNT: u32 = CLUSTER_X * CLUSTER_Y * CLUSTER_Z;
+pub const MAX_LIGHTS_PER_CLUSTER: u32 = 64;
+pub const MAX_LIGHTS: usize = 1024;
+
+// 视空间包围盒，两个 vec4f
+const CLUSTER_BOUNDS_SIZE: wgpu::BufferAddress = 32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClusterParams {
+    view: [[f32; 4]; 4],
+    inverse_projection: [[f32; 4]; 4],
+    screen_size: [f32; 2],
+    z_near: f32,
+    z_far: f32,
+    light_count: u32,
+    _padding: [u32; 3],
+}
+
+fn buffer_entry(binding: u32, buffer: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry {
+        binding,
+        resource: buffer.as_entire_binding(),
+    }
+}
+
+// 簇光照：把视锥划分为 16×9×24 个簇，计算着色器为每个簇收集与之相交的点光源，
+// 片元着色器只遍历所在簇的光源列表
+pub struct ClusteredLighting {
+    lights: Vec<PointLight>,
+    build_pipeline: wgpu::ComputePipeline,
+    assign_pipeline: wgpu::ComputePipeline,
+    compute_bind_group: wgpu::BindGroup,
+    shading_bind_group_layout: wgpu::BindGroupLayout,
+    shading_bind_group: wgpu::BindGroup,
+    params_buffer: wgpu::Buffer,
+    light_buffer: wgpu::Buffer,
+    // 簇包围盒只取决于投影与分辨率，变化时才重新计算
+    params: Option<ClusterParams>,
+    bounds_dirty: bool,
+}
+
+impl ClusteredLighting {
+    // 片元着色器需要 `#include "clustered_lighting_common"` 与 `#include "clustered_lighting_shading"`
+    pub fn register_includes(preprocessor: &mut ShaderPreprocessor) {
+        preprocessor
+            .add_include("clustered_lighting_common", include_str!("clustered_lighting_common.wgsl"))
+            .add_include("clustered_lighting_shading", include_str!("clustered_lighting_shading.wgsl"));
+    }
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let mut preprocessor = ShaderPreprocessor::new();
+        Self::register_includes(&mut preprocessor);
+        let shader = preprocessor.compile(device, "Clustered Lighting Shader", include_str!("clustered_lighting.wgsl"));
+
+        let create_buffer = |label, size, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let params_buffer = create_buffer(
+            "Cluster Params Buffer",
+            std::mem::size_of::<ClusterParams>() as wgpu::BufferAddress,
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let light_buffer = create_buffer(
+            "Cluster Light Buffer",
+            (MAX_LIGHTS * std::mem::size_of::<PointLight>()) as wgpu::BufferAddress,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let bounds_buffer = create_buffer(
+            "Cluster Bounds Buffer",
+            CLUSTER_COUNT as wgpu::BufferAddress * CLUSTER_BOUNDS_SIZE,
+            wgpu::BufferUsages::STORAGE,
+        );
+        let count_buffer = create_buffer(
+            "Cluster Light Count Buffer",
+            CLUSTER_COUNT as wgpu::BufferAddress * 4,
+            wgpu::BufferUsages::STORAGE,
+        );
+        let index_buffer = create_buffer(
+            "Cluster Light Index Buffer",
+            (CLUSTER_COUNT * MAX_LIGHTS_PER_CLUSTER) as wgpu::BufferAddress * 4,
+            wgpu::BufferUsages::STORAGE,
+        );
+
+        let entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let uniform = wgpu::BufferBindingType::Uniform;
+        let read_only = wgpu::BufferBindingType::Storage { read_only: true };
+        let read_write = wgpu::BufferBindingType::Storage { read_only: false };
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cluster_compute_bind_group_layout"),
+            entries: &[
+                entry(0, compute, uniform),
+                entry(1, compute, read_only),
+                entry(2, compute, read_write),
+                entry(3, compute, read_write),
+                entry(4, compute, read_write),
+            ],
+        });
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cluster_compute_bind_group"),
+            layout: &compute_bind_group_layout,
+            entries: &[
+                buffer_entry(0, &params_buffer),
+                buffer_entry(1, &light_buffer),
+                buffer_entry(2, &bounds_buffer),
+                buffer_entry(3, &count_buffer),
+                buffer_entry(4, &index_buffer),
+            ],
+        });
+
+        let fragment = wgpu::ShaderStages::FRAGMENT;
+        let shading_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cluster_shading_bind_group_layout"),
+            entries: &[
+                entry(0, fragment, uniform),
+                entry(1, fragment, read_only),
+                entry(2, fragment, read_only),
+                entry(3, fragment, read_only),
+            ],
+        });
+        let shading_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cluster_shading_bind_group"),
+            layout: &shading_bind_group_layout,
+            entries: &[
+                buffer_entry(0, &params_buffer),
+                buffer_entry(1, &light_buffer),
+                buffer_entry(2, &count_buffer),
+                buffer_entry(3, &index_buffer),
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Clustered Lighting Pipeline Layout"),
+            bind_group_layouts: &[&compute_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let create_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+
+        Self {
+            lights: Vec::new(),
+            build_pipeline: create_pipeline("Cluster Build Pipeline", "cs_build_clusters"),
+            assign_pipeline: create_pipeline("Cluster Light Assignment Pipeline", "cs_assign_lights"),
+            compute_bind_group,
+            shading_bind_group_layout,
+            shading_bind_group,
+            params_buffer,
+            light_buffer,
+            params: None,
+            bounds_dirty: true,
+        }
+    }
+
+    pub fn lights(&self) -> &[PointLight] {
+        &self.lights
+    }
+
+    pub fn set_lights(&mut self, lights: &[PointLight]) {
+        if lights.len() > MAX_LIGHTS {
+            tracing::warn!("clustered lighting: {} point lights exceed the limit of {MAX_LIGHTS}, the rest are ignored", lights.len());
+        }
+        self.lights = lights[..lights.len().min(MAX_LIGHTS)].to_vec();
+    }
+
+    // 片元着色器第 3 组的布局，见 clustered_lighting_shading.wgsl
+    pub fn shading_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.shading_bind_group_layout
+    }
+
+    pub fn shading_bind_group(&self) -> &wgpu::BindGroup {
+        &self.shading_bind_group
+    }
+
+    // 每帧在 assign 之前调用，上传相机参数与光源
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &Camera, size: (u32, u32)) {
+        let inverse_projection = camera
+            .build_projection_matrix()
+            .invert()
+            .expect("camera projection matrix is invertible");
+        let params = ClusterParams {
+            view: camera.build_view_matrix().into(),
+            inverse_projection: inverse_projection.into(),
+            screen_size: [size.0.max(1) as f32, size.1.max(1) as f32],
+            z_near: camera.znear,
+            z_far: camera.zfar,
+            light_count: self.lights.len() as u32,
+            _padding: [0; 3],
+        };
+
+        let projection_changed = self.params.is_none_or(|old| {
+            (old.inverse_projection, old.screen_size, old.z_near, old.z_far)
+                != (params.inverse_projection, params.screen_size, params.z_near, params.z_far)
+        });
+        self.bounds_dirty |= projection_changed;
+        self.params = Some(params);
+
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        if !self.lights.is_empty() {
+            queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&self.lights));
+        }
+    }
+
+    // 录制光源分配；投影变化后先重建簇包围盒
+    pub fn assign(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Cluster Light Assignment Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        // 工作组大小为 16×9×1，每个工作组处理一层簇
+        if self.bounds_dirty {
+            compute_pass.set_pipeline(&self.build_pipeline);
+            compute_pass.dispatch_workgroups(1, 1, CLUSTER_Z);
+            self.bounds_dirty = false;
+        }
+        compute_pass.set_pipeline(&self.assign_pipeline);
+        compute_pass.dispatch_workgroups(1, 1, CLUSTER_Z);
+    }
+}
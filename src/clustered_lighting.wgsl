@@ -0,0 +1,67 @@
+#include "clustered_lighting_common"
+
+// 视空间中的包围盒，w 分量不使用
+struct ClusterBounds {
+    min: vec4f,
+    max: vec4f,
+};
+
+@group(0) @binding(0)
+var<uniform> params: ClusterParams;
+@group(0) @binding(1)
+var<storage, read> lights: array<PointLight>;
+@group(0) @binding(2)
+var<storage, read_write> bounds: array<ClusterBounds>;
+@group(0) @binding(3)
+var<storage, read_write> light_counts: array<u32>;
+@group(0) @binding(4)
+var<storage, read_write> light_indices: array<u32>;
+
+// 从相机出发穿过 NDC 上某点的视线方向
+fn view_ray(ndc: vec2f) -> vec3f {
+    let point = params.inverse_projection * vec4f(ndc, 1.0, 1.0);
+    return point.xyz / point.w;
+}
+
+// 视线上视空间深度为 depth 的点，相机看向 -z
+fn point_at_depth(ray: vec3f, depth: f32) -> vec3f {
+    return ray * (depth / -ray.z);
+}
+
+// 只在投影或分辨率变化时运行
+@compute @workgroup_size(16, 9, 1)
+fn cs_build_clusters(@builtin(global_invocation_id) id: vec3u) {
+    let tile_min = vec2f(id.xy) / vec2f(f32(CLUSTER_X), f32(CLUSTER_Y));
+    let tile_max = vec2f(id.xy + 1u) / vec2f(f32(CLUSTER_X), f32(CLUSTER_Y));
+    // 屏幕 uv 的 y 轴向下，NDC 的 y 轴向上
+    let ray_min = view_ray(vec2f(tile_min.x * 2.0 - 1.0, 1.0 - tile_max.y * 2.0));
+    let ray_max = view_ray(vec2f(tile_max.x * 2.0 - 1.0, 1.0 - tile_min.y * 2.0));
+    let near = cluster_slice_depth(id.z, params.z_near, params.z_far);
+    let far = cluster_slice_depth(id.z + 1u, params.z_near, params.z_far);
+
+    // 簇是一个截头锥体，取两条角线在前后两个深度平面上的 4 个交点的包围盒
+    let a = point_at_depth(ray_min, near);
+    let b = point_at_depth(ray_min, far);
+    let c = point_at_depth(ray_max, near);
+    let d = point_at_depth(ray_max, far);
+    bounds[cluster_index(id)] = ClusterBounds(vec4f(min(min(a, b), min(c, d)), 0.0), vec4f(max(max(a, b), max(c, d)), 0.0));
+}
+
+// 每个线程负责一个簇，遍历所有光源做球与包围盒的相交测试；
+// 各簇只写自己的列表，不需要原子操作，同一光源也不会在一个簇中重复出现
+@compute @workgroup_size(16, 9, 1)
+fn cs_assign_lights(@builtin(global_invocation_id) id: vec3u) {
+    let cluster = cluster_index(id);
+    let cluster_bounds = bounds[cluster];
+    var count = 0u;
+    for (var i = 0u; i < params.light_count && count < MAX_LIGHTS_PER_CLUSTER; i++) {
+        let light = lights[i];
+        let center = (params.view * vec4f(light.position, 1.0)).xyz;
+        let offset = center - clamp(center, cluster_bounds.min.xyz, cluster_bounds.max.xyz);
+        if dot(offset, offset) <= light.radius * light.radius {
+            light_indices[cluster * MAX_LIGHTS_PER_CLUSTER + count] = i;
+            count++;
+        }
+    }
+    light_counts[cluster] = count;
+}
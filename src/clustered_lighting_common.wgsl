@@ -0,0 +1,36 @@
+// 簇网格尺寸，与 clustered_lighting.rs 中的常量一致
+const CLUSTER_X: u32 = 16u;
+const CLUSTER_Y: u32 = 9u;
+const CLUSTER_Z: u32 = 24u;
+const MAX_LIGHTS_PER_CLUSTER: u32 = 64u;
+
+struct ClusterParams {
+    view: mat4x4f,
+    inverse_projection: mat4x4f,
+    screen_size: vec2f,
+    z_near: f32,
+    z_far: f32,
+    light_count: u32,
+};
+
+struct PointLight {
+    position: vec3f,
+    radius: f32,
+    color: vec3f,
+    intensity: f32,
+};
+
+// 深度方向按指数切分：远处的簇更厚，每个簇在屏幕上的形状更接近立方体
+fn cluster_slice(view_depth: f32, z_near: f32, z_far: f32) -> u32 {
+    let slice = log(max(view_depth, z_near) / z_near) / log(z_far / z_near) * f32(CLUSTER_Z);
+    return min(u32(slice), CLUSTER_Z - 1u);
+}
+
+// 第 slice 层簇靠近相机一侧的视空间深度
+fn cluster_slice_depth(slice: u32, z_near: f32, z_far: f32) -> f32 {
+    return z_near * pow(z_far / z_near, f32(slice) / f32(CLUSTER_Z));
+}
+
+fn cluster_index(xyz: vec3u) -> u32 {
+    return xyz.x + xyz.y * CLUSTER_X + xyz.z * CLUSTER_X * CLUSTER_Y;
+}
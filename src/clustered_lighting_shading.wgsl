@@ -0,0 +1,38 @@
+// 片元着色器使用的簇光照查找，需要同时包含 clustered_lighting_common
+// 绑定组布局由 ClusteredLighting::shading_bind_group_layout 创建，固定放在第 3 组
+
+@group(3) @binding(0)
+var<uniform> cluster_params: ClusterParams;
+@group(3) @binding(1)
+var<storage, read> cluster_lights: array<PointLight>;
+@group(3) @binding(2)
+var<storage, read> cluster_light_counts: array<u32>;
+@group(3) @binding(3)
+var<storage, read> cluster_light_indices: array<u32>;
+
+// uv 为屏幕坐标（左上角为原点，范围 0..1），depth 为视空间中到相机的正深度
+fn cluster_xyz_to_index(uv: vec2f, depth: f32) -> u32 {
+    let x = min(u32(saturate(uv.x) * f32(CLUSTER_X)), CLUSTER_X - 1u);
+    let y = min(u32(saturate(uv.y) * f32(CLUSTER_Y)), CLUSTER_Y - 1u);
+    let z = cluster_slice(depth, cluster_params.z_near, cluster_params.z_far);
+    return cluster_index(vec3u(x, y, z));
+}
+
+// 只遍历片元所在簇的光源；frag_coord 为 @builtin(position) 的 xy
+fn clustered_point_lighting(world_position: vec3f, normal: vec3f, frag_coord: vec2f) -> vec3f {
+    let depth = -(cluster_params.view * vec4f(world_position, 1.0)).z;
+    let cluster = cluster_xyz_to_index(frag_coord / cluster_params.screen_size, depth);
+    let count = cluster_light_counts[cluster];
+
+    var total = vec3f(0.0);
+    for (var i = 0u; i < count; i++) {
+        let light = cluster_lights[cluster_light_indices[cluster * MAX_LIGHTS_PER_CLUSTER + i]];
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        // 距离平方衰减，并在光源半径处平滑降到 0，与簇分配使用的半径一致
+        let window = pow(saturate(1.0 - pow(distance / light.radius, 4.0)), 2.0);
+        let attenuation = window / (distance * distance + 1.0);
+        total += light.color * light.intensity * attenuation * max(dot(normal, to_light / max(distance, 1e-4)), 0.0);
+    }
+    return total;
+}
@@ -0,0 +1,90 @@
+use std::ops::{ Deref, DerefMut };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::{ Arc, Mutex };
+
+// 每帧预先创建一批编码器，可在多个线程上并行录制，最后按领取顺序一次提交。
+// 池本身跨帧保留，finish 之后用 reset 为下一帧补齐编码器
+pub struct CommandPool {
+    device: Arc<wgpu::Device>,
+    count: usize,
+    idle: Mutex<Vec<wgpu::CommandEncoder>>,
+    finished: Mutex<Vec<(usize, wgpu::CommandBuffer)>>,
+    next_index: AtomicUsize,
+}
+
+impl CommandPool {
+    pub fn new(device: Arc<wgpu::Device>, count: usize) -> Self {
+        let idle = (0..count).map(|_| Self::create_encoder(&device)).collect();
+        Self {
+            device,
+            count,
+            idle: Mutex::new(idle),
+            finished: Mutex::new(Vec::with_capacity(count)),
+            next_index: AtomicUsize::new(0),
+        }
+    }
+
+    // 编码器 finish 后不能再用，只补齐到 count 个新的；上一帧未领取的保留
+    pub fn reset(&mut self) {
+        let idle = self.idle.get_mut().unwrap();
+        while idle.len() < self.count {
+            idle.push(Self::create_encoder(&self.device));
+        }
+        *self.next_index.get_mut() = 0;
+    }
+
+    fn create_encoder(device: &wgpu::Device) -> wgpu::CommandEncoder {
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Pooled Encoder"),
+        })
+    }
+
+    // 预分配的编码器用完后再临时创建
+    pub fn acquire(&self) -> RecordingEncoder<'_> {
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed);
+        let encoder = self
+            .idle
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| Self::create_encoder(&self.device));
+
+        RecordingEncoder { encoder: Some(encoder), index, pool: self }
+    }
+
+    // 命令缓冲区按 acquire 的先后排序，与单线程录制时的提交顺序一致
+    pub fn finish(&mut self) -> Vec<wgpu::CommandBuffer> {
+        let mut finished = std::mem::take(self.finished.get_mut().unwrap());
+        finished.sort_by_key(|(index, _)| *index);
+        finished.into_iter().map(|(_, buffer)| buffer).collect()
+    }
+}
+
+// 离开作用域时自动结束录制并交还给池
+pub struct RecordingEncoder<'p> {
+    encoder: Option<wgpu::CommandEncoder>,
+    index: usize,
+    pool: &'p CommandPool,
+}
+
+impl Deref for RecordingEncoder<'_> {
+    type Target = wgpu::CommandEncoder;
+
+    fn deref(&self) -> &Self::Target {
+        self.encoder.as_ref().expect("encoder is recording")
+    }
+}
+
+impl DerefMut for RecordingEncoder<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.encoder.as_mut().expect("encoder is recording")
+    }
+}
+
+impl Drop for RecordingEncoder<'_> {
+    fn drop(&mut self) {
+        if let Some(encoder) = self.encoder.take() {
+            self.pool.finished.lock().unwrap().push((self.index, encoder.finish()));
+        }
+    }
+}
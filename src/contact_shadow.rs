@@ -0,0 +1,220 @@
+use cgmath::{ InnerSpace, SquareMatrix, Vector3 };
+
+use crate::camera::Camera;
+use crate::texture::Texture;
+
+pub const CONTACT_SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+// 与 contact_shadow.wgsl 中的 STEP_COUNT 一致
+pub const CONTACT_SHADOW_STEPS: u32 = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ContactShadowParams {
+    proj: [[f32; 4]; 4],
+    inv_proj: [[f32; 4]; 4],
+    // 观察空间中从表面指向光源的方向
+    light_direction: [f32; 3],
+    max_distance: f32,
+    threshold: f32,
+    thickness: f32,
+    contact_hardness: f32,
+    _padding: f32,
+}
+
+// 屏幕空间接触阴影：从每个像素的观察空间位置沿光源方向步进 CONTACT_SHADOW_STEPS 次，
+// 投影回屏幕读取深度，场景表面比光线点更靠近相机超过 threshold（且不超过 thickness）时视为被挡住；
+// 越早被挡住阴影越重，按 contact_hardness 缩放。结果为 1 受光、0 全遮挡的单通道纹理，
+// 由延迟光照通道在光照组的 4 号绑定读取，与阴影图集的结果取 min
+pub struct ContactShadowPass {
+    // 世界空间中从表面指向光源的方向
+    pub light_direction: Vector3<f32>,
+    // 步进的总长度，观察空间单位
+    pub max_distance: f32,
+    pub threshold: f32,
+    // 比光线点靠前超过 thickness 的表面视为在其前方而非挡住光线
+    pub thickness: f32,
+    pub contact_hardness: f32,
+    target: Texture,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+}
+
+impl ContactShadowPass {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Contact Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("contact_shadow.wgsl").into()),
+        });
+
+        // 深度以不可过滤的浮点纹理绑定：GL 后端不支持对 texture_depth_2d 使用 textureLoad
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("contact_shadow_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Contact Shadow Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Contact Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: CONTACT_SHADOW_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Contact Shadow Params Buffer"),
+            size: std::mem::size_of::<ContactShadowParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            light_direction: Vector3::unit_y(),
+            max_distance: 0.2,
+            threshold: 0.005,
+            thickness: 0.1,
+            contact_hardness: 1.0,
+            target: Self::create_target(device, width, height),
+            pipeline,
+            bind_group_layout,
+            params_buffer,
+        }
+    }
+
+    fn create_target(device: &wgpu::Device, width: u32, height: u32) -> Texture {
+        Texture::create_render_target(device, width, height, CONTACT_SHADOW_FORMAT, wgpu::TextureUsages::empty(), "Contact Shadow Target")
+    }
+
+    // 目标纹理重建后，引用它的光照绑定组也需要重建
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.target = Self::create_target(device, width, height);
+    }
+
+    pub fn target(&self) -> &Texture {
+        &self.target
+    }
+
+    // depth 为同尺寸的场景深度缓冲，需在不透明几何写完深度之后调用
+    pub fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera: &Camera,
+        depth: &wgpu::Texture,
+    ) {
+        let proj = camera.build_projection_matrix();
+        let inv_proj = proj.invert().unwrap_or_else(cgmath::Matrix4::identity);
+        let light_direction = (camera.build_view_matrix() * self.light_direction.extend(0.0)).truncate().normalize();
+        let params = ContactShadowParams {
+            proj: proj.into(),
+            inv_proj: inv_proj.into(),
+            light_direction: light_direction.into(),
+            max_distance: self.max_distance,
+            threshold: self.threshold,
+            thickness: self.thickness,
+            contact_hardness: self.contact_hardness,
+            _padding: 0.0,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        // 场景深度可能带模板，采样时只取深度部分
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Contact Shadow Scene Depth"),
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("contact_shadow_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&depth_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Contact Shadow Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // 光照组中接触阴影使用的 4 号绑定，与 bind_group_entry 对应
+    pub fn bind_group_layout_entry(visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 4,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }
+    }
+
+    pub fn bind_group_entry(&self) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: 4,
+            resource: wgpu::BindingResource::TextureView(&self.target.view),
+        }
+    }
+}
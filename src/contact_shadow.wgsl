@@ -0,0 +1,64 @@
+const STEP_COUNT: u32 = 16u;
+
+struct ContactShadowParams {
+    proj: mat4x4f,
+    inv_proj: mat4x4f,
+    light_direction: vec3f,
+    max_distance: f32,
+    threshold: f32,
+    thickness: f32,
+    contact_hardness: f32,
+};
+
+@group(0) @binding(0)
+var t_depth: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: ContactShadowParams;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+fn view_position(uv: vec2f, depth: f32) -> vec3f {
+    let ndc = vec4f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let position = params.inv_proj * ndc;
+    return position.xyz / position.w;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) f32 {
+    let size = vec2f(textureDimensions(t_depth));
+    let texel = vec2<i32>(position.xy);
+    let depth = textureLoad(t_depth, texel, 0).r;
+    // 天空没有表面
+    if depth >= 1.0 {
+        return 1.0;
+    }
+    let origin = view_position(position.xy / size, depth);
+    let step = params.light_direction * (params.max_distance / f32(STEP_COUNT));
+
+    var occlusion = 0.0;
+    for (var i = 1u; i <= STEP_COUNT; i++) {
+        let ray = origin + step * f32(i);
+        let clip = params.proj * vec4f(ray, 1.0);
+        if clip.w <= 0.0 {
+            break;
+        }
+        let ndc = clip.xy / clip.w;
+        let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        if any(uv < vec2f(0.0)) || any(uv >= vec2f(1.0)) {
+            break;
+        }
+        let scene_uv = (floor(uv * size) + 0.5) / size;
+        let scene = view_position(scene_uv, textureLoad(t_depth, vec2<i32>(uv * size), 0).r);
+        // 右手观察空间看向 -z，z 越大越靠近相机
+        let difference = scene.z - ray.z;
+        if difference > params.threshold && difference < params.thickness {
+            occlusion = 1.0 - f32(i - 1u) / f32(STEP_COUNT);
+            break;
+        }
+    }
+    return 1.0 - saturate(occlusion * params.contact_hardness);
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use cgmath::{ InnerSpace, Vector3 };
+
+use crate::aabb::Aabb;
+use crate::frustum::Frustum;
+
+// 平面距离的容差相对于点集包围盒对角线的比例
+const RELATIVE_EPSILON: f32 = 1e-5;
+
+// dot(normal, p) <= offset 表示在内侧
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HalfSpace {
+    normal: Vector3<f32>,
+    offset: f32,
+}
+
+impl HalfSpace {
+    fn new(normal: Vector3<f32>, point: Vector3<f32>) -> Self {
+        Self { normal, offset: normal.dot(point) }
+    }
+
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        self.normal.dot(point) - self.offset
+    }
+}
+
+// 凸包，比轴对齐包围盒更贴合非盒状物体，用于物理与剔除。
+// 点集退化时（单点、共线、共面）得到没有体积的凸包：
+// faces 分别为空、空、正反两面的多边形，contains_point 按容差判断
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConvexHull {
+    pub vertices: Vec<[f32; 3]>,
+    // 三角形面，从外侧看逆时针
+    pub faces: Vec<[u32; 3]>,
+    half_spaces: Vec<HalfSpace>,
+    epsilon: f32,
+}
+
+struct Face {
+    indices: [usize; 3],
+    plane: HalfSpace,
+    // 在该面外侧、尚未处理的点
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Face {
+    fn new(points: &[Vector3<f32>], indices: [usize; 3]) -> Self {
+        let [a, b, c] = indices.map(|index| points[index]);
+        let normal = (b - a).cross(c - a);
+        let normal = if normal.magnitude2() > 0.0 { normal.normalize() } else { normal };
+        Self { indices, plane: HalfSpace::new(normal, a), outside: Vec::new(), alive: true }
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.indices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+impl ConvexHull {
+    // Quickhull（Barber et al. 1996）；忽略非有限的坐标
+    pub fn from_vertices(vertices: &[[f32; 3]]) -> Self {
+        let points: Vec<Vector3<f32>> = vertices
+            .iter()
+            .filter(|point| point.iter().all(|value| value.is_finite()))
+            .map(|&point| point.into())
+            .collect();
+        if points.is_empty() {
+            return Self::default();
+        }
+        let aabb = points.iter().fold(Aabb::EMPTY, |aabb, &point| aabb.including(point.into()));
+        let epsilon = (Vector3::from(aabb.size()).magnitude() * RELATIVE_EPSILON).max(f32::EPSILON);
+
+        // 初始单纯形：各轴极值点中相距最远的两个，再取离直线、平面最远的点
+        let extremes: Vec<usize> = (0..3)
+            .flat_map(|axis| {
+                let by_axis = |a: &&Vector3<f32>, b: &&Vector3<f32>| a[axis].total_cmp(&b[axis]);
+                let min = points.iter().enumerate().min_by(|a, b| by_axis(&a.1, &b.1)).map(|(index, _)| index);
+                let max = points.iter().enumerate().max_by(|a, b| by_axis(&a.1, &b.1)).map(|(index, _)| index);
+                [min, max]
+            })
+            .flatten()
+            .collect();
+        let (i0, i1) = extremes
+            .iter()
+            .flat_map(|&a| extremes.iter().map(move |&b| (a, b)))
+            .max_by(|&(a, b), &(c, d)| (points[a] - points[b]).magnitude2().total_cmp(&(points[c] - points[d]).magnitude2()))
+            .expect("points is not empty");
+        if (points[i1] - points[i0]).magnitude() <= epsilon {
+            return Self::point(points[i0], epsilon);
+        }
+
+        let direction = (points[i1] - points[i0]).normalize();
+        let line_distance = |index: usize| (points[index] - points[i0]).cross(direction).magnitude();
+        let i2 = farthest(&points, line_distance);
+        if line_distance(i2) <= epsilon {
+            return Self::segment(&points, points[i0], direction, epsilon);
+        }
+
+        let normal = (points[i1] - points[i0]).cross(points[i2] - points[i0]).normalize();
+        let plane_distance = |index: usize| normal.dot(points[index] - points[i0]).abs();
+        let i3 = farthest(&points, plane_distance);
+        if plane_distance(i3) <= epsilon {
+            return Self::polygon(&points, points[i0], direction, normal, epsilon);
+        }
+
+        Self::quickhull(&points, [i0, i1, i2, i3], epsilon)
+    }
+
+    fn quickhull(points: &[Vector3<f32>], simplex: [usize; 4], epsilon: f32) -> Self {
+        // 四面体的面朝向远离重心的一侧
+        let centroid = simplex.iter().map(|&index| points[index]).sum::<Vector3<f32>>() / 4.0;
+        let [a, b, c, d] = simplex;
+        let mut faces: Vec<Face> = [[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+            .into_iter()
+            .map(|[a, b, c]| {
+                let face = Face::new(points, [a, b, c]);
+                if face.plane.distance(centroid) > 0.0 { Face::new(points, [a, c, b]) } else { face }
+            })
+            .collect();
+        for index in (0..points.len()).filter(|index| !simplex.contains(index)) {
+            if let Some(face) = faces.iter_mut().find(|face| face.plane.distance(points[index]) > epsilon) {
+                face.outside.push(index);
+            }
+        }
+
+        while let Some(current) = faces.iter().position(|face| face.alive && !face.outside.is_empty()) {
+            let plane = faces[current].plane;
+            let eye = *faces[current]
+                .outside
+                .iter()
+                .max_by(|&&a, &&b| plane.distance(points[a]).total_cmp(&plane.distance(points[b])))
+                .expect("outside is not empty");
+
+            // 从 eye 看得到的面被删除，地平线是可见面上反向边不属于可见面的那些边。
+            // 可见性不加容差，否则与 eye 近似共面的面会和新面之间留下凹陷
+            let visible: Vec<usize> = (0..faces.len())
+                .filter(|&index| faces[index].alive && faces[index].plane.distance(points[eye]) > 0.0)
+                .collect();
+            let visible_edges: Vec<(usize, usize)> = visible.iter().flat_map(|&index| faces[index].edges()).collect();
+            let edge_set: HashSet<(usize, usize)> = visible_edges.iter().copied().collect();
+            let horizon: Vec<(usize, usize)> = visible_edges.into_iter().filter(|&(a, b)| !edge_set.contains(&(b, a))).collect();
+
+            let mut orphans = Vec::new();
+            for &index in &visible {
+                faces[index].alive = false;
+                orphans.append(&mut faces[index].outside);
+            }
+            let first_new = faces.len();
+            faces.extend(horizon.into_iter().map(|(a, b)| Face::new(points, [a, b, eye])));
+            for index in orphans.into_iter().filter(|&index| index != eye) {
+                if let Some(face) = faces[first_new..].iter_mut().find(|face| face.plane.distance(points[index]) > epsilon) {
+                    face.outside.push(index);
+                }
+            }
+        }
+
+        // 只保留被面引用的顶点
+        let mut remap = vec![u32::MAX; points.len()];
+        let mut hull = Self { epsilon, ..Default::default() };
+        for face in faces.iter().filter(|face| face.alive) {
+            let indices = face.indices.map(|index| {
+                if remap[index] == u32::MAX {
+                    remap[index] = hull.vertices.len() as u32;
+                    hull.vertices.push(points[index].into());
+                }
+                remap[index]
+            });
+            hull.faces.push(indices);
+            hull.half_spaces.push(face.plane);
+        }
+        hull
+    }
+
+    fn point(point: Vector3<f32>, epsilon: f32) -> Self {
+        let half_spaces = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()]
+            .into_iter()
+            .flat_map(|axis| [HalfSpace::new(axis, point), HalfSpace::new(-axis, point)])
+            .collect();
+        Self { vertices: vec![point.into()], faces: Vec::new(), half_spaces, epsilon }
+    }
+
+    fn segment(points: &[Vector3<f32>], origin: Vector3<f32>, direction: Vector3<f32>, epsilon: f32) -> Self {
+        let along = |point: &Vector3<f32>| direction.dot(point - origin);
+        let start = *points.iter().min_by(|a, b| along(a).total_cmp(&along(b))).expect("points is not empty");
+        let end = *points.iter().max_by(|a, b| along(a).total_cmp(&along(b))).expect("points is not empty");
+        let side = perpendicular(direction);
+        let up = direction.cross(side);
+        let half_spaces = vec![
+            HalfSpace::new(direction, end),
+            HalfSpace::new(-direction, start),
+            HalfSpace::new(side, start),
+            HalfSpace::new(-side, start),
+            HalfSpace::new(up, start),
+            HalfSpace::new(-up, start),
+        ];
+        Self { vertices: vec![start.into(), end.into()], faces: Vec::new(), half_spaces, epsilon }
+    }
+
+    // 共面的点：在平面内求二维凸包（Andrew 单调链），绕 normal 逆时针
+    fn polygon(points: &[Vector3<f32>], origin: Vector3<f32>, u: Vector3<f32>, normal: Vector3<f32>, epsilon: f32) -> Self {
+        let v = normal.cross(u);
+        let mut sorted: Vec<(f32, f32, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| (u.dot(point - origin), v.dot(point - origin), index))
+            .collect();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        // 二维叉积的单位是长度的平方，容差再乘以对角线长度
+        let tolerance = epsilon * epsilon / RELATIVE_EPSILON;
+        let cross = |o: &(f32, f32, usize), a: &(f32, f32, usize), b: &(f32, f32, usize)| {
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+        let mut chain: Vec<(f32, f32, usize)> = Vec::new();
+        for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+            let start = chain.len();
+            for point in pass {
+                while chain.len() >= start + 2 && cross(&chain[chain.len() - 2], &chain[chain.len() - 1], &point) <= tolerance {
+                    chain.pop();
+                }
+                chain.push(point);
+            }
+            // 每条链的终点是下一条链的起点
+            chain.pop();
+        }
+
+        let vertices: Vec<Vector3<f32>> = chain.iter().map(|&(_, _, index)| points[index]).collect();
+        let count = vertices.len() as u32;
+        let faces = (1..count.saturating_sub(1))
+            .flat_map(|i| [[0, i, i + 1], [0, i + 1, i]])
+            .collect();
+        let mut half_spaces = vec![HalfSpace::new(normal, origin), HalfSpace::new(-normal, origin)];
+        for (i, &start) in vertices.iter().enumerate() {
+            let end = vertices[(i + 1) % vertices.len()];
+            half_spaces.push(HalfSpace::new((end - start).cross(normal).normalize(), start));
+        }
+        Self {
+            vertices: vertices.into_iter().map(Into::into).collect(),
+            faces,
+            half_spaces,
+            epsilon,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    // 在凸包内或表面上（容差内）
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        let point = Vector3::from(point);
+        !self.is_empty() && self.half_spaces.iter().all(|half_space| half_space.distance(point) <= self.epsilon)
+    }
+
+    // 保守测试：只有所有顶点都在某个平面外侧时才返回 false。
+    // 凸包与视锥体需在同一空间；模型空间的凸包可与 Frustum::from_matrix(view_proj * model) 比较
+    pub fn intersects_frustum(&self, frustum: &Frustum) -> bool {
+        !self.is_empty()
+            && frustum
+                .planes
+                .iter()
+                .all(|plane| self.vertices.iter().any(|&vertex| plane.signed_distance(vertex) >= 0.0))
+    }
+}
+
+fn farthest(points: &[Vector3<f32>], distance: impl Fn(usize) -> f32) -> usize {
+    (0..points.len()).max_by(|&a, &b| distance(a).total_cmp(&distance(b))).expect("points is not empty")
+}
+
+// 任意一个与 direction 垂直的单位向量
+fn perpendicular(direction: Vector3<f32>) -> Vector3<f32> {
+    let axis = if direction.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+    direction.cross(axis).normalize()
+}
@@ -0,0 +1,14 @@
+// 六个面依次为 +X -X +Y -Y +Z -Z
+// 纹素中心对应的方向，s 向右、t 向下，遵循 WebGPU 的立方体采样规则
+fn face_direction(face: u32, st: vec2f) -> vec3f {
+    let s = st.x;
+    let t = st.y;
+    switch face {
+        case 0u: { return vec3f(1.0, -t, -s); }
+        case 1u: { return vec3f(-1.0, -t, s); }
+        case 2u: { return vec3f(s, 1.0, t); }
+        case 3u: { return vec3f(s, -1.0, -t); }
+        case 4u: { return vec3f(s, -t, 1.0); }
+        default: { return vec3f(-s, -t, -1.0); }
+    }
+}
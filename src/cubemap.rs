@@ -0,0 +1,322 @@
+use cgmath::{ Matrix4, Point3, Vector3 };
+use wgpu::util::DeviceExt;
+
+use crate::camera::{ Camera, CameraUniform, OPENGL_TO_WGPU_MATRIX };
+use crate::shader_preprocessor::ShaderPreprocessor;
+use crate::texture::Texture;
+
+// 可过滤且能存下 HDR 亮度
+pub const CUBEMAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const WORKGROUP_SIZE: u32 = 8;
+
+// 立方体贴图六个面的朝向与上方向，顺序为 +X -X +Y -Y +Z -Z
+// 纹理坐标 t 向下增长，上方向按 WebGPU 的立方体采样规则取
+pub const CUBE_FACES: [(Vector3<f32>, Vector3<f32>); 6] = [
+    (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, -1.0)),
+    (Vector3::new(0.0, -1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+    (Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 1.0, 0.0)),
+];
+
+// 立方体贴图的面相对普通相机是镜像的，投影里翻转 x，捕获管线的正面需改为顺时针
+pub fn cube_face_view_projections(position: [f32; 3], znear: f32, zfar: f32) -> [Matrix4<f32>; 6] {
+    let eye = Point3::from(position);
+    let projection = OPENGL_TO_WGPU_MATRIX
+        * Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0)
+        * cgmath::perspective(cgmath::Deg(90.0), 1.0, znear, zfar);
+    CUBE_FACES.map(|(forward, up)| projection * Matrix4::look_to_rh(eye, forward, up))
+}
+
+// 六层的二维数组纹理，以 Cube 视图采样，可直接用于 LightProbeSystem 的绑定组布局
+pub struct Cubemap {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub face_size: u32,
+}
+
+impl Cubemap {
+    // `#include "cube_face"` 提供 face_direction(face, st)，把面内坐标转换为采样方向
+    pub fn register_includes(preprocessor: &mut ShaderPreprocessor) {
+        preprocessor.add_include("cube_face", include_str!("cube_face.wgsl"));
+    }
+
+    // 把 360° 经纬度全景图（HDR 或 PNG）转换成立方体贴图，每个面 face_size × face_size
+    pub fn from_equirectangular(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::DynamicImage,
+        face_size: u32,
+    ) -> Self {
+        let face_size = face_size.max(1);
+        let pixels = image.to_rgba32f();
+        let source = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Equirectangular Source"),
+                size: wgpu::Extent3d {
+                    width: pixels.width().max(1),
+                    height: pixels.height().max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba32Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            bytemuck::cast_slice(pixels.as_raw()),
+        );
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Cubemap"),
+            size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBEMAP_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+            view_formats: &[],
+        });
+
+        let mut preprocessor = ShaderPreprocessor::new();
+        Self::register_includes(&mut preprocessor);
+        let shader = preprocessor.compile(device, "Equirectangular To Cubemap Shader", include_str!("cubemap.wgsl"));
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cubemap_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: CUBEMAP_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cubemap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Cubemap Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let source_view = source.create_view(&wgpu::TextureViewDescriptor::default());
+        let faces_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cubemap Faces View"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cubemap_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&faces_view),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Cubemap Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Equirectangular To Cubemap Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            let workgroups = face_size.div_ceil(WORKGROUP_SIZE);
+            compute_pass.dispatch_workgroups(workgroups, workgroups, 6);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cubemap View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        Self { texture, view, face_size }
+    }
+}
+
+// 从任意位置把场景烘焙成立方体贴图，例如离线生成反射探针
+// 每个面的相机放在第 0 组（与场景管线的相机布局一致），绘制回调使用的管线需满足：
+// 颜色格式 CUBEMAP_FORMAT、深度格式 Texture::DEPTH_FORMAT、正面为顺时针（投影翻转了 x）
+pub struct CubemapCapture {
+    pub clear_color: wgpu::Color,
+    pub znear: f32,
+    pub zfar: f32,
+    // 六个面的相机依次存放，间隔满足 uniform 偏移对齐
+    camera_buffer: wgpu::Buffer,
+    camera_stride: wgpu::BufferAddress,
+    camera_bind_groups: [wgpu::BindGroup; 6],
+}
+
+impl CubemapCapture {
+    pub fn new(device: &wgpu::Device, camera_bind_group_layout: &wgpu::BindGroupLayout) -> Self {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let camera_size = std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress;
+        let camera_stride = wgpu::util::align_to(camera_size, alignment);
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cubemap Capture Camera Buffer"),
+            size: camera_stride * 6,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_groups = [0, 1, 2, 3, 4, 5].map(|face| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("cubemap_capture_camera_bind_group"),
+                layout: camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &camera_buffer,
+                        offset: camera_stride * face,
+                        size: wgpu::BufferSize::new(camera_size),
+                    }),
+                }],
+            })
+        });
+
+        Self {
+            clear_color: wgpu::Color::BLACK,
+            znear: 0.05,
+            zfar: 100.0,
+            camera_buffer,
+            camera_stride,
+            camera_bind_groups,
+        }
+    }
+
+    // 第 face 个面（顺序同 CUBE_FACES）的 90° 相机，供绘制回调做剔除或排序
+    pub fn face_camera(&self, position: [f32; 3], face: usize) -> Camera {
+        let (forward, up) = CUBE_FACES[face];
+        let eye = Point3::from(position);
+        Camera {
+            eye,
+            target: eye + forward,
+            up,
+            aspect: 1.0,
+            fovy: 90.0,
+            znear: self.znear,
+            zfar: self.zfar,
+        }
+    }
+
+    // 录制六个面的渲染通道，返回六层的立方体贴图，提交 encoder 后即可作为环境贴图采样
+    // scene 是绘制回调需要的资源，通过参数传入才能在每个通道内借用
+    pub fn record<S>(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        position: [f32; 3],
+        face_size: u32,
+        scene: &S,
+        mut scene_drawer: impl for<'p> FnMut(&mut wgpu::RenderPass<'p>, &'p S, &Camera),
+    ) -> wgpu::Texture {
+        let face_size = face_size.max(1);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Captured Cubemap"),
+            size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBEMAP_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let depth = Texture::create_render_target(
+            device,
+            face_size,
+            face_size,
+            Texture::DEPTH_FORMAT,
+            wgpu::TextureUsages::empty(),
+            "Cubemap Capture Depth",
+        );
+
+        // 相机经由 encoder 中的复制写入，同一个 encoder 里多次捕获也不会互相覆盖
+        let mut cameras = vec![0u8; (self.camera_stride * 6) as usize];
+        for (face, view_proj) in cube_face_view_projections(position, self.znear, self.zfar).into_iter().enumerate() {
+            let uniform = CameraUniform { view_proj: view_proj.into(), ..CameraUniform::new() };
+            let start = self.camera_stride as usize * face;
+            cameras[start..start + std::mem::size_of::<CameraUniform>()].copy_from_slice(bytemuck::bytes_of(&uniform));
+        }
+        let staging = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Cubemap Capture Camera Staging"),
+            contents: &cameras,
+            usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        encoder.copy_buffer_to_buffer(&staging, 0, &self.camera_buffer, 0, self.camera_stride * 6);
+
+        for face in 0..6 {
+            let face_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Captured Cubemap Face"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: face as u32,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Cubemap Capture Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &face_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_bind_group(0, &self.camera_bind_groups[face], &[]);
+            scene_drawer(&mut render_pass, scene, &self.face_camera(position, face));
+        }
+
+        texture
+    }
+}
@@ -0,0 +1,40 @@
+#include "cube_face"
+
+const PI: f32 = 3.14159265;
+
+// 经纬度全景图，Rgba32Float 不可过滤，手动双线性插值
+@group(0) @binding(0)
+var t_equirectangular: texture_2d<f32>;
+// 六个面依次为 +X -X +Y -Y +Z -Z
+@group(0) @binding(1)
+var t_faces: texture_storage_2d_array<rgba16float, write>;
+
+fn load_wrapped(texel: vec2<i32>, size: vec2<i32>) -> vec4f {
+    // 经度方向环绕，纬度方向夹紧
+    let x = ((texel.x % size.x) + size.x) % size.x;
+    let y = clamp(texel.y, 0, size.y - 1);
+    return textureLoad(t_equirectangular, vec2<i32>(x, y), 0);
+}
+
+fn sample_equirectangular(direction: vec3f) -> vec3f {
+    // 经度由 atan2 给出，纬度由 asin 给出；图像上边缘为正上方
+    let uv = vec2f(atan2(direction.z, direction.x) / (2.0 * PI) + 0.5, 0.5 - asin(clamp(direction.y, -1.0, 1.0)) / PI);
+    let size = vec2<i32>(textureDimensions(t_equirectangular));
+    let position = uv * vec2f(size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let top = mix(load_wrapped(base, size), load_wrapped(base + vec2<i32>(1, 0), size), f.x);
+    let bottom = mix(load_wrapped(base + vec2<i32>(0, 1), size), load_wrapped(base + vec2<i32>(1, 1), size), f.x);
+    return mix(top, bottom, f.y).rgb;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let face_size = textureDimensions(t_faces);
+    if any(id.xy >= face_size) {
+        return;
+    }
+    let st = (vec2f(id.xy) + 0.5) / vec2f(face_size) * 2.0 - 1.0;
+    let direction = normalize(face_direction(id.z, st));
+    textureStore(t_faces, vec2<i32>(id.xy), i32(id.z), vec4f(sample_equirectangular(direction), 1.0));
+}
@@ -0,0 +1,269 @@
+use crate::resource_version::{ BindGroupBuilder, SetVersionedBindGroup, VersionCounter, VersionedBindGroup };
+use crate::texture::Texture;
+
+// 默认剥离的层数，为 0 时场景使用加权混合 OIT
+pub const DEPTH_PEEL_LAYERS: usize = 4;
+pub const LAYER_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// 深度剥离：透明物体画 N 遍，每遍丢弃不比上一层更远的片元，深度测试留下剩余片元中最近的一层，
+// 最后从后往前把各层混合到场景上。与 OIT 相比结果精确，代价是 N 次绘制；超过 N 层的部分被丢弃
+pub struct DepthPeelRenderer {
+    layers: Vec<Texture>,
+    // 两张深度纹理轮流作为当前层的深度附件与上一层的深度输入
+    depths: [Texture; 2],
+    targets_version: VersionCounter,
+    peel_bind_group_layout: wgpu::BindGroupLayout,
+    // peel_bind_groups[i] 读取 depths[i]，写入 depths[1 - i]
+    peel_bind_groups: [VersionedBindGroup; 2],
+    composite_pipeline: wgpu::RenderPipeline,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    composite_bind_groups: Vec<VersionedBindGroup>,
+}
+
+impl DepthPeelRenderer {
+    // scene_depth 为不透明场景的深度缓冲，被它遮挡的透明片元不参与剥离
+    pub fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        layer_count: usize,
+        scene_depth: &wgpu::Texture,
+        target_format: wgpu::TextureFormat,
+    ) -> Self {
+        let (layers, depths) = Self::create_targets(device, width, height, layer_count);
+
+        let depth_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            // 以不可过滤的浮点纹理绑定深度：GL 后端不支持对 texture_depth_2d 使用 textureLoad
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        // 绑定号与同一组的光照探针错开，见 shader.wgsl 的 fs_depth_peel
+        let peel_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("depth_peel_bind_group_layout"),
+            entries: &[depth_entry(2), depth_entry(3)],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Depth Peel Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("depth_peel_composite.wgsl").into()),
+        });
+        let composite_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("depth_peel_composite_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Depth Peel Composite Pipeline Layout"),
+            bind_group_layouts: &[&composite_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Depth Peel Composite Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let targets_version = VersionCounter::new("Depth Peel Targets");
+        let peel_bind_groups = Self::create_peel_bind_groups(device, &peel_bind_group_layout, &depths, scene_depth, &targets_version);
+        let composite_bind_groups = Self::create_composite_bind_groups(device, &composite_bind_group_layout, &layers, &targets_version);
+
+        Self {
+            layers,
+            depths,
+            targets_version,
+            peel_bind_group_layout,
+            peel_bind_groups,
+            composite_pipeline,
+            composite_bind_group_layout,
+            composite_bind_groups,
+        }
+    }
+
+    fn create_targets(device: &wgpu::Device, width: u32, height: u32, layer_count: usize) -> (Vec<Texture>, [Texture; 2]) {
+        let usage = wgpu::TextureUsages::empty();
+        let layers = (0..layer_count)
+            .map(|_| Texture::create_render_target(device, width, height, LAYER_FORMAT, usage, "Depth Peel Layer"))
+            .collect();
+        let depths = [0, 1].map(|_| Texture::create_render_target(device, width, height, Texture::DEPTH_FORMAT, usage, "Depth Peel Depth"));
+        (layers, depths)
+    }
+
+    fn create_peel_bind_groups(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        depths: &[Texture; 2],
+        scene_depth: &wgpu::Texture,
+        version: &VersionCounter,
+    ) -> [VersionedBindGroup; 2] {
+        // 场景深度带模板，采样时只取深度部分
+        let scene_depth_view = scene_depth.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Depth Peel Scene Depth"),
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        });
+        [0, 1].map(|index| {
+            BindGroupBuilder::new("depth_peel_bind_group", layout)
+                .texture_view(2, &depths[index].view, version)
+                .entry(3, wgpu::BindingResource::TextureView(&scene_depth_view))
+                .build(device)
+        })
+    }
+
+    fn create_composite_bind_groups(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        layers: &[Texture],
+        version: &VersionCounter,
+    ) -> Vec<VersionedBindGroup> {
+        layers
+            .iter()
+            .map(|layer| {
+                BindGroupBuilder::new("depth_peel_composite_bind_group", layout)
+                    .texture_view(0, &layer.view, version)
+                    .build(device)
+            })
+            .collect()
+    }
+
+    // 场景深度缓冲随窗口尺寸重建，需要一并传入
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32, scene_depth: &wgpu::Texture) {
+        let (layers, depths) = Self::create_targets(device, width, height, self.layers.len());
+        self.targets_version.bump();
+        self.peel_bind_groups = Self::create_peel_bind_groups(device, &self.peel_bind_group_layout, &depths, scene_depth, &self.targets_version);
+        self.composite_bind_groups = Self::create_composite_bind_groups(device, &self.composite_bind_group_layout, &layers, &self.targets_version);
+        self.layers = layers;
+        self.depths = depths;
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    // 剥离管线的第 2 组：上一层深度与场景深度
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.peel_bind_group_layout
+    }
+
+    // 第 layer 遍绘制时绑定在第 2 组
+    pub fn bind_group(&self, layer: usize) -> &wgpu::BindGroup {
+        let bind_group = &self.peel_bind_groups[(layer + 1) % 2];
+        bind_group.assert_current();
+        bind_group.bind_group()
+    }
+
+    // 每一层只保留最近的片元，覆盖写入预乘 alpha 的颜色
+    pub fn color_target() -> wgpu::ColorTargetState {
+        wgpu::ColorTargetState {
+            format: LAYER_FORMAT,
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        }
+    }
+
+    // 所有层使用同一条管线：与上一层的比较在片元着色器中完成，硬件深度测试在剩余片元中选出最近的
+    pub fn depth_stencil_state() -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    // 开始第 layer 遍剥离；第 0 遍之前把“上一层深度”清为 0，使所有片元都能通过
+    pub fn begin_peel_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder, layer: usize) -> wgpu::RenderPass<'a> {
+        if layer == 0 {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Depth Peel Reset Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depths[1].view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth Peel Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.layers[layer].view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depths[layer % 2].view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // 从最远的层开始依次混合到场景上
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth Peel Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.composite_pipeline);
+        for bind_group in self.composite_bind_groups.iter().rev() {
+            render_pass.set_versioned_bind_group(0, bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
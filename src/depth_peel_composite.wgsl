@@ -0,0 +1,14 @@
+@group(0) @binding(0)
+var t_layer: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+// 层中保存的是预乘 alpha 的颜色，由混合状态 (One, OneMinusSrcAlpha) 叠加到目标上
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    return textureLoad(t_layer, vec2<i32>(position.xy), 0);
+}
@@ -0,0 +1,55 @@
+// 帧时间低于目标的这个比例时才提高分辨率，避免在目标附近来回缩放、每帧重建渲染目标
+const UPSCALE_HEADROOM: f32 = 0.85;
+
+// 动态分辨率：GPU 帧时间超过 target_frame_ms 时按 adjustment_rate 降低内部渲染分辨率，
+// 有余量时再逐步提高到 max_scale。只缩放场景渲染目标，交换链保持窗口尺寸，由 Blit 放大
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicResolutionScaling {
+    pub target_frame_ms: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    // 每次调整的缩放比例步长
+    pub adjustment_rate: f32,
+    current_scale: f32,
+}
+
+impl Default for DynamicResolutionScaling {
+    fn default() -> Self {
+        Self::new(1000.0 / 60.0)
+    }
+}
+
+impl DynamicResolutionScaling {
+    pub fn new(target_frame_ms: f32) -> Self {
+        Self {
+            target_frame_ms,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            adjustment_rate: 0.05,
+            current_scale: 1.0,
+        }
+    }
+
+    pub fn current_scale(&self) -> f32 {
+        self.current_scale
+    }
+
+    // 用上一帧的 GPU 耗时调整缩放比例，返回缩放后的渲染尺寸是否变化
+    pub fn update(&mut self, gpu_frame_ms: f32, width: u32, height: u32) -> bool {
+        let previous = self.render_size(width, height);
+        let max_scale = self.max_scale.max(self.min_scale);
+        if gpu_frame_ms > self.target_frame_ms {
+            self.current_scale -= self.adjustment_rate;
+        } else if gpu_frame_ms < self.target_frame_ms * UPSCALE_HEADROOM {
+            self.current_scale += self.adjustment_rate;
+        }
+        self.current_scale = self.current_scale.clamp(self.min_scale, max_scale);
+        self.render_size(width, height) != previous
+    }
+
+    // 窗口尺寸为 width × height 时的内部渲染尺寸，至少 1 × 1
+    pub fn render_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |size: u32| ((size as f32 * self.current_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+}
@@ -0,0 +1,149 @@
+use cgmath::{ Matrix4, SquareMatrix, Vector3 };
+
+use crate::camera::Camera;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyUniform {
+    inverse_view_proj: [[f32; 4]; 4],
+    camera_position: [f32; 3],
+    turbidity: f32,
+    sun_direction: [f32; 3],
+    exposure: f32,
+}
+
+// 一天中 time_of_day 时（地方太阳时，单位为小时）指向太阳的单位向量。
+// 赤纬按一年中的第几天近似，高度角与方位由纬度和时角求出；+y 向上，+x 为东，-z 为北
+pub fn solar_direction(time_of_day: f32, latitude: f32, day_of_year: u32) -> Vector3<f32> {
+    let declination = (-23.44f32).to_radians() * (std::f32::consts::TAU / 365.0 * (day_of_year as f32 + 10.0)).cos();
+    let hour_angle = (15.0 * (time_of_day - 12.0)).to_radians();
+    let latitude = latitude.to_radians();
+    let up = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let east = -declination.cos() * hour_angle.sin();
+    let north = latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos();
+    Vector3::new(east, up, -north)
+}
+
+// 用 Preetham 解析天空模型逐像素计算天空颜色，代替静态的立方体贴图，能表现日出日落。
+// 在不透明物体之后绘制：全屏三角形位于远平面，深度测试为 LessEqual 且不写深度。
+// 输出已做曝光色调映射的线性颜色
+pub struct DynamicSkyShader {
+    // 大气浑浊度，2 为晴朗，10 左右为雾霾
+    pub turbidity: f32,
+    pub exposure: f32,
+    // 纬度（度）与一年中的第几天，决定太阳的轨迹
+    pub latitude: f32,
+    pub day_of_year: u32,
+    sun_direction: Vector3<f32>,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl DynamicSkyShader {
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Dynamic Sky Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("dynamic_sky.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Dynamic Sky Uniform Buffer"),
+            size: std::mem::size_of::<SkyUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("dynamic_sky_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("dynamic_sky_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Dynamic Sky Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Dynamic Sky Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        let day_of_year = 172;
+        Self {
+            turbidity: 2.5,
+            exposure: 0.1,
+            latitude: 35.0,
+            day_of_year,
+            sun_direction: solar_direction(12.0, 35.0, day_of_year),
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    // 上一次 update 算出的太阳方向，可同步给方向光
+    pub fn sun_direction(&self) -> Vector3<f32> {
+        self.sun_direction
+    }
+
+    // time_of_day 为 0..24 的小时数，按它模拟太阳高度角并写入相机与天空参数
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &Camera, time_of_day: f32) {
+        self.sun_direction = solar_direction(time_of_day.rem_euclid(24.0), self.latitude, self.day_of_year);
+        let inverse_view_proj = camera.build_view_projection_matrix().invert().unwrap_or(Matrix4::identity());
+        let uniform = SkyUniform {
+            inverse_view_proj: inverse_view_proj.into(),
+            camera_position: camera.eye.into(),
+            turbidity: self.turbidity.clamp(1.7, 10.0),
+            sun_direction: self.sun_direction.into(),
+            exposure: self.exposure,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,115 @@
+// Preetham 等人 1999 年的解析天空模型：按视线与天顶的夹角 theta、与太阳的夹角 gamma
+// 计算 Perez 分布，得到 Yxy 后转换到 XYZ 再到线性 sRGB
+
+const PI: f32 = 3.14159265;
+// 太阳圆盘的角半径，比真实的约 0.0047 弧度略大以便看清
+const SUN_ANGULAR_RADIUS: f32 = 0.01;
+
+struct SkyUniform {
+    inverse_view_proj: mat4x4f,
+    camera_position: vec3f,
+    // 大气浑浊度，2 为晴朗，10 左右为雾霾
+    turbidity: f32,
+    // 指向太阳的单位向量
+    sun_direction: vec3f,
+    exposure: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> sky: SkyUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) ndc: vec2f,
+};
+
+// 覆盖全屏的大三角形，放在远平面上，只在没有画过物体的像素通过深度测试
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    var out: VertexOutput;
+    out.ndc = uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0);
+    out.clip_position = vec4f(out.ndc, 1.0, 1.0);
+    return out;
+}
+
+// Perez 分布：(1 + A e^(B / cos theta)) (1 + C e^(D gamma) + E cos^2 gamma)
+fn perez(cos_theta: f32, gamma: f32, cos_gamma: f32, a: f32, b: f32, c: f32, d: f32, e: f32) -> f32 {
+    return (1.0 + a * exp(b / cos_theta)) * (1.0 + c * exp(d * gamma) + e * cos_gamma * cos_gamma);
+}
+
+fn sky_color(direction: vec3f) -> vec3f {
+    let t = sky.turbidity;
+    let sun = normalize(sky.sun_direction);
+    // 模型只对地平线以上有效：太阳最低取在地平线上，地平线以下的视线取地平线的颜色
+    let theta_s = acos(clamp(sun.y, 0.0, 1.0));
+    let view = normalize(vec3f(direction.x, max(direction.y, 0.0), direction.z));
+    let cos_theta = max(view.y, 0.01);
+    let cos_gamma = clamp(dot(view, sun), -1.0, 1.0);
+    let gamma = acos(cos_gamma);
+    let cos_theta_s = cos(theta_s);
+
+    // 天顶的亮度（kcd/m²）与色度
+    let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+    let zenith_luminance = max((4.0453 * t - 4.9710) * tan(chi) - 0.2155 * t + 2.4192, 0.0);
+    let theta_s2 = theta_s * theta_s;
+    let theta_s3 = theta_s2 * theta_s;
+    let t2 = t * t;
+    let zenith_x = t2 * (0.00166 * theta_s3 - 0.00375 * theta_s2 + 0.00209 * theta_s)
+        + t * (-0.02903 * theta_s3 + 0.06377 * theta_s2 - 0.03202 * theta_s + 0.00394)
+        + (0.11693 * theta_s3 - 0.21196 * theta_s2 + 0.06052 * theta_s + 0.25886);
+    let zenith_y = t2 * (0.00275 * theta_s3 - 0.00610 * theta_s2 + 0.00317 * theta_s)
+        + t * (-0.04214 * theta_s3 + 0.08970 * theta_s2 - 0.04153 * theta_s + 0.00516)
+        + (0.15346 * theta_s3 - 0.26756 * theta_s2 + 0.06670 * theta_s + 0.26688);
+
+    // 各分量的 Perez 系数随浑浊度线性变化；除以天顶处的值使天顶等于上面求得的值
+    let luminance_a = 0.1787 * t - 1.4630;
+    let luminance_b = -0.3554 * t + 0.4275;
+    let luminance_c = -0.0227 * t + 5.3251;
+    let luminance_d = 0.1206 * t - 2.5771;
+    let luminance_e = -0.0670 * t + 0.3703;
+    let x_a = -0.0193 * t - 0.2592;
+    let x_b = -0.0665 * t + 0.0008;
+    let x_c = -0.0004 * t + 0.2125;
+    let x_d = -0.0641 * t - 0.8989;
+    let x_e = -0.0033 * t + 0.0452;
+    let y_a = -0.0167 * t - 0.2608;
+    let y_b = -0.0950 * t + 0.0092;
+    let y_c = -0.0079 * t + 0.2102;
+    let y_d = -0.0441 * t - 1.6537;
+    let y_e = -0.0109 * t + 0.0529;
+
+    let luminance = zenith_luminance
+        * perez(cos_theta, gamma, cos_gamma, luminance_a, luminance_b, luminance_c, luminance_d, luminance_e)
+        / perez(1.0, theta_s, cos_theta_s, luminance_a, luminance_b, luminance_c, luminance_d, luminance_e);
+    let x = zenith_x
+        * perez(cos_theta, gamma, cos_gamma, x_a, x_b, x_c, x_d, x_e)
+        / perez(1.0, theta_s, cos_theta_s, x_a, x_b, x_c, x_d, x_e);
+    let y = zenith_y
+        * perez(cos_theta, gamma, cos_gamma, y_a, y_b, y_c, y_d, y_e)
+        / perez(1.0, theta_s, cos_theta_s, y_a, y_b, y_c, y_d, y_e);
+
+    // Yxy → XYZ → 线性 sRGB（D65）
+    let xyz = vec3f(x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+    let rgb = mat3x3f(
+        vec3f(3.2406, -0.9689, 0.0557),
+        vec3f(-1.5372, 1.8758, -0.2040),
+        vec3f(-0.4986, 0.0415, 1.0570),
+    ) * xyz;
+    return max(rgb, vec3f(0.0));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let far = sky.inverse_view_proj * vec4f(in.ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w - sky.camera_position);
+
+    var color = sky_color(direction);
+    let sun = normalize(sky.sun_direction);
+    if direction.y > 0.0 && dot(direction, sun) > cos(SUN_ANGULAR_RADIUS) {
+        color *= 20.0;
+    }
+    // 太阳落到地平线以下后逐渐变暗到夜空
+    color *= smoothstep(-0.1, 0.02, sun.y);
+    return vec4f(vec3f(1.0) - exp(-color * sky.exposure), 1.0);
+}
@@ -0,0 +1,66 @@
+#include "cube_face"
+
+const PI: f32 = 3.14159265;
+
+struct PrefilterParams {
+    face_size: u32,
+    // 本级在输出缓冲区中的起始纹素
+    offset: u32,
+    roughness: f32,
+    sample_count: u32,
+}
+
+@group(0) @binding(0)
+var t_source: texture_cube<f32>;
+@group(0) @binding(1)
+var s_source: sampler;
+@group(0) @binding(2)
+var<uniform> params: PrefilterParams;
+// 每个纹素两个 u32，即打包后的 rgba16float，按面、行、列排列，与 KTX2 的层级数据一致
+@group(0) @binding(3)
+var<storage, read_write> output: array<vec2u>;
+
+fn hammersley(i: u32, n: u32) -> vec2f {
+    return vec2f(f32(i) / f32(n), f32(reverseBits(i)) * 2.3283064365386963e-10);
+}
+
+// 按 GGX 分布采样半程向量
+fn importance_sample_ggx(xi: vec2f, n: vec3f, roughness: f32) -> vec3f {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    let up = select(vec3f(1.0, 0.0, 0.0), vec3f(0.0, 0.0, 1.0), abs(n.z) < 0.999);
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+    return normalize(tangent * (cos(phi) * sin_theta) + bitangent * (sin(phi) * sin_theta) + n * cos_theta);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= vec2u(params.face_size)) {
+        return;
+    }
+    let st = (vec2f(id.xy) + 0.5) / f32(params.face_size) * 2.0 - 1.0;
+    // 按惯例假设视线方向等于法线
+    let n = normalize(face_direction(id.z, st));
+
+    var color = textureSampleLevel(t_source, s_source, n, 0.0).rgb;
+    if params.roughness > 0.0 {
+        var sum = vec3f(0.0);
+        var weight = 0.0;
+        for (var i = 0u; i < params.sample_count; i++) {
+            let h = importance_sample_ggx(hammersley(i, params.sample_count), n, params.roughness);
+            let l = reflect(-n, h);
+            let n_dot_l = dot(n, l);
+            if n_dot_l > 0.0 {
+                sum += textureSampleLevel(t_source, s_source, l, 0.0).rgb * n_dot_l;
+                weight += n_dot_l;
+            }
+        }
+        color = sum / max(weight, 1e-4);
+    }
+
+    let index = params.offset + (id.z * params.face_size + id.y) * params.face_size + id.x;
+    output[index] = vec2u(pack2x16float(color.rg), pack2x16float(vec2f(color.b, 1.0)));
+}
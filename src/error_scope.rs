@@ -0,0 +1,70 @@
+use std::future::Future;
+use std::task::{ Context, Poll, Waker };
+
+// 在作用域内捕获 wgpu 校验错误，离开作用域时自动弹出并记录日志
+pub struct ErrorScope<'a> {
+    device: &'a wgpu::Device,
+    label: &'static str,
+    popped: bool,
+}
+
+impl<'a> ErrorScope<'a> {
+    pub fn new(device: &'a wgpu::Device, label: &'static str) -> Self {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        Self { device, label, popped: false }
+    }
+
+    pub async fn pop(mut self) -> Vec<wgpu::Error> {
+        self.popped = true;
+        self.device.pop_error_scope().await.into_iter().collect()
+    }
+
+    // 原生后端上 pop_error_scope 立即就绪，无需异步运行时
+    fn pop_now(&mut self) -> Vec<wgpu::Error> {
+        self.popped = true;
+        let mut future = std::pin::pin!(self.device.pop_error_scope());
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(error) => error.into_iter().collect(),
+            Poll::Pending => {
+                tracing::warn!(scope = self.label, "error scope result is not ready, errors are dropped");
+                Vec::new()
+            }
+        }
+    }
+
+    // 同步取出作用域内的错误，调用方自行处理
+    pub fn take_errors(mut self) -> Vec<wgpu::Error> {
+        self.pop_now()
+    }
+
+    pub fn assert_no_errors(mut self) {
+        let errors = self.pop_now();
+        assert!(errors.is_empty(), "{}", format_errors(self.label, &errors));
+    }
+
+    pub fn log_errors(mut self) {
+        let errors = self.pop_now();
+        if !errors.is_empty() {
+            tracing::error!(scope = self.label, "{}", format_errors(self.label, &errors));
+        }
+    }
+}
+
+impl Drop for ErrorScope<'_> {
+    fn drop(&mut self) {
+        if !self.popped {
+            let errors = self.pop_now();
+            if !errors.is_empty() {
+                tracing::error!(scope = self.label, "{}", format_errors(self.label, &errors));
+            }
+        }
+    }
+}
+
+fn format_errors(label: &str, errors: &[wgpu::Error]) -> String {
+    let mut message = format!("{} wgpu validation error(s) in `{label}`", errors.len());
+    for error in errors {
+        message.push_str(&format!("\n  - {error}"));
+    }
+    message
+}
@@ -0,0 +1,411 @@
+use std::fmt;
+
+use cgmath::{ Matrix4, SquareMatrix };
+
+use crate::camera::CameraUniform;
+use crate::lighting::SpotLight;
+use crate::post_process::gaussian_blur::{ BlurFormat, GaussianBlurPass, R32Float, Rgba32Float };
+use crate::post_process::PostProcessEffect;
+use crate::shader_preprocessor::ShaderPreprocessor;
+use crate::texture::Texture;
+
+pub const MOMENTS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+pub const EXPONENTIAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+pub const FOUR_MOMENTS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const FILTERED_SHADOW_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+// FilteredShadowUniform::light_index 取此值时没有光源使用可过滤阴影
+pub const NO_FILTERED_SHADOW_LIGHT: i32 = -1;
+// exp(c) 超过 f32 上限前的最大指数
+pub const MAX_ESM_EXPONENT: f32 = 88.0;
+const BLUR_KERNEL_RADIUS: u32 = 4;
+
+// 与 shader.wgsl 中的 SHADOW_MODE_* 一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShadowMode {
+    // 阴影图集的比较采样，见 ShadowAtlas
+    Pcf,
+    // 方差阴影图：切比雪夫上界，多层遮挡时漏光
+    #[default]
+    Variance,
+    // 指数阴影图：exp(c · (d_occluder - d_receiver))，漏光少，但接触处的阴影会变亮
+    Exponential,
+    // 矩阴影图：四阶矩重建深度分布，大半影下的漏光与锯齿都比前两者少
+    Moment,
+}
+
+impl ShadowMode {
+    // 按声明顺序循环，Moment 之后回到 Pcf
+    pub fn next(self) -> Self {
+        match self {
+            ShadowMode::Pcf => ShadowMode::Variance,
+            ShadowMode::Variance => ShadowMode::Exponential,
+            ShadowMode::Exponential => ShadowMode::Moment,
+            ShadowMode::Moment => ShadowMode::Pcf,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShadowMode::Pcf => "PCF",
+            ShadowMode::Variance => "VSM",
+            ShadowMode::Exponential => "ESM",
+            ShadowMode::Moment => "MSM",
+        }
+    }
+}
+
+impl fmt::Display for ShadowMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EsmConfig {
+    // 指数的斜率，越大阴影边界越硬、漏光越少；深度归一化到 [0, 1]，不超过 MAX_ESM_EXPONENT
+    pub c: f32,
+    pub blur_sigma: f32,
+}
+
+impl Default for EsmConfig {
+    fn default() -> Self {
+        Self { c: 80.0, blur_sigma: 2.0 }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FilteredShadowUniform {
+    view_proj: [[f32; 4]; 4],
+    light_index: i32,
+    mode: u32,
+    min_variance: f32,
+    light_bleed_reduction: f32,
+    esm_c: f32,
+    far: f32,
+    moment_bias: f32,
+    _padding: f32,
+}
+
+// 每种算法一张图；模糊结果中方差阴影的矩用 Rgba32Float，因为 Rg32Float 不能作为存储纹理
+struct ShadowTargets {
+    variance: Texture,
+    exponential: Texture,
+    moment: Texture,
+}
+
+// 可预先模糊的聚光灯阴影图。矩通道同时渲染三个目标：
+// 方差阴影（Donnelly & Lauritzen 2006）的 (d, d²) 存入 Rg32Float，光照时用切比雪夫上界 p_max = σ² / (σ² + (d - E[d])²)；
+// 指数阴影（Annen et al. 2008）的 exp(c · d) 存入 R32Float，光照时取 saturate(exp(c · d_occluder) · exp(-c · d_receiver))；
+// 矩阴影（Peters & Klein 2015）的 (z, z², z³, z⁴) 存入 Rgba32Float，z = 2d - 1，光照时由 moments.wgsl 重建遮挡概率。
+// d 为光源观察空间的线性深度（clip.w）除以远平面。只对当前模式的图做高斯模糊，着色时每个片元采样一次，
+// 模式存在 uniform 中，切换时不需要重建管线。一张图服务一个聚光灯（light_index），Pcf 模式下该灯使用阴影图集
+pub struct FilteredShadowMap {
+    pub size: u32,
+    pub mode: ShadowMode,
+    // 方差下限，避免平面上因精度产生的自阴影
+    pub min_variance: f32,
+    // 把 p_max 低于此值的部分视为全阴影，减轻多层遮挡时的漏光
+    pub light_bleed_reduction: f32,
+    // 矩阴影的偏移量，见 moments.wgsl
+    pub moment_bias: f32,
+    esm: EsmConfig,
+    light_index: i32,
+    view_proj: Matrix4<f32>,
+    // 光源远平面，深度按它归一化
+    far: f32,
+    targets: ShadowTargets,
+    depth: Texture,
+    // 模糊结果；不支持计算着色器时为 None，直接采样未模糊的图
+    blurred: Option<ShadowTargets>,
+    // 方差阴影与矩阴影共用，同一时刻只有一种模式需要模糊
+    moments_blur: Option<GaussianBlurPass<Rgba32Float>>,
+    exponential_blur: Option<GaussianBlurPass<R32Float>>,
+    uniform_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    pass_bind_group_layout: wgpu::BindGroupLayout,
+    pass_bind_group: wgpu::BindGroup,
+}
+
+impl FilteredShadowMap {
+    pub fn new(device: &wgpu::Device, size: u32, compute_supported: bool) -> Self {
+        let target = |format, usage, label| Texture::create_render_target(device, size, size, format, usage, label);
+        let targets = ShadowTargets {
+            variance: target(MOMENTS_FORMAT, wgpu::TextureUsages::empty(), "Filtered Shadow Moments"),
+            exponential: target(EXPONENTIAL_FORMAT, wgpu::TextureUsages::empty(), "Filtered Shadow Exponential"),
+            moment: target(FOUR_MOMENTS_FORMAT, wgpu::TextureUsages::empty(), "Filtered Shadow Four Moments"),
+        };
+        let depth = target(FILTERED_SHADOW_DEPTH_FORMAT, wgpu::TextureUsages::empty(), "Filtered Shadow Depth");
+        let esm = EsmConfig::default();
+        let (blurred, moments_blur, exponential_blur) = if compute_supported {
+            let blurred = ShadowTargets {
+                variance: target(Rgba32Float::FORMAT, wgpu::TextureUsages::STORAGE_BINDING, "Filtered Shadow Blurred Moments"),
+                exponential: target(R32Float::FORMAT, wgpu::TextureUsages::STORAGE_BINDING, "Filtered Shadow Blurred Exponential"),
+                moment: target(Rgba32Float::FORMAT, wgpu::TextureUsages::STORAGE_BINDING, "Filtered Shadow Blurred Four Moments"),
+            };
+            let mut moments_blur = GaussianBlurPass::<Rgba32Float>::new(device, 2.0, BLUR_KERNEL_RADIUS);
+            moments_blur.resize(device, size, size);
+            let mut exponential_blur = GaussianBlurPass::<R32Float>::new(device, esm.blur_sigma, BLUR_KERNEL_RADIUS);
+            exponential_blur.resize(device, size, size);
+            (Some(blurred), Some(moments_blur), Some(exponential_blur))
+        } else {
+            (None, None, None)
+        };
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Filtered Shadow Uniform Buffer"),
+            size: std::mem::size_of::<FilteredShadowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Filtered Shadow Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // 矩通道的第 0 组：0 号与场景相同为相机，1 号为本结构体的 uniform，片元着色器需要 c 与远平面
+        let pass_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("filtered_shadow_pass_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("filtered_shadow_pass_bind_group"),
+            layout: &pass_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            size,
+            mode: ShadowMode::default(),
+            min_variance: 1e-6,
+            light_bleed_reduction: 0.2,
+            moment_bias: 3e-5,
+            esm,
+            light_index: NO_FILTERED_SHADOW_LIGHT,
+            view_proj: Matrix4::identity(),
+            far: 1.0,
+            targets,
+            depth,
+            blurred,
+            moments_blur,
+            exponential_blur,
+            uniform_buffer,
+            camera_buffer,
+            pass_bind_group_layout,
+            pass_bind_group,
+        }
+    }
+
+    // index 为聚光灯在 LightingUniform::spot_lights 中的序号
+    pub fn set_spot_light(&mut self, index: usize, light: &SpotLight) {
+        self.light_index = index as i32;
+        self.view_proj = light.view_projection();
+        self.far = light.radius;
+    }
+
+    // 光照着色器需要 `#include "moments"`
+    pub fn register_includes(preprocessor: &mut ShaderPreprocessor) {
+        preprocessor.add_include("moments", include_str!("moments.wgsl"));
+    }
+
+    pub fn clear_light(&mut self) {
+        self.light_index = NO_FILTERED_SHADOW_LIGHT;
+    }
+
+    pub fn esm_config(&self) -> EsmConfig {
+        self.esm
+    }
+
+    // 之后需要调用 write
+    pub fn set_esm_config(&mut self, queue: &wgpu::Queue, config: EsmConfig) {
+        self.esm = EsmConfig { c: config.c.clamp(0.0, MAX_ESM_EXPONENT), ..config };
+        if let Some(blur) = &mut self.exponential_blur {
+            blur.set_kernel(queue, self.esm.blur_sigma, BLUR_KERNEL_RADIUS);
+        }
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue) {
+        let uniform = FilteredShadowUniform {
+            view_proj: self.view_proj.into(),
+            light_index: self.light_index,
+            mode: self.mode as u32,
+            min_variance: self.min_variance,
+            light_bleed_reduction: self.light_bleed_reduction,
+            esm_c: self.esm.c,
+            far: self.far,
+            moment_bias: self.moment_bias,
+            _padding: 0.0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+        let camera = CameraUniform { view_proj: self.view_proj.into(), ..CameraUniform::new() };
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::bytes_of(&camera));
+    }
+
+    // 所有目标都清为远平面处的值；第 0 组已绑定，调用方设置管线后绘制投射阴影的物体
+    pub fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        let color_attachment = |view, color| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        let far_exponential = (self.esm.c as f64).exp();
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Filtered Shadow Pass"),
+            color_attachments: &[
+                color_attachment(&self.targets.variance.view, wgpu::Color { r: 1.0, g: 1.0, b: 0.0, a: 0.0 }),
+                color_attachment(&self.targets.exponential.view, wgpu::Color { r: far_exponential, g: 0.0, b: 0.0, a: 0.0 }),
+                color_attachment(&self.targets.moment.view, wgpu::Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_bind_group(0, &self.pass_bind_group, &[]);
+        render_pass
+    }
+
+    // 在矩通道之后调用，只模糊当前模式使用的图
+    pub fn blur(&self, encoder: &mut wgpu::CommandEncoder, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Some(blurred) = &self.blurred else {
+            return;
+        };
+        match self.mode {
+            ShadowMode::Pcf => {}
+            ShadowMode::Variance => {
+                if let Some(blur) = &self.moments_blur {
+                    blur.apply(encoder, &self.targets.variance.view, &blurred.variance.view, device, queue);
+                }
+            }
+            ShadowMode::Exponential => {
+                if let Some(blur) = &self.exponential_blur {
+                    blur.apply(encoder, &self.targets.exponential.view, &blurred.exponential.view, device, queue);
+                }
+            }
+            ShadowMode::Moment => {
+                if let Some(blur) = &self.moments_blur {
+                    blur.apply(encoder, &self.targets.moment.view, &blurred.moment.view, device, queue);
+                }
+            }
+        }
+    }
+
+    // 矩通道管线的第 0 组，顶点着色器与场景共用
+    pub fn pass_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.pass_bind_group_layout
+    }
+
+    // 矩通道管线的颜色目标，片元着色器输出 (d, d²)、exp(c · d) 与 (z, z², z³, z⁴)
+    pub fn color_targets() -> [Option<wgpu::ColorTargetState>; 3] {
+        [MOMENTS_FORMAT, EXPONENTIAL_FORMAT, FOUR_MOMENTS_FORMAT].map(|format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+    }
+
+    pub fn depth_stencil_state() -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: FILTERED_SHADOW_DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    // 光照组中可过滤阴影使用的 5~8 号绑定，与 bind_group_entries 对应。32 位浮点纹理不可过滤，着色器用 textureLoad 读取
+    pub fn bind_group_layout_entries(visibility: wgpu::ShaderStages) -> [wgpu::BindGroupLayoutEntry; 4] {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        [
+            texture_entry(5),
+            wgpu::BindGroupLayoutEntry {
+                binding: 6,
+                visibility,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            texture_entry(7),
+            texture_entry(8),
+        ]
+    }
+
+    pub fn bind_group_entries(&self) -> [wgpu::BindGroupEntry<'_>; 4] {
+        let targets = self.blurred.as_ref().unwrap_or(&self.targets);
+        [
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&targets.variance.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: self.uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: wgpu::BindingResource::TextureView(&targets.exponential.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 8,
+                resource: wgpu::BindingResource::TextureView(&targets.moment.view),
+            },
+        ]
+    }
+}
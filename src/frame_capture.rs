@@ -0,0 +1,981 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufWriter, Write };
+use std::ops::Range;
+use std::path::{ Path, PathBuf };
+
+// 文件头：魔数 + 版本号，之后是一条条 (标签, 内容) 记录
+const MAGIC: &[u8; 4] = b"LWFC";
+const VERSION: u32 = 1;
+
+// 可序列化的枚举取值表，记录中存放的是表中的下标
+const TEXTURE_FORMATS: &[wgpu::TextureFormat] = &[
+    wgpu::TextureFormat::R8Unorm,
+    wgpu::TextureFormat::R16Float,
+    wgpu::TextureFormat::R32Float,
+    wgpu::TextureFormat::Rg16Float,
+    wgpu::TextureFormat::Rg32Float,
+    wgpu::TextureFormat::Rgba8Unorm,
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Bgra8Unorm,
+    wgpu::TextureFormat::Bgra8UnormSrgb,
+    wgpu::TextureFormat::Rgba16Float,
+    wgpu::TextureFormat::Rgba32Float,
+    wgpu::TextureFormat::Depth32Float,
+    wgpu::TextureFormat::Depth24Plus,
+    wgpu::TextureFormat::Depth24PlusStencil8,
+];
+
+const VERTEX_FORMATS: &[wgpu::VertexFormat] = &[
+    wgpu::VertexFormat::Uint8x4,
+    wgpu::VertexFormat::Unorm8x4,
+    wgpu::VertexFormat::Uint16x2,
+    wgpu::VertexFormat::Uint16x4,
+    wgpu::VertexFormat::Float32,
+    wgpu::VertexFormat::Float32x2,
+    wgpu::VertexFormat::Float32x3,
+    wgpu::VertexFormat::Float32x4,
+    wgpu::VertexFormat::Uint32,
+    wgpu::VertexFormat::Uint32x2,
+    wgpu::VertexFormat::Uint32x3,
+    wgpu::VertexFormat::Uint32x4,
+    wgpu::VertexFormat::Sint32,
+    wgpu::VertexFormat::Sint32x2,
+    wgpu::VertexFormat::Sint32x3,
+    wgpu::VertexFormat::Sint32x4,
+];
+
+const TOPOLOGIES: &[wgpu::PrimitiveTopology] = &[
+    wgpu::PrimitiveTopology::PointList,
+    wgpu::PrimitiveTopology::LineList,
+    wgpu::PrimitiveTopology::LineStrip,
+    wgpu::PrimitiveTopology::TriangleList,
+    wgpu::PrimitiveTopology::TriangleStrip,
+];
+
+#[derive(Debug)]
+pub enum CaptureError {
+    Io { path: PathBuf, source: io::Error },
+    // 文件不是捕获文件、版本不符或内容被截断
+    Corrupt(String),
+    UnsupportedTextureFormat(wgpu::TextureFormat),
+    UnsupportedVertexFormat(wgpu::VertexFormat),
+    MissingResource(ResourceId),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::Io { path, source } => write!(f, "frame capture {}: {source}", path.display()),
+            CaptureError::Corrupt(message) => write!(f, "corrupt frame capture: {message}"),
+            CaptureError::UnsupportedTextureFormat(format) => write!(f, "texture format {format:?} cannot be captured"),
+            CaptureError::UnsupportedVertexFormat(format) => write!(f, "vertex format {format:?} cannot be captured"),
+            CaptureError::MissingResource(id) => write!(f, "frame capture references unknown resource {}", id.0),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CaptureError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// 捕获中的资源编号，回放时按编号重建同一组资源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceId(u32);
+
+#[derive(Debug, Clone, Copy)]
+pub enum BindingKind {
+    Uniform { dynamic: bool },
+    Storage { read_only: bool },
+}
+
+// 只支持缓冲区绑定：纹理与采样器的内容无法从 write_buffer 调用中重建
+#[derive(Debug, Clone, Copy)]
+pub struct BindingDesc {
+    pub binding: u32,
+    pub visibility: wgpu::ShaderStages,
+    pub kind: BindingKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct VertexLayoutDesc {
+    pub array_stride: wgpu::BufferAddress,
+    pub step_mode: wgpu::VertexStepMode,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderPipelineDesc {
+    pub label: String,
+    pub shader: ResourceId,
+    pub vertex_entry: String,
+    pub fragment_entry: String,
+    pub bind_group_layouts: Vec<ResourceId>,
+    pub vertex_buffers: Vec<VertexLayoutDesc>,
+    pub topology: wgpu::PrimitiveTopology,
+    pub color_format: wgpu::TextureFormat,
+    pub depth_format: Option<wgpu::TextureFormat>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    SetPipeline(ResourceId),
+    SetBindGroup { index: u32, bind_group: ResourceId, offsets: Vec<u32> },
+    SetVertexBuffer { slot: u32, buffer: ResourceId },
+    SetIndexBuffer { buffer: ResourceId, format: wgpu::IndexFormat },
+    Draw { vertices: Range<u32>, instances: Range<u32> },
+    DrawIndexed { indices: Range<u32>, base_vertex: i32, instances: Range<u32> },
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderPassDesc {
+    pub color: ResourceId,
+    // None 表示保留纹理原有内容
+    pub clear_color: Option<wgpu::Color>,
+    pub depth: Option<ResourceId>,
+    pub clear_depth: Option<f32>,
+    pub commands: Vec<DrawCommand>,
+}
+
+// 捕获或回放过程中创建的全部 GPU 对象
+#[derive(Default)]
+pub struct CaptureResources {
+    buffers: HashMap<ResourceId, wgpu::Buffer>,
+    textures: HashMap<ResourceId, (wgpu::Texture, wgpu::TextureView)>,
+    shaders: HashMap<ResourceId, wgpu::ShaderModule>,
+    bind_group_layouts: HashMap<ResourceId, wgpu::BindGroupLayout>,
+    bind_groups: HashMap<ResourceId, wgpu::BindGroup>,
+    pipelines: HashMap<ResourceId, wgpu::RenderPipeline>,
+}
+
+impl CaptureResources {
+    pub fn buffer(&self, id: ResourceId) -> Option<&wgpu::Buffer> {
+        self.buffers.get(&id)
+    }
+
+    pub fn texture(&self, id: ResourceId) -> Option<&wgpu::Texture> {
+        self.textures.get(&id).map(|(texture, _)| texture)
+    }
+
+    pub fn texture_view(&self, id: ResourceId) -> Option<&wgpu::TextureView> {
+        self.textures.get(&id).map(|(_, view)| view)
+    }
+}
+
+fn lookup<T>(map: &HashMap<ResourceId, T>, id: ResourceId) -> Result<&T, CaptureError> {
+    map.get(&id).ok_or(CaptureError::MissingResource(id))
+}
+
+// 一条捕获记录；捕获时先写入文件再执行，回放时读出后执行同一段代码
+enum Command {
+    CreateBuffer { id: ResourceId, label: String, size: u64, usage: wgpu::BufferUsages },
+    WriteBuffer { id: ResourceId, offset: u64, data: Vec<u8> },
+    CreateTexture { id: ResourceId, label: String, size: (u32, u32), format: wgpu::TextureFormat, usage: wgpu::TextureUsages },
+    CreateShaderModule { id: ResourceId, label: String, source: String },
+    CreateBindGroupLayout { id: ResourceId, label: String, entries: Vec<BindingDesc> },
+    CreateBindGroup { id: ResourceId, label: String, layout: ResourceId, entries: Vec<(u32, ResourceId)> },
+    CreateRenderPipeline { id: ResourceId, desc: RenderPipelineDesc },
+    Submit { passes: Vec<RenderPassDesc> },
+}
+
+impl Command {
+    fn execute(&self, device: &wgpu::Device, queue: &wgpu::Queue, resources: &mut CaptureResources) -> Result<(), CaptureError> {
+        match self {
+            Command::CreateBuffer { id, label, size, usage } => {
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(label),
+                    size: *size,
+                    usage: *usage,
+                    mapped_at_creation: false,
+                });
+                resources.buffers.insert(*id, buffer);
+            }
+            Command::WriteBuffer { id, offset, data } => {
+                queue.write_buffer(lookup(&resources.buffers, *id)?, *offset, data);
+            }
+            Command::CreateTexture { id, label, size, format, usage } => {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: *format,
+                    usage: *usage,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                resources.textures.insert(*id, (texture, view));
+            }
+            Command::CreateShaderModule { id, label, source } => {
+                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(label),
+                    source: wgpu::ShaderSource::Wgsl(source.as_str().into()),
+                });
+                resources.shaders.insert(*id, shader);
+            }
+            Command::CreateBindGroupLayout { id, label, entries } => {
+                let entries = entries
+                    .iter()
+                    .map(|entry| wgpu::BindGroupLayoutEntry {
+                        binding: entry.binding,
+                        visibility: entry.visibility,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: match entry.kind {
+                                BindingKind::Uniform { .. } => wgpu::BufferBindingType::Uniform,
+                                BindingKind::Storage { read_only } => wgpu::BufferBindingType::Storage { read_only },
+                            },
+                            has_dynamic_offset: matches!(entry.kind, BindingKind::Uniform { dynamic: true }),
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    })
+                    .collect::<Vec<_>>();
+                let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(label),
+                    entries: &entries,
+                });
+                resources.bind_group_layouts.insert(*id, layout);
+            }
+            Command::CreateBindGroup { id, label, layout, entries } => {
+                let entries = entries
+                    .iter()
+                    .map(|(binding, buffer)| {
+                        Ok(wgpu::BindGroupEntry {
+                            binding: *binding,
+                            resource: lookup(&resources.buffers, *buffer)?.as_entire_binding(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, CaptureError>>()?;
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(label),
+                    layout: lookup(&resources.bind_group_layouts, *layout)?,
+                    entries: &entries,
+                });
+                resources.bind_groups.insert(*id, bind_group);
+            }
+            Command::CreateRenderPipeline { id, desc } => {
+                let pipeline = create_render_pipeline(device, resources, desc)?;
+                resources.pipelines.insert(*id, pipeline);
+            }
+            Command::Submit { passes } => {
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Frame Capture Encoder"),
+                });
+                for pass in passes {
+                    record_pass(&mut encoder, resources, pass)?;
+                }
+                queue.submit(std::iter::once(encoder.finish()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    resources: &CaptureResources,
+    desc: &RenderPipelineDesc,
+) -> Result<wgpu::RenderPipeline, CaptureError> {
+    let shader = lookup(&resources.shaders, desc.shader)?;
+    let bind_group_layouts = desc
+        .bind_group_layouts
+        .iter()
+        .map(|id| lookup(&resources.bind_group_layouts, *id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(&desc.label),
+        bind_group_layouts: &bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+    let vertex_buffers = desc
+        .vertex_buffers
+        .iter()
+        .map(|buffer| wgpu::VertexBufferLayout {
+            array_stride: buffer.array_stride,
+            step_mode: buffer.step_mode,
+            attributes: &buffer.attributes,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&desc.label),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: &desc.vertex_entry,
+            buffers: &vertex_buffers,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: &desc.fragment_entry,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: desc.color_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: desc.topology,
+            ..Default::default()
+        },
+        depth_stencil: desc.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    }))
+}
+
+fn record_pass(encoder: &mut wgpu::CommandEncoder, resources: &CaptureResources, pass: &RenderPassDesc) -> Result<(), CaptureError> {
+    let color = lookup(&resources.textures, pass.color).map(|(_, view)| view)?;
+    let depth = pass
+        .depth
+        .map(|id| lookup(&resources.textures, id).map(|(_, view)| view))
+        .transpose()?;
+
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Frame Capture Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: color,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: pass.clear_color.map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: depth.map(|view| wgpu::RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(wgpu::Operations {
+                load: pass.clear_depth.map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear),
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    for command in &pass.commands {
+        match command {
+            DrawCommand::SetPipeline(id) => render_pass.set_pipeline(lookup(&resources.pipelines, *id)?),
+            DrawCommand::SetBindGroup { index, bind_group, offsets } => {
+                render_pass.set_bind_group(*index, lookup(&resources.bind_groups, *bind_group)?, offsets);
+            }
+            DrawCommand::SetVertexBuffer { slot, buffer } => {
+                render_pass.set_vertex_buffer(*slot, lookup(&resources.buffers, *buffer)?.slice(..));
+            }
+            DrawCommand::SetIndexBuffer { buffer, format } => {
+                render_pass.set_index_buffer(lookup(&resources.buffers, *buffer)?.slice(..), *format);
+            }
+            DrawCommand::Draw { vertices, instances } => render_pass.draw(vertices.clone(), instances.clone()),
+            DrawCommand::DrawIndexed { indices, base_vertex, instances } => {
+                render_pass.draw_indexed(indices.clone(), *base_vertex, instances.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+// 把整帧用到的 GPU 调用录制到文件：资源创建、write_buffer、管线描述与提交的渲染通道
+// 调用方通过这里的方法代替直接调用 wgpu，拿到的 ResourceId 用于后续引用
+pub struct FrameCapture {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    resources: CaptureResources,
+    next_id: u32,
+}
+
+impl FrameCapture {
+    pub fn start(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        let path = path.as_ref().to_path_buf();
+        let io_error = |source| CaptureError::Io { path: path.clone(), source };
+        let mut writer = BufWriter::new(File::create(&path).map_err(io_error)?);
+        writer.write_all(MAGIC).map_err(io_error)?;
+        writer.write_all(&VERSION.to_le_bytes()).map_err(io_error)?;
+
+        Ok(Self {
+            path,
+            writer,
+            resources: CaptureResources::default(),
+            next_id: 0,
+        })
+    }
+
+    pub fn resources(&self) -> &CaptureResources {
+        &self.resources
+    }
+
+    fn allocate_id(&mut self) -> ResourceId {
+        self.next_id += 1;
+        ResourceId(self.next_id)
+    }
+
+    fn record(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, command: Command) -> Result<(), CaptureError> {
+        let mut bytes = Vec::new();
+        encode(&mut bytes, &command)?;
+        self.writer
+            .write_all(&bytes)
+            .map_err(|source| CaptureError::Io { path: self.path.clone(), source })?;
+        command.execute(device, queue, &mut self.resources)
+    }
+
+    pub fn create_buffer(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        size: u64,
+        usage: wgpu::BufferUsages,
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        self.record(device, queue, Command::CreateBuffer { id, label: label.to_string(), size, usage })?;
+        Ok(id)
+    }
+
+    pub fn write_buffer(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffer: ResourceId,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), CaptureError> {
+        self.record(device, queue, Command::WriteBuffer { id: buffer, offset, data: data.to_vec() })
+    }
+
+    pub fn create_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        size: (u32, u32),
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        self.record(device, queue, Command::CreateTexture { id, label: label.to_string(), size, format, usage })?;
+        Ok(id)
+    }
+
+    pub fn create_shader_module(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        source: &str,
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        self.record(device, queue, Command::CreateShaderModule { id, label: label.to_string(), source: source.to_string() })?;
+        Ok(id)
+    }
+
+    pub fn create_bind_group_layout(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        entries: &[BindingDesc],
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        self.record(device, queue, Command::CreateBindGroupLayout { id, label: label.to_string(), entries: entries.to_vec() })?;
+        Ok(id)
+    }
+
+    // entries 为 (binding, 缓冲区) 对，整个缓冲区绑定到对应位置
+    pub fn create_bind_group(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        layout: ResourceId,
+        entries: &[(u32, ResourceId)],
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        let command = Command::CreateBindGroup { id, label: label.to_string(), layout, entries: entries.to_vec() };
+        self.record(device, queue, command)?;
+        Ok(id)
+    }
+
+    pub fn create_render_pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        desc: &RenderPipelineDesc,
+    ) -> Result<ResourceId, CaptureError> {
+        let id = self.allocate_id();
+        self.record(device, queue, Command::CreateRenderPipeline { id, desc: desc.clone() })?;
+        Ok(id)
+    }
+
+    // 录制并提交一组渲染通道，相当于一次 queue.submit
+    pub fn submit(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, passes: &[RenderPassDesc]) -> Result<(), CaptureError> {
+        self.record(device, queue, Command::Submit { passes: passes.to_vec() })
+    }
+
+    // 写完文件，返回捕获期间创建的资源，用于读取本次的渲染结果
+    pub fn finish(mut self) -> Result<CaptureResources, CaptureError> {
+        self.writer
+            .flush()
+            .map_err(|source| CaptureError::Io { path: self.path.clone(), source })?;
+        Ok(self.resources)
+    }
+
+    // 读取捕获文件并在给定设备上按顺序重新执行所有调用，资源编号与捕获时相同
+    pub fn replay(path: impl AsRef<Path>, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<CaptureResources, CaptureError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| CaptureError::Io { path: path.to_path_buf(), source })?;
+        let mut decoder = Decoder { data: &data, position: 0 };
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(CaptureError::Corrupt("missing capture header".to_string()));
+        }
+        let version = decoder.u32()?;
+        if version != VERSION {
+            return Err(CaptureError::Corrupt(format!("unsupported capture version {version}")));
+        }
+
+        let mut resources = CaptureResources::default();
+        while !decoder.is_empty() {
+            decode(&mut decoder)?.execute(device, queue, &mut resources)?;
+        }
+        Ok(resources)
+    }
+}
+
+// ---- 编码 ----
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    put_u64(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn put_range(out: &mut Vec<u8>, range: &Range<u32>) {
+    put_u32(out, range.start);
+    put_u32(out, range.end);
+}
+
+fn table_index<T: PartialEq + Copy>(table: &[T], value: T) -> Option<u8> {
+    table.iter().position(|entry| *entry == value).map(|index| index as u8)
+}
+
+fn put_texture_format(out: &mut Vec<u8>, format: wgpu::TextureFormat) -> Result<(), CaptureError> {
+    out.push(table_index(TEXTURE_FORMATS, format).ok_or(CaptureError::UnsupportedTextureFormat(format))?);
+    Ok(())
+}
+
+fn encode(out: &mut Vec<u8>, command: &Command) -> Result<(), CaptureError> {
+    match command {
+        Command::CreateBuffer { id, label, size, usage } => {
+            out.push(0);
+            put_u32(out, id.0);
+            put_bytes(out, label.as_bytes());
+            put_u64(out, *size);
+            put_u32(out, usage.bits());
+        }
+        Command::WriteBuffer { id, offset, data } => {
+            out.push(1);
+            put_u32(out, id.0);
+            put_u64(out, *offset);
+            put_bytes(out, data);
+        }
+        Command::CreateTexture { id, label, size, format, usage } => {
+            out.push(2);
+            put_u32(out, id.0);
+            put_bytes(out, label.as_bytes());
+            put_u32(out, size.0);
+            put_u32(out, size.1);
+            put_texture_format(out, *format)?;
+            put_u32(out, usage.bits());
+        }
+        Command::CreateShaderModule { id, label, source } => {
+            out.push(3);
+            put_u32(out, id.0);
+            put_bytes(out, label.as_bytes());
+            put_bytes(out, source.as_bytes());
+        }
+        Command::CreateBindGroupLayout { id, label, entries } => {
+            out.push(4);
+            put_u32(out, id.0);
+            put_bytes(out, label.as_bytes());
+            put_u32(out, entries.len() as u32);
+            for entry in entries {
+                put_u32(out, entry.binding);
+                put_u32(out, entry.visibility.bits());
+                let (kind, flag) = match entry.kind {
+                    BindingKind::Uniform { dynamic } => (0, dynamic),
+                    BindingKind::Storage { read_only } => (1, read_only),
+                };
+                out.extend_from_slice(&[kind, flag as u8]);
+            }
+        }
+        Command::CreateBindGroup { id, label, layout, entries } => {
+            out.push(5);
+            put_u32(out, id.0);
+            put_bytes(out, label.as_bytes());
+            put_u32(out, layout.0);
+            put_u32(out, entries.len() as u32);
+            for (binding, buffer) in entries {
+                put_u32(out, *binding);
+                put_u32(out, buffer.0);
+            }
+        }
+        Command::CreateRenderPipeline { id, desc } => {
+            out.push(6);
+            put_u32(out, id.0);
+            encode_pipeline(out, desc)?;
+        }
+        Command::Submit { passes } => {
+            out.push(7);
+            put_u32(out, passes.len() as u32);
+            for pass in passes {
+                encode_pass(out, pass);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode_pipeline(out: &mut Vec<u8>, desc: &RenderPipelineDesc) -> Result<(), CaptureError> {
+    put_bytes(out, desc.label.as_bytes());
+    put_u32(out, desc.shader.0);
+    put_bytes(out, desc.vertex_entry.as_bytes());
+    put_bytes(out, desc.fragment_entry.as_bytes());
+    put_u32(out, desc.bind_group_layouts.len() as u32);
+    for layout in &desc.bind_group_layouts {
+        put_u32(out, layout.0);
+    }
+    put_u32(out, desc.vertex_buffers.len() as u32);
+    for buffer in &desc.vertex_buffers {
+        put_u64(out, buffer.array_stride);
+        out.push(matches!(buffer.step_mode, wgpu::VertexStepMode::Instance) as u8);
+        put_u32(out, buffer.attributes.len() as u32);
+        for attribute in &buffer.attributes {
+            let format = table_index(VERTEX_FORMATS, attribute.format)
+                .ok_or(CaptureError::UnsupportedVertexFormat(attribute.format))?;
+            out.push(format);
+            put_u64(out, attribute.offset);
+            put_u32(out, attribute.shader_location);
+        }
+    }
+    out.push(table_index(TOPOLOGIES, desc.topology).expect("all primitive topologies are listed"));
+    put_texture_format(out, desc.color_format)?;
+    match desc.depth_format {
+        Some(format) => {
+            out.push(1);
+            put_texture_format(out, format)?;
+        }
+        None => out.push(0),
+    }
+    Ok(())
+}
+
+fn encode_pass(out: &mut Vec<u8>, pass: &RenderPassDesc) {
+    put_u32(out, pass.color.0);
+    match pass.clear_color {
+        Some(color) => {
+            out.push(1);
+            for channel in [color.r, color.g, color.b, color.a] {
+                out.extend_from_slice(&channel.to_le_bytes());
+            }
+        }
+        None => out.push(0),
+    }
+    match pass.depth {
+        Some(depth) => {
+            out.push(1);
+            put_u32(out, depth.0);
+        }
+        None => out.push(0),
+    }
+    match pass.clear_depth {
+        Some(depth) => {
+            out.push(1);
+            out.extend_from_slice(&depth.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+
+    put_u32(out, pass.commands.len() as u32);
+    for command in &pass.commands {
+        match command {
+            DrawCommand::SetPipeline(id) => {
+                out.push(0);
+                put_u32(out, id.0);
+            }
+            DrawCommand::SetBindGroup { index, bind_group, offsets } => {
+                out.push(1);
+                put_u32(out, *index);
+                put_u32(out, bind_group.0);
+                put_u32(out, offsets.len() as u32);
+                for offset in offsets {
+                    put_u32(out, *offset);
+                }
+            }
+            DrawCommand::SetVertexBuffer { slot, buffer } => {
+                out.push(2);
+                put_u32(out, *slot);
+                put_u32(out, buffer.0);
+            }
+            DrawCommand::SetIndexBuffer { buffer, format } => {
+                out.push(3);
+                put_u32(out, buffer.0);
+                out.push(matches!(format, wgpu::IndexFormat::Uint32) as u8);
+            }
+            DrawCommand::Draw { vertices, instances } => {
+                out.push(4);
+                put_range(out, vertices);
+                put_range(out, instances);
+            }
+            DrawCommand::DrawIndexed { indices, base_vertex, instances } => {
+                out.push(5);
+                put_range(out, indices);
+                out.extend_from_slice(&base_vertex.to_le_bytes());
+                put_range(out, instances);
+            }
+        }
+    }
+}
+
+// ---- 解码 ----
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CaptureError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| CaptureError::Corrupt(format!("unexpected end of file at byte {}", self.position)))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CaptureError> {
+        Ok(self.take(N)?.try_into().expect("take returns exactly N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, CaptureError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, CaptureError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u32(&mut self) -> Result<u32, CaptureError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, CaptureError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn id(&mut self) -> Result<ResourceId, CaptureError> {
+        self.u32().map(ResourceId)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], CaptureError> {
+        let len = self.u64()?;
+        let len = usize::try_from(len).map_err(|_| CaptureError::Corrupt(format!("length {len} is too large")))?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, CaptureError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| CaptureError::Corrupt("string is not valid UTF-8".to_string()))
+    }
+
+    fn range(&mut self) -> Result<Range<u32>, CaptureError> {
+        Ok(self.u32()?..self.u32()?)
+    }
+
+    fn table<T: Copy>(&mut self, table: &[T], name: &str) -> Result<T, CaptureError> {
+        let index = self.u8()?;
+        table
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| CaptureError::Corrupt(format!("unknown {name} {index}")))
+    }
+
+    // 读取元素个数，并确认剩余字节至少能容纳这么多最小尺寸的元素，避免损坏文件导致巨量分配
+    fn count(&mut self, min_element_size: usize) -> Result<usize, CaptureError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_element_size) > self.data.len() - self.position {
+            return Err(CaptureError::Corrupt(format!("element count {count} exceeds file size")));
+        }
+        Ok(count)
+    }
+}
+
+fn decode(decoder: &mut Decoder) -> Result<Command, CaptureError> {
+    let tag = decoder.u8()?;
+    let command = match tag {
+        0 => Command::CreateBuffer {
+            id: decoder.id()?,
+            label: decoder.string()?,
+            size: decoder.u64()?,
+            usage: wgpu::BufferUsages::from_bits_truncate(decoder.u32()?),
+        },
+        1 => Command::WriteBuffer {
+            id: decoder.id()?,
+            offset: decoder.u64()?,
+            data: decoder.bytes()?.to_vec(),
+        },
+        2 => Command::CreateTexture {
+            id: decoder.id()?,
+            label: decoder.string()?,
+            size: (decoder.u32()?, decoder.u32()?),
+            format: decoder.table(TEXTURE_FORMATS, "texture format")?,
+            usage: wgpu::TextureUsages::from_bits_truncate(decoder.u32()?),
+        },
+        3 => Command::CreateShaderModule {
+            id: decoder.id()?,
+            label: decoder.string()?,
+            source: decoder.string()?,
+        },
+        4 => {
+            let id = decoder.id()?;
+            let label = decoder.string()?;
+            let entries = (0..decoder.count(10)?)
+                .map(|_| {
+                    let binding = decoder.u32()?;
+                    let visibility = wgpu::ShaderStages::from_bits_truncate(decoder.u32()?);
+                    let kind = match (decoder.u8()?, decoder.bool()?) {
+                        (0, dynamic) => BindingKind::Uniform { dynamic },
+                        (1, read_only) => BindingKind::Storage { read_only },
+                        (kind, _) => return Err(CaptureError::Corrupt(format!("unknown binding kind {kind}"))),
+                    };
+                    Ok(BindingDesc { binding, visibility, kind })
+                })
+                .collect::<Result<_, _>>()?;
+            Command::CreateBindGroupLayout { id, label, entries }
+        }
+        5 => {
+            let id = decoder.id()?;
+            let label = decoder.string()?;
+            let layout = decoder.id()?;
+            let entries = (0..decoder.count(8)?)
+                .map(|_| Ok((decoder.u32()?, decoder.id()?)))
+                .collect::<Result<_, CaptureError>>()?;
+            Command::CreateBindGroup { id, label, layout, entries }
+        }
+        6 => Command::CreateRenderPipeline {
+            id: decoder.id()?,
+            desc: decode_pipeline(decoder)?,
+        },
+        7 => {
+            let passes = (0..decoder.count(8)?)
+                .map(|_| decode_pass(decoder))
+                .collect::<Result<_, _>>()?;
+            Command::Submit { passes }
+        }
+        _ => return Err(CaptureError::Corrupt(format!("unknown record tag {tag}"))),
+    };
+    Ok(command)
+}
+
+fn decode_pipeline(decoder: &mut Decoder) -> Result<RenderPipelineDesc, CaptureError> {
+    let label = decoder.string()?;
+    let shader = decoder.id()?;
+    let vertex_entry = decoder.string()?;
+    let fragment_entry = decoder.string()?;
+    let bind_group_layouts = (0..decoder.count(4)?).map(|_| decoder.id()).collect::<Result<_, _>>()?;
+    let vertex_buffers = (0..decoder.count(13)?)
+        .map(|_| {
+            let array_stride = decoder.u64()?;
+            let step_mode = if decoder.bool()? { wgpu::VertexStepMode::Instance } else { wgpu::VertexStepMode::Vertex };
+            let attributes = (0..decoder.count(13)?)
+                .map(|_| {
+                    Ok(wgpu::VertexAttribute {
+                        format: decoder.table(VERTEX_FORMATS, "vertex format")?,
+                        offset: decoder.u64()?,
+                        shader_location: decoder.u32()?,
+                    })
+                })
+                .collect::<Result<_, CaptureError>>()?;
+            Ok(VertexLayoutDesc { array_stride, step_mode, attributes })
+        })
+        .collect::<Result<_, CaptureError>>()?;
+    let topology = decoder.table(TOPOLOGIES, "primitive topology")?;
+    let color_format = decoder.table(TEXTURE_FORMATS, "texture format")?;
+    let depth_format = if decoder.bool()? { Some(decoder.table(TEXTURE_FORMATS, "texture format")?) } else { None };
+
+    Ok(RenderPipelineDesc {
+        label,
+        shader,
+        vertex_entry,
+        fragment_entry,
+        bind_group_layouts,
+        vertex_buffers,
+        topology,
+        color_format,
+        depth_format,
+    })
+}
+
+fn decode_pass(decoder: &mut Decoder) -> Result<RenderPassDesc, CaptureError> {
+    let color = decoder.id()?;
+    let clear_color = if decoder.bool()? {
+        let [r, g, b, a] = [0; 4].map(|_| decoder.array().map(f64::from_le_bytes));
+        Some(wgpu::Color { r: r?, g: g?, b: b?, a: a? })
+    } else {
+        None
+    };
+    let depth = if decoder.bool()? { Some(decoder.id()?) } else { None };
+    let clear_depth = if decoder.bool()? { Some(f32::from_le_bytes(decoder.array()?)) } else { None };
+
+    let commands = (0..decoder.count(5)?)
+        .map(|_| {
+            let tag = decoder.u8()?;
+            let command = match tag {
+                0 => DrawCommand::SetPipeline(decoder.id()?),
+                1 => DrawCommand::SetBindGroup {
+                    index: decoder.u32()?,
+                    bind_group: decoder.id()?,
+                    offsets: (0..decoder.count(4)?).map(|_| decoder.u32()).collect::<Result<_, _>>()?,
+                },
+                2 => DrawCommand::SetVertexBuffer { slot: decoder.u32()?, buffer: decoder.id()? },
+                3 => DrawCommand::SetIndexBuffer {
+                    buffer: decoder.id()?,
+                    format: if decoder.bool()? { wgpu::IndexFormat::Uint32 } else { wgpu::IndexFormat::Uint16 },
+                },
+                4 => DrawCommand::Draw { vertices: decoder.range()?, instances: decoder.range()? },
+                5 => DrawCommand::DrawIndexed {
+                    indices: decoder.range()?,
+                    base_vertex: i32::from_le_bytes(decoder.array()?),
+                    instances: decoder.range()?,
+                },
+                _ => return Err(CaptureError::Corrupt(format!("unknown draw command {tag}"))),
+            };
+            Ok(command)
+        })
+        .collect::<Result<_, CaptureError>>()?;
+
+    Ok(RenderPassDesc { color, clear_color, depth, clear_depth, commands })
+}
@@ -0,0 +1,57 @@
+use cgmath::{ InnerSpace, Matrix, Matrix4, Vector3, Vector4 };
+
+use crate::aabb::Aabb;
+
+// 法线指向内侧：dot(normal, p) + distance >= 0 表示在平面内侧
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    fn from_row(row: Vector4<f32>) -> Self {
+        let length = row.truncate().magnitude().max(f32::EPSILON);
+        Self {
+            normal: row.truncate() / length,
+            distance: row.w / length,
+        }
+    }
+
+    pub fn signed_distance(&self, point: [f32; 3]) -> f32 {
+        self.normal.dot(Vector3::from(point)) + self.distance
+    }
+}
+
+// 视锥体的六个平面：左、右、下、上、近、远
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    // 从裁剪矩阵的行提取平面（Gribb & Hartmann 2001），wgpu 的裁剪空间 z 范围为 0..w。
+    // 传入 view_proj 得到世界空间的视锥体，传入 view_proj * model 得到模型空间的视锥体
+    pub fn from_matrix(matrix: Matrix4<f32>) -> Self {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|row| matrix.row(row));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, z, w - z].map(Plane::from_row),
+        }
+    }
+
+    pub fn contains_point(&self, point: [f32; 3]) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    // 保守测试：只有整个包围盒都在某个平面外侧时才返回 false
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        self.planes.iter().all(|plane| {
+            // 沿法线方向最靠内的角
+            let corner = [0, 1, 2].map(|axis| if plane.normal[axis] >= 0.0 { aabb.max[axis] } else { aabb.min[axis] });
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
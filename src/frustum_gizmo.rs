@@ -0,0 +1,61 @@
+use cgmath::{ EuclideanSpace, Matrix4, Point3, SquareMatrix, Vector4 };
+
+use crate::camera::Camera;
+use crate::debug_draw::DebugDraw;
+
+// 视锥的 8 个角：前 4 个在近平面，后 4 个在远平面，依次为左下、右下、右上、左上
+// 由 view_proj 的逆矩阵反投影 NDC 立方体（wgpu 的 z 范围为 0..1）得到
+pub fn frustum_corners(view_proj: Matrix4<f32>) -> Option<[Point3<f32>; 8]> {
+    let inverse = view_proj.invert()?;
+    let mut corners = [Point3::origin(); 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        let z = if i < 4 { 0.0 } else { 1.0 };
+        let (x, y) = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)][i % 4];
+        let world = inverse * Vector4::new(x, y, z, 1.0);
+        *corner = Point3::from_homogeneous(world);
+    }
+    Some(corners)
+}
+
+// 画出相机的视锥线框，便于调试 fov、宽高比与远近平面
+// 画的是另一台相机（如传送门、探针或被调试的相机）的视锥；从相机自身看视锥退化成屏幕边框
+pub struct FrustumGizmo {
+    pub color: [f32; 4],
+    pub plane_color: [f32; 4],
+    pub center_line_color: [f32; 4],
+}
+
+impl Default for FrustumGizmo {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 1.0, 1.0, 1.0],
+            plane_color: [1.0, 0.6, 0.1, 1.0],
+            center_line_color: [0.2, 0.9, 0.9, 1.0],
+        }
+    }
+}
+
+impl FrustumGizmo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 每帧调用，直接从相机当前参数计算，修改 fov 或远近平面后立即反映出来
+    pub fn draw(&self, debug_draw: &mut DebugDraw, camera: &Camera) {
+        let Some(corners) = frustum_corners(camera.build_view_projection_matrix()) else {
+            tracing::warn!("frustum gizmo: camera view-projection matrix is not invertible");
+            return;
+        };
+        debug_draw.box_corners(&corners, self.color);
+
+        // 近、远平面上的十字线，连接相对两边的中点
+        for plane in [&corners[0..4], &corners[4..8]] {
+            let midpoint = |a: Point3<f32>, b: Point3<f32>| a.midpoint(b);
+            debug_draw.line(midpoint(plane[0], plane[1]), midpoint(plane[2], plane[3]), self.plane_color);
+            debug_draw.line(midpoint(plane[1], plane[2]), midpoint(plane[3], plane[0]), self.plane_color);
+        }
+
+        let center = Point3::centroid(&corners);
+        debug_draw.line(camera.eye, center, self.center_line_color);
+    }
+}
@@ -0,0 +1,315 @@
+use cgmath::{ Matrix4, SquareMatrix };
+use wgpu::util::DeviceExt;
+
+use crate::mesh::Vertex;
+use crate::mesh_upload::GpuMesh;
+use crate::texture::TextureHandle;
+
+pub const DENSITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FurConfig {
+    // 壳层数 K，越多毛发越连续，每层一次绘制
+    pub num_shells: u32,
+    // 最外层壳沿法线的偏移（世界单位）
+    pub fur_length: f32,
+    // 由 FurTextures::add 返回
+    pub density_texture: TextureHandle,
+    pub tiling: f32,
+    pub color: [f32; 3],
+}
+
+impl FurConfig {
+    pub fn new(density_texture: TextureHandle) -> Self {
+        Self {
+            num_shells: 32,
+            fur_length: 0.1,
+            density_texture,
+            tiling: 8.0,
+            color: [0.6, 0.45, 0.3],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FurUniform {
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+    num_shells: u32,
+    fur_length: f32,
+    tiling: f32,
+    _padding: u32,
+}
+
+impl FurUniform {
+    fn new(config: &FurConfig, model: Matrix4<f32>) -> Self {
+        let [r, g, b] = config.color;
+        Self {
+            model: model.into(),
+            color: [r, g, b, 1.0],
+            num_shells: config.num_shells,
+            fur_length: config.fur_length,
+            tiling: config.tiling,
+            _padding: 0,
+        }
+    }
+}
+
+// 每个纹素一个随机值的噪声纹理，用作发丝遮罩
+pub fn create_strand_texture(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, seed: u32) -> wgpu::Texture {
+    // xorshift32，种子不能为 0
+    let mut state = seed.max(1);
+    let data = (0..size * size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect::<Vec<_>>();
+
+    let extent = wgpu::Extent3d {
+        width: size,
+        height: size,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Fur Strand Texture"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DENSITY_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(size),
+            rows_per_image: Some(size),
+        },
+        extent,
+    );
+    texture
+}
+
+// 毛发遮罩纹理的集合，多个 ShellRenderer 可以共用
+pub struct FurTextures {
+    bind_group_layout: wgpu::BindGroupLayout,
+    // 以 TextureHandle 为下标
+    bind_groups: Vec<wgpu::BindGroup>,
+    sampler: wgpu::Sampler,
+}
+
+impl FurTextures {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("fur_density_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        // 最近邻采样保持每根发丝的边缘清晰
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Fur Density Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self { bind_group_layout, bind_groups: Vec::new(), sampler }
+    }
+
+    // 注册一张单通道遮罩纹理，返回的句柄用于 FurConfig::density_texture
+    pub fn add(&mut self, device: &wgpu::Device, texture: &wgpu::Texture) -> TextureHandle {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("fur_density_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        self.bind_groups.push(bind_group);
+        TextureHandle(self.bind_groups.len() as u32 - 1)
+    }
+}
+
+// 壳层纹理毛发：把基础网格沿法线逐层外扩绘制 K 次，
+// 片元着色器按噪声纹理丢弃像素，留下的部分在各层之间连成发丝
+pub struct ShellRenderer {
+    pub config: FurConfig,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    mesh: GpuMesh,
+    model: Matrix4<f32>,
+}
+
+impl ShellRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        mesh: GpuMesh,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        textures: &FurTextures,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        config: FurConfig,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fur Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("fur.wgsl").into()),
+        });
+
+        let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("fur_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+        });
+
+        let model = Matrix4::identity();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fur Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[FurUniform::new(&config, model)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("fur_bind_group"),
+            layout: &uniform_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Fur Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &uniform_bind_group_layout, &textures.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Fur Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        Self {
+            config,
+            pipeline,
+            uniform_buffer,
+            uniform_bind_group,
+            mesh,
+            model,
+        }
+    }
+
+    pub fn set_model(&mut self, queue: &wgpu::Queue, model: Matrix4<f32>) {
+        self.model = model;
+        self.write_uniform(queue);
+    }
+
+    // 修改 config 后调用
+    pub fn update(&self, queue: &wgpu::Queue) {
+        self.write_uniform(queue);
+    }
+
+    fn write_uniform(&self, queue: &wgpu::Queue) {
+        let uniform = FurUniform::new(&self.config, self.model);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    // 由内向外逐层绘制，外层的深度测试会剔除被内层挡住的片元
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: &'a wgpu::BindGroup,
+        textures: &'a FurTextures,
+    ) {
+        let Some(density) = textures.bind_groups.get(self.config.density_texture.0 as usize) else {
+            tracing::warn!("fur: density texture {:?} is not registered", self.config.density_texture);
+            return;
+        };
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(2, density, &[]);
+        render_pass.set_vertex_buffer(0, self.mesh.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for layer in 0..self.config.num_shells {
+            render_pass.draw_indexed(0..self.mesh.index_count, 0, layer..layer + 1);
+        }
+    }
+}
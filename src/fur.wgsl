@@ -0,0 +1,65 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct FurUniform {
+    model: mat4x4f,
+    color: vec4f,
+    num_shells: u32,
+    fur_length: f32,
+    // 噪声纹理在 uv 上的重复次数，决定毛发的粗细
+    tiling: f32,
+};
+@group(1) @binding(0)
+var<uniform> fur: FurUniform;
+
+@group(2) @binding(0)
+var t_density: texture_2d<f32>;
+@group(2) @binding(1)
+var s_density: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) tex_coords: vec2f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+    @location(1) world_normal: vec3f,
+    // 当前壳层的高度 layer / K
+    @location(2) height: f32,
+};
+
+// 每个壳层是一次独立的绘制，实例序号即层号
+@vertex
+fn vs_main(model: VertexInput, @builtin(instance_index) layer: u32) -> VertexOutput {
+    let height = f32(layer) / f32(max(fur.num_shells, 1u));
+    let world_normal = normalize((fur.model * vec4f(model.normal, 0.0)).xyz);
+    let world_position = (fur.model * vec4f(model.position, 1.0)).xyz + world_normal * height * fur.fur_length;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4f(world_position, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.world_normal = world_normal;
+    out.height = height;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // 毛发遮罩：噪声值越大的发丝越短
+    let density = textureSample(t_density, s_density, in.tex_coords * fur.tiling).r;
+    if density > 1.0 - in.height {
+        discard;
+    }
+
+    // 根部更暗，近似发丝之间的自遮挡
+    let occlusion = mix(0.3, 1.0, in.height);
+    let light = max(dot(normalize(in.world_normal), normalize(vec3f(0.3, 1.0, 0.5))), 0.0) * 0.7 + 0.3;
+    return vec4f(fur.color.rgb * occlusion * light, 1.0);
+}
@@ -0,0 +1,345 @@
+use std::fmt;
+
+use crate::camera::Camera;
+use crate::resource_version::{ BindGroupBuilder, SetVersionedBindGroup, VersionCounter, VersionedBindGroup };
+use crate::texture::Texture;
+
+// 为 false 时不透明物体回到前向渲染，G-buffer 只在调试视图中绘制
+pub const DEFERRED_SHADING: bool = true;
+
+pub const ALBEDO_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const POSITION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const AO_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+// 调试视图显示的 G-buffer 通道，None 时正常显示光照结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GBufferDebugMode {
+    #[default]
+    None,
+    Normals,
+    Albedo,
+    Depth,
+    Roughness,
+    Metallic,
+    Ao,
+}
+
+impl GBufferDebugMode {
+    // 按声明顺序循环，Ao 之后回到 None
+    pub fn next(self) -> Self {
+        match self {
+            GBufferDebugMode::None => GBufferDebugMode::Normals,
+            GBufferDebugMode::Normals => GBufferDebugMode::Albedo,
+            GBufferDebugMode::Albedo => GBufferDebugMode::Depth,
+            GBufferDebugMode::Depth => GBufferDebugMode::Roughness,
+            GBufferDebugMode::Roughness => GBufferDebugMode::Metallic,
+            GBufferDebugMode::Metallic => GBufferDebugMode::Ao,
+            GBufferDebugMode::Ao => GBufferDebugMode::None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GBufferDebugMode::None => "None",
+            GBufferDebugMode::Normals => "Normals",
+            GBufferDebugMode::Albedo => "Albedo",
+            GBufferDebugMode::Depth => "Depth",
+            GBufferDebugMode::Roughness => "Roughness",
+            GBufferDebugMode::Metallic => "Metallic",
+            GBufferDebugMode::Ao => "AO",
+        }
+    }
+}
+
+impl fmt::Display for GBufferDebugMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DebugParams {
+    // 由世界空间位置重新计算观察空间深度
+    view_proj: [[f32; 4]; 4],
+    // 与 gbuffer_debug.wgsl 中的常量一致，None 为 0
+    mode: u32,
+    // 观察空间深度除以它后显示
+    far: f32,
+    _padding: [f32; 2],
+}
+
+// 延迟渲染的几何缓冲：几何通道（shader.wgsl 的 fs_gbuffer）同时写入四个颜色目标，
+// 光照通道（fs_deferred）在第 1 组的 1~4 号绑定读取它们计算着色；
+// 调试通道用全屏三角形直接显示其中一个通道，不经过光照
+//   RT0: ALBEDO_FORMAT   rgb 基础色，a 为 1 标记有表面
+//   RT1: NORMAL_FORMAT   xyz 世界空间法线，w 粗糙度
+//   RT2: POSITION_FORMAT xyz 世界空间位置，w 金属度
+//   RT3: AO_FORMAT       环境光遮蔽
+pub struct GBuffer {
+    albedo: Texture,
+    normal: Texture,
+    position: Texture,
+    ao: Texture,
+    // 所有目标随窗口尺寸重新分配，共用一个版本
+    targets_version: VersionCounter,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: VersionedBindGroup,
+    debug_pipeline: wgpu::RenderPipeline,
+    debug_bind_group: VersionedBindGroup,
+    debug_buffer: wgpu::Buffer,
+}
+
+impl GBuffer {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, target_format: wgpu::TextureFormat) -> Self {
+        let [albedo, normal, position, ao] = Self::create_targets(device, width, height);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("G-Buffer Debug Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gbuffer_debug.wgsl").into()),
+        });
+
+        // 位置为 Rgba32Float，不可过滤，着色器统一用 textureLoad 读取
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        // 第 0 号绑定留给场景着色器中同组的材质
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gbuffer_bind_group_layout"),
+            entries: &[texture_entry(1), texture_entry(2), texture_entry(3), texture_entry(4)],
+        });
+        let debug_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gbuffer_debug_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("G-Buffer Debug Pipeline Layout"),
+            bind_group_layouts: &[&debug_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let debug_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("G-Buffer Debug Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let debug_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("G-Buffer Debug Buffer"),
+            size: std::mem::size_of::<DebugParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let debug_bind_group = BindGroupBuilder::new("gbuffer_debug_bind_group", &debug_bind_group_layout)
+            .entry(0, debug_buffer.as_entire_binding())
+            .build(device);
+
+        let targets_version = VersionCounter::new("G-Buffer Targets");
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, [&albedo, &normal, &position, &ao], &targets_version);
+
+        Self {
+            albedo,
+            normal,
+            position,
+            ao,
+            targets_version,
+            bind_group_layout,
+            bind_group,
+            debug_pipeline,
+            debug_bind_group,
+            debug_buffer,
+        }
+    }
+
+    fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> [Texture; 4] {
+        let usage = wgpu::TextureUsages::empty();
+        [
+            Texture::create_render_target(device, width, height, ALBEDO_FORMAT, usage, "G-Buffer Albedo"),
+            Texture::create_render_target(device, width, height, NORMAL_FORMAT, usage, "G-Buffer Normal"),
+            Texture::create_render_target(device, width, height, POSITION_FORMAT, usage, "G-Buffer Position"),
+            Texture::create_render_target(device, width, height, AO_FORMAT, usage, "G-Buffer AO"),
+        ]
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        targets: [&Texture; 4],
+        version: &VersionCounter,
+    ) -> VersionedBindGroup {
+        BindGroupBuilder::new("gbuffer_bind_group", layout)
+            .texture_view(1, &targets[0].view, version)
+            .texture_view(2, &targets[1].view, version)
+            .texture_view(3, &targets[2].view, version)
+            .texture_view(4, &targets[3].view, version)
+            .build(device)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let [albedo, normal, position, ao] = Self::create_targets(device, width, height);
+        self.targets_version.bump();
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            [&albedo, &normal, &position, &ao],
+            &self.targets_version,
+        );
+        self.albedo = albedo;
+        self.normal = normal;
+        self.position = position;
+        self.ao = ao;
+    }
+
+    // 屏幕空间效果（如 SsgiPass）直接读取的通道
+    pub fn albedo(&self) -> &Texture {
+        &self.albedo
+    }
+
+    pub fn normal(&self) -> &Texture {
+        &self.normal
+    }
+
+    // 光照通道的第 1 组
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    // 几何管线的四个颜色目标，顺序与 fs_gbuffer 的 @location(0)..@location(3) 一致
+    pub fn color_targets() -> [Option<wgpu::ColorTargetState>; 4] {
+        [ALBEDO_FORMAT, NORMAL_FORMAT, POSITION_FORMAT, AO_FORMAT].map(|format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+    }
+
+    pub fn depth_stencil_state(format: wgpu::TextureFormat) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    // 清空所有目标与场景深度（含模板），之后用几何管线绘制不透明物体；
+    // 后续通道加载同一张深度缓冲，透明物体与传送门仍能与不透明物体做深度测试
+    pub fn begin_geometry_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        depth_view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        let attachment = |texture: &'a Texture| {
+            Some(wgpu::RenderPassColorAttachment {
+                view: &texture.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("G-Buffer Geometry Pass"),
+            color_attachments: &[
+                attachment(&self.albedo),
+                attachment(&self.normal),
+                attachment(&self.position),
+                attachment(&self.ao),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Store,
+                }),
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // 光照管线与其余各组由调用方设置，这里绑定第 1 组并绘制全屏三角形
+    pub fn draw_lighting<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_versioned_bind_group(1, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // 用选中的通道覆盖 target，深度视图中 camera.zfar 处显示为黑色
+    pub fn draw_debug(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        target: &wgpu::TextureView,
+        mode: GBufferDebugMode,
+        camera: &Camera,
+    ) {
+        let params = DebugParams {
+            view_proj: camera.build_view_projection_matrix().into(),
+            mode: mode as u32,
+            far: camera.zfar,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.debug_buffer, 0, bytemuck::bytes_of(&params));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("G-Buffer Debug Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.debug_pipeline);
+        render_pass.set_versioned_bind_group(0, &self.debug_bind_group, &[]);
+        render_pass.set_versioned_bind_group(1, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,69 @@
+const MODE_NORMALS: u32 = 1u;
+const MODE_ALBEDO: u32 = 2u;
+const MODE_DEPTH: u32 = 3u;
+const MODE_ROUGHNESS: u32 = 4u;
+const MODE_METALLIC: u32 = 5u;
+const MODE_AO: u32 = 6u;
+
+struct DebugParams {
+    view_proj: mat4x4f,
+    mode: u32,
+    far: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: DebugParams;
+
+// 与光照通道相同的 G-buffer 绑定
+@group(1) @binding(1)
+var t_albedo: texture_2d<f32>;
+@group(1) @binding(2)
+var t_normal: texture_2d<f32>;
+@group(1) @binding(3)
+var t_position: texture_2d<f32>;
+@group(1) @binding(4)
+var t_ao: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let texel = vec2<i32>(position.xy);
+    let albedo = textureLoad(t_albedo, texel, 0);
+    // 几何通道清空为 0，alpha 为 1 的像素才有表面
+    if albedo.a <= 0.0 {
+        return vec4f(0.0, 0.0, 0.0, 1.0);
+    }
+    let normal = textureLoad(t_normal, texel, 0);
+    let world_position = textureLoad(t_position, texel, 0);
+
+    var color = vec3f(0.0);
+    switch params.mode {
+        case MODE_NORMALS: {
+            color = normal.xyz * 0.5 + 0.5;
+        }
+        case MODE_ALBEDO: {
+            color = albedo.rgb;
+        }
+        case MODE_DEPTH: {
+            // 近处亮、远处暗
+            let view_depth = (params.view_proj * vec4f(world_position.xyz, 1.0)).w;
+            color = vec3f(1.0 - saturate(view_depth / params.far));
+        }
+        case MODE_ROUGHNESS: {
+            color = vec3f(normal.w);
+        }
+        case MODE_METALLIC: {
+            color = vec3f(world_position.w);
+        }
+        case MODE_AO: {
+            color = vec3f(textureLoad(t_ao, texel, 0).r);
+        }
+        default: {}
+    }
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,535 @@
+use cgmath::{ Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation3, SquareMatrix, Transform as _, Vector3 };
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::mesh::MeshData;
+use crate::primitives;
+use crate::ray_cast::{ self, Ray };
+use crate::transform::Transform;
+
+// 操纵柄在屏幕上保持大致固定的尺寸：世界尺寸 = 到相机的距离 × 该系数
+const SCREEN_SCALE: f32 = 0.25;
+const RING_RADIUS: f32 = 0.9;
+const HOVER_COLOR: [f32; 4] = [1.0, 0.9, 0.1, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoHandle {
+    Axis(usize),
+    // 平移、缩放时为中心小方块；旋转时为整个轨迹球
+    Center,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GizmoVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+impl GizmoVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GizmoVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+struct GizmoPart {
+    mode: GizmoMode,
+    handle: GizmoHandle,
+    color: [f32; 4],
+    // 以单位尺寸建模；绘制网格与拾取网格分开，拾取网格更粗以便点中
+    draw_mesh: MeshData,
+    pick_mesh: MeshData,
+}
+
+struct DragState {
+    handle: GizmoHandle,
+    start: Transform,
+    origin: Point3<f32>,
+    size: f32,
+    // 开始拖拽时射线与约束（直线、平面或球面）的交点
+    start_point: Vector3<f32>,
+}
+
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    hovered: Option<GizmoHandle>,
+    drag: Option<DragState>,
+    parts: Vec<GizmoPart>,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+}
+
+impl Gizmo {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let parts = build_parts();
+        let max_vertices = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale]
+            .iter()
+            .map(|mode| parts.iter().filter(|part| part.mode == *mode).map(|part| part.draw_mesh.vertices.len()).sum::<usize>())
+            .max()
+            .unwrap_or(0);
+        let max_indices = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale]
+            .iter()
+            .map(|mode| parts.iter().filter(|part| part.mode == *mode).map(|part| part.draw_mesh.indices.len()).sum::<usize>())
+            .max()
+            .unwrap_or(0);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gizmo Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gizmo.wgsl").into()),
+        });
+
+        let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gizmo Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[identity]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gizmo_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("gizmo_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Gizmo Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Gizmo Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[GizmoVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // 操纵柄很细，从任何角度都应可见
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            // 不使用深度，操纵柄总是绘制在场景之上
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Gizmo Vertex Buffer"),
+            size: (max_vertices * std::mem::size_of::<GizmoVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Gizmo Index Buffer"),
+            size: (max_indices * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            mode: GizmoMode::Translate,
+            hovered: None,
+            drag: None,
+            parts,
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            vertex_buffer,
+            index_buffer,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: GizmoMode) {
+        if self.drag.is_none() {
+            self.mode = mode;
+            self.hovered = None;
+        }
+    }
+
+    pub fn hovered(&self) -> Option<GizmoHandle> {
+        self.hovered
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    fn size(camera: &Camera, origin: Point3<f32>) -> f32 {
+        (camera.eye - origin).magnitude() * SCREEN_SCALE
+    }
+
+    fn pick(&self, ray: &Ray, target: &Transform, camera: &Camera) -> Option<GizmoHandle> {
+        let origin = Point3::from_vec(target.position);
+        let size = Self::size(camera, origin);
+        let world_to_gizmo = gizmo_matrix(origin, size).invert()?;
+        let local_ray = ray.transformed(&world_to_gizmo);
+
+        let nearest_part = self.parts
+            .iter()
+            .filter(|part| part.mode == self.mode && !part.pick_mesh.indices.is_empty())
+            .filter_map(|part| {
+                ray_cast::intersect_mesh(local_ray, &part.pick_mesh.vertices, &part.pick_mesh.indices)
+                    .map(|hit| (part.handle, hit.t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(handle, _)| handle);
+
+        // 旋转模式下，没点中圆环但点在轨迹球内部时自由旋转
+        nearest_part.or_else(|| {
+            (self.mode == GizmoMode::Rotate && intersect_sphere(ray, origin, RING_RADIUS * size).is_some())
+                .then_some(GizmoHandle::Center)
+        })
+    }
+
+    pub fn update_hover(&mut self, ray: &Ray, target: &Transform, camera: &Camera) {
+        if self.drag.is_none() {
+            self.hovered = self.pick(ray, target, camera);
+        }
+    }
+
+    // 射线命中操纵柄时开始拖拽并返回 true
+    pub fn begin_drag(&mut self, ray: &Ray, target: &Transform, camera: &Camera) -> bool {
+        let Some(handle) = self.pick(ray, target, camera) else {
+            return false;
+        };
+        let origin = Point3::from_vec(target.position);
+        let size = Self::size(camera, origin);
+        let Some(start_point) = constraint_point(self.mode, handle, ray, origin, size, camera) else {
+            return false;
+        };
+
+        self.hovered = Some(handle);
+        self.drag = Some(DragState {
+            handle,
+            start: *target,
+            origin,
+            size,
+            start_point,
+        });
+        true
+    }
+
+    pub fn drag(&mut self, ray: &Ray, camera: &Camera, target: &mut Transform) {
+        let Some(drag) = self.drag.as_ref() else {
+            return;
+        };
+        let Some(point) = constraint_point(self.mode, drag.handle, ray, drag.origin, drag.size, camera) else {
+            return;
+        };
+        let start = drag.start;
+        let from = drag.start_point;
+
+        *target = match (self.mode, drag.handle) {
+            (GizmoMode::Translate, GizmoHandle::Axis(_)) | (GizmoMode::Translate, GizmoHandle::Center) => Transform {
+                position: start.position + (point - from),
+                ..start
+            },
+            (GizmoMode::Rotate, GizmoHandle::Axis(axis)) => {
+                let axis = axis_vector(axis);
+                let angle = axis.dot(from.cross(point)).atan2(from.dot(point));
+                Transform {
+                    rotation: Quaternion::from_axis_angle(axis, Rad(angle)) * start.rotation,
+                    ..start
+                }
+            }
+            (GizmoMode::Rotate, GizmoHandle::Center) => {
+                if from.magnitude2() < f32::EPSILON || point.magnitude2() < f32::EPSILON {
+                    return;
+                }
+                Transform {
+                    rotation: Quaternion::from_arc(from.normalize(), point.normalize(), None) * start.rotation,
+                    ..start
+                }
+            }
+            (GizmoMode::Scale, handle) => {
+                let from_length = from.magnitude();
+                if from_length < f32::EPSILON {
+                    return;
+                }
+                let factor = match handle {
+                    // 沿轴缩放时保留方向，允许翻转
+                    GizmoHandle::Axis(axis) => point.dot(axis_vector(axis)) / from.dot(axis_vector(axis)),
+                    GizmoHandle::Center => point.magnitude() / from_length,
+                };
+                if !factor.is_finite() {
+                    return;
+                }
+                let mut scale = start.scale;
+                match handle {
+                    GizmoHandle::Axis(axis) => scale[axis] *= factor,
+                    GizmoHandle::Center => scale *= factor,
+                }
+                Transform { scale, ..start }
+            }
+        };
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    // 在单独的通道中绘制到 view 上，保留已有内容
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        target: &Transform,
+        camera: &Camera,
+    ) {
+        let origin = Point3::from_vec(target.position);
+        let model = gizmo_matrix(origin, Self::size(camera, origin));
+
+        let mut vertices = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for part in self.parts.iter().filter(|part| part.mode == self.mode) {
+            let color = if self.hovered == Some(part.handle) { HOVER_COLOR } else { part.color };
+            let base = vertices.len() as u32;
+            vertices.extend(part.draw_mesh.vertices.iter().map(|vertex| GizmoVertex {
+                position: model.transform_point(Point3::from(vertex.position)).into(),
+                color,
+            }));
+            indices.extend(part.draw_mesh.indices.iter().map(|index| base + *index as u32));
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        let view_proj: [[f32; 4]; 4] = camera.build_view_projection_matrix().into();
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[view_proj]));
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(&indices));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gizmo Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+    }
+}
+
+fn gizmo_matrix(origin: Point3<f32>, size: f32) -> Matrix4<f32> {
+    Matrix4::from_translation(origin.to_vec()) * Matrix4::from_scale(size)
+}
+
+fn axis_vector(axis: usize) -> Vector3<f32> {
+    let mut v = Vector3::new(0.0, 0.0, 0.0);
+    v[axis] = 1.0;
+    v
+}
+
+// 图元都沿 +Y 建模，旋转到目标轴
+fn axis_rotation(axis: usize) -> Matrix4<f32> {
+    match axis {
+        0 => Matrix4::from_angle_z(Deg(-90.0)),
+        1 => Matrix4::identity(),
+        _ => Matrix4::from_angle_x(Deg(90.0)),
+    }
+}
+
+fn axis_color(axis: usize) -> [f32; 4] {
+    match axis {
+        0 => [0.9, 0.2, 0.2, 1.0],
+        1 => [0.2, 0.9, 0.2, 1.0],
+        _ => [0.2, 0.4, 0.9, 1.0],
+    }
+}
+
+fn transformed(mut mesh: MeshData, matrix: Matrix4<f32>) -> MeshData {
+    for vertex in mesh.vertices.iter_mut() {
+        vertex.position = matrix.transform_point(Point3::from(vertex.position)).into();
+    }
+    mesh
+}
+
+fn merged(meshes: impl IntoIterator<Item = MeshData>) -> MeshData {
+    let mut merged = MeshData::default();
+    for mesh in meshes {
+        let base = merged.vertices.len() as u16;
+        merged.vertices.extend(mesh.vertices);
+        merged.indices.extend(mesh.indices.iter().map(|index| base + index));
+    }
+    merged
+}
+
+// 杆从原点延伸到 0.8，末端为箭头或方块
+fn handle_mesh(shaft_radius: f32, tip: MeshData) -> MeshData {
+    merged([
+        transformed(primitives::cylinder(shaft_radius, 0.8, 8), Matrix4::from_translation(Vector3::new(0.0, 0.4, 0.0))),
+        transformed(tip, Matrix4::from_translation(Vector3::new(0.0, 0.9, 0.0))),
+    ])
+}
+
+fn build_parts() -> Vec<GizmoPart> {
+    let mut parts = Vec::new();
+    for axis in 0..3 {
+        let rotation = axis_rotation(axis);
+        let color = axis_color(axis);
+        let handle = GizmoHandle::Axis(axis);
+
+        parts.push(GizmoPart {
+            mode: GizmoMode::Translate,
+            handle,
+            color,
+            draw_mesh: transformed(handle_mesh(0.015, primitives::cone(0.06, 0.2, 12)), rotation),
+            pick_mesh: transformed(handle_mesh(0.06, primitives::cone(0.08, 0.2, 8)), rotation),
+        });
+        parts.push(GizmoPart {
+            mode: GizmoMode::Rotate,
+            handle,
+            color,
+            draw_mesh: transformed(primitives::torus(RING_RADIUS, 0.015, 48, 6), rotation),
+            pick_mesh: transformed(primitives::torus(RING_RADIUS, 0.06, 24, 6), rotation),
+        });
+        parts.push(GizmoPart {
+            mode: GizmoMode::Scale,
+            handle,
+            color,
+            draw_mesh: transformed(handle_mesh(0.015, primitives::cube(0.05)), rotation),
+            pick_mesh: transformed(handle_mesh(0.06, primitives::cube(0.08)), rotation),
+        });
+    }
+
+    let center_color = [0.9, 0.9, 0.9, 1.0];
+    for mode in [GizmoMode::Translate, GizmoMode::Scale] {
+        parts.push(GizmoPart {
+            mode,
+            handle: GizmoHandle::Center,
+            color: center_color,
+            draw_mesh: primitives::cube(0.06),
+            pick_mesh: primitives::cube(0.1),
+        });
+    }
+    parts
+}
+
+// 射线与球面最近的交点参数
+fn intersect_sphere(ray: &Ray, center: Point3<f32>, radius: f32) -> Option<f32> {
+    let direction = ray.direction.normalize();
+    let offset = ray.origin - center;
+    let b = offset.dot(direction);
+    let c = offset.magnitude2() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    let t = if t >= 0.0 { t } else { -b + discriminant.sqrt() };
+    (t >= 0.0).then(|| t / ray.direction.magnitude())
+}
+
+fn intersect_plane(ray: &Ray, point: Point3<f32>, normal: Vector3<f32>) -> Option<Point3<f32>> {
+    let denom = normal.dot(ray.direction);
+    if denom.abs() < 1e-6 {
+        return None;
+    }
+    let t = normal.dot(point - ray.origin) / denom;
+    (t >= 0.0).then(|| ray.at(t))
+}
+
+// 直线 origin + s * axis 上离射线最近的点对应的 s
+fn closest_on_axis(ray: &Ray, origin: Point3<f32>, axis: Vector3<f32>) -> Option<f32> {
+    let d = ray.direction;
+    let w = origin - ray.origin;
+    let b = axis.dot(d);
+    let c = d.dot(d);
+    let denom = c - b * b;
+    // 射线几乎与轴平行，无法确定位置
+    if denom.abs() < 1e-6 * c {
+        return None;
+    }
+    Some((b * d.dot(w) - c * axis.dot(w)) / denom)
+}
+
+// 根据操纵柄类型求约束上的点，返回相对 origin 的偏移
+fn constraint_point(
+    mode: GizmoMode,
+    handle: GizmoHandle,
+    ray: &Ray,
+    origin: Point3<f32>,
+    size: f32,
+    camera: &Camera,
+) -> Option<Vector3<f32>> {
+    let view_normal = (camera.target - camera.eye).normalize();
+    match (mode, handle) {
+        // 沿轴平移或缩放：直线约束
+        (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Axis(axis)) => {
+            let axis = axis_vector(axis);
+            closest_on_axis(ray, origin, axis).map(|s| axis * s)
+        }
+        // 中心方块：在与视线垂直的平面内移动
+        (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Center) => {
+            intersect_plane(ray, origin, view_normal).map(|p| p - origin)
+        }
+        // 绕轴旋转：垂直于轴的平面约束
+        (GizmoMode::Rotate, GizmoHandle::Axis(axis)) => {
+            intersect_plane(ray, origin, axis_vector(axis)).map(|p| p - origin)
+        }
+        // 轨迹球：球面约束，射线未命中时使用视平面上的点
+        (GizmoMode::Rotate, GizmoHandle::Center) => match intersect_sphere(ray, origin, RING_RADIUS * size) {
+            Some(t) => Some(ray.at(t) - origin),
+            None => intersect_plane(ray, origin, view_normal).map(|p| p - origin),
+        },
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Deref;
+use std::sync::{ Arc, Mutex };
+
+use wgpu::util::DeviceExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationKind {
+    Buffer(wgpu::BufferUsages),
+    Texture(wgpu::TextureUsages),
+}
+
+#[derive(Debug, Clone)]
+pub struct AllocationRecord {
+    pub label: String,
+    pub kind: AllocationKind,
+    // 字节数；纹理按格式与 mip 链估算，驱动实际分配可能因对齐与压缩而不同
+    pub size: u64,
+}
+
+#[derive(Default)]
+struct Allocations {
+    next_id: u64,
+    records: HashMap<u64, AllocationRecord>,
+}
+
+// 记录经由它创建的缓冲区与纹理，包装对象被 drop 时移除记录；克隆得到的句柄共享同一份记录
+#[derive(Clone, Default)]
+pub struct GpuMemoryTracker {
+    allocations: Arc<Mutex<Allocations>>,
+}
+
+impl GpuMemoryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, record: AllocationRecord) -> u64 {
+        let mut allocations = self.allocations.lock().unwrap();
+        let id = allocations.next_id;
+        allocations.next_id += 1;
+        allocations.records.insert(id, record);
+        id
+    }
+
+    fn remove(&self, id: u64) {
+        self.allocations.lock().unwrap().records.remove(&id);
+    }
+
+    pub fn create_buffer(&self, device: &wgpu::Device, desc: &wgpu::BufferDescriptor) -> TrackedBuffer {
+        let buffer = device.create_buffer(desc);
+        self.track_buffer(buffer, desc.label)
+    }
+
+    pub fn create_buffer_init(&self, device: &wgpu::Device, desc: &wgpu::util::BufferInitDescriptor) -> TrackedBuffer {
+        let buffer = device.create_buffer_init(desc);
+        self.track_buffer(buffer, desc.label)
+    }
+
+    fn track_buffer(&self, buffer: wgpu::Buffer, label: Option<&str>) -> TrackedBuffer {
+        let id = self.insert(AllocationRecord {
+            label: label.unwrap_or("<unlabeled>").to_string(),
+            kind: AllocationKind::Buffer(buffer.usage()),
+            size: buffer.size(),
+        });
+        TrackedBuffer { buffer, id, tracker: self.clone() }
+    }
+
+    pub fn create_texture(&self, device: &wgpu::Device, desc: &wgpu::TextureDescriptor) -> TrackedTexture {
+        let texture = device.create_texture(desc);
+        let id = self.insert(AllocationRecord {
+            label: desc.label.unwrap_or("<unlabeled>").to_string(),
+            kind: AllocationKind::Texture(desc.usage),
+            size: texture_size(desc),
+        });
+        TrackedTexture { texture, id, tracker: self.clone() }
+    }
+
+    pub fn allocations(&self) -> Vec<AllocationRecord> {
+        self.allocations.lock().unwrap().records.values().cloned().collect()
+    }
+
+    pub fn total_buffer_bytes(&self) -> u64 {
+        self.total(|kind| matches!(kind, AllocationKind::Buffer(_)))
+    }
+
+    pub fn total_texture_bytes(&self) -> u64 {
+        self.total(|kind| matches!(kind, AllocationKind::Texture(_)))
+    }
+
+    fn total(&self, filter: impl Fn(&AllocationKind) -> bool) -> u64 {
+        self.allocations
+            .lock()
+            .unwrap()
+            .records
+            .values()
+            .filter(|record| filter(&record.kind))
+            .map(|record| record.size)
+            .sum()
+    }
+
+    // 按大小从大到小列出所有存活的分配
+    pub fn report(&self) -> String {
+        let mut records = self.allocations();
+        records.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.label.cmp(&b.label)));
+
+        let mut report = format!(
+            "GPU memory: {} in buffers, {} in textures, {} allocations",
+            format_bytes(self.total_buffer_bytes()),
+            format_bytes(self.total_texture_bytes()),
+            records.len(),
+        );
+        for record in &records {
+            let (kind, usage) = match record.kind {
+                AllocationKind::Buffer(usage) => ("buffer", format!("{usage:?}")),
+                AllocationKind::Texture(usage) => ("texture", format!("{usage:?}")),
+            };
+            let _ = write!(report, "\n  {:>10}  {kind:<7}  {}  ({usage})", format_bytes(record.size), record.label);
+        }
+        report
+    }
+
+    pub fn print_report(&self) {
+        tracing::info!("{}", self.report());
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{bytes} B") } else { format!("{value:.1} {}", UNITS[unit]) }
+}
+
+// 按格式与 mip 链估算纹理占用的字节数
+pub fn estimate_texture_bytes(texture: &wgpu::Texture) -> u64 {
+    texture_size(&wgpu::TextureDescriptor {
+        label: None,
+        size: texture.size(),
+        mip_level_count: texture.mip_level_count(),
+        sample_count: texture.sample_count(),
+        dimension: texture.dimension(),
+        format: texture.format(),
+        usage: texture.usage(),
+        view_formats: &[],
+    })
+}
+
+// 各级 mip 按块计算后求和，再乘以采样数
+fn texture_size(desc: &wgpu::TextureDescriptor) -> u64 {
+    let (block_width, block_height) = desc.format.block_dimensions();
+    // 深度格式（如 Depth24Plus）的实际大小由驱动决定，按每像素 4 字节估算
+    let block_size = desc.format.block_size(None).unwrap_or(4) as u64;
+    let is_3d = desc.dimension == wgpu::TextureDimension::D3;
+
+    (0..desc.mip_level_count)
+        .map(|level| {
+            let size = desc.size.mip_level_size(level, desc.dimension);
+            let layers = if is_3d { size.depth_or_array_layers } else { desc.size.depth_or_array_layers };
+            let blocks_x = size.width.div_ceil(block_width) as u64;
+            let blocks_y = size.height.div_ceil(block_height) as u64;
+            blocks_x * blocks_y * layers as u64 * block_size
+        })
+        .sum::<u64>()
+        * desc.sample_count as u64
+}
+
+pub struct TrackedBuffer {
+    buffer: wgpu::Buffer,
+    id: u64,
+    tracker: GpuMemoryTracker,
+}
+
+impl Deref for TrackedBuffer {
+    type Target = wgpu::Buffer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl Drop for TrackedBuffer {
+    fn drop(&mut self) {
+        self.tracker.remove(self.id);
+    }
+}
+
+pub struct TrackedTexture {
+    texture: wgpu::Texture,
+    id: u64,
+    tracker: GpuMemoryTracker,
+}
+
+impl Deref for TrackedTexture {
+    type Target = wgpu::Texture;
+
+    fn deref(&self) -> &Self::Target {
+        &self.texture
+    }
+}
+
+impl Drop for TrackedTexture {
+    fn drop(&mut self) {
+        self.tracker.remove(self.id);
+    }
+}
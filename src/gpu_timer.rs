@@ -0,0 +1,179 @@
+use std::sync::atomic::{ AtomicU8, Ordering };
+use std::sync::Arc;
+
+// 每个时间戳查询结果占 8 字节
+const TIMESTAMP_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+const MAP_PENDING: u8 = 0;
+const MAP_READY: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+struct Scope {
+    label: String,
+    closed: bool,
+}
+
+enum Readback {
+    Idle,
+    // 解析与复制命令已录制，等待随本帧提交
+    Copied(Vec<Scope>),
+    Mapping(Vec<Scope>, Arc<AtomicU8>),
+}
+
+// 用时间戳查询测量各通道的 GPU 耗时：第 i 个区间的起止时间戳写在查询 2i 与 2i + 1
+// 设备未启用 TIMESTAMP_QUERY 时所有方法都是空操作
+pub struct GpuTimer {
+    query_set: Option<wgpu::QuerySet>,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    max_scopes: u32,
+    scopes: Vec<Scope>,
+    readback: Readback,
+    // 区间数超过上限时只警告一次
+    overflowed: bool,
+}
+
+impl GpuTimer {
+    pub fn new(device: &wgpu::Device, max_scopes: u32) -> Self {
+        let max_scopes = max_scopes.max(1);
+        let query_count = max_scopes * 2;
+        let query_set = device.features().contains(wgpu::Features::TIMESTAMP_QUERY).then(|| {
+            device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("GPU Timer Query Set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: query_count,
+            })
+        });
+
+        let size = query_count as wgpu::BufferAddress * TIMESTAMP_SIZE;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            max_scopes,
+            scopes: Vec::new(),
+            readback: Readback::Idle,
+            overflowed: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.query_set.is_some()
+    }
+
+    pub fn begin(&mut self, encoder: &mut wgpu::CommandEncoder, label: &str) {
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        if self.scopes.len() as u32 >= self.max_scopes {
+            if !self.overflowed {
+                tracing::warn!("gpu timer: more than {} scopes in one frame, '{label}' is not timed", self.max_scopes);
+                self.overflowed = true;
+            }
+            return;
+        }
+        encoder.write_timestamp(query_set, self.scopes.len() as u32 * 2);
+        self.scopes.push(Scope { label: label.to_string(), closed: false });
+    }
+
+    // 结束最近一个同名且未结束的区间
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder, label: &str) {
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        let Some(index) = self.scopes.iter().rposition(|scope| !scope.closed && scope.label == label) else {
+            // 超出上限而没有计时的区间在结束时不再警告
+            if !self.overflowed {
+                tracing::warn!("gpu timer: end called for '{label}' without a matching begin");
+            }
+            return;
+        };
+        encoder.write_timestamp(query_set, index as u32 * 2 + 1);
+        self.scopes[index].closed = true;
+    }
+
+    // 在提交前调用：把本帧的查询结果解析到回读缓冲区
+    // 上一次回读完成之前不会再复制，本帧的区间被丢弃，结果因此会延迟若干帧
+    pub fn resolve_queries(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let scopes = std::mem::take(&mut self.scopes);
+        let Some(query_set) = &self.query_set else {
+            return;
+        };
+        if scopes.is_empty() || !matches!(self.readback, Readback::Idle) {
+            return;
+        }
+
+        let query_count = scopes.len() as u32 * 2;
+        encoder.resolve_query_set(query_set, 0..query_count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            query_count as wgpu::BufferAddress * TIMESTAMP_SIZE,
+        );
+        self.readback = Readback::Copied(scopes);
+    }
+
+    // 在提交之后调用：发起映射，或在映射完成时返回 (标签, 纳秒) 列表，顺序与 begin 调用顺序一致
+    pub fn resolve(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Vec<(String, f64)>> {
+        match std::mem::replace(&mut self.readback, Readback::Idle) {
+            Readback::Idle => None,
+            Readback::Copied(scopes) => {
+                let status = Arc::new(AtomicU8::new(MAP_PENDING));
+                let callback_status = status.clone();
+                self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                    callback_status.store(if result.is_ok() { MAP_READY } else { MAP_FAILED }, Ordering::Release);
+                });
+                self.readback = Readback::Mapping(scopes, status);
+                None
+            }
+            Readback::Mapping(scopes, status) => {
+                device.poll(wgpu::Maintain::Poll);
+                match status.load(Ordering::Acquire) {
+                    MAP_PENDING => {
+                        self.readback = Readback::Mapping(scopes, status);
+                        return None;
+                    }
+                    MAP_FAILED => return None,
+                    _ => {}
+                }
+
+                let period = queue.get_timestamp_period() as f64;
+                let timings = {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    let timestamps: &[u64] = bytemuck::cast_slice(&data[..scopes.len() * 2 * TIMESTAMP_SIZE as usize]);
+                    scopes
+                        .into_iter()
+                        .zip(timestamps.chunks_exact(2))
+                        .filter(|(scope, _)| scope.closed)
+                        .map(|(scope, ticks)| (scope.label, ticks[1].saturating_sub(ticks[0]) as f64 * period))
+                        .collect()
+                };
+                self.readback_buffer.unmap();
+                Some(timings)
+            }
+        }
+    }
+}
+
+// 按耗时从大到小取前 count 个
+pub fn slowest(timings: &[(String, f64)], count: usize) -> Vec<(String, f64)> {
+    let mut sorted = timings.to_vec();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    sorted.truncate(count);
+    sorted
+}
@@ -0,0 +1,441 @@
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+
+pub const HEIGHT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+pub const DENSITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+const WORKGROUP_SIZE: u32 = 8;
+const WIND_TEXTURE_SIZE: u32 = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GrassConfig {
+    // 超过这个距离的草叶在计算着色器中被剔除
+    pub max_distance: f32,
+    pub blade_height: f32,
+    pub blade_width: f32,
+    // 叶尖的最大偏移，以叶片高度为单位
+    pub wind_strength: f32,
+    pub wind_scale: f32,
+    pub wind_speed: f32,
+}
+
+impl Default for GrassConfig {
+    fn default() -> Self {
+        Self {
+            max_distance: 50.0,
+            blade_height: 0.5,
+            blade_width: 0.05,
+            wind_strength: 0.3,
+            wind_scale: 0.05,
+            wind_speed: 0.1,
+        }
+    }
+}
+
+// 一块地形：xz 平面上 origin 到 origin + size 的矩形
+pub struct TerrainPatch<'a> {
+    pub origin: [f32; 2],
+    pub size: [f32; 2],
+    // R32Float，乘以 height_scale 得到世界高度
+    pub heightmap: &'a wgpu::Texture,
+    pub height_scale: f32,
+    // R8Unorm，每格长出草的概率
+    pub density_map: &'a wgpu::Texture,
+    // 每边的格子数，草叶数上限为 resolution²
+    pub resolution: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GrassBlade {
+    pub base_pos: [f32; 3],
+    pub height: f32,
+    pub dir: [f32; 3],
+    pub color_variation: f32,
+    pub facing: f32,
+    pub _padding: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GrassUniform {
+    camera_position: [f32; 3],
+    time: f32,
+    max_distance: f32,
+    blade_height: f32,
+    blade_width: f32,
+    wind_strength: f32,
+    wind_scale: f32,
+    wind_speed: f32,
+    _padding: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PatchUniform {
+    origin: [f32; 2],
+    size: [f32; 2],
+    height_scale: f32,
+    resolution: u32,
+    _padding: [u32; 2],
+}
+
+struct GrassPatch {
+    resolution: u32,
+    generate_bind_group: wgpu::BindGroup,
+    render_bind_group: wgpu::BindGroup,
+    // 计算着色器累加 vertex_count，每帧重置
+    draw_args: wgpu::Buffer,
+}
+
+// 每帧为每块地形运行一次计算着色器，按密度图与距离生成草叶写入存储缓冲区，
+// 再用间接绘制按顶点序号展开（每根草叶 6 个顶点），草叶本身不占用网格
+pub struct GrassRenderer {
+    pub config: GrassConfig,
+    generate_pipeline: wgpu::ComputePipeline,
+    generate_bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    render_bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    wind_view: wgpu::TextureView,
+    wind_sampler: wgpu::Sampler,
+    patches: Vec<GrassPatch>,
+}
+
+impl GrassRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        config: GrassConfig,
+    ) -> Self {
+        let generate_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Grass Generate Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("grass_generate.wgsl").into()),
+        });
+        let render_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Grass Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("grass.wgsl").into()),
+        });
+
+        let uniform_entry = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let texture_entry = |binding, visibility, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let storage_entry = |binding, visibility, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let generate_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("grass_generate_bind_group_layout"),
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::COMPUTE),
+                uniform_entry(1, wgpu::ShaderStages::COMPUTE),
+                texture_entry(2, wgpu::ShaderStages::COMPUTE, false),
+                texture_entry(3, wgpu::ShaderStages::COMPUTE, false),
+                storage_entry(4, wgpu::ShaderStages::COMPUTE, false),
+                storage_entry(5, wgpu::ShaderStages::COMPUTE, false),
+            ],
+        });
+        let render_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("grass_bind_group_layout"),
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::VERTEX),
+                storage_entry(1, wgpu::ShaderStages::VERTEX, true),
+                texture_entry(2, wgpu::ShaderStages::VERTEX, true),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let generate_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grass Generate Pipeline Layout"),
+            bind_group_layouts: &[&generate_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let generate_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Grass Generate Pipeline"),
+            layout: Some(&generate_layout),
+            module: &generate_shader,
+            entry_point: "cs_main",
+        });
+
+        let render_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grass Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &render_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Grass Pipeline"),
+            layout: Some(&render_layout),
+            vertex: wgpu::VertexState {
+                module: &render_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &render_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                // 叶片两面都可见
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Grass Uniform Buffer"),
+            size: std::mem::size_of::<GrassUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let wind_view = create_wind_texture(device, queue).create_view(&wgpu::TextureViewDescriptor::default());
+        // 线性过滤把低分辨率的白噪声插值成平滑的值噪声
+        let wind_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Grass Wind Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            config,
+            generate_pipeline,
+            generate_bind_group_layout,
+            render_pipeline,
+            render_bind_group_layout,
+            uniform_buffer,
+            wind_view,
+            wind_sampler,
+            patches: Vec::new(),
+        }
+    }
+
+    pub fn add_patch(&mut self, device: &wgpu::Device, patch: &TerrainPatch) -> usize {
+        let resolution = patch.resolution.max(1);
+        let patch_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grass Patch Buffer"),
+            contents: bytemuck::cast_slice(&[PatchUniform {
+                origin: patch.origin,
+                size: patch.size,
+                height_scale: patch.height_scale,
+                resolution,
+                _padding: [0; 2],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let blade_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Grass Blade Buffer"),
+            size: (resolution * resolution) as wgpu::BufferAddress * std::mem::size_of::<GrassBlade>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let draw_args = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grass Draw Args Buffer"),
+            contents: wgpu::util::DrawIndirect { vertex_count: 0, instance_count: 1, base_vertex: 0, base_instance: 0 }.as_bytes(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let height_view = patch.heightmap.create_view(&wgpu::TextureViewDescriptor::default());
+        let density_view = patch.density_map.create_view(&wgpu::TextureViewDescriptor::default());
+        let generate_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("grass_generate_bind_group"),
+            layout: &self.generate_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: patch_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&height_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&density_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: blade_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: draw_args.as_entire_binding(),
+                },
+            ],
+        });
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("grass_bind_group"),
+            layout: &self.render_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: blade_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.wind_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.wind_sampler),
+                },
+            ],
+        });
+
+        self.patches.push(GrassPatch {
+            resolution,
+            generate_bind_group,
+            render_bind_group,
+            draw_args,
+        });
+        self.patches.len() - 1
+    }
+
+    pub fn patch_count(&self) -> usize {
+        self.patches.len()
+    }
+
+    // 写入相机与时间，并把每块地形的草叶计数清零；generate 须在同一次提交中录制
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, time: f32) {
+        let uniform = GrassUniform {
+            camera_position: camera.eye.into(),
+            time,
+            max_distance: self.config.max_distance,
+            blade_height: self.config.blade_height,
+            blade_width: self.config.blade_width,
+            wind_strength: self.config.wind_strength,
+            wind_scale: self.config.wind_scale,
+            wind_speed: self.config.wind_speed,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+        let reset = wgpu::util::DrawIndirect { vertex_count: 0, instance_count: 1, base_vertex: 0, base_instance: 0 };
+        for patch in &self.patches {
+            queue.write_buffer(&patch.draw_args, 0, reset.as_bytes());
+        }
+    }
+
+    pub fn generate(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Grass Generate Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.generate_pipeline);
+        for patch in &self.patches {
+            let workgroups = patch.resolution.div_ceil(WORKGROUP_SIZE);
+            compute_pass.set_bind_group(0, &patch.generate_bind_group, &[]);
+            compute_pass.dispatch_workgroups(workgroups, workgroups, 1);
+        }
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, camera_bind_group: &'a wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        for patch in &self.patches {
+            render_pass.set_bind_group(1, &patch.render_bind_group, &[]);
+            render_pass.draw_indirect(&patch.draw_args, 0);
+        }
+    }
+}
+
+// 两个通道的低分辨率白噪声，作为 xz 方向的风力
+fn create_wind_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+    let mut state = 0x9e37_79b9u32;
+    let data = (0..WIND_TEXTURE_SIZE * WIND_TEXTURE_SIZE * 4)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect::<Vec<_>>();
+
+    let extent = wgpu::Extent3d {
+        width: WIND_TEXTURE_SIZE,
+        height: WIND_TEXTURE_SIZE,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Grass Wind Texture"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * WIND_TEXTURE_SIZE),
+            rows_per_image: Some(WIND_TEXTURE_SIZE),
+        },
+        extent,
+    );
+    texture
+}
@@ -0,0 +1,84 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct GrassUniform {
+    camera_position: vec3f,
+    time: f32,
+    max_distance: f32,
+    blade_height: f32,
+    blade_width: f32,
+    wind_strength: f32,
+    // 风噪声纹理每世界单位重复的次数
+    wind_scale: f32,
+    wind_speed: f32,
+};
+
+struct GrassBlade {
+    base_pos: vec3f,
+    height: f32,
+    dir: vec3f,
+    color_variation: f32,
+    facing: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> grass: GrassUniform;
+@group(1) @binding(1)
+var<storage, read> blades: array<GrassBlade>;
+@group(1) @binding(2)
+var t_wind: texture_2d<f32>;
+@group(1) @binding(3)
+var s_wind: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec3f,
+};
+
+// 两个三角形组成的叶片，x 为横向 -0.5..0.5，y 为从根到尖 0..1
+var<private> CORNERS: array<vec2f, 6> = array<vec2f, 6>(
+    vec2f(-0.5, 0.0),
+    vec2f(0.5, 0.0),
+    vec2f(0.5, 1.0),
+    vec2f(-0.5, 0.0),
+    vec2f(0.5, 1.0),
+    vec2f(-0.5, 1.0),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let blade = blades[vertex_index / 6u];
+    let corner = CORNERS[vertex_index % 6u];
+
+    // 叶片宽度方向：水平朝向向量投影到与生长方向垂直的平面
+    let facing = vec3f(cos(blade.facing), 0.0, sin(blade.facing));
+    let side = normalize(facing - blade.dir * dot(facing, blade.dir) + vec3f(1e-4, 0.0, 0.0));
+
+    // 风：随时间平移的噪声纹理，越靠近叶尖偏移越大
+    let wind_uv = blade.base_pos.xz * grass.wind_scale + vec2f(grass.time * grass.wind_speed, 0.0);
+    let wind = (textureSampleLevel(t_wind, s_wind, wind_uv, 0.0).rg * 2.0 - 1.0) * grass.wind_strength;
+    let bend = corner.y * corner.y;
+
+    let width = grass.blade_width * (1.0 - corner.y * 0.7);
+    let position = blade.base_pos
+        + side * corner.x * width
+        + blade.dir * corner.y * blade.height
+        + vec3f(wind.x, 0.0, wind.y) * bend * blade.height;
+
+    let root = vec3f(0.05, 0.2, 0.03);
+    let tip = mix(vec3f(0.3, 0.6, 0.1), vec3f(0.6, 0.65, 0.2), blade.color_variation);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4f(position, 1.0);
+    out.color = mix(root, tip, corner.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return vec4f(in.color, 1.0);
+}
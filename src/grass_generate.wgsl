@@ -0,0 +1,118 @@
+struct GrassUniform {
+    camera_position: vec3f,
+    time: f32,
+    max_distance: f32,
+    blade_height: f32,
+    blade_width: f32,
+    wind_strength: f32,
+    wind_scale: f32,
+    wind_speed: f32,
+};
+
+// 地形块覆盖 xz 平面上 origin 到 origin + size 的矩形
+struct PatchUniform {
+    origin: vec2f,
+    size: vec2f,
+    height_scale: f32,
+    // 每边的格子数，每格最多一根草
+    resolution: u32,
+};
+
+struct GrassBlade {
+    base_pos: vec3f,
+    height: f32,
+    // 生长方向（单位向量）
+    dir: vec3f,
+    color_variation: f32,
+    // 叶片朝向，绕生长方向的角度
+    facing: f32,
+};
+
+// 与 wgpu::util::DrawIndirect 布局一致，vertex_count 同时是草叶计数 × 6
+struct DrawArgs {
+    vertex_count: atomic<u32>,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> grass: GrassUniform;
+@group(0) @binding(1)
+var<uniform> terrain: PatchUniform;
+@group(0) @binding(2)
+var t_height: texture_2d<f32>;
+@group(0) @binding(3)
+var t_density: texture_2d<f32>;
+@group(0) @binding(4)
+var<storage, read_write> blades: array<GrassBlade>;
+@group(0) @binding(5)
+var<storage, read_write> args: DrawArgs;
+
+fn hash(seed: u32) -> u32 {
+    var x = seed * 747796405u + 2891336453u;
+    x = ((x >> ((x >> 28u) + 4u)) ^ x) * 277803737u;
+    return (x >> 22u) ^ x;
+}
+
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed)) / 4294967296.0;
+}
+
+fn load_clamped(texture: texture_2d<f32>, texel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(texture));
+    return textureLoad(texture, clamp(texel, vec2<i32>(0), size - 1), 0).r;
+}
+
+fn height_at(uv: vec2f) -> f32 {
+    let size = vec2f(textureDimensions(t_height));
+    return load_clamped(t_height, vec2<i32>(uv * size)) * terrain.height_scale;
+}
+
+// 由高度图的中心差分得到地形法线
+fn normal_at(uv: vec2f) -> vec3f {
+    let size = vec2f(textureDimensions(t_height));
+    let texel = vec2<i32>(uv * size);
+    let left = load_clamped(t_height, texel - vec2<i32>(1, 0));
+    let right = load_clamped(t_height, texel + vec2<i32>(1, 0));
+    let down = load_clamped(t_height, texel - vec2<i32>(0, 1));
+    let up = load_clamped(t_height, texel + vec2<i32>(0, 1));
+    let step = terrain.size / size * 2.0;
+    let dx = (right - left) * terrain.height_scale / step.x;
+    let dz = (up - down) * terrain.height_scale / step.y;
+    return normalize(vec3f(-dx, 1.0, -dz));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= terrain.resolution || id.y >= terrain.resolution {
+        return;
+    }
+    let seed = hash(id.y * terrain.resolution + id.x);
+
+    // 格子内随机抖动，避免整齐的网格
+    let cell = (vec2f(id.xy) + vec2f(random(seed), random(seed + 1u))) / f32(terrain.resolution);
+    let density = load_clamped(t_density, vec2<i32>(cell * vec2f(textureDimensions(t_density))));
+    if random(seed + 2u) >= density {
+        return;
+    }
+
+    let base_pos = vec3f(terrain.origin.x + cell.x * terrain.size.x, height_at(cell), terrain.origin.y + cell.y * terrain.size.y);
+    if distance(base_pos, grass.camera_position) > grass.max_distance {
+        return;
+    }
+
+    // 生长方向介于地形法线与竖直方向之间，再加一点随机倾斜
+    let lean = (vec2f(random(seed + 3u), random(seed + 4u)) - 0.5) * 0.4;
+    let dir = normalize(mix(normal_at(cell), vec3f(0.0, 1.0, 0.0), 0.5) + vec3f(lean.x, 0.0, lean.y));
+
+    var blade: GrassBlade;
+    blade.base_pos = base_pos;
+    blade.height = grass.blade_height * mix(0.6, 1.2, random(seed + 5u));
+    blade.dir = dir;
+    blade.color_variation = random(seed + 6u);
+    blade.facing = random(seed + 7u) * 6.2831853;
+
+    let index = atomicAdd(&args.vertex_count, 6u) / 6u;
+    blades[index] = blade;
+}
@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+use wgpu::util::DrawIndexedIndirect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstanceSlot(u32);
+
+impl InstanceSlot {
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+// 按槽位管理的实例缓冲区：删除实例只回收槽位，每帧只上传改动过的槽位
+// 绘制时把连续的活动槽位合并成一条间接绘制命令，空闲槽位被跳过
+pub struct DynamicInstanceBuffer<T: bytemuck::Pod> {
+    label: String,
+    buffer: wgpu::Buffer,
+    indirect_buffer: wgpu::Buffer,
+    capacity: u32,
+    data: Vec<T>,
+    free: Vec<InstanceSlot>,
+    // 活动槽位，flush 时排序
+    active: Vec<InstanceSlot>,
+    dirty: Vec<InstanceSlot>,
+    active_changed: bool,
+    resized: bool,
+    runs: Vec<Range<u32>>,
+    index_count: u32,
+    features: wgpu::Features,
+}
+
+impl<T: bytemuck::Pod> DynamicInstanceBuffer<T> {
+    pub fn new(device: &wgpu::Device, capacity: u32, label: &str) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            label: label.to_string(),
+            buffer: Self::create_buffer(device, capacity, label),
+            indirect_buffer: Self::create_indirect_buffer(device, capacity, label),
+            capacity,
+            data: Vec::with_capacity(capacity as usize),
+            free: Vec::new(),
+            active: Vec::new(),
+            dirty: Vec::new(),
+            active_changed: false,
+            resized: false,
+            runs: Vec::new(),
+            index_count: 0,
+            features: device.features(),
+        }
+    }
+
+    pub fn insert(&mut self, instance: T) -> InstanceSlot {
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.data[slot.0 as usize] = instance;
+                slot
+            }
+            None => {
+                self.data.push(instance);
+                InstanceSlot(self.data.len() as u32 - 1)
+            }
+        };
+        self.active.push(slot);
+        self.dirty.push(slot);
+        self.active_changed = true;
+        slot
+    }
+
+    pub fn update(&mut self, slot: InstanceSlot, instance: T) {
+        self.data[slot.0 as usize] = instance;
+        self.dirty.push(slot);
+    }
+
+    pub fn get(&self, slot: InstanceSlot) -> Option<&T> {
+        self.contains(slot).then(|| &self.data[slot.0 as usize])
+    }
+
+    pub fn remove(&mut self, slot: InstanceSlot) {
+        // 重复删除同一个槽位会让空闲列表出现重复项
+        if let Some(position) = self.active.iter().position(|&active| active == slot) {
+            self.active.swap_remove(position);
+            self.free.push(slot);
+            self.active_changed = true;
+        }
+    }
+
+    pub fn contains(&self, slot: InstanceSlot) -> bool {
+        self.active.contains(&slot)
+    }
+
+    pub fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // 每帧结束时调用：整理活动槽位，上传脏槽位，并重建间接绘制命令
+    pub fn flush(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, index_count: u32) {
+        if self.data.len() as u32 > self.capacity {
+            self.capacity = (self.data.len() as u32).next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.capacity, &self.label);
+            self.indirect_buffer = Self::create_indirect_buffer(device, self.capacity, &self.label);
+            self.resized = true;
+        }
+
+        if self.resized {
+            // 新缓冲区没有旧数据，所有活动槽位都要重新上传
+            self.dirty.clear();
+            self.dirty.extend_from_slice(&self.active);
+        }
+        self.upload_dirty(queue);
+
+        if self.active_changed || self.resized || self.index_count != index_count {
+            self.active.sort_unstable();
+            self.rebuild_runs();
+            self.index_count = index_count;
+            let commands: Vec<u8> = self
+                .runs
+                .iter()
+                .flat_map(|run| {
+                    DrawIndexedIndirect {
+                        vertex_count: index_count,
+                        instance_count: run.end - run.start,
+                        base_index: 0,
+                        vertex_offset: 0,
+                        base_instance: run.start,
+                    }
+                    .as_bytes()
+                    .to_vec()
+                })
+                .collect();
+            if !commands.is_empty() {
+                queue.write_buffer(&self.indirect_buffer, 0, &commands);
+            }
+        }
+
+        self.active_changed = false;
+        self.resized = false;
+    }
+
+    // 调用前需要绑定好网格的顶点/索引缓冲区，实例缓冲区绑定到 slot
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, slot: u32) {
+        if self.runs.is_empty() {
+            return;
+        }
+        render_pass.set_vertex_buffer(slot, self.buffer.slice(..));
+
+        // 间接命令里的 base_instance 非零时需要 INDIRECT_FIRST_INSTANCE，否则退回直接绘制
+        if !self.features.contains(wgpu::Features::INDIRECT_FIRST_INSTANCE) {
+            for run in &self.runs {
+                render_pass.draw_indexed(0..self.index_count, 0, run.clone());
+            }
+        } else if self.features.contains(wgpu::Features::MULTI_DRAW_INDIRECT) {
+            render_pass.multi_draw_indexed_indirect(&self.indirect_buffer, 0, self.runs.len() as u32);
+        } else {
+            for i in 0..self.runs.len() {
+                let offset = (i * std::mem::size_of::<DrawIndexedIndirect>()) as wgpu::BufferAddress;
+                render_pass.draw_indexed_indirect(&self.indirect_buffer, offset);
+            }
+        }
+    }
+
+    pub fn draw_call_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    fn upload_dirty(&mut self, queue: &wgpu::Queue) {
+        if self.dirty.is_empty() {
+            return;
+        }
+        self.dirty.sort_unstable();
+        self.dirty.dedup();
+
+        // 相邻的脏槽位合并成一次 write_buffer
+        let stride = std::mem::size_of::<T>() as wgpu::BufferAddress;
+        let mut start = 0;
+        while start < self.dirty.len() {
+            let mut end = start + 1;
+            while end < self.dirty.len() && self.dirty[end].0 == self.dirty[end - 1].0 + 1 {
+                end += 1;
+            }
+            let first = self.dirty[start].0 as usize;
+            let last = self.dirty[end - 1].0 as usize;
+            queue.write_buffer(&self.buffer, first as wgpu::BufferAddress * stride, bytemuck::cast_slice(&self.data[first..=last]));
+            start = end;
+        }
+        self.dirty.clear();
+    }
+
+    fn rebuild_runs(&mut self) {
+        self.runs.clear();
+        for slot in &self.active {
+            match self.runs.last_mut() {
+                Some(run) if run.end == slot.0 => run.end += 1,
+                _ => self.runs.push(slot.0..slot.0 + 1),
+            }
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: u32, label: &str) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: capacity as wgpu::BufferAddress * std::mem::size_of::<T>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    // 最坏情况下活动槽位两两不相邻，命令数为容量的一半，这里按容量分配
+    fn create_indirect_buffer(device: &wgpu::Device, capacity: u32, label: &str) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label} Indirect")),
+            size: capacity as wgpu::BufferAddress * std::mem::size_of::<DrawIndexedIndirect>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
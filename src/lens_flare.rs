@@ -0,0 +1,436 @@
+use std::sync::atomic::{ AtomicU8, Ordering };
+use std::sync::Arc;
+
+use crate::texture::Texture;
+
+// 太阳圆盘上用于遮挡查询的点数，覆盖率 = 通过深度测试的采样数 / SUN_SAMPLE_COUNT
+pub const SUN_SAMPLE_COUNT: u32 = 16;
+pub const MAX_FLARE_ELEMENTS: usize = 16;
+const QUERY_RESULT_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+const MAP_PENDING: u8 = 0;
+const MAP_READY: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+// 沿屏幕中心到太阳的轴排列的一个光斑：offset_along_axis 为 1 时位于太阳处，0 时位于屏幕中心，
+// 负数落在中心另一侧；size 为半边长占屏幕高度的比例
+#[derive(Clone)]
+pub struct FlareElement {
+    pub texture: Arc<Texture>,
+    pub offset_along_axis: f32,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LensFlareUniform {
+    // 太阳的 NDC 坐标
+    sun_position: [f32; 2],
+    // 太阳圆盘的 NDC 半径，x 已按宽高比换算
+    sun_radius: [f32; 2],
+    aspect: f32,
+    // 遮挡查询得到的覆盖率，光斑的 alpha 乘以它
+    fade: f32,
+    sample_count: u32,
+    _padding: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FlareInstance {
+    center: [f32; 2],
+    size: f32,
+    _padding: f32,
+    color: [f32; 4],
+}
+
+impl FlareInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32,
+        2 => Float32x4,
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<FlareInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+enum Readback {
+    Idle,
+    // 解析与复制命令已录制，等待随本帧提交
+    Copied,
+    Mapping(Arc<AtomicU8>),
+}
+
+// 镜头光晕：每帧在太阳圆盘上画 SUN_SAMPLE_COUNT 个点做一次遮挡查询（深度为 1，只在天空处通过），
+// 结果回读后作为覆盖率，非零时把各光斑作为屏幕空间四边形相加混合到目标上。
+// 查询结果延迟若干帧可用；部分后端（GL）的遮挡查询只返回 0 或 1，此时覆盖率最多为 1 / SUN_SAMPLE_COUNT
+pub struct LensFlareSystem {
+    // 太阳圆盘半径占屏幕高度的比例
+    pub sun_radius: f32,
+    elements: Vec<FlareElement>,
+    element_bind_groups: Vec<wgpu::BindGroup>,
+    sun_screen_pos: Option<[f32; 2]>,
+    coverage: f32,
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    readback: Readback,
+    query_pipeline: wgpu::RenderPipeline,
+    flare_pipeline: wgpu::RenderPipeline,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    instance_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+}
+
+impl LensFlareSystem {
+    // depth_format 为场景深度缓冲的格式，遮挡查询与它做深度测试
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Lens Flare Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("lens_flare.wgsl").into()),
+        });
+
+        let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("lens_flare_uniform_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let texture_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("lens_flare_texture_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let query_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lens Flare Query Pipeline Layout"),
+            bind_group_layouts: &[&uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        // 只做深度测试，不写颜色与深度；太阳在远平面上，深度为清屏值 1 的天空像素才通过
+        let query_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Lens Flare Query Pipeline"),
+            layout: Some(&query_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_sun",
+                buffers: &[],
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::PointList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let flare_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lens Flare Pipeline Layout"),
+            bind_group_layouts: &[&uniform_bind_group_layout, &texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let flare_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Lens Flare Pipeline"),
+            layout: Some(&flare_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_flare",
+                buffers: &[FlareInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_flare",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    // 片元输出已预乘 alpha
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Lens Flare Occlusion Query Set"),
+            ty: wgpu::QueryType::Occlusion,
+            count: 1,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lens Flare Resolve Buffer"),
+            size: QUERY_RESULT_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lens Flare Readback Buffer"),
+            size: QUERY_RESULT_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lens Flare Uniform Buffer"),
+            size: std::mem::size_of::<LensFlareUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("lens_flare_uniform_bind_group"),
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lens Flare Instance Buffer"),
+            size: (std::mem::size_of::<FlareInstance>() * MAX_FLARE_ELEMENTS) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Lens Flare Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            sun_radius: 0.02,
+            elements: Vec::new(),
+            element_bind_groups: Vec::new(),
+            sun_screen_pos: None,
+            coverage: 0.0,
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            readback: Readback::Idle,
+            query_pipeline,
+            flare_pipeline,
+            texture_bind_group_layout,
+            uniform_buffer,
+            uniform_bind_group,
+            instance_buffer,
+            sampler,
+        }
+    }
+
+    // 超过 MAX_FLARE_ELEMENTS 的光斑被忽略
+    pub fn add_element(&mut self, device: &wgpu::Device, element: FlareElement) {
+        if self.elements.len() >= MAX_FLARE_ELEMENTS {
+            tracing::warn!("lens flare: more than {MAX_FLARE_ELEMENTS} elements, extra element is ignored");
+            return;
+        }
+        self.element_bind_groups.push(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("lens_flare_texture_bind_group"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&element.texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        }));
+        self.elements.push(element);
+    }
+
+    pub fn elements(&self) -> &[FlareElement] {
+        &self.elements
+    }
+
+    // 最近一次回读的覆盖率，0 为完全被遮挡或太阳不在视野中
+    pub fn coverage(&self) -> f32 {
+        self.coverage
+    }
+
+    // sun_screen_pos 为太阳的屏幕纹理坐标（左上角为 (0, 0)），太阳在相机背后时传 None；aspect 为宽 / 高
+    pub fn update(&mut self, queue: &wgpu::Queue, sun_screen_pos: Option<[f32; 2]>, aspect: f32) {
+        self.sun_screen_pos = sun_screen_pos;
+        let Some([u, v]) = sun_screen_pos else {
+            self.coverage = 0.0;
+            return;
+        };
+        let sun = [u * 2.0 - 1.0, 1.0 - v * 2.0];
+        let radius = self.sun_radius * 2.0;
+        let uniform = LensFlareUniform {
+            sun_position: sun,
+            sun_radius: [radius / aspect, radius],
+            aspect,
+            fade: self.coverage,
+            sample_count: SUN_SAMPLE_COUNT,
+            _padding: 0.0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let instances: Vec<FlareInstance> = self
+            .elements
+            .iter()
+            .map(|element| FlareInstance {
+                center: [sun[0] * element.offset_along_axis, sun[1] * element.offset_along_axis],
+                size: element.size,
+                _padding: 0.0,
+                color: element.color,
+            })
+            .collect();
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+    }
+
+    // 在场景深度写完之后录制遮挡查询；上一次回读完成之前不会再解析结果
+    pub fn query(&mut self, encoder: &mut wgpu::CommandEncoder, depth_view: &wgpu::TextureView) {
+        if self.sun_screen_pos.is_none() {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Lens Flare Occlusion Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: Some(&self.query_set),
+        });
+        render_pass.set_pipeline(&self.query_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.begin_occlusion_query(0);
+        render_pass.draw(0..SUN_SAMPLE_COUNT, 0..1);
+        render_pass.end_occlusion_query();
+        drop(render_pass);
+
+        if matches!(self.readback, Readback::Idle) {
+            encoder.resolve_query_set(&self.query_set, 0..1, &self.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, QUERY_RESULT_SIZE);
+            self.readback = Readback::Copied;
+        }
+    }
+
+    // 在提交之后调用：发起映射，或在映射完成时更新覆盖率
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        match std::mem::replace(&mut self.readback, Readback::Idle) {
+            Readback::Idle => {}
+            Readback::Copied => {
+                let status = Arc::new(AtomicU8::new(MAP_PENDING));
+                let callback_status = status.clone();
+                self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                    callback_status.store(if result.is_ok() { MAP_READY } else { MAP_FAILED }, Ordering::Release);
+                });
+                self.readback = Readback::Mapping(status);
+            }
+            Readback::Mapping(status) => {
+                device.poll(wgpu::Maintain::Poll);
+                match status.load(Ordering::Acquire) {
+                    MAP_PENDING => self.readback = Readback::Mapping(status),
+                    MAP_FAILED => {}
+                    _ => {
+                        let samples = {
+                            let data = self.readback_buffer.slice(..).get_mapped_range();
+                            bytemuck::pod_read_unaligned::<u64>(&data[..QUERY_RESULT_SIZE as usize])
+                        };
+                        self.readback_buffer.unmap();
+                        // 多重采样时一个点可能计入多个采样
+                        let coverage = (samples as f32 / SUN_SAMPLE_COUNT as f32).min(1.0);
+                        if self.sun_screen_pos.is_some() {
+                            self.coverage = coverage;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 覆盖率为 0 时不绘制
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        if self.sun_screen_pos.is_none() || self.coverage <= 0.0 || self.elements.is_empty() {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Lens Flare Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.flare_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        for (index, bind_group) in self.element_bind_groups.iter().enumerate() {
+            let instance = index as u32;
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.draw(0..4, instance..instance + 1);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+struct LensFlareUniform {
+    sun_position: vec2f,
+    sun_radius: vec2f,
+    aspect: f32,
+    fade: f32,
+    sample_count: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> flare: LensFlareUniform;
+
+const GOLDEN_ANGLE: f32 = 2.39996323;
+
+// 太阳圆盘上按黄金角螺旋分布的点，放在远平面上
+@vertex
+fn vs_sun(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let r = sqrt((f32(in_vertex_index) + 0.5) / f32(flare.sample_count));
+    let theta = f32(in_vertex_index) * GOLDEN_ANGLE;
+    let offset = vec2f(cos(theta), sin(theta)) * r * flare.sun_radius;
+    return vec4f(flare.sun_position + offset, 1.0, 1.0);
+}
+
+struct FlareInput {
+    @location(0) center: vec2f,
+    @location(1) size: f32,
+    @location(2) color: vec4f,
+};
+
+struct FlareOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) uv: vec2f,
+    @location(1) color: vec4f,
+};
+
+// 三角形带的四个顶点组成以 center 为中心的正方形
+@vertex
+fn vs_flare(@builtin(vertex_index) in_vertex_index: u32, instance: FlareInput) -> FlareOutput {
+    let uv = vec2f(f32(in_vertex_index & 1u), f32(in_vertex_index >> 1u));
+    let corner = uv * 2.0 - 1.0;
+    var out: FlareOutput;
+    out.clip_position = vec4f(instance.center + corner * vec2f(instance.size / flare.aspect, instance.size) * 2.0, 0.0, 1.0);
+    out.uv = vec2f(uv.x, 1.0 - uv.y);
+    out.color = instance.color;
+    return out;
+}
+
+@group(1) @binding(0)
+var t_flare: texture_2d<f32>;
+@group(1) @binding(1)
+var s_flare: sampler;
+
+@fragment
+fn fs_flare(in: FlareOutput) -> @location(0) vec4f {
+    let texel = textureSample(t_flare, s_flare, in.uv) * in.color;
+    let alpha = texel.a * flare.fade;
+    return vec4f(texel.rgb * alpha, alpha);
+}
@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+
+pub mod aabb;
+pub mod asset_cache;
+pub mod backend;
+pub mod baked_env_map;
+pub mod blue_noise;
+pub mod brick_pattern;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod camera;
+pub mod camera_path;
+pub mod clock;
+pub mod clustered_lighting;
+pub mod command_pool;
+pub mod contact_shadow;
+pub mod convex_hull;
+pub mod cubemap;
+pub mod debug_draw;
+pub mod depth_peel;
+pub mod dynamic_resolution;
+pub mod dynamic_sky;
+pub mod error_scope;
+pub mod filtered_shadow;
+pub mod frame_capture;
+pub mod frustum;
+pub mod frustum_gizmo;
+pub mod fur;
+pub mod gbuffer;
+pub mod gizmo;
+pub mod gpu_memory;
+pub mod gpu_timer;
+pub mod grass;
+pub mod instance_buffer;
+pub mod lens_flare;
+pub mod light_probe;
+#[cfg(feature = "gi-lpv")]
+pub mod light_propagation;
+pub mod lighting;
+pub mod loading;
+pub mod lod_stream;
+pub mod logging;
+pub mod material;
+pub mod material_animation;
+pub mod mesh;
+pub mod mesh_upload;
+pub mod mipmap;
+pub mod normal_visualizer;
+pub mod oit;
+pub mod path_tracer;
+pub mod perlin_noise;
+pub mod physics;
+pub mod plugin;
+pub mod pipeline_manager;
+pub mod pipeline_patcher;
+pub mod point_cloud;
+pub mod portal;
+pub mod post_process;
+pub mod prefix_sum;
+pub mod primitives;
+pub mod profiling;
+pub mod radix_sort;
+pub mod ray_cast;
+pub mod render_bundle_cache;
+pub mod render_queue;
+pub mod render_state;
+pub mod resource_version;
+pub mod scene;
+pub mod sdf_atlas;
+pub mod sdf_shapes;
+pub mod secondary_command_buffer;
+pub mod shader_error;
+pub mod shader_preprocessor;
+pub mod shader_reflection;
+pub mod shadow_atlas;
+pub mod spline;
+pub mod ssgi;
+pub mod static_batch;
+pub mod streaming_texture;
+pub mod terrain;
+pub mod texture;
+pub mod texture_atlas;
+pub mod texture_cache;
+pub mod timestep;
+pub mod transform;
+pub mod triplanar;
+pub mod triple_buffer;
+pub mod vertex_format;
+pub mod virtual_texture;
+pub mod volume;
+pub mod voxel;
+pub mod vr;
+pub mod water;
+pub mod wgsl_lint;
+pub mod world;
+pub mod worley_noise;
@@ -0,0 +1,288 @@
+use cgmath::{ InnerSpace, Matrix4, Vector3 };
+use wgpu::util::DeviceExt;
+
+use crate::camera::CameraUniform;
+use crate::cubemap::cube_face_view_projections;
+use crate::post_process::SCENE_COLOR_FORMAT;
+use crate::texture::Texture;
+
+pub const PROBE_RESOLUTION: u32 = 64;
+
+pub struct LightProbe {
+    pub position: [f32; 3],
+    pub cubemap: wgpu::Texture,
+    // 两次捕获之间的最短间隔，单位为秒
+    pub update_interval: f32,
+    pub active: bool,
+    face_views: Vec<wgpu::TextureView>,
+    depth: Texture,
+    camera_bind_groups: Vec<wgpu::BindGroup>,
+    bind_group: wgpu::BindGroup,
+    since_update: f32,
+}
+
+impl LightProbe {
+    fn distance2(&self, position: [f32; 3]) -> f32 {
+        (Vector3::from(self.position) - Vector3::from(position)).magnitude2()
+    }
+}
+
+// 在运行时捕获环境立方体贴图，光照通道按物体位置选用最近的探针作为 IBL 来源
+// 每帧最多更新一个探针（6 个面），多个探针轮流更新以分摊开销
+pub struct LightProbeSystem {
+    probes: Vec<LightProbe>,
+    next: usize,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // 没有探针或正在捕获时绑定的纯黑立方体贴图
+    fallback_bind_group: wgpu::BindGroup,
+}
+
+impl LightProbeSystem {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let bind_group_layout = Self::create_bind_group_layout(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Light Probe Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let fallback = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Fallback Light Probe"),
+                size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 6 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            &[0; 4 * 6],
+        );
+        let fallback_view = fallback.create_view(&Self::cube_view_descriptor());
+        let fallback_bind_group = Self::create_bind_group(device, &bind_group_layout, &fallback_view, &sampler);
+
+        Self {
+            probes: Vec::new(),
+            next: 0,
+            sampler,
+            bind_group_layout,
+            fallback_bind_group,
+        }
+    }
+
+    // 着色器中的 probe_cubemap 与 probe_sampler
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("light_probe_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn fallback_bind_group(&self) -> &wgpu::BindGroup {
+        &self.fallback_bind_group
+    }
+
+    // camera_bind_group_layout 与场景管线第 0 组一致，捕获时每个面使用自己的相机
+    pub fn add_probe(
+        &mut self,
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        position: [f32; 3],
+        update_interval: f32,
+    ) -> usize {
+        let cubemap = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Light Probe Cubemap"),
+            size: wgpu::Extent3d { width: PROBE_RESOLUTION, height: PROBE_RESOLUTION, depth_or_array_layers: 6 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SCENE_COLOR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let face_views = (0..6)
+            .map(|face| {
+                cubemap.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Light Probe Face"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: face,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let depth = Texture::create_render_target(
+            device,
+            PROBE_RESOLUTION,
+            PROBE_RESOLUTION,
+            Texture::DEPTH_FORMAT,
+            wgpu::TextureUsages::empty(),
+            "Light Probe Depth",
+        );
+
+        let camera_bind_groups = Self::face_view_projections(position)
+            .into_iter()
+            .map(|view_proj| {
+                let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Light Probe Camera Buffer"),
+                    contents: bytemuck::cast_slice(&[CameraUniform { view_proj: view_proj.into(), ..CameraUniform::new() }]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("light_probe_camera_bind_group"),
+                    layout: camera_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                })
+            })
+            .collect();
+
+        let cube_view = cubemap.create_view(&Self::cube_view_descriptor());
+        let bind_group = Self::create_bind_group(device, &self.bind_group_layout, &cube_view, &self.sampler);
+
+        self.probes.push(LightProbe {
+            position,
+            cubemap,
+            update_interval,
+            active: true,
+            face_views,
+            depth,
+            camera_bind_groups,
+            bind_group,
+            // 首帧立即捕获
+            since_update: f32::INFINITY,
+        });
+        self.probes.len() - 1
+    }
+
+    pub fn probes(&self) -> &[LightProbe] {
+        &self.probes
+    }
+
+    pub fn probe_mut(&mut self, index: usize) -> Option<&mut LightProbe> {
+        self.probes.get_mut(index)
+    }
+
+    // 推进计时，返回本帧需要重新捕获的探针；从上次更新的下一个开始轮询，保证每个探针都有机会
+    pub fn update(&mut self, dt: f32) -> Option<usize> {
+        for probe in &mut self.probes {
+            probe.since_update += dt;
+        }
+
+        let count = self.probes.len();
+        let index = (0..count)
+            .map(|offset| (self.next + offset) % count)
+            .find(|&index| {
+                let probe = &self.probes[index];
+                probe.active && probe.since_update >= probe.update_interval
+            })?;
+        self.probes[index].since_update = 0.0;
+        self.next = index + 1;
+        Some(index)
+    }
+
+    // 开始渲染探针的某个面（0..6），返回的通道已绑定该面的相机（第 0 组）
+    // 捕获期间不能采样正在写入的立方体贴图，场景应绑定 fallback_bind_group
+    pub fn begin_face_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        index: usize,
+        face: usize,
+        clear_color: wgpu::Color,
+    ) -> wgpu::RenderPass<'a> {
+        let probe = &self.probes[index];
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Light Probe Capture Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &probe.face_views[face],
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &probe.depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_bind_group(0, &probe.camera_bind_groups[face], &[]);
+        render_pass
+    }
+
+    // 离 position 最近的活动探针，没有时返回纯黑的备用贴图
+    pub fn bind_group_for(&self, position: [f32; 3]) -> &wgpu::BindGroup {
+        self.probes
+            .iter()
+            .filter(|probe| probe.active)
+            .min_by(|a, b| a.distance2(position).total_cmp(&b.distance2(position)))
+            .map_or(&self.fallback_bind_group, |probe| &probe.bind_group)
+    }
+
+    fn face_view_projections(position: [f32; 3]) -> [Matrix4<f32>; 6] {
+        cube_face_view_projections(position, 0.05, 100.0)
+    }
+
+    fn cube_view_descriptor() -> wgpu::TextureViewDescriptor<'static> {
+        wgpu::TextureViewDescriptor {
+            label: Some("Light Probe Cube View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("light_probe_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+}
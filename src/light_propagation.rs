@@ -0,0 +1,527 @@
+use cgmath::InnerSpace;
+use wgpu::util::DeviceExt;
+
+use crate::camera::CameraUniform;
+use crate::lighting::SpotLight;
+use crate::shader_preprocessor::ShaderPreprocessor;
+use crate::texture::Texture;
+
+pub const LPV_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const RSM_FLUX_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const RSM_POSITION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const RSM_NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const RSM_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+// 每个纹素一个虚拟点光源
+pub const RSM_SIZE: u32 = 64;
+pub const PROPAGATION_STEPS: u32 = 6;
+// 注入用定点数累加，与 light_propagation.wgsl 的 fixed_point_scale 一致；单个格子的系数不能超过 2^31 / FIXED_POINT_SCALE
+const FIXED_POINT_SCALE: f32 = 1048576.0;
+// 每个格子三个颜色通道各 4 个系数
+const CELL_SIZE_BYTES: wgpu::BufferAddress = 3 * 4 * 4;
+// LPV_FORMAT 每个纹素的字节数
+const TEXEL_SIZE_BYTES: u32 = 8;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LpvParams {
+    origin: [f32; 3],
+    cell_size: f32,
+    grid: [u32; 3],
+    rsm_size: u32,
+    fixed_point_scale: f32,
+    row_texels: u32,
+    _padding: [f32; 2],
+}
+
+// 反射阴影图通道中的聚光灯，与 shader.wgsl 的 RsmLight 一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct RsmLight {
+    position: [f32; 3],
+    cos_outer: f32,
+    direction: [f32; 3],
+    cos_inner: f32,
+    // 颜色 × 强度 × 每个纹素覆盖的立体角
+    flux: [f32; 3],
+    _padding: f32,
+}
+
+// 场景着色器读取体积时的参数，与 shader.wgsl 的 LpvUniform 一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LpvUniform {
+    origin: [f32; 3],
+    cell_size: f32,
+    grid: [u32; 3],
+    intensity: f32,
+}
+
+// 传播用的计算管线与缓冲区，只在启用时创建
+struct Propagation {
+    inject_pipeline: wgpu::ComputePipeline,
+    resolve_pipeline: wgpu::ComputePipeline,
+    propagate_pipeline: wgpu::ComputePipeline,
+    store_pipeline: wgpu::ComputePipeline,
+    injected: wgpu::Buffer,
+    // 三个颜色通道打包后的体积，存储阶段写入后复制到三维纹理
+    packed_volumes: wgpu::Buffer,
+    bytes_per_row: u32,
+    // 两个绑定组交换 source 与 destination，依次传播
+    bind_groups: [wgpu::BindGroup; 2],
+    rsm_flux: Texture,
+    rsm_position: Texture,
+    rsm_normal: Texture,
+    rsm_depth: Texture,
+    camera_buffer: wgpu::Buffer,
+    light_buffer: wgpu::Buffer,
+    rsm_bind_group: wgpu::BindGroup,
+}
+
+// 光传播体积：从一盏聚光灯渲染反射阴影图（每个纹素的反射光通量、世界坐标与法线），
+// 把纹素作为虚拟点光源以二阶球谐注入覆盖场景的网格，再向相邻格子传播 PROPAGATION_STEPS 次并累加，
+// 场景着色器在世界坐标处三线性采样体积，沿法线求值得到一次反弹的漫反射间接光。
+// 没有几何遮挡体积，间接光会穿过物体
+pub struct LightPropagationVolumes {
+    pub grid: [u32; 3],
+    // 每个颜色通道一张三维纹理，四个通道存球谐系数
+    pub textures: [wgpu::Texture; 3],
+    pub origin: [f32; 3],
+    pub cell_size: f32,
+    // 间接光的倍率
+    pub intensity: f32,
+    views: [wgpu::TextureView; 3],
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    rsm_bind_group_layout: wgpu::BindGroupLayout,
+    propagation: Option<Propagation>,
+}
+
+impl LightPropagationVolumes {
+    // 网格覆盖 origin 起 grid × cell_size 的轴对齐盒子；enabled 为 false（或不支持计算着色器）时只创建全零的体积
+    pub fn new(device: &wgpu::Device, grid: [u32; 3], origin: [f32; 3], cell_size: f32, enabled: bool) -> Self {
+        let grid = grid.map(|size| size.max(1));
+        let textures = ["LPV Red", "LPV Green", "LPV Blue"].map(|label| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: grid[0],
+                    height: grid[1],
+                    depth_or_array_layers: grid[2],
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D3,
+                format: LPV_FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            })
+        });
+        let views = textures.each_ref().map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("LPV Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LPV Uniform Buffer"),
+            size: std::mem::size_of::<LpvUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // 反射阴影图通道的第 0 组：0 号为光源相机，2 号为 RsmLight（1 号留给 FilteredShadowMap 的矩通道）
+        let uniform_entry = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let rsm_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("rsm_bind_group_layout"),
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::VERTEX_FRAGMENT),
+                uniform_entry(2, wgpu::ShaderStages::FRAGMENT),
+            ],
+        });
+
+        let mut volumes = Self {
+            grid,
+            textures,
+            origin,
+            cell_size,
+            intensity: 1.0,
+            views,
+            sampler,
+            uniform_buffer,
+            rsm_bind_group_layout,
+            propagation: None,
+        };
+        if enabled {
+            volumes.propagation = Some(volumes.create_propagation(device));
+        }
+        volumes
+    }
+
+    fn create_propagation(&self, device: &wgpu::Device) -> Propagation {
+        let rsm_target = |format, label| Texture::create_render_target(device, RSM_SIZE, RSM_SIZE, format, wgpu::TextureUsages::empty(), label);
+        let rsm_flux = rsm_target(RSM_FLUX_FORMAT, "RSM Flux");
+        let rsm_position = rsm_target(RSM_POSITION_FORMAT, "RSM Position");
+        let rsm_normal = rsm_target(RSM_NORMAL_FORMAT, "RSM Normal");
+        let rsm_depth = rsm_target(RSM_DEPTH_FORMAT, "RSM Depth");
+
+        let cell_count = (self.grid[0] * self.grid[1] * self.grid[2]) as wgpu::BufferAddress;
+        let storage = |label, size, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+                mapped_at_creation: false,
+            })
+        };
+        let injected = storage("LPV Injected", cell_count * CELL_SIZE_BYTES, wgpu::BufferUsages::COPY_DST);
+        let cells = [
+            storage("LPV Cells A", cell_count * CELL_SIZE_BYTES, wgpu::BufferUsages::empty()),
+            storage("LPV Cells B", cell_count * CELL_SIZE_BYTES, wgpu::BufferUsages::empty()),
+        ];
+        let accumulated = storage("LPV Accumulated", cell_count * CELL_SIZE_BYTES, wgpu::BufferUsages::empty());
+        let bytes_per_row = (self.grid[0] * TEXEL_SIZE_BYTES).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let packed_size = 3 * (bytes_per_row * self.grid[1] * self.grid[2]) as wgpu::BufferAddress;
+        let packed_volumes = storage("LPV Packed Volumes", packed_size, wgpu::BufferUsages::COPY_SRC);
+
+        let params = LpvParams {
+            origin: self.origin,
+            cell_size: self.cell_size,
+            grid: self.grid,
+            rsm_size: RSM_SIZE,
+            fixed_point_scale: FIXED_POINT_SCALE,
+            row_texels: bytes_per_row / TEXEL_SIZE_BYTES,
+            _padding: [0.0; 2],
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("LPV Params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty,
+            count: None,
+        };
+        let texture = wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        };
+        let buffer = |read_only| wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("lpv_bind_group_layout"),
+            entries: &[
+                entry(0, wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                }),
+                entry(1, texture),
+                entry(2, texture),
+                entry(3, texture),
+                entry(4, buffer(false)),
+                entry(5, buffer(true)),
+                entry(6, buffer(false)),
+                entry(7, buffer(false)),
+                entry(8, buffer(false)),
+            ],
+        });
+        let bind_groups = [(0, 1), (1, 0)].map(|(source, destination): (usize, usize)| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("lpv_bind_group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&rsm_flux.view) },
+                    wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&rsm_position.view) },
+                    wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&rsm_normal.view) },
+                    wgpu::BindGroupEntry { binding: 4, resource: injected.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 5, resource: cells[source].as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 6, resource: cells[destination].as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 7, resource: accumulated.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 8, resource: packed_volumes.as_entire_binding() },
+                ],
+            })
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Light Propagation Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("light_propagation.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Light Propagation Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let create_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+
+        let uniform = |label, size| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+        let camera_buffer = uniform("RSM Camera Buffer", std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress);
+        let light_buffer = uniform("RSM Light Buffer", std::mem::size_of::<RsmLight>() as wgpu::BufferAddress);
+        let rsm_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rsm_bind_group"),
+            layout: &self.rsm_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: light_buffer.as_entire_binding() },
+            ],
+        });
+
+        Propagation {
+            inject_pipeline: create_pipeline("LPV Inject Pipeline", "cs_inject"),
+            resolve_pipeline: create_pipeline("LPV Resolve Pipeline", "cs_resolve"),
+            propagate_pipeline: create_pipeline("LPV Propagate Pipeline", "cs_propagate"),
+            store_pipeline: create_pipeline("LPV Store Pipeline", "cs_store"),
+            injected,
+            packed_volumes,
+            bytes_per_row,
+            bind_groups,
+            rsm_flux,
+            rsm_position,
+            rsm_normal,
+            rsm_depth,
+            camera_buffer,
+            light_buffer,
+            rsm_bind_group,
+        }
+    }
+
+    // 场景着色器的 lpv_indirect 与 fs_rsm
+    pub fn register_includes(preprocessor: &mut ShaderPreprocessor) {
+        preprocessor.add_include("lpv_lighting", include_str!("lpv_lighting.wgsl"));
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.propagation.is_some()
+    }
+
+    // 反射阴影图从这盏灯渲染；每个纹素的光通量按它覆盖的立体角估算
+    pub fn set_spot_light(&self, queue: &wgpu::Queue, light: &SpotLight) {
+        let Some(propagation) = &self.propagation else {
+            return;
+        };
+        let view_proj = light.view_projection();
+        let camera = CameraUniform { view_proj: view_proj.into(), ..CameraUniform::new() };
+        queue.write_buffer(&propagation.camera_buffer, 0, bytemuck::bytes_of(&camera));
+
+        let texel_solid_angle = (2.0 * light.outer_angle.tan()).powi(2) / (RSM_SIZE * RSM_SIZE) as f32;
+        let rsm_light = RsmLight {
+            position: light.position,
+            cos_outer: light.outer_angle.cos(),
+            direction: cgmath::Vector3::from(light.direction).normalize().into(),
+            cos_inner: light.inner_angle.cos(),
+            flux: light.color.map(|channel| channel * light.intensity * texel_solid_angle),
+            _padding: 0.0,
+        };
+        queue.write_buffer(&propagation.light_buffer, 0, bytemuck::bytes_of(&rsm_light));
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue) {
+        let uniform = LpvUniform {
+            origin: self.origin,
+            cell_size: self.cell_size,
+            grid: self.grid,
+            intensity: if self.is_enabled() { self.intensity } else { 0.0 },
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    // 反射阴影图通道；未启用时返回 None。第 0 组已绑定，调用方设置管线与材质组后绘制场景
+    pub fn begin_rsm_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> Option<wgpu::RenderPass<'a>> {
+        let propagation = self.propagation.as_ref()?;
+        let color_attachment = |view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("RSM Pass"),
+            color_attachments: &[
+                color_attachment(&propagation.rsm_flux.view),
+                color_attachment(&propagation.rsm_position.view),
+                color_attachment(&propagation.rsm_normal.view),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &propagation.rsm_depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_bind_group(0, &propagation.rsm_bind_group, &[]);
+        Some(render_pass)
+    }
+
+    // 在反射阴影图通道之后调用：注入、传播并写入体积纹理
+    pub fn propagate(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(propagation) = &self.propagation else {
+            return;
+        };
+        encoder.clear_buffer(&propagation.injected, 0, None);
+        let cells = [self.grid[0].div_ceil(4), self.grid[1].div_ceil(4), self.grid[2].div_ceil(4)];
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Light Propagation Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_bind_group(0, &propagation.bind_groups[0], &[]);
+            compute_pass.set_pipeline(&propagation.inject_pipeline);
+            compute_pass.dispatch_workgroups(RSM_SIZE.div_ceil(8), RSM_SIZE.div_ceil(8), 1);
+            // 注入结果写入第 1 个绑定组的 destination，即第 0 个绑定组的 source
+            compute_pass.set_bind_group(0, &propagation.bind_groups[1], &[]);
+            compute_pass.set_pipeline(&propagation.resolve_pipeline);
+            compute_pass.dispatch_workgroups(cells[0], cells[1], cells[2]);
+
+            compute_pass.set_pipeline(&propagation.propagate_pipeline);
+            for step in 0..PROPAGATION_STEPS as usize {
+                compute_pass.set_bind_group(0, &propagation.bind_groups[step % 2], &[]);
+                compute_pass.dispatch_workgroups(cells[0], cells[1], cells[2]);
+            }
+            compute_pass.set_pipeline(&propagation.store_pipeline);
+            compute_pass.dispatch_workgroups(cells[0], cells[1], cells[2]);
+        }
+
+        let rows_per_volume = self.grid[1] * self.grid[2];
+        for (channel, texture) in self.textures.iter().enumerate() {
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: &propagation.packed_volumes,
+                    layout: wgpu::ImageDataLayout {
+                        offset: (channel as u32 * propagation.bytes_per_row * rows_per_volume) as wgpu::BufferAddress,
+                        bytes_per_row: Some(propagation.bytes_per_row),
+                        rows_per_image: Some(self.grid[1]),
+                    },
+                },
+                texture.as_image_copy(),
+                texture.size(),
+            );
+        }
+    }
+
+    // 反射阴影图管线的第 0 组
+    pub fn rsm_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.rsm_bind_group_layout
+    }
+
+    // 反射阴影图管线的颜色目标，片元着色器输出光通量、世界坐标与法线
+    pub fn rsm_color_targets() -> [Option<wgpu::ColorTargetState>; 3] {
+        [RSM_FLUX_FORMAT, RSM_POSITION_FORMAT, RSM_NORMAL_FORMAT].map(|format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+    }
+
+    pub fn rsm_depth_stencil_state() -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: RSM_DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    // 光照组中光传播体积使用的 9~13 号绑定，与 bind_group_entries 对应
+    pub fn bind_group_layout_entries(visibility: wgpu::ShaderStages) -> [wgpu::BindGroupLayoutEntry; 5] {
+        let volume_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D3,
+                multisampled: false,
+            },
+            count: None,
+        };
+        [
+            volume_entry(9),
+            volume_entry(10),
+            volume_entry(11),
+            wgpu::BindGroupLayoutEntry {
+                binding: 12,
+                visibility,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 13,
+                visibility,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ]
+    }
+
+    pub fn bind_group_entries(&self) -> [wgpu::BindGroupEntry<'_>; 5] {
+        [
+            wgpu::BindGroupEntry {
+                binding: 9,
+                resource: wgpu::BindingResource::TextureView(&self.views[0]),
+            },
+            wgpu::BindGroupEntry {
+                binding: 10,
+                resource: wgpu::BindingResource::TextureView(&self.views[1]),
+            },
+            wgpu::BindGroupEntry {
+                binding: 11,
+                resource: wgpu::BindingResource::TextureView(&self.views[2]),
+            },
+            wgpu::BindGroupEntry {
+                binding: 12,
+                resource: wgpu::BindingResource::Sampler(&self.sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 13,
+                resource: self.uniform_buffer.as_entire_binding(),
+            },
+        ]
+    }
+}
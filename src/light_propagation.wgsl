@@ -0,0 +1,177 @@
+// 光传播体积（Kaplanyan 2009）：反射阴影图的虚拟点光源注入网格，再逐步向六个相邻格子传播。
+// 每个格子按颜色通道各存一组二阶球谐（L0 + L1，4 个系数）
+
+const PI: f32 = 3.14159265;
+const SH_C0: f32 = 0.282094792;
+const SH_C1: f32 = 0.488602512;
+// 余弦瓣投影到球谐的系数
+const SH_COS_LOBE_C0: f32 = 0.886226925;
+const SH_COS_LOBE_C1: f32 = 1.02332671;
+// 从相邻格子看，正对的面与四个侧面各自张成的立体角（已除以 4π）
+const DIRECT_FACE_SOLID_ANGLE: f32 = 0.03188571;
+const SIDE_FACE_SOLID_ANGLE: f32 = 0.03369559;
+
+struct LpvParams {
+    origin: vec3f,
+    cell_size: f32,
+    grid: vec3u,
+    rsm_size: u32,
+    // 注入时 f32 转为 i32 定点数的比例，与 light_propagation.rs 中的 FIXED_POINT_SCALE 一致
+    fixed_point_scale: f32,
+    // 打包体积中每行的纹素数，按 copy_buffer_to_texture 要求的 256 字节对齐
+    row_texels: u32,
+};
+
+struct Cell {
+    r: vec4f,
+    g: vec4f,
+    b: vec4f,
+};
+
+@group(0) @binding(0)
+var<uniform> params: LpvParams;
+@group(0) @binding(1)
+var rsm_flux: texture_2d<f32>;
+@group(0) @binding(2)
+var rsm_position: texture_2d<f32>;
+@group(0) @binding(3)
+var rsm_normal: texture_2d<f32>;
+// 每个格子 12 个定点数：三个颜色通道各 4 个系数
+@group(0) @binding(4)
+var<storage, read_write> injected: array<atomic<i32>>;
+@group(0) @binding(5)
+var<storage, read> source: array<Cell>;
+@group(0) @binding(6)
+var<storage, read_write> destination: array<Cell>;
+@group(0) @binding(7)
+var<storage, read_write> accumulated: array<Cell>;
+// 三个颜色通道依次排列的 rgba16float 纹素，每个纹素两个 u32，之后整体复制到三维纹理
+@group(0) @binding(8)
+var<storage, read_write> packed_volumes: array<vec2u>;
+
+fn sh_evaluate(direction: vec3f) -> vec4f {
+    return vec4f(SH_C0, -SH_C1 * direction.y, SH_C1 * direction.z, -SH_C1 * direction.x);
+}
+
+fn sh_cos_lobe(direction: vec3f) -> vec4f {
+    return vec4f(SH_COS_LOBE_C0, -SH_COS_LOBE_C1 * direction.y, SH_COS_LOBE_C1 * direction.z, -SH_COS_LOBE_C1 * direction.x);
+}
+
+fn cell_index(cell: vec3u) -> u32 {
+    return (cell.z * params.grid.y + cell.y) * params.grid.x + cell.x;
+}
+
+// 每个反射阴影图纹素是一个虚拟点光源，沿法线偏移半个格子后注入，避免照亮自身所在的表面
+@compute @workgroup_size(8, 8)
+fn cs_inject(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= vec2u(params.rsm_size)) {
+        return;
+    }
+    let position = textureLoad(rsm_position, id.xy, 0);
+    if position.w <= 0.0 {
+        return;
+    }
+    let normal = normalize(textureLoad(rsm_normal, id.xy, 0).xyz);
+    let flux = textureLoad(rsm_flux, id.xy, 0).rgb;
+    let grid_position = (position.xyz + normal * params.cell_size * 0.5 - params.origin) / params.cell_size;
+    if any(grid_position < vec3f(0.0)) || any(grid_position >= vec3f(params.grid)) {
+        return;
+    }
+    let base = cell_index(vec3u(grid_position)) * 12u;
+    let lobe = sh_cos_lobe(normal) / PI;
+    for (var channel = 0u; channel < 3u; channel++) {
+        let coefficients = lobe * flux[channel] * params.fixed_point_scale;
+        for (var k = 0u; k < 4u; k++) {
+            atomicAdd(&injected[base + channel * 4u + k], i32(round(coefficients[k])));
+        }
+    }
+}
+
+// 定点数还原为浮点，作为传播的起点与累加的初值
+@compute @workgroup_size(4, 4, 4)
+fn cs_resolve(@builtin(global_invocation_id) id: vec3u) {
+    if any(id >= params.grid) {
+        return;
+    }
+    let index = cell_index(id);
+    var channels: array<vec4f, 3>;
+    for (var channel = 0u; channel < 3u; channel++) {
+        for (var k = 0u; k < 4u; k++) {
+            channels[channel][k] = f32(atomicLoad(&injected[index * 12u + channel * 4u + k])) / params.fixed_point_scale;
+        }
+    }
+    let cell = Cell(channels[0], channels[1], channels[2]);
+    destination[index] = cell;
+    accumulated[index] = cell;
+}
+
+// 与 main_direction 垂直的第 side 个方向
+fn side_direction(main_direction: vec3f, side: u32) -> vec3f {
+    let a = main_direction.yzx;
+    let b = main_direction.zxy;
+    switch side {
+        case 0u: { return a; }
+        case 1u: { return -a; }
+        case 2u: { return b; }
+        default: { return -b; }
+    }
+}
+
+// 从六个相邻格子收集流向本格的光：相邻格子的强度沿本格各个面的方向求值，再按面的法线重新投影为余弦瓣
+@compute @workgroup_size(4, 4, 4)
+fn cs_propagate(@builtin(global_invocation_id) id: vec3u) {
+    if any(id >= params.grid) {
+        return;
+    }
+    var r = vec4f(0.0);
+    var g = vec4f(0.0);
+    var b = vec4f(0.0);
+    for (var axis = 0u; axis < 6u; axis++) {
+        var main_direction = vec3f(0.0);
+        main_direction[axis / 2u] = select(1.0, -1.0, axis % 2u == 1u);
+        // 光沿 main_direction 从相邻格子流入
+        let neighbor = vec3i(id) - vec3i(main_direction);
+        if any(neighbor < vec3i(0)) || any(neighbor >= vec3i(params.grid)) {
+            continue;
+        }
+        let cell = source[cell_index(vec3u(neighbor))];
+
+        let direct_sh = sh_evaluate(main_direction);
+        let direct_lobe = sh_cos_lobe(main_direction) * DIRECT_FACE_SOLID_ANGLE;
+        r += max(dot(cell.r, direct_sh), 0.0) * direct_lobe;
+        g += max(dot(cell.g, direct_sh), 0.0) * direct_lobe;
+        b += max(dot(cell.b, direct_sh), 0.0) * direct_lobe;
+
+        for (var side = 0u; side < 4u; side++) {
+            let side_dir = side_direction(main_direction, side);
+            // 相邻格子中心指向本格侧面中心的方向
+            let eval_sh = sh_evaluate(main_direction * 0.894427 + side_dir * 0.4472136);
+            let side_lobe = sh_cos_lobe(side_dir) * SIDE_FACE_SOLID_ANGLE;
+            r += max(dot(cell.r, eval_sh), 0.0) * side_lobe;
+            g += max(dot(cell.g, eval_sh), 0.0) * side_lobe;
+            b += max(dot(cell.b, eval_sh), 0.0) * side_lobe;
+        }
+    }
+    let index = cell_index(id);
+    destination[index] = Cell(r, g, b);
+    let total = accumulated[index];
+    accumulated[index] = Cell(total.r + r, total.g + g, total.b + b);
+}
+
+fn pack_texel(coefficients: vec4f) -> vec2u {
+    return vec2u(pack2x16float(coefficients.xy), pack2x16float(coefficients.zw));
+}
+
+// 累加结果按纹理布局打包。GL 后端的三维存储纹理只能写入第一层，因此不直接 textureStore
+@compute @workgroup_size(4, 4, 4)
+fn cs_store(@builtin(global_invocation_id) id: vec3u) {
+    if any(id >= params.grid) {
+        return;
+    }
+    let cell = accumulated[cell_index(id)];
+    let row = id.z * params.grid.y + id.y;
+    let channel_rows = params.grid.y * params.grid.z;
+    packed_volumes[row * params.row_texels + id.x] = pack_texel(cell.r);
+    packed_volumes[(channel_rows + row) * params.row_texels + id.x] = pack_texel(cell.g);
+    packed_volumes[(2u * channel_rows + row) * params.row_texels + id.x] = pack_texel(cell.b);
+}
@@ -0,0 +1,140 @@
+use cgmath::{ InnerSpace, Matrix4, Point3, Vector3 };
+
+use crate::camera::OPENGL_TO_WGPU_MATRIX;
+use crate::contact_shadow::ContactShadowPass;
+use crate::filtered_shadow::FilteredShadowMap;
+#[cfg(feature = "gi-lpv")]
+use crate::light_propagation::LightPropagationVolumes;
+use crate::shadow_atlas::ShadowAtlas;
+
+pub const MAX_POINT_LIGHTS: usize = 16;
+pub const MAX_SPOT_LIGHTS: usize = 8;
+// 聚光灯阴影投影的近平面，远平面为光源半径；与 shader.wgsl 中的 SPOT_SHADOW_NEAR 一致
+pub const SPOT_SHADOW_NEAR: f32 = 0.05;
+// SpotLight::shadow_tile 取此值时不投射阴影
+pub const NO_SHADOW: i32 = -1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointLight {
+    pub position: [f32; 3],
+    // 影响范围，超出后光照为 0
+    pub radius: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+// 在点光源的距离衰减之上，按片元偏离 direction 的角度在内外锥角之间平滑衰减
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpotLight {
+    pub position: [f32; 3],
+    pub radius: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    // 光锥朝向，着色器中会归一化
+    pub direction: [f32; 3],
+    // 半角，单位为弧度；内锥以内不衰减，外锥以外为 0
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    // ShadowAtlas 中的图块序号，NO_SHADOW 表示没有阴影
+    pub shadow_tile: i32,
+    // 数组元素需要 16 字节对齐
+    pub _padding: [f32; 2],
+}
+
+impl SpotLight {
+    // 覆盖外锥的透视投影，用于渲染该光源的阴影贴图
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        let direction = Vector3::from(self.direction).normalize();
+        let up = if direction.y.abs() > 0.99 { Vector3::unit_z() } else { Vector3::unit_y() };
+        let view = Matrix4::look_to_rh(Point3::from(self.position), direction, up);
+        let fovy = cgmath::Rad(self.outer_angle * 2.0);
+        OPENGL_TO_WGPU_MATRIX * cgmath::perspective(fovy, 1.0, SPOT_SHADOW_NEAR, self.radius) * view
+    }
+}
+
+// 场景着色器第 3 组的光源数组，整体作为一个 uniform 缓冲区上传
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightingUniform {
+    pub point_lights: [PointLight; MAX_POINT_LIGHTS],
+    pub spot_lights: [SpotLight; MAX_SPOT_LIGHTS],
+    pub num_point_lights: u32,
+    pub num_spot_lights: u32,
+    // uniform 缓冲区需要 16 字节对齐
+    pub _padding: [u32; 2],
+}
+
+impl Default for LightingUniform {
+    fn default() -> Self {
+        bytemuck::Zeroable::zeroed()
+    }
+}
+
+impl LightingUniform {
+    pub fn point_lights(&self) -> &[PointLight] {
+        &self.point_lights[..self.num_point_lights as usize]
+    }
+
+    // 超过 MAX_POINT_LIGHTS 的光源被忽略
+    pub fn set_point_lights(&mut self, lights: &[PointLight]) {
+        if lights.len() > MAX_POINT_LIGHTS {
+            tracing::warn!("lighting: {} point lights exceed the limit of {MAX_POINT_LIGHTS}, the rest are ignored", lights.len());
+        }
+        let count = lights.len().min(MAX_POINT_LIGHTS);
+        self.point_lights[..count].copy_from_slice(&lights[..count]);
+        self.point_lights[count..].fill(bytemuck::Zeroable::zeroed());
+        self.num_point_lights = count as u32;
+    }
+
+    pub fn spot_lights(&self) -> &[SpotLight] {
+        &self.spot_lights[..self.num_spot_lights as usize]
+    }
+
+    // 超过 MAX_SPOT_LIGHTS 的光源被忽略
+    pub fn set_spot_lights(&mut self, lights: &[SpotLight]) {
+        if lights.len() > MAX_SPOT_LIGHTS {
+            tracing::warn!("lighting: {} spot lights exceed the limit of {MAX_SPOT_LIGHTS}, the rest are ignored", lights.len());
+        }
+        let count = lights.len().min(MAX_SPOT_LIGHTS);
+        self.spot_lights[..count].copy_from_slice(&lights[..count]);
+        self.spot_lights[count..].fill(bytemuck::Zeroable::zeroed());
+        self.num_spot_lights = count as u32;
+    }
+
+    // 0 号绑定为本结构体，1~3 号为 ShadowAtlas::bind_group_entries，4 号为 ContactShadowPass::bind_group_entry，
+    // 5~8 号为 FilteredShadowMap::bind_group_entries，启用 gi-lpv 时 9~13 号为 LightPropagationVolumes::bind_group_entries
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let [atlas, sampler, tiles] = ShadowAtlas::bind_group_layout_entries(wgpu::ShaderStages::FRAGMENT);
+        let [moments, filtered, exponential, four_moments] = FilteredShadowMap::bind_group_layout_entries(wgpu::ShaderStages::FRAGMENT);
+        #[cfg(feature = "gi-lpv")]
+        let lpv_entries = LightPropagationVolumes::bind_group_layout_entries(wgpu::ShaderStages::FRAGMENT);
+        #[cfg(not(feature = "gi-lpv"))]
+        let lpv_entries: [wgpu::BindGroupLayoutEntry; 0] = [];
+        let entries = [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            atlas,
+            sampler,
+            tiles,
+            ContactShadowPass::bind_group_layout_entry(wgpu::ShaderStages::FRAGMENT),
+            moments,
+            filtered,
+            exponential,
+            four_moments,
+        ];
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("lighting_bind_group_layout"),
+            entries: &[entries.as_slice(), &lpv_entries].concat(),
+        })
+    }
+}
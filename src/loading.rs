@@ -0,0 +1,235 @@
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Arc;
+
+use tokio::task::{ JoinHandle, JoinSet };
+
+pub type TaskOutput = Arc<dyn Any + Send + Sync>;
+pub type TaskError = Box<dyn std::error::Error + Send + Sync>;
+
+type TaskFuture = Pin<Box<dyn Future<Output = Result<TaskOutput, TaskError>> + Send>>;
+type TaskFn = Box<dyn FnOnce(TaskInputs) -> TaskFuture + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(usize);
+
+// 依赖任务的输出，顺序与 add_task 时传入的依赖顺序一致
+pub struct TaskInputs(Vec<TaskOutput>);
+
+impl TaskInputs {
+    pub fn get<T: Any + Send + Sync>(&self, index: usize) -> Arc<T> {
+        self.0[index]
+            .clone()
+            .downcast::<T>()
+            .unwrap_or_else(|_| panic!("load task input {index} is not a {}", std::any::type_name::<T>()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+pub struct LoadTask {
+    label: String,
+    dependencies: Vec<TaskId>,
+    run: TaskFn,
+}
+
+// 加载步骤组成的有向无环图，例如：读取字节 → 解析 JSON → 加载纹理 → 上传缓冲区
+#[derive(Default)]
+pub struct LoadGraph {
+    tasks: Vec<LoadTask>,
+}
+
+impl LoadGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 依赖只能指向已添加的任务，因此图天然无环
+    pub fn add_task<T, F, Fut>(&mut self, label: impl Into<String>, dependencies: &[TaskId], run: F) -> TaskId
+    where
+        T: Any + Send + Sync,
+        F: FnOnce(TaskInputs) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, TaskError>> + Send + 'static,
+    {
+        let id = TaskId(self.tasks.len());
+        for dependency in dependencies {
+            assert!(dependency.0 < id.0, "load task dependency {dependency:?} does not exist yet");
+        }
+
+        self.tasks.push(LoadTask {
+            label: label.into(),
+            dependencies: dependencies.to_vec(),
+            run: Box::new(move |inputs| {
+                Box::pin(async move { run(inputs).await.map(|output| Arc::new(output) as TaskOutput) })
+            }),
+        });
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub total: usize,
+    pub done: usize,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProgressCounter {
+    total: AtomicUsize,
+    done: AtomicUsize,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Task { label: String, source: TaskError },
+    Panicked(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Task { label, source } => write!(f, "load task `{label}` failed: {source}"),
+            LoadError::Panicked(message) => write!(f, "load task panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Task { source, .. } => Some(source.as_ref()),
+            LoadError::Panicked(_) => None,
+        }
+    }
+}
+
+pub struct LoadResults {
+    outputs: Vec<TaskOutput>,
+}
+
+impl LoadResults {
+    pub fn get<T: Any + Send + Sync>(&self, id: TaskId) -> Option<Arc<T>> {
+        self.outputs[id.0].clone().downcast::<T>().ok()
+    }
+}
+
+// 在 tokio 线程池上并发执行所有依赖已满足的任务
+pub struct LoadExecutor {
+    progress: Arc<ProgressCounter>,
+    handle: JoinHandle<Result<LoadResults, LoadError>>,
+}
+
+impl LoadExecutor {
+    // 必须在 tokio 运行时中调用
+    pub fn spawn(graph: LoadGraph) -> Self {
+        let progress = Arc::new(ProgressCounter::default());
+        progress.total.store(graph.len(), Ordering::Relaxed);
+
+        let handle = tokio::task::spawn(execute(graph, progress.clone()));
+        Self { progress, handle }
+    }
+
+    // 不会阻塞，主线程每帧调用以显示加载进度
+    pub fn poll_progress(&self) -> Progress {
+        Progress {
+            total: self.progress.total.load(Ordering::Relaxed),
+            done: self.progress.done.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub async fn join(self) -> Result<LoadResults, LoadError> {
+        self.handle.await.map_err(|e| LoadError::Panicked(e.to_string()))?
+    }
+}
+
+async fn execute(graph: LoadGraph, progress: Arc<ProgressCounter>) -> Result<LoadResults, LoadError> {
+    let count = graph.tasks.len();
+    let mut labels = Vec::with_capacity(count);
+    let mut remaining = Vec::with_capacity(count);
+    let mut dependencies = Vec::with_capacity(count);
+    let mut dependents = vec![Vec::new(); count];
+    let mut runs = Vec::with_capacity(count);
+
+    for (index, task) in graph.tasks.into_iter().enumerate() {
+        for dependency in &task.dependencies {
+            dependents[dependency.0].push(index);
+        }
+        labels.push(task.label);
+        remaining.push(task.dependencies.len());
+        dependencies.push(task.dependencies);
+        runs.push(Some(task.run));
+    }
+
+    let mut outputs: Vec<Option<TaskOutput>> = vec![None; count];
+    let mut running = JoinSet::new();
+
+    let mut spawn_task = |index: usize, outputs: &[Option<TaskOutput>], running: &mut JoinSet<_>| {
+        let inputs = dependencies[index]
+            .iter()
+            .map(|dependency| outputs[dependency.0].clone().expect("dependency finished"))
+            .collect();
+        let run = runs[index].take().expect("load task spawned twice");
+        running.spawn(async move { (index, run(TaskInputs(inputs)).await) });
+    };
+
+    let ready = (0..count).filter(|&index| remaining[index] == 0).collect::<Vec<_>>();
+    for index in ready {
+        spawn_task(index, &outputs, &mut running);
+    }
+
+    while let Some(joined) = running.join_next().await {
+        let (index, result) = joined.map_err(|e| LoadError::Panicked(e.to_string()))?;
+        match result {
+            Ok(output) => {
+                outputs[index] = Some(output);
+                progress.done.fetch_add(1, Ordering::Relaxed);
+
+                for &dependent in &dependents[index] {
+                    remaining[dependent] -= 1;
+                    if remaining[dependent] == 0 {
+                        spawn_task(dependent, &outputs, &mut running);
+                    }
+                }
+            }
+            Err(source) => {
+                running.abort_all();
+                return Err(LoadError::Task { label: labels[index].clone(), source });
+            }
+        }
+    }
+
+    Ok(LoadResults {
+        outputs: outputs.into_iter().map(|output| output.expect("every load task finished")).collect(),
+    })
+}
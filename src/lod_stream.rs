@@ -0,0 +1,233 @@
+use std::io::{ Read, Write };
+use std::path::{ Path, PathBuf };
+use std::sync::mpsc::{ self, Receiver, Sender };
+use std::sync::Arc;
+
+use cgmath::{ InnerSpace, Point3 };
+
+use crate::aabb::Aabb;
+use crate::camera::Camera;
+use crate::mesh::{ MeshData, Vertex };
+use crate::mesh_upload::{ MeshHandle, MeshUploader };
+
+pub const LOD_COUNT: usize = 4;
+// 换到新 LOD 时新旧两级同时绘制的帧数
+pub const FADE_FRAMES: u32 = 2;
+// lod_distances 按此视场角定义，其他视场角按屏幕上的直径换算
+const REFERENCE_FOVY: f32 = 45.0;
+const MESH_MAGIC: &[u8; 4] = b"LWMS";
+const MESH_VERSION: u32 = 1;
+
+pub type MeshLoadError = Box<dyn std::error::Error + Send + Sync>;
+
+// 在工作线程上调用，lod 为 0（最精细）..LOD_COUNT
+pub trait MeshLoader: Send + Sync + 'static {
+    fn load(&self, lod: usize) -> Result<MeshData, MeshLoadError>;
+}
+
+// 从磁盘读取预先生成的各级网格：<root>/lod<N>.mesh，格式见 write_mesh_file
+pub struct DiskMeshLoader {
+    root: PathBuf,
+}
+
+impl DiskMeshLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self, lod: usize) -> PathBuf {
+        self.root.join(format!("lod{lod}.mesh"))
+    }
+}
+
+impl MeshLoader for DiskMeshLoader {
+    fn load(&self, lod: usize) -> Result<MeshData, MeshLoadError> {
+        read_mesh_file(&self.path(lod))
+    }
+}
+
+// 魔数、版本、顶点数、索引数，之后依次是 Vertex 数组与 u16 索引，均为小端
+pub fn write_mesh_file(path: &Path, mesh: &MeshData) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    file.write_all(MESH_MAGIC)?;
+    for value in [MESH_VERSION, mesh.vertices.len() as u32, mesh.indices.len() as u32] {
+        file.write_all(&value.to_le_bytes())?;
+    }
+    file.write_all(bytemuck::cast_slice(&mesh.vertices))?;
+    file.write_all(bytemuck::cast_slice(&mesh.indices))?;
+    file.flush()
+}
+
+pub fn read_mesh_file(path: &Path) -> Result<MeshData, MeshLoadError> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+    let header_size = MESH_MAGIC.len() + 12;
+    if bytes.len() < header_size || &bytes[..4] != MESH_MAGIC {
+        return Err(format!("{} is not a mesh file", path.display()).into());
+    }
+    let word = |index: usize| u32::from_le_bytes(bytes[4 + index * 4..8 + index * 4].try_into().unwrap());
+    if word(0) != MESH_VERSION {
+        return Err(format!("{} has unsupported mesh version {}", path.display(), word(0)).into());
+    }
+    let vertex_bytes = word(1) as usize * std::mem::size_of::<Vertex>();
+    let index_bytes = word(2) as usize * std::mem::size_of::<u16>();
+    if bytes.len() != header_size + vertex_bytes + index_bytes {
+        return Err(format!("{} is truncated", path.display()).into());
+    }
+    let vertices = &bytes[header_size..header_size + vertex_bytes];
+    let indices = &bytes[header_size + vertex_bytes..];
+    Ok(MeshData {
+        vertices: vertices.chunks_exact(std::mem::size_of::<Vertex>()).map(bytemuck::pod_read_unaligned).collect(),
+        indices: indices.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LodFade {
+    from: usize,
+    to: usize,
+    frame: u32,
+}
+
+// 单个物体的 LOD 流式加载：按屏幕上的直径选择所需的级别，更精细的级别不在内存中时在后台从磁盘读取，
+// 上传完成后再切换，切换时新旧两级以实例透明度交叉淡化 FADE_FRAMES 帧，避免跳变
+pub struct LodStream {
+    // 0 为最精细；已提交上传的级别，是否可用由调用方根据 MeshUploader 的结果判断
+    pub lod_handles: [Option<MeshHandle>; LOD_COUNT],
+    // 各级的最远使用距离（45° 视场角下），应递增
+    pub lod_distances: [f32; LOD_COUNT],
+    // 超出该距离不再发起加载，只使用已有的级别
+    pub load_radius: f32,
+    loader: Arc<dyn MeshLoader>,
+    sender: Sender<(usize, Result<MeshData, MeshLoadError>)>,
+    receiver: Receiver<(usize, Result<MeshData, MeshLoadError>)>,
+    loading: [bool; LOD_COUNT],
+    // 读取失败的级别不再重试
+    failed: [bool; LOD_COUNT],
+    current: Option<usize>,
+    fade: Option<LodFade>,
+    screen_diameter: f32,
+}
+
+impl LodStream {
+    pub fn new(loader: Arc<dyn MeshLoader>, lod_distances: [f32; LOD_COUNT], load_radius: f32) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            lod_handles: [None; LOD_COUNT],
+            lod_distances,
+            load_radius,
+            loader,
+            sender,
+            receiver,
+            loading: [false; LOD_COUNT],
+            failed: [false; LOD_COUNT],
+            current: None,
+            fade: None,
+            screen_diameter: 0.0,
+        }
+    }
+
+    // 当前完全显示的级别，首个网格上传完成之前为 None
+    pub fn current_lod(&self) -> Option<usize> {
+        self.current
+    }
+
+    // 上一次 update 时包围球在屏幕上的直径，单位为像素
+    pub fn screen_diameter(&self) -> f32 {
+        self.screen_diameter
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    // 必须在 tokio 运行时中调用，每帧一次；is_resident 判断网格是否已上传完成（即已从 MeshUploader::collect 取出）
+    pub fn update(
+        &mut self,
+        uploader: &mut MeshUploader,
+        camera: &Camera,
+        screen_height: u32,
+        world_aabb: &Aabb,
+        is_resident: impl Fn(MeshHandle) -> bool,
+    ) {
+        for (lod, result) in self.receiver.try_iter() {
+            self.loading[lod] = false;
+            match result {
+                Ok(mesh) => self.lod_handles[lod] = Some(uploader.upload(Arc::new(mesh))),
+                Err(e) => {
+                    tracing::warn!("lod stream: failed to load LOD {lod}: {e}");
+                    self.failed[lod] = true;
+                }
+            }
+        }
+
+        let center = Point3::from(world_aabb.center());
+        let radius = cgmath::Vector3::from(world_aabb.size()).magnitude() * 0.5;
+        let distance = (center - camera.eye).magnitude();
+        self.screen_diameter = projected_diameter(radius, distance, camera.fovy, screen_height);
+        let desired = (0..LOD_COUNT)
+            .find(|&lod| self.screen_diameter >= projected_diameter(radius, self.lod_distances[lod], REFERENCE_FOVY, screen_height))
+            .unwrap_or(LOD_COUNT - 1);
+
+        // 最粗的一级总是加载，保证有东西可画
+        self.request(LOD_COUNT - 1);
+        if distance <= self.load_radius {
+            self.request(desired);
+        }
+
+        let resident = |lod: usize| self.lod_handles[lod].is_some_and(&is_resident);
+        // 所需级别未就绪时先用更粗的，没有更粗的再用更精细的
+        let target = (desired..LOD_COUNT).chain((0..desired).rev()).find(|&lod| resident(lod));
+
+        match (self.fade, self.current, target) {
+            (Some(mut fade), _, _) => {
+                fade.frame += 1;
+                if fade.frame >= FADE_FRAMES {
+                    self.current = Some(fade.to);
+                    self.fade = None;
+                } else {
+                    self.fade = Some(fade);
+                }
+            }
+            (None, None, Some(target)) => self.current = Some(target),
+            (None, Some(current), Some(target)) if current != target => {
+                self.fade = Some(LodFade { from: current, to: target, frame: 0 });
+            }
+            _ => {}
+        }
+    }
+
+    fn request(&mut self, lod: usize) {
+        if self.lod_handles[lod].is_some() || self.loading[lod] || self.failed[lod] {
+            return;
+        }
+        self.loading[lod] = true;
+        let loader = self.loader.clone();
+        let sender = self.sender.clone();
+        tokio::task::spawn_blocking(move || {
+            // 接收端已销毁说明物体已被移除，直接丢弃
+            let _ = sender.send((lod, loader.load(lod)));
+        });
+    }
+
+    // 本帧要绘制的网格及其实例透明度；淡化期间新旧两级各画一次，透明度之和为 1
+    pub fn draws(&self) -> Vec<(MeshHandle, f32)> {
+        let handle = |lod: usize| self.lod_handles[lod].expect("displayed LOD has been uploaded");
+        match (self.fade, self.current) {
+            (Some(fade), _) => {
+                let t = (fade.frame + 1) as f32 / (FADE_FRAMES + 1) as f32;
+                vec![(handle(fade.from), 1.0 - t), (handle(fade.to), t)]
+            }
+            (None, Some(current)) => vec![(handle(current), 1.0)],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+// 半径为 radius 的包围球在 distance 处、垂直视场角 fovy（度）下占据的像素直径
+fn projected_diameter(radius: f32, distance: f32, fovy: f32, screen_height: u32) -> f32 {
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    radius / (distance * (fovy.to_radians() * 0.5).tan()) * screen_height as f32
+}
@@ -0,0 +1,14 @@
+use tracing_subscriber::EnvFilter;
+
+// 默认输出人类可读的文本；LOG_FORMAT=json 时每个事件输出一行 JSON，便于日志系统采集
+// 过滤规则沿用 RUST_LOG，wgpu 通过 log 发出的记录同样会被收集
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn,learn_wgpu=info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    if std::env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json")) {
+        builder.json().with_current_span(true).init();
+    } else {
+        builder.init();
+    }
+}
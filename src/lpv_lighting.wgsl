@@ -0,0 +1,68 @@
+// 场景着色器中与光传播体积有关的部分，由 LightPropagationVolumes::register_includes 注册为 lpv_lighting
+struct LpvUniform {
+    origin: vec3f,
+    cell_size: f32,
+    grid: vec3u,
+    // 未启用光传播体积时为 0
+    intensity: f32,
+};
+
+// 光传播体积，每个颜色通道一张，四个通道为二阶球谐系数；见 LightPropagationVolumes
+@group(3) @binding(9)
+var lpv_red: texture_3d<f32>;
+@group(3) @binding(10)
+var lpv_green: texture_3d<f32>;
+@group(3) @binding(11)
+var lpv_blue: texture_3d<f32>;
+@group(3) @binding(12)
+var lpv_sampler: sampler;
+@group(3) @binding(13)
+var<uniform> lpv: LpvUniform;
+
+// 光传播体积在 world_position 处流向表面的漫反射间接光，与 light_propagation.wgsl 的球谐约定一致
+fn lpv_indirect(world_position: vec3f, normal: vec3f) -> vec3f {
+    let uvw = (world_position - lpv.origin) / (vec3f(lpv.grid) * lpv.cell_size);
+    let sh = vec4f(0.282094792, 0.488602512 * normal.y, -0.488602512 * normal.z, 0.488602512 * normal.x);
+    let red = textureSampleLevel(lpv_red, lpv_sampler, uvw, 0.0);
+    let green = textureSampleLevel(lpv_green, lpv_sampler, uvw, 0.0);
+    let blue = textureSampleLevel(lpv_blue, lpv_sampler, uvw, 0.0);
+    let intensity = max(vec3f(dot(sh, red), dot(sh, green), dot(sh, blue)), vec3f(0.0));
+    // 网格之外没有间接光
+    let inside = all(uvw >= vec3f(0.0)) && all(uvw <= vec3f(1.0));
+    return select(vec3f(0.0), intensity * lpv.intensity / 3.14159265, inside);
+}
+
+// 光传播体积的反射阴影图通道，第 0 组换成 LightPropagationVolumes 的布局
+struct RsmLight {
+    position: vec3f,
+    cos_outer: f32,
+    direction: vec3f,
+    cos_inner: f32,
+    // 颜色 × 强度 × 每个纹素覆盖的立体角
+    flux: vec3f,
+};
+@group(0) @binding(2)
+var<uniform> rsm_light: RsmLight;
+
+struct RsmOutput {
+    @location(0) flux: vec4f,
+    @location(1) position: vec4f,
+    @location(2) normal: vec4f,
+};
+
+// 每个纹素记录被聚光灯照亮后反射的光通量；position.w 为 1 表示有表面
+@fragment
+fn fs_rsm(in: VertexOutput) -> RsmOutput {
+    let light_dir = normalize(in.world_position - rsm_light.position);
+    let cone = smoothstep(rsm_light.cos_outer, rsm_light.cos_inner, dot(rsm_light.direction, light_dir));
+    // 双面的薄物体，法线取朝向光源的一侧
+    var normal = normalize(in.world_normal);
+    if dot(normal, light_dir) > 0.0 {
+        normal = -normal;
+    }
+    var out: RsmOutput;
+    out.flux = vec4f(material.base_color.rgb * rsm_light.flux * cone, 1.0);
+    out.position = vec4f(in.world_position, 1.0);
+    out.normal = vec4f(normal, 0.0);
+    return out;
+}
@@ -0,0 +1,4 @@
+// 未启用 gi-lpv 特性时替代 lpv_lighting.wgsl：没有光传播体积的绑定，间接光为 0
+fn lpv_indirect(world_position: vec3f, normal: vec3f) -> vec3f {
+    return vec3f(0.0);
+}
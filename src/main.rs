@@ -1,23 +1,77 @@
 #![allow(dead_code)]
 
+#[cfg(feature = "audio")]
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+#[cfg(feature = "audio")]
+use std::time::{ Duration, Instant };
+
 use winit::{
     event::*,
     event_loop::{ ControlFlow, EventLoop },
     window::{ Window, WindowBuilder }
 };
 
+#[cfg(feature = "audio")]
+use learn_wgpu::audio;
+use learn_wgpu::{ logging, profiling };
+use learn_wgpu::asset_cache::AssetCache;
+use learn_wgpu::backend::RenderCapabilities;
+use learn_wgpu::error_scope::ErrorScope;
+use learn_wgpu::gbuffer::GBufferDebugMode;
+use learn_wgpu::plugin::AppBuilder;
+use learn_wgpu::scene::Scene;
+
+const WINDOW_TITLE: &str = "learn-wgpu";
+// 按住移动键时两次脚步声的间隔
+#[cfg(feature = "audio")]
+const FOOTSTEP_INTERVAL: Duration = Duration::from_millis(450);
+
+// 窗口与交换链；模拟与渲染都在 Scene 中，每帧渲染到交换链纹理
 struct State {
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
+    // 设备恢复时先释放：同一窗口上不能同时存在两个交换链
+    surface: Option<wgpu::Surface>,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
+    scene: Scene,
+
+    #[cfg(feature = "audio")]
+    audio: audio::AudioManager,
+    // 按住的移动键；系统的按键重复只会重复插入同一个键
+    #[cfg(feature = "audio")]
+    movement_keys: HashSet<VirtualKeyCode>,
+    #[cfg(feature = "audio")]
+    last_footstep: Option<Instant>,
+    #[cfg(feature = "profiling")]
+    profiler: profiling::ProfilerWindow,
+}
 
-    render_pipeline: wgpu::RenderPipeline,
+#[derive(Debug)]
+enum StateError {
+    CreateSurface(wgpu::CreateSurfaceError),
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
 }
 
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::CreateSurface(e) => write!(f, "failed to create surface: {e}"),
+            StateError::NoAdapter => write!(f, "no adapter supports the window surface"),
+            StateError::RequestDevice(e) => write!(f, "failed to request device: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
 impl State {
-    async fn new(window: &Window) -> Self {
+    async fn new(window: &Window) -> Result<Self, StateError> {
+        Self::with_assets(window, Scene::default_assets()).await
+    }
+
+    async fn with_assets(window: &Window, assets: AssetCache) -> Result<Self, StateError> {
 
         let size = window.inner_size();
 
@@ -25,25 +79,37 @@ impl State {
             backends: wgpu::Backends::all(),
             ..Default::default()
         });
-        let surface = unsafe { instance.create_surface(window).unwrap() };
+        let surface = unsafe { instance.create_surface(window) }.map_err(StateError::CreateSurface)?;
+        #[allow(clippy::filter_next)]
         let adapter = instance
             .enumerate_adapters(wgpu::Backends::all())
             .filter(|adapter| adapter.is_surface_supported(&surface))
             .next()
-            .unwrap();
+            .ok_or(StateError::NoAdapter)?;
 
+        // 支持时启用 BCn 压缩纹理，KTX2 纹理可免解码直接上传；间接绘制特性供 DynamicInstanceBuffer 使用；
+        // 格式扩展特性允许 WaterSimulator 把频谱写入 Rg32Float 存储纹理；
+        // 备用管线的片元着色器不读取顶点输出，需要 SHADER_UNUSED_VERTEX_OUTPUT；
+        // 时间戳查询只在性能分析时启用，供 GpuTimer 测量各通道耗时
+        // WebGL2 上去掉不支持的特性并改用 downlevel 限制，依赖它们的功能走回退路径
+        let capabilities = RenderCapabilities::negotiate(
+            &adapter,
+            wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::MULTI_DRAW_INDIRECT
+                | wgpu::Features::INDIRECT_FIRST_INSTANCE
+                | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+                | wgpu::Features::SHADER_UNUSED_VERTEX_OUTPUT
+                | if cfg!(feature = "profiling") { wgpu::Features::TIMESTAMP_QUERY } else { wgpu::Features::empty() },
+        );
         let (device, queue) = adapter.request_device(
             &wgpu::DeviceDescriptor {
-                features: wgpu::Features::empty(),
-                limits: if cfg!(target_arch = "wasm32") {
-                    wgpu::Limits::downlevel_webgl2_defaults()
-                } else {
-                    wgpu::Limits::default()
-                },
+                features: capabilities.features,
+                limits: capabilities.limits.clone(),
                 label: None
             },
             None
-        ).await.unwrap();
+        ).await.map_err(StateError::RequestDevice)?;
+        let device = Arc::new(device);
 
         let caps = surface.get_capabilities(&adapter);
         let config = wgpu::SurfaceConfiguration {
@@ -56,139 +122,182 @@ impl State {
             view_formats: vec![],
         };
 
+        // 初始化期间的校验错误只记录日志，不直接崩溃
+        let error_scope = ErrorScope::new(&device, "State::new");
         surface.configure(&device, &config);
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into())
-        });
-
-        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[]
-        });
+        let mut scene = Scene::new(
+            device.clone(),
+            queue,
+            capabilities,
+            config.format,
+            (config.width, config.height),
+            assets,
+            register_plugins,
+        );
+        // 错误作用域对整个设备生效，后台编译的管线必须在弹出作用域之前完成，其校验错误才会记在这里
+        scene.wait_for_pipelines();
+        #[cfg(feature = "profiling")]
+        let profiler = profiling::ProfilerWindow::new(window, &device, config.format);
+        error_scope.log_errors();
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[]
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL
-                })]
-            }),
-            // 图元（primitive）, 描述了将如何解释顶点来转换为三角形
-            primitive: wgpu::PrimitiveState {
-                // 每三个顶点组成一个三角形
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                // 告诉 wgpu 如何确定三角形的朝向
-                front_face: wgpu::FrontFace::Ccw,
-                // 告诉 wgpu 如何做三角形剔除
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false
-            },
-            // 多重采样
-            multisample: wgpu::MultisampleState {
-                // 确定管线将使用多少个采样
-                count: 1,
-                // 哪些采样应处于活动状态。目前我们使用全部采样
-                mask: !0,
-                // 与抗锯齿有关
-                alpha_to_coverage_enabled: false
-            },
-            depth_stencil: None,
-            // 表示渲染附件可以有多少数组层，不会渲染到数组纹理
-            multiview: None,
-        });
+        #[cfg(feature = "audio")]
+        let audio = {
+            let mut audio = audio::AudioManager::new();
+            for (name, path) in [("music", "assets/audio/music.ogg"), ("footstep", "assets/audio/footstep.ogg")] {
+                if let Err(e) = audio.load(name, path) {
+                    tracing::warn!("audio: failed to load {path}: {e}");
+                }
+            }
+            audio.play("music", 0.5, true);
+            audio
+        };
 
-        Self {
-            size,
-            surface,
-            device,
-            queue,
+        Ok(Self {
+            surface: Some(surface),
             config,
-            render_pipeline
-        }
+            size,
+            scene,
+            #[cfg(feature = "audio")]
+            audio,
+            #[cfg(feature = "audio")]
+            movement_keys: HashSet::new(),
+            #[cfg(feature = "audio")]
+            last_footstep: None,
+            #[cfg(feature = "profiling")]
+            profiler,
+        })
+    }
+
+    // 设备丢失后重建全部 GPU 对象，模拟状态从旧的 Scene 中接管。
+    // 旧表面（及其交换链）先释放再为同一窗口创建新的，否则 Vulkan 上可能报 ERROR_NATIVE_WINDOW_IN_USE_KHR
+    async fn recover_device(&mut self, window: &Window) -> Result<Self, StateError> {
+        self.surface = None;
+        let mut recovered = Self::with_assets(window, self.scene.assets().clone()).await?;
+        recovered.scene.adopt_simulation(&mut self.scene);
+        Ok(recovered)
     }
 
+    #[tracing::instrument(skip_all, fields(width = new_size.width, height = new_size.height))]
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(self.scene.device(), &self.config);
+            }
+            self.scene.resize(new_size.width, new_size.height);
         }
     }
 
     fn input(&mut self, event: &WindowEvent) -> bool {
-        false
-    }
+        #[cfg(feature = "profiling")]
+        if self.profiler.on_event(event) {
+            return true;
+        }
 
-    fn update(&mut self) {
+        // 只记录移动键的按下状态，脚步声在 step_frame 中按间隔播放；事件继续向下传递
+        #[cfg(feature = "audio")]
+        match event {
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state,
+                    virtual_keycode: Some(key @ (VirtualKeyCode::W | VirtualKeyCode::A | VirtualKeyCode::S | VirtualKeyCode::D)),
+                    ..
+                },
+                ..
+            } => {
+                if *state == ElementState::Pressed {
+                    self.movement_keys.insert(*key);
+                } else {
+                    self.movement_keys.remove(key);
+                }
+            }
+            // 失去焦点时收不到松开事件
+            WindowEvent::Focused(false) => self.movement_keys.clear(),
+            _ => {}
+        }
+
+        #[cfg(feature = "profiling")]
+        if matches!(
+            event,
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::F3),
+                    ..
+                },
+                ..
+            }
+        ) {
+            self.profiler.toggle();
+            return true;
+        }
 
+        self.scene.input(event)
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+    // 按住移动键时立即播放一次脚步声，之后每隔 FOOTSTEP_INTERVAL 播放一次
+    #[cfg(feature = "audio")]
+    fn play_footsteps(&mut self) {
+        if self.movement_keys.is_empty() {
+            self.last_footstep = None;
+            return;
+        }
+        let now = Instant::now();
+        if self.last_footstep.is_none_or(|last| now - last >= FOOTSTEP_INTERVAL) {
+            self.audio.play("footstep", 0.8, false);
+            self.last_footstep = Some(now);
+        }
+    }
 
-        let output = self.surface.get_current_texture()?;
+    // 推进一帧并渲染到交换链，性能分析窗口叠加在场景之上
+    #[tracing::instrument(skip_all, fields(frame_number = self.scene.frame_number(), surface_error = tracing::field::Empty))]
+    fn step_frame(&mut self, window: &Window) -> Result<(), wgpu::SurfaceError> {
+        // 表面在设备恢复时已释放，按丢失处理
+        let surface = self.surface.as_ref().ok_or(wgpu::SurfaceError::Lost)?;
+        let output = surface.get_current_texture().inspect_err(|e| {
+            tracing::Span::current().record("surface_error", tracing::field::debug(e));
+        })?;
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let mut encoder = self.device.create_command_encoder(
-            &wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder")
-            }
-        );
-
-        // 渲染通道
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[
-                // 这就是片元着色器中 @location(0) 标记指向的颜色附件
-                Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        // 告诉 wgpu 如何处理存储在前一帧的颜色
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0
-                        }),
-                        // 是否要将渲染的结果存储到纹理视图后面的纹理上
-                        store: wgpu::StoreOp::Store
-                    }
-                })
-            ],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
+        #[cfg(feature = "audio")]
+        self.play_footsteps();
+        let device = self.scene.device().clone();
+        let error_scope = ErrorScope::new(&device, "State::render");
+        let timings = self.scene.step_frame(&view, (self.config.width, self.config.height));
+        error_scope.log_errors();
 
-        render_pass.set_pipeline(&self.render_pipeline);
-        // 告诉 wgpu 用 3 个顶点和 1 个实例
-        render_pass.draw(0..3, 0..1);
-
-        drop(render_pass);
+        #[cfg(feature = "profiling")]
+        {
+            if let Some(timings) = &timings {
+                self.profiler.set_gpu_timings(timings);
+            }
+            let (device, queue) = (self.scene.device(), self.scene.queue());
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Profiler Encoder"),
+            });
+            let profiler_commands = self.profiler.render(
+                &mut encoder,
+                device,
+                queue,
+                window,
+                &view,
+                [self.config.width, self.config.height],
+            );
+            queue.submit(profiler_commands.into_iter().chain(std::iter::once(encoder.finish())));
+        }
+        #[cfg(not(feature = "profiling"))]
+        let _ = (window, timings);
 
-        self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
-
         Ok(())
     }
 }
 
+// 在这里注册渲染插件，按注册顺序更新与绘制
+fn register_plugins(_app: &mut AppBuilder) {}
+
 #[tokio::main]
 async fn main() {
     run().await;
@@ -196,11 +305,14 @@ async fn main() {
 
 pub async fn run() {
 
-    env_logger::init();
+    logging::init();
+    profiling::init();
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let window = WindowBuilder::new().with_title(WINDOW_TITLE).build(&event_loop).unwrap();
 
-    let mut state = State::new(&window).await;
+    let mut state = State::new(&window).await.expect("failed to initialize GPU state");
+    // 连续丢失交换链的帧数，重新配置无效时视为设备丢失
+    let mut lost_frames = 0;
 
     event_loop.run(move |event, _, control_flow| {
         match event {
@@ -209,17 +321,37 @@ pub async fn run() {
                 window.request_redraw();
             }
             Event::RedrawRequested(window_id) if window_id == window.id() => {
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
+                profiling::new_frame();
+                match state.step_frame(&window) {
+                    Ok(_) => lost_frames = 0,
                     // 当展示平面的上下文丢失，就需重新配置
-                    Err(wgpu::SurfaceError::Lost) => state.resize(state.size),
+                    Err(wgpu::SurfaceError::Lost) if lost_frames == 0 => {
+                        lost_frames += 1;
+                        state.resize(state.size);
+                    }
+                    // 重新配置后仍然丢失，重建设备与所有资源
+                    Err(wgpu::SurfaceError::Lost) => {
+                        let recovered = tokio::task::block_in_place(|| {
+                            tokio::runtime::Handle::current().block_on(state.recover_device(&window))
+                        });
+                        match recovered {
+                            Ok(recovered) => {
+                                state = recovered;
+                                lost_frames = 0;
+                            }
+                            Err(e) => {
+                                tracing::error!(error = %e, "device recovery failed");
+                                *control_flow = ControlFlow::ExitWithCode(1);
+                            }
+                        }
+                    }
                     // 系统内存不足时，程序应该退出。
                     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::ExitWithCode(0),
                     // 所有其他错误（过期、超时等）应在下一帧解决
-                    Err(e) => eprintln!("{:?}", e)
+                    Err(e) => tracing::error!(surface_error = ?e, frame_number = state.scene.frame_number(), "failed to render frame")
                 }
             }
+            #[allow(clippy::collapsible_match)]
             Event::WindowEvent { ref event, window_id } if window_id == window.id() => {
                 if !state.input(event) {
                     match event {
@@ -229,6 +361,41 @@ pub async fn run() {
                         WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                             state.resize(**new_inner_size);
                         }
+                        // G 循环切换 G-buffer 调试视图，标题栏显示当前通道
+                        WindowEvent::KeyboardInput {
+                            input: KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::G),
+                                ..
+                            },
+                            ..
+                        } => match state.scene.cycle_gbuffer_debug_mode() {
+                            GBufferDebugMode::None => window.set_title(WINDOW_TITLE),
+                            mode => window.set_title(&format!("{WINDOW_TITLE} - G-Buffer: {mode}")),
+                        },
+                        // M 循环切换聚光灯的阴影算法（PCF / VSM / ESM / MSM）
+                        WindowEvent::KeyboardInput {
+                            input: KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::M),
+                                ..
+                            },
+                            ..
+                        } => {
+                            let mode = state.scene.cycle_shadow_mode();
+                            window.set_title(&format!("{WINDOW_TITLE} - Shadows: {mode}"));
+                        }
+                        WindowEvent::KeyboardInput {
+                            input: KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::I),
+                                ..
+                            },
+                            ..
+                        } => {
+                            let status = if state.scene.toggle_ssgi() { "on" } else { "off" };
+                            window.set_title(&format!("{WINDOW_TITLE} - SSGI: {status}"));
+                        }
                         WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
                             input: KeyboardInput {
                                 state: ElementState::Released,
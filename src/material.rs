@@ -0,0 +1,231 @@
+use crate::texture::{ Texture, TextureHandle };
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialUniform {
+    // a 分量即不透明度
+    pub base_color: [f32; 4],
+    pub emissive_color: [f32; 3],
+    // 按基础色叠加的自发光倍数，没有物理单位，由 MaterialAnimator 驱动
+    pub emissive_intensity: f32,
+    // emissive_color 的亮度，单位为尼特（cd/m²）
+    pub emissive_intensity_nits: f32,
+    // 以下三项目前只写入 G-buffer，前向光照尚未使用
+    pub roughness: f32,
+    pub metallic: f32,
+    // 环境光遮蔽，1 为不遮蔽
+    pub ao: f32,
+    // 次表面散射参数，见 SSSMaterial；只在前向光照中使用
+    pub scatter_color: [f32; 3],
+    pub scatter_width: f32,
+    pub subsurface_weight: f32,
+    // 纹理坐标变换，见 UvTransform
+    pub uv_rotation: f32,
+    pub uv_scale: [f32; 2],
+    pub uv_offset: [f32; 2],
+    // 每秒加到 uv_offset 上的量，由 update_uv_scroll 推进，着色器不读取
+    pub uv_scroll: [f32; 2],
+    // 三平面映射的参数，见 TriplanarShader
+    pub triplanar_scale: f32,
+    pub triplanar_sharpness: f32,
+    pub _padding: [f32; 2],
+}
+
+impl Default for MaterialUniform {
+    fn default() -> Self {
+        Self {
+            base_color: [0.3, 0.2, 0.1, 1.0],
+            emissive_color: [0.0; 3],
+            emissive_intensity: 0.0,
+            emissive_intensity_nits: 0.0,
+            roughness: 0.5,
+            metallic: 0.0,
+            ao: 1.0,
+            scatter_color: [1.0; 3],
+            scatter_width: 1.0,
+            subsurface_weight: 0.0,
+            uv_rotation: 0.0,
+            uv_scale: [1.0; 2],
+            uv_offset: [0.0; 2],
+            uv_scroll: [0.0; 2],
+            triplanar_scale: 1.0,
+            triplanar_sharpness: 4.0,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+// 皮肤、蜡、树叶等半透明材质的次表面散射近似：从背面照入的光穿过物体，
+// 按穿透距离 exp(-dist / scatter_width) 衰减并乘以 scatter_color 作为透射项，
+// 再按 subsurface_weight 在普通漫反射与加上透射项的结果之间插值。
+// 穿透距离由阴影图集求得，因此只有投射阴影的聚光灯产生透射
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SSSMaterial {
+    pub scatter_color: [f32; 3],
+    // 透射强度衰减到 1/e 的穿透距离，世界单位
+    pub scatter_width: f32,
+    pub subsurface_weight: f32,
+}
+
+impl SSSMaterial {
+    pub fn skin() -> Self {
+        Self {
+            scatter_color: [1.0, 0.3, 0.2],
+            scatter_width: 0.05,
+            subsurface_weight: 0.5,
+        }
+    }
+}
+
+// 顶点着色器中的纹理坐标变换：先绕 (0.5, 0.5) 旋转 rotation 弧度，再乘以 scale（平铺次数）、加上 offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    pub scale: [f32; 2],
+    pub offset: [f32; 2],
+    pub rotation: f32,
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        Self {
+            scale: [1.0; 2],
+            offset: [0.0; 2],
+            rotation: 0.0,
+        }
+    }
+}
+
+impl MaterialUniform {
+    // 着色器按 ACES 色调映射的 80 cd/m² 参考白换算为场景亮度
+    pub fn set_emissive(&mut self, color: [f32; 3], nits: f32) -> &mut Self {
+        self.emissive_color = color;
+        self.emissive_intensity_nits = nits.max(0.0);
+        self
+    }
+
+    pub fn set_subsurface(&mut self, sss: SSSMaterial) -> &mut Self {
+        self.scatter_color = sss.scatter_color;
+        self.scatter_width = sss.scatter_width.max(1e-4);
+        self.subsurface_weight = sss.subsurface_weight.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn set_uv_transform(&mut self, transform: UvTransform) -> &mut Self {
+        self.uv_scale = transform.scale;
+        self.uv_offset = transform.offset;
+        self.uv_rotation = transform.rotation;
+        self
+    }
+
+    pub fn uv_transform(&self) -> UvTransform {
+        UvTransform {
+            scale: self.uv_scale,
+            offset: self.uv_offset,
+            rotation: self.uv_rotation,
+        }
+    }
+
+    // 水面、传送带等表面的纹理滚动，单位为每秒的纹理坐标
+    pub fn set_uv_scroll(&mut self, velocity: [f32; 2]) -> &mut Self {
+        self.uv_scroll = velocity;
+        self
+    }
+
+    // 每帧调用，按 uv_scroll 推进 uv_offset；纹理按 1 重复，取小数部分避免长时间运行后丢失精度
+    pub fn update_uv_scroll(&mut self, dt: f32) {
+        for (offset, velocity) in self.uv_offset.iter_mut().zip(self.uv_scroll) {
+            *offset = (*offset + velocity * dt).rem_euclid(1.0);
+        }
+    }
+
+    // scale 为每个世界单位的纹理重复次数；sharpness 越大，三个投影之间的过渡越窄
+    pub fn set_triplanar(&mut self, scale: f32, sharpness: f32) -> &mut Self {
+        self.triplanar_scale = scale;
+        self.triplanar_sharpness = sharpness.max(1.0);
+        self
+    }
+
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("material_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    // lightmap 为材质的光照贴图，没有时传 1 × 1 的白色纹理
+    pub fn bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, buffer: &wgpu::Buffer, lightmap: &Texture) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("material_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&lightmap.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::Sampler(&lightmap.sampler),
+                },
+            ],
+        })
+    }
+}
+
+// 材质参数与可选的纹理槽。纹理槽只记录 TextureHandle，
+// 由持有纹理的系统解析后传给 MaterialUniform::bind_group
+#[derive(Debug, Clone, Copy)]
+pub struct Material {
+    pub uniform: MaterialUniform,
+    lightmap: Option<TextureHandle>,
+}
+
+impl Material {
+    pub fn new(uniform: MaterialUniform) -> Self {
+        Self { uniform, lightmap: None }
+    }
+
+    // 光照贴图按顶点的 uv1 采样，乘在漫反射上；传 None 取消
+    pub fn set_lightmap_texture(&mut self, texture: Option<TextureHandle>) -> &mut Self {
+        self.lightmap = texture;
+        self
+    }
+
+    pub fn lightmap_texture(&self) -> Option<TextureHandle> {
+        self.lightmap
+    }
+}
+
+// 材质的标识，具体的绑定组由调用方管理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaterialHandle(pub u32);
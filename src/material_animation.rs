@@ -0,0 +1,105 @@
+use std::ops::RangeInclusive;
+
+use crate::material::MaterialUniform;
+use crate::spline::Lerp;
+
+// 按时间排序的关键帧，关键帧之间线性插值
+#[derive(Debug, Clone, Default)]
+pub struct Curve<T> {
+    pub keyframes: Vec<(f32, T)>,
+}
+
+impl<T: Lerp + Copy> Curve<T> {
+    pub fn new(mut keyframes: Vec<(f32, T)>) -> Self {
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keyframes }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |(time, _)| *time)
+    }
+
+    // 超出首尾关键帧时保持端点值
+    pub fn evaluate(&self, t: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        if t <= first.0 {
+            return Some(first.1);
+        }
+
+        let next = self.keyframes.partition_point(|(time, _)| *time <= t);
+        if next == self.keyframes.len() {
+            return self.keyframes.last().map(|(_, value)| *value);
+        }
+
+        let (t0, v0) = self.keyframes[next - 1];
+        let (t1, v1) = self.keyframes[next];
+        let span = t1 - t0;
+        let s = if span > 0.0 { (t - t0) / span } else { 1.0 };
+        Some(v0.lerp(&v1, s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialParamId {
+    EmissiveIntensity,
+    Opacity,
+}
+
+impl MaterialParamId {
+    fn write(self, uniform: &mut MaterialUniform, value: f32) {
+        match self {
+            MaterialParamId::EmissiveIntensity => uniform.emissive_intensity = value,
+            MaterialParamId::Opacity => uniform.base_color[3] = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MaterialAnimator {
+    pub entries: Vec<(MaterialParamId, Curve<f32>)>,
+    pub looping: bool,
+}
+
+impl MaterialAnimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn animate(&mut self, param: MaterialParamId, curve: Curve<f32>) -> &mut Self {
+        self.entries.push((param, curve));
+        self
+    }
+
+    pub fn animate_emissive_intensity(&mut self, range: RangeInclusive<f32>, duration_secs: f32) -> &mut Self {
+        self.animate(MaterialParamId::EmissiveIntensity, linear_curve(range, duration_secs))
+    }
+
+    pub fn animate_opacity(&mut self, range: RangeInclusive<f32>, duration_secs: f32) -> &mut Self {
+        self.animate(MaterialParamId::Opacity, linear_curve(range, duration_secs))
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.entries.iter().map(|(_, curve)| curve.duration()).fold(0.0, f32::max)
+    }
+
+    // t 为动画开始后经过的秒数
+    pub fn update(&self, t: f32, material_uniform: &mut MaterialUniform) {
+        let duration = self.duration();
+        let t = if self.looping && duration > 0.0 { t.rem_euclid(duration) } else { t };
+
+        for (param, curve) in &self.entries {
+            if let Some(value) = curve.evaluate(t) {
+                param.write(material_uniform, value);
+            }
+        }
+    }
+}
+
+fn linear_curve(range: RangeInclusive<f32>, duration_secs: f32) -> Curve<f32> {
+    Curve::new(vec![(0.0, *range.start()), (duration_secs, *range.end())])
+}
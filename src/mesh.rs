@@ -0,0 +1,89 @@
+use wgpu::util::DeviceExt;
+
+use crate::aabb::Aabb;
+use crate::convex_hull::ConvexHull;
+use crate::vertex_format::{ AttributeSource, VertexAttribute, VertexFormat };
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coords: [f32; 2],
+    // 第二套纹理坐标，光照贴图使用；没有单独展开时与 tex_coords 相同
+    pub uv1: [f32; 2],
+}
+
+impl Vertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x2,
+        3 => Float32x2,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+
+    // 与 desc 相同的交错布局；阴影通道可用 format().select(&[0]) 只读取位置
+    pub fn format() -> VertexFormat {
+        let attribute = |shader_location, format| VertexAttribute { shader_location, format };
+        VertexFormat::new()
+            .interleaved_stride(std::mem::size_of::<Vertex>() as wgpu::BufferAddress)
+            .attribute(attribute(0, wgpu::VertexFormat::Float32x3), AttributeSource::Interleaved { offset: 0 })
+            .attribute(attribute(1, wgpu::VertexFormat::Float32x3), AttributeSource::Interleaved { offset: 12 })
+            .attribute(attribute(2, wgpu::VertexFormat::Float32x2), AttributeSource::Interleaved { offset: 24 })
+            .attribute(attribute(3, wgpu::VertexFormat::Float32x2), AttributeSource::Interleaved { offset: 32 })
+    }
+}
+
+// CPU 端的网格数据
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+}
+
+impl MeshData {
+    pub fn index_count(&self) -> u32 {
+        self.indices.len() as u32
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_vertices(&self.vertices)
+    }
+
+    pub fn convex_hull(&self) -> ConvexHull {
+        let positions: Vec<[f32; 3]> = self.vertices.iter().map(|vertex| vertex.position).collect();
+        ConvexHull::from_vertices(&positions)
+    }
+
+    // 创建顶点缓冲区与索引缓冲区
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::Buffer, wgpu::Buffer) {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(&self.vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // write_buffer 要求字节数是 COPY_BUFFER_ALIGNMENT 的倍数，u16 索引可能需要补齐
+        let index_bytes: &[u8] = bytemuck::cast_slice(&self.indices);
+        let padded_size = wgpu::util::align_to(index_bytes.len() as wgpu::BufferAddress, wgpu::COPY_BUFFER_ALIGNMENT);
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mesh Index Buffer"),
+            size: padded_size,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut padded = index_bytes.to_vec();
+        padded.resize(padded_size as usize, 0);
+        queue.write_buffer(&index_buffer, 0, &padded);
+
+        (vertex_buffer, index_buffer)
+    }
+}
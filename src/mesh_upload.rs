@@ -0,0 +1,151 @@
+use std::cell::Cell;
+use std::sync::mpsc::{ self, Receiver, Sender };
+use std::sync::Arc;
+
+use cgmath::Matrix4;
+
+use crate::aabb::Aabb;
+use crate::convex_hull::ConvexHull;
+use crate::frustum::Frustum;
+use crate::mesh::MeshData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshHandle(u32);
+
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    // 上传时由顶点位置计算的局部包围盒
+    pub local_aabb: Aabb,
+    // 局部凸包，比包围盒贴合，用于精确剔除
+    pub local_hull: ConvexHull,
+    // 最近一次请求的模型矩阵及对应的世界包围盒
+    world_aabb: Cell<Option<([[f32; 4]; 4], Aabb)>>,
+}
+
+impl GpuMesh {
+    // 模型矩阵不变时直接返回上次的结果
+    pub fn world_aabb(&self, model: &[[f32; 4]; 4]) -> Aabb {
+        if let Some((cached_model, aabb)) = self.world_aabb.get() {
+            if cached_model == *model {
+                return aabb;
+            }
+        }
+        let aabb = self.local_aabb.transformed(model);
+        self.world_aabb.set(Some((*model, aabb)));
+        aabb
+    }
+
+    // 先用世界包围盒粗略剔除，再在模型空间用凸包测试，不必变换凸包的顶点
+    pub fn is_visible(&self, view_proj: Matrix4<f32>, model: &[[f32; 4]; 4]) -> bool {
+        Frustum::from_matrix(view_proj).intersects_aabb(&self.world_aabb(model))
+            && self.local_hull.intersects_frustum(&Frustum::from_matrix(view_proj * Matrix4::from(*model)))
+    }
+}
+
+struct UploadedMesh {
+    handle: MeshHandle,
+    mesh: GpuMesh,
+    commands: wgpu::CommandBuffer,
+}
+
+// 在工作线程上创建暂存缓冲区并录制复制命令，主线程每帧统一提交
+pub struct MeshUploader {
+    device: Arc<wgpu::Device>,
+    sender: Sender<UploadedMesh>,
+    receiver: Receiver<UploadedMesh>,
+    next_handle: u32,
+    pending: usize,
+}
+
+impl MeshUploader {
+    pub fn new(device: Arc<wgpu::Device>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { device, sender, receiver, next_handle: 0, pending: 0 }
+    }
+
+    // 必须在 tokio 运行时中调用
+    pub fn upload(&mut self, mesh: Arc<MeshData>) -> MeshHandle {
+        let handle = MeshHandle(self.next_handle);
+        self.next_handle += 1;
+        self.pending += 1;
+
+        let device = self.device.clone();
+        let sender = self.sender.clone();
+        tokio::task::spawn_blocking(move || {
+            let (mesh, commands) = record_upload(&device, &mesh);
+            // 接收端已销毁说明 State 已被替换，直接丢弃
+            let _ = sender.send(UploadedMesh { handle, mesh, commands });
+        });
+        handle
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    // 取出已录制完成的上传，返回的命令缓冲区必须先于使用这些网格的命令提交
+    pub fn collect(&mut self) -> (Vec<wgpu::CommandBuffer>, Vec<(MeshHandle, GpuMesh)>) {
+        let mut commands = Vec::new();
+        let mut meshes = Vec::new();
+        for uploaded in self.receiver.try_iter() {
+            commands.push(uploaded.commands);
+            meshes.push((uploaded.handle, uploaded.mesh));
+        }
+        self.pending -= meshes.len();
+        (commands, meshes)
+    }
+}
+
+fn record_upload(device: &wgpu::Device, mesh: &MeshData) -> (GpuMesh, wgpu::CommandBuffer) {
+    let vertex_bytes: &[u8] = bytemuck::cast_slice(&mesh.vertices);
+    let index_bytes: &[u8] = bytemuck::cast_slice(&mesh.indices);
+
+    let (vertex_staging, vertex_size) = create_staging_buffer(device, vertex_bytes, "Mesh Vertex Staging Buffer");
+    let (index_staging, index_size) = create_staging_buffer(device, index_bytes, "Mesh Index Staging Buffer");
+
+    let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Mesh Vertex Buffer"),
+        size: vertex_size,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Mesh Index Buffer"),
+        size: index_size,
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mesh Upload Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(&vertex_staging, 0, &vertex_buffer, 0, vertex_size);
+    encoder.copy_buffer_to_buffer(&index_staging, 0, &index_buffer, 0, index_size);
+
+    let mesh = GpuMesh {
+        vertex_buffer,
+        index_buffer,
+        index_count: mesh.index_count(),
+        local_aabb: mesh.aabb(),
+        local_hull: mesh.convex_hull(),
+        world_aabb: Cell::new(None),
+    };
+    (mesh, encoder.finish())
+}
+
+// 复制大小必须是 COPY_BUFFER_ALIGNMENT 的倍数，u16 索引可能需要补齐
+fn create_staging_buffer(device: &wgpu::Device, bytes: &[u8], label: &str) -> (wgpu::Buffer, wgpu::BufferAddress) {
+    let size = wgpu::util::align_to(bytes.len() as wgpu::BufferAddress, wgpu::COPY_BUFFER_ALIGNMENT)
+        .max(wgpu::COPY_BUFFER_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size,
+        usage: wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: true,
+    });
+    buffer.slice(..).get_mapped_range_mut()[..bytes.len()].copy_from_slice(bytes);
+    buffer.unmap();
+    (buffer, size)
+}
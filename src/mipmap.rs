@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::error_scope::ErrorScope;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+#[derive(Debug)]
+pub enum MipmapError {
+    UnsupportedFormat(wgpu::TextureFormat),
+    // 都需要 COPY_SRC；计算路径另需 STORAGE_BINDING，sRGB 另需 RENDER_ATTACHMENT
+    MissingUsage(wgpu::TextureUsages),
+    // 生成过程中捕获到的 wgpu 校验错误
+    Validation(Vec<wgpu::Error>),
+}
+
+impl fmt::Display for MipmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MipmapError::UnsupportedFormat(format) => write!(f, "cannot generate mipmaps for format {format:?}"),
+            MipmapError::MissingUsage(usage) => write!(f, "texture used for mipmap generation is missing usage {usage:?}"),
+            MipmapError::Validation(errors) => {
+                write!(f, "{} wgpu validation error(s) while generating mipmaps", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  - {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MipmapError {}
+
+// 逐级对上一级做 2x2 平均，生成 1..mip_level_count 的所有 mip。
+// Rgba8Unorm 与 Rgba16Float 用计算着色器写存储纹理；Rgba8UnormSrgb 不能作为存储纹理，
+// 改为渲染到下一级，采样时解码、写入时编码，平均在线性空间中进行。
+// GL 后端无法采样非零 mip 或层的视图，上一级的每一层先复制到暂存纹理再读取
+pub fn generate_mipmaps(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    array_layer_count: u32,
+) -> Result<(), MipmapError> {
+    let required = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba16Float => wgpu::TextureUsages::STORAGE_BINDING,
+        wgpu::TextureFormat::Rgba8UnormSrgb => wgpu::TextureUsages::RENDER_ATTACHMENT,
+        _ => return Err(MipmapError::UnsupportedFormat(format)),
+    } | wgpu::TextureUsages::COPY_SRC;
+    if !texture.usage().contains(required) {
+        return Err(MipmapError::MissingUsage(required - texture.usage()));
+    }
+    if texture.mip_level_count() <= 1 {
+        return Ok(());
+    }
+
+    let error_scope = ErrorScope::new(device, "generate_mipmaps");
+    let downsampler = Downsampler::new(device, format);
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+    let size = texture.size();
+    for level in 1..texture.mip_level_count() {
+        let source_size = wgpu::Extent3d {
+            width: (size.width >> (level - 1)).max(1),
+            height: (size.height >> (level - 1)).max(1),
+            depth_or_array_layers: 1,
+        };
+        let scratch = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Mipmap Scratch Texture"),
+            size: source_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let scratch_view = scratch.create_view(&wgpu::TextureViewDescriptor::default());
+        for layer in 0..array_layer_count {
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: level - 1,
+                    origin: wgpu::Origin3d { x: 0, y: 0, z: layer },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                scratch.as_image_copy(),
+                source_size,
+            );
+            let destination = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mipmap View"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let (width, height) = ((size.width >> level).max(1), (size.height >> level).max(1));
+            downsampler.encode(device, &mut encoder, &scratch_view, &destination, (width, height));
+        }
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let errors = error_scope.take_errors();
+    if errors.is_empty() { Ok(()) } else { Err(MipmapError::Validation(errors)) }
+}
+
+enum DownsamplePipeline {
+    Compute(wgpu::ComputePipeline),
+    Render(wgpu::RenderPipeline),
+}
+
+struct Downsampler {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: DownsamplePipeline,
+}
+
+impl Downsampler {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("mipmap.wgsl").into()),
+        });
+        let source_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        if format == wgpu::TextureFormat::Rgba8UnormSrgb {
+            let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("mipmap_render_bind_group_layout"),
+                entries: &[source_entry(wgpu::ShaderStages::FRAGMENT)],
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Mipmap Render Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Mipmap Render Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(format.into())],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+            return Self { bind_group_layout, pipeline: DownsamplePipeline::Render(pipeline) };
+        }
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("mipmap_bind_group_layout"),
+            entries: &[
+                source_entry(wgpu::ShaderStages::COMPUTE),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mipmap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Mipmap Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: if format == wgpu::TextureFormat::Rgba16Float { "cs_rgba16" } else { "cs_rgba8" },
+        });
+        Self { bind_group_layout, pipeline: DownsamplePipeline::Compute(pipeline) }
+    }
+
+    // 从 source 读取并写入 destination，size 为 destination 的尺寸
+    fn encode(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        destination: &wgpu::TextureView,
+        (width, height): (u32, u32),
+    ) {
+        match &self.pipeline {
+            DownsamplePipeline::Compute(pipeline) => {
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("mipmap_bind_group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(source),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(destination),
+                        },
+                    ],
+                });
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Mipmap Pass"),
+                    timestamp_writes: None,
+                });
+                compute_pass.set_pipeline(pipeline);
+                compute_pass.set_bind_group(0, &bind_group, &[]);
+                compute_pass.dispatch_workgroups(width.div_ceil(WORKGROUP_SIZE), height.div_ceil(WORKGROUP_SIZE), 1);
+            }
+            DownsamplePipeline::Render(pipeline) => {
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("mipmap_render_bind_group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    }],
+                });
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Mipmap Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: destination,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+    }
+}
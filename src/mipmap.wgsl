@@ -0,0 +1,56 @@
+// 复制到暂存纹理的上一级 mip 的一层，按纹素读取；sRGB 读取时已解码到线性空间
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+
+// 2x2 纹素取平均，尺寸为奇数时最后一行/列与自身平均
+fn downsample(texel: vec2<i32>) -> vec4f {
+    let source_size = vec2<i32>(textureDimensions(t_source));
+    let base = texel * 2;
+    var sum = vec4f(0.0);
+    for (var y = 0; y < 2; y++) {
+        for (var x = 0; x < 2; x++) {
+            sum += textureLoad(t_source, min(base + vec2<i32>(x, y), source_size - 1), 0);
+        }
+    }
+    return sum * 0.25;
+}
+
+// ---- Rgba8Unorm ----
+
+@group(0) @binding(1)
+var t_destination_rgba8: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(8, 8)
+fn cs_rgba8(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= textureDimensions(t_destination_rgba8)) {
+        return;
+    }
+    textureStore(t_destination_rgba8, vec2<i32>(id.xy), downsample(vec2<i32>(id.xy)));
+}
+
+// ---- Rgba16Float ----
+
+@group(0) @binding(1)
+var t_destination_rgba16: texture_storage_2d<rgba16float, write>;
+
+@compute @workgroup_size(8, 8)
+fn cs_rgba16(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= textureDimensions(t_destination_rgba16)) {
+        return;
+    }
+    textureStore(t_destination_rgba16, vec2<i32>(id.xy), downsample(vec2<i32>(id.xy)));
+}
+
+// ---- Rgba8UnormSrgb：sRGB 格式不能作为存储纹理，渲染到下一级 ----
+// 读取时解码、写入附件时再编码，平均在线性空间中进行
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    return downsample(vec2<i32>(position.xy));
+}
@@ -0,0 +1,41 @@
+// 矩阴影图（Peters & Klein 2015）的 Hamburger 4MSM 重建。
+// moments 为 (z, z², z³, z⁴) 经模糊后的期望，z 为映射到 [-1, 1] 的深度；
+// moment_bias 把矩向 (0, 0.63, 0, 0.63) 偏移，保证 Hankel 矩阵正定，32 位浮点取 3e-5 左右即可
+
+// 返回深度为 depth 的片元被遮挡的概率：0 完全受光，1 完全在阴影中
+fn compute_shadow_probability(moments: vec4f, depth: f32, moment_bias: f32) -> f32 {
+    let b = mix(moments, vec4f(0.0, 0.63, 0.0, 0.63), moment_bias);
+    // Hankel 矩阵的 Cholesky 分解
+    let l32_d22 = -b.x * b.y + b.z;
+    let d22 = -b.x * b.x + b.y;
+    let squared_depth_variance = -b.y * b.y + b.w;
+    let d33_d22 = dot(vec2f(squared_depth_variance, -l32_d22), vec2f(d22, l32_d22));
+    let inv_d22 = 1.0 / d22;
+    let l32 = l32_d22 * inv_d22;
+
+    // 解出以 depth 为一个支撑点的二次多项式系数 c
+    var c = vec3f(1.0, depth, depth * depth);
+    c.y -= b.x;
+    c.z -= b.y + l32 * c.y;
+    c.y *= inv_d22;
+    c.z *= d22 / d33_d22;
+    c.y -= l32 * c.z;
+    c.x -= dot(c.yz, b.xy);
+
+    // 另外两个支撑点为二次方程的根
+    let p = c.y / c.z;
+    let q = c.x / c.z;
+    let r = sqrt(max(p * p * 0.25 - q, 0.0));
+    let z1 = -p * 0.5 - r;
+    let z2 = -p * 0.5 + r;
+
+    // 按 depth 相对两个根的位置选择插值的支撑点和权重
+    var switch_value = vec4f(0.0);
+    if z2 < depth {
+        switch_value = vec4f(z1, depth, 1.0, 1.0);
+    } else if z1 < depth {
+        switch_value = vec4f(depth, z1, 0.0, 1.0);
+    }
+    let quotient = (switch_value.x * z2 - b.x * (switch_value.x + z2) + b.y) / ((z2 - switch_value.y) * (depth - z1));
+    return saturate(switch_value.z + switch_value.w * quotient);
+}
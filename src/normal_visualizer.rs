@@ -0,0 +1,239 @@
+use cgmath::{ Matrix4, SquareMatrix };
+
+use crate::camera::Camera;
+use crate::mesh::Vertex;
+
+const WORKGROUP_SIZE: u32 = 64;
+// 每个三角形输出两个 vec4f
+const LINE_VERTEX_SIZE: wgpu::BufferAddress = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct NormalVisualizerUniform {
+    view_proj: [[f32; 4]; 4],
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+    length: f32,
+    _padding: [f32; 3],
+}
+
+// wgpu 没有几何着色器：先用计算着色器把每个三角形展开成一条从重心沿面法线伸出的线段，
+// 再以 LineList 绘制展开结果，用于检查网格的朝向
+pub struct NormalVisualizer {
+    pub length: f32,
+    pub color: [f32; 4],
+    model: Matrix4<f32>,
+    compute_pipeline: wgpu::ComputePipeline,
+    compute_bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    render_bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    line_buffer: wgpu::Buffer,
+    // line_buffer 能容纳的三角形数
+    capacity: u32,
+    // 最近一次 render 展开的三角形数
+    triangle_count: u32,
+}
+
+impl NormalVisualizer {
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Normal Visualizer Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("normal_visualizer.wgsl").into()),
+        });
+
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE | wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("normal_visualizer_compute_bind_group_layout"),
+            entries: &[uniform_entry, storage_entry(1, true), storage_entry(2, false)],
+        });
+        let render_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("normal_visualizer_render_bind_group_layout"),
+            entries: &[uniform_entry],
+        });
+
+        let compute_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Normal Visualizer Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Normal Visualizer Compute Pipeline"),
+            layout: Some(&compute_layout),
+            module: &shader,
+            entry_point: "cs_expand",
+        });
+
+        let render_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Normal Visualizer Render Pipeline Layout"),
+            bind_group_layouts: &[&render_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Normal Visualizer Render Pipeline"),
+            layout: Some(&render_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: LINE_VERTEX_SIZE,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x4],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            // 只做深度测试，不遮挡之后绘制的物体
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Normal Visualizer Uniform Buffer"),
+            size: std::mem::size_of::<NormalVisualizerUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("normal_visualizer_render_bind_group"),
+            layout: &render_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let capacity = 1024;
+        Self {
+            length: 0.1,
+            color: [1.0, 1.0, 0.0, 1.0],
+            model: Matrix4::identity(),
+            compute_pipeline,
+            compute_bind_group_layout,
+            render_pipeline,
+            render_bind_group,
+            uniform_buffer,
+            line_buffer: Self::create_line_buffer(device, capacity),
+            capacity,
+            triangle_count: 0,
+        }
+    }
+
+    fn create_line_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Normal Visualizer Line Buffer"),
+            size: capacity as wgpu::BufferAddress * 2 * LINE_VERTEX_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn set_model(&mut self, model: Matrix4<f32>) {
+        self.model = model;
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera) {
+        let uniform = NormalVisualizerUniform {
+            view_proj: camera.build_view_projection_matrix().into(),
+            model: self.model.into(),
+            color: self.color,
+            length: self.length,
+            _padding: [0.0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    // 展开 mesh_buffer 中前 vertex_count 个顶点组成的三角形列表（不带索引，每 3 个顶点一个三角形）
+    // mesh_buffer 需要带 STORAGE 用途，顶点布局为 mesh::Vertex
+    pub fn render(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, mesh_buffer: &wgpu::Buffer, vertex_count: u32) {
+        self.triangle_count = vertex_count / 3;
+        if self.triangle_count == 0 {
+            return;
+        }
+        if self.triangle_count > self.capacity {
+            self.capacity = self.triangle_count.next_power_of_two();
+            self.line_buffer = Self::create_line_buffer(device, self.capacity);
+        }
+
+        // 只绑定用到的顶点，着色器据此判断边界
+        let vertex_bytes = self.triangle_count as wgpu::BufferAddress * 3 * std::mem::size_of::<Vertex>() as wgpu::BufferAddress;
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("normal_visualizer_compute_bind_group"),
+            layout: &self.compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: mesh_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(vertex_bytes),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.line_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Normal Visualizer Expand Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.triangle_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    // 绘制最近一次 render 展开的线段
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.triangle_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.render_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.line_buffer.slice(..));
+        render_pass.draw(0..self.triangle_count * 2, 0..1);
+    }
+}
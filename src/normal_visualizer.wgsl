@@ -0,0 +1,55 @@
+struct NormalVisualizerUniform {
+    view_proj: mat4x4f,
+    model: mat4x4f,
+    color: vec4f,
+    // 线段长度，世界空间
+    length: f32,
+};
+@group(0) @binding(0)
+var<uniform> uniforms: NormalVisualizerUniform;
+
+// ---- 计算着色器：每个三角形展开成一条线段，代替几何着色器 ----
+
+// 网格顶点缓冲区按 f32 读取，布局与 mesh::Vertex 相同：position(3) normal(3) tex_coords(2)
+const VERTEX_STRIDE: u32 = 8u;
+
+@group(0) @binding(1)
+var<storage, read> vertices: array<f32>;
+// 每个三角形两个顶点：重心与沿法线偏移后的点
+@group(0) @binding(2)
+var<storage, read_write> lines: array<vec4f>;
+
+fn world_position(index: u32) -> vec3f {
+    let base = index * VERTEX_STRIDE;
+    let position = vec3f(vertices[base], vertices[base + 1u], vertices[base + 2u]);
+    return (uniforms.model * vec4f(position, 1.0)).xyz;
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn cs_expand(@builtin(global_invocation_id) id: vec3u) {
+    let triangle = id.x;
+    if (triangle * 3u + 2u) * VERTEX_STRIDE >= arrayLength(&vertices) {
+        return;
+    }
+    let a = world_position(triangle * 3u);
+    let b = world_position(triangle * 3u + 1u);
+    let c = world_position(triangle * 3u + 2u);
+    let centroid = (a + b + c) / 3.0;
+    // 退化三角形没有法线，画成长度为零的线段
+    let face = cross(b - a, c - a);
+    let normal = select(vec3f(0.0), normalize(face), dot(face, face) > 1e-12);
+    lines[triangle * 2u] = vec4f(centroid, 1.0);
+    lines[triangle * 2u + 1u] = vec4f(centroid + normal * uniforms.length, 1.0);
+}
+
+// ---- LineList 渲染 ----
+
+@vertex
+fn vs_main(@location(0) position: vec4f) -> @builtin(position) vec4f {
+    return uniforms.view_proj * position;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return uniforms.color;
+}
@@ -0,0 +1,223 @@
+use crate::resource_version::{ BindGroupBuilder, SetVersionedBindGroup, VersionCounter, VersionedBindGroup };
+use crate::texture::Texture;
+
+pub const ACCUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const REVEALAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R16Float;
+
+// 加权混合顺序无关透明（McGuire & Bavoil 2013）
+// 透明通道把加权颜色累加到 accum，把 (1 - alpha) 连乘到 revealage，最后一次性合成到不透明场景上
+pub struct OitRenderer {
+    accum: Texture,
+    revealage: Texture,
+    // 两张目标随窗口尺寸重新分配，共用一个版本
+    targets_version: VersionCounter,
+    composite_pipeline: wgpu::RenderPipeline,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    composite_bind_group: VersionedBindGroup,
+}
+
+impl OitRenderer {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, target_format: wgpu::TextureFormat) -> Self {
+        let (accum, revealage) = Self::create_targets(device, width, height);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("OIT Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("oit_composite.wgsl").into()),
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let composite_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("oit_composite_bind_group_layout"),
+            entries: &[texture_entry(0), texture_entry(1)],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("OIT Composite Pipeline Layout"),
+            bind_group_layouts: &[&composite_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("OIT Composite Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let targets_version = VersionCounter::new("OIT Targets");
+        let composite_bind_group = Self::create_bind_group(device, &composite_bind_group_layout, &accum, &revealage, &targets_version);
+
+        Self {
+            accum,
+            revealage,
+            targets_version,
+            composite_pipeline,
+            composite_bind_group_layout,
+            composite_bind_group,
+        }
+    }
+
+    fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> (Texture, Texture) {
+        let usage = wgpu::TextureUsages::empty();
+        (
+            Texture::create_render_target(device, width, height, ACCUM_FORMAT, usage, "OIT Accum"),
+            Texture::create_render_target(device, width, height, REVEALAGE_FORMAT, usage, "OIT Revealage"),
+        )
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        accum: &Texture,
+        revealage: &Texture,
+        version: &VersionCounter,
+    ) -> VersionedBindGroup {
+        BindGroupBuilder::new("oit_composite_bind_group", layout)
+            .texture_view(0, &accum.view, version)
+            .texture_view(1, &revealage.view, version)
+            .build(device)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let (accum, revealage) = Self::create_targets(device, width, height);
+        self.targets_version.bump();
+        self.composite_bind_group = Self::create_bind_group(
+            device,
+            &self.composite_bind_group_layout,
+            &accum,
+            &revealage,
+            &self.targets_version,
+        );
+        self.accum = accum;
+        self.revealage = revealage;
+    }
+
+    // 透明管线的两个颜色目标：accum 相加，revealage 相乘
+    pub fn color_targets() -> [Option<wgpu::ColorTargetState>; 2] {
+        [
+            Some(wgpu::ColorTargetState {
+                format: ACCUM_FORMAT,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            }),
+            Some(wgpu::ColorTargetState {
+                format: REVEALAGE_FORMAT,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Zero,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent::REPLACE,
+                }),
+                write_mask: wgpu::ColorWrites::RED,
+            }),
+        ]
+    }
+
+    // 透明物体与不透明物体做深度测试，但不写入深度
+    pub fn depth_stencil_state(format: wgpu::TextureFormat) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    pub fn begin_transparent_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        depth_view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("OIT Transparent Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.accum.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.revealage.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        // 完全可见
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // 把透明结果合成到不透明场景上
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("OIT Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_versioned_bind_group(0, &self.composite_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
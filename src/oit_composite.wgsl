@@ -0,0 +1,25 @@
+@group(0) @binding(0)
+var t_accum: texture_2d<f32>;
+@group(0) @binding(1)
+var t_revealage: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let texel = vec2<i32>(position.xy);
+    let revealage = textureLoad(t_revealage, texel, 0).r;
+    // 没有透明片元覆盖的像素
+    if revealage >= 1.0 {
+        discard;
+    }
+    let accum = textureLoad(t_accum, texel, 0);
+    let average_color = accum.rgb / clamp(accum.a, 1e-4, 5e4);
+    // 与混合状态 (SrcAlpha, OneMinusSrcAlpha) 一起得到
+    // C = average_color * (1 - revealage) + opaque * revealage
+    return vec4f(average_color, 1.0 - revealage);
+}
@@ -0,0 +1,622 @@
+use bytemuck::Zeroable;
+use cgmath::{ InnerSpace, Matrix, Matrix4, SquareMatrix, Transform as _ };
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::mesh::MeshData;
+use crate::texture::Texture;
+
+pub const ACCUMULATION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const POSITION_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+const WORKGROUP_SIZE: u32 = 8;
+// 叶子节点最多包含的三角形数
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathTracerMaterial {
+    pub base_color: [f32; 3],
+    pub metallic: f32,
+    pub roughness: f32,
+    // 自发光，场景中的光源
+    pub emissive: [f32; 3],
+}
+
+impl Default for PathTracerMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: [0.8, 0.8, 0.8],
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive: [0.0; 3],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathTracerConfig {
+    // 每条路径最多的弹射次数
+    pub max_bounces: u32,
+    // 累积到这么多样本后停止追踪
+    pub max_samples: u32,
+    pub sky_horizon: [f32; 3],
+    pub sky_zenith: [f32; 3],
+    pub exposure: f32,
+}
+
+impl Default for PathTracerConfig {
+    fn default() -> Self {
+        Self {
+            max_bounces: 4,
+            max_samples: 1024,
+            sky_horizon: [1.0, 1.0, 1.0],
+            sky_zenith: [0.5, 0.7, 1.0],
+            exposure: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BvhNode {
+    min: [f32; 3],
+    // 内部节点为右孩子下标（左孩子紧跟在自身之后），叶子节点为第一个三角形下标
+    left_first: u32,
+    max: [f32; 3],
+    // 为 0 表示内部节点
+    count: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuTriangle {
+    v0: [f32; 3],
+    material: u32,
+    v1: [f32; 3],
+    n0_x: f32,
+    v2: [f32; 3],
+    n0_y: f32,
+    n1: [f32; 3],
+    n0_z: f32,
+    n2: [f32; 3],
+    _padding: f32,
+}
+
+impl GpuTriangle {
+    fn centroid(&self) -> [f32; 3] {
+        std::array::from_fn(|axis| (self.v0[axis] + self.v1[axis] + self.v2[axis]) / 3.0)
+    }
+
+    fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let min = std::array::from_fn(|axis| self.v0[axis].min(self.v1[axis]).min(self.v2[axis]));
+        let max = std::array::from_fn(|axis| self.v0[axis].max(self.v1[axis]).max(self.v2[axis]));
+        (min, max)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuMaterial {
+    base_color: [f32; 3],
+    metallic: f32,
+    emissive: [f32; 3],
+    roughness: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TraceParams {
+    inverse_view_proj: [[f32; 4]; 4],
+    camera_position: [f32; 3],
+    sample_count: u32,
+    sky_horizon: [f32; 3],
+    frame_index: u32,
+    sky_zenith: [f32; 3],
+    max_bounces: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PresentParams {
+    exposure: f32,
+    _padding: [f32; 3],
+}
+
+// 路径追踪的场景：所有网格变换到世界空间后合并为一个三角形列表
+#[derive(Debug, Default)]
+pub struct PathTracerScene {
+    triangles: Vec<GpuTriangle>,
+    materials: Vec<GpuMaterial>,
+}
+
+impl PathTracerScene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_mesh(&mut self, mesh: &MeshData, transform: Matrix4<f32>, material: PathTracerMaterial) {
+        let material_index = self.materials.len() as u32;
+        self.materials.push(GpuMaterial {
+            base_color: material.base_color,
+            metallic: material.metallic,
+            emissive: material.emissive,
+            roughness: material.roughness,
+        });
+
+        // 法线用逆转置矩阵变换，缩放不均匀时仍垂直于表面
+        let normal_matrix = transform.invert().unwrap_or(Matrix4::identity()).transpose();
+        let vertex = |index: u16| {
+            let vertex = mesh.vertices[index as usize];
+            let position = transform.transform_point(vertex.position.into());
+            let normal = normal_matrix.transform_vector(vertex.normal.into());
+            let normal = if normal.magnitude2() > 0.0 { normal.normalize() } else { normal };
+            (position.into(), normal.into())
+        };
+
+        for face in mesh.indices.chunks_exact(3) {
+            let (v0, n0): ([f32; 3], [f32; 3]) = vertex(face[0]);
+            let (v1, n1) = vertex(face[1]);
+            let (v2, n2) = vertex(face[2]);
+            self.triangles.push(GpuTriangle {
+                v0,
+                material: material_index,
+                v1,
+                n0_x: n0[0],
+                v2,
+                n0_y: n0[1],
+                n1,
+                n0_z: n0[2],
+                n2,
+                _padding: 0.0,
+            });
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    // 按质心在最长轴上取中位数递归二分，三角形按叶子顺序重排
+    fn build_bvh(&self) -> (Vec<BvhNode>, Vec<GpuTriangle>) {
+        let mut order = (0..self.triangles.len()).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(self.triangles.len() * 2);
+        if !order.is_empty() {
+            self.build_node(&mut order, 0, &mut nodes);
+        }
+        let triangles = order.iter().map(|&i| self.triangles[i]).collect();
+        (nodes, triangles)
+    }
+
+    fn build_node(&self, order: &mut [usize], first: usize, nodes: &mut Vec<BvhNode>) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        let mut centroid_min = [f32::MAX; 3];
+        let mut centroid_max = [f32::MIN; 3];
+        for &i in order.iter() {
+            let (tri_min, tri_max) = self.triangles[i].bounds();
+            let centroid = self.triangles[i].centroid();
+            for axis in 0..3 {
+                min[axis] = min[axis].min(tri_min[axis]);
+                max[axis] = max[axis].max(tri_max[axis]);
+                centroid_min[axis] = centroid_min[axis].min(centroid[axis]);
+                centroid_max[axis] = centroid_max[axis].max(centroid[axis]);
+            }
+        }
+
+        let index = nodes.len();
+        nodes.push(BvhNode { min, left_first: first as u32, max, count: order.len() as u32 });
+        if order.len() <= MAX_LEAF_TRIANGLES {
+            return;
+        }
+
+        let extent = std::array::from_fn::<f32, 3, _>(|axis| centroid_max[axis] - centroid_min[axis]);
+        let axis = (0..3).max_by(|&a, &b| extent[a].total_cmp(&extent[b])).unwrap_or(0);
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&a, &b| {
+            self.triangles[a].centroid()[axis].total_cmp(&self.triangles[b].centroid()[axis])
+        });
+
+        let (left, right) = order.split_at_mut(mid);
+        self.build_node(left, first, nodes);
+        let right_index = nodes.len();
+        self.build_node(right, first + mid, nodes);
+        nodes[index].left_first = right_index as u32;
+        nodes[index].count = 0;
+    }
+}
+
+// 计算着色器路径追踪：每帧每像素一个样本，累加到 Rgba32Float 纹理中（alpha 为样本数）
+// 累积纹理是一对乒乓纹理，因为 WebGPU 不允许 rgba32float 的读写存储纹理
+pub struct PathTracer {
+    pub config: PathTracerConfig,
+    trace_pipeline: wgpu::ComputePipeline,
+    trace_bind_group_layout: wgpu::BindGroupLayout,
+    present_pipeline: wgpu::RenderPipeline,
+    present_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    present_buffer: wgpu::Buffer,
+    node_buffer: wgpu::Buffer,
+    triangle_buffer: wgpu::Buffer,
+    material_buffer: wgpu::Buffer,
+    accumulation: Option<[Texture; 2]>,
+    // 首次命中点的法线与世界坐标（G-buffer）
+    normal: Option<Texture>,
+    position: Option<Texture>,
+    // 最近一次写入的累积纹理
+    current: usize,
+    size: (u32, u32),
+    sample_count: u32,
+    frame_index: u32,
+    view_proj: Matrix4<f32>,
+}
+
+impl PathTracer {
+    pub fn new(device: &wgpu::Device, scene: &PathTracerScene, output_format: wgpu::TextureFormat, config: PathTracerConfig) -> Self {
+        let trace_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Path Tracer Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("path_tracer.wgsl").into()),
+        });
+        let present_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Path Tracer Present Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("path_tracer_present.wgsl").into()),
+        });
+
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage_texture_entry = |binding, format| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        };
+        let trace_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("path_tracer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1),
+                storage_entry(2),
+                storage_entry(3),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                storage_texture_entry(5, ACCUMULATION_FORMAT),
+                storage_texture_entry(6, NORMAL_FORMAT),
+                storage_texture_entry(7, POSITION_FORMAT),
+            ],
+        });
+        let trace_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Path Tracer Pipeline Layout"),
+            bind_group_layouts: &[&trace_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let trace_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Path Tracer Pipeline"),
+            layout: Some(&trace_layout),
+            module: &trace_shader,
+            entry_point: "cs_main",
+        });
+
+        let present_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("path_tracer_present_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let present_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Path Tracer Present Pipeline Layout"),
+            bind_group_layouts: &[&present_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let present_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Path Tracer Present Pipeline"),
+            layout: Some(&present_layout),
+            vertex: wgpu::VertexState {
+                module: &present_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &present_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Path Tracer Params Buffer"),
+            size: std::mem::size_of::<TraceParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let present_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Path Tracer Present Buffer"),
+            size: std::mem::size_of::<PresentParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let (nodes, triangles) = scene.build_bvh();
+        // 存储缓冲区不能为空，空场景时放一个不会被命中的节点
+        let nodes = if nodes.is_empty() {
+            vec![BvhNode { min: [f32::MAX; 3], left_first: 0, max: [f32::MIN; 3], count: 0 }]
+        } else {
+            nodes
+        };
+        let create_storage = |label, contents: &[u8]| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let triangles = if triangles.is_empty() { vec![GpuTriangle::zeroed()] } else { triangles };
+        let materials = if scene.materials.is_empty() { vec![GpuMaterial::zeroed()] } else { scene.materials.clone() };
+        let node_buffer = create_storage("Path Tracer BVH Buffer", bytemuck::cast_slice(&nodes));
+        let triangle_buffer = create_storage("Path Tracer Triangle Buffer", bytemuck::cast_slice(&triangles));
+        let material_buffer = create_storage("Path Tracer Material Buffer", bytemuck::cast_slice(&materials));
+
+        Self {
+            config,
+            trace_pipeline,
+            trace_bind_group_layout,
+            present_pipeline,
+            present_bind_group_layout,
+            params_buffer,
+            present_buffer,
+            node_buffer,
+            triangle_buffer,
+            material_buffer,
+            accumulation: None,
+            normal: None,
+            position: None,
+            current: 0,
+            size: (0, 0),
+            sample_count: 0,
+            frame_index: 0,
+            view_proj: Matrix4::identity(),
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.size = (width.max(1), height.max(1));
+        let create = |format, label| {
+            Texture::create_render_target(
+                device,
+                self.size.0,
+                self.size.1,
+                format,
+                wgpu::TextureUsages::STORAGE_BINDING,
+                label,
+            )
+        };
+        self.accumulation = Some([
+            create(ACCUMULATION_FORMAT, "Path Tracer Accumulation A"),
+            create(ACCUMULATION_FORMAT, "Path Tracer Accumulation B"),
+        ]);
+        self.normal = Some(create(NORMAL_FORMAT, "Path Tracer Normal"));
+        self.position = Some(create(POSITION_FORMAT, "Path Tracer Position"));
+        self.reset();
+    }
+
+    // 场景或相机变化后重新开始累积
+    pub fn reset(&mut self) {
+        self.sample_count = 0;
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // 当前的累积结果：rgb 为样本之和，alpha 为样本数
+    pub fn accumulation_view(&self) -> &wgpu::TextureView {
+        let accumulation = self.accumulation.as_ref().expect("PathTracer::resize must be called before accumulation_view");
+        &accumulation[self.current].view
+    }
+
+    // xyz 为首次命中点的法线，w 为 1 表示命中
+    pub fn normal_view(&self) -> &wgpu::TextureView {
+        &self.normal.as_ref().expect("PathTracer::resize must be called before normal_view").view
+    }
+
+    // xyz 为首次命中点的世界坐标，w 为 1 表示命中
+    pub fn position_view(&self) -> &wgpu::TextureView {
+        &self.position.as_ref().expect("PathTracer::resize must be called before position_view").view
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.sample_count >= self.config.max_samples
+    }
+
+    // 相机移动时自动重置累积
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &Camera) {
+        let view_proj = camera.build_view_projection_matrix();
+        if view_proj != self.view_proj {
+            self.view_proj = view_proj;
+            self.reset();
+        }
+
+        let params = TraceParams {
+            inverse_view_proj: view_proj.invert().unwrap_or(Matrix4::identity()).into(),
+            camera_position: camera.eye.into(),
+            sample_count: self.sample_count,
+            sky_horizon: self.config.sky_horizon,
+            frame_index: self.frame_index,
+            sky_zenith: self.config.sky_zenith,
+            max_bounces: self.config.max_bounces,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        let present = PresentParams { exposure: self.config.exposure, _padding: [0.0; 3] };
+        queue.write_buffer(&self.present_buffer, 0, bytemuck::cast_slice(&[present]));
+    }
+
+    // 追加一个样本；达到 max_samples 后不再调度，每帧调用前需先 update
+    pub fn trace(&mut self, encoder: &mut wgpu::CommandEncoder, device: &wgpu::Device) {
+        if self.is_converged() {
+            return;
+        }
+        let accumulation = self.accumulation.as_ref().expect("PathTracer::resize must be called before trace");
+        let previous = &accumulation[self.current];
+        let next = &accumulation[1 - self.current];
+        let normal = self.normal.as_ref().expect("PathTracer::resize must be called before trace");
+        let position = self.position.as_ref().expect("PathTracer::resize must be called before trace");
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("path_tracer_bind_group"),
+            layout: &self.trace_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.node_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.triangle_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.material_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&previous.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&next.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::TextureView(&normal.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: wgpu::BindingResource::TextureView(&position.view),
+                },
+            ],
+        });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Path Tracer Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.trace_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(self.size.0.div_ceil(WORKGROUP_SIZE), self.size.1.div_ceil(WORKGROUP_SIZE), 1);
+        }
+
+        self.current = 1 - self.current;
+        self.sample_count += 1;
+        self.frame_index = self.frame_index.wrapping_add(1);
+    }
+
+    // 把累积结果取平均、色调映射后画到 target 上
+    pub fn present(&self, encoder: &mut wgpu::CommandEncoder, device: &wgpu::Device, target: &wgpu::TextureView) {
+        self.present_from(encoder, device, self.accumulation_view(), target);
+    }
+
+    // source 的格式与累积纹理相同（rgb / alpha 为平均值），例如降噪后的结果
+    pub fn present_from(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        source: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("path_tracer_present_bind_group"),
+            layout: &self.present_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.present_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Path Tracer Present Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.present_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,263 @@
+const PI: f32 = 3.14159265;
+const STACK_SIZE: u32 = 32u;
+const RAY_EPSILON: f32 = 1e-4;
+
+struct TraceParams {
+    inverse_view_proj: mat4x4f,
+    camera_position: vec3f,
+    // 已累积的样本数，为 0 时丢弃上一帧的累积结果
+    sample_count: u32,
+    sky_horizon: vec3f,
+    frame_index: u32,
+    sky_zenith: vec3f,
+    max_bounces: u32,
+};
+
+// 线性 BVH：内部节点的左孩子紧跟在自身之后，left_first 为右孩子下标；叶子节点的 left_first 为第一个三角形
+struct BvhNode {
+    min: vec3f,
+    left_first: u32,
+    max: vec3f,
+    count: u32,
+};
+
+struct Triangle {
+    v0: vec3f,
+    material: u32,
+    v1: vec3f,
+    n0_x: f32,
+    v2: vec3f,
+    n0_y: f32,
+    n1: vec3f,
+    n0_z: f32,
+    n2: vec3f,
+    _padding: f32,
+};
+
+struct Material {
+    base_color: vec3f,
+    metallic: f32,
+    emissive: vec3f,
+    roughness: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: TraceParams;
+@group(0) @binding(1)
+var<storage, read> nodes: array<BvhNode>;
+@group(0) @binding(2)
+var<storage, read> triangles: array<Triangle>;
+@group(0) @binding(3)
+var<storage, read> materials: array<Material>;
+@group(0) @binding(4)
+var t_previous: texture_2d<f32>;
+@group(0) @binding(5)
+var t_accumulation: texture_storage_2d<rgba32float, write>;
+// 首次命中的几何信息，供降噪的边缘停止函数使用
+@group(0) @binding(6)
+var t_normal: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(7)
+var t_position: texture_storage_2d<rgba32float, write>;
+
+var<private> rng_state: u32;
+// 首次命中点的法线与位置，w 为 1 表示命中了几何体
+var<private> primary_normal: vec4f;
+var<private> primary_position: vec4f;
+
+// PCG 哈希
+fn random() -> f32 {
+    rng_state = rng_state * 747796405u + 2891336453u;
+    var word = ((rng_state >> ((rng_state >> 28u) + 4u)) ^ rng_state) * 277803737u;
+    word = (word >> 22u) ^ word;
+    return f32(word) / 4294967296.0;
+}
+
+struct Hit {
+    t: f32,
+    u: f32,
+    v: f32,
+    triangle: u32,
+};
+
+fn intersect_aabb(origin: vec3f, inverse_direction: vec3f, box_min: vec3f, box_max: vec3f, t_max: f32) -> bool {
+    let t0 = (box_min - origin) * inverse_direction;
+    let t1 = (box_max - origin) * inverse_direction;
+    let near = max(max(min(t0.x, t1.x), min(t0.y, t1.y)), min(t0.z, t1.z));
+    let far = min(min(max(t0.x, t1.x), max(t0.y, t1.y)), max(t0.z, t1.z));
+    return near <= far && far > 0.0 && near < t_max;
+}
+
+// Möller-Trumbore，命中时更新 hit
+fn intersect_triangle(origin: vec3f, direction: vec3f, index: u32, hit: ptr<function, Hit>) {
+    let triangle = triangles[index];
+    let edge1 = triangle.v1 - triangle.v0;
+    let edge2 = triangle.v2 - triangle.v0;
+    let p = cross(direction, edge2);
+    let det = dot(edge1, p);
+    if abs(det) < 1e-8 {
+        return;
+    }
+    let inverse_det = 1.0 / det;
+    let s = origin - triangle.v0;
+    let u = dot(s, p) * inverse_det;
+    if u < 0.0 || u > 1.0 {
+        return;
+    }
+    let q = cross(s, edge1);
+    let v = dot(direction, q) * inverse_det;
+    if v < 0.0 || u + v > 1.0 {
+        return;
+    }
+    let t = dot(edge2, q) * inverse_det;
+    if t > RAY_EPSILON && t < (*hit).t {
+        *hit = Hit(t, u, v, index);
+    }
+}
+
+fn trace(origin: vec3f, direction: vec3f) -> Hit {
+    var hit = Hit(1e30, 0.0, 0.0, 0xffffffffu);
+    let inverse_direction = 1.0 / direction;
+
+    var stack: array<u32, STACK_SIZE>;
+    var stack_len = 1u;
+    stack[0] = 0u;
+    while stack_len > 0u {
+        stack_len -= 1u;
+        let index = stack[stack_len];
+        let node = nodes[index];
+        if !intersect_aabb(origin, inverse_direction, node.min, node.max, hit.t) {
+            continue;
+        }
+        if node.count > 0u {
+            for (var i = 0u; i < node.count; i++) {
+                intersect_triangle(origin, direction, node.left_first + i, &hit);
+            }
+        } else if stack_len + 2u <= STACK_SIZE {
+            stack[stack_len] = node.left_first;
+            stack[stack_len + 1u] = index + 1u;
+            stack_len += 2u;
+        }
+    }
+    return hit;
+}
+
+fn sky(direction: vec3f) -> vec3f {
+    return mix(params.sky_horizon, params.sky_zenith, clamp(direction.y, 0.0, 1.0));
+}
+
+// 以 normal 为轴的余弦加权半球采样，pdf = cos / π
+fn sample_cosine_hemisphere(normal: vec3f) -> vec3f {
+    let r1 = random();
+    let r2 = random();
+    let phi = 2.0 * PI * r1;
+    let radius = sqrt(r2);
+    let local = vec3f(radius * cos(phi), radius * sin(phi), sqrt(1.0 - r2));
+
+    let helper = select(vec3f(1.0, 0.0, 0.0), vec3f(0.0, 1.0, 0.0), abs(normal.x) > 0.9);
+    let tangent = normalize(cross(helper, normal));
+    let bitangent = cross(normal, tangent);
+    return tangent * local.x + bitangent * local.y + normal * local.z;
+}
+
+// Cook-Torrance（GGX + Smith + Schlick）加 Lambert 漫反射，返回 brdf * cos / pdf
+fn evaluate_brdf(material: Material, normal: vec3f, view: vec3f, light: vec3f) -> vec3f {
+    let half_vector = normalize(view + light);
+    let n_dot_l = max(dot(normal, light), 1e-4);
+    let n_dot_v = max(dot(normal, view), 1e-4);
+    let n_dot_h = max(dot(normal, half_vector), 0.0);
+    let v_dot_h = max(dot(view, half_vector), 0.0);
+
+    let alpha = max(material.roughness * material.roughness, 1e-3);
+    let alpha2 = alpha * alpha;
+    let d_denominator = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    let d = alpha2 / (PI * d_denominator * d_denominator);
+    let k = (material.roughness + 1.0) * (material.roughness + 1.0) / 8.0;
+    let g = n_dot_l / (n_dot_l * (1.0 - k) + k) * n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let f0 = mix(vec3f(0.04), material.base_color, material.metallic);
+    let f = f0 + (1.0 - f0) * pow(1.0 - v_dot_h, 5.0);
+
+    let specular = d * g * f / (4.0 * n_dot_l * n_dot_v);
+    let diffuse = (1.0 - f) * (1.0 - material.metallic) * material.base_color / PI;
+    // cos / pdf = π
+    return (diffuse + specular) * PI;
+}
+
+fn radiance(primary_origin: vec3f, primary_direction: vec3f) -> vec3f {
+    var origin = primary_origin;
+    var direction = primary_direction;
+    var throughput = vec3f(1.0);
+    var color = vec3f(0.0);
+
+    for (var bounce = 0u; bounce <= params.max_bounces; bounce++) {
+        let hit = trace(origin, direction);
+        if hit.triangle == 0xffffffffu {
+            color += throughput * sky(direction);
+            break;
+        }
+
+        let triangle = triangles[hit.triangle];
+        let material = materials[triangle.material];
+        color += throughput * material.emissive;
+
+        let n0 = vec3f(triangle.n0_x, triangle.n0_y, triangle.n0_z);
+        var normal = normalize(n0 * (1.0 - hit.u - hit.v) + triangle.n1 * hit.u + triangle.n2 * hit.v);
+        // 双面材质：法线朝向射线来的一侧
+        if dot(normal, direction) > 0.0 {
+            normal = -normal;
+        }
+
+        if bounce == 0u {
+            primary_normal = vec4f(normal, 1.0);
+            primary_position = vec4f(origin + direction * hit.t, 1.0);
+        }
+        if bounce == params.max_bounces {
+            break;
+        }
+
+        let view = -direction;
+        origin = origin + direction * hit.t + normal * RAY_EPSILON;
+        direction = sample_cosine_hemisphere(normal);
+        throughput *= evaluate_brdf(material, normal, view, direction);
+
+        // 俄罗斯轮盘赌：贡献越小越早终止，存活的路径按概率放大保持无偏
+        if bounce >= 1u {
+            let survive = clamp(max(throughput.x, max(throughput.y, throughput.z)), 0.05, 0.95);
+            if random() > survive {
+                break;
+            }
+            throughput /= survive;
+        }
+    }
+    return color;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(t_accumulation);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+    rng_state = (id.y * size.x + id.x) * 9781u + params.frame_index * 6271u + 1u;
+
+    // 像素内随机抖动，累积后即为抗锯齿
+    let pixel = vec2f(id.xy) + vec2f(random(), random());
+    let ndc = vec2f(pixel.x / f32(size.x) * 2.0 - 1.0, 1.0 - pixel.y / f32(size.y) * 2.0);
+    let far = params.inverse_view_proj * vec4f(ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w - params.camera_position);
+
+    primary_normal = vec4f(0.0);
+    primary_position = vec4f(0.0);
+    var sample = radiance(params.camera_position, direction);
+    // 丢弃 NaN / Inf，避免一个坏样本污染整个累积结果
+    if any(sample != sample) || any(abs(sample) > vec3f(1e6)) {
+        sample = vec3f(0.0);
+    }
+
+    var sum = vec4f(sample, 1.0);
+    if params.sample_count > 0u {
+        sum += textureLoad(t_previous, vec2<i32>(id.xy), 0);
+    }
+    textureStore(t_accumulation, vec2<i32>(id.xy), sum);
+    textureStore(t_normal, vec2<i32>(id.xy), primary_normal);
+    textureStore(t_position, vec2<i32>(id.xy), primary_position);
+}
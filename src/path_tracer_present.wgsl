@@ -0,0 +1,34 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+};
+
+// 用一个覆盖全屏的大三角形代替四边形
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    return out;
+}
+
+struct PresentParams {
+    exposure: f32,
+};
+
+@group(0) @binding(0)
+var t_accumulation: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: PresentParams;
+
+// Narkowicz 的 ACES 近似
+fn aces(x: vec3f) -> vec3f {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3f(0.0), vec3f(1.0));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // alpha 通道累加的是样本数
+    let sum = textureLoad(t_accumulation, vec2<i32>(in.clip_position.xy), 0);
+    let color = sum.rgb / max(sum.a, 1.0);
+    return vec4f(aces(color * params.exposure), 1.0);
+}
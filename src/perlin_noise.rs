@@ -0,0 +1,211 @@
+use wgpu::util::DeviceExt;
+
+pub const PERLIN_NOISE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+// 超过后最高层的格子小于一个纹素，再叠加也没有细节
+pub const MAX_PERLIN_OCTAVES: u32 = 8;
+// 排列表的随机种子，每次生成的噪声相同
+const PERMUTATION_SEED: u32 = 0x5eed;
+const WORKGROUP_SIZE: u32 = 8;
+const TEXEL_SIZE_BYTES: u32 = 4;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PerlinParams {
+    size: u32,
+    octaves: u32,
+    persistence: f32,
+    tileable: u32,
+    dimension: u32,
+    row_floats: u32,
+    _padding: [u32; 2],
+}
+
+// 分形 Perlin 噪声纹理：计算着色器在每个纹素中心求 octaves 层改进 Perlin 噪声之和，
+// 第一层整张纹理覆盖 4 个格子（perlin_noise.wgsl 的 BASE_FREQUENCY），之后每层频率翻倍、振幅乘以 persistence，
+// 结果按振幅之和归一化，大致在 [-1, 1]。平铺版本的格点坐标按每层的格子数取模，
+// 左右（以及上下、前后）边缘的梯度相同，纹理可以无缝重复。
+// R32Float 不可过滤，着色器用 textureLoad 读取或自行插值。
+// 结果先写入存储缓冲再复制到纹理：GL 后端的三维存储纹理只能写入第一层
+pub struct PerlinNoiseTexture;
+
+impl PerlinNoiseTexture {
+    // size × size × size 的三维噪声，用于体积云、烟雾等
+    pub fn generate_3d(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, octaves: u32, persistence: f32) -> wgpu::Texture {
+        Self::generate(device, queue, wgpu::TextureDimension::D3, false, size, octaves, persistence)
+    }
+
+    // size × size 的二维噪声，用于高度图
+    pub fn generate_2d(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, octaves: u32, persistence: f32) -> wgpu::Texture {
+        Self::generate(device, queue, wgpu::TextureDimension::D2, false, size, octaves, persistence)
+    }
+
+    pub fn generate_tileable_3d(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, octaves: u32, persistence: f32) -> wgpu::Texture {
+        Self::generate(device, queue, wgpu::TextureDimension::D3, true, size, octaves, persistence)
+    }
+
+    pub fn generate_tileable_2d(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, octaves: u32, persistence: f32) -> wgpu::Texture {
+        Self::generate(device, queue, wgpu::TextureDimension::D2, true, size, octaves, persistence)
+    }
+
+    fn generate(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dimension: wgpu::TextureDimension,
+        tileable: bool,
+        size: u32,
+        octaves: u32,
+        persistence: f32,
+    ) -> wgpu::Texture {
+        let limits = device.limits();
+        let (max_size, depth) = match dimension {
+            wgpu::TextureDimension::D3 => (limits.max_texture_dimension_3d, size),
+            _ => (limits.max_texture_dimension_2d, 1),
+        };
+        let size = size.clamp(1, max_size);
+        let depth = depth.clamp(1, size);
+        let octaves = octaves.clamp(1, MAX_PERLIN_OCTAVES);
+        let bytes_per_row = (size * TEXEL_SIZE_BYTES).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let params = PerlinParams {
+            size,
+            octaves,
+            persistence,
+            tileable: tileable as u32,
+            dimension: if dimension == wgpu::TextureDimension::D3 { 3 } else { 2 },
+            row_floats: bytes_per_row / TEXEL_SIZE_BYTES,
+            _padding: [0; 2],
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Perlin Noise Params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let permutation_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Perlin Noise Permutation"),
+            contents: bytemuck::cast_slice(&permutation()),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Perlin Noise Output"),
+            size: (bytes_per_row * size * depth) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("perlin_noise_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("perlin_noise_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: permutation_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: output.as_entire_binding() },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Perlin Noise Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("perlin_noise.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Perlin Noise Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Perlin Noise Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(if tileable { "Tileable Perlin Noise" } else { "Perlin Noise" }),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: depth,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension,
+            format: PERLIN_NOISE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Perlin Noise Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Perlin Noise Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(size.div_ceil(WORKGROUP_SIZE), size.div_ceil(WORKGROUP_SIZE), depth);
+        }
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &output,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(size),
+                },
+            },
+            texture.as_image_copy(),
+            texture.size(),
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+        texture
+    }
+}
+
+// 0..256 的确定性随机排列（Fisher–Yates），重复两遍供着色器嵌套查表
+fn permutation() -> Vec<u32> {
+    let mut table: Vec<u32> = (0..256).collect();
+    let mut state = PERMUTATION_SEED;
+    for i in (1..table.len()).rev() {
+        state = hash(state);
+        table.swap(i, state as usize % (i + 1));
+    }
+    table.extend_from_within(..);
+    table
+}
+
+// 整数哈希（lowbias32）
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
@@ -0,0 +1,107 @@
+// 改进的 Perlin 噪声（Perlin 2002），按 octaves 层叠加，每层频率翻倍、振幅乘以 persistence
+
+// 与 perlin_noise.rs 中的 BASE_FREQUENCY 一致：整张纹理在第一层覆盖的格子数
+const BASE_FREQUENCY: f32 = 4.0;
+
+struct PerlinParams {
+    size: u32,
+    octaves: u32,
+    persistence: f32,
+    // 非 0 时格点坐标按每层的周期取模，纹理首尾无缝衔接
+    tileable: u32,
+    // 2 或 3
+    dimension: u32,
+    // 输出缓冲每行的 f32 个数，按 copy_buffer_to_texture 要求的 256 字节对齐
+    row_floats: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: PerlinParams;
+// 0..256 的随机排列重复两遍，查表时不必再取模
+@group(0) @binding(1)
+var<storage, read> permutation: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> output: array<f32>;
+
+fn fade(t: vec3f) -> vec3f {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+// 格点坐标映射到排列表的下标；平铺时先按周期取模
+fn lattice(cell: vec3i, period: i32) -> vec3u {
+    var wrapped = cell;
+    if params.tileable != 0u {
+        wrapped = ((cell % period) + period) % period;
+    }
+    return vec3u(wrapped & vec3i(255));
+}
+
+fn hash(cell: vec3u) -> u32 {
+    return permutation[permutation[permutation[cell.x] + cell.y] + cell.z];
+}
+
+// 十二条棱方向（补齐到 16 个）之一与 offset 的点积
+fn gradient_3d(hash: u32, offset: vec3f) -> f32 {
+    let h = hash & 15u;
+    let u = select(offset.y, offset.x, h < 8u);
+    let v = select(select(offset.z, offset.x, h == 12u || h == 14u), offset.y, h < 4u);
+    return select(-u, u, (h & 1u) == 0u) + select(-v, v, (h & 2u) == 0u);
+}
+
+// 八个等分方向之一与 offset 的点积
+fn gradient_2d(hash: u32, offset: vec2f) -> f32 {
+    let angle = f32(hash & 7u) * 0.785398163;
+    return dot(vec2f(cos(angle), sin(angle)), offset);
+}
+
+fn perlin_3d(p: vec3f, period: i32) -> f32 {
+    let cell = vec3i(floor(p));
+    let f = p - floor(p);
+    let w = fade(f);
+    var corners: array<f32, 8>;
+    for (var i = 0u; i < 8u; i++) {
+        let corner = vec3u(i & 1u, (i >> 1u) & 1u, i >> 2u);
+        corners[i] = gradient_3d(hash(lattice(cell + vec3i(corner), period)), f - vec3f(corner));
+    }
+    let x0 = mix(vec4f(corners[0], corners[2], corners[4], corners[6]), vec4f(corners[1], corners[3], corners[5], corners[7]), w.x);
+    let y0 = mix(x0.xz, x0.yw, w.y);
+    return mix(y0.x, y0.y, w.z);
+}
+
+fn perlin_2d(p: vec2f, period: i32) -> f32 {
+    let cell = vec3i(vec2i(floor(p)), 0);
+    let f = p - floor(p);
+    let w = fade(vec3f(f, 0.0)).xy;
+    let a = gradient_2d(hash(lattice(cell, period)), f);
+    let b = gradient_2d(hash(lattice(cell + vec3i(1, 0, 0), period)), f - vec2f(1.0, 0.0));
+    let c = gradient_2d(hash(lattice(cell + vec3i(0, 1, 0), period)), f - vec2f(0.0, 1.0));
+    let d = gradient_2d(hash(lattice(cell + vec3i(1, 1, 0), period)), f - vec2f(1.0, 1.0));
+    // 单位梯度的二维噪声最大约为 √2 / 2，放大到与三维相近的范围
+    return mix(mix(a, b, w.x), mix(c, d, w.x), w.y) * 1.41421356;
+}
+
+// 结果按各层振幅之和归一化，大致落在 [-1, 1]
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let depth = select(1u, params.size, params.dimension == 3u);
+    if any(id.xy >= vec2u(params.size)) || id.z >= depth {
+        return;
+    }
+    let position = (vec3f(id) + 0.5) / f32(params.size);
+    var frequency = BASE_FREQUENCY;
+    var amplitude = 1.0;
+    var value = 0.0;
+    var total_amplitude = 0.0;
+    for (var octave = 0u; octave < params.octaves; octave++) {
+        let period = i32(frequency);
+        if params.dimension == 3u {
+            value += amplitude * perlin_3d(position * frequency, period);
+        } else {
+            value += amplitude * perlin_2d(position.xy * frequency, period);
+        }
+        total_amplitude += amplitude;
+        amplitude *= params.persistence;
+        frequency *= 2.0;
+    }
+    output[(id.z * params.size + id.y) * params.row_floats + id.x] = value / max(total_amplitude, 1e-6);
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use cgmath::{ Quaternion, Vector3 };
+use rapier3d::na::{ self, Translation3, UnitQuaternion };
+use rapier3d::prelude::*;
+
+use crate::world::{ Entity, World };
+
+// 包装 rapier3d 的各个集合与管线，按固定步长推进
+pub struct PhysicsWorld {
+    pub gravity: Vector<Real>,
+    pub integration_parameters: IntegrationParameters,
+    pub bodies: RigidBodySet,
+    pub colliders: ColliderSet,
+    pipeline: PhysicsPipeline,
+    island_manager: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    entities: HashMap<RigidBodyHandle, Entity>,
+}
+
+impl PhysicsWorld {
+    pub fn new(gravity: Vector3<f32>, dt: f32) -> Self {
+        let integration_parameters = IntegrationParameters {
+            dt,
+            ..Default::default()
+        };
+
+        Self {
+            gravity: Vector::new(gravity.x, gravity.y, gravity.z),
+            integration_parameters,
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            pipeline: PhysicsPipeline::new(),
+            island_manager: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    // 以实体当前的 Transform 作为刚体初始位姿
+    pub fn insert_body(&mut self, world: &World, entity: Entity, mut body: RigidBody, collider: Option<Collider>) -> RigidBodyHandle {
+        let transform = world.transform(entity);
+        body.set_position(to_isometry(transform.position, transform.rotation), true);
+
+        let handle = self.bodies.insert(body);
+        if let Some(collider) = collider {
+            self.colliders.insert_with_parent(collider, handle, &mut self.bodies);
+        }
+        self.entities.insert(handle, entity);
+        handle
+    }
+
+    pub fn remove_body(&mut self, handle: RigidBodyHandle) {
+        self.bodies.remove(
+            handle,
+            &mut self.island_manager,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+        self.entities.remove(&handle);
+    }
+
+    pub fn handle(&self, entity: Entity) -> Option<RigidBodyHandle> {
+        self.entities.iter().find(|(_, e)| **e == entity).map(|(handle, _)| *handle)
+    }
+
+    // 把实体的 Transform 写入刚体，用于编辑器等外部修改
+    pub fn set_body_transform(&mut self, world: &World, entity: Entity) {
+        let Some(body) = self.handle(entity).and_then(|handle| self.bodies.get_mut(handle)) else {
+            return;
+        };
+        let transform = world.transform(entity);
+        body.set_position(to_isometry(transform.position, transform.rotation), true);
+    }
+
+    pub fn entity(&self, handle: RigidBodyHandle) -> Option<Entity> {
+        self.entities.get(&handle).copied()
+    }
+
+    pub fn step(&mut self) {
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.island_manager,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+    }
+
+    // 把刚体位姿写回对应实体的 Transform，缩放保持不变
+    pub fn sync_transforms(&self, world: &mut World) {
+        for (handle, entity) in &self.entities {
+            let Some(body) = self.bodies.get(*handle) else {
+                continue;
+            };
+            let position = body.position();
+            let transform = world.transform_mut(*entity);
+            transform.position = Vector3::new(position.translation.x, position.translation.y, position.translation.z);
+            let rotation = position.rotation;
+            transform.rotation = Quaternion::new(rotation.w, rotation.i, rotation.j, rotation.k);
+        }
+    }
+}
+
+fn to_isometry(position: Vector3<f32>, rotation: Quaternion<f32>) -> Isometry<Real> {
+    let rotation = UnitQuaternion::from_quaternion(na::Quaternion::new(rotation.s, rotation.v.x, rotation.v.y, rotation.v.z));
+    Isometry::from_parts(Translation3::new(position.x, position.y, position.z), rotation)
+}
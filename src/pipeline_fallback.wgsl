@@ -0,0 +1,13 @@
+// 只读取 @builtin(position)，设备启用 SHADER_UNUSED_VERTEX_OUTPUT 时可以与任意顶点着色器搭配
+@fragment
+fn fs_fallback(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    // 紫色斜条纹，一眼就能看出哪些物体用的是备用管线
+    let stripe = step(0.5, fract((position.x + position.y) / 16.0));
+    return vec4f(mix(vec3f(0.5, 0.0, 0.5), vec3f(1.0, 0.0, 1.0), stripe), 1.0);
+}
+
+// 异步编译期间的占位：纯色，不像 fs_fallback 那样显眼
+@fragment
+fn fs_solid() -> @location(0) vec4f {
+    return vec4f(0.5, 0.5, 0.5, 1.0);
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::mpsc::{ self, Receiver, TryRecvError };
+
+// 创建管线所需的一切都由闭包持有（着色器模块、布局等通常以 Arc 共享）
+pub type PipelineDescriptor = Box<dyn FnOnce(&wgpu::Device) -> wgpu::RenderPipeline>;
+
+pub enum PipelineSlot {
+    Pending(PipelineDescriptor),
+    // 在工作线程上编译，完成前取用时返回 fallback
+    Compiling {
+        receiver: Receiver<wgpu::RenderPipeline>,
+        fallback: wgpu::RenderPipeline,
+    },
+    Ready(wgpu::RenderPipeline),
+}
+
+// 管线在第一次取用时才创建，本次运行用不到的管线不会拖慢启动
+// 编译停顿随之推迟到第一次需要它的那一帧，可以在加载阶段用 warm_up 提前创建
+pub struct PipelineManager {
+    device: Arc<wgpu::Device>,
+    slots: HashMap<String, PipelineSlot>,
+}
+
+impl PipelineManager {
+    pub fn new(device: Arc<wgpu::Device>) -> Self {
+        Self {
+            device,
+            slots: HashMap::new(),
+        }
+    }
+
+    // 同名管线会被替换
+    pub fn register(&mut self, name: impl Into<String>, create: impl FnOnce(&wgpu::Device) -> wgpu::RenderPipeline + 'static) {
+        self.slots.insert(name.into(), PipelineSlot::Pending(Box::new(create)));
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, pipeline: wgpu::RenderPipeline) {
+        self.slots.insert(name.into(), PipelineSlot::Ready(pipeline));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.slots.contains_key(name)
+    }
+
+    pub fn is_ready(&self, name: &str) -> bool {
+        matches!(self.slots.get(name), Some(PipelineSlot::Ready(_)))
+    }
+
+    pub fn is_compiling(&self, name: &str) -> bool {
+        matches!(self.slots.get(name), Some(PipelineSlot::Compiling { .. }))
+    }
+
+    // wgpu 0.18 没有 create_render_pipeline_async，这里在阻塞线程池上调用 create_render_pipeline，
+    // 事件循环不会被编译卡住；fallback 通常是同一布局与顶点着色器配上 fs_solid 的纯色管线
+    pub fn compile_async(
+        &mut self,
+        name: impl Into<String>,
+        create: impl FnOnce(&wgpu::Device) -> wgpu::RenderPipeline + Send + 'static,
+        fallback: wgpu::RenderPipeline,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        let device = self.device.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = sender.send(create(&device));
+        });
+        self.slots.insert(name.into(), PipelineSlot::Compiling { receiver, fallback });
+    }
+
+    // 每帧调用一次，不阻塞；把编译完成的管线转为 Ready，返回本次完成的数量
+    pub fn poll(&mut self) -> usize {
+        let mut finished = 0;
+        let mut failed = Vec::new();
+        for (name, slot) in &mut self.slots {
+            let PipelineSlot::Compiling { receiver, .. } = slot else {
+                continue;
+            };
+            match receiver.try_recv() {
+                Ok(pipeline) => {
+                    *slot = PipelineSlot::Ready(pipeline);
+                    finished += 1;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => failed.push(name.clone()),
+            }
+        }
+
+        // 工作线程 panic，之后一直使用备用管线
+        for name in failed {
+            tracing::warn!("pipeline manager: compiling `{name}` failed, keeping the fallback pipeline");
+            if let Some(PipelineSlot::Compiling { fallback, .. }) = self.slots.remove(&name) {
+                self.slots.insert(name, PipelineSlot::Ready(fallback));
+            }
+        }
+        finished
+    }
+
+    // 阻塞直到所有后台编译结束，用于需要逐帧复现的测试
+    pub fn wait(&mut self) {
+        for slot in self.slots.values_mut() {
+            let PipelineSlot::Compiling { receiver, .. } = slot else {
+                continue;
+            };
+            if let Ok(pipeline) = receiver.recv() {
+                *slot = PipelineSlot::Ready(pipeline);
+            }
+        }
+        // 接收失败的由 poll 换成备用管线
+        self.poll();
+    }
+
+    // Pending 的管线在这里创建并转为 Ready
+    pub fn get(&mut self, name: &str) -> Option<&wgpu::RenderPipeline> {
+        if let Some(PipelineSlot::Pending(_)) = self.slots.get(name) {
+            let Some(PipelineSlot::Pending(create)) = self.slots.remove(name) else {
+                unreachable!();
+            };
+            let _span = tracing::info_span!("create_render_pipeline", name).entered();
+            self.slots.insert(name.to_string(), PipelineSlot::Ready(create(&self.device)));
+        }
+
+        match self.slots.get(name) {
+            Some(PipelineSlot::Ready(pipeline)) => Some(pipeline),
+            Some(PipelineSlot::Compiling { fallback, .. }) => Some(fallback),
+            Some(PipelineSlot::Pending(_)) => unreachable!(),
+            None => {
+                tracing::warn!("pipeline manager: `{name}` is not registered");
+                None
+            }
+        }
+    }
+
+    // 不取用，只确保已创建
+    pub fn warm_up<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        for name in names {
+            self.get(name);
+        }
+    }
+}
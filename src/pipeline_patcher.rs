@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use crate::error_scope::ErrorScope;
+use crate::shader_error::ShaderError;
+
+struct PatchedPipeline {
+    pipeline: wgpu::RenderPipeline,
+    fallback: wgpu::RenderPipeline,
+    broken: bool,
+}
+
+// 调试用：着色器编译失败时用备用管线替换原管线，修好后再换回来
+// 绘制代码每帧通过 get 取管线，替换在下一次取用时生效
+#[derive(Default)]
+pub struct PipelinePatcher {
+    pipelines: HashMap<String, PatchedPipeline>,
+}
+
+impl PipelinePatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 备用管线通常沿用原管线的布局与顶点着色器，片元着色器换成 fallback_shader 中的 fs_fallback
+    pub fn watch(&mut self, name: impl Into<String>, pipeline: wgpu::RenderPipeline, fallback: wgpu::RenderPipeline) {
+        self.pipelines.insert(name.into(), PatchedPipeline { pipeline, fallback, broken: false });
+    }
+
+    pub fn set_broken(&mut self, name: &str) {
+        match self.pipelines.get_mut(name) {
+            Some(entry) => {
+                if !entry.broken {
+                    tracing::warn!("pipeline patcher: `{name}` is broken, using fallback pipeline");
+                }
+                entry.broken = true;
+            }
+            None => tracing::warn!("pipeline patcher: `{name}` is not watched"),
+        }
+    }
+
+    pub fn set_healed(&mut self, name: &str, pipeline: wgpu::RenderPipeline) {
+        match self.pipelines.get_mut(name) {
+            Some(entry) => {
+                entry.pipeline = pipeline;
+                entry.broken = false;
+            }
+            None => tracing::warn!("pipeline patcher: `{name}` is not watched"),
+        }
+    }
+
+    pub fn is_broken(&self, name: &str) -> bool {
+        self.pipelines.get(name).is_some_and(|entry| entry.broken)
+    }
+
+    // 被标记为损坏时返回备用管线
+    pub fn get(&self, name: &str) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines
+            .get(name)
+            .map(|entry| if entry.broken { &entry.fallback } else { &entry.pipeline })
+    }
+
+    // 在错误作用域中重新创建管线：有校验错误（包括 WGSL 编译失败）时标记为损坏，否则替换并恢复
+    // 着色器文件变化后由调用方触发，返回是否成功
+    pub fn rebuild(&mut self, device: &wgpu::Device, name: &str, create: impl FnOnce(&wgpu::Device) -> wgpu::RenderPipeline) -> bool {
+        self.rebuild_inner(device, name, None, create)
+    }
+
+    // 同 rebuild，失败时在日志中附上出错位置附近的 WGSL 源码
+    pub fn rebuild_with_source(
+        &mut self,
+        device: &wgpu::Device,
+        name: &str,
+        wgsl_source: &str,
+        create: impl FnOnce(&wgpu::Device) -> wgpu::RenderPipeline,
+    ) -> bool {
+        self.rebuild_inner(device, name, Some(wgsl_source), create)
+    }
+
+    fn rebuild_inner(
+        &mut self,
+        device: &wgpu::Device,
+        name: &str,
+        wgsl_source: Option<&str>,
+        create: impl FnOnce(&wgpu::Device) -> wgpu::RenderPipeline,
+    ) -> bool {
+        let error_scope = ErrorScope::new(device, "PipelinePatcher::rebuild");
+        let pipeline = create(device);
+        let errors = error_scope.take_errors();
+        if errors.is_empty() {
+            self.set_healed(name, pipeline);
+            return true;
+        }
+        for error in &errors {
+            match wgsl_source {
+                Some(source) => {
+                    let report = ShaderError::format_with_source(source, &error.to_string());
+                    tracing::warn!("pipeline patcher: failed to rebuild `{name}`:\n{report}");
+                }
+                None => tracing::warn!("pipeline patcher: failed to rebuild `{name}`: {error}"),
+            }
+        }
+        self.set_broken(name);
+        false
+    }
+
+    // 片元入口为 fs_fallback（紫色条纹）与 fs_solid（纯灰色）；它们不读取顶点输出，
+    // 与输出 @location 的顶点着色器搭配时设备需启用 SHADER_UNUSED_VERTEX_OUTPUT
+    pub fn fallback_shader(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fallback Pipeline Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("pipeline_fallback.wgsl").into()),
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use crate::backend::RenderCapabilities;
+use crate::camera::Camera;
+use crate::post_process::{ PostProcessEffect, PostProcessStack };
+use crate::world::World;
+
+// 渲染功能以插件形式接入主循环，不需要修改 State 的字段与方法
+pub trait Plugin {
+    // 注册时调用一次：可以在这里注册依赖的插件或后处理效果
+    fn build(&self, app: &mut AppBuilder);
+
+    // 每帧调用一次，dt 为本帧经过的秒数
+    fn update(&mut self, _world: &mut World, _dt: f32) {}
+
+    // 在不透明与透明物体绘制完成之后、后处理之前调用，可向 resources.color_view 追加绘制
+    fn render(&mut self, _encoder: &mut wgpu::CommandEncoder, _resources: &GpuResources) {}
+}
+
+// 插件渲染时可用的 GPU 资源，GPU 对象可以在第一次 render 时按需创建
+pub struct GpuResources<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    // 场景 HDR 颜色目标，格式为 AppBuilder::color_format
+    pub color_view: &'a wgpu::TextureView,
+    pub depth_view: &'a wgpu::TextureView,
+    pub camera: &'a Camera,
+    // 布局与 CameraUniform::bind_group_layout 相同
+    pub camera_bind_group: &'a wgpu::BindGroup,
+    pub size: (u32, u32),
+}
+
+// 设备创建之后、进入主循环之前用来注册插件；设备丢失重建时插件会重新注册
+pub struct AppBuilder<'a> {
+    device: &'a wgpu::Device,
+    queue: &'a wgpu::Queue,
+    capabilities: &'a RenderCapabilities,
+    color_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    post_process: &'a mut PostProcessStack,
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl<'a> AppBuilder<'a> {
+    pub fn new(
+        device: &'a wgpu::Device,
+        queue: &'a wgpu::Queue,
+        capabilities: &'a RenderCapabilities,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        post_process: &'a mut PostProcessStack,
+    ) -> Self {
+        Self {
+            device,
+            queue,
+            capabilities,
+            color_format,
+            depth_format,
+            post_process,
+            plugins: Vec::new(),
+        }
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        self.queue
+    }
+
+    // 插件据此选择回退实现，例如 WebGL2 上没有计算着色器
+    pub fn capabilities(&self) -> &RenderCapabilities {
+        self.capabilities
+    }
+
+    pub fn color_format(&self) -> wgpu::TextureFormat {
+        self.color_format
+    }
+
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.depth_format
+    }
+
+    // 先调用插件的 build 再加入列表，因此 build 中注册的依赖排在它前面执行
+    pub fn add_plugin(&mut self, plugin: impl Plugin + 'static) -> &mut Self {
+        plugin.build(self);
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    pub fn add_post_process_effect(&mut self, effect: Box<dyn PostProcessEffect>) -> &mut Self {
+        self.post_process.push(effect);
+        self
+    }
+
+    pub fn finish(self) -> PluginSet {
+        PluginSet { plugins: self.plugins }
+    }
+}
+
+// 按注册顺序调用的插件列表
+#[derive(Default)]
+pub struct PluginSet {
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl PluginSet {
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    pub fn update(&mut self, world: &mut World, dt: f32) {
+        for plugin in &mut self.plugins {
+            plugin.update(world, dt);
+        }
+    }
+
+    pub fn render(&mut self, encoder: &mut wgpu::CommandEncoder, resources: &GpuResources) {
+        for plugin in &mut self.plugins {
+            plugin.render(encoder, resources);
+        }
+    }
+}
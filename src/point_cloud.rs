@@ -0,0 +1,237 @@
+use std::ops::Range;
+
+use crate::camera::Camera;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Point {
+    pub position: [f32; 3],
+    // RGBA8，r 在最低字节
+    pub color: u32,
+}
+
+impl Point {
+    pub fn new(position: [f32; 3], rgba: [u8; 4]) -> Self {
+        Self { position, color: u32::from_le_bytes(rgba) }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PointCloudUniform {
+    view_proj: [[f32; 4]; 4],
+    viewport_size: [f32; 2],
+    point_size: f32,
+    projection_scale: f32,
+    min_pixels: f32,
+    _padding: [f32; 3],
+}
+
+// 激光雷达一类的点云：点存放在存储缓冲区里，顶点着色器按顶点序号把每个点展开成屏幕空间的方形
+// WebGPU 的 PointList 只能画 1 像素的点，所以每个点用 6 个顶点的 TriangleList 绘制
+// 缓冲区是环形的：写满后新点覆盖最旧的点，删除也从最旧的点开始
+pub struct PointCloudRenderer {
+    // 点在世界空间中的直径
+    pub point_size: f32,
+    // 屏幕上的最小直径（像素）
+    pub min_pixels: f32,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    point_buffer: wgpu::Buffer,
+    capacity: u32,
+    // 最旧的点所在槽位与当前点数
+    tail: u32,
+    len: u32,
+}
+
+impl PointCloudRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        capacity: u32,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let capacity = capacity.max(1);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Point Cloud Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("point_cloud.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("point_cloud_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Cloud Uniform Buffer"),
+            size: std::mem::size_of::<PointCloudUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let point_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Cloud Buffer"),
+            size: capacity as wgpu::BufferAddress * std::mem::size_of::<Point>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("point_cloud_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: point_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Point Cloud Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Point Cloud Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        Self {
+            point_size: 0.02,
+            min_pixels: 2.0,
+            pipeline,
+            bind_group,
+            uniform_buffer,
+            point_buffer,
+            capacity,
+            tail: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // 追加点，超出容量时覆盖最旧的点
+    pub fn insert(&mut self, queue: &wgpu::Queue, points: &[Point]) {
+        // 一次写入超过容量时只有最后 capacity 个点会保留
+        let points = &points[points.len().saturating_sub(self.capacity as usize)..];
+        let count = points.len() as u32;
+        let head = (self.tail + self.len) % self.capacity;
+
+        let first = count.min(self.capacity - head);
+        self.write(queue, head, &points[..first as usize]);
+        self.write(queue, 0, &points[first as usize..]);
+
+        let overflow = (self.len + count).saturating_sub(self.capacity);
+        self.tail = (self.tail + overflow) % self.capacity;
+        self.len = (self.len + count).min(self.capacity);
+    }
+
+    pub fn remove_oldest(&mut self, count: u32) {
+        let count = count.min(self.len);
+        self.tail = (self.tail + count) % self.capacity;
+        self.len -= count;
+    }
+
+    pub fn clear(&mut self) {
+        self.tail = 0;
+        self.len = 0;
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, viewport_size: [u32; 2]) {
+        let uniform = PointCloudUniform {
+            view_proj: camera.build_view_projection_matrix().into(),
+            viewport_size: [viewport_size[0].max(1) as f32, viewport_size[1].max(1) as f32],
+            point_size: self.point_size,
+            projection_scale: 1.0 / (camera.fovy.to_radians() * 0.5).tan(),
+            min_pixels: self.min_pixels,
+            _padding: [0.0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.len == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        // 跨过缓冲区末尾时分两段绘制
+        for slots in self.live_ranges() {
+            render_pass.draw(slots.start * 6..slots.end * 6, 0..1);
+        }
+    }
+
+    fn live_ranges(&self) -> impl Iterator<Item = Range<u32>> {
+        let end = self.tail + self.len;
+        let wrapped = end.saturating_sub(self.capacity);
+        [self.tail..end.min(self.capacity), 0..wrapped].into_iter().filter(|range| !range.is_empty())
+    }
+
+    fn write(&self, queue: &wgpu::Queue, slot: u32, points: &[Point]) {
+        if points.is_empty() {
+            return;
+        }
+        let offset = slot as wgpu::BufferAddress * std::mem::size_of::<Point>() as wgpu::BufferAddress;
+        queue.write_buffer(&self.point_buffer, offset, bytemuck::cast_slice(points));
+    }
+}
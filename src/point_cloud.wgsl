@@ -0,0 +1,63 @@
+struct PointCloudUniform {
+    view_proj: mat4x4f,
+    viewport_size: vec2f,
+    // 点在世界空间中的直径
+    point_size: f32,
+    // 投影矩阵的 y 缩放，即 1 / tan(fovy / 2)
+    projection_scale: f32,
+    min_pixels: f32,
+};
+@group(0) @binding(0)
+var<uniform> uniforms: PointCloudUniform;
+
+struct Point {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    // RGBA8，r 在最低字节
+    color: u32,
+};
+@group(0) @binding(1)
+var<storage, read> points: array<Point>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+    @location(1) corner: vec2f,
+};
+
+// 每个点展开成 6 个顶点（两个三角形）
+var<private> CORNERS: array<vec2f, 6> = array<vec2f, 6>(
+    vec2f(-1.0, -1.0),
+    vec2f(1.0, -1.0),
+    vec2f(1.0, 1.0),
+    vec2f(-1.0, -1.0),
+    vec2f(1.0, 1.0),
+    vec2f(-1.0, 1.0),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let point = points[vertex_index / 6u];
+    let corner = CORNERS[vertex_index % 6u];
+
+    var clip = uniforms.view_proj * vec4f(point.position_x, point.position_y, point.position_z, 1.0);
+    // 近处的点更大，远处不小于 min_pixels
+    let pixels = max(uniforms.min_pixels, uniforms.point_size * uniforms.projection_scale * uniforms.viewport_size.y * 0.5 / clip.w);
+    clip = vec4f(clip.xy + corner * pixels / uniforms.viewport_size * clip.w, clip.zw);
+
+    var out: VertexOutput;
+    out.clip_position = clip;
+    out.color = unpack4x8unorm(point.color);
+    out.corner = corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // 裁成圆点
+    if dot(in.corner, in.corner) > 1.0 {
+        discard;
+    }
+    return in.color;
+}
@@ -0,0 +1,226 @@
+use cgmath::{ InnerSpace, Vector3 };
+use wgpu::util::DeviceExt;
+
+use crate::camera::{ Camera, CameraUniform };
+
+pub struct Portal {
+    // 四边形的四个角，按 0-1-2、0-2-3 组成两个三角形
+    pub corners: [[f32; 3]; 4],
+    // 传送门另一侧（或镜面反射后）的相机，aspect 每帧按屏幕更新
+    pub reflected_camera: Camera,
+    vertex_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+}
+
+impl Portal {
+    // 传送门所在平面，法线朝向远离 reflected_camera 的一侧
+    fn plane(&self) -> (Vector3<f32>, f32) {
+        let [a, b, c, _] = self.corners.map(Vector3::from);
+        let mut normal = (b - a).cross(c - a).normalize();
+        let eye = Vector3::new(self.reflected_camera.eye.x, self.reflected_camera.eye.y, self.reflected_camera.eye.z);
+        if normal.dot(eye - a) > 0.0 {
+            normal = -normal;
+        }
+        (normal, -normal.dot(a))
+    }
+}
+
+// 模板遮罩的两遍绘制：先把传送门轮廓写入模板（Replace），再在模板相等（Equal/Keep）的区域
+// 用 reflected_camera 渲染场景。每个传送门的模板参考值为序号 + 1，最多 255 个
+pub struct PortalRenderer {
+    portals: Vec<Portal>,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    mask_pipeline: wgpu::RenderPipeline,
+    clear_depth_pipeline: wgpu::RenderPipeline,
+    restore_depth_pipeline: wgpu::RenderPipeline,
+}
+
+impl PortalRenderer {
+    pub fn new(device: &wgpu::Device, color_format: wgpu::TextureFormat, depth_stencil_format: wgpu::TextureFormat) -> Self {
+        // 与场景管线第 0 组的布局相同，传送门相机的绑定组可直接用于场景管线
+        let camera_bind_group_layout = CameraUniform::bind_group_layout(device);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Portal Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("portal.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Portal Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let create_pipeline = |label: &str, entry_point: &str, depth_write_enabled: bool, depth_compare, stencil_compare, pass_op| {
+            let stencil_face = wgpu::StencilFaceState {
+                compare: stencil_compare,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op,
+            };
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point,
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: color_format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::empty(),
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: depth_stencil_format,
+                    depth_write_enabled,
+                    depth_compare,
+                    stencil: wgpu::StencilState {
+                        front: stencil_face,
+                        back: stencil_face,
+                        read_mask: 0xff,
+                        write_mask: 0xff,
+                    },
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+
+        // 被场景遮挡的部分不写模板
+        let mask_pipeline = create_pipeline(
+            "Portal Mask Pipeline",
+            "vs_main",
+            false,
+            wgpu::CompareFunction::LessEqual,
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Replace,
+        );
+        let clear_depth_pipeline = create_pipeline(
+            "Portal Clear Depth Pipeline",
+            "vs_far",
+            true,
+            wgpu::CompareFunction::Always,
+            wgpu::CompareFunction::Equal,
+            wgpu::StencilOperation::Keep,
+        );
+        // 内容画完后把传送门平面的深度写回，之后的透明物体能被它正确遮挡
+        let restore_depth_pipeline = create_pipeline(
+            "Portal Restore Depth Pipeline",
+            "vs_main",
+            true,
+            wgpu::CompareFunction::Always,
+            wgpu::CompareFunction::Equal,
+            wgpu::StencilOperation::Keep,
+        );
+
+        Self {
+            portals: Vec::new(),
+            camera_bind_group_layout,
+            mask_pipeline,
+            clear_depth_pipeline,
+            restore_depth_pipeline,
+        }
+    }
+
+    // 场景中透过传送门渲染的管线使用的模板状态：只在参考值相等处绘制，不修改模板
+    pub fn content_stencil_state() -> wgpu::StencilState {
+        let face = wgpu::StencilFaceState {
+            compare: wgpu::CompareFunction::Equal,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op: wgpu::StencilOperation::Keep,
+        };
+        wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask: 0,
+        }
+    }
+
+    pub fn add_portal(&mut self, device: &wgpu::Device, corners: [[f32; 3]; 4], reflected_camera: Camera) -> usize {
+        assert!(self.portals.len() < u8::MAX as usize, "at most 255 portals fit in the stencil buffer");
+        let vertices = [corners[0], corners[1], corners[2], corners[0], corners[2], corners[3]];
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Portal Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Portal Camera Buffer"),
+            contents: bytemuck::cast_slice(&[CameraUniform::new()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("portal_camera_bind_group"),
+            layout: &self.camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        self.portals.push(Portal { corners, reflected_camera, vertex_buffer, camera_buffer, camera_bind_group });
+        self.portals.len() - 1
+    }
+
+    pub fn portals(&self) -> &[Portal] {
+        &self.portals
+    }
+
+    pub fn portal_mut(&mut self, index: usize) -> Option<&mut Portal> {
+        self.portals.get_mut(index)
+    }
+
+    // 更新每个传送门相机的 uniform；裁剪平面设为传送门所在平面，去掉相机与传送门之间的物体
+    pub fn update(&mut self, queue: &wgpu::Queue, aspect: f32) {
+        for portal in &mut self.portals {
+            portal.reflected_camera.aspect = aspect;
+            let (normal, distance) = portal.plane();
+            let mut uniform = CameraUniform::new();
+            uniform.update_view_proj(&portal.reflected_camera);
+            uniform.set_clip_plane(normal, distance);
+            queue.write_buffer(&portal.camera_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        }
+    }
+
+    // 在已有场景深度的渲染通道中绘制所有传送门，通道的深度模板附件需要带模板且已清零
+    // draw_scene 用传入的相机绑定组（第 0 组）和 content_stencil_state 的管线绘制场景
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: &'a wgpu::BindGroup,
+        mut draw_scene: impl FnMut(&mut wgpu::RenderPass<'a>, &'a wgpu::BindGroup),
+    ) {
+        for (index, portal) in self.portals.iter().enumerate() {
+            let reference = index as u32 + 1;
+            render_pass.set_stencil_reference(reference);
+            render_pass.set_vertex_buffer(0, portal.vertex_buffer.slice(..));
+
+            render_pass.set_pipeline(&self.mask_pipeline);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.draw(0..6, 0..1);
+
+            render_pass.set_pipeline(&self.clear_depth_pipeline);
+            render_pass.draw(0..6, 0..1);
+
+            draw_scene(render_pass, &portal.camera_bind_group);
+
+            render_pass.set_stencil_reference(reference);
+            render_pass.set_pipeline(&self.restore_depth_pipeline);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, portal.vertex_buffer.slice(..));
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+// 传送门/镜面的模板遮罩：只写模板与深度，不写颜色
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(@location(0) position: vec3f) -> @builtin(position) vec4f {
+    return camera.view_proj * vec4f(position, 1.0);
+}
+
+// 把遮罩区域的深度推到远平面，传送门后的内容才能通过深度测试
+@vertex
+fn vs_far(@location(0) position: vec3f) -> @builtin(position) vec4f {
+    let clip = camera.view_proj * vec4f(position, 1.0);
+    return vec4f(clip.xy, clip.w, clip.w);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return vec4f(0.0);
+}
@@ -0,0 +1,221 @@
+use wgpu::util::DeviceExt;
+
+use crate::texture::Texture;
+
+pub const OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+// 采样间隔依次为 1、2、4、8、16 像素
+pub const ITERATIONS: u32 = 5;
+const WORKGROUP_SIZE: u32 = 8;
+
+// 边缘停止函数的强度，越大越不容易跨越对应的边缘
+#[derive(Debug, Clone, Copy)]
+pub struct AtrousConfig {
+    // 法线夹角余弦的指数
+    pub normal_weight: f32,
+    // 世界空间距离平方的衰减系数
+    pub position_weight: f32,
+    // 亮度差的衰减系数，每次迭代加倍
+    pub luma_weight: f32,
+}
+
+impl Default for AtrousConfig {
+    fn default() -> Self {
+        Self {
+            normal_weight: 64.0,
+            position_weight: 1.0,
+            luma_weight: 4.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct AtrousParams {
+    stride: i32,
+    normal_weight: f32,
+    position_weight: f32,
+    luma_weight: f32,
+}
+
+impl AtrousParams {
+    fn new(config: &AtrousConfig, iteration: u32) -> Self {
+        let scale = (1 << iteration) as f32;
+        Self {
+            stride: 1 << iteration,
+            normal_weight: config.normal_weight,
+            position_weight: config.position_weight,
+            luma_weight: config.luma_weight * scale,
+        }
+    }
+}
+
+// À-trous 小波滤波（Dammertz et al. 2010）：5x5 的 B3 样条核以逐次加倍的间隔迭代，
+// 用 G-buffer 的法线与位置作为边缘停止函数。输入为路径追踪的累积纹理（alpha 为样本数），
+// 时间方向的降噪由累积本身完成，结果写入单独的纹理，累积继续进行时显示它
+pub struct AtrousDenoiser {
+    pub config: AtrousConfig,
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params: Vec<wgpu::Buffer>,
+    // 两张纹理乒乓，迭代次数为奇数时最终结果落在 output
+    output: Option<Texture>,
+    scratch: Option<Texture>,
+    size: (u32, u32),
+}
+
+impl AtrousDenoiser {
+    pub fn new(device: &wgpu::Device, config: AtrousConfig) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("A-Trous Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("atrous.wgsl").into()),
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("atrous_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                texture_entry(2),
+                texture_entry(3),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: OUTPUT_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("A-Trous Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("A-Trous Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let params = (0..ITERATIONS)
+            .map(|iteration| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("A-Trous Params"),
+                    contents: bytemuck::cast_slice(&[AtrousParams::new(&config, iteration)]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+            })
+            .collect();
+
+        Self {
+            config,
+            pipeline,
+            bind_group_layout,
+            params,
+            output: None,
+            scratch: None,
+            size: (0, 0),
+        }
+    }
+
+    pub fn set_config(&mut self, queue: &wgpu::Queue, config: AtrousConfig) {
+        self.config = config;
+        for (iteration, buffer) in self.params.iter().enumerate() {
+            let params = AtrousParams::new(&self.config, iteration as u32);
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[params]));
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.size = (width.max(1), height.max(1));
+        let create = |label| {
+            Texture::create_render_target(device, self.size.0, self.size.1, OUTPUT_FORMAT, wgpu::TextureUsages::STORAGE_BINDING, label)
+        };
+        self.output = Some(create("A-Trous Output"));
+        self.scratch = Some(create("A-Trous Scratch"));
+    }
+
+    // 降噪后的颜色，alpha 恒为 1
+    pub fn output_view(&self) -> &wgpu::TextureView {
+        &self.output.as_ref().expect("AtrousDenoiser::resize must be called before output_view").view
+    }
+
+    pub fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        color: &wgpu::TextureView,
+        normal: &wgpu::TextureView,
+        position: &wgpu::TextureView,
+    ) {
+        let output = self.output.as_ref().expect("AtrousDenoiser::resize must be called before apply");
+        let scratch = self.scratch.as_ref().expect("AtrousDenoiser::resize must be called before apply");
+        let workgroups = (self.size.0.div_ceil(WORKGROUP_SIZE), self.size.1.div_ceil(WORKGROUP_SIZE));
+
+        let mut input = color;
+        for (iteration, params) in self.params.iter().enumerate() {
+            let target = if iteration % 2 == 0 { output } else { scratch };
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("atrous_bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(input),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(normal),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(position),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(&target.view),
+                    },
+                ],
+            });
+
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("A-Trous Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+            drop(compute_pass);
+
+            input = &target.view;
+        }
+    }
+}
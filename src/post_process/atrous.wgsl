@@ -0,0 +1,88 @@
+struct AtrousParams {
+    // 本次迭代的采样间隔（像素）
+    stride: i32,
+    normal_weight: f32,
+    position_weight: f32,
+    luma_weight: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: AtrousParams;
+// rgb / alpha 为颜色；第一次迭代的输入是路径追踪的累积和
+@group(0) @binding(1)
+var t_input: texture_2d<f32>;
+@group(0) @binding(2)
+var t_normal: texture_2d<f32>;
+@group(0) @binding(3)
+var t_position: texture_2d<f32>;
+@group(0) @binding(4)
+var t_output: texture_storage_2d<rgba32float, write>;
+
+// B3 样条核 (1/16, 1/4, 3/8, 1/4, 1/16)
+fn kernel(offset: i32) -> f32 {
+    switch abs(offset) {
+        case 0: {
+            return 0.375;
+        }
+        case 1: {
+            return 0.25;
+        }
+        default: {
+            return 0.0625;
+        }
+    }
+}
+
+fn load_color(pixel: vec2<i32>) -> vec3f {
+    let color = textureLoad(t_input, pixel, 0);
+    return color.rgb / max(color.a, 1.0);
+}
+
+fn luminance(color: vec3f) -> f32 {
+    return dot(color, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = vec2<i32>(textureDimensions(t_input));
+    let pixel = vec2<i32>(id.xy);
+    if pixel.x >= size.x || pixel.y >= size.y {
+        return;
+    }
+
+    let center_color = load_color(pixel);
+    let center_luma = luminance(center_color);
+    let center_normal = textureLoad(t_normal, pixel, 0);
+    let center_position = textureLoad(t_position, pixel, 0);
+
+    var sum = vec3f(0.0);
+    var total_weight = 0.0;
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let sample_pixel = clamp(pixel + vec2<i32>(x, y) * params.stride, vec2<i32>(0), size - 1);
+            let color = load_color(sample_pixel);
+            let normal = textureLoad(t_normal, sample_pixel, 0);
+            let position = textureLoad(t_position, sample_pixel, 0);
+
+            // 命中几何体与命中天空的像素互不混合
+            if position.w != center_position.w {
+                continue;
+            }
+
+            let kernel = kernel(x) * kernel(y);
+            let offset = position.xyz - center_position.xyz;
+            let w_normal = pow(max(dot(normal.xyz, center_normal.xyz), 0.0), params.normal_weight);
+            let w_position = exp(-dot(offset, offset) * params.position_weight);
+            let w_luma = exp(-abs(luminance(color) - center_luma) * params.luma_weight);
+            // 天空像素没有几何信息，只按亮度停止
+            let w_geometry = select(1.0, w_normal * w_position, center_position.w > 0.0);
+
+            let weight = kernel * w_geometry * w_luma;
+            sum += color * weight;
+            total_weight += weight;
+        }
+    }
+
+    // 中心像素的核权重恒为正，total_weight 不会为 0
+    textureStore(t_output, pixel, vec4f(sum / total_weight, 1.0));
+}
@@ -0,0 +1,311 @@
+use std::cell::Cell;
+use std::time::Instant;
+
+use super::PostProcessEffect;
+
+const BIN_COUNT: u64 = 256;
+const TILE_SIZE: u32 = 16;
+// ExposureState 只有一个 f32，按 16 字节分配以满足 uniform 缓冲的对齐要求
+const STATE_SIZE: wgpu::BufferAddress = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ExposureParams {
+    min_log2_luminance: f32,
+    log2_luminance_range: f32,
+    low_percentile: f32,
+    high_percentile: f32,
+    key: f32,
+    blend: f32,
+    _padding: [f32; 2],
+}
+
+// 自动曝光：统计场景亮度直方图，取 10%~90% 百分位之间的平均亮度作为目标曝光，
+// 随时间指数平滑后在色调映射前乘以 exp(-log_exposure)，输出为 ACES 映射后的 LDR 颜色
+pub struct ExposureHistogram {
+    pub min_log2_luminance: f32,
+    pub log2_luminance_range: f32,
+    pub low_percentile: f32,
+    pub high_percentile: f32,
+    pub key: f32,
+    // 每秒向目标曝光靠拢的速度，越大适应越快
+    pub adaptation_speed: f32,
+    histogram_pipeline: wgpu::ComputePipeline,
+    exposure_pipeline: wgpu::ComputePipeline,
+    compute_bind_group_layout: wgpu::BindGroupLayout,
+    tonemap_pipeline: wgpu::RenderPipeline,
+    tonemap_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    histogram_buffer: wgpu::Buffer,
+    // 跨帧保留的平滑曝光，计算着色器读写
+    state_buffer: wgpu::Buffer,
+    // state_buffer 的副本，供色调映射的片元着色器读取
+    exposure_buffer: wgpu::Buffer,
+    size: (u32, u32),
+    last_apply: Cell<Option<Instant>>,
+}
+
+impl ExposureHistogram {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Exposure Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("exposure.wgsl").into()),
+        });
+
+        let texture_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let buffer_entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = wgpu::BufferBindingType::Storage { read_only: false };
+        let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("exposure_compute_bind_group_layout"),
+            entries: &[
+                texture_entry(wgpu::ShaderStages::COMPUTE),
+                buffer_entry(1, wgpu::ShaderStages::COMPUTE, storage),
+                buffer_entry(2, wgpu::ShaderStages::COMPUTE, wgpu::BufferBindingType::Uniform),
+                buffer_entry(3, wgpu::ShaderStages::COMPUTE, storage),
+            ],
+        });
+        let tonemap_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("exposure_tonemap_bind_group_layout"),
+            entries: &[
+                texture_entry(wgpu::ShaderStages::FRAGMENT),
+                buffer_entry(4, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+
+        let compute_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Exposure Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let create_compute = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&compute_layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+        let histogram_pipeline = create_compute("Exposure Histogram Pipeline", "cs_histogram");
+        let exposure_pipeline = create_compute("Exposure Average Pipeline", "cs_exposure");
+
+        let tonemap_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Exposure Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&tonemap_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let tonemap_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Exposure Tonemap Pipeline"),
+            layout: Some(&tonemap_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_tonemap",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: super::SCENE_COLOR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let create_buffer = |label, size, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let params_buffer = create_buffer(
+            "Exposure Params Buffer",
+            std::mem::size_of::<ExposureParams>() as wgpu::BufferAddress,
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let histogram_buffer = create_buffer(
+            "Exposure Histogram Buffer",
+            BIN_COUNT * std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let state_buffer = create_buffer(
+            "Exposure State Buffer",
+            STATE_SIZE,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let exposure_buffer = create_buffer(
+            "Exposure Buffer",
+            STATE_SIZE,
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+
+        Self {
+            min_log2_luminance: -10.0,
+            log2_luminance_range: 22.0,
+            low_percentile: 0.1,
+            high_percentile: 0.9,
+            key: 0.18,
+            adaptation_speed: 1.5,
+            histogram_pipeline,
+            exposure_pipeline,
+            compute_bind_group_layout,
+            tonemap_pipeline,
+            tonemap_bind_group_layout,
+            params_buffer,
+            histogram_buffer,
+            state_buffer,
+            exposure_buffer,
+            size: (0, 0),
+            last_apply: Cell::new(None),
+        }
+    }
+
+    // 当前平滑后的 log 曝光（16 字节，首个 f32 有效），可供其他着色器复用
+    pub fn exposure_buffer(&self) -> &wgpu::Buffer {
+        &self.exposure_buffer
+    }
+
+    // 下一帧直接跳到目标曝光，用于切换场景或相机瞬移
+    pub fn reset_adaptation(&self) {
+        self.last_apply.set(None);
+    }
+
+    // 首帧没有上一帧的曝光，直接采用目标值
+    fn blend_factor(&self) -> f32 {
+        let now = Instant::now();
+        let blend = match self.last_apply.replace(Some(now)) {
+            Some(last) => 1.0 - (-(now - last).as_secs_f32() * self.adaptation_speed).exp(),
+            None => 1.0,
+        };
+        blend.clamp(0.0, 1.0)
+    }
+}
+
+impl PostProcessEffect for ExposureHistogram {
+    fn label(&self) -> &str {
+        "Exposure Histogram"
+    }
+
+    fn uses_compute(&self) -> bool {
+        true
+    }
+
+    fn resize(&mut self, _device: &wgpu::Device, width: u32, height: u32) {
+        self.size = (width, height);
+    }
+
+    fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let params = ExposureParams {
+            min_log2_luminance: self.min_log2_luminance,
+            log2_luminance_range: self.log2_luminance_range.max(f32::EPSILON),
+            low_percentile: self.low_percentile,
+            high_percentile: self.high_percentile.max(self.low_percentile),
+            key: self.key.max(f32::EPSILON),
+            blend: self.blend_factor(),
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        encoder.clear_buffer(&self.histogram_buffer, 0, None);
+
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("exposure_compute_bind_group"),
+            layout: &self.compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.histogram_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.state_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let (width, height) = self.size;
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Exposure Histogram Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_bind_group(0, &compute_bind_group, &[]);
+        compute_pass.set_pipeline(&self.histogram_pipeline);
+        compute_pass.dispatch_workgroups(width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE), 1);
+        compute_pass.set_pipeline(&self.exposure_pipeline);
+        compute_pass.dispatch_workgroups(1, 1, 1);
+        drop(compute_pass);
+
+        encoder.copy_buffer_to_buffer(&self.state_buffer, 0, &self.exposure_buffer, 0, STATE_SIZE);
+
+        let tonemap_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("exposure_tonemap_bind_group"),
+            layout: &self.tonemap_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: self.exposure_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Exposure Tonemap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.tonemap_pipeline);
+        render_pass.set_bind_group(0, &tonemap_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,132 @@
+const BIN_COUNT: u32 = 256u;
+
+struct ExposureParams {
+    // 直方图覆盖的 log2 亮度范围
+    min_log2_luminance: f32,
+    log2_luminance_range: f32,
+    low_percentile: f32,
+    high_percentile: f32,
+    // 曝光后平均亮度对应的中灰
+    key: f32,
+    // 平滑系数 1 - exp(-dt * speed)，由 CPU 按帧间隔计算
+    blend: f32,
+};
+
+struct ExposureState {
+    // ln(平均亮度 / key)，色调映射时乘以 exp(-log_exposure)
+    log_exposure: f32,
+};
+
+@group(0) @binding(0)
+var t_scene: texture_2d<f32>;
+@group(0) @binding(1)
+var<storage, read_write> histogram: array<atomic<u32>, 256>;
+@group(0) @binding(2)
+var<uniform> params: ExposureParams;
+@group(0) @binding(3)
+var<storage, read_write> state: ExposureState;
+
+fn luminance(color: vec3f) -> f32 {
+    return dot(color, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+// 0 号桶存放接近全黑的像素，不参与百分位统计
+fn luminance_bin(l: f32) -> u32 {
+    if l < 1e-5 {
+        return 0u;
+    }
+    let t = saturate((log2(l) - params.min_log2_luminance) / params.log2_luminance_range);
+    return u32(t * 254.0 + 1.0);
+}
+
+// ---- 第一次派发：统计亮度直方图 ----
+
+var<workgroup> local_histogram: array<atomic<u32>, 256>;
+
+@compute @workgroup_size(16, 16, 1)
+fn cs_histogram(@builtin(global_invocation_id) id: vec3u, @builtin(local_invocation_index) local_index: u32) {
+    atomicStore(&local_histogram[local_index], 0u);
+    workgroupBarrier();
+
+    if all(id.xy < textureDimensions(t_scene)) {
+        let color = textureLoad(t_scene, vec2<i32>(id.xy), 0).rgb;
+        atomicAdd(&local_histogram[luminance_bin(luminance(color))], 1u);
+    }
+    workgroupBarrier();
+
+    // 每个线程负责一个桶，先在工作组内累加再写回全局，减少全局原子操作
+    let count = atomicLoad(&local_histogram[local_index]);
+    if count > 0u {
+        atomicAdd(&histogram[local_index], count);
+    }
+}
+
+// ---- 第二次派发：取百分位之间的平均亮度，平滑后写入曝光 ----
+
+var<workgroup> bins: array<u32, 256>;
+
+@compute @workgroup_size(256, 1, 1)
+fn cs_exposure(@builtin(local_invocation_index) local_index: u32) {
+    bins[local_index] = atomicLoad(&histogram[local_index]);
+    workgroupBarrier();
+    if local_index != 0u {
+        return;
+    }
+
+    var total = 0u;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        total += bins[i];
+    }
+    if total == 0u {
+        return;
+    }
+
+    // 每个桶落在 [low, high] 内的部分按桶中心加权平均
+    let low = f32(total) * params.low_percentile;
+    let high = f32(total) * params.high_percentile;
+    var cumulative = 0.0;
+    var weighted = 0.0;
+    var weight = 0.0;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        let begin = cumulative;
+        cumulative += f32(bins[i]);
+        let inside = clamp(cumulative, low, high) - clamp(begin, low, high);
+        weighted += inside * f32(i);
+        weight += inside;
+    }
+    let bin = select(weighted / weight, 1.0, weight <= 0.0);
+    let log2_average = (bin - 0.5) / 254.0 * params.log2_luminance_range + params.min_log2_luminance;
+    let target_exposure = log2_average * log(2.0) - log(params.key);
+
+    state.log_exposure = mix(state.log_exposure, target_exposure, params.blend);
+}
+
+// ---- 色调映射 ----
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+};
+
+// 用一个覆盖全屏的大三角形代替四边形
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    return out;
+}
+
+// state 的副本，片元着色器只读取 uniform
+@group(0) @binding(4)
+var<uniform> exposure: ExposureState;
+
+// Narkowicz 的 ACES 近似
+fn aces(x: vec3f) -> vec3f {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3f(0.0), vec3f(1.0));
+}
+
+@fragment
+fn fs_tonemap(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureLoad(t_scene, vec2<i32>(in.clip_position.xy), 0);
+    return vec4f(aces(color.rgb * exp(-exposure.log_exposure)), color.a);
+}
@@ -0,0 +1,277 @@
+use std::marker::PhantomData;
+
+use wgpu::util::DeviceExt;
+
+use super::PostProcessEffect;
+use crate::texture::Texture;
+
+pub const MAX_KERNEL_RADIUS: u32 = 31;
+const TILE_SIZE: u32 = 128;
+
+// 模糊可处理的纹理格式
+pub trait BlurFormat {
+    const FORMAT: wgpu::TextureFormat;
+    const WGSL_FORMAT: &'static str;
+}
+
+// 单通道，用于环境光遮蔽。作为存储纹理需要 TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+pub struct R8Unorm;
+
+impl BlurFormat for R8Unorm {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+    const WGSL_FORMAT: &'static str = "r8unorm";
+}
+
+// HDR 颜色，用于泛光
+pub struct Rgba16Float;
+
+impl BlurFormat for Rgba16Float {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    const WGSL_FORMAT: &'static str = "rgba16float";
+}
+
+// 32 位单通道浮点，用于指数阴影图
+pub struct R32Float;
+
+impl BlurFormat for R32Float {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+    const WGSL_FORMAT: &'static str = "r32float";
+}
+
+// 32 位浮点，用于方差阴影图的矩。Rg32Float 在 GL 后端不能作为存储纹理，因此输出用四通道
+pub struct Rgba32Float;
+
+impl BlurFormat for Rgba32Float {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+    const WGSL_FORMAT: &'static str = "rgba32float";
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BlurParams {
+    weights: [[f32; 4]; 8],
+    radius: u32,
+    direction: u32,
+    _padding: [u32; 2],
+}
+
+impl BlurParams {
+    fn new(sigma: f32, kernel_radius: u32, direction: u32) -> Self {
+        let weights = gaussian_weights(sigma, kernel_radius);
+        let mut packed = [[0.0; 4]; 8];
+        for (i, weight) in weights.iter().enumerate() {
+            packed[i / 4][i % 4] = *weight;
+        }
+        Self {
+            weights: packed,
+            radius: kernel_radius,
+            direction,
+            _padding: [0; 2],
+        }
+    }
+}
+
+// 一维高斯核的一半（含中心），归一化后 w0 + 2 * Σ wk = 1
+pub fn gaussian_weights(sigma: f32, kernel_radius: u32) -> Vec<f32> {
+    let kernel_radius = kernel_radius.min(MAX_KERNEL_RADIUS);
+    let sigma = sigma.max(f32::EPSILON);
+    let mut weights = (0..=kernel_radius)
+        .map(|k| (-((k * k) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect::<Vec<_>>();
+
+    let total = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+    for weight in weights.iter_mut() {
+        *weight /= total;
+    }
+    weights
+}
+
+// 可分离高斯模糊：先水平后垂直，两次计算着色器调度
+pub struct GaussianBlurPass<F: BlurFormat> {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    horizontal_params: wgpu::Buffer,
+    vertical_params: wgpu::Buffer,
+    // 水平结果，作为垂直通道的输入
+    intermediate: Option<Texture>,
+    size: (u32, u32),
+    sigma: f32,
+    kernel_radius: u32,
+    _format: PhantomData<F>,
+}
+
+impl<F: BlurFormat> GaussianBlurPass<F> {
+    pub fn new(device: &wgpu::Device, sigma: f32, kernel_radius: u32) -> Self {
+        let kernel_radius = kernel_radius.min(MAX_KERNEL_RADIUS);
+        let source = include_str!("gaussian_blur.wgsl").replace("{{format}}", F::WGSL_FORMAT);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gaussian Blur Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("gaussian_blur_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: F::FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Gaussian Blur Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Gaussian Blur Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let create_params = |label, direction| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&[BlurParams::new(sigma, kernel_radius, direction)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        };
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            horizontal_params: create_params("Gaussian Blur Horizontal Params", 0),
+            vertical_params: create_params("Gaussian Blur Vertical Params", 1),
+            intermediate: None,
+            size: (0, 0),
+            sigma,
+            kernel_radius,
+            _format: PhantomData,
+        }
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+
+    pub fn kernel_radius(&self) -> u32 {
+        self.kernel_radius
+    }
+
+    pub fn set_kernel(&mut self, queue: &wgpu::Queue, sigma: f32, kernel_radius: u32) {
+        self.sigma = sigma;
+        self.kernel_radius = kernel_radius.min(MAX_KERNEL_RADIUS);
+        for (buffer, direction) in [(&self.horizontal_params, 0), (&self.vertical_params, 1)] {
+            let params = BlurParams::new(self.sigma, self.kernel_radius, direction);
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[params]));
+        }
+    }
+
+    fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        params: &wgpu::Buffer,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+        workgroups: (u32, u32),
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("gaussian_blur_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(output),
+                },
+            ],
+        });
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Gaussian Blur Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+    }
+}
+
+impl<F: BlurFormat> PostProcessEffect for GaussianBlurPass<F> {
+    fn label(&self) -> &str {
+        "Gaussian Blur"
+    }
+
+    fn uses_compute(&self) -> bool {
+        true
+    }
+
+    fn output_usage(&self) -> wgpu::TextureUsages {
+        wgpu::TextureUsages::STORAGE_BINDING
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.size = (width, height);
+        self.intermediate = Some(Texture::create_render_target(
+            device,
+            width,
+            height,
+            F::FORMAT,
+            wgpu::TextureUsages::STORAGE_BINDING,
+            "Gaussian Blur Intermediate",
+        ));
+    }
+
+    fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+        device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) {
+        let intermediate = self.intermediate.as_ref().expect("GaussianBlurPass::resize must be called before apply");
+        let (width, height) = self.size;
+
+        // 每个工作组沿模糊方向处理 TILE_SIZE 个像素，另一方向每行一个工作组
+        self.dispatch(encoder, device, &self.horizontal_params, input, &intermediate.view, (width.div_ceil(TILE_SIZE), height));
+        self.dispatch(encoder, device, &self.vertical_params, &intermediate.view, output, (height.div_ceil(TILE_SIZE), width));
+    }
+}
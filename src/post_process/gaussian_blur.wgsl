@@ -0,0 +1,63 @@
+// {{format}} 在创建管线时替换为具体的存储纹理格式
+const TILE_SIZE: u32 = 128u;
+const MAX_RADIUS: u32 = 31u;
+
+struct BlurParams {
+    // 32 个权重打包为 vec4，满足 uniform 数组 16 字节步长要求
+    weights: array<vec4f, 8>,
+    radius: u32,
+    // 0 为水平，1 为垂直
+    direction: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: BlurParams;
+@group(0) @binding(1)
+var t_input: texture_2d<f32>;
+@group(0) @binding(2)
+var t_output: texture_storage_2d<{{format}}, write>;
+
+// 一个工作组处理一行中的 TILE_SIZE 个像素，外加两侧各 radius 个像素
+var<workgroup> tile: array<vec4f, 190>;
+
+fn weight(i: u32) -> f32 {
+    return params.weights[i / 4u][i % 4u];
+}
+
+fn texel(along: i32, across: i32) -> vec2<i32> {
+    if params.direction == 0u {
+        return vec2<i32>(along, across);
+    }
+    return vec2<i32>(across, along);
+}
+
+@compute @workgroup_size(128, 1, 1)
+fn cs_main(
+    @builtin(workgroup_id) workgroup_id: vec3u,
+    @builtin(local_invocation_id) local_id: vec3u,
+) {
+    let dims = vec2<i32>(textureDimensions(t_input));
+    let length = select(dims.y, dims.x, params.direction == 0u);
+    let radius = min(params.radius, MAX_RADIUS);
+    let start = i32(workgroup_id.x * TILE_SIZE) - i32(radius);
+    let across = i32(workgroup_id.y);
+
+    // 协作地把采样范围读入共享内存，边缘处钳制
+    for (var i = local_id.x; i < TILE_SIZE + 2u * radius; i += TILE_SIZE) {
+        let along = clamp(start + i32(i), 0, length - 1);
+        tile[i] = textureLoad(t_input, texel(along, across), 0);
+    }
+    workgroupBarrier();
+
+    let along = i32(workgroup_id.x * TILE_SIZE + local_id.x);
+    if along >= length {
+        return;
+    }
+
+    let center = local_id.x + radius;
+    var sum = tile[center] * weight(0u);
+    for (var k = 1u; k <= radius; k++) {
+        sum += (tile[center - k] + tile[center + k]) * weight(k);
+    }
+    textureStore(t_output, texel(along, across), sum);
+}
@@ -0,0 +1,309 @@
+use cgmath::Vector3;
+
+use crate::camera::Camera;
+use crate::texture::Texture;
+
+pub const DEFAULT_SAMPLE_COUNT: u32 = 64;
+// 遮挡图为半分辨率，径向模糊本身就很平滑
+const MASK_SCALE: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GodRayConfig {
+    // 太阳在屏幕上的纹理坐标，左上角为 (0, 0)；可以在屏幕之外
+    pub sun_screen_pos: [f32; 2],
+    // 采样路径占像素到太阳距离的比例，1 时一直走到太阳处
+    pub density: f32,
+    // 每一步对后续样本权重的衰减
+    pub decay: f32,
+    // 每个样本的权重，结果按它缩放后叠加到场景上
+    pub weight: f32,
+}
+
+impl Default for GodRayConfig {
+    fn default() -> Self {
+        Self {
+            sun_screen_pos: [0.5, 0.25],
+            density: 0.9,
+            decay: 0.97,
+            weight: 0.02,
+        }
+    }
+}
+
+impl GodRayConfig {
+    // 沿 sun_direction（从场景指向太阳）无限远处的点投影到屏幕，太阳在相机背后时返回 None
+    pub fn sun_screen_position(camera: &Camera, sun_direction: Vector3<f32>) -> Option<[f32; 2]> {
+        // w = 0 的点只受旋转影响，相当于无限远处
+        let clip = camera.build_view_projection_matrix() * sun_direction.extend(0.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some([clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GodRayParams {
+    sun_screen_pos: [f32; 2],
+    density: f32,
+    decay: f32,
+    weight: f32,
+    sample_count: u32,
+    _padding: [f32; 2],
+}
+
+// 体积光（Mitchell, GPU Gems 3 第 13 章）：先把深度为最大值（天空）的场景颜色写入半分辨率遮挡图，
+// 被几何体挡住的像素为黑色；再从每个像素向太阳的屏幕位置径向采样遮挡图，
+// 按 decay 逐步衰减累加，最后相加混合到场景上
+pub struct GodRayPass {
+    pub config: GodRayConfig,
+    pub sample_count: u32,
+    mask: Texture,
+    mask_pipeline: wgpu::RenderPipeline,
+    mask_bind_group_layout: wgpu::BindGroupLayout,
+    blur_pipeline: wgpu::RenderPipeline,
+    blur_bind_group: wgpu::BindGroup,
+    blur_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+}
+
+impl GodRayPass {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, scene_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("God Ray Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("god_rays.wgsl").into()),
+        });
+
+        let texture_entry = |binding, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        // 深度以不可过滤的浮点纹理绑定：GL 后端不支持对 texture_depth_2d 使用 textureLoad
+        let mask_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("god_ray_mask_bind_group_layout"),
+            entries: &[texture_entry(0, false), texture_entry(1, false)],
+        });
+        let blur_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("god_ray_blur_bind_group_layout"),
+            entries: &[
+                texture_entry(0, true),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let create_pipeline = |label, layout: &wgpu::BindGroupLayout, entry_point, blend| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: scene_format,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: None,
+                multiview: None,
+            })
+        };
+        let mask_pipeline = create_pipeline("God Ray Mask Pipeline", &mask_bind_group_layout, "fs_mask", wgpu::BlendState::REPLACE);
+        // 只加到场景颜色上，alpha 不变
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+        };
+        let blur_pipeline = create_pipeline("God Ray Blur Pipeline", &blur_bind_group_layout, "fs_blur", additive);
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("God Ray Params Buffer"),
+            size: std::mem::size_of::<GodRayParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("God Ray Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let mask = Self::create_mask(device, width, height, scene_format);
+        let blur_bind_group = Self::create_blur_bind_group(device, &blur_bind_group_layout, &mask, &sampler, &params_buffer);
+
+        Self {
+            config: GodRayConfig::default(),
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            mask,
+            mask_pipeline,
+            mask_bind_group_layout,
+            blur_pipeline,
+            blur_bind_group,
+            blur_bind_group_layout,
+            params_buffer,
+            sampler,
+        }
+    }
+
+    fn create_mask(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Texture {
+        let usage = wgpu::TextureUsages::empty();
+        Texture::create_render_target(device, width / MASK_SCALE, height / MASK_SCALE, format, usage, "God Ray Mask")
+    }
+
+    fn create_blur_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        mask: &Texture,
+        sampler: &wgpu::Sampler,
+        params_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("god_ray_blur_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&mask.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.mask = Self::create_mask(device, width, height, self.mask.texture.format());
+        self.blur_bind_group =
+            Self::create_blur_bind_group(device, &self.blur_bind_group_layout, &self.mask, &self.sampler, &self.params_buffer);
+    }
+
+    // scene 既是遮挡图的来源也是叠加的目标，两者在不同的通道中使用；depth 为同尺寸的场景深度缓冲
+    pub fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        scene: &wgpu::TextureView,
+        depth: &wgpu::Texture,
+    ) {
+        let params = GodRayParams {
+            sun_screen_pos: self.config.sun_screen_pos,
+            density: self.config.density,
+            decay: self.config.decay,
+            weight: self.config.weight,
+            sample_count: self.sample_count,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        // 场景深度可能带模板，采样时只取深度部分
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("God Ray Scene Depth"),
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        });
+        let mask_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("god_ray_mask_bind_group"),
+            layout: &self.mask_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(scene),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&depth_view),
+                },
+            ],
+        });
+
+        let mut mask_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("God Ray Mask Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.mask.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        mask_pass.set_pipeline(&self.mask_pipeline);
+        mask_pass.set_bind_group(0, &mask_bind_group, &[]);
+        mask_pass.draw(0..3, 0..1);
+        drop(mask_pass);
+
+        let mut blur_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("God Ray Blur Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: scene,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        blur_pass.set_pipeline(&self.blur_pipeline);
+        blur_pass.set_bind_group(0, &self.blur_bind_group, &[]);
+        blur_pass.draw(0..3, 0..1);
+    }
+}
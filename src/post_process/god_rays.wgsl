@@ -0,0 +1,60 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_scene: texture_2d<f32>;
+@group(0) @binding(1)
+var t_depth: texture_2d<f32>;
+
+// 遮挡图：只保留天空（深度为最大值）的场景颜色
+@fragment
+fn fs_mask(in: VertexOutput) -> @location(0) vec4f {
+    let size = vec2f(textureDimensions(t_scene));
+    let texel = vec2<i32>(min(in.uv * size, size - 1.0));
+    if textureLoad(t_depth, texel, 0).r < 1.0 {
+        return vec4f(0.0, 0.0, 0.0, 1.0);
+    }
+    return vec4f(textureLoad(t_scene, texel, 0).rgb, 1.0);
+}
+
+struct GodRayParams {
+    sun_screen_pos: vec2f,
+    density: f32,
+    decay: f32,
+    weight: f32,
+    sample_count: u32,
+};
+
+@group(0) @binding(0)
+var t_mask: texture_2d<f32>;
+@group(0) @binding(1)
+var s_mask: sampler;
+@group(0) @binding(2)
+var<uniform> params: GodRayParams;
+
+// 从像素向太阳位置步进，越靠近太阳的样本经过的衰减越多
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4f {
+    let count = max(params.sample_count, 1u);
+    let delta = (in.uv - params.sun_screen_pos) * params.density / f32(count);
+    var uv = in.uv;
+    var illumination_decay = 1.0;
+    var color = vec3f(0.0);
+    for (var i = 0u; i < count; i++) {
+        uv -= delta;
+        color += textureSampleLevel(t_mask, s_mask, uv, 0.0).rgb * illumination_decay * params.weight;
+        illumination_decay *= params.decay;
+    }
+    return vec4f(color, 0.0);
+}
@@ -0,0 +1,156 @@
+pub mod atrous;
+mod blit;
+pub mod exposure;
+pub mod gaussian_blur;
+pub mod god_rays;
+
+pub use blit::Blit;
+
+use crate::texture::Texture;
+
+// 场景先渲染到 HDR 离屏纹理，再依次经过后处理效果
+pub const SCENE_COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+pub trait PostProcessEffect {
+    fn label(&self) -> &str;
+
+    // 输出纹理除渲染附件、采样之外还需要的用途，例如计算着色器写入需要 STORAGE_BINDING
+    fn output_usage(&self) -> wgpu::TextureUsages {
+        wgpu::TextureUsages::empty()
+    }
+
+    // 需要计算着色器的效果在不支持的后端（WebGL2）上被跳过
+    fn uses_compute(&self) -> bool {
+        false
+    }
+
+    // 渲染尺寸变化或效果加入栈时调用，用于分配效果内部的中间纹理
+    fn resize(&mut self, _device: &wgpu::Device, _width: u32, _height: u32) {}
+
+    fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    );
+}
+
+// 按顺序执行的后处理效果列表，运行时可增删与调换顺序
+pub struct PostProcessStack {
+    effects: Vec<Box<dyn PostProcessEffect>>,
+    format: wgpu::TextureFormat,
+    // 乒乓纹理：效果轮流从一张读、向另一张写
+    targets: Option<[Texture; 2]>,
+    // 新加入的效果还没有收到 resize
+    effects_changed: bool,
+    compute_supported: bool,
+}
+
+impl PostProcessStack {
+    pub fn new(format: wgpu::TextureFormat) -> Self {
+        Self {
+            effects: Vec::new(),
+            format,
+            targets: None,
+            effects_changed: false,
+            compute_supported: true,
+        }
+    }
+
+    // 由 RenderCapabilities::compute_shaders 决定
+    pub fn set_compute_supported(&mut self, supported: bool) {
+        // 之前跳过的效果还没有收到 resize
+        self.effects_changed |= supported != self.compute_supported;
+        self.compute_supported = supported;
+    }
+
+    fn is_skipped(&self, effect: &dyn PostProcessEffect) -> bool {
+        !self.compute_supported && effect.uses_compute()
+    }
+
+    pub fn push(&mut self, effect: Box<dyn PostProcessEffect>) {
+        if self.is_skipped(effect.as_ref()) {
+            tracing::warn!("post process: '{}' needs compute shaders and is skipped on this backend", effect.label());
+        }
+        self.effects.push(effect);
+        self.effects_changed = true;
+    }
+
+    pub fn remove(&mut self, index: usize) -> Box<dyn PostProcessEffect> {
+        self.effects_changed = true;
+        self.effects.remove(index)
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.effects.swap(i, j);
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    pub fn effects(&self) -> impl Iterator<Item = &dyn PostProcessEffect> {
+        self.effects.iter().map(|effect| effect.as_ref())
+    }
+
+    // 跳过的效果不参与分配，否则 WebGL2 上会为乒乓纹理请求不支持的 STORAGE_BINDING
+    fn required_usage(&self) -> wgpu::TextureUsages {
+        self.effects
+            .iter()
+            .filter(|effect| !self.is_skipped(effect.as_ref()))
+            .fold(wgpu::TextureUsages::empty(), |usage, effect| usage | effect.output_usage())
+    }
+
+    fn ensure_targets(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let usage = self.required_usage();
+        let up_to_date = self.targets.as_ref().is_some_and(|targets| {
+            targets[0].size() == (width, height) && targets[0].texture.usage().contains(usage)
+        });
+
+        if !up_to_date {
+            let create = |label| Texture::create_render_target(device, width, height, self.format, usage, label);
+            self.targets = Some([create("Post Process Ping"), create("Post Process Pong")]);
+        }
+
+        if !up_to_date || self.effects_changed {
+            let compute_supported = self.compute_supported;
+            for effect in self.effects.iter_mut().filter(|effect| compute_supported || !effect.uses_compute()) {
+                effect.resize(device, width, height);
+            }
+            self.effects_changed = false;
+        }
+    }
+
+    // 依次执行所有效果，返回最终结果所在的纹理视图
+    pub fn run<'a>(
+        &'a mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &'a wgpu::TextureView,
+        size: (u32, u32),
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> &'a wgpu::TextureView {
+        if self.effects.is_empty() {
+            return input;
+        }
+
+        self.ensure_targets(device, size.0, size.1);
+        let targets = self.targets.as_ref().expect("post process targets allocated");
+
+        let compute_supported = self.compute_supported;
+        let mut source = input;
+        let effects = self.effects.iter().filter(|effect| compute_supported || !effect.uses_compute());
+        for (index, effect) in effects.enumerate() {
+            let target = &targets[index % 2].view;
+            effect.apply(encoder, source, target, device, queue);
+            source = target;
+        }
+        source
+    }
+}
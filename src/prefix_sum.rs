@@ -0,0 +1,267 @@
+use wgpu::util::DeviceExt;
+
+// 与 prefix_sum.wgsl 一致：每个工作组 256 个线程，每个线程 4 个元素
+const WORKGROUP_SIZE: u32 = 256;
+const ITEMS_PER_THREAD: u32 = 4;
+const BLOCK_SIZE: u32 = WORKGROUP_SIZE * ITEMS_PER_THREAD;
+// 第一层的块数不超过单维派发上限 65535，块和再扫两层即可
+pub const MAX_ELEMENTS: u32 = 1 << 24;
+const ELEMENT_SIZE: wgpu::BufferAddress = 4;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScanParams {
+    count: u32,
+    _padding: [u32; 3],
+}
+
+struct ScanPipelines {
+    reduce: wgpu::ComputePipeline,
+    scan: wgpu::ComputePipeline,
+}
+
+// 上一次扫描用到的绑定组，输入输出缓冲与元素数不变时复用
+struct CachedBindGroups {
+    key: (wgpu::Id<wgpu::Buffer>, wgpu::Id<wgpu::Buffer>, u32),
+    reduce: Vec<wgpu::BindGroup>,
+    scan: Vec<wgpu::BindGroup>,
+}
+
+// 每层保存上一层的块和与它的排他前缀和
+struct ScanLevel {
+    sums: wgpu::Buffer,
+    scanned: wgpu::Buffer,
+}
+
+// GPU 排他前缀和：先归约再扫描。cs_reduce 求每 1024 个元素一块的和，块和逐层递归扫描，
+// 最后 cs_scan 在块内做 Blelloch 扫描并加上块偏移。所有派发记录在同一个计算通道里，
+// 最多 2^24 个元素。u32 与 f32 各有一套管线，临时缓冲两者共用
+pub struct GpuPrefixSum {
+    bind_group_layout: wgpu::BindGroupLayout,
+    u32_pipelines: ScanPipelines,
+    f32_pipelines: ScanPipelines,
+    // 只有一块时的块偏移
+    zero_buffer: wgpu::Buffer,
+    // 元素数超过 capacity 时重建
+    levels: Vec<ScanLevel>,
+    capacity: u32,
+    bind_groups: Option<CachedBindGroups>,
+}
+
+impl GpuPrefixSum {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("prefix_sum_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+                storage_entry(3, true),
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Prefix Sum Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipelines = |header: &str, label: &str| {
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(format!("{header}\n{}", include_str!("prefix_sum.wgsl")).into()),
+            });
+            let pipeline = |entry_point| {
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&layout),
+                    module: &shader,
+                    entry_point,
+                })
+            };
+            ScanPipelines { reduce: pipeline("cs_reduce"), scan: pipeline("cs_scan") }
+        };
+        let u32_pipelines = pipelines("alias Element = u32;\nconst ZERO: Element = 0u;", "Prefix Sum u32");
+        let f32_pipelines = pipelines("alias Element = f32;\nconst ZERO: Element = 0.0;", "Prefix Sum f32");
+
+        let zero_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Prefix Sum Zero Offset"),
+            contents: bytemuck::bytes_of(&0u32),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        Self {
+            bind_group_layout,
+            u32_pipelines,
+            f32_pipelines,
+            zero_buffer,
+            levels: Vec::new(),
+            capacity: 0,
+            bind_groups: None,
+        }
+    }
+
+    // output[i] = values[0] + ... + values[i - 1]，u32 加法按 2^32 回绕。
+    // 两个缓冲都需要 STORAGE 用途且不能是同一个缓冲
+    pub fn exclusive_scan(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        values_buffer: &wgpu::Buffer,
+        output_buffer: &wgpu::Buffer,
+        count: u32,
+    ) {
+        self.scan(device, encoder, values_buffer, output_buffer, count, false);
+    }
+
+    // 与 exclusive_scan 相同，元素为 f32；求和顺序与 CPU 顺序累加不同，结果会有舍入误差
+    pub fn exclusive_scan_f32(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        values_buffer: &wgpu::Buffer,
+        output_buffer: &wgpu::Buffer,
+        count: u32,
+    ) {
+        self.scan(device, encoder, values_buffer, output_buffer, count, true);
+    }
+
+    fn scan(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        values_buffer: &wgpu::Buffer,
+        output_buffer: &wgpu::Buffer,
+        count: u32,
+        float: bool,
+    ) {
+        let buffer_count = (values_buffer.size().min(output_buffer.size()) / ELEMENT_SIZE) as u32;
+        if count > MAX_ELEMENTS {
+            tracing::warn!("prefix_sum: {count} elements exceed the limit of {MAX_ELEMENTS}, truncating");
+        }
+        let count = count.min(buffer_count).min(MAX_ELEMENTS);
+        if count == 0 {
+            return;
+        }
+        if count > self.capacity {
+            self.levels = create_levels(device, count);
+            self.capacity = count;
+            self.bind_groups = None;
+        }
+
+        // counts[depth] 为第 depth 层输入的元素数，最后一层只剩一块
+        let mut counts = vec![count];
+        while counts[counts.len() - 1] > BLOCK_SIZE {
+            counts.push(counts[counts.len() - 1].div_ceil(BLOCK_SIZE));
+        }
+        let key = (values_buffer.global_id(), output_buffer.global_id(), count);
+        let bind_groups = match self.bind_groups.take().filter(|cached| cached.key == key) {
+            Some(cached) => cached,
+            None => self.create_bind_groups(device, values_buffer, output_buffer, &counts),
+        };
+
+        let pipelines = if float { &self.f32_pipelines } else { &self.u32_pipelines };
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Prefix Sum Pass"),
+                timestamp_writes: None,
+            });
+            // 自上而下归约出各层块和，再自下而上扫描，每层用下一层的扫描结果作块偏移
+            compute_pass.set_pipeline(&pipelines.reduce);
+            for (depth, bind_group) in bind_groups.reduce.iter().enumerate() {
+                compute_pass.set_bind_group(0, bind_group, &[]);
+                compute_pass.dispatch_workgroups(counts[depth].div_ceil(BLOCK_SIZE), 1, 1);
+            }
+            compute_pass.set_pipeline(&pipelines.scan);
+            for (depth, bind_group) in bind_groups.scan.iter().enumerate().rev() {
+                compute_pass.set_bind_group(0, bind_group, &[]);
+                compute_pass.dispatch_workgroups(counts[depth].div_ceil(BLOCK_SIZE), 1, 1);
+            }
+        }
+        self.bind_groups = Some(bind_groups);
+    }
+
+    // 每层一组参数与绑定组，只在输入输出缓冲或元素数变化时重建
+    fn create_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        values_buffer: &wgpu::Buffer,
+        output_buffer: &wgpu::Buffer,
+        counts: &[u32],
+    ) -> CachedBindGroups {
+        let input = |depth: usize| if depth == 0 { values_buffer } else { &self.levels[depth - 1].sums };
+        let output = |depth: usize| if depth == 0 { output_buffer } else { &self.levels[depth - 1].scanned };
+        let bind_group = |count: u32, source: &wgpu::Buffer, destination: &wgpu::Buffer, offsets: &wgpu::Buffer| {
+            let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Prefix Sum Params"),
+                contents: bytemuck::bytes_of(&ScanParams { count, _padding: [0; 3] }),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("prefix_sum_bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: source.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: destination.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 3, resource: offsets.as_entire_binding() },
+                ],
+            })
+        };
+
+        let last = counts.len() - 1;
+        let reduce = (0..last)
+            .map(|depth| bind_group(counts[depth], input(depth), &self.levels[depth].sums, &self.zero_buffer))
+            .collect();
+        let scan = (0..=last)
+            .map(|depth| {
+                let offsets = if depth == last { &self.zero_buffer } else { &self.levels[depth].scanned };
+                bind_group(counts[depth], input(depth), output(depth), offsets)
+            })
+            .collect();
+        CachedBindGroups {
+            key: (values_buffer.global_id(), output_buffer.global_id(), counts[0]),
+            reduce,
+            scan,
+        }
+    }
+}
+
+fn create_levels(device: &wgpu::Device, capacity: u32) -> Vec<ScanLevel> {
+    let mut levels = Vec::new();
+    let mut count = capacity;
+    while count > BLOCK_SIZE {
+        count = count.div_ceil(BLOCK_SIZE);
+        let buffer = |label| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: ELEMENT_SIZE * count as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        };
+        levels.push(ScanLevel {
+            sums: buffer("Prefix Sum Block Sums"),
+            scanned: buffer("Prefix Sum Block Offsets"),
+        });
+    }
+    levels
+}
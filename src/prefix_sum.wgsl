@@ -0,0 +1,87 @@
+// 排他前缀和，先归约再扫描：cs_reduce 求每块之和，块和递归扫描后作为 cs_scan 的块偏移。
+// Element 与 ZERO 由 prefix_sum.rs 在源码前拼接，分别生成 u32 与 f32 两套管线
+
+// 与 prefix_sum.rs 中的 WORKGROUP_SIZE、ITEMS_PER_THREAD 一致：每个工作组处理 1024 个元素
+const WORKGROUP_SIZE: u32 = 256u;
+const ITEMS_PER_THREAD: u32 = 4u;
+
+struct ScanParams {
+    count: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: ScanParams;
+@group(0) @binding(1)
+var<storage, read> values: array<Element>;
+// cs_reduce 写每块之和，cs_scan 写前缀和
+@group(0) @binding(2)
+var<storage, read_write> output: array<Element>;
+// 每块的排他前缀和；只有一块时为单个 ZERO
+@group(0) @binding(3)
+var<storage, read> block_offsets: array<Element>;
+
+var<workgroup> sums: array<Element, WORKGROUP_SIZE>;
+
+// 每个线程连续的 ITEMS_PER_THREAD 个元素之和
+fn thread_sum(block: u32, local: u32) -> Element {
+    let start = (block * WORKGROUP_SIZE + local) * ITEMS_PER_THREAD;
+    var sum = ZERO;
+    for (var i = 0u; i < ITEMS_PER_THREAD; i++) {
+        if start + i < params.count {
+            sum += values[start + i];
+        }
+    }
+    return sum;
+}
+
+@compute @workgroup_size(256)
+fn cs_reduce(@builtin(local_invocation_index) local: u32, @builtin(workgroup_id) block: vec3u) {
+    sums[local] = thread_sum(block.x, local);
+    workgroupBarrier();
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride >>= 1u) {
+        if local < stride {
+            sums[local] += sums[local + stride];
+        }
+        workgroupBarrier();
+    }
+    if local == 0u {
+        output[block.x] = sums[0];
+    }
+}
+
+// 线程和做 Blelloch 扫描（上扫归约、根置零、下扫分发），再在线程内顺序展开
+@compute @workgroup_size(256)
+fn cs_scan(@builtin(local_invocation_index) local: u32, @builtin(workgroup_id) block: vec3u) {
+    sums[local] = thread_sum(block.x, local);
+    workgroupBarrier();
+
+    for (var stride = 1u; stride < WORKGROUP_SIZE; stride <<= 1u) {
+        let index = (local + 1u) * stride * 2u - 1u;
+        if index < WORKGROUP_SIZE {
+            sums[index] += sums[index - stride];
+        }
+        workgroupBarrier();
+    }
+    if local == 0u {
+        sums[WORKGROUP_SIZE - 1u] = ZERO;
+    }
+    workgroupBarrier();
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride >>= 1u) {
+        let index = (local + 1u) * stride * 2u - 1u;
+        if index < WORKGROUP_SIZE {
+            let left = sums[index - stride];
+            sums[index - stride] = sums[index];
+            sums[index] += left;
+        }
+        workgroupBarrier();
+    }
+
+    let start = (block.x * WORKGROUP_SIZE + local) * ITEMS_PER_THREAD;
+    var running = block_offsets[block.x] + sums[local];
+    for (var i = 0u; i < ITEMS_PER_THREAD; i++) {
+        if start + i < params.count {
+            output[start + i] = running;
+            running += values[start + i];
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::f32::consts::PI;
+
+use crate::mesh::{ MeshData, Vertex };
+
+// 所有图元都以原点为中心，三角形为逆时针（Ccw）朝外
+
+pub fn sphere(radius: f32, rings: u32, sectors: u32) -> MeshData {
+    let rings = rings.max(2);
+    let sectors = sectors.max(3);
+    assert_vertex_count((rings + 1) * (sectors + 1));
+
+    let mut mesh = MeshData::default();
+    for i in 0..=rings {
+        let v = i as f32 / rings as f32;
+        let phi = v * PI;
+        for j in 0..=sectors {
+            let u = j as f32 / sectors as f32;
+            let theta = u * 2.0 * PI;
+            let normal = [phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin()];
+            mesh.vertices.push(Vertex {
+                position: scale(normal, radius),
+                normal,
+                tex_coords: [u, v],
+                uv1: [u, v],
+            });
+        }
+    }
+
+    let stride = sectors + 1;
+    for i in 0..rings {
+        for j in 0..sectors {
+            let a = i * stride + j;
+            let b = a + stride;
+            let c = b + 1;
+            let d = a + 1;
+            push_quad(&mut mesh, a, d, c, b);
+        }
+    }
+    mesh
+}
+
+pub fn cube(half_extent: f32) -> MeshData {
+    // (法线, u 轴, v 轴)，满足 u × v = 法线
+    const FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+
+    let mut mesh = MeshData::default();
+    for (normal, u, v) in FACES {
+        let center = scale(normal, half_extent);
+        push_grid(&mut mesh, center, scale(u, half_extent), scale(v, half_extent), normal, 1);
+    }
+    mesh
+}
+
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> MeshData {
+    let segments = segments.max(3);
+    assert_vertex_count(2 * (segments + 1) + 2 * (segments + 2));
+
+    let mut mesh = MeshData::default();
+    let half_height = height * 0.5;
+    let ring = |k: u32| {
+        let theta = k as f32 / segments as f32 * 2.0 * PI;
+        (theta.cos(), theta.sin())
+    };
+
+    // 侧面
+    let side_start = mesh.vertices.len() as u32;
+    for k in 0..=segments {
+        let (x, z) = ring(k);
+        let u = k as f32 / segments as f32;
+        for (y, v) in [(half_height, 0.0), (-half_height, 1.0)] {
+            mesh.vertices.push(Vertex {
+                position: [x * radius, y, z * radius],
+                normal: [x, 0.0, z],
+                tex_coords: [u, v],
+                uv1: [u, v],
+            });
+        }
+    }
+    for k in 0..segments {
+        let top = side_start + k * 2;
+        let bottom = top + 1;
+        push_quad(&mut mesh, top, top + 2, bottom + 2, bottom);
+    }
+
+    // 顶面与底面
+    for (y, ny) in [(half_height, 1.0), (-half_height, -1.0)] {
+        let center = mesh.vertices.len() as u32;
+        mesh.vertices.push(Vertex {
+            position: [0.0, y, 0.0],
+            normal: [0.0, ny, 0.0],
+            tex_coords: [0.5, 0.5],
+            uv1: [0.5, 0.5],
+        });
+        for k in 0..=segments {
+            let (x, z) = ring(k);
+            mesh.vertices.push(Vertex {
+                position: [x * radius, y, z * radius],
+                normal: [0.0, ny, 0.0],
+                tex_coords: [x * 0.5 + 0.5, z * 0.5 + 0.5],
+                uv1: [x * 0.5 + 0.5, z * 0.5 + 0.5],
+            });
+        }
+        for k in 0..segments {
+            let current = (center + 1 + k) as u16;
+            let next = current + 1;
+            if ny > 0.0 {
+                mesh.indices.extend_from_slice(&[center as u16, next, current]);
+            } else {
+                mesh.indices.extend_from_slice(&[center as u16, current, next]);
+            }
+        }
+    }
+    mesh
+}
+
+// 底面在 y = -height / 2，尖端在 y = +height / 2
+pub fn cone(radius: f32, height: f32, segments: u32) -> MeshData {
+    let segments = segments.max(3);
+    assert_vertex_count(2 * (segments + 1) + segments + 2);
+
+    let mut mesh = MeshData::default();
+    let half_height = height * 0.5;
+    let slant = (radius * radius + height * height).sqrt();
+    let ring = |k: u32| {
+        let theta = k as f32 / segments as f32 * 2.0 * PI;
+        (theta.cos(), theta.sin())
+    };
+
+    // 侧面：每段使用单独的尖端顶点，使法线连续
+    let side_start = mesh.vertices.len() as u32;
+    for k in 0..=segments {
+        let (x, z) = ring(k);
+        let normal = [x * height / slant, radius / slant, z * height / slant];
+        let u = k as f32 / segments as f32;
+        mesh.vertices.push(Vertex {
+            position: [0.0, half_height, 0.0],
+            normal,
+            tex_coords: [u, 0.0],
+            uv1: [u, 0.0],
+        });
+        mesh.vertices.push(Vertex {
+            position: [x * radius, -half_height, z * radius],
+            normal,
+            tex_coords: [u, 1.0],
+            uv1: [u, 1.0],
+        });
+    }
+    for k in 0..segments {
+        let apex = (side_start + k * 2) as u16;
+        let base = apex + 1;
+        mesh.indices.extend_from_slice(&[apex, base + 2, base]);
+    }
+
+    // 底面
+    let center = mesh.vertices.len() as u32;
+    mesh.vertices.push(Vertex {
+        position: [0.0, -half_height, 0.0],
+        normal: [0.0, -1.0, 0.0],
+        tex_coords: [0.5, 0.5],
+        uv1: [0.5, 0.5],
+    });
+    for k in 0..=segments {
+        let (x, z) = ring(k);
+        mesh.vertices.push(Vertex {
+            position: [x * radius, -half_height, z * radius],
+            normal: [0.0, -1.0, 0.0],
+            tex_coords: [x * 0.5 + 0.5, z * 0.5 + 0.5],
+            uv1: [x * 0.5 + 0.5, z * 0.5 + 0.5],
+        });
+    }
+    for k in 0..segments {
+        let current = (center + 1 + k) as u16;
+        mesh.indices.extend_from_slice(&[center as u16, current, current + 1]);
+    }
+    mesh
+}
+
+// 环面位于 XZ 平面，绕 Y 轴
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> MeshData {
+    let major_segments = major_segments.max(3);
+    let minor_segments = minor_segments.max(3);
+    assert_vertex_count((major_segments + 1) * (minor_segments + 1));
+
+    let mut mesh = MeshData::default();
+    for i in 0..=major_segments {
+        let u = i as f32 / major_segments as f32;
+        let theta = u * 2.0 * PI;
+        for j in 0..=minor_segments {
+            let v = j as f32 / minor_segments as f32;
+            let phi = v * 2.0 * PI;
+            let normal = [phi.cos() * theta.cos(), phi.sin(), phi.cos() * theta.sin()];
+            let distance = major_radius + minor_radius * phi.cos();
+            mesh.vertices.push(Vertex {
+                position: [distance * theta.cos(), minor_radius * phi.sin(), distance * theta.sin()],
+                normal,
+                tex_coords: [u, v],
+                uv1: [u, v],
+            });
+        }
+    }
+
+    let stride = minor_segments + 1;
+    for i in 0..major_segments {
+        for j in 0..minor_segments {
+            let a = i * stride + j;
+            let b = a + stride;
+            push_quad(&mut mesh, a, a + 1, b + 1, b);
+        }
+    }
+    mesh
+}
+
+// 位于 XZ 平面，法线朝 +Y
+pub fn plane(half_width: f32, half_height: f32, subdivisions: u32) -> MeshData {
+    let mut mesh = MeshData::default();
+    push_grid(
+        &mut mesh,
+        [0.0, 0.0, 0.0],
+        [half_width, 0.0, 0.0],
+        [0.0, 0.0, -half_height],
+        [0.0, 1.0, 0.0],
+        subdivisions.max(1),
+    );
+    mesh
+}
+
+// 生成以 center 为中心、由 u、v 两个半轴张成的细分网格
+fn push_grid(mesh: &mut MeshData, center: [f32; 3], u: [f32; 3], v: [f32; 3], normal: [f32; 3], subdivisions: u32) {
+    let start = mesh.vertices.len() as u32;
+    let stride = subdivisions + 1;
+    assert_vertex_count(start + stride * stride);
+
+    for i in 0..=subdivisions {
+        let s = i as f32 / subdivisions as f32;
+        for j in 0..=subdivisions {
+            let t = j as f32 / subdivisions as f32;
+            let (a, b) = (s * 2.0 - 1.0, t * 2.0 - 1.0);
+            mesh.vertices.push(Vertex {
+                position: [
+                    center[0] + u[0] * a + v[0] * b,
+                    center[1] + u[1] * a + v[1] * b,
+                    center[2] + u[2] * a + v[2] * b,
+                ],
+                normal,
+                tex_coords: [s, 1.0 - t],
+                uv1: [s, 1.0 - t],
+            });
+        }
+    }
+
+    for i in 0..subdivisions {
+        for j in 0..subdivisions {
+            let a = start + i * stride + j;
+            let b = a + stride;
+            push_quad(mesh, a, b, b + 1, a + 1);
+        }
+    }
+}
+
+// 四个顶点按逆时针给出
+fn push_quad(mesh: &mut MeshData, a: u32, b: u32, c: u32, d: u32) {
+    let [a, b, c, d] = [a as u16, b as u16, c as u16, d as u16];
+    mesh.indices.extend_from_slice(&[a, b, c, a, c, d]);
+}
+
+fn scale(v: [f32; 3], s: f32) -> [f32; 3] {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+fn assert_vertex_count(count: u32) {
+    assert!(count <= u16::MAX as u32 + 1, "primitive has {count} vertices, which exceeds the u16 index range");
+}
@@ -0,0 +1,139 @@
+// 启用 profiling 特性时转发到 puffin，否则展开为空，发布构建没有任何开销
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        puffin::profile_scope!($name);
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {};
+}
+
+pub fn init() {
+    #[cfg(feature = "profiling")]
+    puffin::set_scopes_on(true);
+}
+
+// 每帧开始时调用一次，划分 puffin 的帧边界
+#[inline]
+pub fn new_frame() {
+    #[cfg(feature = "profiling")]
+    puffin::GlobalProfiler::lock().new_frame();
+}
+
+// 覆盖层中最多列出的 GPU 通道数
+#[cfg(feature = "profiling")]
+const GPU_PASS_COUNT: usize = 10;
+
+// 在 egui 覆盖层中显示 puffin 火焰图与最耗时的 GPU 通道，F3 切换显示
+#[cfg(feature = "profiling")]
+pub struct ProfilerWindow {
+    pub visible: bool,
+    // 按耗时从大到小排列的 (通道, 纳秒)
+    gpu_timings: Vec<(String, f64)>,
+    context: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+}
+
+#[cfg(feature = "profiling")]
+impl ProfilerWindow {
+    pub fn new(window: &winit::window::Window, device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let context = egui::Context::default();
+        let state = egui_winit::State::new(
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer = egui_wgpu::Renderer::new(device, format, None, 1);
+
+        Self { visible: false, gpu_timings: Vec::new(), context, state, renderer }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // 传入 GpuTimer::resolve 的结果，只保留最耗时的几个通道
+    pub fn set_gpu_timings(&mut self, timings: &[(String, f64)]) {
+        self.gpu_timings = crate::gpu_timer::slowest(timings, GPU_PASS_COUNT);
+    }
+
+    // 返回 true 表示事件被 egui 消费，不再传给场景
+    pub fn on_event(&mut self, event: &winit::event::WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+        self.state.on_window_event(&self.context, event).consumed
+    }
+
+    // 叠加绘制到交换链纹理上，返回需要在主命令缓冲区之前提交的命令
+    pub fn render(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        window: &winit::window::Window,
+        view: &wgpu::TextureView,
+        size: [u32; 2],
+    ) -> Vec<wgpu::CommandBuffer> {
+        if !self.visible {
+            return Vec::new();
+        }
+
+        let raw_input = self.state.take_egui_input(window);
+        let mut open = true;
+        let gpu_timings = &self.gpu_timings;
+        let output = self.context.run(raw_input, |context| {
+            open = puffin_egui::profiler_window(context);
+            egui::Window::new("GPU Passes").show(context, |ui| {
+                if gpu_timings.is_empty() {
+                    ui.label("timestamp queries are not supported by this device");
+                }
+                for (label, nanoseconds) in gpu_timings {
+                    ui.label(format!("{label}: {:.3} ms", nanoseconds / 1_000_000.0));
+                }
+            });
+        });
+        self.visible = open;
+        self.state.handle_platform_output(window, &self.context, output.platform_output);
+
+        let primitives = self.context.tessellate(output.shapes, output.pixels_per_point);
+        let screen = egui_wgpu::renderer::ScreenDescriptor {
+            size_in_pixels: size,
+            pixels_per_point: output.pixels_per_point,
+        };
+
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        let command_buffers = self.renderer.update_buffers(device, queue, encoder, &primitives, &screen);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Profiler Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.renderer.render(&mut render_pass, &primitives, &screen);
+        drop(render_pass);
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+        command_buffers
+    }
+}
@@ -0,0 +1,291 @@
+use crate::prefix_sum::GpuPrefixSum;
+
+// 与 radix_sort.wgsl 一致：每个工作组 256 个线程，一块 256 个元素
+const WORKGROUP_SIZE: u32 = 256;
+const RADIX: u32 = 256;
+const RADIX_BITS: u32 = 8;
+// 32 位键分 4 趟，偶数趟之后结果回到原缓冲
+const RADIX_PASSES: u32 = 4;
+// 每个元素是 (depth_key, draw_index) 两个 u32
+const KEY_SIZE: wgpu::BufferAddress = 8;
+const PARAMS_SIZE: wgpu::BufferAddress = std::mem::size_of::<SortParams>() as wgpu::BufferAddress;
+const DRAW_INDEXED_INDIRECT_SIZE: wgpu::BufferAddress = 20;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SortParams {
+    count: u32,
+    shift: u32,
+    num_tiles: u32,
+    _padding: u32,
+}
+
+// 从远到近排序的键：正浮点数的位模式与数值同序，取反后深度越大键越小
+pub fn depth_key(view_depth: f32) -> u32 {
+    !view_depth.max(0.0).to_bits()
+}
+
+// GPU 基数排序：对 (depth_key, draw_index) 按 depth_key 升序稳定排序，
+// 每趟 8 位，每趟依次派发直方图、GpuPrefixSum 前缀和、散射，在缓冲之间来回写 4 趟。
+// 用于透明物体从远到近的绘制顺序：排序后用 gather_indirect 按 draw_index 重排间接绘制参数，
+// 再用 draw_indexed_sorted 逐个间接绘制，CPU 不需要读回结果。
+// 缓冲与绑定组按容量预先分配，每帧只用 queue.write_buffer 更新参数
+pub struct GpuRadixSort {
+    bind_group_layout: wgpu::BindGroupLayout,
+    gather_bind_group_layout: wgpu::BindGroupLayout,
+    histogram_pipeline: wgpu::ComputePipeline,
+    scatter_pipeline: wgpu::ComputePipeline,
+    gather_pipeline: wgpu::ComputePipeline,
+    prefix_sum: GpuPrefixSum,
+    // 每趟一组参数，按动态偏移选取
+    params_buffer: wgpu::Buffer,
+    params_stride: wgpu::BufferAddress,
+    // 乒乓用的临时键缓冲、直方图与它的前缀和，元素数超过 capacity 时重建
+    scratch_buffer: wgpu::Buffer,
+    histogram_buffer: wgpu::Buffer,
+    offsets_buffer: wgpu::Buffer,
+    capacity: u32,
+    // 上一次 sort 的元素数，gather_indirect 沿用
+    count: u32,
+    // 偶数趟与奇数趟的绑定组，以键缓冲的 id 区分
+    sort_bind_groups: Option<(wgpu::Id<wgpu::Buffer>, [wgpu::BindGroup; 2])>,
+    gather_bind_group: Option<([wgpu::Id<wgpu::Buffer>; 3], wgpu::BindGroup)>,
+}
+
+impl GpuRadixSort {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Radix Sort Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("radix_sort.wgsl").into()),
+        });
+
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(PARAMS_SIZE),
+            },
+            count: None,
+        };
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("radix_sort_bind_group_layout"),
+            entries: &[
+                uniform_entry,
+                storage_entry(1, true),
+                storage_entry(2, false),
+                storage_entry(3, false),
+                storage_entry(4, true),
+            ],
+        });
+        let gather_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("radix_sort_gather_bind_group_layout"),
+            entries: &[uniform_entry, storage_entry(1, true), storage_entry(5, true), storage_entry(6, false)],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Radix Sort Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let gather_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Radix Sort Gather Pipeline Layout"),
+            bind_group_layouts: &[&gather_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |layout, entry_point, label| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+        let histogram_pipeline = pipeline(&layout, "cs_histogram", "Radix Sort Histogram Pipeline");
+        let scatter_pipeline = pipeline(&layout, "cs_scatter", "Radix Sort Scatter Pipeline");
+        let gather_pipeline = pipeline(&gather_layout, "cs_gather", "Radix Sort Gather Pipeline");
+
+        let params_stride = wgpu::util::align_to(PARAMS_SIZE, device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress);
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Radix Sort Params"),
+            size: params_stride * RADIX_PASSES as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let (scratch_buffer, histogram_buffer, offsets_buffer) = create_scratch_buffers(device, 1);
+        Self {
+            bind_group_layout,
+            gather_bind_group_layout,
+            histogram_pipeline,
+            scatter_pipeline,
+            gather_pipeline,
+            prefix_sum: GpuPrefixSum::new(device),
+            params_buffer,
+            params_stride,
+            scratch_buffer,
+            histogram_buffer,
+            offsets_buffer,
+            capacity: 1,
+            count: 0,
+            sort_bind_groups: None,
+            gather_bind_group: None,
+        }
+    }
+
+    // keys_buffer 的前 count 个元素原地排序，需要 STORAGE 用途。
+    // 参数经 queue.write_buffer 在下一次提交开始时写入，每次提交只能排序一次
+    pub fn sort(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        keys_buffer: &wgpu::Buffer,
+        count: u32,
+    ) {
+        let count = count.min((keys_buffer.size() / KEY_SIZE) as u32);
+        if count > self.capacity {
+            (self.scratch_buffer, self.histogram_buffer, self.offsets_buffer) = create_scratch_buffers(device, count);
+            self.capacity = count;
+            self.sort_bind_groups = None;
+        }
+        self.count = count;
+
+        let num_tiles = count.div_ceil(WORKGROUP_SIZE);
+        let mut params = vec![0; (self.params_stride * RADIX_PASSES as wgpu::BufferAddress) as usize];
+        for pass in 0..RADIX_PASSES {
+            let offset = (self.params_stride * pass as wgpu::BufferAddress) as usize;
+            let pass_params = SortParams { count, shift: pass * RADIX_BITS, num_tiles, _padding: 0 };
+            params[offset..offset + PARAMS_SIZE as usize].copy_from_slice(bytemuck::bytes_of(&pass_params));
+        }
+        queue.write_buffer(&self.params_buffer, 0, &params);
+        if count <= 1 {
+            return;
+        }
+
+        let key = keys_buffer.global_id();
+        let bind_groups = match self.sort_bind_groups.take().filter(|(cached, _)| *cached == key) {
+            Some((_, bind_groups)) => bind_groups,
+            None => {
+                let bind_group = |source: &wgpu::Buffer, destination: &wgpu::Buffer| {
+                    device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("radix_sort_bind_group"),
+                        layout: &self.bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry { binding: 0, resource: self.params_binding() },
+                            wgpu::BindGroupEntry { binding: 1, resource: source.as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 2, resource: destination.as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 3, resource: self.histogram_buffer.as_entire_binding() },
+                            wgpu::BindGroupEntry { binding: 4, resource: self.offsets_buffer.as_entire_binding() },
+                        ],
+                    })
+                };
+                [bind_group(keys_buffer, &self.scratch_buffer), bind_group(&self.scratch_buffer, keys_buffer)]
+            }
+        };
+
+        for pass in 0..RADIX_PASSES {
+            let bind_group = &bind_groups[pass as usize % 2];
+            let params_offset = [(self.params_stride * pass as wgpu::BufferAddress) as u32];
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Radix Sort Histogram Pass"),
+                    timestamp_writes: None,
+                });
+                compute_pass.set_pipeline(&self.histogram_pipeline);
+                compute_pass.set_bind_group(0, bind_group, &params_offset);
+                compute_pass.dispatch_workgroups(num_tiles, 1, 1);
+            }
+            self.prefix_sum.exclusive_scan(device, encoder, &self.histogram_buffer, &self.offsets_buffer, RADIX * num_tiles);
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Radix Sort Scatter Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.scatter_pipeline);
+            compute_pass.set_bind_group(0, bind_group, &params_offset);
+            compute_pass.dispatch_workgroups(num_tiles, 1, 1);
+        }
+        self.sort_bind_groups = Some((key, bind_groups));
+    }
+
+    // 按上一次 sort 后 keys_buffer 中的 draw_index 重排 draws（wgpu::util::DrawIndexedIndirect 数组），
+    // 写入 sorted_draws（需要 STORAGE | INDIRECT 用途），之后可直接用于 draw_indexed_sorted
+    pub fn gather_indirect(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        keys_buffer: &wgpu::Buffer,
+        draws: &wgpu::Buffer,
+        sorted_draws: &wgpu::Buffer,
+    ) {
+        let key = [keys_buffer.global_id(), draws.global_id(), sorted_draws.global_id()];
+        if self.gather_bind_group.as_ref().map(|(cached, _)| *cached) != Some(key) {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("radix_sort_gather_bind_group"),
+                layout: &self.gather_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: self.params_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: keys_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 5, resource: draws.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 6, resource: sorted_draws.as_entire_binding() },
+                ],
+            });
+            self.gather_bind_group = Some((key, bind_group));
+        }
+
+        let count = self.count.min((sorted_draws.size() / DRAW_INDEXED_INDIRECT_SIZE) as u32);
+        if let Some((_, bind_group)) = &self.gather_bind_group {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Radix Sort Gather Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.gather_pipeline);
+            // 第一趟的参数中 count 与 num_tiles 即本次排序的元素数
+            compute_pass.set_bind_group(0, bind_group, &[0]);
+            compute_pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+    }
+
+    fn params_binding(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &self.params_buffer,
+            offset: 0,
+            size: wgpu::BufferSize::new(PARAMS_SIZE),
+        })
+    }
+
+    // 按排序后的顺序逐个间接绘制；顶点、索引缓冲与绑定组由调用方设置，所有绘制共用
+    pub fn draw_indexed_sorted<'a>(render_pass: &mut wgpu::RenderPass<'a>, sorted_draws: &'a wgpu::Buffer, count: u32) {
+        for index in 0..count as wgpu::BufferAddress {
+            render_pass.draw_indexed_indirect(sorted_draws, index * DRAW_INDEXED_INDIRECT_SIZE);
+        }
+    }
+}
+
+fn create_scratch_buffers(device: &wgpu::Device, capacity: u32) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
+    let scratch_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Radix Sort Scratch Buffer"),
+        size: KEY_SIZE * capacity as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    });
+    let histogram = |label| {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: 4 * (RADIX * capacity.div_ceil(WORKGROUP_SIZE)) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    };
+    (scratch_buffer, histogram("Radix Sort Histogram Buffer"), histogram("Radix Sort Offsets Buffer"))
+}
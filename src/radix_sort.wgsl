@@ -0,0 +1,91 @@
+// 最低位优先的基数排序，每趟 8 位：直方图 → 前缀和（GpuPrefixSum）→ 稳定散射
+
+const RADIX: u32 = 256u;
+// 与 radix_sort.rs 中的 WORKGROUP_SIZE 一致，每个工作组处理一块
+const WORKGROUP_SIZE: u32 = 256u;
+
+struct SortParams {
+    count: u32,
+    // 本趟取的位：(key >> shift) & 0xff
+    shift: u32,
+    num_tiles: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: SortParams;
+// (depth_key, draw_index)
+@group(0) @binding(1)
+var<storage, read> source: array<vec2u>;
+@group(0) @binding(2)
+var<storage, read_write> destination: array<vec2u>;
+// 按数位优先排列：histogram[digit * num_tiles + tile]
+@group(0) @binding(3)
+var<storage, read_write> histogram: array<u32>;
+// histogram 的排他前缀和，即每块每个数位的写入起点
+@group(0) @binding(4)
+var<storage, read> offsets: array<u32>;
+
+var<workgroup> counts: array<atomic<u32>, RADIX>;
+var<workgroup> digits: array<u32, WORKGROUP_SIZE>;
+
+fn digit(key: u32) -> u32 {
+    return (key >> params.shift) & (RADIX - 1u);
+}
+
+@compute @workgroup_size(256)
+fn cs_histogram(@builtin(local_invocation_index) local: u32, @builtin(workgroup_id) tile: vec3u) {
+    atomicStore(&counts[local], 0u);
+    workgroupBarrier();
+    let index = tile.x * WORKGROUP_SIZE + local;
+    if index < params.count {
+        atomicAdd(&counts[digit(source[index].x)], 1u);
+    }
+    workgroupBarrier();
+    histogram[local * params.num_tiles + tile.x] = atomicLoad(&counts[local]);
+}
+
+// 块内相同数位的元素按原顺序排列，排序是稳定的
+@compute @workgroup_size(256)
+fn cs_scatter(@builtin(local_invocation_index) local: u32, @builtin(workgroup_id) tile: vec3u) {
+    let index = tile.x * WORKGROUP_SIZE + local;
+    let valid = index < params.count;
+    var pair = vec2u(0u);
+    digits[local] = RADIX;
+    if valid {
+        pair = source[index];
+        digits[local] = digit(pair.x);
+    }
+    workgroupBarrier();
+    if !valid {
+        return;
+    }
+    let d = digits[local];
+    var rank = 0u;
+    for (var i = 0u; i < local; i++) {
+        rank += u32(digits[i] == d);
+    }
+    destination[offsets[d * params.num_tiles + tile.x] + rank] = pair;
+}
+
+// 与 wgpu::util::DrawIndexedIndirect 的布局一致
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+@group(0) @binding(5)
+var<storage, read> draws: array<DrawIndexedIndirect>;
+@group(0) @binding(6)
+var<storage, read_write> sorted_draws: array<DrawIndexedIndirect>;
+
+// 按排序后的 draw_index 重排间接绘制参数
+@compute @workgroup_size(256)
+fn cs_gather(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= params.count {
+        return;
+    }
+    sorted_draws[id.x] = draws[source[id.x].y];
+}
@@ -0,0 +1,96 @@
+use cgmath::{ InnerSpace, Matrix4, Point3, SquareMatrix, Transform as _, Vector3, Vector4 };
+
+use crate::mesh::Vertex;
+
+const EPSILON: f32 = 1e-7;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    // 不要求单位长度；t 以 direction 的长度为单位
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.direction * t
+    }
+
+    // 把屏幕坐标（像素，左上角为原点）经逆视图投影矩阵反投影为世界空间射线
+    pub fn from_screen(screen_pos: [f32; 2], screen_size: [f32; 2], view_proj: Matrix4<f32>) -> Option<Self> {
+        let inverse = view_proj.invert()?;
+        let ndc_x = screen_pos[0] / screen_size[0] * 2.0 - 1.0;
+        let ndc_y = 1.0 - screen_pos[1] / screen_size[1] * 2.0;
+
+        let unproject = |ndc_z: f32| {
+            let p = inverse * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+            Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+        };
+        // wgpu 的 NDC 深度范围是 0..1
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        Some(Self::new(near, (far - near).normalize()))
+    }
+
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Self {
+        Self {
+            origin: matrix.transform_point(self.origin),
+            direction: matrix.transform_vector(self.direction),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitInfo {
+    pub t: f32,
+    // 重心坐标，命中点 = (1 - u - v) * v0 + u * v1 + v * v2
+    pub u: f32,
+    pub v: f32,
+    pub triangle: usize,
+}
+
+// Möller-Trumbore 射线-三角形相交，双面检测
+pub fn intersect_triangle(ray: &Ray, v0: Point3<f32>, v1: Point3<f32>, v2: Point3<f32>) -> Option<(f32, f32, f32)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = ray.direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < EPSILON {
+        // 射线与三角形平行
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = ray.origin - v0;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inv_det;
+    (t > EPSILON).then_some((t, u, v))
+}
+
+// 返回最近的命中
+pub fn intersect_mesh(ray: Ray, vertices: &[Vertex], indices: &[u16]) -> Option<HitInfo> {
+    let position = |index: u16| Point3::from(vertices[index as usize].position);
+
+    indices
+        .chunks_exact(3)
+        .enumerate()
+        .filter_map(|(triangle, tri)| {
+            intersect_triangle(&ray, position(tri[0]), position(tri[1]), position(tri[2]))
+                .map(|(t, u, v)| HitInfo { t, u, v, triangle })
+        })
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::hash::{ DefaultHasher, Hash, Hasher };
+
+use crate::render_queue::RenderQueue;
+use crate::secondary_command_buffer::{ BundleTarget, SecondaryCommandBuffer };
+
+struct CachedBundle {
+    hash: u64,
+    bundle: wgpu::RenderBundle,
+}
+
+// 静态物体按桶录制成渲染包，绘制列表的哈希不变时直接复用上一帧的渲染包
+// 物体增删或绘制参数变化会改变哈希，对应的桶重新录制；缓冲区内容的更新不需要重新录制
+#[derive(Default)]
+pub struct RenderBundleCache {
+    buckets: HashMap<String, CachedBundle>,
+    hits: u64,
+    misses: u64,
+}
+
+impl RenderBundleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // queue 需要已排序，其中全部绘制录制进同一个渲染包
+    pub fn get_or_record(
+        &mut self,
+        bucket: &str,
+        device: &wgpu::Device,
+        target: &BundleTarget,
+        queue: &RenderQueue<'_>,
+    ) -> &wgpu::RenderBundle {
+        let hash = Self::hash_draws(target, queue);
+        let cached = self.buckets.get(bucket).is_some_and(|cached| cached.hash == hash);
+        if cached {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            let mut buffer = SecondaryCommandBuffer::new(device, target, Some(bucket));
+            queue.record_into(&mut buffer);
+            self.buckets.insert(bucket.to_string(), CachedBundle { hash, bundle: buffer.finish() });
+        }
+        &self.buckets[bucket].bundle
+    }
+
+    pub fn invalidate(&mut self, bucket: &str) {
+        self.buckets.remove(bucket);
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    fn hash_draws(target: &BundleTarget, queue: &RenderQueue<'_>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        target.color_formats.hash(&mut hasher);
+        target.depth_stencil.map(|depth_stencil| depth_stencil.format).hash(&mut hasher);
+        target.sample_count.hash(&mut hasher);
+        queue.draws().len().hash(&mut hasher);
+        for draw in queue.draws() {
+            draw.hash_state(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
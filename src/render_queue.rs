@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+use std::hash::{ Hash, Hasher };
+use std::ops::{ Bound, Range, RangeBounds, RangeInclusive };
+
+use wgpu::util::RenderEncoder;
+
+use crate::secondary_command_buffer::{ self, BundleTarget, SecondaryCommandBuffer };
+
+// 绘制层，数值小的先画
+pub mod layer {
+    pub const OPAQUE: u8 = 0;
+    pub const ALPHA_TEST: u8 = 1;
+    pub const TRANSPARENT: u8 = 2;
+    pub const UI: u8 = 3;
+    pub const DEBUG: u8 = 255;
+}
+
+// 一次绘制所需的全部状态，录制前由 RenderQueue 统一排序
+pub struct DrawCall<'a> {
+    pipeline: &'a wgpu::RenderPipeline,
+    bind_groups: Vec<(u32, &'a wgpu::BindGroup)>,
+    vertex_buffers: Vec<(u32, BufferRange<'a>)>,
+    index_buffer: Option<(BufferRange<'a>, wgpu::IndexFormat)>,
+    // 有索引缓冲区时是索引区间，否则是顶点区间
+    elements: Range<u32>,
+    instances: Range<u32>,
+    layer: u8,
+    depth_sort_key: u32,
+}
+
+impl<'a> DrawCall<'a> {
+    pub fn new(pipeline: &'a wgpu::RenderPipeline) -> Self {
+        Self {
+            pipeline,
+            bind_groups: Vec::new(),
+            vertex_buffers: Vec::new(),
+            index_buffer: None,
+            elements: 0..0,
+            instances: 0..1,
+            layer: layer::OPAQUE,
+            depth_sort_key: 0,
+        }
+    }
+
+    pub fn with_bind_group(mut self, index: u32, bind_group: &'a wgpu::BindGroup) -> Self {
+        self.bind_groups.push((index, bind_group));
+        self
+    }
+
+    // bounds 与 wgpu::Buffer::slice 相同，.. 表示整个缓冲区
+    pub fn with_vertex_buffer(mut self, slot: u32, buffer: &'a wgpu::Buffer, bounds: impl RangeBounds<wgpu::BufferAddress>) -> Self {
+        self.vertex_buffers.push((slot, BufferRange::new(buffer, bounds)));
+        self
+    }
+
+    pub fn with_index_buffer(
+        mut self,
+        buffer: &'a wgpu::Buffer,
+        bounds: impl RangeBounds<wgpu::BufferAddress>,
+        format: wgpu::IndexFormat,
+    ) -> Self {
+        self.index_buffer = Some((BufferRange::new(buffer, bounds), format));
+        self
+    }
+
+    pub fn with_elements(mut self, elements: Range<u32>) -> Self {
+        self.elements = elements;
+        self
+    }
+
+    pub fn with_instances(mut self, instances: Range<u32>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: u8) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    // 观察空间深度（到相机的距离），非负浮点数的位模式与数值同序，可直接作为排序键
+    pub fn with_depth(mut self, view_depth: f32) -> Self {
+        self.depth_sort_key = view_depth.max(0.0).to_bits();
+        self
+    }
+
+    pub fn with_depth_sort_key(mut self, depth_sort_key: u32) -> Self {
+        self.depth_sort_key = depth_sort_key;
+        self
+    }
+
+    pub fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    pub fn pipeline(&self) -> &'a wgpu::RenderPipeline {
+        self.pipeline
+    }
+
+    pub fn bind_groups(&self) -> &[(u32, &'a wgpu::BindGroup)] {
+        &self.bind_groups
+    }
+
+    // 不透明与 alpha 测试由近到远（利于提前深度测试），透明由远到近（画家算法），UI 与调试层保持提交顺序
+    pub fn sort_key(&self) -> (u8, u32) {
+        let depth = match self.layer {
+            layer::OPAQUE | layer::ALPHA_TEST => self.depth_sort_key,
+            layer::TRANSPARENT => u32::MAX - self.depth_sort_key,
+            _ => 0,
+        };
+        (self.layer, depth)
+    }
+
+    // 资源借用期间地址唯一，用于帧内排序与去重；跨帧的缓存键改用 global_id，见 hash_state
+    pub fn pipeline_id(&self) -> usize {
+        std::ptr::from_ref(self.pipeline) as usize
+    }
+
+    fn bind_group_ids(&self) -> impl Iterator<Item = (u32, usize)> + '_ {
+        self.bind_groups.iter().map(|&(index, bind_group)| (index, std::ptr::from_ref(bind_group) as usize))
+    }
+
+    // 层内先按管线、再按绑定组分组，最后按深度；透明层必须保持深度顺序，不参与分组
+    fn compare_state(&self, other: &Self) -> Ordering {
+        let (layer, depth) = self.sort_key();
+        let (other_layer, other_depth) = other.sort_key();
+        layer.cmp(&other_layer).then_with(|| {
+            if layer == layer::TRANSPARENT {
+                return Ordering::Equal;
+            }
+            self.pipeline_id()
+                .cmp(&other.pipeline_id())
+                .then_with(|| self.bind_group_ids().cmp(other.bind_group_ids()))
+        })
+        .then(depth.cmp(&other_depth))
+    }
+
+    pub fn record<'p>(&'p self, render_pass: &mut wgpu::RenderPass<'p>) {
+        self.record_with_state(render_pass, &mut BoundState::default());
+    }
+
+    // 绘制所用的全部状态，RenderBundleCache 据此判断是否需要重新录制。
+    // 资源按 wgpu 的 global_id 参与哈希：原地重建的绑定组或缓冲区地址不变，但 id 不同
+    pub fn hash_state(&self, state: &mut impl Hasher) {
+        self.pipeline.global_id().hash(state);
+        for &(index, bind_group) in &self.bind_groups {
+            (index, bind_group.global_id()).hash(state);
+        }
+        for &(slot, buffer) in &self.vertex_buffers {
+            (slot, buffer.id()).hash(state);
+        }
+        if let Some((buffer, format)) = self.index_buffer {
+            (buffer.id(), format).hash(state);
+        }
+        (self.elements.clone(), self.instances.clone(), self.layer).hash(state);
+    }
+
+    // 与上一次绘制相同的管线和绑定组不再重复设置
+    // RenderEncoder 同时覆盖渲染通道与渲染包编码器
+    fn record_with_state<'p>(&'p self, render_pass: &mut impl RenderEncoder<'p>, state: &mut BoundState) {
+        let pipeline_id = self.pipeline_id();
+        if state.pipeline != Some(pipeline_id) {
+            render_pass.set_pipeline(self.pipeline);
+            state.pipeline = Some(pipeline_id);
+            state.stats.pipeline_switches += 1;
+        }
+        for &(index, bind_group) in &self.bind_groups {
+            let slot = index as usize;
+            let bind_group_id = std::ptr::from_ref(bind_group) as usize;
+            if state.bind_groups.len() <= slot {
+                state.bind_groups.resize(slot + 1, None);
+            }
+            if state.bind_groups[slot] != Some(bind_group_id) {
+                render_pass.set_bind_group(index, bind_group, &[]);
+                state.bind_groups[slot] = Some(bind_group_id);
+                state.stats.bind_group_switches += 1;
+            }
+        }
+        state.stats.draws += 1;
+        for &(slot, buffer) in &self.vertex_buffers {
+            render_pass.set_vertex_buffer(slot, buffer.slice());
+        }
+        match self.index_buffer {
+            Some((buffer, format)) => {
+                render_pass.set_index_buffer(buffer.slice(), format);
+                render_pass.draw_indexed(self.elements.clone(), 0, self.instances.clone());
+            }
+            None => render_pass.draw(self.elements.clone(), self.instances.clone()),
+        }
+    }
+}
+
+// 缓冲区的一段，wgpu::BufferSlice 不公开所属缓冲区与区间，无法参与哈希，因此自己保存
+#[derive(Clone, Copy)]
+struct BufferRange<'a> {
+    buffer: &'a wgpu::Buffer,
+    offset: wgpu::BufferAddress,
+    // None 表示到缓冲区末尾
+    end: Option<wgpu::BufferAddress>,
+}
+
+impl<'a> BufferRange<'a> {
+    fn new(buffer: &'a wgpu::Buffer, bounds: impl RangeBounds<wgpu::BufferAddress>) -> Self {
+        let offset = match bounds.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(&end) => Some(end + 1),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+        Self { buffer, offset, end }
+    }
+
+    fn slice(&self) -> wgpu::BufferSlice<'a> {
+        match self.end {
+            Some(end) => self.buffer.slice(self.offset..end),
+            None => self.buffer.slice(self.offset..),
+        }
+    }
+
+    // (缓冲区 id, 起点, 终点)
+    fn id(&self) -> (wgpu::Id<wgpu::Buffer>, wgpu::BufferAddress, Option<wgpu::BufferAddress>) {
+        (self.buffer.global_id(), self.offset, self.end)
+    }
+}
+
+// 录制时实际发出的状态切换次数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordStats {
+    pub draws: u32,
+    pub pipeline_switches: u32,
+    pub bind_group_switches: u32,
+}
+
+#[derive(Default)]
+struct BoundState {
+    pipeline: Option<usize>,
+    bind_groups: Vec<Option<usize>>,
+    stats: RecordStats,
+}
+
+pub struct RenderQueue<'a> {
+    draws: Vec<DrawCall<'a>>,
+    sorted: bool,
+    material_sort: bool,
+}
+
+impl<'a> RenderQueue<'a> {
+    pub fn new() -> Self {
+        Self { draws: Vec::new(), sorted: true, material_sort: true }
+    }
+
+    // 只按层与深度排序，用于对比材质排序的效果
+    pub fn without_material_sort(mut self) -> Self {
+        self.material_sort = false;
+        self
+    }
+
+    pub fn push(&mut self, draw: DrawCall<'a>) {
+        self.draws.push(draw);
+        self.sorted = false;
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    // 稳定排序，键相同的绘制保持提交顺序
+    pub fn sort(&mut self) {
+        if !self.sorted {
+            if self.material_sort {
+                self.draws.sort_by(DrawCall::compare_state);
+            } else {
+                self.draws.sort_by_key(DrawCall::sort_key);
+            }
+            self.sorted = true;
+        }
+    }
+
+    pub fn draws(&self) -> &[DrawCall<'a>] {
+        &self.draws
+    }
+
+    // 不同层常在不同的渲染通道里录制（例如透明层写入 OIT 目标），按层区间分别录制
+    // 录制前需要先调用 sort
+    pub fn record_layers<'p>(&'p self, render_pass: &mut wgpu::RenderPass<'p>, layers: RangeInclusive<u8>) -> RecordStats {
+        debug_assert!(self.sorted, "RenderQueue::sort must be called before recording");
+        let mut state = BoundState::default();
+        for draw in self.draws.iter().filter(|draw| layers.contains(&draw.layer)) {
+            draw.record_with_state(render_pass, &mut state);
+        }
+        state.stats
+    }
+
+    // 把所有绘制录制进一个渲染包
+    pub fn record_into<'p>(&'p self, bundle: &mut SecondaryCommandBuffer<'p>) {
+        debug_assert!(self.sorted, "RenderQueue::sort must be called before recording");
+        let mut state = BoundState::default();
+        for draw in &self.draws {
+            draw.record_with_state(&mut **bundle, &mut state);
+        }
+    }
+
+    // 把层区间内的绘制平均分成 partitions 份，每份在独立线程上录制成渲染包
+    // 返回的渲染包按原顺序排列，交给 render_pass.execute_bundles 执行
+    pub fn record_parallel(
+        &self,
+        device: &wgpu::Device,
+        target: &BundleTarget,
+        layers: RangeInclusive<u8>,
+        partitions: usize,
+    ) -> Vec<wgpu::RenderBundle> {
+        debug_assert!(self.sorted, "RenderQueue::sort must be called before recording");
+        let draws = self.draws.iter().filter(|draw| layers.contains(&draw.layer)).collect::<Vec<_>>();
+        if draws.is_empty() {
+            return Vec::new();
+        }
+        let chunks = draws.chunks(draws.len().div_ceil(partitions.max(1))).collect::<Vec<_>>();
+        secondary_command_buffer::record_parallel(device, target, &chunks, |bundle: &mut SecondaryCommandBuffer, chunk| {
+            // 渲染包不继承任何状态，每份都从头绑定
+            let mut state = BoundState::default();
+            for draw in chunk.iter() {
+                draw.record_with_state(&mut **bundle, &mut state);
+            }
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.draws.clear();
+        self.sorted = true;
+    }
+}
+
+impl Default for RenderQueue<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+use crate::render_queue::RecordStats;
+
+// wgpu 默认限制下的绑定组槽位数
+const MAX_BIND_GROUPS: usize = 8;
+
+// 包装渲染通道，记住上一次绑定的管线与绑定组，值没有变化时跳过 set_* 调用
+// 资源按地址比较，和 RenderQueue 的做法一致；带动态偏移的绑定组总是重新设置
+pub struct RenderPassStateTracker<'r, 'p> {
+    render_pass: &'r mut wgpu::RenderPass<'p>,
+    pipeline: Option<usize>,
+    bind_groups: [Option<usize>; MAX_BIND_GROUPS],
+    stats: RecordStats,
+}
+
+impl<'r, 'p> RenderPassStateTracker<'r, 'p> {
+    pub fn new(render_pass: &'r mut wgpu::RenderPass<'p>) -> Self {
+        Self {
+            render_pass,
+            pipeline: None,
+            bind_groups: [None; MAX_BIND_GROUPS],
+            stats: RecordStats::default(),
+        }
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: &'p wgpu::RenderPipeline) {
+        let id = std::ptr::from_ref(pipeline) as usize;
+        if self.pipeline != Some(id) {
+            self.render_pass.set_pipeline(pipeline);
+            self.pipeline = Some(id);
+            self.stats.pipeline_switches += 1;
+        }
+    }
+
+    pub fn set_bind_group(&mut self, index: u32, bind_group: &'p wgpu::BindGroup, offsets: &[wgpu::DynamicOffset]) {
+        let id = std::ptr::from_ref(bind_group) as usize;
+        let slot = self.bind_groups.get_mut(index as usize);
+        if offsets.is_empty() && slot.as_deref() == Some(&Some(id)) {
+            return;
+        }
+        self.render_pass.set_bind_group(index, bind_group, offsets);
+        if let Some(slot) = slot {
+            *slot = offsets.is_empty().then_some(id);
+        }
+        self.stats.bind_group_switches += 1;
+    }
+
+    pub fn set_vertex_buffer(&mut self, slot: u32, buffer_slice: wgpu::BufferSlice<'p>) {
+        self.render_pass.set_vertex_buffer(slot, buffer_slice);
+    }
+
+    pub fn set_index_buffer(&mut self, buffer_slice: wgpu::BufferSlice<'p>, format: wgpu::IndexFormat) {
+        self.render_pass.set_index_buffer(buffer_slice, format);
+    }
+
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.render_pass.draw(vertices, instances);
+        self.stats.draws += 1;
+    }
+
+    pub fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        self.render_pass.draw_indexed(indices, base_vertex, instances);
+        self.stats.draws += 1;
+    }
+
+    // 忘掉缓存的状态，下一次 set_* 一定会下发
+    pub fn invalidate(&mut self) {
+        self.pipeline = None;
+        self.bind_groups = [None; MAX_BIND_GROUPS];
+    }
+
+    // 用于设置其他状态（视口、模板参考值等），绕过跟踪器设置管线或绑定组后要调用 invalidate
+    pub fn render_pass(&mut self) -> &mut wgpu::RenderPass<'p> {
+        self.render_pass
+    }
+
+    pub fn stats(&self) -> RecordStats {
+        self.stats
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResourceVersion(pub u64);
+
+// 可重新分配的纹理或缓冲区持有一个计数器，每次重新分配（尺寸变化、资源重载）时递增
+// 克隆得到的计数器共享同一个版本号
+#[derive(Debug, Clone)]
+pub struct VersionCounter {
+    label: Arc<str>,
+    version: Arc<AtomicU64>,
+}
+
+impl VersionCounter {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.into(),
+            version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn current(&self) -> ResourceVersion {
+        ResourceVersion(self.version.load(Ordering::Acquire))
+    }
+
+    // 重新分配资源之后调用，之前创建的绑定组随之失效
+    pub fn bump(&self) -> ResourceVersion {
+        ResourceVersion(self.version.fetch_add(1, Ordering::AcqRel) + 1)
+    }
+}
+
+struct Dependency {
+    binding: u32,
+    counter: VersionCounter,
+    version: ResourceVersion,
+}
+
+// 创建绑定组的同时记录所有受版本管理的资源当前的版本
+pub struct BindGroupBuilder<'a> {
+    label: &'a str,
+    layout: &'a wgpu::BindGroupLayout,
+    entries: Vec<wgpu::BindGroupEntry<'a>>,
+    dependencies: Vec<Dependency>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    pub fn new(label: &'a str, layout: &'a wgpu::BindGroupLayout) -> Self {
+        Self {
+            label,
+            layout,
+            entries: Vec::new(),
+            dependencies: Vec::new(),
+        }
+    }
+
+    // 不会被重新分配的资源，例如采样器
+    pub fn entry(mut self, binding: u32, resource: wgpu::BindingResource<'a>) -> Self {
+        self.entries.push(wgpu::BindGroupEntry { binding, resource });
+        self
+    }
+
+    pub fn texture_view(self, binding: u32, view: &'a wgpu::TextureView, counter: &VersionCounter) -> Self {
+        self.tracked(binding, wgpu::BindingResource::TextureView(view), counter)
+    }
+
+    pub fn buffer(self, binding: u32, buffer: &'a wgpu::Buffer, counter: &VersionCounter) -> Self {
+        self.tracked(binding, buffer.as_entire_binding(), counter)
+    }
+
+    pub fn tracked(mut self, binding: u32, resource: wgpu::BindingResource<'a>, counter: &VersionCounter) -> Self {
+        self.dependencies.push(Dependency {
+            binding,
+            counter: counter.clone(),
+            version: counter.current(),
+        });
+        self.entry(binding, resource)
+    }
+
+    pub fn build(self, device: &wgpu::Device) -> VersionedBindGroup {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(self.label),
+            layout: self.layout,
+            entries: &self.entries,
+        });
+        VersionedBindGroup {
+            bind_group,
+            label: self.label.to_string(),
+            dependencies: self.dependencies,
+        }
+    }
+}
+
+pub struct VersionedBindGroup {
+    bind_group: wgpu::BindGroup,
+    label: String,
+    dependencies: Vec<Dependency>,
+}
+
+impl VersionedBindGroup {
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale_dependency().is_some()
+    }
+
+    // 第一个在创建绑定组之后被重新分配的资源，返回 (绑定号, 资源标签)
+    pub fn stale_binding(&self) -> Option<(u32, &str)> {
+        self.stale_dependency().map(|dependency| (dependency.binding, dependency.counter.label()))
+    }
+
+    fn stale_dependency(&self) -> Option<&Dependency> {
+        self.dependencies.iter().find(|dependency| dependency.counter.current() != dependency.version)
+    }
+
+    // 引用的资源已被重新分配时 panic；调试构建下先打印失效的绑定
+    pub fn assert_current(&self) {
+        let Some(dependency) = self.stale_dependency() else {
+            return;
+        };
+        if cfg!(debug_assertions) {
+            tracing::error!(
+                "resource_version: bind group `{}` binding {} references `{}` at version {}, current version is {}",
+                self.label,
+                dependency.binding,
+                dependency.counter.label(),
+                dependency.version.0,
+                dependency.counter.current().0,
+            );
+        }
+        panic!("stale bind group `{}`", self.label);
+    }
+}
+
+// 设置绑定组之前检查版本
+pub trait SetVersionedBindGroup<'a> {
+    fn set_versioned_bind_group(&mut self, index: u32, bind_group: &'a VersionedBindGroup, offsets: &[wgpu::DynamicOffset]);
+}
+
+impl<'a> SetVersionedBindGroup<'a> for wgpu::RenderPass<'a> {
+    fn set_versioned_bind_group(&mut self, index: u32, bind_group: &'a VersionedBindGroup, offsets: &[wgpu::DynamicOffset]) {
+        bind_group.assert_current();
+        self.set_bind_group(index, &bind_group.bind_group, offsets);
+    }
+}
+
+impl<'a> SetVersionedBindGroup<'a> for wgpu::ComputePass<'a> {
+    fn set_versioned_bind_group(&mut self, index: u32, bind_group: &'a VersionedBindGroup, offsets: &[wgpu::DynamicOffset]) {
+        bind_group.assert_current();
+        self.set_bind_group(index, &bind_group.bind_group, offsets);
+    }
+}
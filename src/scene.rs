@@ -0,0 +1,1652 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cgmath::{ Deg, InnerSpace, Point3, Quaternion, Rotation3, SquareMatrix, Vector3 };
+use rapier3d::prelude::{ ColliderBuilder, RigidBodyBuilder, Vector as PhysicsVector };
+use wgpu::util::DeviceExt;
+use winit::event::{ ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent };
+
+use crate::{ primitives, profile_scope, ray_cast };
+use crate::aabb::Aabb;
+use crate::asset_cache::AssetCache;
+use crate::backend::RenderCapabilities;
+use crate::camera::{ Camera, CameraUniform };
+use crate::camera_path::{ CameraKeyframe, CameraPath };
+use crate::clock::DeterministicClock;
+use crate::command_pool::CommandPool;
+use crate::contact_shadow::ContactShadowPass;
+use crate::depth_peel::{ DepthPeelRenderer, DEPTH_PEEL_LAYERS };
+use crate::dynamic_resolution::DynamicResolutionScaling;
+use crate::filtered_shadow::{ FilteredShadowMap, ShadowMode };
+use crate::frustum::Frustum;
+use crate::gbuffer::{ GBuffer, GBufferDebugMode, DEFERRED_SHADING };
+use crate::gizmo::{ Gizmo, GizmoMode };
+use crate::gpu_timer::GpuTimer;
+use crate::light_probe::LightProbeSystem;
+#[cfg(feature = "gi-lpv")]
+use crate::light_propagation::LightPropagationVolumes;
+use crate::lighting::{ LightingUniform, PointLight, SpotLight };
+use crate::material::{ Material, MaterialUniform };
+use crate::material_animation::MaterialAnimator;
+use crate::mesh::{ MeshData, Vertex };
+use crate::mesh_upload::{ GpuMesh, MeshHandle, MeshUploader };
+use crate::oit::OitRenderer;
+use crate::physics::PhysicsWorld;
+use crate::pipeline_manager::PipelineManager;
+use crate::pipeline_patcher::PipelinePatcher;
+use crate::plugin::{ AppBuilder, GpuResources, PluginSet };
+use crate::portal::PortalRenderer;
+use crate::radix_sort::{ depth_key, GpuRadixSort };
+use crate::post_process::{ Blit, PostProcessStack, SCENE_COLOR_FORMAT };
+use crate::ray_cast::{ HitInfo, Ray };
+use crate::render_queue::{ layer, DrawCall, RenderQueue };
+use crate::shader_preprocessor::ShaderPreprocessor;
+use crate::shader_reflection::ShaderReflection;
+use crate::shadow_atlas::ShadowAtlas;
+use crate::ssgi::{ SsgiInputs, SsgiPass };
+use crate::texture::{ Texture, TextureHandle };
+use crate::timestep::FixedTimestep;
+use crate::transform::{ InstanceRaw, Transform };
+use crate::world::{ Entity, ObjectId, World };
+
+const SHADER_SOURCE: &str = include_str!("shader.wgsl");
+
+// 物理更新频率，与渲染帧率无关
+const PHYSICS_HZ: f32 = 60.0;
+
+// 演示场景的模拟与渲染，不依赖窗口与交换链：每帧渲染到调用方给出的纹理视图，
+// 窗口程序传入交换链纹理，测试传入离屏纹理
+pub struct Scene {
+    // 工作线程上传网格时共享设备
+    device: Arc<wgpu::Device>,
+    queue: wgpu::Queue,
+    // 每帧录制用的编码器池，跨帧保留
+    command_pool: CommandPool,
+    // 输出目标的格式与尺寸
+    target_format: wgpu::TextureFormat,
+    size: (u32, u32),
+    // 场景渲染目标按它缩放，输出目标保持原尺寸
+    dynamic_resolution: DynamicResolutionScaling,
+    // 后端支持的功能，决定各渲染功能是否走回退路径
+    capabilities: RenderCapabilities,
+
+    render_pipeline: wgpu::RenderPipeline,
+    transparent_pipeline: wgpu::RenderPipeline,
+    depth_texture: Texture,
+    oit: OitRenderer,
+    // DEPTH_PEEL_LAYERS 不为 0 时透明物体改用深度剥离
+    depth_peel: DepthPeelRenderer,
+    depth_peel_pipeline: wgpu::RenderPipeline,
+    // DEFERRED_SHADING 时不透明物体先写入 G-buffer，再由全屏光照通道着色到场景纹理；
+    // 调试视图不为 None 时用选中的 G-buffer 通道覆盖最终画面
+    gbuffer: GBuffer,
+    gbuffer_pipeline: wgpu::RenderPipeline,
+    deferred_lighting_pipeline: wgpu::RenderPipeline,
+    gbuffer_debug_mode: GBufferDebugMode,
+
+    // 场景先渲染到这张 HDR 纹理，经过后处理后再复制到交换链
+    scene_target: Texture,
+    post_process: PostProcessStack,
+    blit: Blit,
+    plugins: PluginSet,
+
+    camera: Camera,
+    camera_uniform: CameraUniform,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_path: Option<CameraPath>,
+
+    material: Material,
+    material_buffer: wgpu::Buffer,
+    material_bind_group_layout: wgpu::BindGroupLayout,
+    material_bind_group: wgpu::BindGroup,
+    // material 的光照贴图槽以 TextureHandle 为下标解析；没有设置时绑定白色纹理
+    lightmaps: Vec<Texture>,
+    white_lightmap: Texture,
+    material_animator: MaterialAnimator,
+
+    lighting_buffer: wgpu::Buffer,
+    lighting_bind_group_layout: wgpu::BindGroupLayout,
+    // 引用接触阴影的目标纹理，尺寸变化时重建
+    lighting_bind_group: wgpu::BindGroup,
+    // 延迟光照前由 G-buffer 写入的深度计算，方向取投射阴影的聚光灯
+    contact_shadow: ContactShadowPass,
+    // 所有投射阴影的光源共用一张深度图集
+    shadow_atlas: ShadowAtlas,
+    shadow_pipeline: wgpu::RenderPipeline,
+    filtered_shadow: FilteredShadowMap,
+    filtered_shadow_pipeline: wgpu::RenderPipeline,
+    #[cfg(feature = "gi-lpv")]
+    light_propagation: LightPropagationVolumes,
+    #[cfg(feature = "gi-lpv")]
+    rsm_pipeline: wgpu::RenderPipeline,
+    ssgi: SsgiPass,
+
+    // 设备丢失后从这里重新上传所有 GPU 资源
+    assets: AssetCache,
+    textures: HashMap<String, Texture>,
+
+    mesh_uploader: MeshUploader,
+    // 已在 GPU 上可用的异步上传网格
+    uploaded_meshes: HashMap<MeshHandle, GpuMesh>,
+
+    // CPU 端保留一份网格，供拾取使用
+    mesh: Arc<MeshData>,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+
+    // 半透明物体不参与拾取与物理，单独通过 OIT 绘制
+    glass: TransparentObjects,
+    // 支持计算着色器与间接绘制时对半透明物体做 GPU 深度排序
+    radix_sort: Option<GpuRadixSort>,
+
+    portals: PortalRenderer,
+
+    light_probes: LightProbeSystem,
+    // 按需创建的管线："light_probe" 只在第一次捕获探针时创建，"portal" 在后台编译
+    pipelines: PipelineManager,
+    // update 中选出的待捕获探针，在本帧 render 中渲染
+    pending_probe_capture: Option<usize>,
+
+    world: World,
+    physics: PhysicsWorld,
+    selected: Option<Entity>,
+    gizmo: Gizmo,
+    // 各通道的 GPU 耗时，在性能分析窗口中显示
+    gpu_timer: GpuTimer,
+    instance_buffer: wgpu::Buffer,
+    timestep: FixedTimestep,
+    clock: DeterministicClock,
+    // 程序启动后经过的秒数
+    elapsed_time: f32,
+    // 已提交的帧数，写入日志字段
+    frame_number: u64,
+    cursor_position: Option<[f32; 2]>,
+}
+
+impl Scene {
+    // 演示场景用到的网格：自转的三角形与半透明玻璃板
+    pub fn default_assets() -> AssetCache {
+        let mut assets = AssetCache::new();
+        assets.insert_mesh("triangle", triangle_mesh());
+        assets.insert_mesh("glass", primitives::plane(0.7, 0.3, 1));
+        assets
+    }
+
+    // device 需按 capabilities 创建；register_plugins 在后处理链创建之后注册渲染插件。
+    // 异步管线编译与网格上传用到 spawn_blocking，需要在 tokio 运行时中调用。
+    // 本类型不压入错误作用域，校验错误由调用方用 ErrorScope 捕获
+    pub fn new(
+        device: Arc<wgpu::Device>,
+        queue: wgpu::Queue,
+        capabilities: RenderCapabilities,
+        target_format: wgpu::TextureFormat,
+        (width, height): (u32, u32),
+        assets: AssetCache,
+        register_plugins: impl FnOnce(&mut AppBuilder),
+    ) -> Self {
+        let mut preprocessor = ShaderPreprocessor::new();
+        FilteredShadowMap::register_includes(&mut preprocessor);
+        #[cfg(feature = "gi-lpv")]
+        LightPropagationVolumes::register_includes(&mut preprocessor);
+        #[cfg(not(feature = "gi-lpv"))]
+        preprocessor.add_include("lpv_lighting", include_str!("lpv_lighting_disabled.wgsl"));
+        let shader_source = preprocessor.process(SHADER_SOURCE);
+        let shader = Arc::new(preprocessor.compile(&device, "Shader", &shader_source));
+
+        // 顶点布局由着色器的 @location 反射生成：0..5 为逐顶点属性，5..9 为实例的模型矩阵
+        let reflection = ShaderReflection::from_wgsl(&shader_source);
+        let vertex_layout = reflection.vertex_buffer_layout(0..5, wgpu::VertexStepMode::Vertex);
+        let instance_layout = reflection.vertex_buffer_layout(5..9, wgpu::VertexStepMode::Instance);
+        debug_assert_eq!(vertex_layout.array_stride, std::mem::size_of::<Vertex>() as wgpu::BufferAddress);
+        debug_assert_eq!(instance_layout.array_stride, std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress);
+
+        let camera = Camera {
+            eye: (0.0, 0.0, 2.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: Vector3::unit_y(),
+            aspect: width as f32 / height as f32,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+        };
+
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(&camera);
+
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout = CameraUniform::bind_group_layout(&device);
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("camera_bind_group"),
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                }
+            ],
+        });
+
+        // 演示用的相机路径：在三角形前方来回摆动
+        let keyframe = |x: f32, y: f32, z: f32, fov: f32| CameraKeyframe {
+            position: Point3::new(x, y, z),
+            target: Point3::new(0.0, 0.0, 0.0),
+            fov,
+        };
+        let camera_path = CameraPath::new(vec![
+            keyframe(0.0, 0.0, 2.0, 45.0),
+            keyframe(1.2, 0.4, 1.8, 50.0),
+            keyframe(0.0, 0.8, 2.4, 40.0),
+            keyframe(-1.2, 0.4, 1.8, 50.0),
+            keyframe(0.0, 0.0, 2.0, 45.0),
+        ], 0.5);
+
+        let material = Material::new(MaterialUniform::default());
+        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Material Buffer"),
+            contents: bytemuck::cast_slice(&[material.uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let material_bind_group_layout = MaterialUniform::bind_group_layout(&device);
+        // 演示场景没有烘焙光照贴图，可用 add_lightmap 与 set_lightmap 设置
+        let white_lightmap = Texture::from_color(&device, &queue, [255; 4], "White Lightmap");
+        let material_bind_group = MaterialUniform::bind_group(&device, &material_bind_group_layout, &material_buffer, &white_lightmap);
+
+        // 自发光强度在 2 秒内从 0 升到 2，循环播放
+        let mut material_animator = MaterialAnimator::new().looping(true);
+        material_animator.animate_emissive_intensity(0.0..=2.0, 2.0);
+
+        // 三角形前方一暖一冷两盏点光源，上方一盏聚光灯照向三角形并投射阴影
+        let mut shadow_atlas = ShadowAtlas::new(&device, 1024, &camera_bind_group_layout);
+        let spot_shadow_tile = shadow_atlas.allocate(512).expect("empty shadow atlas has room for one tile");
+        let mut lighting_uniform = LightingUniform::default();
+        lighting_uniform.set_point_lights(&[
+            PointLight { position: [0.8, 0.6, 0.8], radius: 3.0, color: [1.0, 0.8, 0.6], intensity: 1.5 },
+            PointLight { position: [-0.8, -0.2, 0.6], radius: 2.0, color: [0.4, 0.6, 1.0], intensity: 1.0 },
+        ]);
+        let spot_light = SpotLight {
+            position: [0.0, 1.5, 1.0],
+            radius: 5.0,
+            color: [1.0, 1.0, 0.9],
+            intensity: 4.0,
+            direction: [0.0, -1.5, -1.0],
+            inner_angle: 15f32.to_radians(),
+            outer_angle: 25f32.to_radians(),
+            shadow_tile: spot_shadow_tile as i32,
+            _padding: [0.0; 2],
+        };
+        lighting_uniform.set_spot_lights(&[spot_light]);
+        shadow_atlas.set_view_projection(spot_shadow_tile, spot_light.view_projection());
+        shadow_atlas.write(&queue);
+        // 聚光灯的直接光照默认使用方差阴影，图集中的图块仍用于次表面散射的透射距离和 PCF 模式
+        let mut filtered_shadow = FilteredShadowMap::new(&device, 512, capabilities.compute_shaders);
+        filtered_shadow.set_spot_light(0, &spot_light);
+        filtered_shadow.write(&queue);
+        // 聚光灯照亮的表面反弹一次的间接光，网格覆盖原点周围 4 × 4 × 4 的范围
+        #[cfg(feature = "gi-lpv")]
+        let light_propagation = {
+            let light_propagation = LightPropagationVolumes::new(&device, [16; 3], [-2.0; 3], 0.25, capabilities.compute_shaders);
+            light_propagation.set_spot_light(&queue, &spot_light);
+            light_propagation.write(&queue);
+            light_propagation
+        };
+        // 光传播体积运行时默认关闭屏幕空间全局光照，避免两份间接光叠加
+        #[cfg(feature = "gi-lpv")]
+        let ssgi_enabled = !light_propagation.is_enabled();
+        #[cfg(not(feature = "gi-lpv"))]
+        let ssgi_enabled = true;
+        let mut ssgi = SsgiPass::new(&device, width, height);
+        ssgi.enabled = ssgi_enabled;
+        let lighting_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lighting Buffer"),
+            contents: bytemuck::cast_slice(&[lighting_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let lighting_bind_group_layout = LightingUniform::bind_group_layout(&device);
+        let mut contact_shadow = ContactShadowPass::new(&device, width, height);
+        contact_shadow.light_direction = -Vector3::from(spot_light.direction).normalize();
+        #[cfg(feature = "gi-lpv")]
+        let lpv_entries = light_propagation.bind_group_entries();
+        #[cfg(not(feature = "gi-lpv"))]
+        let lpv_entries = [];
+        let lighting_bind_group =
+            Self::create_lighting_bind_group(&device, &lighting_bind_group_layout, &lighting_buffer, &shadow_atlas, &contact_shadow, &filtered_shadow, &lpv_entries);
+
+        // 两个探针分别位于三角形前后，轮流更新
+        let mut light_probes = LightProbeSystem::new(&device, &queue);
+        light_probes.add_probe(&device, &camera_bind_group_layout, [0.0, 0.0, 0.8], 0.5);
+        light_probes.add_probe(&device, &camera_bind_group_layout, [0.0, 0.0, -0.8], 1.0);
+
+        let render_pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &material_bind_group_layout,
+                light_probes.bind_group_layout(),
+                &lighting_bind_group_layout,
+            ],
+            push_constant_ranges: &[]
+        }));
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: SCENE_COLOR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL
+                })]
+            }),
+            // 图元（primitive）, 描述了将如何解释顶点来转换为三角形
+            primitive: wgpu::PrimitiveState {
+                // 每三个顶点组成一个三角形
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                // 告诉 wgpu 如何确定三角形的朝向
+                front_face: wgpu::FrontFace::Ccw,
+                // 告诉 wgpu 如何做三角形剔除
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false
+            },
+            // 多重采样
+            multisample: wgpu::MultisampleState {
+                // 确定管线将使用多少个采样
+                count: 1,
+                // 哪些采样应处于活动状态。目前我们使用全部采样
+                mask: !0,
+                // 与抗锯齿有关
+                alpha_to_coverage_enabled: false
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_STENCIL_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            // 表示渲染附件可以有多少数组层，不会渲染到数组纹理
+            multiview: None,
+        });
+
+        // 透过传送门绘制场景：只画模板值等于传送门参考值的区域；另一侧相机看到的是背面，不做剔除
+        let mut pipelines = PipelineManager::new(device.clone());
+        let fallback_shader = PipelinePatcher::fallback_shader(&device);
+        let portal_fallback = create_portal_pipeline(&device, &render_pipeline_layout, &reflection, &shader, &fallback_shader, "fs_solid");
+        let (portal_shader, portal_layout, portal_reflection) = (shader.clone(), render_pipeline_layout.clone(), reflection.clone());
+        pipelines.compile_async(
+            "portal",
+            move |device| create_portal_pipeline(device, &portal_layout, &portal_reflection, &portal_shader, &portal_shader, "fs_main"),
+            portal_fallback,
+        );
+        // 三角形后方的传送门，从另一侧看向三角形的背面
+        let mut portals = PortalRenderer::new(&device, SCENE_COLOR_FORMAT, Texture::DEPTH_STENCIL_FORMAT);
+        portals.add_portal(
+            &device,
+            [[-1.0, 0.3, -1.0], [1.0, 0.3, -1.0], [1.0, 0.9, -1.0], [-1.0, 0.9, -1.0]],
+            Camera {
+                eye: (0.0, 0.5, -3.0).into(),
+                target: (0.0, 0.0, 0.0).into(),
+                ..camera.clone()
+            },
+        );
+
+        // 立方体贴图的面是镜像的，捕获时使用正面为顺时针的管线
+        let (probe_shader, probe_layout, probe_reflection) = (shader.clone(), render_pipeline_layout.clone(), reflection.clone());
+        pipelines.register("light_probe", move |device| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Light Probe Pipeline"),
+            layout: Some(&probe_layout),
+            vertex: wgpu::VertexState {
+                module: &probe_shader,
+                entry_point: "vs_main",
+                buffers: &[
+                    probe_reflection.vertex_buffer_layout(0..5, wgpu::VertexStepMode::Vertex).layout(),
+                    probe_reflection.vertex_buffer_layout(5..9, wgpu::VertexStepMode::Instance).layout(),
+                ]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &probe_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: SCENE_COLOR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL
+                })]
+            }),
+            primitive: wgpu::PrimitiveState {
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        }));
+
+        // 透明管线与不透明管线共用顶点格式，片元输出到 OIT 的两个目标
+        let transparent_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Transparent Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_transparent",
+                targets: &OitRenderer::color_targets(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(OitRenderer::depth_stencil_state(Texture::DEPTH_STENCIL_FORMAT)),
+            multiview: None,
+        });
+
+        let depth_texture = Texture::create_depth_texture(&device, width, height, "Depth Texture");
+        let oit = OitRenderer::new(&device, width, height, SCENE_COLOR_FORMAT);
+
+        // 深度剥离的第 2 组换成上一层深度与场景深度，片元着色器不使用光照探针与光源
+        let depth_peel = DepthPeelRenderer::new(
+            &device,
+            width,
+            height,
+            DEPTH_PEEL_LAYERS,
+            &depth_texture.texture,
+            SCENE_COLOR_FORMAT,
+        );
+        let depth_peel_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Depth Peel Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &material_bind_group_layout, depth_peel.bind_group_layout()],
+            push_constant_ranges: &[]
+        });
+        let depth_peel_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Depth Peel Pipeline"),
+            layout: Some(&depth_peel_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_depth_peel",
+                targets: &[Some(DepthPeelRenderer::color_target())],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(DepthPeelRenderer::depth_stencil_state()),
+            multiview: None,
+        });
+
+        let gbuffer = GBuffer::new(&device, width, height, target_format);
+        let gbuffer_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("G-Buffer Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &material_bind_group_layout],
+            push_constant_ranges: &[]
+        });
+        let gbuffer_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("G-Buffer Pipeline"),
+            layout: Some(&gbuffer_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_gbuffer",
+                targets: &GBuffer::color_targets(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(GBuffer::depth_stencil_state(Texture::DEPTH_STENCIL_FORMAT)),
+            multiview: None,
+        });
+        // 第 1 组换成 G-buffer，其余各组与场景管线相同
+        let deferred_lighting_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Deferred Lighting Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                gbuffer.bind_group_layout(),
+                light_probes.bind_group_layout(),
+                &lighting_bind_group_layout,
+            ],
+            push_constant_ranges: &[]
+        });
+        let deferred_lighting_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Deferred Lighting Pipeline"),
+            layout: Some(&deferred_lighting_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_fullscreen",
+                buffers: &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_deferred",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: SCENE_COLOR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL
+                })]
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        // 阴影通道只写深度，第 0 组为 ShadowAtlas 中各图块的光源相机；三角形很薄，两面都投射阴影。
+        // vs_main 按材质变换纹理坐标，第 1 组仍需绑定材质
+        let shadow_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &material_bind_group_layout],
+            push_constant_ranges: &[]
+        });
+        let shadow_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&shadow_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(ShadowAtlas::depth_stencil_state()),
+            multiview: None,
+        });
+        // 可过滤阴影的矩通道与阴影通道共用顶点阶段，第 0 组换成 FilteredShadowMap 的布局，片元同时输出矩与指数
+        let filtered_shadow_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Filtered Shadow Pipeline Layout"),
+            bind_group_layouts: &[filtered_shadow.pass_bind_group_layout(), &material_bind_group_layout],
+            push_constant_ranges: &[]
+        });
+        let filtered_shadow_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Filtered Shadow Pipeline"),
+            layout: Some(&filtered_shadow_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[vertex_layout.layout(), instance_layout.layout()]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_shadow_moments",
+                targets: &FilteredShadowMap::color_targets(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(FilteredShadowMap::depth_stencil_state()),
+            multiview: None,
+        });
+        #[cfg(feature = "gi-lpv")]
+        let rsm_pipeline = {
+            // 反射阴影图需要材质颜色，第 1 组与场景相同
+            let rsm_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("RSM Pipeline Layout"),
+                bind_group_layouts: &[light_propagation.rsm_bind_group_layout(), &material_bind_group_layout],
+                push_constant_ranges: &[]
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("RSM Pipeline"),
+                layout: Some(&rsm_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[vertex_layout.layout(), instance_layout.layout()]
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_rsm",
+                    targets: &LightPropagationVolumes::rsm_color_targets(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: Some(LightPropagationVolumes::rsm_depth_stencil_state()),
+                multiview: None,
+            })
+        };
+
+        let scene_target = Self::create_scene_target(&device, width, height);
+        let gizmo = Gizmo::new(&device, target_format);
+        let gpu_timer = GpuTimer::new(&device, 16);
+        let mut post_process = PostProcessStack::new(SCENE_COLOR_FORMAT);
+        post_process.set_compute_supported(capabilities.compute_shaders);
+        let blit = Blit::new(&device, target_format);
+        let plugins = {
+            let mut app = AppBuilder::new(
+                &device,
+                &queue,
+                &capabilities,
+                SCENE_COLOR_FORMAT,
+                Texture::DEPTH_STENCIL_FORMAT,
+                &mut post_process,
+            );
+            register_plugins(&mut app);
+            app.finish()
+        };
+
+        let mesh = assets.mesh("triangle").expect("triangle mesh is cached");
+        let (vertex_buffer, index_buffer) = mesh.upload(&device, &queue);
+        let textures = assets
+            .images()
+            .map(|(name, image)| (name.to_string(), Texture::from_image(&device, &queue, image, name)))
+            .collect::<HashMap<_, _>>();
+
+        // 两块竖直的半透明玻璃前后错开，挡在两个三角形前面
+        let glass = TransparentObjects::new(
+            &device,
+            &queue,
+            &material_bind_group_layout,
+            &white_lightmap,
+            &assets.mesh("glass").expect("glass mesh is cached"),
+            &[Vector3::new(0.0, 0.0, 0.3), Vector3::new(0.3, 0.15, 0.6)].map(|position| Transform {
+                position,
+                rotation: Quaternion::from_angle_x(Deg(90.0)),
+                ..Default::default()
+            }),
+            MaterialUniform {
+                base_color: [0.2, 0.5, 0.9, 0.4],
+                ..Default::default()
+            },
+        );
+
+        let mut world = World::new();
+        // 演示场景没有地面，关闭重力，两个三角形只绕 z 轴自转
+        let mut physics = PhysicsWorld::new(Vector3::new(0.0, 0.0, 0.0), 1.0 / PHYSICS_HZ);
+        for x in [-0.5, 0.5] {
+            let entity = world.spawn(Transform {
+                position: Vector3::new(x, 0.0, 0.0),
+                scale: Vector3::new(0.8, 0.8, 0.8),
+                ..Default::default()
+            });
+            let body = RigidBodyBuilder::dynamic()
+                .angvel(PhysicsVector::new(0.0, 0.0, std::f32::consts::FRAC_PI_2))
+                .build();
+            let collider = ColliderBuilder::cuboid(0.2, 0.2, 0.01).build();
+            physics.insert_body(&world, entity, body, Some(collider));
+        }
+
+        let instance_buffer = Self::create_instance_buffer(&device, &world);
+
+        let mesh_uploader = MeshUploader::new(device.clone());
+        let command_pool = CommandPool::new(device.clone(), 1);
+        // 排序结果经由间接绘制参数使用，base_instance 非零需要 INDIRECT_FIRST_INSTANCE
+        let radix_sort = (capabilities.storage_buffers && capabilities.multi_draw_indirect).then(|| GpuRadixSort::new(&device));
+
+        Self {
+            device,
+            queue,
+            command_pool,
+            target_format,
+            size: (width, height),
+            dynamic_resolution: DynamicResolutionScaling::default(),
+            capabilities,
+            render_pipeline,
+            transparent_pipeline,
+            depth_texture,
+            oit,
+            depth_peel,
+            depth_peel_pipeline,
+            gbuffer,
+            gbuffer_pipeline,
+            deferred_lighting_pipeline,
+            gbuffer_debug_mode: GBufferDebugMode::None,
+            scene_target,
+            post_process,
+            plugins,
+            blit,
+            camera,
+            camera_uniform,
+            camera_buffer,
+            camera_bind_group,
+            camera_path: Some(camera_path),
+            material,
+            material_buffer,
+            material_bind_group_layout,
+            material_bind_group,
+            lightmaps: Vec::new(),
+            white_lightmap,
+            material_animator,
+            lighting_buffer,
+            lighting_bind_group_layout,
+            lighting_bind_group,
+            contact_shadow,
+            shadow_atlas,
+            shadow_pipeline,
+            filtered_shadow,
+            filtered_shadow_pipeline,
+            #[cfg(feature = "gi-lpv")]
+            light_propagation,
+            #[cfg(feature = "gi-lpv")]
+            rsm_pipeline,
+            ssgi,
+            assets,
+            textures,
+            mesh_uploader,
+            uploaded_meshes: HashMap::new(),
+            mesh,
+            vertex_buffer,
+            index_buffer,
+            glass,
+            radix_sort,
+            light_probes,
+            pipelines,
+            portals,
+            pending_probe_capture: None,
+            world,
+            physics,
+            selected: None,
+            gizmo,
+            gpu_timer,
+            instance_buffer,
+            timestep: FixedTimestep::from_hz(PHYSICS_HZ),
+            clock: DeterministicClock::from_env(),
+            elapsed_time: 0.0,
+            frame_number: 0,
+            cursor_position: None,
+        }
+    }
+
+    fn create_instance_buffer(device: &wgpu::Device, world: &World) -> wgpu::Buffer {
+        let instance_data = world
+            .interpolated_transforms(0.0)
+            .map(|transform| InstanceRaw::from(&transform))
+            .collect::<Vec<_>>();
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&instance_data),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    // 设备丢失后用新设备重建场景，再调用它从旧场景接管模拟状态：相机、物理、动画与时钟
+    pub fn adopt_simulation(&mut self, previous: &mut Scene) {
+        std::mem::swap(&mut self.camera, &mut previous.camera);
+        std::mem::swap(&mut self.camera_path, &mut previous.camera_path);
+        // 光照贴图属于旧设备，不随模拟状态接管
+        std::mem::swap(&mut self.material.uniform, &mut previous.material.uniform);
+        std::mem::swap(&mut self.material_animator, &mut previous.material_animator);
+        std::mem::swap(&mut self.world, &mut previous.world);
+        std::mem::swap(&mut self.physics, &mut previous.physics);
+        self.selected = previous.selected;
+        self.gbuffer_debug_mode = previous.gbuffer_debug_mode;
+        self.set_shadow_mode(previous.filtered_shadow.mode);
+        self.ssgi.enabled = previous.ssgi.enabled;
+        std::mem::swap(&mut self.timestep, &mut previous.timestep);
+        std::mem::swap(&mut self.clock, &mut previous.clock);
+        std::mem::swap(&mut self.dynamic_resolution, &mut previous.dynamic_resolution);
+        self.camera_uniform = previous.camera_uniform;
+        self.elapsed_time = previous.elapsed_time;
+        self.frame_number = previous.frame_number;
+
+        self.instance_buffer = Self::create_instance_buffer(&self.device, &self.world);
+        self.camera.aspect = self.size.0 as f32 / self.size.1 as f32;
+        self.resize_render_targets();
+    }
+
+    pub fn device(&self) -> &Arc<wgpu::Device> {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    pub fn assets(&self) -> &AssetCache {
+        &self.assets
+    }
+
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    // 替换帧时钟，测试中传入 DeterministicClock::fixed 逐帧复现
+    pub fn set_clock(&mut self, clock: DeterministicClock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    // 阻塞到后台编译的管线全部就绪，之后每帧使用的管线不再变化
+    pub fn wait_for_pipelines(&mut self) {
+        self.pipelines.wait();
+    }
+
+    // 输出目标的尺寸；render 收到不同的尺寸时也会调用
+    #[tracing::instrument(skip_all, fields(width = width, height = height))]
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.size = (width, height);
+            self.camera.aspect = width as f32 / height as f32;
+            self.resize_render_targets();
+        }
+    }
+
+    // 动态分辨率缩放后的场景渲染尺寸
+    fn render_size(&self) -> (u32, u32) {
+        self.dynamic_resolution.render_size(self.size.0, self.size.1)
+    }
+
+    // 按 render_size 重建场景渲染目标，以及引用它们的光照绑定组
+    fn resize_render_targets(&mut self) {
+        let (width, height) = self.render_size();
+        self.scene_target = Self::create_scene_target(&self.device, width, height);
+        self.depth_texture = Texture::create_depth_texture(&self.device, width, height, "Depth Texture");
+        self.oit.resize(&self.device, width, height);
+        self.depth_peel.resize(&self.device, width, height, &self.depth_texture.texture);
+        self.gbuffer.resize(&self.device, width, height);
+        self.contact_shadow.resize(&self.device, width, height);
+        self.ssgi.resize(&self.device, width, height);
+        #[cfg(feature = "gi-lpv")]
+        let lpv_entries = self.light_propagation.bind_group_entries();
+        #[cfg(not(feature = "gi-lpv"))]
+        let lpv_entries = [];
+        self.lighting_bind_group = Self::create_lighting_bind_group(
+            &self.device,
+            &self.lighting_bind_group_layout,
+            &self.lighting_buffer,
+            &self.shadow_atlas,
+            &self.contact_shadow,
+            &self.filtered_shadow,
+            &lpv_entries,
+        );
+    }
+
+    fn create_lighting_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        lighting_buffer: &wgpu::Buffer,
+        shadow_atlas: &ShadowAtlas,
+        contact_shadow: &ContactShadowPass,
+        filtered_shadow: &FilteredShadowMap,
+        // LightPropagationVolumes::bind_group_entries，未启用 gi-lpv 时为空
+        lpv_entries: &[wgpu::BindGroupEntry],
+    ) -> wgpu::BindGroup {
+        let [atlas_entry, sampler_entry, tiles_entry] = shadow_atlas.bind_group_entries();
+        let [moments_entry, filtered_entry, exponential_entry, four_moments_entry] = filtered_shadow.bind_group_entries();
+        let entries = [
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: lighting_buffer.as_entire_binding(),
+            },
+            atlas_entry,
+            sampler_entry,
+            tiles_entry,
+            contact_shadow.bind_group_entry(),
+            moments_entry,
+            filtered_entry,
+            exponential_entry,
+            four_moments_entry,
+        ];
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("lighting_bind_group"),
+            layout,
+            entries: &[entries.as_slice(), lpv_entries].concat(),
+        })
+    }
+
+    fn create_scene_target(device: &wgpu::Device, width: u32, height: u32) -> Texture {
+        Texture::create_render_target(
+            device,
+            width,
+            height,
+            SCENE_COLOR_FORMAT,
+            wgpu::TextureUsages::empty(),
+            "Scene Color Target",
+        )
+    }
+
+    // 操纵柄模式、剖面视图与拾取；返回 true 表示事件已被处理
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(key @ (VirtualKeyCode::Key1 | VirtualKeyCode::Key2 | VirtualKeyCode::Key3)),
+                    ..
+                },
+                ..
+            } => {
+                self.gizmo.set_mode(match key {
+                    VirtualKeyCode::Key1 => GizmoMode::Translate,
+                    VirtualKeyCode::Key2 => GizmoMode::Rotate,
+                    _ => GizmoMode::Scale,
+                });
+                true
+            }
+            // C 切换剖面视图：裁掉 y < 0 的部分
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::C),
+                    ..
+                },
+                ..
+            } => {
+                if self.camera_uniform.clip_plane == [0.0; 4] {
+                    self.set_clip_plane(Vector3::unit_y(), 0.0);
+                } else {
+                    self.clear_clip_plane();
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = [position.x as f32, position.y as f32];
+                self.cursor_position = Some(position);
+                if let (Some(entity), Some(ray)) = (self.selected, self.cursor_ray(position)) {
+                    if self.gizmo.is_dragging() {
+                        let mut transform = *self.world.transform(entity);
+                        self.gizmo.drag(&ray, &self.camera, &mut transform);
+                        self.world.teleport(entity, transform);
+                        self.physics.set_body_transform(&self.world, entity);
+                    } else {
+                        self.gizmo.update_hover(&ray, self.world.transform(entity), &self.camera);
+                    }
+                }
+                false
+            }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                let Some(position) = self.cursor_position else {
+                    return false;
+                };
+                // 操纵柄优先于物体拾取
+                if let (Some(entity), Some(ray)) = (self.selected, self.cursor_ray(position)) {
+                    if self.gizmo.begin_drag(&ray, self.world.transform(entity), &self.camera) {
+                        return true;
+                    }
+                }
+                self.selected = self.pick_cpu(position).map(|(object, hit)| {
+                    tracing::info!(?object, t = hit.t, triangle = hit.triangle, "picked object");
+                    object
+                });
+                true
+            }
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                self.gizmo.end_drag();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // 丢弃平面下方（dot(p, normal) + distance < 0）的几何体，用于水面反射与剖面视图
+    fn set_clip_plane(&mut self, normal: Vector3<f32>, distance: f32) {
+        self.camera_uniform.set_clip_plane(normal, distance);
+    }
+
+    fn clear_clip_plane(&mut self) {
+        self.camera_uniform.clear_clip_plane();
+    }
+
+    // 不透明实例写入 G-buffer，同时清空并写入场景深度
+    fn record_geometry_pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut geometry_pass = self.gbuffer.begin_geometry_pass(encoder, &self.depth_texture.view);
+        geometry_pass.set_pipeline(&self.gbuffer_pipeline);
+        geometry_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        geometry_pass.set_bind_group(1, &self.material_bind_group, &[]);
+        geometry_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        geometry_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        geometry_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        geometry_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+    }
+
+    // 纹理需为本场景设备上创建的，返回的句柄交给 set_lightmap
+    pub fn add_lightmap(&mut self, lightmap: Texture) -> TextureHandle {
+        self.lightmaps.push(lightmap);
+        TextureHandle(self.lightmaps.len() as u32 - 1)
+    }
+
+    // 设置场景材质的光照贴图并重建材质绑定组；None 或无效句柄时绑定白色纹理
+    pub fn set_lightmap(&mut self, lightmap: Option<TextureHandle>) -> &mut Self {
+        self.material.set_lightmap_texture(lightmap);
+        let texture = self.material
+            .lightmap_texture()
+            .and_then(|handle| self.lightmaps.get(handle.0 as usize))
+            .unwrap_or(&self.white_lightmap);
+        self.material_bind_group = MaterialUniform::bind_group(&self.device, &self.material_bind_group_layout, &self.material_buffer, texture);
+        self
+    }
+
+    // 只改 uniform 中的模式，不重建管线
+    pub fn set_shadow_mode(&mut self, mode: ShadowMode) {
+        self.filtered_shadow.mode = mode;
+        self.filtered_shadow.write(&self.queue);
+    }
+
+    pub fn cycle_shadow_mode(&mut self) -> ShadowMode {
+        let mode = self.filtered_shadow.mode.next();
+        self.set_shadow_mode(mode);
+        mode
+    }
+
+    pub fn cycle_gbuffer_debug_mode(&mut self) -> GBufferDebugMode {
+        self.gbuffer_debug_mode = self.gbuffer_debug_mode.next();
+        self.gbuffer_debug_mode
+    }
+
+    // 返回切换后是否启用
+    pub fn toggle_ssgi(&mut self) -> bool {
+        self.ssgi.enabled = !self.ssgi.enabled;
+        self.ssgi.enabled
+    }
+
+    fn cursor_ray(&self, mouse_pos: [f32; 2]) -> Option<Ray> {
+        let screen_size = [self.size.0 as f32, self.size.1 as f32];
+        Ray::from_screen(mouse_pos, screen_size, self.camera.build_view_projection_matrix())
+    }
+
+    // 在 CPU 上拾取：把鼠标位置反投影成世界空间射线，再变换到每个物体的局部空间与网格求交。
+    // 视锥体外的物体与射线碰不到局部包围盒的物体不逐三角形求交
+    fn pick_cpu(&self, mouse_pos: [f32; 2]) -> Option<(ObjectId, HitInfo)> {
+        let ray = self.cursor_ray(mouse_pos)?;
+        let view_proj = self.camera.build_view_projection_matrix();
+        let local_bounds = Aabb::from_vertices(&self.mesh.vertices);
+
+        self.world
+            .entities()
+            .filter_map(|entity| {
+                let local_to_world = self.world.transform(entity).to_matrix();
+                if !Frustum::from_matrix(view_proj * local_to_world).intersects_aabb(&local_bounds) {
+                    return None;
+                }
+                let world_to_local = local_to_world.invert()?;
+                // 射线方向经过同一矩阵变换，局部空间的 t 与世界空间一致
+                let local_ray = ray.transformed(&world_to_local);
+                local_bounds.intersect_ray(&local_ray)?;
+                let hit = ray_cast::intersect_mesh(local_ray, &self.mesh.vertices, &self.mesh.indices)?;
+                Some((entity, hit))
+            })
+            .min_by(|a, b| a.1.t.total_cmp(&b.1.t))
+    }
+
+    #[tracing::instrument(skip_all, fields(frame_number = self.frame_number))]
+    fn update(&mut self, elapsed: Duration) {
+        profile_scope!("update");
+
+        let steps = self.timestep.advance(elapsed);
+        self.elapsed_time += elapsed.as_secs_f32();
+
+        if let Some(camera_path) = self.camera_path.as_mut() {
+            camera_path.update(elapsed.as_secs_f32(), &mut self.camera);
+        }
+        self.camera_uniform.update_view_proj(&self.camera);
+        self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+        self.portals.update(&self.queue, self.camera.aspect);
+
+        // 上一个待捕获的探针还没渲染时不再选新的，避免跳过
+        if self.pending_probe_capture.is_none() {
+            self.pending_probe_capture = self.light_probes.update(elapsed.as_secs_f32());
+        }
+
+        self.material_animator.update(self.elapsed_time, &mut self.material.uniform);
+        self.material.uniform.update_uv_scroll(elapsed.as_secs_f32());
+        self.queue.write_buffer(&self.material_buffer, 0, bytemuck::cast_slice(&[self.material.uniform]));
+
+        self.pipelines.poll();
+        self.plugins.update(&mut self.world, elapsed.as_secs_f32());
+
+        for _ in 0..steps {
+            // 在物理步边界记录上一状态，供渲染插值使用
+            self.world.record_previous_transforms();
+            self.fixed_update(self.timestep.step());
+        }
+    }
+
+    // 以固定步长推进模拟
+    fn fixed_update(&mut self, dt: f32) {
+        self.physics.integration_parameters.dt = dt;
+        self.physics.step();
+        self.physics.sync_transforms(&mut self.world);
+    }
+
+    // 按帧间余量 alpha 混合前后两次物理状态，写入实例缓冲区
+    fn upload_interpolated_instances(&self) {
+        let alpha = self.timestep.alpha();
+        let instance_data = self.world
+            .interpolated_transforms(alpha)
+            .map(|transform| InstanceRaw::from(&transform))
+            .collect::<Vec<_>>();
+        self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instance_data));
+    }
+
+    // 到相机的距离，作为绘制排序的深度
+    fn view_depth(&self, position: [f32; 3]) -> f32 {
+        (Point3::from(position) - self.camera.eye).magnitude()
+    }
+
+    fn world_center(&self) -> [f32; 3] {
+        let (sum, count) = self.world
+            .interpolated_transforms(self.timestep.alpha())
+            .fold((Vector3::new(0.0, 0.0, 0.0), 0.0), |(sum, count), transform| (sum + transform.0.position, count + 1.0));
+        if count > 0.0 { (sum / count).into() } else { [0.0; 3] }
+    }
+
+    // 推进一帧并渲染到 target，不依赖事件循环，配合固定步长时钟可逐帧复现
+    pub fn step_frame(&mut self, target: &wgpu::TextureView, size: (u32, u32)) -> Option<Vec<(String, f64)>> {
+        let elapsed = self.clock.tick();
+        self.update(elapsed);
+        self.render(target, size)
+    }
+
+    // target 的格式须与创建时的 target_format 一致，size 为它的尺寸。
+    // 命令在返回前提交；返回本帧解析出的各通道 GPU 耗时，没有计时结果时为 None
+    #[tracing::instrument(skip_all, fields(wgpu_label = "Render Pass", frame_number = self.frame_number))]
+    pub fn render(&mut self, target: &wgpu::TextureView, size: (u32, u32)) -> Option<Vec<(String, f64)>> {
+        profile_scope!("render");
+        if size != self.size {
+            self.resize(size.0, size.1);
+        }
+        // 上一帧调整了动态分辨率时，在录制本帧之前重建渲染目标
+        if self.scene_target.size() != self.render_size() {
+            self.resize_render_targets();
+        }
+
+        self.upload_interpolated_instances();
+
+        // 工作线程录制好的网格上传命令随本帧一起提交，且排在本帧命令之前
+        let (upload_commands, uploaded_meshes) = self.mesh_uploader.collect();
+        self.uploaded_meshes.extend(uploaded_meshes);
+
+        self.command_pool.reset();
+        let mut encoder = self.command_pool.acquire();
+
+        {
+            profile_scope!("shadow_atlas");
+            let mut shadow_pass = self.shadow_atlas.begin_pass(&mut encoder);
+            shadow_pass.set_pipeline(&self.shadow_pipeline);
+            shadow_pass.set_bind_group(1, &self.material_bind_group, &[]);
+            shadow_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            shadow_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            shadow_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            for tile in 0..self.shadow_atlas.tiles().len() {
+                self.shadow_atlas.set_tile(&mut shadow_pass, tile);
+                shadow_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+            }
+        }
+        {
+            profile_scope!("filtered_shadow");
+            {
+                let mut moments_pass = self.filtered_shadow.begin_pass(&mut encoder);
+                moments_pass.set_pipeline(&self.filtered_shadow_pipeline);
+                moments_pass.set_bind_group(1, &self.material_bind_group, &[]);
+                moments_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                moments_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                moments_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                moments_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+            }
+            self.filtered_shadow.blur(&mut encoder, &self.device, &self.queue);
+        }
+        #[cfg(feature = "gi-lpv")]
+        {
+            profile_scope!("light_propagation");
+            if let Some(mut rsm_pass) = self.light_propagation.begin_rsm_pass(&mut encoder) {
+                rsm_pass.set_pipeline(&self.rsm_pipeline);
+                rsm_pass.set_bind_group(1, &self.material_bind_group, &[]);
+                rsm_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                rsm_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                rsm_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                rsm_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+            }
+            self.light_propagation.propagate(&mut encoder);
+        }
+
+        let clear_color = wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 };
+        // 捕获时场景绑定备用贴图，不能采样正在写入的立方体贴图
+        if let Some(index) = self.pending_probe_capture.take() {
+            profile_scope!("light_probe_capture");
+            self.gpu_timer.begin(&mut encoder, "Light Probe Capture");
+            let probe_pipeline = self.pipelines.get("light_probe").expect("light probe pipeline is registered");
+            for face in 0..6 {
+                let mut render_pass = self.light_probes.begin_face_pass(&mut encoder, index, face, clear_color);
+                render_pass.set_pipeline(probe_pipeline);
+                render_pass.set_bind_group(1, &self.material_bind_group, &[]);
+                render_pass.set_bind_group(2, self.light_probes.fallback_bind_group(), &[]);
+                render_pass.set_bind_group(3, &self.lighting_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+            }
+            self.gpu_timer.end(&mut encoder, "Light Probe Capture");
+        }
+
+        // 所有实例一次绘制，共用离它们中心最近的探针
+        let world_center = self.world_center();
+        if DEFERRED_SHADING {
+            self.gpu_timer.begin(&mut encoder, "Deferred Shading");
+            self.record_geometry_pass(&mut encoder);
+            self.contact_shadow.apply(&mut encoder, &self.device, &self.queue, &self.camera, &self.depth_texture.texture);
+            let mut lighting_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Deferred Lighting Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.scene_target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(clear_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            lighting_pass.set_pipeline(&self.deferred_lighting_pipeline);
+            lighting_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            lighting_pass.set_bind_group(2, self.light_probes.bind_group_for(world_center), &[]);
+            lighting_pass.set_bind_group(3, &self.lighting_bind_group, &[]);
+            self.gbuffer.draw_lighting(&mut lighting_pass);
+            drop(lighting_pass);
+            self.ssgi.apply(
+                &mut encoder,
+                &self.device,
+                &self.queue,
+                &self.camera,
+                SsgiInputs {
+                    depth: &self.depth_texture.texture,
+                    normal: &self.gbuffer.normal().view,
+                    albedo: &self.gbuffer.albedo().view,
+                    scene_color: &self.scene_target.view,
+                },
+            );
+            self.gpu_timer.end(&mut encoder, "Deferred Shading");
+        }
+
+        let mut render_queue = RenderQueue::new();
+        if !DEFERRED_SHADING {
+            render_queue.push(
+                DrawCall::new(&self.render_pipeline)
+                    .with_bind_group(0, &self.camera_bind_group)
+                    .with_bind_group(1, &self.material_bind_group)
+                    .with_bind_group(2, self.light_probes.bind_group_for(world_center))
+                    .with_bind_group(3, &self.lighting_bind_group)
+                    .with_vertex_buffer(0, &self.vertex_buffer, ..)
+                    .with_vertex_buffer(1, &self.instance_buffer, ..)
+                    .with_index_buffer(&self.index_buffer, .., wgpu::IndexFormat::Uint16)
+                    .with_elements(0..self.mesh.index_count())
+                    // 每个实体一个实例
+                    .with_instances(0..self.world.len() as u32)
+                    .with_layer(layer::OPAQUE)
+                    .with_depth(self.view_depth(world_center)),
+            );
+        }
+        render_queue.sort();
+
+        // 渲染通道
+        self.gpu_timer.begin(&mut encoder, "Scene Pass");
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[
+                // 这就是片元着色器中 @location(0) 标记指向的颜色附件
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.scene_target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        // 告诉 wgpu 如何处理存储在前一帧的颜色；延迟着色时保留光照通道的结果
+                        load: if DEFERRED_SHADING { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear(clear_color) },
+                        // 是否要将渲染的结果存储到纹理视图后面的纹理上
+                        store: wgpu::StoreOp::Store
+                    }
+                })
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                // 延迟着色时几何通道已写入不透明物体的深度
+                depth_ops: Some(wgpu::Operations {
+                    load: if DEFERRED_SHADING { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear(1.0) },
+                    store: wgpu::StoreOp::Store,
+                }),
+                // 传送门遮罩写入模板
+                stencil_ops: Some(wgpu::Operations {
+                    load: if DEFERRED_SHADING { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear(0) },
+                    store: wgpu::StoreOp::Store,
+                }),
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_queue.record_layers(&mut render_pass, layer::OPAQUE..=layer::ALPHA_TEST);
+
+        let probe_bind_group = self.light_probes.bind_group_for(world_center);
+        let portal_pipeline = self.pipelines.get("portal").expect("portal pipeline is registered");
+        self.portals.draw(&mut render_pass, &self.camera_bind_group, |render_pass, camera_bind_group| {
+            render_pass.set_pipeline(portal_pipeline);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.material_bind_group, &[]);
+            render_pass.set_bind_group(2, probe_bind_group, &[]);
+            render_pass.set_bind_group(3, &self.lighting_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..self.mesh.index_count(), 0, 0..self.world.len() as u32);
+        });
+        drop(render_pass);
+        self.gpu_timer.end(&mut encoder, "Scene Pass");
+
+        // 透明物体：深度剥离逐层绘制后从后往前合成；关闭时先累加到 OIT 目标，再合成到场景纹理上
+        self.gpu_timer.begin(&mut encoder, "OIT");
+        self.glass.sort(&self.device, &self.queue, &mut encoder, self.radix_sort.as_mut(), self.camera.eye);
+        if DEPTH_PEEL_LAYERS > 0 {
+            for layer in 0..self.depth_peel.layer_count() {
+                let mut peel_pass = self.depth_peel.begin_peel_pass(&mut encoder, layer);
+                self.glass.draw(&mut peel_pass, &self.depth_peel_pipeline, &self.camera_bind_group, self.depth_peel.bind_group(layer));
+            }
+            self.depth_peel.composite(&mut encoder, &self.scene_target.view);
+        } else {
+            let mut transparent_pass = self.oit.begin_transparent_pass(&mut encoder, &self.depth_texture.view);
+            transparent_pass.set_bind_group(3, &self.lighting_bind_group, &[]);
+            self.glass.draw(
+                &mut transparent_pass,
+                &self.transparent_pipeline,
+                &self.camera_bind_group,
+                self.light_probes.bind_group_for(self.glass.center()),
+            );
+            drop(transparent_pass);
+            self.oit.composite(&mut encoder, &self.scene_target.view);
+        }
+        self.gpu_timer.end(&mut encoder, "OIT");
+
+        self.gpu_timer.begin(&mut encoder, "Plugins");
+        self.plugins.render(
+            &mut encoder,
+            &GpuResources {
+                device: &self.device,
+                queue: &self.queue,
+                color_view: &self.scene_target.view,
+                depth_view: &self.depth_texture.view,
+                camera: &self.camera,
+                camera_bind_group: &self.camera_bind_group,
+                size: self.render_size(),
+            },
+        );
+        self.gpu_timer.end(&mut encoder, "Plugins");
+
+        self.gpu_timer.begin(&mut encoder, "Post Process");
+        let final_view = self.post_process.run(
+            &mut encoder,
+            &self.scene_target.view,
+            self.render_size(),
+            &self.device,
+            &self.queue,
+        );
+        self.gpu_timer.end(&mut encoder, "Post Process");
+        self.gpu_timer.begin(&mut encoder, "Blit");
+        self.blit.draw(&mut encoder, &self.device, final_view, target);
+        self.gpu_timer.end(&mut encoder, "Blit");
+
+        if self.gbuffer_debug_mode != GBufferDebugMode::None {
+            self.gpu_timer.begin(&mut encoder, "G-Buffer Debug");
+            // 前向渲染时 G-buffer 只为调试视图绘制，此时场景深度已不再使用
+            if !DEFERRED_SHADING {
+                self.record_geometry_pass(&mut encoder);
+            }
+            self.gbuffer.draw_debug(&mut encoder, &self.queue, target, self.gbuffer_debug_mode, &self.camera);
+            self.gpu_timer.end(&mut encoder, "G-Buffer Debug");
+        }
+
+        if let Some(entity) = self.selected {
+            self.gizmo.render(&mut encoder, &self.queue, target, self.world.transform(entity), &self.camera);
+        }
+
+        self.gpu_timer.resolve_queries(&mut encoder);
+
+        drop(encoder);
+        self.queue.submit(upload_commands.into_iter().chain(self.command_pool.finish()));
+        self.frame_number += 1;
+
+        // 时间戳查询只在 profiling 特性下请求，否则没有计时结果，分辨率保持不变
+        let timings = self.gpu_timer.resolve(&self.device, &self.queue)?;
+        let gpu_frame_ms = timings.iter().map(|(_, nanoseconds)| nanoseconds).sum::<f64>() / 1_000_000.0;
+        self.dynamic_resolution.update(gpu_frame_ms as f32, self.size.0, self.size.1);
+        Some(timings)
+    }
+}
+
+// 透过传送门绘制场景：只画模板值等于传送门参考值的区域；另一侧相机看到的是背面，不做剔除
+fn create_portal_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    reflection: &ShaderReflection,
+    shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    fragment_entry_point: &str,
+) -> wgpu::RenderPipeline {
+    let vertex_layout = reflection.vertex_buffer_layout(0..5, wgpu::VertexStepMode::Vertex);
+    let instance_layout = reflection.vertex_buffer_layout(5..9, wgpu::VertexStepMode::Instance);
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Portal Scene Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[vertex_layout.layout(), instance_layout.layout()]
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fragment_shader,
+            entry_point: fragment_entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format: SCENE_COLOR_FORMAT,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL
+            })]
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        multisample: wgpu::MultisampleState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: Texture::DEPTH_STENCIL_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: PortalRenderer::content_stencil_state(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multiview: None,
+    })
+}
+
+// 与最初着色器中按顶点序号生成的三角形相同
+fn triangle_mesh() -> MeshData {
+    let vertex = |x: f32, y: f32| Vertex {
+        position: [x, y, 0.0],
+        normal: [0.0, 0.0, 1.0],
+        tex_coords: [x + 0.5, 0.5 - y],
+        uv1: [x + 0.5, 0.5 - y],
+    };
+    MeshData {
+        vertices: vec![vertex(0.5, -0.5), vertex(0.0, 0.5), vertex(-0.5, -0.5)],
+        indices: vec![0, 1, 2],
+    }
+}
+
+// 共用网格与材质的一组半透明物体，每个物体是一个实例。每帧按到相机的距离在 GPU 上基数排序，
+// 再按排序后的间接绘制参数从远到近逐个绘制；不支持计算着色器或间接绘制的后端在 CPU 上排序后逐实例绘制
+struct TransparentObjects {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    instance_buffer: wgpu::Buffer,
+    material_bind_group: wgpu::BindGroup,
+    // 用于计算深度与选择光照探针
+    positions: Vec<[f32; 3]>,
+    // (depth_key, draw_index)，每帧重写后原地排序
+    keys_buffer: wgpu::Buffer,
+    // 每个物体一个 DrawIndexedIndirect，base_instance 指向它的实例
+    draws_buffer: wgpu::Buffer,
+    sorted_draws: Option<wgpu::Buffer>,
+    // CPU 回退路径的绘制顺序
+    cpu_order: Vec<u32>,
+}
+
+impl TransparentObjects {
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        material_bind_group_layout: &wgpu::BindGroupLayout,
+        lightmap: &Texture,
+        mesh: &MeshData,
+        transforms: &[Transform],
+        material: MaterialUniform,
+    ) -> Self {
+        let (vertex_buffer, index_buffer) = mesh.upload(device, queue);
+        let instances = transforms.iter().map(|transform| InstanceRaw { model: transform.to_matrix().into() }).collect::<Vec<_>>();
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Transparent Instance Buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Transparent Material Buffer"),
+            contents: bytemuck::cast_slice(&[material]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let material_bind_group = MaterialUniform::bind_group(device, material_bind_group_layout, &material_buffer, lightmap);
+
+        let keys_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Transparent Sort Keys"),
+            size: (std::mem::size_of::<[u32; 2]>() * transforms.len().max(1)) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let draws = (0..transforms.len() as u32)
+            .flat_map(|instance| {
+                wgpu::util::DrawIndexedIndirect {
+                    vertex_count: mesh.index_count(),
+                    instance_count: 1,
+                    base_index: 0,
+                    vertex_offset: 0,
+                    base_instance: instance,
+                }
+                .as_bytes()
+                .to_vec()
+            })
+            .collect::<Vec<_>>();
+        let draws_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Transparent Indirect Draws"),
+            contents: &draws,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_count: mesh.index_count(),
+            instance_buffer,
+            material_bind_group,
+            positions: transforms.iter().map(|transform| transform.position.into()).collect(),
+            keys_buffer,
+            draws_buffer,
+            sorted_draws: None,
+            cpu_order: (0..transforms.len() as u32).collect(),
+        }
+    }
+
+    fn center(&self) -> [f32; 3] {
+        let sum = self.positions.iter().fold(Vector3::new(0.0, 0.0, 0.0), |sum, &position| sum + Vector3::from(position));
+        (sum / self.positions.len().max(1) as f32).into()
+    }
+
+    // 按到 eye 的距离从远到近排序；radix_sort 为 None 时在 CPU 上排序
+    fn sort(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        radix_sort: Option<&mut GpuRadixSort>,
+        eye: Point3<f32>,
+    ) {
+        let keys = self.positions
+            .iter()
+            .enumerate()
+            .map(|(index, &position)| [depth_key((Point3::from(position) - eye).magnitude()), index as u32])
+            .collect::<Vec<_>>();
+        let count = keys.len() as u32;
+        match radix_sort {
+            Some(radix_sort) => {
+                queue.write_buffer(&self.keys_buffer, 0, bytemuck::cast_slice(&keys));
+                radix_sort.sort(device, queue, encoder, &self.keys_buffer, count);
+                let sorted_draws = self.sorted_draws.get_or_insert_with(|| {
+                    device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Sorted Indirect Draws"),
+                        size: self.draws_buffer.size(),
+                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT,
+                        mapped_at_creation: false,
+                    })
+                });
+                radix_sort.gather_indirect(device, encoder, &self.keys_buffer, &self.draws_buffer, sorted_draws);
+            }
+            None => {
+                let mut keys = keys;
+                keys.sort_by_key(|&[key, _]| key);
+                self.cpu_order = keys.into_iter().map(|[_, index]| index).collect();
+            }
+        }
+    }
+
+    // 第 3 组（若管线需要）由调用方设置；需要先调用 sort
+    fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        camera_bind_group: &'a wgpu::BindGroup,
+        group_2_bind_group: &'a wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.material_bind_group, &[]);
+        render_pass.set_bind_group(2, group_2_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        match &self.sorted_draws {
+            Some(sorted_draws) => GpuRadixSort::draw_indexed_sorted(render_pass, sorted_draws, self.positions.len() as u32),
+            None => {
+                for &instance in &self.cpu_order {
+                    render_pass.draw_indexed(0..self.index_count, 0, instance..instance + 1);
+                }
+            }
+        }
+    }
+}
+
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use ttf_parser::{ Face, GlyphId, OutlineBuilder };
+
+// 每个输出像素在每个方向上的超采样倍数
+const SUPERSAMPLE: u32 = 8;
+// 曲线展平为直线段的段数
+const CURVE_SEGMENTS: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphMetrics {
+    // 在图集中的纹理坐标，包含 sdf_range 的留白
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+    // 图集中字形块的像素尺寸
+    pub size: [f32; 2],
+    // 笔位原点到字形块左上角的偏移，y 轴向上
+    pub bearing: [f32; 2],
+    pub advance: f32,
+}
+
+// 单通道有符号距离场图集，128 为轮廓边缘，越大越靠近字形内部
+pub struct SdfAtlas {
+    pub texture_data: Vec<u8>,
+    pub glyph_metrics: HashMap<char, GlyphMetrics>,
+    pub atlas_size: u32,
+    // 光栅化时使用的字号（像素/em）
+    pub font_size: f32,
+    pub sdf_range: u32,
+    pub ascender: f32,
+    pub descender: f32,
+    pub line_gap: f32,
+}
+
+impl SdfAtlas {
+    pub fn create_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let size = wgpu::Extent3d {
+            width: self.atlas_size,
+            height: self.atlas_size,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("SDF Atlas Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &self.texture_data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(self.atlas_size),
+                rows_per_image: Some(self.atlas_size),
+            },
+            size,
+        );
+        texture
+    }
+}
+
+#[derive(Debug)]
+pub enum SdfAtlasError {
+    Parse(ttf_parser::FaceParsingError),
+    // 图集过小，连最小字号都放不下
+    AtlasTooSmall,
+}
+
+impl fmt::Display for SdfAtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdfAtlasError::Parse(error) => write!(f, "failed to parse font: {error}"),
+            SdfAtlasError::AtlasTooSmall => write!(f, "glyph set does not fit into the atlas"),
+        }
+    }
+}
+
+impl std::error::Error for SdfAtlasError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SdfAtlasError::Parse(error) => Some(error),
+            SdfAtlasError::AtlasTooSmall => None,
+        }
+    }
+}
+
+struct PlacedGlyph {
+    character: char,
+    glyph_id: GlyphId,
+    bbox: Option<ttf_parser::Rect>,
+    advance: f32,
+    // 字形块左上角在图集中的位置与尺寸，无轮廓的字形（空格）尺寸为 0
+    origin: [u32; 2],
+    size: [u32; 2],
+}
+
+pub struct SdfAtlasBuilder;
+
+impl SdfAtlasBuilder {
+    // 字号根据图集大小自动选择：从估计值开始缩小，直到所有字形都能装下
+    pub fn from_ttf(font_bytes: &[u8], glyph_set: &str, atlas_size: u32, sdf_range: u32) -> Result<SdfAtlas, SdfAtlasError> {
+        let face = Face::parse(font_bytes, 0).map_err(SdfAtlasError::Parse)?;
+        let units_per_em = face.units_per_em() as f32;
+
+        let mut characters = glyph_set.chars().collect::<Vec<_>>();
+        characters.sort_unstable();
+        characters.dedup();
+        let glyphs = characters
+            .into_iter()
+            .filter_map(|character| face.glyph_index(character).map(|glyph_id| (character, glyph_id)))
+            .collect::<Vec<_>>();
+
+        // 多数字形比 1 em 窄，按两倍的 em 网格估计起点
+        let columns = (glyphs.len().max(1) as f32).sqrt().ceil();
+        let mut font_size = 2.0 * atlas_size as f32 / columns;
+
+        let placed = loop {
+            if font_size < 1.0 {
+                return Err(SdfAtlasError::AtlasTooSmall);
+            }
+            if let Some(placed) = pack(&face, &glyphs, font_size / units_per_em, atlas_size, sdf_range) {
+                break placed;
+            }
+            font_size *= 0.9;
+        };
+
+        let scale = font_size / units_per_em;
+        let mut texture_data = vec![0u8; (atlas_size * atlas_size) as usize];
+        let mut glyph_metrics = HashMap::with_capacity(placed.len());
+
+        for glyph in &placed {
+            let bearing = match glyph.bbox {
+                Some(bbox) => [
+                    bbox.x_min as f32 * scale - sdf_range as f32,
+                    bbox.y_max as f32 * scale + sdf_range as f32,
+                ],
+                None => [0.0, 0.0],
+            };
+
+            if let Some(bbox) = glyph.bbox {
+                let field = rasterize_glyph(&face, glyph.glyph_id, bbox, scale, glyph.size, sdf_range);
+                for y in 0..glyph.size[1] {
+                    let src = (y * glyph.size[0]) as usize;
+                    let dst = ((glyph.origin[1] + y) * atlas_size + glyph.origin[0]) as usize;
+                    texture_data[dst..dst + glyph.size[0] as usize]
+                        .copy_from_slice(&field[src..src + glyph.size[0] as usize]);
+                }
+            }
+
+            let atlas = atlas_size as f32;
+            glyph_metrics.insert(glyph.character, GlyphMetrics {
+                uv_min: [glyph.origin[0] as f32 / atlas, glyph.origin[1] as f32 / atlas],
+                uv_max: [
+                    (glyph.origin[0] + glyph.size[0]) as f32 / atlas,
+                    (glyph.origin[1] + glyph.size[1]) as f32 / atlas,
+                ],
+                size: [glyph.size[0] as f32, glyph.size[1] as f32],
+                bearing,
+                advance: glyph.advance,
+            });
+        }
+
+        Ok(SdfAtlas {
+            texture_data,
+            glyph_metrics,
+            atlas_size,
+            font_size,
+            sdf_range,
+            ascender: face.ascender() as f32 * scale,
+            descender: face.descender() as f32 * scale,
+            line_gap: face.line_gap() as f32 * scale,
+        })
+    }
+}
+
+// 按行（shelf）装箱，装不下时返回 None
+fn pack(face: &Face, glyphs: &[(char, GlyphId)], scale: f32, atlas_size: u32, sdf_range: u32) -> Option<Vec<PlacedGlyph>> {
+    let mut placed = Vec::with_capacity(glyphs.len());
+    let (mut x, mut y, mut shelf_height) = (0u32, 0u32, 0u32);
+
+    for &(character, glyph_id) in glyphs {
+        let advance = face.glyph_hor_advance(glyph_id).unwrap_or(0) as f32 * scale;
+        let bbox = face.glyph_bounding_box(glyph_id);
+        let size = match bbox {
+            Some(bbox) => [
+                ((bbox.x_max - bbox.x_min) as f32 * scale).ceil() as u32 + 2 * sdf_range,
+                ((bbox.y_max - bbox.y_min) as f32 * scale).ceil() as u32 + 2 * sdf_range,
+            ],
+            None => [0, 0],
+        };
+
+        if x + size[0] > atlas_size {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        if x + size[0] > atlas_size || y + size[1] > atlas_size {
+            return None;
+        }
+
+        placed.push(PlacedGlyph { character, glyph_id, bbox, advance, origin: [x, y], size });
+        x += size[0];
+        shelf_height = shelf_height.max(size[1]);
+    }
+
+    Some(placed)
+}
+
+// 把轮廓展平为超采样像素空间中的线段，y 轴向下
+struct EdgeCollector {
+    edges: Vec<[f32; 4]>,
+    start: [f32; 2],
+    current: [f32; 2],
+    origin: [f32; 2],
+    scale: f32,
+}
+
+impl EdgeCollector {
+    fn to_pixel(&self, x: f32, y: f32) -> [f32; 2] {
+        [(x * self.scale - self.origin[0]), (self.origin[1] - y * self.scale)]
+    }
+
+    fn push_line(&mut self, to: [f32; 2]) {
+        self.edges.push([self.current[0], self.current[1], to[0], to[1]]);
+        self.current = to;
+    }
+}
+
+impl OutlineBuilder for EdgeCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.to_pixel(x, y);
+        self.current = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.to_pixel(x, y);
+        self.push_line(to);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p0 = self.current;
+        let p1 = self.to_pixel(x1, y1);
+        let p2 = self.to_pixel(x, y);
+        for i in 1..=CURVE_SEGMENTS {
+            let t = i as f32 / CURVE_SEGMENTS as f32;
+            let u = 1.0 - t;
+            let point = [
+                u * u * p0[0] + 2.0 * u * t * p1[0] + t * t * p2[0],
+                u * u * p0[1] + 2.0 * u * t * p1[1] + t * t * p2[1],
+            ];
+            self.push_line(point);
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p0 = self.current;
+        let p1 = self.to_pixel(x1, y1);
+        let p2 = self.to_pixel(x2, y2);
+        let p3 = self.to_pixel(x, y);
+        for i in 1..=CURVE_SEGMENTS {
+            let t = i as f32 / CURVE_SEGMENTS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            let point = [
+                a * p0[0] + b * p1[0] + c * p2[0] + d * p3[0],
+                a * p0[1] + b * p1[1] + c * p2[1] + d * p3[1],
+            ];
+            self.push_line(point);
+        }
+    }
+
+    fn close(&mut self) {
+        if self.current != self.start {
+            self.push_line(self.start);
+        }
+    }
+}
+
+// 先以 8 倍分辨率光栅化覆盖掩码并计算距离变换，再按块取平均下采样
+fn rasterize_glyph(face: &Face, glyph_id: GlyphId, bbox: ttf_parser::Rect, scale: f32, size: [u32; 2], sdf_range: u32) -> Vec<u8> {
+    let hi_scale = scale * SUPERSAMPLE as f32;
+    let padding = (sdf_range * SUPERSAMPLE) as f32;
+    let mut collector = EdgeCollector {
+        edges: Vec::new(),
+        start: [0.0; 2],
+        current: [0.0; 2],
+        origin: [bbox.x_min as f32 * hi_scale - padding, bbox.y_max as f32 * hi_scale + padding],
+        scale: hi_scale,
+    };
+    face.outline_glyph(glyph_id, &mut collector);
+
+    let width = (size[0] * SUPERSAMPLE) as usize;
+    let height = (size[1] * SUPERSAMPLE) as usize;
+    let inside = coverage_mask(&collector.edges, width, height);
+
+    // 外部像素到最近内部像素的距离，以及内部像素到最近外部像素的距离
+    let mut to_inside = inside.iter().map(|&is_inside| if is_inside { 0.0 } else { f32::INFINITY }).collect::<Vec<_>>();
+    let mut to_outside = inside.iter().map(|&is_inside| if is_inside { f32::INFINITY } else { 0.0 }).collect::<Vec<_>>();
+    distance_transform(&mut to_inside, width, height);
+    distance_transform(&mut to_outside, width, height);
+
+    let block = SUPERSAMPLE as usize;
+    let samples = (block * block) as f32;
+    let mut field = Vec::with_capacity((size[0] * size[1]) as usize);
+    for y in 0..size[1] as usize {
+        for x in 0..size[0] as usize {
+            let mut sum = 0.0;
+            for sy in y * block..(y + 1) * block {
+                for sx in x * block..(x + 1) * block {
+                    let index = sy * width + sx;
+                    // 内部为正，单位为超采样像素
+                    sum += to_outside[index].sqrt() - to_inside[index].sqrt();
+                }
+            }
+            let distance = sum / samples / SUPERSAMPLE as f32;
+            let value = 0.5 + 0.5 * distance / sdf_range as f32;
+            field.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    field
+}
+
+// 扫描线 + 非零环绕规则
+fn coverage_mask(edges: &[[f32; 4]], width: usize, height: usize) -> Vec<bool> {
+    let mut mask = vec![false; width * height];
+    let mut crossings = Vec::new();
+
+    for row in 0..height {
+        let y = row as f32 + 0.5;
+        crossings.clear();
+        for &[x0, y0, x1, y1] in edges {
+            if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                let x = x0 + (y - y0) * (x1 - x0) / (y1 - y0);
+                crossings.push((x, if y1 > y0 { 1 } else { -1 }));
+            }
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].1;
+            if winding == 0 {
+                continue;
+            }
+            let start = (pair[0].0 - 0.5).ceil().max(0.0) as usize;
+            let end = ((pair[1].0 - 0.5).ceil().max(0.0) as usize).min(width);
+            for pixel in &mut mask[row * width + start.min(end)..row * width + end] {
+                *pixel = true;
+            }
+        }
+    }
+    mask
+}
+
+// Felzenszwalb 平方欧氏距离变换，先按列再按行
+fn distance_transform(grid: &mut [f32], width: usize, height: usize) {
+    let length = width.max(height);
+    let mut f = vec![0.0; length];
+    let mut d = vec![0.0; length];
+    let mut v = vec![0usize; length];
+    let mut z = vec![0.0; length + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        distance_transform_1d(&f[..height], &mut d[..height], &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+    for y in 0..height {
+        f[..width].copy_from_slice(&grid[y * width..(y + 1) * width]);
+        distance_transform_1d(&f[..width], &mut d[..width], &mut v, &mut z);
+        grid[y * width..(y + 1) * width].copy_from_slice(&d[..width]);
+    }
+}
+
+fn distance_transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    // 全部为无穷时抛物线下包络无意义
+    let Some(first) = f.iter().position(|value| value.is_finite()) else {
+        d.fill(f32::INFINITY);
+        return;
+    };
+
+    let mut k = 0;
+    v[0] = first;
+    z[0] = f32::NEG_INFINITY;
+    z[1] = f32::INFINITY;
+
+    for q in first + 1..f.len() {
+        if !f[q].is_finite() {
+            continue;
+        }
+        loop {
+            let p = v[k];
+            let s = ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * (q - p) as f32);
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                k += 1;
+                v[k] = q;
+                z[k] = s;
+                z[k + 1] = f32::INFINITY;
+                break;
+            }
+        }
+    }
+
+    k = 0;
+    for (q, distance) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let offset = q as f32 - v[k] as f32;
+        *distance = offset * offset + f[v[k]];
+    }
+}
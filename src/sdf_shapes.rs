@@ -0,0 +1,244 @@
+use crate::post_process::PostProcessEffect;
+
+// 单次渲染的图形数上限，对应存储缓冲区的容量
+pub const MAX_SHAPES: usize = 256;
+
+const SHAPE_CIRCLE: u32 = 0;
+const SHAPE_RECT: u32 = 1;
+const SHAPE_ROUNDED_RECT: u32 = 2;
+const SHAPE_BEZIER: u32 = 3;
+
+// 二维矢量图形，坐标为像素，左上角为原点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SdfShape {
+    Circle { center: [f32; 2], radius: f32 },
+    Rect { center: [f32; 2], half_size: [f32; 2] },
+    RoundedRect { center: [f32; 2], half_size: [f32; 2], radius: f32 },
+    // 二次贝塞尔曲线与弦 p0-p2 围成的填充区域
+    Bezier { p0: [f32; 2], p1: [f32; 2], p2: [f32; 2] },
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuShape {
+    color: [f32; 4],
+    a: [f32; 4],
+    b: [f32; 4],
+    kind: u32,
+    _padding: [u32; 3],
+}
+
+impl GpuShape {
+    fn new(shape: &SdfShape, color: [f32; 4]) -> Self {
+        let (kind, a, b) = match *shape {
+            SdfShape::Circle { center, radius } => (SHAPE_CIRCLE, [center[0], center[1], radius, 0.0], [0.0; 4]),
+            SdfShape::Rect { center, half_size } => {
+                (SHAPE_RECT, [center[0], center[1], half_size[0], half_size[1]], [0.0; 4])
+            }
+            SdfShape::RoundedRect { center, half_size, radius } => {
+                (SHAPE_ROUNDED_RECT, [center[0], center[1], half_size[0], half_size[1]], [radius, 0.0, 0.0, 0.0])
+            }
+            SdfShape::Bezier { p0, p1, p2 } => (SHAPE_BEZIER, [p0[0], p0[1], p1[0], p1[1]], [p2[0], p2[1], 0.0, 0.0]),
+        };
+        Self { color, a, b, kind, _padding: [0; 3] }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SdfParams {
+    shape_count: u32,
+    smoothness: f32,
+    _padding: [u32; 2],
+}
+
+// 用有符号距离场绘制二维矢量图形，作为后处理效果叠加在三维场景之上
+// 每个像素对所有图形取平滑最小距离，重叠的图形像融化一样连在一起
+pub struct SdfRenderer {
+    // smooth-min 的混合半径（像素），为 0 时图形之间是硬边
+    pub smoothness: f32,
+    shapes: Vec<(SdfShape, [f32; 4])>,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    shape_buffer: wgpu::Buffer,
+}
+
+impl SdfRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("SDF Shapes Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("sdf_shapes.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sdf_shapes_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("SDF Shapes Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("SDF Shapes Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            multiview: None,
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("SDF Shapes Params Buffer"),
+            size: std::mem::size_of::<SdfParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let shape_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("SDF Shapes Buffer"),
+            size: (MAX_SHAPES * std::mem::size_of::<GpuShape>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            smoothness: 0.0,
+            shapes: Vec::new(),
+            pipeline,
+            bind_group_layout,
+            params_buffer,
+            shape_buffer,
+        }
+    }
+
+    // 超过 MAX_SHAPES 的图形会被忽略
+    pub fn add(&mut self, shape: SdfShape, color: [f32; 4]) {
+        if self.shapes.len() < MAX_SHAPES {
+            self.shapes.push((shape, color));
+        } else {
+            tracing::warn!("sdf shapes: renderer is full, dropping {shape:?}");
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = &(SdfShape, [f32; 4])> {
+        self.shapes.iter()
+    }
+}
+
+impl PostProcessEffect for SdfRenderer {
+    fn label(&self) -> &str {
+        "SDF Shapes"
+    }
+
+    fn uses_compute(&self) -> bool {
+        true
+    }
+
+    fn apply(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let params = SdfParams {
+            shape_count: self.shapes.len() as u32,
+            smoothness: self.smoothness,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        if !self.shapes.is_empty() {
+            let shapes = self.shapes.iter().map(|(shape, color)| GpuShape::new(shape, *color)).collect::<Vec<_>>();
+            queue.write_buffer(&self.shape_buffer, 0, bytemuck::cast_slice(&shapes));
+        }
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sdf_shapes_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.shape_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("SDF Shapes Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
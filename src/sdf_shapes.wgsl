@@ -0,0 +1,165 @@
+const SHAPE_CIRCLE: u32 = 0u;
+const SHAPE_RECT: u32 = 1u;
+const SHAPE_ROUNDED_RECT: u32 = 2u;
+const SHAPE_BEZIER: u32 = 3u;
+
+struct SdfParams {
+    shape_count: u32,
+    // smooth-min 的混合半径（像素），为 0 时退化为普通的 min
+    smoothness: f32,
+};
+
+// 坐标均为像素，左上角为原点
+struct Shape {
+    color: vec4f,
+    // 圆：中心、半径；矩形：中心、半尺寸；贝塞尔：p0、p1
+    a: vec4f,
+    // 圆角矩形：圆角半径；贝塞尔：p2
+    b: vec4f,
+    kind: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: SdfParams;
+@group(0) @binding(1)
+var<storage, read> shapes: array<Shape>;
+@group(0) @binding(2)
+var t_scene: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+};
+
+// 用一个覆盖全屏的大三角形代替四边形
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    return out;
+}
+
+fn sd_circle(p: vec2f, center: vec2f, radius: f32) -> f32 {
+    return length(p - center) - radius;
+}
+
+fn sd_rounded_rect(p: vec2f, center: vec2f, half_size: vec2f, radius: f32) -> f32 {
+    let r = min(radius, min(half_size.x, half_size.y));
+    let q = abs(p - center) - half_size + r;
+    return length(max(q, vec2f(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+fn cross2(a: vec2f, b: vec2f) -> f32 {
+    return a.x * b.y - a.y * b.x;
+}
+
+fn sd_segment(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / max(dot(ba, ba), 1e-8), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+// 到二次贝塞尔曲线的距离：求解最近点满足的三次方程
+fn distance_to_bezier(p: vec2f, p0: vec2f, p1: vec2f, p2: vec2f) -> f32 {
+    let a = p1 - p0;
+    let b = p0 - 2.0 * p1 + p2;
+    if dot(b, b) < 1e-6 {
+        return sd_segment(p, p0, p2);
+    }
+    let c = a * 2.0;
+    let d = p0 - p;
+    let kk = 1.0 / dot(b, b);
+    let kx = kk * dot(a, b);
+    let ky = kk * (2.0 * dot(a, a) + dot(d, b)) / 3.0;
+    let kz = kk * dot(d, a);
+    let pp = ky - kx * kx;
+    let q = kx * (2.0 * kx * kx - 3.0 * ky) + kz;
+    let h = q * q + 4.0 * pp * pp * pp;
+
+    if h >= 0.0 {
+        // 一个实根
+        let root = sqrt(h);
+        let x = (vec2f(root, -root) - q) / 2.0;
+        let uv = sign(x) * pow(abs(x), vec2f(1.0 / 3.0));
+        let t = clamp(uv.x + uv.y - kx, 0.0, 1.0);
+        let offset = d + (c + b * t) * t;
+        return length(offset);
+    }
+
+    // 三个实根，取其中较近的两个
+    let z = sqrt(-pp);
+    let v = acos(q / (pp * z * 2.0)) / 3.0;
+    let m = cos(v);
+    let n = sin(v) * 1.732050808;
+    let t = clamp(vec3f(m + m, -n - m, n - m) * z - kx, vec3f(0.0), vec3f(1.0));
+    let offset_x = d + (c + b * t.x) * t.x;
+    let offset_y = d + (c + b * t.y) * t.y;
+    return sqrt(min(dot(offset_x, offset_x), dot(offset_y, offset_y)));
+}
+
+// 曲线与弦 p0-p2 围成的区域。Loop-Blinn 隐式形式：在 (p0, p1, p2) → ((0,0), (½,0), (1,1)) 的
+// 重心映射下，u² - v < 0 且与 p1 位于弦同侧的点在区域内
+fn sd_bezier_fill(p: vec2f, p0: vec2f, p1: vec2f, p2: vec2f) -> f32 {
+    let distance = min(distance_to_bezier(p, p0, p1, p2), sd_segment(p, p0, p2));
+
+    let area = cross2(p1 - p0, p2 - p0);
+    if abs(area) < 1e-6 {
+        return distance;
+    }
+    let w1 = cross2(p - p0, p2 - p0) / area;
+    let w2 = cross2(p1 - p0, p - p0) / area;
+    let u = 0.5 * w1 + w2;
+    let v = w2;
+    let same_side = sign(cross2(p2 - p0, p - p0)) == sign(cross2(p2 - p0, p1 - p0));
+    let inside = same_side && u * u - v < 0.0;
+    return select(distance, -distance, inside);
+}
+
+fn shape_distance(shape: Shape, p: vec2f) -> f32 {
+    switch shape.kind {
+        case SHAPE_CIRCLE: {
+            return sd_circle(p, shape.a.xy, shape.a.z);
+        }
+        case SHAPE_RECT: {
+            return sd_rounded_rect(p, shape.a.xy, shape.a.zw, 0.0);
+        }
+        case SHAPE_ROUNDED_RECT: {
+            return sd_rounded_rect(p, shape.a.xy, shape.a.zw, shape.b.x);
+        }
+        default: {
+            return sd_bezier_fill(p, shape.a.xy, shape.a.zw, shape.b.xy);
+        }
+    }
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let pixel = vec2<i32>(in.clip_position.xy);
+    let scene = textureLoad(t_scene, pixel, 0);
+    if params.shape_count == 0u {
+        return scene;
+    }
+
+    // 在像素中心取所有图形的（平滑）最小距离，颜色按同样的权重混合
+    let p = in.clip_position.xy;
+    var distance = 1e10;
+    var color = vec4f(0.0);
+    for (var i = 0u; i < params.shape_count; i++) {
+        let shape = shapes[i];
+        let d = shape_distance(shape, p);
+        if i == 0u {
+            distance = d;
+            color = shape.color;
+            continue;
+        }
+        let k = max(params.smoothness, 1e-4);
+        let h = clamp(0.5 + 0.5 * (distance - d) / k, 0.0, 1.0);
+        distance = mix(distance, d, h) - k * h * (1.0 - h);
+        color = mix(color, shape.color, h);
+    }
+
+    // 边缘一个像素宽的抗锯齿
+    let coverage = clamp(0.5 - distance, 0.0, 1.0) * color.a;
+    return vec4f(mix(scene.rgb, color.rgb, coverage), scene.a);
+}
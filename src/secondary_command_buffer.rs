@@ -0,0 +1,91 @@
+use std::ops::{ Deref, DerefMut };
+use std::sync::mpsc;
+
+// 渲染包要与执行它的渲染通道的附件格式一致
+#[derive(Debug, Clone)]
+pub struct BundleTarget {
+    pub color_formats: Vec<Option<wgpu::TextureFormat>>,
+    pub depth_stencil: Option<wgpu::RenderBundleDepthStencil>,
+    pub sample_count: u32,
+}
+
+impl BundleTarget {
+    pub fn new(color_formats: &[wgpu::TextureFormat], depth_format: Option<wgpu::TextureFormat>) -> Self {
+        Self {
+            color_formats: color_formats.iter().copied().map(Some).collect(),
+            depth_stencil: depth_format.map(|format| wgpu::RenderBundleDepthStencil {
+                format,
+                depth_read_only: false,
+                stencil_read_only: true,
+            }),
+            sample_count: 1,
+        }
+    }
+}
+
+// 独立录制一部分绘制调用的二级命令缓冲区，结束后得到可在渲染通道中执行的渲染包
+// RenderBundleEncoder 不能跨线程传递，需要在录制它的线程上创建
+pub struct SecondaryCommandBuffer<'a> {
+    encoder: wgpu::RenderBundleEncoder<'a>,
+    label: Option<String>,
+}
+
+impl<'a> SecondaryCommandBuffer<'a> {
+    pub fn new(device: &'a wgpu::Device, target: &BundleTarget, label: Option<&str>) -> Self {
+        let encoder = device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+            label,
+            color_formats: &target.color_formats,
+            depth_stencil: target.depth_stencil,
+            sample_count: target.sample_count,
+            multiview: None,
+        });
+        Self { encoder, label: label.map(str::to_string) }
+    }
+
+    pub fn finish(self) -> wgpu::RenderBundle {
+        self.encoder.finish(&wgpu::RenderBundleDescriptor {
+            label: self.label.as_deref(),
+        })
+    }
+}
+
+impl<'a> Deref for SecondaryCommandBuffer<'a> {
+    type Target = wgpu::RenderBundleEncoder<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.encoder
+    }
+}
+
+impl DerefMut for SecondaryCommandBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.encoder
+    }
+}
+
+// 每个分区一个线程，录制完成的渲染包经通道送回调用线程，按分区顺序返回
+pub fn record_parallel<'a, P: Sync>(
+    device: &'a wgpu::Device,
+    target: &BundleTarget,
+    partitions: &'a [P],
+    record: impl Fn(&mut SecondaryCommandBuffer<'a>, &'a P) + Sync,
+) -> Vec<wgpu::RenderBundle> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for (index, partition) in partitions.iter().enumerate() {
+            let sender = sender.clone();
+            let record = &record;
+            scope.spawn(move || {
+                let mut buffer = SecondaryCommandBuffer::new(device, target, Some("Secondary Command Buffer"));
+                record(&mut buffer, partition);
+                // 接收端在作用域结束前一直存在
+                let _ = sender.send((index, buffer.finish()));
+            });
+        }
+    });
+    drop(sender);
+
+    let mut bundles = receiver.into_iter().collect::<Vec<_>>();
+    bundles.sort_by_key(|(index, _)| *index);
+    bundles.into_iter().map(|(_, bundle)| bundle).collect()
+}
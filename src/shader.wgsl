@@ -1,18 +1,500 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    // xyz 为平面法线，w 为距离；全零时不裁剪
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct MaterialUniform {
+    base_color: vec4f,
+    emissive_color: vec3f,
+    emissive_intensity: f32,
+    // 单位为尼特（cd/m²）
+    emissive_intensity_nits: f32,
+    roughness: f32,
+    metallic: f32,
+    ao: f32,
+    scatter_color: vec3f,
+    scatter_width: f32,
+    subsurface_weight: f32,
+    uv_rotation: f32,
+    uv_scale: vec2f,
+    uv_offset: vec2f,
+    uv_scroll: vec2f,
+    triplanar_scale: f32,
+    triplanar_sharpness: f32,
+};
+@group(1) @binding(0)
+var<uniform> material: MaterialUniform;
+// 光照贴图按 uv1 采样，乘在漫反射上；材质没有光照贴图时绑定白色纹理。
+// 1~4 号绑定留给延迟光照通道的 G-buffer
+@group(1) @binding(5)
+var lightmap_texture: texture_2d<f32>;
+@group(1) @binding(6)
+var lightmap_sampler: sampler;
+
+// 离物体最近的光照探针，作为环境光（IBL）来源
+@group(2) @binding(0)
+var probe_cubemap: texture_cube<f32>;
+@group(2) @binding(1)
+var probe_sampler: sampler;
+
+const MAX_POINT_LIGHTS: u32 = 16u;
+const MAX_SPOT_LIGHTS: u32 = 8u;
+
+struct PointLight {
+    position: vec3f,
+    radius: f32,
+    color: vec3f,
+    intensity: f32,
+};
+
+struct SpotLight {
+    position: vec3f,
+    radius: f32,
+    color: vec3f,
+    intensity: f32,
+    direction: vec3f,
+    // 半角，单位为弧度
+    inner_angle: f32,
+    outer_angle: f32,
+    // 阴影图集中的图块序号，-1 表示没有阴影
+    shadow_tile: i32,
+};
+
+struct LightingUniform {
+    point_lights: array<PointLight, MAX_POINT_LIGHTS>,
+    spot_lights: array<SpotLight, MAX_SPOT_LIGHTS>,
+    num_point_lights: u32,
+    num_spot_lights: u32,
+};
+@group(3) @binding(0)
+var<uniform> lighting: LightingUniform;
+
+const MAX_SHADOW_TILES: u32 = 16u;
+
+struct ShadowTile {
+    view_proj: mat4x4f,
+    offset: vec2f,
+    scale: vec2f,
+};
+
+struct ShadowAtlasUniform {
+    tiles: array<ShadowTile, MAX_SHADOW_TILES>,
+    texel_size: f32,
+};
+
+// 所有光源的阴影贴图装在一张图集里，按图块的 offset 与 scale 换算纹理坐标
+@group(3) @binding(1)
+var shadow_atlas: texture_depth_2d;
+@group(3) @binding(2)
+var shadow_sampler: sampler_comparison;
+@group(3) @binding(3)
+var<uniform> shadow_tiles: ShadowAtlasUniform;
+// 屏幕空间接触阴影，只在延迟光照通道中读取，见 ContactShadowPass
+@group(3) @binding(4)
+var contact_shadow: texture_2d<f32>;
+
+// 与 filtered_shadow.rs 中的 ShadowMode 一致
+const SHADOW_MODE_PCF: u32 = 0u;
+const SHADOW_MODE_VARIANCE: u32 = 1u;
+const SHADOW_MODE_EXPONENTIAL: u32 = 2u;
+const SHADOW_MODE_MOMENT: u32 = 3u;
+
+struct FilteredShadowUniform {
+    view_proj: mat4x4f,
+    light_index: i32,
+    mode: u32,
+    min_variance: f32,
+    light_bleed_reduction: f32,
+    esm_c: f32,
+    far: f32,
+    moment_bias: f32,
+};
+
+// 可预先模糊的阴影图，d 为光源观察空间的线性深度除以远平面；见 FilteredShadowMap
+// 方差阴影的两个矩 (d, d²)
+@group(3) @binding(5)
+var shadow_moments: texture_2d<f32>;
+@group(3) @binding(6)
+var<uniform> filtered_shadow: FilteredShadowUniform;
+// 指数阴影的 exp(c · d)
+@group(3) @binding(7)
+var shadow_exponential: texture_2d<f32>;
+// 矩阴影的 (z, z², z³, z⁴)，z = 2d - 1
+@group(3) @binding(8)
+var shadow_four_moments: texture_2d<f32>;
+
+#include "moments"
+
+// 光传播体积的绑定、lpv_indirect 与反射阴影图通道 fs_rsm；未启用 gi-lpv 特性时 lpv_indirect 恒为 0
+#include "lpv_lighting"
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) tex_coords: vec2f,
+    @location(3) uv1: vec2f,
+};
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4f,
+    @location(6) model_matrix_1: vec4f,
+    @location(7) model_matrix_2: vec4f,
+    @location(8) model_matrix_3: vec4f,
+};
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4f,
+    // 观察空间深度，透明通道计算权重时使用
+    @location(0) view_depth: f32,
+    @location(1) world_normal: vec3f,
+    @location(2) world_position: vec3f,
+    // 经过材质的 UV 变换
+    @location(3) tex_coords: vec2f,
+    // 光照贴图坐标，不做 UV 变换
+    @location(4) uv1: vec2f,
 };
 
+// 绕 (0.5, 0.5) 旋转后缩放、平移
+fn transform_uv(uv: vec2f) -> vec2f {
+    let c = cos(material.uv_rotation);
+    let s = sin(material.uv_rotation);
+    let centered = uv - 0.5;
+    let rotated = vec2f(c * centered.x - s * centered.y, s * centered.x + c * centered.y);
+    return (rotated + 0.5) * material.uv_scale + material.uv_offset;
+}
+
 @vertex
-fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    let world_position = model_matrix * vec4f(model.position, 1.0);
+
     var out: VertexOutput;
-    let x = f32(1 - i32(in_vertex_index)) * 0.5;
-    let y = f32(i32(in_vertex_index & 1u) * 2 - 1) * 0.5;
-    out.clip_position = vec4f(x, y, 0.0, 1.0);
+    out.clip_position = camera.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    out.view_depth = out.clip_position.w;
+    out.world_normal = (model_matrix * vec4f(model.normal, 0.0)).xyz;
+    out.tex_coords = transform_uv(model.tex_coords);
+    out.uv1 = model.uv1;
     return out;
 }
 
+// ACES 色调映射以 80 cd/m² 为场景亮度 1.0
+const REFERENCE_WHITE_NITS: f32 = 80.0;
+
+fn emissive_radiance() -> vec3f {
+    return material.emissive_color * material.emissive_intensity_nits / REFERENCE_WHITE_NITS;
+}
+
+// naga 还不支持 @builtin(clip_distances)，在片元着色器里丢弃平面下方的片元
+fn clip(world_position: vec3f) {
+    if dot(world_position, camera.clip_plane.xyz) + camera.clip_plane.w < 0.0 {
+        discard;
+    }
+}
+
+// 距离平方衰减，并在光源半径处平滑降到 0
+fn distance_attenuation(distance: f32, radius: f32) -> f32 {
+    let window = pow(saturate(1.0 - pow(distance / radius, 4.0)), 2.0);
+    return window / (distance * distance + 1.0);
+}
+
+// 与 lighting.rs 中的 SPOT_SHADOW_NEAR 一致
+const SPOT_SHADOW_NEAR: f32 = 0.05;
+
+// xy 为图集纹理坐标，z 为光源 NDC 深度；在光源视锥之外时 z 为 -1
+fn shadow_atlas_coords(tile_index: i32, world_position: vec3f) -> vec3f {
+    if tile_index < 0 || u32(tile_index) >= MAX_SHADOW_TILES {
+        return vec3f(0.0, 0.0, -1.0);
+    }
+    let tile = shadow_tiles.tiles[tile_index];
+    let clip = tile.view_proj * vec4f(world_position, 1.0);
+    if clip.w <= 0.0 {
+        return vec3f(0.0, 0.0, -1.0);
+    }
+    let ndc = clip.xyz / clip.w;
+    if any(abs(ndc.xy) > vec2f(1.0)) || ndc.z > 1.0 {
+        return vec3f(0.0, 0.0, -1.0);
+    }
+    // 收缩半个纹素，双线性比较不会读到相邻图块
+    let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5) * tile.scale + tile.offset;
+    let half_texel = vec2f(shadow_tiles.texel_size * 0.5);
+    return vec3f(clamp(uv, tile.offset + half_texel, tile.offset + tile.scale - half_texel), ndc.z);
+}
+
+// 1 为完全受光；超出光源视锥的位置视为受光
+fn shadow_factor(tile_index: i32, world_position: vec3f) -> f32 {
+    let coords = shadow_atlas_coords(tile_index, world_position);
+    if coords.z < 0.0 {
+        return 1.0;
+    }
+    return textureSampleCompareLevel(shadow_atlas, shadow_sampler, coords.xy, coords.z);
+}
+
+// 光线从挡光表面到片元在物体内穿过的距离，世界单位；在光源视锥之外时返回 -1。
+// GL 后端不能对深度纹理 textureLoad，这里用比较采样二分查找挡光表面的深度，再按聚光灯的投影换算为线性距离
+fn transmission_distance(tile_index: i32, world_position: vec3f, far: f32) -> f32 {
+    let coords = shadow_atlas_coords(tile_index, world_position);
+    if coords.z < 0.0 {
+        return -1.0;
+    }
+    var near_depth = 0.0;
+    var far_depth = coords.z;
+    for (var i = 0u; i < 10u; i++) {
+        let middle = (near_depth + far_depth) * 0.5;
+        if textureSampleCompareLevel(shadow_atlas, shadow_sampler, coords.xy, middle) > 0.5 {
+            near_depth = middle;
+        } else {
+            far_depth = middle;
+        }
+    }
+    let linear = SPOT_SHADOW_NEAR * far / (far - vec2f(coords.z, near_depth) * (far - SPOT_SHADOW_NEAR));
+    return max(linear.x - linear.y, 0.0);
+}
+
+// 按当前模式每个片元读一次预先模糊的阴影图；超出光源视锥的位置视为受光
+fn filtered_shadow_factor(world_position: vec3f) -> f32 {
+    let clip = filtered_shadow.view_proj * vec4f(world_position, 1.0);
+    if clip.w <= 0.0 {
+        return 1.0;
+    }
+    let ndc = clip.xy / clip.w;
+    if any(abs(ndc) > vec2f(1.0)) {
+        return 1.0;
+    }
+    let size = textureDimensions(shadow_moments);
+    let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    let texel = min(vec2u(uv * vec2f(size)), size - 1u);
+    let depth = clip.w / filtered_shadow.far;
+    if filtered_shadow.mode == SHADOW_MODE_EXPONENTIAL {
+        // exp(c · (d_occluder - d_receiver))，先乘小的指数避免溢出
+        let occluder = textureLoad(shadow_exponential, texel, 0).r;
+        return saturate(occluder * exp(-filtered_shadow.esm_c * depth));
+    }
+    if filtered_shadow.mode == SHADOW_MODE_MOMENT {
+        let moments = textureLoad(shadow_four_moments, texel, 0);
+        return 1.0 - compute_shadow_probability(moments, depth * 2.0 - 1.0, filtered_shadow.moment_bias);
+    }
+    // 切比雪夫上界 p_max = σ² / (σ² + (d - E[d])²)
+    let moments = textureLoad(shadow_moments, texel, 0).rg;
+    if depth <= moments.x {
+        return 1.0;
+    }
+    let variance = max(moments.y - moments.x * moments.x, filtered_shadow.min_variance);
+    let delta = depth - moments.x;
+    let p_max = variance / (variance + delta * delta);
+    // 把低于 light_bleed_reduction 的尾部截掉，减轻漏光
+    return saturate((p_max - filtered_shadow.light_bleed_reduction) / (1.0 - filtered_shadow.light_bleed_reduction));
+}
+
+// 从背面照入、穿过物体后透射出来的光，见 SSSMaterial；只有投射阴影的聚光灯能求得穿透距离
+fn translucency(world_position: vec3f, normal: vec3f) -> vec3f {
+    var total = vec3f(0.0);
+    for (var i = 0u; i < min(lighting.num_spot_lights, MAX_SPOT_LIGHTS); i++) {
+        let light = lighting.spot_lights[i];
+        let distance_through = transmission_distance(light.shadow_tile, world_position, light.radius);
+        if distance_through < 0.0 {
+            continue;
+        }
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        let light_dir = to_light / max(distance, 1e-4);
+        let cos_angle = dot(normalize(light.direction), -light_dir);
+        let cone = smoothstep(cos(light.outer_angle), cos(light.inner_angle), cos_angle);
+        let attenuation = light.intensity * distance_attenuation(distance, light.radius) * cone;
+        let transmittance = exp(-distance_through / max(material.scatter_width, 1e-4));
+        total += light.color * attenuation * transmittance * max(dot(-normal, light_dir), 0.0);
+    }
+    return total * material.scatter_color;
+}
+
+// contact 为接触阴影，与投射阴影的聚光灯的图集或可过滤阴影取 min；没有接触阴影时传 1
+fn direct_lighting(world_position: vec3f, normal: vec3f, contact: f32) -> vec3f {
+    var total = vec3f(0.0);
+    for (var i = 0u; i < min(lighting.num_point_lights, MAX_POINT_LIGHTS); i++) {
+        let light = lighting.point_lights[i];
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        let light_dir = to_light / max(distance, 1e-4);
+        let attenuation = light.intensity * distance_attenuation(distance, light.radius);
+        total += light.color * attenuation * max(dot(normal, light_dir), 0.0);
+    }
+    for (var i = 0u; i < min(lighting.num_spot_lights, MAX_SPOT_LIGHTS); i++) {
+        let light = lighting.spot_lights[i];
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        let light_dir = to_light / max(distance, 1e-4);
+        // 光锥方向与光源指向片元方向夹角的余弦
+        let cos_angle = dot(normalize(light.direction), -light_dir);
+        let cone = smoothstep(cos(light.outer_angle), cos(light.inner_angle), cos_angle);
+        let filtered = i32(i) == filtered_shadow.light_index && filtered_shadow.mode != SHADOW_MODE_PCF;
+        var shadow = 1.0;
+        if filtered {
+            shadow = filtered_shadow_factor(world_position);
+        } else {
+            shadow = shadow_factor(light.shadow_tile, world_position);
+        }
+        if light.shadow_tile >= 0 || filtered {
+            shadow = min(shadow, contact);
+        }
+        let attenuation = light.intensity * distance_attenuation(distance, light.radius) * cone * shadow;
+        total += light.color * attenuation * max(dot(normal, light_dir), 0.0);
+    }
+    return total;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
-    return vec4f(0.3, 0.2, 0.1, 1.0);
+    clip(in.world_position);
+    let normal = normalize(in.world_normal);
+    let ambient = textureSample(probe_cubemap, probe_sampler, normal).rgb;
+    var direct = direct_lighting(in.world_position, normal, 1.0);
+    if material.subsurface_weight > 0.0 {
+        direct = mix(direct, direct + translucency(in.world_position, normal), material.subsurface_weight);
+    }
+    let indirect = lpv_indirect(in.world_position, normal);
+    let lightmap = textureSample(lightmap_texture, lightmap_sampler, in.uv1).rgb;
+    let diffuse = material.base_color.rgb * (1.0 + ambient + direct + indirect) * lightmap;
+    let color = diffuse + material.base_color.rgb * material.emissive_intensity + emissive_radiance();
+    return vec4f(color, material.base_color.a);
+}
+
+struct GBufferOutput {
+    // rgb 为基础色，a 为 1 标记有表面
+    @location(0) albedo: vec4f,
+    // xyz 为世界空间法线，w 为粗糙度
+    @location(1) normal: vec4f,
+    // xyz 为世界空间位置，w 为金属度
+    @location(2) position: vec4f,
+    @location(3) ao: f32,
+};
+
+// 几何通道只写入表面属性，不计算光照，布局见 GBuffer
+@fragment
+fn fs_gbuffer(in: VertexOutput) -> GBufferOutput {
+    clip(in.world_position);
+    var out: GBufferOutput;
+    // G-buffer 没有单独的漫反射通道，光照贴图直接乘进基础色
+    let lightmap = textureSample(lightmap_texture, lightmap_sampler, in.uv1).rgb;
+    out.albedo = vec4f(material.base_color.rgb * lightmap, 1.0);
+    out.normal = vec4f(normalize(in.world_normal), material.roughness);
+    out.position = vec4f(in.world_position, material.metallic);
+    out.ao = material.ao;
+    return out;
+}
+
+// 延迟光照通道在第 1 组的 1~4 号绑定读取 G-buffer，与材质错开
+@group(1) @binding(1)
+var gbuffer_albedo: texture_2d<f32>;
+@group(1) @binding(2)
+var gbuffer_normal: texture_2d<f32>;
+@group(1) @binding(3)
+var gbuffer_position: texture_2d<f32>;
+@group(1) @binding(4)
+var gbuffer_ao: texture_2d<f32>;
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+// 镜面反射率 F0 在非金属取 0.04，金属取基础色；G-buffer 中没有观察方向，环境镜面项沿法线采样探针
+@fragment
+fn fs_deferred(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let texel = vec2<i32>(position.xy);
+    let albedo = textureLoad(gbuffer_albedo, texel, 0);
+    // 没有表面的像素保留清屏颜色
+    if albedo.a <= 0.0 {
+        discard;
+    }
+    let normal_roughness = textureLoad(gbuffer_normal, texel, 0);
+    let position_metallic = textureLoad(gbuffer_position, texel, 0);
+    let ao = textureLoad(gbuffer_ao, texel, 0).r;
+
+    let normal = normalize(normal_roughness.xyz);
+    let roughness = normal_roughness.w;
+    let metallic = position_metallic.w;
+    let ambient = textureSample(probe_cubemap, probe_sampler, normal).rgb * ao;
+    let contact = textureLoad(contact_shadow, texel, 0).r;
+    let direct = direct_lighting(position_metallic.xyz, normal, contact);
+    let indirect = lpv_indirect(position_metallic.xyz, normal);
+
+    let diffuse_color = albedo.rgb * (1.0 - metallic);
+    let specular_color = mix(vec3f(0.04), albedo.rgb, metallic);
+    let color = diffuse_color * (ambient + direct + indirect) + specular_color * ambient * (1.0 - roughness);
+    return vec4f(color, 1.0);
+}
+
+// 深度剥离使用第 2 组的 2、3 号绑定，与光照探针错开，管线布局见 DepthPeelRenderer
+@group(2) @binding(2)
+var peel_previous_depth: texture_2d<f32>;
+@group(2) @binding(3)
+var peel_scene_depth: texture_2d<f32>;
+
+// 丢弃被不透明物体遮挡、或不比上一层更远的片元，剩余片元中最近的一个由深度测试留下
+@fragment
+fn fs_depth_peel(in: VertexOutput) -> @location(0) vec4f {
+    clip(in.world_position);
+    let texel = vec2<i32>(in.clip_position.xy);
+    let depth = in.clip_position.z;
+    if depth >= textureLoad(peel_scene_depth, texel, 0).r || depth <= textureLoad(peel_previous_depth, texel, 0).r {
+        discard;
+    }
+    let color = material.base_color.rgb * (1.0 + material.emissive_intensity) + emissive_radiance();
+    let alpha = material.base_color.a;
+    return vec4f(color * alpha, alpha);
+}
+
+struct OitOutput {
+    @location(0) accum: vec4f,
+    @location(1) revealage: f32,
+};
+
+// 加权混合 OIT 的透明通道：越近、越不透明的片元权重越大
+@fragment
+fn fs_transparent(in: VertexOutput) -> OitOutput {
+    clip(in.world_position);
+    let color = material.base_color.rgb * (1.0 + material.emissive_intensity) + emissive_radiance();
+    let alpha = material.base_color.a;
+    let premultiplied = vec4f(color * alpha, alpha);
+
+    let depth = clamp(in.view_depth / 100.0, 0.0, 1.0);
+    let weight = clamp(pow(min(1.0, alpha * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - depth * 0.9, 3.0), 1e-2, 3e3);
+
+    var out: OitOutput;
+    out.accum = premultiplied * weight;
+    out.revealage = alpha;
+    return out;
+}
+
+// 可过滤阴影的矩通道，第 0 组换成 FilteredShadowMap 的布局，1 号绑定同一个 uniform
+@group(0) @binding(1)
+var<uniform> shadow_pass: FilteredShadowUniform;
+
+struct ShadowMomentsOutput {
+    @location(0) moments: vec2f,
+    @location(1) exponential: f32,
+    @location(2) four_moments: vec4f,
+};
+
+// 输出归一化线性深度的一阶、二阶矩、指数，以及映射到 [-1, 1] 后的前四阶矩
+@fragment
+fn fs_shadow_moments(in: VertexOutput) -> ShadowMomentsOutput {
+    let depth = in.view_depth / shadow_pass.far;
+    var out: ShadowMomentsOutput;
+    out.moments = vec2f(depth, depth * depth);
+    out.exponential = exp(shadow_pass.esm_c * depth);
+    let z = depth * 2.0 - 1.0;
+    let z2 = z * z;
+    out.four_moments = vec4f(z, z2, z2 * z, z2 * z2);
+    return out;
 }
@@ -0,0 +1,79 @@
+use std::fmt;
+
+// 前后各显示两行，共 5 行
+const CONTEXT_LINES: usize = 2;
+
+// 从 wgpu 返回的着色器错误文本中解析出的位置与消息，行列号从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ShaderError {
+    // naga 的报告形如 "Shader 'X' parsing error: ...\n  ┌─ wgsl:4:5\n ..."
+    pub fn parse(error: &str) -> Self {
+        let message = error
+            .lines()
+            .find_map(|line| line.split_once("error: ").map(|(_, message)| message.trim()))
+            .or_else(|| error.lines().map(str::trim).rfind(|line| !line.is_empty()))
+            .unwrap_or_default()
+            .to_string();
+
+        let location = error.split("wgsl:").skip(1).find_map(|rest| {
+            let mut numbers = rest.split(|c: char| !c.is_ascii_digit());
+            let line = numbers.next()?.parse().ok()?;
+            Some((line, numbers.next().and_then(|column| column.parse().ok())))
+        });
+
+        Self {
+            message,
+            line: location.map(|(line, _)| line),
+            column: location.and_then(|(_, column)| column),
+        }
+    }
+
+    // 取出错行前后的源码，在出错行下方用 ^ 标出列；解析不到行号时只返回消息
+    pub fn format_with_source(wgsl_source: &str, error: &str) -> String {
+        let error = Self::parse(error);
+        let mut output = format!("error: {}", error.message);
+        let Some(line) = error.line.filter(|&line| line >= 1 && line <= wgsl_source.lines().count()) else {
+            return output;
+        };
+
+        let first = line.saturating_sub(CONTEXT_LINES).max(1);
+        let last = line + CONTEXT_LINES;
+        let width = last.to_string().len();
+        output.push_str(&format!("\n{:width$}--> line {line}", ""));
+        if let Some(column) = error.column {
+            output.push_str(&format!(", column {column}"));
+        }
+
+        for (number, text) in wgsl_source.lines().enumerate().map(|(index, text)| (index + 1, text)) {
+            if number < first || number > last {
+                continue;
+            }
+            let marker = if number == line { '>' } else { ' ' };
+            output.push_str(&format!("\n{marker} {number:>width$} | {text}"));
+            if number == line {
+                // 没有列号时指向第一个非空白字符
+                let column = error.column.unwrap_or_else(|| text.len() - text.trim_start().len() + 1);
+                output.push_str(&format!("\n  {:width$} | {:>column$}", "", "^"));
+            }
+        }
+        output
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{line}:{column}: {}", self.message),
+            (Some(line), None) => write!(f, "{line}: {}", self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
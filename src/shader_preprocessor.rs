@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use crate::wgsl_lint::WgslLinter;
+
+// 把 `#include "name"` 行替换为预先注册的 WGSL 片段，编译前先经过静态检查
+#[derive(Default)]
+pub struct ShaderPreprocessor {
+    includes: HashMap<String, &'static str>,
+}
+
+impl ShaderPreprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_include(&mut self, name: impl Into<String>, source: &'static str) -> &mut Self {
+        self.includes.insert(name.into(), source);
+        self
+    }
+
+    // 每个片段只展开一次，重复或循环包含会被忽略
+    pub fn process(&self, source: &str) -> String {
+        let mut included = Vec::new();
+        self.expand(source, &mut included)
+    }
+
+    fn expand(&self, source: &str, included: &mut Vec<String>) -> String {
+        let mut output = String::with_capacity(source.len());
+        for line in source.lines() {
+            let include = line
+                .trim()
+                .strip_prefix("#include")
+                .map(|rest| rest.trim().trim_matches('"'));
+
+            match include {
+                Some(name) if included.iter().any(|done| done == name) => {}
+                Some(name) => match self.includes.get(name) {
+                    Some(snippet) => {
+                        included.push(name.to_string());
+                        output.push_str(&self.expand(snippet, included));
+                    }
+                    None => panic!("unknown shader include `{name}`"),
+                },
+                None => {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+        output
+    }
+
+    // 检查结果只记录日志，真正的编译错误仍由 wgpu 报告
+    pub fn compile(&self, device: &wgpu::Device, label: &str, source: &str) -> wgpu::ShaderModule {
+        let source = self.process(source);
+        for error in WgslLinter::check(&source) {
+            tracing::warn!(shader = label, line = error.line, column = error.column, "{}", error.message);
+        }
+
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        })
+    }
+}
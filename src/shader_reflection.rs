@@ -0,0 +1,122 @@
+use std::ops::Range;
+
+use naga::{ Binding, ScalarKind, TypeInner };
+
+// 顶点入口的输入属性，按 location 排序
+#[derive(Debug, Clone)]
+pub struct ShaderReflection {
+    pub entry_point: String,
+    // (名称, location, 标量类型, 字节大小)
+    pub attributes: Vec<(String, u32, ScalarKind, u32)>,
+}
+
+// 由反射结果生成、持有属性数组的顶点缓冲区布局
+pub struct ReflectedVertexLayout {
+    pub array_stride: wgpu::BufferAddress,
+    pub step_mode: wgpu::VertexStepMode,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl ReflectedVertexLayout {
+    pub fn layout(&self) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+}
+
+impl ShaderReflection {
+    // 着色器源码在编译期嵌入，解析失败属于编程错误，直接 panic 并给出带位置的错误信息
+    pub fn from_wgsl(source: &str) -> ShaderReflection {
+        let module = naga::front::wgsl::parse_str(source)
+            .unwrap_or_else(|e| panic!("failed to parse WGSL for reflection:\n{}", e.emit_to_string(source)));
+        let entry_point = module
+            .entry_points
+            .iter()
+            .find(|entry_point| entry_point.stage == naga::ShaderStage::Vertex)
+            .expect("shader has no vertex entry point");
+
+        let mut attributes = Vec::new();
+        for argument in &entry_point.function.arguments {
+            match (&argument.binding, &module.types[argument.ty].inner) {
+                (Some(binding), inner) => {
+                    if let Some(attribute) = reflect_attribute(argument.name.as_deref(), binding, inner) {
+                        attributes.push(attribute);
+                    }
+                }
+                // 结构体参数的 location 标注在成员上
+                (None, TypeInner::Struct { members, .. }) => {
+                    for member in members {
+                        if let Some(binding) = &member.binding {
+                            let inner = &module.types[member.ty].inner;
+                            if let Some(attribute) = reflect_attribute(member.name.as_deref(), binding, inner) {
+                                attributes.push(attribute);
+                            }
+                        }
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+        attributes.sort_by_key(|(_, location, _, _)| *location);
+
+        ShaderReflection { entry_point: entry_point.name.clone(), attributes }
+    }
+
+    // 把 locations 范围内的属性按 location 顺序紧密排列，Rust 端结构体字段顺序需与之一致
+    pub fn vertex_buffer_layout(&self, locations: Range<u32>, step_mode: wgpu::VertexStepMode) -> ReflectedVertexLayout {
+        let mut offset = 0;
+        let attributes = self
+            .attributes
+            .iter()
+            .filter(|(_, location, _, _)| locations.contains(location))
+            .map(|(name, location, kind, size)| {
+                let format = vertex_format(*kind, *size)
+                    .unwrap_or_else(|| panic!("vertex attribute `{name}` has no matching vertex format"));
+                let attribute = wgpu::VertexAttribute {
+                    format,
+                    offset,
+                    shader_location: *location,
+                };
+                offset += format.size();
+                attribute
+            })
+            .collect();
+
+        ReflectedVertexLayout { array_stride: offset, step_mode, attributes }
+    }
+}
+
+fn reflect_attribute(name: Option<&str>, binding: &Binding, inner: &TypeInner) -> Option<(String, u32, ScalarKind, u32)> {
+    let Binding::Location { location, .. } = binding else {
+        return None;
+    };
+    let (kind, size) = match *inner {
+        TypeInner::Scalar { kind, width } => (kind, width as u32),
+        TypeInner::Vector { size, kind, width } => (kind, size as u32 * width as u32),
+        _ => return None,
+    };
+    Some((name.unwrap_or_default().to_string(), *location, kind, size))
+}
+
+fn vertex_format(kind: ScalarKind, size: u32) -> Option<wgpu::VertexFormat> {
+    use wgpu::VertexFormat::*;
+
+    Some(match (kind, size) {
+        (ScalarKind::Float, 4) => Float32,
+        (ScalarKind::Float, 8) => Float32x2,
+        (ScalarKind::Float, 12) => Float32x3,
+        (ScalarKind::Float, 16) => Float32x4,
+        (ScalarKind::Uint, 4) => Uint32,
+        (ScalarKind::Uint, 8) => Uint32x2,
+        (ScalarKind::Uint, 12) => Uint32x3,
+        (ScalarKind::Uint, 16) => Uint32x4,
+        (ScalarKind::Sint, 4) => Sint32,
+        (ScalarKind::Sint, 8) => Sint32x2,
+        (ScalarKind::Sint, 12) => Sint32x3,
+        (ScalarKind::Sint, 16) => Sint32x4,
+        _ => return None,
+    })
+}
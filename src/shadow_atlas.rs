@@ -0,0 +1,300 @@
+use std::fmt;
+
+use cgmath::{ Matrix4, SquareMatrix };
+
+use crate::camera::CameraUniform;
+
+pub const SHADOW_ATLAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+// 与 shader.wgsl 中的 MAX_SHADOW_TILES 一致
+pub const MAX_SHADOW_TILES: usize = 16;
+
+// 图集中一个光源的阴影贴图：像素区域用于视口与裁剪，offset/scale 把 [0, 1] 纹理坐标映射到图集中
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasTile {
+    pub x: u32,
+    pub y: u32,
+    pub size: u32,
+    pub offset: [f32; 2],
+    pub scale: [f32; 2],
+    // 光源的投影矩阵，由 set_view_projection 设置
+    pub view_proj: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowTileUniform {
+    view_proj: [[f32; 4]; 4],
+    offset: [f32; 2],
+    scale: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowAtlasUniform {
+    tiles: [ShadowTileUniform; MAX_SHADOW_TILES],
+    // 一个纹素在图集纹理坐标中的大小，采样时据此避免越过图块边界
+    texel_size: f32,
+    _padding: [f32; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShadowAtlasError {
+    TooManyTiles,
+    // 没有足够大的空闲区域
+    AtlasFull { size: u32 },
+}
+
+impl fmt::Display for ShadowAtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadowAtlasError::TooManyTiles => write!(f, "shadow atlas already holds {MAX_SHADOW_TILES} tiles"),
+            ShadowAtlasError::AtlasFull { size } => write!(f, "no free {size}x{size} region left in the shadow atlas"),
+        }
+    }
+}
+
+impl std::error::Error for ShadowAtlasError {}
+
+// 把多个光源的阴影贴图装进一张深度纹理：图块为 2 的幂的正方形，按四叉树切分分配，
+// 每个图块有自己的相机绑定组（第 0 组，与场景管线的相机布局一致），阴影通道通过视口与裁剪写入各自区域；
+// 场景着色器从光照组的 1~3 号绑定读取图集、比较采样器与各图块的投影
+pub struct ShadowAtlas {
+    pub texture: wgpu::Texture,
+    pub size: u32,
+    tiles: Vec<AtlasTile>,
+    // 空闲的正方形区域 (x, y, size)
+    free: Vec<(u32, u32, u32)>,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    camera_stride: wgpu::BufferAddress,
+    camera_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl ShadowAtlas {
+    // size 会向上取到 2 的幂
+    pub fn new(device: &wgpu::Device, size: u32, camera_bind_group_layout: &wgpu::BindGroupLayout) -> Self {
+        let size = size.max(1).next_power_of_two();
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_ATLAS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Atlas Buffer"),
+            size: std::mem::size_of::<ShadowAtlasUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let camera_size = std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress;
+        let camera_stride = wgpu::util::align_to(camera_size, alignment);
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Atlas Camera Buffer"),
+            size: camera_stride * MAX_SHADOW_TILES as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_groups = (0..MAX_SHADOW_TILES as wgpu::BufferAddress)
+            .map(|tile| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("shadow_atlas_camera_bind_group"),
+                    layout: camera_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: &camera_buffer,
+                            offset: camera_stride * tile,
+                            size: wgpu::BufferSize::new(camera_size),
+                        }),
+                    }],
+                })
+            })
+            .collect();
+
+        Self {
+            texture,
+            size,
+            tiles: Vec::new(),
+            free: vec![(0, 0, size)],
+            view,
+            sampler,
+            uniform_buffer,
+            camera_buffer,
+            camera_stride,
+            camera_bind_groups,
+        }
+    }
+
+    // 分配一个边长为 resolution（向上取到 2 的幂）的图块，返回其序号
+    pub fn allocate(&mut self, resolution: u32) -> Result<usize, ShadowAtlasError> {
+        if self.tiles.len() >= MAX_SHADOW_TILES {
+            return Err(ShadowAtlasError::TooManyTiles);
+        }
+        let resolution = resolution.max(1).next_power_of_two();
+        // 取能容纳的最小空闲区域，逐级四分直到恰好等于所需大小
+        let index = self
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, size))| *size >= resolution)
+            .min_by_key(|(_, (_, _, size))| *size)
+            .map(|(index, _)| index)
+            .ok_or(ShadowAtlasError::AtlasFull { size: resolution })?;
+        let (x, y, mut size) = self.free.swap_remove(index);
+        while size > resolution {
+            size /= 2;
+            self.free.extend_from_slice(&[(x + size, y, size), (x, y + size, size), (x + size, y + size, size)]);
+        }
+
+        let atlas_size = self.size as f32;
+        self.tiles.push(AtlasTile {
+            x,
+            y,
+            size,
+            offset: [x as f32 / atlas_size, y as f32 / atlas_size],
+            scale: [size as f32 / atlas_size; 2],
+            view_proj: Matrix4::identity().into(),
+        });
+        Ok(self.tiles.len() - 1)
+    }
+
+    // 释放全部图块，之后可按新的光源重新分配
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.free = vec![(0, 0, self.size)];
+    }
+
+    pub fn tiles(&self) -> &[AtlasTile] {
+        &self.tiles
+    }
+
+    pub fn set_view_projection(&mut self, tile: usize, view_proj: Matrix4<f32>) {
+        self.tiles[tile].view_proj = view_proj.into();
+    }
+
+    // 上传各图块的投影，阴影通道与场景着色器都从这里读取
+    pub fn write(&self, queue: &wgpu::Queue) {
+        let mut uniform: ShadowAtlasUniform = bytemuck::Zeroable::zeroed();
+        uniform.texel_size = 1.0 / self.size as f32;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            uniform.tiles[index] = ShadowTileUniform {
+                view_proj: tile.view_proj,
+                offset: tile.offset,
+                scale: tile.scale,
+            };
+            let camera = CameraUniform { view_proj: tile.view_proj, ..CameraUniform::new() };
+            queue.write_buffer(&self.camera_buffer, self.camera_stride * index as wgpu::BufferAddress, bytemuck::bytes_of(&camera));
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    // 清空整个图集，随后对每个图块调用 set_tile 再绘制投射阴影的物体
+    pub fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Atlas Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // 把视口与裁剪矩形限制在图块内，并在第 0 组绑定该图块的光源相机
+    pub fn set_tile<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, tile: usize) {
+        let AtlasTile { x, y, size, .. } = self.tiles[tile];
+        render_pass.set_viewport(x as f32, y as f32, size as f32, size as f32, 0.0, 1.0);
+        render_pass.set_scissor_rect(x, y, size, size);
+        render_pass.set_bind_group(0, &self.camera_bind_groups[tile], &[]);
+    }
+
+    // 阴影通道管线的深度状态，偏移用于消除自阴影条纹
+    pub fn depth_stencil_state() -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: SHADOW_ATLAS_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            },
+        }
+    }
+
+    // 光照组中图集使用的 1~3 号绑定，与 bind_group_entries 对应
+    pub fn bind_group_layout_entries(visibility: wgpu::ShaderStages) -> [wgpu::BindGroupLayoutEntry; 3] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ]
+    }
+
+    pub fn bind_group_entries(&self) -> [wgpu::BindGroupEntry<'_>; 3] {
+        [
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&self.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&self.sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: self.uniform_buffer.as_entire_binding(),
+            },
+        ]
+    }
+}
@@ -0,0 +1,69 @@
+use cgmath::{ Point3, Vector3 };
+
+// 可线性插值的类型。t 允许超出 [0, 1]（样条计算需要外插）
+pub trait Lerp: Sized {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl<const N: usize> Lerp for [f32; N] {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        std::array::from_fn(|i| self[i].lerp(&other[i], t))
+    }
+}
+
+impl Lerp for Vector3<f32> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Lerp for Point3<f32> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+// 均匀参数化的 Catmull-Rom 样条，曲线经过每一个控制点
+#[derive(Debug, Clone, Default)]
+pub struct CatmullRomSpline<T: Lerp> {
+    pub points: Vec<T>,
+}
+
+impl<T: Lerp> CatmullRomSpline<T> {
+    pub fn new(points: Vec<T>) -> Self {
+        Self { points }
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    // t 为整条曲线上的归一化参数，0 为第一个控制点，1 为最后一个
+    pub fn sample(&self, t: f32) -> T {
+        assert!(!self.points.is_empty(), "cannot sample a spline without control points");
+
+        let last = self.points.len() - 1;
+        let scaled = t.clamp(0.0, 1.0) * last as f32;
+        let segment = (scaled.floor() as usize).min(last.saturating_sub(1));
+        let u = scaled - segment as f32;
+
+        // 首尾控制点重复使用，使曲线在端点处停止
+        let point = |i: isize| &self.points[i.clamp(0, last as isize) as usize];
+        let i = segment as isize;
+        let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+
+        // Barry-Goldman 金字塔形式，只需要线性插值
+        let a1 = p0.lerp(p1, 1.0 + u);
+        let a2 = p1.lerp(p2, u);
+        let a3 = p2.lerp(p3, u - 1.0);
+        let b1 = a1.lerp(&a2, (1.0 + u) * 0.5);
+        let b2 = a2.lerp(&a3, u * 0.5);
+        b1.lerp(&b2, u)
+    }
+}
@@ -0,0 +1,321 @@
+use cgmath::{ Matrix4, SquareMatrix };
+
+use crate::camera::Camera;
+use crate::post_process::SCENE_COLOR_FORMAT;
+use crate::texture::Texture;
+
+pub const SSGI_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+// 与 ssgi.wgsl 中的 RAY_COUNT、STEP_COUNT 一致
+pub const SSGI_RAY_COUNT: u32 = 8;
+pub const SSGI_STEP_COUNT: u32 = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SsgiParams {
+    proj: [[f32; 4]; 4],
+    inv_proj: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+    inv_view: [[f32; 4]; 4],
+    previous_view_proj: [[f32; 4]; 4],
+    max_distance: f32,
+    thickness: f32,
+    history_weight: f32,
+    intensity: f32,
+    frame: u32,
+    history_valid: u32,
+    _padding: [f32; 2],
+}
+
+// 追踪需要的同尺寸屏幕纹理，均在不透明物体的延迟光照之后读取
+pub struct SsgiInputs<'a> {
+    // 场景深度缓冲，可能带模板
+    pub depth: &'a wgpu::Texture,
+    // G-buffer 的世界空间法线与基础色
+    pub normal: &'a wgpu::TextureView,
+    pub albedo: &'a wgpu::TextureView,
+    // 只含不透明物体的场景颜色，间接光最后也相加到这里
+    pub scene_color: &'a wgpu::TextureView,
+}
+
+// 屏幕空间全局光照：每个像素沿世界空间法线的余弦加权半球方向发出 SSGI_RAY_COUNT 条光线，
+// 在观察空间步进 SSGI_STEP_COUNT 次并与深度缓冲比较，命中处的场景颜色即为一次反弹的入射光，
+// 乘以基础色（朗伯 BRDF 与余弦概率密度相消）得到间接漫反射。结果按上一帧的观察投影矩阵重投影，
+// 与历史纹理按 history_weight 混合以分摊采样数，最后乘以 intensity 相加到场景颜色上。
+// 屏幕外与被遮挡的表面没有深度信息，看不到它们反射的光
+pub struct SsgiPass {
+    pub enabled: bool,
+    // 每条光线步进的总长度，观察空间单位
+    pub max_distance: f32,
+    // 比光线点靠前超过 thickness 的表面视为在其前方而非挡住光线
+    pub thickness: f32,
+    // 历史结果所占的比例，越大噪点越少但光照变化时拖影越长
+    pub history_weight: f32,
+    pub intensity: f32,
+    // 两张纹理轮流作为本帧输出与上一帧历史
+    targets: [Texture; 2],
+    current: usize,
+    frame: u32,
+    // 第一帧与尺寸变化后没有可用的历史
+    previous_view_proj: Option<Matrix4<f32>>,
+    trace_pipeline: wgpu::RenderPipeline,
+    trace_bind_group_layout: wgpu::BindGroupLayout,
+    composite_pipeline: wgpu::RenderPipeline,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+}
+
+impl SsgiPass {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("SSGI Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("ssgi.wgsl").into()),
+        });
+
+        // 深度以不可过滤的浮点纹理绑定：GL 后端不支持对 texture_depth_2d 使用 textureLoad
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let params_entry = wgpu::BindGroupLayoutEntry {
+            binding: 5,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let trace_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssgi_trace_bind_group_layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                texture_entry(2),
+                texture_entry(3),
+                texture_entry(4),
+                params_entry,
+            ],
+        });
+        let composite_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssgi_composite_bind_group_layout"),
+            entries: &[params_entry, texture_entry(6)],
+        });
+
+        let create_pipeline = |label, layout: &wgpu::BindGroupLayout, entry_point, target| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(target)],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+        let trace_pipeline = create_pipeline(
+            "SSGI Trace Pipeline",
+            &trace_bind_group_layout,
+            "fs_main",
+            wgpu::ColorTargetState {
+                format: SSGI_FORMAT,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            },
+        );
+        // 只加到颜色上，保留场景的 alpha
+        let composite_pipeline = create_pipeline(
+            "SSGI Composite Pipeline",
+            &composite_bind_group_layout,
+            "fs_composite",
+            wgpu::ColorTargetState {
+                format: SCENE_COLOR_FORMAT,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Zero,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            },
+        );
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("SSGI Params Buffer"),
+            size: std::mem::size_of::<SsgiParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            enabled: true,
+            max_distance: 1.0,
+            thickness: 0.1,
+            history_weight: 0.9,
+            intensity: 1.0,
+            targets: Self::create_targets(device, width, height),
+            current: 0,
+            frame: 0,
+            previous_view_proj: None,
+            trace_pipeline,
+            trace_bind_group_layout,
+            composite_pipeline,
+            composite_bind_group_layout,
+            params_buffer,
+        }
+    }
+
+    fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> [Texture; 2] {
+        ["SSGI Target A", "SSGI Target B"].map(|label| {
+            Texture::create_render_target(device, width, height, SSGI_FORMAT, wgpu::TextureUsages::empty(), label)
+        })
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.targets = Self::create_targets(device, width, height);
+        self.previous_view_proj = None;
+    }
+
+    // 本帧累积后的间接光，a 为观察空间深度
+    pub fn output(&self) -> &Texture {
+        &self.targets[self.current]
+    }
+
+    // 追踪并与历史混合，再相加到 inputs.scene_color 上；enabled 为 false 时什么都不做
+    pub fn apply(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera: &Camera,
+        inputs: SsgiInputs<'_>,
+    ) {
+        if !self.enabled {
+            self.previous_view_proj = None;
+            return;
+        }
+        let proj = camera.build_projection_matrix();
+        let view = camera.build_view_matrix();
+        let view_proj = camera.build_view_projection_matrix();
+        let params = SsgiParams {
+            proj: proj.into(),
+            inv_proj: proj.invert().unwrap_or_else(Matrix4::identity).into(),
+            view: view.into(),
+            inv_view: view.invert().unwrap_or_else(Matrix4::identity).into(),
+            previous_view_proj: self.previous_view_proj.unwrap_or(view_proj).into(),
+            max_distance: self.max_distance,
+            thickness: self.thickness,
+            history_weight: self.history_weight.clamp(0.0, 1.0),
+            intensity: self.intensity,
+            frame: self.frame,
+            history_valid: self.previous_view_proj.is_some() as u32,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        self.current = 1 - self.current;
+        let [output, history] = [&self.targets[self.current], &self.targets[1 - self.current]];
+        // 场景深度可能带模板，采样时只取深度部分
+        let depth_view = inputs.depth.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("SSGI Scene Depth"),
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        });
+        let view_entry = |binding, view| wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::TextureView(view),
+        };
+        let trace_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ssgi_trace_bind_group"),
+            layout: &self.trace_bind_group_layout,
+            entries: &[
+                view_entry(0, &depth_view),
+                view_entry(1, inputs.normal),
+                view_entry(2, inputs.albedo),
+                view_entry(3, inputs.scene_color),
+                view_entry(4, &history.view),
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        let composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ssgi_composite_bind_group"),
+            layout: &self.composite_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                view_entry(6, &output.view),
+            ],
+        });
+
+        let mut trace_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("SSGI Trace Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &output.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        trace_pass.set_pipeline(&self.trace_pipeline);
+        trace_pass.set_bind_group(0, &trace_bind_group, &[]);
+        trace_pass.draw(0..3, 0..1);
+        drop(trace_pass);
+
+        let mut composite_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("SSGI Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: inputs.scene_color,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        composite_pass.set_pipeline(&self.composite_pipeline);
+        composite_pass.set_bind_group(0, &composite_bind_group, &[]);
+        composite_pass.draw(0..3, 0..1);
+        drop(composite_pass);
+
+        self.previous_view_proj = Some(view_proj);
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
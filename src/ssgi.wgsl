@@ -0,0 +1,151 @@
+// 与 ssgi.rs 中的 SSGI_RAY_COUNT、SSGI_STEP_COUNT 一致
+const RAY_COUNT: u32 = 8u;
+const STEP_COUNT: u32 = 16u;
+const PI: f32 = 3.14159265;
+
+struct SsgiParams {
+    proj: mat4x4f,
+    inv_proj: mat4x4f,
+    view: mat4x4f,
+    inv_view: mat4x4f,
+    // 上一帧的观察投影矩阵，用于把当前像素重投影到历史纹理
+    previous_view_proj: mat4x4f,
+    max_distance: f32,
+    thickness: f32,
+    history_weight: f32,
+    intensity: f32,
+    frame: u32,
+    history_valid: u32,
+};
+
+@group(0) @binding(0)
+var t_depth: texture_2d<f32>;
+@group(0) @binding(1)
+var t_normal: texture_2d<f32>;
+@group(0) @binding(2)
+var t_albedo: texture_2d<f32>;
+@group(0) @binding(3)
+var t_scene: texture_2d<f32>;
+@group(0) @binding(4)
+var t_history: texture_2d<f32>;
+@group(0) @binding(5)
+var<uniform> params: SsgiParams;
+// 合成通道读取本帧的累积结果
+@group(0) @binding(6)
+var t_result: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+// PCG 哈希
+fn hash(seed: u32) -> u32 {
+    var x = seed * 747796405u + 2891336453u;
+    x = ((x >> ((x >> 28u) + 4u)) ^ x) * 277803737u;
+    return (x >> 22u) ^ x;
+}
+
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed)) / 4294967296.0;
+}
+
+fn view_position(uv: vec2f, depth: f32) -> vec3f {
+    let ndc = vec4f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let position = params.inv_proj * ndc;
+    return position.xyz / position.w;
+}
+
+// 以 normal 为轴的余弦加权半球方向
+fn cosine_hemisphere(normal: vec3f, u1: f32, u2: f32) -> vec3f {
+    let r = sqrt(u1);
+    let phi = 2.0 * PI * u2;
+    let up = select(vec3f(0.0, 1.0, 0.0), vec3f(1.0, 0.0, 0.0), abs(normal.y) > 0.99);
+    let tangent = normalize(cross(up, normal));
+    let bitangent = cross(normal, tangent);
+    return normalize(tangent * (r * cos(phi)) + bitangent * (r * sin(phi)) + normal * sqrt(max(1.0 - u1, 0.0)));
+}
+
+// 沿观察空间方向步进，命中深度缓冲中的表面时返回该处场景颜色，w 为 1 表示命中
+fn trace(origin: vec3f, direction: vec3f, jitter: f32, size: vec2f) -> vec4f {
+    let step = direction * (params.max_distance / f32(STEP_COUNT));
+    for (var i = 0u; i < STEP_COUNT; i++) {
+        let ray = origin + step * (f32(i) + jitter);
+        let clip = params.proj * vec4f(ray, 1.0);
+        if clip.w <= 0.0 {
+            break;
+        }
+        let ndc = clip.xy / clip.w;
+        let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        if any(uv < vec2f(0.0)) || any(uv >= vec2f(1.0)) {
+            break;
+        }
+        let texel = vec2<i32>(uv * size);
+        let depth = textureLoad(t_depth, texel, 0).r;
+        if depth >= 1.0 {
+            continue;
+        }
+        let scene = view_position((floor(uv * size) + 0.5) / size, depth);
+        // 右手观察空间看向 -z，表面比光线点更靠近相机且不超过 thickness 时视为命中
+        let difference = scene.z - ray.z;
+        if difference > 0.0 && difference < params.thickness {
+            return vec4f(textureLoad(t_scene, texel, 0).rgb, 1.0);
+        }
+    }
+    return vec4f(0.0);
+}
+
+// 输出 rgb 为间接漫反射，a 为观察空间深度，下一帧据此判断历史是否仍属于同一表面
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let size = vec2f(textureDimensions(t_depth));
+    let texel = vec2<i32>(position.xy);
+    let depth = textureLoad(t_depth, texel, 0).r;
+    let albedo = textureLoad(t_albedo, texel, 0);
+    // 天空没有表面
+    if depth >= 1.0 || albedo.a == 0.0 {
+        return vec4f(0.0);
+    }
+    let origin = view_position(position.xy / size, depth);
+    let world_normal = normalize(textureLoad(t_normal, texel, 0).xyz);
+    let view_normal = normalize((params.view * vec4f(world_normal, 0.0)).xyz);
+    // 沿法线抬起半个步长，避免命中自身所在的表面
+    let start = origin + view_normal * (params.max_distance / f32(STEP_COUNT) * 0.5);
+
+    let seed = hash((u32(texel.y) * u32(size.x) + u32(texel.x)) ^ hash(params.frame));
+    var radiance = vec3f(0.0);
+    for (var i = 0u; i < RAY_COUNT; i++) {
+        let ray_seed = seed + i * 3u;
+        let direction = cosine_hemisphere(world_normal, random(ray_seed), random(ray_seed + 1u));
+        let view_direction = (params.view * vec4f(direction, 0.0)).xyz;
+        radiance += trace(start, view_direction, random(ray_seed + 2u), size).rgb;
+    }
+    // 朗伯 BRDF albedo / π 乘以 cos θ，再除以余弦加权的概率密度 cos θ / π，只剩 albedo
+    var result = albedo.rgb * radiance / f32(RAY_COUNT);
+
+    let view_depth = -origin.z;
+    if params.history_valid != 0u {
+        let world = params.inv_view * vec4f(origin, 1.0);
+        let previous_clip = params.previous_view_proj * world;
+        if previous_clip.w > 0.0 {
+            let ndc = previous_clip.xy / previous_clip.w;
+            let previous_uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+            if all(previous_uv >= vec2f(0.0)) && all(previous_uv < vec2f(1.0)) {
+                let history = textureLoad(t_history, vec2<i32>(previous_uv * size), 0);
+                // 上一帧该处的深度与重投影的深度相差过大时说明被遮挡后重新露出，丢弃历史
+                if abs(history.a - previous_clip.w) < 0.05 * previous_clip.w {
+                    result = mix(result, history.rgb, params.history_weight);
+                }
+            }
+        }
+    }
+    return vec4f(result, view_depth);
+}
+
+// 相加混合到场景颜色上
+@fragment
+fn fs_composite(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let gi = textureLoad(t_result, vec2<i32>(position.xy), 0).rgb;
+    return vec4f(gi * params.intensity, 0.0);
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use cgmath::{ InnerSpace, Matrix, Matrix3, Matrix4, SquareMatrix, Vector3, Vector4 };
+use wgpu::util::DeviceExt;
+
+use crate::material::MaterialHandle;
+use crate::mesh::{ MeshData, Vertex };
+
+// 收集静态网格，烘焙时在 CPU 上把顶点变换到世界空间并按材质合并
+#[derive(Default)]
+pub struct StaticBatcher {
+    meshes: BTreeMap<MaterialHandle, Vec<(MeshData, Matrix4<f32>)>>,
+}
+
+impl StaticBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, mesh_data: MeshData, transform: Matrix4<f32>, material: MaterialHandle) {
+        self.meshes.entry(material).or_default().push((mesh_data, transform));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+
+    // 所有材质共用一对缓冲区，每个材质占据连续的索引区间
+    pub fn bake(self, device: &wgpu::Device, queue: &wgpu::Queue) -> BakedBatch {
+        let mut vertices = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        let mut draws = Vec::with_capacity(self.meshes.len());
+
+        for (material, meshes) in self.meshes {
+            let start = indices.len() as u32;
+            for (mesh, transform) in meshes {
+                let base = vertices.len() as u32;
+                // 非均匀缩放下法线需要用逆转置矩阵变换
+                let linear = Matrix3::from_cols(transform.x.truncate(), transform.y.truncate(), transform.z.truncate());
+                let normal_matrix = linear.invert().unwrap_or(linear).transpose();
+
+                vertices.extend(mesh.vertices.iter().map(|vertex| {
+                    let position = transform * Vector4::new(vertex.position[0], vertex.position[1], vertex.position[2], 1.0);
+                    let normal = normal_matrix * Vector3::from(vertex.normal);
+                    let normal = if normal.magnitude2() > 0.0 { normal.normalize() } else { normal };
+                    Vertex {
+                        position: position.truncate().into(),
+                        normal: normal.into(),
+                        tex_coords: vertex.tex_coords,
+                        uv1: vertex.uv1,
+                    }
+                }));
+                indices.extend(mesh.indices.iter().map(|&index| base + index as u32));
+            }
+            draws.push(BatchDraw { material, indices: start..indices.len() as u32 });
+        }
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Static Batch Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        // 合并后的顶点数可能超过 u16 范围，统一使用 u32 索引
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Static Batch Index Buffer"),
+            size: (indices.len().max(1) * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&index_buffer, 0, bytemuck::cast_slice(&indices));
+
+        BakedBatch { vertex_buffer, index_buffer, draws }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchDraw {
+    pub material: MaterialHandle,
+    pub indices: Range<u32>,
+}
+
+pub struct BakedBatch {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub draws: Vec<BatchDraw>,
+}
+
+impl BakedBatch {
+    // 每个材质一次绘制调用，绑定材质的工作交给调用方
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        mut bind_material: impl FnMut(&mut wgpu::RenderPass<'a>, MaterialHandle),
+    ) {
+        if self.draws.is_empty() {
+            return;
+        }
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for draw in &self.draws {
+            bind_material(render_pass, draw.material);
+            render_pass.draw_indexed(draw.indices.clone(), 0, 0..1);
+        }
+    }
+
+    pub fn draw_call_count(&self) -> usize {
+        self.draws.len()
+    }
+}
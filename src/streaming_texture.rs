@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{ self, Receiver };
+use std::sync::Arc;
+
+use image::imageops::FilterType;
+
+struct MipLevel {
+    level: u32,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+// 先上传最小的 mip 让物体立即可见，更精细的 mip 在后台生成后逐级补上
+pub struct StreamingTexture {
+    pub texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    mip_level_count: u32,
+    // 已驻留的最精细层级，从它到最粗层级之间全部可用
+    finest_resident: u32,
+    receiver: Receiver<MipLevel>,
+    // 比 finest_resident 更细但还不连续的层级先暂存
+    ready: HashMap<u32, MipLevel>,
+}
+
+impl StreamingTexture {
+    // 必须在 tokio 运行时中调用
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, image: Arc<image::DynamicImage>, label: &str) -> Self {
+        let (width, height) = (image.width().max(1), image.height().max(1));
+        let mip_level_count = 32 - width.max(height).leading_zeros();
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let coarsest = mip_level_count - 1;
+        let first = generate_mip(&image, coarsest);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: coarsest,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &first.data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * first.width),
+                rows_per_image: Some(first.height),
+            },
+            wgpu::Extent3d {
+                width: first.width,
+                height: first.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        // 由粗到细依次派发，粗层级通常先完成
+        let (sender, receiver) = mpsc::channel();
+        for level in (0..coarsest).rev() {
+            let image = image.clone();
+            let sender = sender.clone();
+            tokio::task::spawn_blocking(move || {
+                let _ = sender.send(generate_mip(&image, level));
+            });
+        }
+
+        Self {
+            view: Self::create_view(&texture, coarsest),
+            texture,
+            mip_level_count,
+            finest_resident: coarsest,
+            receiver,
+            ready: HashMap::new(),
+        }
+    }
+
+    fn create_view(texture: &wgpu::Texture, base_mip_level: u32) -> wgpu::TextureView {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            base_mip_level,
+            ..Default::default()
+        })
+    }
+
+    // 只暴露已驻留的层级，绑定组需要在 update 返回 true 后重建
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn finest_resident_mip(&self) -> u32 {
+        self.finest_resident
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.finest_resident == 0
+    }
+
+    // 每帧调用，为新完成的层级录制复制命令，返回视图是否变化
+    pub fn update(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) -> bool {
+        for mip in self.receiver.try_iter() {
+            self.ready.insert(mip.level, mip);
+        }
+
+        let previous = self.finest_resident;
+        while self.finest_resident > 0 {
+            let Some(mip) = self.ready.remove(&(self.finest_resident - 1)) else {
+                break;
+            };
+            self.record_copy(device, encoder, &mip);
+            self.finest_resident = mip.level;
+        }
+
+        if self.finest_resident != previous {
+            self.view = Self::create_view(&self.texture, self.finest_resident);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn record_copy(&self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, mip: &MipLevel) {
+        // 缓冲区到纹理的复制要求每行字节数按 256 对齐
+        let unpadded_row = 4 * mip.width;
+        let padded_row = wgpu::util::align_to(unpadded_row, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Streaming Mip Staging Buffer"),
+            size: (padded_row * mip.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        {
+            let mut mapped = staging.slice(..).get_mapped_range_mut();
+            for (row, src) in mip.data.chunks_exact(unpadded_row as usize).enumerate() {
+                let start = row * padded_row as usize;
+                mapped[start..start + unpadded_row as usize].copy_from_slice(src);
+            }
+        }
+        staging.unmap();
+
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &staging,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(mip.height),
+                },
+            },
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: mip.level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: mip.width,
+                height: mip.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.mip_level_count
+    }
+}
+
+fn generate_mip(image: &image::DynamicImage, level: u32) -> MipLevel {
+    let width = (image.width() >> level).max(1);
+    let height = (image.height() >> level).max(1);
+    let data = if level == 0 {
+        image.to_rgba8().into_raw()
+    } else {
+        image.resize_exact(width, height, FilterType::Triangle).to_rgba8().into_raw()
+    };
+    MipLevel { level, width, height, data }
+}
@@ -0,0 +1,279 @@
+use cgmath::{ InnerSpace, Point3, Vector3 };
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+
+// 形变范围按此视场角下的距离定义，其他视场角按屏幕上的大小换算
+const REFERENCE_FOVY: f32 = 45.0;
+// 形变范围为空时按此长度计算，避免除零
+const MIN_MORPH_RANGE: f32 = 1e-4;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TerrainVertex {
+    pub position: [f32; 3],
+    // 在下一级（格子边长加倍）网格上的位置，落在粗网格的三角形内
+    pub morph_position: [f32; 3],
+    pub normal: [f32; 3],
+    pub morph_normal: [f32; 3],
+}
+
+impl TerrainVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x3,
+        3 => Float32x3,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TerrainVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PatchUniform {
+    camera_position: [f32; 3],
+    // tan(fovy / 2) / tan(REFERENCE_FOVY / 2)，把距离换算为参考视场角下屏幕大小相同的距离
+    fov_scale: f32,
+    morph_start: f32,
+    morph_end: f32,
+    _padding: [f32; 2],
+}
+
+// 一块规则网格地形，xz 平面上 origin 到 origin + size；顶点着色器按形变因子在本级与下一级位置之间插值，
+// 形变因子由顶点处网格在屏幕上的大小给出（换算成参考视场角下的距离后映射到形变范围），
+// 相邻地形块在公共边上得到相同的因子，形变过程中不会出现裂缝
+pub struct TerrainPatch {
+    pub origin: [f32; 2],
+    pub size: f32,
+    // 每边的格子数，为偶数，下一级为其一半
+    pub resolution: u32,
+    morph_start: f32,
+    morph_end: f32,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl TerrainPatch {
+    // height 给出世界坐标 (x, z) 处的高度，相邻地形块使用同一个函数，边上的顶点才会重合
+    pub fn new(
+        device: &wgpu::Device,
+        renderer: &TerrainRenderer,
+        origin: [f32; 2],
+        size: f32,
+        resolution: u32,
+        height: impl Fn(f32, f32) -> f32,
+    ) -> Self {
+        assert!(resolution >= 2 && resolution.is_multiple_of(2), "terrain patch resolution must be even, got {resolution}");
+        let stride = resolution + 1;
+        assert!(stride * stride <= u16::MAX as u32 + 1, "terrain patch resolution {resolution} exceeds u16 indices");
+
+        let cell = size / resolution as f32;
+        let position = |i: u32, j: u32| {
+            let (x, z) = (origin[0] + i as f32 * cell, origin[1] + j as f32 * cell);
+            Vector3::new(x, height(x, z), z)
+        };
+        // 有限差分法线，spacing 为所在网格的格子边长
+        let normal = |i: u32, j: u32, spacing: f32| {
+            let (x, z) = (origin[0] + i as f32 * cell, origin[1] + j as f32 * cell);
+            let dx = height(x + spacing, z) - height(x - spacing, z);
+            let dz = height(x, z + spacing) - height(x, z - spacing);
+            Vector3::new(-dx, 2.0 * spacing, -dz).normalize()
+        };
+
+        let mut vertices = Vec::with_capacity((stride * stride) as usize);
+        for j in 0..stride {
+            for i in 0..stride {
+                // 粗网格的三角形沿 (0, 0)-(2, 2) 方向切分，奇数顶点取所在粗边两端的平均
+                let (a, b) = match (i % 2, j % 2) {
+                    (0, 0) => ((i, j), (i, j)),
+                    (1, 0) => ((i - 1, j), (i + 1, j)),
+                    (0, _) => ((i, j - 1), (i, j + 1)),
+                    _ => ((i - 1, j - 1), (i + 1, j + 1)),
+                };
+                vertices.push(TerrainVertex {
+                    position: position(i, j).into(),
+                    morph_position: ((position(a.0, a.1) + position(b.0, b.1)) * 0.5).into(),
+                    normal: normal(i, j, cell).into(),
+                    morph_normal: (normal(a.0, a.1, cell * 2.0) + normal(b.0, b.1, cell * 2.0)).normalize().into(),
+                });
+            }
+        }
+
+        // 细网格沿同一对角线方向切分，形变到底时每个细三角形都落在一个粗三角形内
+        let mut indices = Vec::with_capacity((resolution * resolution * 6) as usize);
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let corner = |di: u32, dj: u32| ((j + dj) * stride + i + di) as u16;
+                indices.extend_from_slice(&[corner(0, 0), corner(0, 1), corner(1, 1), corner(0, 0), corner(1, 1), corner(1, 0)]);
+            }
+        }
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Terrain Patch Buffer"),
+            size: std::mem::size_of::<PatchUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("terrain_patch_bind_group"),
+            layout: &renderer.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            origin,
+            size,
+            resolution,
+            // 默认从不形变
+            morph_start: f32::MAX,
+            morph_end: f32::MAX,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    // 距离（参考视场角下）小于 start_dist 时使用本级网格，大于 end_dist 时完全变为下一级；
+    // end_dist 应不大于换用下一级地形块的距离，切换时才不会跳变
+    pub fn set_morph_range(&mut self, start_dist: f32, end_dist: f32) {
+        self.morph_start = start_dist;
+        self.morph_end = end_dist;
+    }
+
+    pub fn morph_range(&self) -> (f32, f32) {
+        (self.morph_start, self.morph_end)
+    }
+
+    // 与着色器相同的换算，position 处的形变因子
+    pub fn morph_factor(&self, camera: &Camera, position: [f32; 3]) -> f32 {
+        let distance = (Point3::from(position) - camera.eye).magnitude() * fov_scale(camera);
+        ((distance - self.morph_start) / (self.morph_end - self.morph_start).max(MIN_MORPH_RANGE)).clamp(0.0, 1.0)
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera) {
+        let uniform = PatchUniform {
+            camera_position: camera.eye.into(),
+            fov_scale: fov_scale(camera),
+            morph_start: self.morph_start,
+            morph_end: self.morph_end,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+}
+
+pub struct TerrainRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl TerrainRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Terrain Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("terrain.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("terrain_patch_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Terrain Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Terrain Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[TerrainVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        Self { pipeline, bind_group_layout }
+    }
+
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: &'a wgpu::BindGroup,
+        patches: &'a [TerrainPatch],
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        for patch in patches {
+            render_pass.set_bind_group(1, &patch.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, patch.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(patch.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..patch.index_count, 0, 0..1);
+        }
+    }
+}
+
+fn fov_scale(camera: &Camera) -> f32 {
+    (camera.fovy.to_radians() * 0.5).tan() / (REFERENCE_FOVY.to_radians() * 0.5).tan()
+}
@@ -0,0 +1,60 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct PatchUniform {
+    camera_position: vec3f,
+    // 把距离换算为参考视场角下屏幕大小相同的距离
+    fov_scale: f32,
+    morph_start: f32,
+    morph_end: f32,
+    _padding: vec2f,
+};
+@group(1) @binding(0)
+var<uniform> patch_uniform: PatchUniform;
+
+// 与 TerrainPatch::morph_factor 保持一致
+const MIN_MORPH_RANGE: f32 = 1e-4;
+const SUN_DIRECTION: vec3f = vec3f(0.4, 0.8, 0.45);
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) morph_position: vec3f,
+    @location(2) normal: vec3f,
+    @location(3) morph_normal: vec3f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) normal: vec3f,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    // 按本级位置计算，公共边上的顶点在相邻地形块中得到同样的因子
+    let distance = length(in.position - patch_uniform.camera_position) * patch_uniform.fov_scale;
+    let range = max(patch_uniform.morph_end - patch_uniform.morph_start, MIN_MORPH_RANGE);
+    let morph_factor = saturate((distance - patch_uniform.morph_start) / range);
+
+    var out: VertexOutput;
+    out.world_position = mix(in.position, in.morph_position, morph_factor);
+    out.normal = normalize(mix(in.normal, in.morph_normal, morph_factor));
+    out.clip_position = camera.view_proj * vec4f(out.world_position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    if dot(camera.clip_plane.xyz, in.world_position) + camera.clip_plane.w < 0.0 {
+        discard;
+    }
+    // 平缓处为草地，陡峭处为岩石
+    let normal = normalize(in.normal);
+    let albedo = mix(vec3f(0.35, 0.3, 0.25), vec3f(0.25, 0.45, 0.15), smoothstep(0.7, 0.9, normal.y));
+    let diffuse = max(dot(normal, normalize(SUN_DIRECTION)), 0.0);
+    return vec4f(albedo * (0.2 + 0.8 * diffuse), 1.0);
+}
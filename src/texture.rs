@@ -0,0 +1,333 @@
+use std::fmt;
+
+// 纹理的标识，由持有纹理的系统分配与解析
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle(pub u32);
+
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    // 场景深度缓冲带模板，供传送门/镜面的模板遮罩使用
+    pub const DEPTH_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+    pub fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32, label: &str) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 100.0,
+            ..Default::default()
+        });
+
+        Self { texture, view, sampler }
+    }
+
+    // 上传 sRGB 颜色纹理，图像统一转换为 RGBA8
+    pub fn from_image(device: &wgpu::Device, queue: &wgpu::Queue, image: &image::DynamicImage, label: &str) -> Self {
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { texture, view, sampler }
+    }
+
+    // 1 × 1 的纯色纹理，用作未设置的纹理槽的默认值
+    pub fn from_color(device: &wgpu::Device, queue: &wgpu::Queue, color: [u8; 4], label: &str) -> Self {
+        let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba(color)));
+        Self::from_image(device, queue, &image, label)
+    }
+
+    // 离屏渲染目标，可被后续通道采样
+    pub fn create_render_target(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+        label: &str,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: usage | wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self { texture, view, sampler }
+    }
+
+    // 设备启用 TEXTURE_COMPRESSION_BC 时直接上传 BCn 块数据，否则在 CPU 上解码为 BGRA8
+    pub fn from_ktx2(bytes: &[u8], device: &wgpu::Device, queue: &wgpu::Queue, label: &str) -> Result<Self, Ktx2Error> {
+        let reader = ktx2::Reader::new(bytes).map_err(Ktx2Error::Parse)?;
+        let header = reader.header();
+        if header.supercompression_scheme.is_some() {
+            return Err(Ktx2Error::Supercompressed);
+        }
+        let format = header.format.ok_or(Ktx2Error::UnsupportedFormat(None))?;
+        let block = BlockFormat::from_ktx2(format).ok_or(Ktx2Error::UnsupportedFormat(Some(format)))?;
+
+        let (width, height) = (header.pixel_width, header.pixel_height.max(1));
+        // wgpu 要求块压缩纹理第 0 级的宽高是块尺寸的整数倍；软件解码时同样检查，不同设备上的结果一致
+        if width % 4 != 0 || height % 4 != 0 {
+            return Err(Ktx2Error::UnalignedSize { width, height });
+        }
+        let mip_level_count = header.level_count.max(1);
+        let compressed = device.features().contains(wgpu::Features::TEXTURE_COMPRESSION_BC);
+        let texture_format = if compressed { block.wgpu_format() } else { block.decoded_format() };
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: texture_format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (level, data) in reader.levels().enumerate().take(mip_level_count as usize) {
+            let level = level as u32;
+            let mip_width = (width >> level).max(1);
+            let mip_height = (height >> level).max(1);
+            let copy_texture = wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            };
+
+            if compressed {
+                // 压缩纹理按 4×4 块为单位复制，小于一个块的 mip 也要按整块写入
+                let blocks_wide = mip_width.div_ceil(4);
+                let blocks_high = mip_height.div_ceil(4);
+                queue.write_texture(
+                    copy_texture,
+                    data,
+                    wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(blocks_wide * block.block_size()),
+                        rows_per_image: Some(blocks_high),
+                    },
+                    wgpu::Extent3d {
+                        width: blocks_wide * 4,
+                        height: blocks_high * 4,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            } else {
+                let pixels = block.decode(data, mip_width, mip_height).map_err(Ktx2Error::Decode)?;
+                queue.write_texture(
+                    copy_texture,
+                    bytemuck::cast_slice(&pixels),
+                    wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(4 * mip_width),
+                        rows_per_image: Some(mip_height),
+                    },
+                    wgpu::Extent3d {
+                        width: mip_width,
+                        height: mip_height,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Ok(Self { texture, view, sampler })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+}
+
+#[derive(Debug)]
+pub enum Ktx2Error {
+    Parse(ktx2::ParseError),
+    UnsupportedFormat(Option<ktx2::Format>),
+    // zstd / zlib / BasisLZ 超压缩数据需要先解压
+    Supercompressed,
+    // 第 0 级的尺寸不是 4×4 块的整数倍
+    UnalignedSize { width: u32, height: u32 },
+    Decode(&'static str),
+}
+
+impl fmt::Display for Ktx2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ktx2Error::Parse(e) => write!(f, "failed to parse KTX2 file: {e}"),
+            Ktx2Error::UnsupportedFormat(format) => write!(f, "unsupported KTX2 format: {format:?}"),
+            Ktx2Error::Supercompressed => write!(f, "supercompressed KTX2 files are not supported"),
+            Ktx2Error::UnalignedSize { width, height } => {
+                write!(f, "block-compressed KTX2 texture is {width}x{height}, but its width and height must be multiples of 4")
+            }
+            Ktx2Error::Decode(e) => write!(f, "failed to decode block-compressed texture: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Ktx2Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockFormat {
+    Bc1 { srgb: bool },
+    Bc3 { srgb: bool },
+    Bc5,
+    Bc7 { srgb: bool },
+}
+
+impl BlockFormat {
+    fn from_ktx2(format: ktx2::Format) -> Option<Self> {
+        Some(match format {
+            ktx2::Format::BC1_RGBA_UNORM_BLOCK => BlockFormat::Bc1 { srgb: false },
+            ktx2::Format::BC1_RGBA_SRGB_BLOCK => BlockFormat::Bc1 { srgb: true },
+            ktx2::Format::BC3_UNORM_BLOCK => BlockFormat::Bc3 { srgb: false },
+            ktx2::Format::BC3_SRGB_BLOCK => BlockFormat::Bc3 { srgb: true },
+            ktx2::Format::BC5_UNORM_BLOCK => BlockFormat::Bc5,
+            ktx2::Format::BC7_UNORM_BLOCK => BlockFormat::Bc7 { srgb: false },
+            ktx2::Format::BC7_SRGB_BLOCK => BlockFormat::Bc7 { srgb: true },
+            _ => return None,
+        })
+    }
+
+    fn block_size(self) -> u32 {
+        match self {
+            BlockFormat::Bc1 { .. } => 8,
+            _ => 16,
+        }
+    }
+
+    fn wgpu_format(self) -> wgpu::TextureFormat {
+        match self {
+            BlockFormat::Bc1 { srgb: false } => wgpu::TextureFormat::Bc1RgbaUnorm,
+            BlockFormat::Bc1 { srgb: true } => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            BlockFormat::Bc3 { srgb: false } => wgpu::TextureFormat::Bc3RgbaUnorm,
+            BlockFormat::Bc3 { srgb: true } => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            BlockFormat::Bc5 => wgpu::TextureFormat::Bc5RgUnorm,
+            BlockFormat::Bc7 { srgb: false } => wgpu::TextureFormat::Bc7RgbaUnorm,
+            BlockFormat::Bc7 { srgb: true } => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+        }
+    }
+
+    // 软件解码输出的像素按 BGRA 字节序排列
+    fn decoded_format(self) -> wgpu::TextureFormat {
+        match self {
+            BlockFormat::Bc1 { srgb: true } | BlockFormat::Bc3 { srgb: true } | BlockFormat::Bc7 { srgb: true } => {
+                wgpu::TextureFormat::Bgra8UnormSrgb
+            }
+            _ => wgpu::TextureFormat::Bgra8Unorm,
+        }
+    }
+
+    fn decode(self, data: &[u8], width: u32, height: u32) -> Result<Vec<u32>, &'static str> {
+        let (width, height) = (width as usize, height as usize);
+        let mut pixels = vec![0u32; width * height];
+        match self {
+            BlockFormat::Bc1 { .. } => texture2ddecoder::decode_bc1a(data, width, height, &mut pixels)?,
+            BlockFormat::Bc3 { .. } => texture2ddecoder::decode_bc3(data, width, height, &mut pixels)?,
+            BlockFormat::Bc5 => texture2ddecoder::decode_bc5(data, width, height, &mut pixels)?,
+            BlockFormat::Bc7 { .. } => texture2ddecoder::decode_bc7(data, width, height, &mut pixels)?,
+        }
+        Ok(pixels)
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::mesh::MeshData;
+
+pub const ATLAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AtlasHandle(u32);
+
+// 图集中一张图片的纹理坐标范围，不含留白
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl UvRect {
+    // 把原图的 [0, 1] 纹理坐标映射到图集中
+    pub fn map(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            self.min[0] + uv[0] * (self.max[0] - self.min[0]),
+            self.min[1] + uv[1] * (self.max[1] - self.min[1]),
+        ]
+    }
+
+    // 就地修正网格的纹理坐标，使其采样图集中的对应区域
+    pub fn apply(&self, mesh: &mut MeshData) {
+        for vertex in &mut mesh.vertices {
+            vertex.tex_coords = self.map(vertex.tex_coords);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AtlasError {
+    // 单张图片加上留白就超过了 max_size
+    ImageTooLarge { label: String, width: u32, height: u32 },
+    AtlasFull { max_size: u32 },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::ImageTooLarge { label, width, height } => {
+                write!(f, "image `{label}` ({width}x{height}) does not fit into the atlas")
+            }
+            AtlasError::AtlasFull { max_size } => write!(f, "images do not fit into a {max_size}x{max_size} atlas"),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+struct AtlasImage {
+    label: String,
+    image: image::RgbaImage,
+}
+
+// 加载时把许多小纹理合并成一张，绘制时只需绑定一次
+#[derive(Default)]
+pub struct AtlasPacker {
+    images: Vec<AtlasImage>,
+}
+
+impl AtlasPacker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, label: &str, image: &image::DynamicImage) -> AtlasHandle {
+        self.images.push(AtlasImage { label: label.to_string(), image: image.to_rgba8() });
+        AtlasHandle(self.images.len() as u32 - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    // 图集边长从能容纳总面积的 2 的幂开始，放不下时加倍，直到 max_size
+    // padding 为图片之间的留白像素，用边缘像素填充，避免线性过滤时混入相邻图片
+    pub fn build(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        max_size: u32,
+        padding: u32,
+    ) -> Result<(wgpu::Texture, HashMap<AtlasHandle, UvRect>), AtlasError> {
+        let padded = |image: &image::RgbaImage| [image.width() + 2 * padding, image.height() + 2 * padding];
+        if let Some(entry) = self.images.iter().find(|entry| padded(&entry.image).into_iter().any(|side| side > max_size)) {
+            return Err(AtlasError::ImageTooLarge {
+                label: entry.label.clone(),
+                width: entry.image.width(),
+                height: entry.image.height(),
+            });
+        }
+
+        // 按高度从高到低排列，同一层的图片高度接近，浪费更少
+        let mut order = (0..self.images.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| std::cmp::Reverse(self.images[index].image.height()));
+        let sizes = order.iter().map(|&index| padded(&self.images[index].image)).collect::<Vec<_>>();
+
+        let area = sizes.iter().map(|[w, h]| *w as u64 * *h as u64).sum::<u64>();
+        let mut atlas_size = ((area as f64).sqrt().ceil() as u32).max(1).next_power_of_two().min(max_size);
+        let origins = loop {
+            if let Some(origins) = shelf_pack(&sizes, atlas_size) {
+                break origins;
+            }
+            if atlas_size >= max_size {
+                return Err(AtlasError::AtlasFull { max_size });
+            }
+            atlas_size = (atlas_size * 2).min(max_size);
+        };
+
+        let mut atlas = image::RgbaImage::new(atlas_size, atlas_size);
+        let mut rects = HashMap::with_capacity(self.images.len());
+        for (&index, origin) in order.iter().zip(origins) {
+            let image = &self.images[index].image;
+            let (width, height) = image.dimensions();
+            let [x0, y0] = [origin[0] + padding, origin[1] + padding];
+            // 空图片只占位，没有像素可复制
+            let rows = if width > 0 && height > 0 { height + 2 * padding } else { 0 };
+            for y in 0..rows {
+                for x in 0..width + 2 * padding {
+                    let source_x = x.saturating_sub(padding).min(width - 1);
+                    let source_y = y.saturating_sub(padding).min(height - 1);
+                    atlas.put_pixel(origin[0] + x, origin[1] + y, *image.get_pixel(source_x, source_y));
+                }
+            }
+            let scale = 1.0 / atlas_size as f32;
+            rects.insert(
+                AtlasHandle(index as u32),
+                UvRect {
+                    min: [x0 as f32 * scale, y0 as f32 * scale],
+                    max: [(x0 + width) as f32 * scale, (y0 + height) as f32 * scale],
+                },
+            );
+        }
+
+        let size = wgpu::Extent3d {
+            width: atlas_size,
+            height: atlas_size,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture Atlas"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ATLAS_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &atlas,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * atlas_size),
+                rows_per_image: Some(atlas_size),
+            },
+            size,
+        );
+
+        Ok((texture, rects))
+    }
+}
+
+// 从左到右排成一层，放不下时另起一层；返回每个矩形的左上角
+fn shelf_pack(sizes: &[[u32; 2]], atlas_size: u32) -> Option<Vec<[u32; 2]>> {
+    let mut origins = Vec::with_capacity(sizes.len());
+    let (mut x, mut y, mut shelf_height) = (0u32, 0u32, 0u32);
+
+    for &[width, height] in sizes {
+        if x + width > atlas_size {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        if x + width > atlas_size || y + height > atlas_size {
+            return None;
+        }
+
+        origins.push([x, y]);
+        x += width;
+        shelf_height = shelf_height.max(height);
+    }
+
+    Some(origins)
+}
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::asset_cache::AssetCache;
+use crate::gpu_memory::estimate_texture_bytes;
+use crate::texture::Texture;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextureBudget {
+    pub max_bytes: u64,
+}
+
+#[derive(Debug)]
+pub enum TextureCacheError {
+    Load { path: String, source: image::ImageError },
+}
+
+impl fmt::Display for TextureCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureCacheError::Load { path, source } => write!(f, "failed to load texture `{path}`: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for TextureCacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureCacheError::Load { source, .. } => Some(source),
+        }
+    }
+}
+
+struct CachedTexture {
+    texture: Texture,
+    size: u64,
+    // get 只借用 &self，绘制调用构建期间也能更新
+    last_used_frame: Cell<u64>,
+}
+
+// 按路径缓存纹理，超出预算时丢弃最久未使用的纹理，之后再用到时重新加载
+pub struct TextureCache {
+    budget: TextureBudget,
+    textures: HashMap<String, CachedTexture>,
+    total_bytes: u64,
+    frame: u64,
+}
+
+impl TextureCache {
+    pub fn new(budget: TextureBudget) -> Self {
+        Self {
+            budget,
+            textures: HashMap::new(),
+            total_bytes: 0,
+            frame: 0,
+        }
+    }
+
+    pub fn budget(&self) -> TextureBudget {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: TextureBudget) {
+        self.budget = budget;
+        self.evict(None);
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.textures.contains_key(path)
+    }
+
+    // 每帧开始时调用
+    pub fn begin_frame(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    // 只查找已驻留的纹理，并记录本帧用过
+    pub fn get(&self, path: &str) -> Option<&Texture> {
+        let entry = self.textures.get(path)?;
+        entry.last_used_frame.set(self.frame);
+        Some(&entry.texture)
+    }
+
+    // 未命中时先从 AssetCache 中的图像上传，没有再从磁盘读取
+    pub fn get_or_load(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &str,
+        assets: Option<&AssetCache>,
+    ) -> Result<&Texture, TextureCacheError> {
+        if !self.textures.contains_key(path) {
+            let texture = match assets.and_then(|assets| assets.image(path)) {
+                Some(image) => Texture::from_image(device, queue, &image, path),
+                None => {
+                    let image = image::open(path).map_err(|source| TextureCacheError::Load { path: path.to_string(), source })?;
+                    Texture::from_image(device, queue, &image, path)
+                }
+            };
+            let size = estimate_texture_bytes(&texture.texture);
+            self.total_bytes += size;
+            self.textures.insert(
+                path.to_string(),
+                CachedTexture { texture, size, last_used_frame: Cell::new(self.frame) },
+            );
+            self.evict(Some(path));
+        }
+        Ok(self.get(path).expect("texture was just loaded"))
+    }
+
+    // 本帧用过的纹理与 keep 不会被丢弃，它们本身超出预算时只记录警告
+    fn evict(&mut self, keep: Option<&str>) {
+        while self.total_bytes > self.budget.max_bytes {
+            let victim = self
+                .textures
+                .iter()
+                .filter(|(path, entry)| Some(path.as_str()) != keep && entry.last_used_frame.get() < self.frame)
+                .min_by_key(|(_, entry)| entry.last_used_frame.get())
+                .map(|(path, _)| path.clone());
+            let Some(victim) = victim else {
+                tracing::warn!(
+                    "texture cache: {} bytes in use this frame exceed the budget of {} bytes",
+                    self.total_bytes,
+                    self.budget.max_bytes,
+                );
+                break;
+            };
+            // 已录制的命令仍持有纹理的引用，drop 后要等 GPU 用完才真正释放
+            if let Some(entry) = self.textures.remove(&victim) {
+                self.total_bytes -= entry.size;
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+// 固定步长累加器：物理以固定频率推进，渲染按实际帧率进行
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    // 防止卡顿后一次追赶过多步（螺旋死亡）
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn from_hz(hz: f32) -> Self {
+        Self {
+            step: 1.0 / hz,
+            accumulator: 0.0,
+            max_steps: 8,
+        }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    // 累加经过的时间，返回本帧需要执行的物理步数
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed.as_secs_f32();
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+            if steps == self.max_steps {
+                self.accumulator = 0.0;
+                break;
+            }
+        }
+        steps
+    }
+
+    // 当前时刻在上一步与下一步之间的比例，范围 [0, 1)
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+}
@@ -0,0 +1,91 @@
+use cgmath::{ InnerSpace, Matrix4, Quaternion, Vector3, VectorSpace };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_position(position: Vector3<f32>) -> Self {
+        Self { position, ..Default::default() }
+    }
+
+    pub fn to_matrix(self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.position)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    // 在两个变换之间插值，旋转使用 nlerp（物理步长很小，足够平滑）
+    pub fn lerp(&self, other: &Transform, alpha: f32) -> Transform {
+        // 四元数 q 与 -q 表示同一旋转，取最短路径
+        let target = if self.rotation.dot(other.rotation) < 0.0 {
+            -other.rotation
+        } else {
+            other.rotation
+        };
+
+        Transform {
+            position: self.position.lerp(other.position, alpha),
+            rotation: self.rotation.nlerp(target, alpha),
+            scale: self.scale.lerp(other.scale, alpha),
+        }
+    }
+}
+
+// 上一个物理步结束时的变换
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreviousTransform(pub Transform);
+
+// 渲染时使用的混合结果
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterpolatedTransform(pub Transform);
+
+impl InterpolatedTransform {
+    pub fn blend(previous: &PreviousTransform, current: &Transform, alpha: f32) -> Self {
+        Self(previous.0.lerp(current, alpha))
+    }
+}
+
+// 上传到实例缓冲区的模型矩阵
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct InstanceRaw {
+    pub model: [[f32; 4]; 4],
+}
+
+impl InstanceRaw {
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4,
+        8 => Float32x4,
+    ];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            // 每个实例前进一次，而不是每个顶点
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+impl From<&InterpolatedTransform> for InstanceRaw {
+    fn from(transform: &InterpolatedTransform) -> Self {
+        Self { model: transform.0.to_matrix().into() }
+    }
+}
@@ -0,0 +1,95 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// 与 shader.wgsl 中的 MaterialUniform 保持一致
+struct MaterialUniform {
+    base_color: vec4f,
+    emissive_color: vec3f,
+    emissive_intensity: f32,
+    emissive_intensity_nits: f32,
+    roughness: f32,
+    metallic: f32,
+    ao: f32,
+    scatter_color: vec3f,
+    scatter_width: f32,
+    subsurface_weight: f32,
+    uv_rotation: f32,
+    uv_scale: vec2f,
+    uv_offset: vec2f,
+    uv_scroll: vec2f,
+    // 每个世界单位的纹理重复次数
+    triplanar_scale: f32,
+    // 法线各分量的混合指数
+    triplanar_sharpness: f32,
+};
+@group(1) @binding(0)
+var<uniform> material: MaterialUniform;
+
+@group(2) @binding(0)
+var t_albedo: texture_2d<f32>;
+@group(2) @binding(1)
+var s_albedo: sampler;
+
+const SUN_DIRECTION: vec3f = vec3f(0.4, 0.8, 0.45);
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+};
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4f,
+    @location(6) model_matrix_1: vec4f,
+    @location(7) model_matrix_2: vec4f,
+    @location(8) model_matrix_3: vec4f,
+};
+
+// 不需要纹理坐标，只传世界空间位置与法线
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) world_normal: vec3f,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    let world_position = model_matrix * vec4f(model.position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    out.world_normal = (model_matrix * vec4f(model.normal, 0.0)).xyz;
+    return out;
+}
+
+// 沿三个坐标轴各投影采样一次，按 |法线分量|^sharpness 归一化后混合
+fn triplanar_sample(world_position: vec3f, normal: vec3f) -> vec4f {
+    let p = world_position * material.triplanar_scale;
+    var weights = pow(abs(normal), vec3f(material.triplanar_sharpness));
+    weights /= max(weights.x + weights.y + weights.z, 1e-4);
+    let x = textureSample(t_albedo, s_albedo, p.yz);
+    let y = textureSample(t_albedo, s_albedo, p.xz);
+    let z = textureSample(t_albedo, s_albedo, p.xy);
+    return x * weights.x + y * weights.y + z * weights.z;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    if dot(camera.clip_plane.xyz, in.world_position) + camera.clip_plane.w < 0.0 {
+        discard;
+    }
+    let normal = normalize(in.world_normal);
+    let albedo = triplanar_sample(in.world_position, normal) * material.base_color;
+    let diffuse = max(dot(normal, normalize(SUN_DIRECTION)), 0.0);
+    return vec4f(albedo.rgb * (0.2 + 0.8 * diffuse), albedo.a);
+}
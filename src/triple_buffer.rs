@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+
+const FRAMES_IN_FLIGHT: usize = 3;
+
+// 三份轮换的 uniform 缓冲区：CPU 写入第 frame_index % 3 份时，GPU 仍可读取前两帧的那两份，
+// 避免等待 GPU 读完同一块内存。每份按动态偏移对齐存放 slots 个 T，供大量物体各自的 uniform 使用
+pub struct TripleBuffer<T: bytemuck::Pod> {
+    buffers: [wgpu::Buffer; FRAMES_IN_FLIGHT],
+    frame_index: usize,
+    // 相邻槽位之间的字节数，满足 min_uniform_buffer_offset_alignment
+    stride: wgpu::BufferAddress,
+    slots: usize,
+    _data: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> TripleBuffer<T> {
+    pub fn new(device: &wgpu::Device, slots: usize, label: &str) -> Self {
+        let slots = slots.max(1);
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let stride = wgpu::util::align_to(std::mem::size_of::<T>() as wgpu::BufferAddress, alignment);
+        let buffers = [0, 1, 2].map(|index| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("{label} {index}")),
+                size: stride * slots as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        Self {
+            buffers,
+            frame_index: 0,
+            stride,
+            slots,
+            _data: PhantomData,
+        }
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    pub fn stride(&self) -> wgpu::BufferAddress {
+        self.stride
+    }
+
+    // 本帧使用的缓冲区序号，用于从 create_bind_groups 的结果中选出绑定组
+    pub fn current_index(&self) -> usize {
+        self.frame_index % FRAMES_IN_FLIGHT
+    }
+
+    pub fn current(&self) -> &wgpu::Buffer {
+        &self.buffers[self.current_index()]
+    }
+
+    pub fn buffers(&self) -> &[wgpu::Buffer; FRAMES_IN_FLIGHT] {
+        &self.buffers
+    }
+
+    // 写入本帧缓冲区的第 slot 个槽位，返回绑定时使用的动态偏移
+    pub fn write(&self, queue: &wgpu::Queue, slot: usize, data: &T) -> u32 {
+        assert!(slot < self.slots, "triple buffer slot {slot} out of range ({} slots)", self.slots);
+        let offset = self.stride * slot as wgpu::BufferAddress;
+        queue.write_buffer(self.current(), offset, bytemuck::bytes_of(data));
+        offset as u32
+    }
+
+    // 从第 0 个槽位起按对齐间隔写入全部数据，只产生一次写入
+    pub fn write_all(&self, queue: &wgpu::Queue, data: &[T]) {
+        assert!(data.len() <= self.slots, "{} values do not fit in {} triple buffer slots", data.len(), self.slots);
+        let Some(last) = data.len().checked_sub(1) else {
+            return;
+        };
+        let element_size = std::mem::size_of::<T>();
+        let mut bytes = vec![0u8; self.stride as usize * last + element_size];
+        for (index, value) in data.iter().enumerate() {
+            let start = self.stride as usize * index;
+            bytes[start..start + element_size].copy_from_slice(bytemuck::bytes_of(value));
+        }
+        queue.write_buffer(self.current(), 0, &bytes);
+    }
+
+    // 每帧提交之后调用，下一帧写入另一份缓冲区
+    pub fn advance(&mut self) {
+        self.frame_index = self.frame_index.wrapping_add(1);
+    }
+
+    // 为三份缓冲区各创建一个绑定组，binding 处的条目绑定一个槽位大小，需使用动态偏移
+    pub fn create_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        binding: u32,
+        label: &str,
+    ) -> [wgpu::BindGroup; FRAMES_IN_FLIGHT] {
+        let size = wgpu::BufferSize::new(std::mem::size_of::<T>() as wgpu::BufferAddress);
+        [0, 1, 2].map(|index| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.buffers[index],
+                        offset: 0,
+                        size,
+                    }),
+                }],
+            })
+        })
+    }
+}
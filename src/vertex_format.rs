@@ -0,0 +1,196 @@
+use wgpu::util::RenderEncoder;
+
+// 一个着色器输入属性；在缓冲区中的位置由 AttributeSource 决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub shader_location: u32,
+    pub format: wgpu::VertexFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeSource {
+    // 与其他交错属性共用 interleaved_slot 上的缓冲区，offset 为在一个顶点内的字节偏移
+    Interleaved { offset: wgpu::BufferAddress },
+    // 单独占用 slot 上的缓冲区，紧密排列
+    Separate { slot: u32 },
+}
+
+// 持有属性数组的顶点缓冲区布局，slot 即 set_vertex_buffer 使用的槽位
+#[derive(Debug, Clone)]
+pub struct VertexBufferDesc {
+    pub slot: u32,
+    pub array_stride: wgpu::BufferAddress,
+    pub step_mode: wgpu::VertexStepMode,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl VertexBufferDesc {
+    pub fn layout(&self) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+}
+
+// 描述顶点属性来自哪些缓冲区：交错、逐属性分开，或两者混合
+// 阴影等只需要部分属性的通道用 select 取子集，交错缓冲区原样复用，不必另建只含位置的缓冲区
+#[derive(Debug, Clone)]
+pub struct VertexFormat {
+    attributes: Vec<(VertexAttribute, AttributeSource)>,
+    interleaved_slot: u32,
+    // 为 None 时取交错属性的最大结束位置
+    interleaved_stride: Option<wgpu::BufferAddress>,
+    step_mode: wgpu::VertexStepMode,
+}
+
+impl Default for VertexFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VertexFormat {
+    pub fn new() -> Self {
+        Self {
+            attributes: Vec::new(),
+            interleaved_slot: 0,
+            interleaved_stride: None,
+            step_mode: wgpu::VertexStepMode::Vertex,
+        }
+    }
+
+    pub fn interleaved_slot(mut self, slot: u32) -> Self {
+        self.interleaved_slot = slot;
+        self
+    }
+
+    // 交错缓冲区中一个顶点的字节数，顶点结构体带有不参与绘制的字段时需要显式指定
+    pub fn interleaved_stride(mut self, stride: wgpu::BufferAddress) -> Self {
+        self.interleaved_stride = Some(stride);
+        self
+    }
+
+    pub fn step_mode(mut self, step_mode: wgpu::VertexStepMode) -> Self {
+        self.step_mode = step_mode;
+        self
+    }
+
+    pub fn attribute(mut self, attribute: VertexAttribute, source: AttributeSource) -> Self {
+        assert!(
+            self.attributes.iter().all(|(existing, _)| existing.shader_location != attribute.shader_location),
+            "vertex attribute location {} is declared twice",
+            attribute.shader_location
+        );
+        self.attributes.push((attribute, source));
+        self
+    }
+
+    pub fn attributes(&self) -> &[(VertexAttribute, AttributeSource)] {
+        &self.attributes
+    }
+
+    // 只保留给定 location 的属性，来源与步长不变，因此可以绑定同样的缓冲区
+    pub fn select(&self, locations: &[u32]) -> VertexFormat {
+        VertexFormat {
+            attributes: self
+                .attributes
+                .iter()
+                .filter(|(attribute, _)| locations.contains(&attribute.shader_location))
+                .copied()
+                .collect(),
+            interleaved_stride: Some(self.stride_of_interleaved()),
+            ..self.clone()
+        }
+    }
+
+    fn stride_of_interleaved(&self) -> wgpu::BufferAddress {
+        self.interleaved_stride.unwrap_or_else(|| {
+            self.attributes
+                .iter()
+                .filter_map(|(attribute, source)| match source {
+                    AttributeSource::Interleaved { offset } => Some(offset + attribute.format.size()),
+                    AttributeSource::Separate { .. } => None,
+                })
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    // 用到的槽位，从小到大
+    pub fn slots(&self) -> Vec<u32> {
+        let mut slots: Vec<u32> = self
+            .attributes
+            .iter()
+            .map(|(_, source)| match source {
+                AttributeSource::Interleaved { .. } => self.interleaved_slot,
+                AttributeSource::Separate { slot } => *slot,
+            })
+            .collect();
+        slots.sort_unstable();
+        slots.dedup();
+        slots
+    }
+
+    // 按槽位顺序生成布局，可直接作为管线的 buffers；管线中的每个槽位都必须绑定缓冲区，
+    // 因此用到的槽位需从 0 开始连续。位置属性放在 0 号槽位时，select 出的阴影格式只需绑定一个缓冲区
+    pub fn buffer_descs(&self) -> Vec<VertexBufferDesc> {
+        let stride = self.stride_of_interleaved();
+        let slots = self.slots();
+        assert!(
+            slots.iter().enumerate().all(|(index, slot)| index as u32 == *slot),
+            "vertex buffer slots {slots:?} must be contiguous from 0"
+        );
+        slots
+            .into_iter()
+            .map(|slot| {
+                let mut array_stride = 0;
+                let attributes: Vec<_> = self
+                    .attributes
+                    .iter()
+                    .filter_map(|(attribute, source)| {
+                        let offset = match *source {
+                            AttributeSource::Interleaved { offset } if slot == self.interleaved_slot => {
+                                assert!(
+                                    offset + attribute.format.size() <= stride,
+                                    "interleaved vertex attribute at location {} exceeds the stride of {stride} bytes",
+                                    attribute.shader_location
+                                );
+                                array_stride = stride;
+                                offset
+                            }
+                            AttributeSource::Separate { slot: source_slot } if slot == source_slot => {
+                                array_stride = attribute.format.size();
+                                0
+                            }
+                            _ => return None,
+                        };
+                        Some(wgpu::VertexAttribute {
+                            format: attribute.format,
+                            offset,
+                            shader_location: attribute.shader_location,
+                        })
+                    })
+                    .collect();
+                let separate = self.attributes.iter().filter(|(_, source)| *source == AttributeSource::Separate { slot }).count();
+                assert!(
+                    separate == 0 || attributes.len() == 1,
+                    "vertex buffer slot {slot} is shared by a separate attribute and other attributes"
+                );
+                VertexBufferDesc { slot, array_stride, step_mode: self.step_mode, attributes }
+            })
+            .collect()
+    }
+
+    // buffers 为 (槽位, 缓冲区)，只绑定本格式用到的槽位；阴影通道可直接传入主通道的全部缓冲区
+    pub fn set_vertex_buffers<'a>(&self, render_pass: &mut impl RenderEncoder<'a>, buffers: &[(u32, &'a wgpu::Buffer)]) {
+        for slot in self.slots() {
+            let (_, buffer) = buffers
+                .iter()
+                .find(|(buffer_slot, _)| *buffer_slot == slot)
+                .unwrap_or_else(|| panic!("no vertex buffer provided for slot {slot}"));
+            render_pass.set_vertex_buffer(slot, buffer.slice(..));
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+use std::sync::atomic::{ AtomicU8, Ordering };
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+
+use crate::mesh::Vertex;
+use crate::texture::Texture;
+use crate::transform::InstanceRaw;
+
+use super::{ TileId, VirtualTextureConfig };
+
+const FEEDBACK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Uint;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FeedbackParams {
+    virtual_size: f32,
+    tiles_per_side: f32,
+    max_mip: f32,
+    mip_bias: f32,
+}
+
+enum Readback {
+    Idle,
+    // 复制命令已录制，等待随本帧提交
+    Copied,
+    Mapping(Arc<AtomicU8>),
+}
+
+const MAP_PENDING: u8 = 0;
+const MAP_READY: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+// 以降低的分辨率渲染瓦片 ID，回读到 CPU 后得到本帧需要的瓦片集合
+pub struct FeedbackPass {
+    pipeline: wgpu::RenderPipeline,
+    params_bind_group: wgpu::BindGroup,
+    target: wgpu::Texture,
+    target_view: wgpu::TextureView,
+    depth_texture: Texture,
+    readback_buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+    scale: u32,
+    readback: Readback,
+}
+
+impl FeedbackPass {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_layout: &wgpu::BindGroupLayout,
+        config: &VirtualTextureConfig,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Virtual Texture Feedback Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("feedback.wgsl").into()),
+        });
+
+        let params = FeedbackParams {
+            virtual_size: config.virtual_size as f32,
+            tiles_per_side: config.tiles_per_side(0) as f32,
+            max_mip: config.max_mip() as f32,
+            mip_bias: -(config.feedback_scale as f32).log2(),
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Virtual Texture Feedback Params"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("vt_feedback_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("vt_feedback_bind_group"),
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Virtual Texture Feedback Pipeline Layout"),
+            bind_group_layouts: &[camera_layout, &params_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Virtual Texture Feedback Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), InstanceRaw::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FEEDBACK_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        let (target, target_view, depth_texture, readback_buffer, padded_bytes_per_row) =
+            Self::create_targets(device, width / config.feedback_scale, height / config.feedback_scale);
+
+        Self {
+            pipeline,
+            params_bind_group,
+            target,
+            target_view,
+            depth_texture,
+            readback_buffer,
+            padded_bytes_per_row,
+            scale: config.feedback_scale,
+            readback: Readback::Idle,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView, Texture, wgpu::Buffer, u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Virtual Texture Feedback Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FEEDBACK_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_texture = Texture::create_render_target(
+            device,
+            width,
+            height,
+            Texture::DEPTH_FORMAT,
+            wgpu::TextureUsages::empty(),
+            "Virtual Texture Feedback Depth",
+        );
+
+        let padded_bytes_per_row = wgpu::util::align_to(4 * width, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Virtual Texture Feedback Readback"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        (target, target_view, depth_texture, readback_buffer, padded_bytes_per_row)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        (self.target, self.target_view, self.depth_texture, self.readback_buffer, self.padded_bytes_per_row) =
+            Self::create_targets(device, width / self.scale, height / self.scale);
+        self.readback = Readback::Idle;
+    }
+
+    // 调用方在返回的通道上设置相机绑定组（组 0）并绘制使用虚拟纹理的几何体
+    pub fn begin<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Virtual Texture Feedback Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(1, &self.params_bind_group, &[]);
+        render_pass
+    }
+
+    // 上一次回读完成之前不会再复制，反馈结果因此会延迟若干帧
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if !matches!(self.readback, Readback::Idle) {
+            return;
+        }
+        encoder.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(self.target.height()),
+                },
+            },
+            self.target.size(),
+        );
+        self.readback = Readback::Copied;
+    }
+
+    // 在复制命令提交之后调用：发起映射，或在映射完成时解析出瓦片请求
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<HashSet<TileId>> {
+        match &self.readback {
+            Readback::Idle => None,
+            Readback::Copied => {
+                let status = Arc::new(AtomicU8::new(MAP_PENDING));
+                let callback_status = status.clone();
+                self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                    callback_status.store(if result.is_ok() { MAP_READY } else { MAP_FAILED }, Ordering::Release);
+                });
+                self.readback = Readback::Mapping(status);
+                None
+            }
+            Readback::Mapping(status) => {
+                device.poll(wgpu::Maintain::Poll);
+                match status.load(Ordering::Acquire) {
+                    MAP_PENDING => return None,
+                    MAP_FAILED => {
+                        self.readback = Readback::Idle;
+                        return None;
+                    }
+                    _ => {}
+                }
+
+                let mut tiles = HashSet::new();
+                {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    let row_bytes = 4 * self.target.width() as usize;
+                    for row in data.chunks_exact(self.padded_bytes_per_row as usize) {
+                        for texel in row[..row_bytes].chunks_exact(4) {
+                            if texel[3] != 0 {
+                                tiles.insert(TileId { x: texel[0] as u32, y: texel[1] as u32, mip: texel[2] as u32 });
+                            }
+                        }
+                    }
+                }
+                self.readback_buffer.unmap();
+                self.readback = Readback::Idle;
+                Some(tiles)
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// 反馈通道：以低分辨率渲染场景，每个像素输出需要的虚拟纹理瓦片（x, y, mip）
+struct CameraUniform {
+    view_proj: mat4x4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct FeedbackParams {
+    virtual_size: f32,
+    // mip 0 每行的瓦片数
+    tiles_per_side: f32,
+    max_mip: f32,
+    // 反馈目标分辨率较低，屏幕空间导数偏大，需要补偿
+    mip_bias: f32,
+};
+@group(1) @binding(0)
+var<uniform> params: FeedbackParams;
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) tex_coords: vec2f,
+};
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4f,
+    @location(6) model_matrix_1: vec4f,
+    @location(7) model_matrix_2: vec4f,
+    @location(8) model_matrix_3: vec4f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0);
+    out.tex_coords = model.tex_coords;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4u {
+    let texel = in.tex_coords * params.virtual_size;
+    let dx = dpdx(texel);
+    let dy = dpdy(texel);
+    let rho = max(dot(dx, dx), dot(dy, dy));
+    let mip = u32(clamp(floor(0.5 * log2(max(rho, 1e-8)) + params.mip_bias), 0.0, params.max_mip));
+
+    let tiles = u32(params.tiles_per_side) >> mip;
+    let tile = min(vec2u(fract(in.tex_coords) * f32(tiles)), vec2u(tiles - 1u));
+    // alpha 为 255 表示有效，清屏值 0 表示没有几何体
+    return vec4u(tile, mip, 255u);
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use super::TileId;
+
+pub type TileLoadError = Box<dyn std::error::Error + Send + Sync>;
+
+// 在工作线程上调用，返回 tile_size × tile_size 的 RGBA8 像素
+pub trait TileLoader: Send + Sync + 'static {
+    fn load(&self, tile: TileId) -> Result<Vec<u8>, TileLoadError>;
+}
+
+// 从磁盘读取预先切好的瓦片：<root>/<mip>/<x>_<y>.png
+pub struct DiskTileLoader {
+    root: PathBuf,
+    tile_size: u32,
+}
+
+impl DiskTileLoader {
+    pub fn new(root: impl Into<PathBuf>, tile_size: u32) -> Self {
+        Self { root: root.into(), tile_size }
+    }
+}
+
+impl TileLoader for DiskTileLoader {
+    fn load(&self, tile: TileId) -> Result<Vec<u8>, TileLoadError> {
+        let path = self.root.join(tile.mip.to_string()).join(format!("{}_{}.png", tile.x, tile.y));
+        let image = image::open(&path)?.to_rgba8();
+        if image.dimensions() != (self.tile_size, self.tile_size) {
+            return Err(format!(
+                "tile {} is {:?}, expected {}x{}",
+                path.display(),
+                image.dimensions(),
+                self.tile_size,
+                self.tile_size,
+            ).into());
+        }
+        Ok(image.into_raw())
+    }
+}
@@ -0,0 +1,408 @@
+mod feedback;
+pub mod loader;
+
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::sync::mpsc::{ self, Receiver, Sender };
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+
+pub use feedback::FeedbackPass;
+use loader::{ TileLoadError, TileLoader };
+
+// 使用虚拟纹理的着色器需要把这段源码拼接在前面
+pub const SAMPLE_WGSL: &str = include_str!("sample.wgsl");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileId {
+    pub x: u32,
+    pub y: u32,
+    pub mip: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualTextureConfig {
+    pub virtual_size: u32,
+    pub tile_size: u32,
+    pub physical_size: u32,
+    // 反馈通道分辨率为屏幕的 1/feedback_scale
+    pub feedback_scale: u32,
+    // 每帧最多上传的瓦片数，避免一次加载过多造成卡顿
+    pub max_uploads_per_frame: usize,
+}
+
+impl Default for VirtualTextureConfig {
+    fn default() -> Self {
+        Self {
+            virtual_size: 16384,
+            tile_size: 128,
+            physical_size: 4096,
+            feedback_scale: 8,
+            max_uploads_per_frame: 16,
+        }
+    }
+}
+
+impl VirtualTextureConfig {
+    pub fn tiles_per_side(&self, mip: u32) -> u32 {
+        (self.virtual_size / self.tile_size) >> mip
+    }
+
+    // 最粗一级只有一个瓦片
+    pub fn max_mip(&self) -> u32 {
+        self.tiles_per_side(0).trailing_zeros()
+    }
+
+    pub fn physical_tiles(&self) -> u32 {
+        self.physical_size / self.tile_size
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VirtualTextureParams {
+    virtual_size: f32,
+    tiles_per_side: f32,
+    max_mip: f32,
+    physical_tiles: f32,
+}
+
+struct ResidentTile {
+    slot: [u32; 2],
+    last_used: u64,
+}
+
+// 虚拟纹理：只有被反馈通道请求到的瓦片才会加载到较小的物理纹理中，
+// 间接纹理记录每个虚拟瓦片在物理纹理中的槽位
+pub struct VirtualTexture {
+    pub config: VirtualTextureConfig,
+    physical: wgpu::Texture,
+    indirection: wgpu::Texture,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    feedback: FeedbackPass,
+
+    loader: Arc<dyn TileLoader>,
+    sender: Sender<(TileId, Result<Vec<u8>, TileLoadError>)>,
+    receiver: Receiver<(TileId, Result<Vec<u8>, TileLoadError>)>,
+    loaded: VecDeque<(TileId, Vec<u8>)>,
+
+    resident: HashMap<TileId, ResidentTile>,
+    pending: HashSet<TileId>,
+    free_slots: Vec<[u32; 2]>,
+    frame: u64,
+}
+
+impl VirtualTexture {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera_layout: &wgpu::BindGroupLayout,
+        config: VirtualTextureConfig,
+        loader: Arc<dyn TileLoader>,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        // 反馈目标与间接纹理都以 8 位整数存储瓦片坐标
+        assert!(config.tiles_per_side(0) <= 256, "virtual texture has more than 256 tiles per side");
+        assert!(config.physical_tiles() <= 256, "physical texture has more than 256 slots per side");
+
+        let physical = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Virtual Texture Physical"),
+            size: wgpu::Extent3d {
+                width: config.physical_size,
+                height: config.physical_size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        // 每个 mip 一层，纹素 = (槽位 x, 槽位 y, 保留, 是否驻留)
+        let indirection = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Virtual Texture Indirection"),
+            size: wgpu::Extent3d {
+                width: config.tiles_per_side(0),
+                height: config.tiles_per_side(0),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: config.max_mip() + 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Uint,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let params = VirtualTextureParams {
+            virtual_size: config.virtual_size as f32,
+            tiles_per_side: config.tiles_per_side(0) as f32,
+            max_mip: config.max_mip() as f32,
+            physical_tiles: config.physical_tiles() as f32,
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Virtual Texture Params"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Virtual Texture Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("virtual_texture_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Uint,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let physical_view = physical.create_view(&wgpu::TextureViewDescriptor::default());
+        let indirection_view = indirection.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("virtual_texture_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&physical_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&indirection_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let feedback = FeedbackPass::new(device, camera_layout, &config, width, height);
+        let slots = config.physical_tiles();
+        let free_slots = (0..slots).flat_map(|y| (0..slots).map(move |x| [x, y])).rev().collect();
+        let (sender, receiver) = mpsc::channel();
+
+        let mut virtual_texture = Self {
+            config,
+            physical,
+            indirection,
+            bind_group_layout,
+            bind_group,
+            feedback,
+            loader,
+            sender,
+            receiver,
+            loaded: VecDeque::new(),
+            resident: HashMap::new(),
+            pending: HashSet::new(),
+            free_slots,
+            frame: 0,
+        };
+
+        // 最粗一级同步加载，保证采样时总有可退回的瓦片
+        let root = virtual_texture.root_tile();
+        match virtual_texture.loader.load(root) {
+            Ok(data) => virtual_texture.upload_tile(queue, root, &data),
+            Err(e) => tracing::error!(?root, error = %e, "failed to load root virtual texture tile"),
+        }
+        virtual_texture
+    }
+
+    fn root_tile(&self) -> TileId {
+        TileId { x: 0, y: 0, mip: self.config.max_mip() }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn feedback_pass(&mut self) -> &mut FeedbackPass {
+        &mut self.feedback
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.feedback.resize(device, width, height);
+    }
+
+    pub fn resident_count(&self) -> usize {
+        self.resident.len()
+    }
+
+    // 每帧在录制命令之前调用：处理反馈回读、派发加载任务并上传已加载的瓦片
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.frame += 1;
+
+        if let Some(requests) = self.feedback.poll(device) {
+            // 同时请求所有祖先瓦片，细节瓦片加载完成前可以退回
+            let mut tiles = HashSet::new();
+            for tile in requests {
+                let mut tile = tile;
+                loop {
+                    tiles.insert(tile);
+                    if tile.mip >= self.config.max_mip() {
+                        break;
+                    }
+                    tile = TileId { x: tile.x / 2, y: tile.y / 2, mip: tile.mip + 1 };
+                }
+            }
+            for tile in tiles {
+                self.request(tile);
+            }
+        }
+
+        for (tile, result) in self.receiver.try_iter() {
+            match result {
+                Ok(data) => self.loaded.push_back((tile, data)),
+                Err(e) => {
+                    self.pending.remove(&tile);
+                    tracing::warn!(?tile, error = %e, "failed to load virtual texture tile");
+                }
+            }
+        }
+
+        for _ in 0..self.config.max_uploads_per_frame {
+            let Some((tile, data)) = self.loaded.pop_front() else {
+                break;
+            };
+            self.pending.remove(&tile);
+            self.upload_tile(queue, tile, &data);
+        }
+    }
+
+    fn request(&mut self, tile: TileId) {
+        if let Some(resident) = self.resident.get_mut(&tile) {
+            resident.last_used = self.frame;
+            return;
+        }
+        if !self.pending.insert(tile) {
+            return;
+        }
+
+        let loader = self.loader.clone();
+        let sender = self.sender.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = sender.send((tile, loader.load(tile)));
+        });
+    }
+
+    fn upload_tile(&mut self, queue: &wgpu::Queue, tile: TileId, data: &[u8]) {
+        let tile_size = self.config.tile_size;
+        if data.len() != (tile_size * tile_size * 4) as usize {
+            tracing::warn!(?tile, len = data.len(), "virtual texture tile has the wrong size");
+            return;
+        }
+        let Some(slot) = self.allocate_slot(queue) else {
+            // 所有槽位都在本帧使用中，下一次反馈时再请求
+            return;
+        };
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.physical,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: slot[0] * tile_size, y: slot[1] * tile_size, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * tile_size),
+                rows_per_image: Some(tile_size),
+            },
+            wgpu::Extent3d {
+                width: tile_size,
+                height: tile_size,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.write_indirection(queue, tile, [slot[0] as u8, slot[1] as u8, 0, 255]);
+        self.resident.insert(tile, ResidentTile { slot, last_used: self.frame });
+    }
+
+    // 没有空闲槽位时淘汰最久未使用的瓦片，最粗一级永不淘汰
+    fn allocate_slot(&mut self, queue: &wgpu::Queue) -> Option<[u32; 2]> {
+        if let Some(slot) = self.free_slots.pop() {
+            return Some(slot);
+        }
+
+        let root = self.root_tile();
+        let (&victim, _) = self
+            .resident
+            .iter()
+            .filter(|(tile, resident)| **tile != root && resident.last_used < self.frame)
+            .min_by_key(|(_, resident)| resident.last_used)?;
+        let evicted = self.resident.remove(&victim)?;
+        self.write_indirection(queue, victim, [0; 4]);
+        Some(evicted.slot)
+    }
+
+    fn write_indirection(&self, queue: &wgpu::Queue, tile: TileId, texel: [u8; 4]) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.indirection,
+                mip_level: tile.mip,
+                origin: wgpu::Origin3d { x: tile.x, y: tile.y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &texel,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4),
+                rows_per_image: Some(1),
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
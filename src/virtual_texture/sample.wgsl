@@ -0,0 +1,44 @@
+// 虚拟纹理采样函数，拼接到使用虚拟纹理的着色器源码之前
+// 绑定组编号固定为 2，与 VirtualTexture::bind_group_layout 对应
+struct VirtualTextureParams {
+    virtual_size: f32,
+    tiles_per_side: f32,
+    max_mip: f32,
+    // 物理纹理每行的瓦片槽位数
+    physical_tiles: f32,
+};
+
+@group(2) @binding(0)
+var vt_physical: texture_2d<f32>;
+@group(2) @binding(1)
+var vt_sampler: sampler;
+@group(2) @binding(2)
+var vt_indirection: texture_2d<u32>;
+@group(2) @binding(3)
+var<uniform> vt_params: VirtualTextureParams;
+
+fn vt_tile_coord(uv: vec2f, mip: u32) -> vec2u {
+    let tiles = u32(vt_params.tiles_per_side) >> mip;
+    return min(vec2u(fract(uv) * f32(tiles)), vec2u(tiles - 1u));
+}
+
+// 所需瓦片未驻留时逐级退回更粗的 mip，最粗一级始终驻留
+fn sample_virtual(uv: vec2f) -> vec4f {
+    let texel = uv * vt_params.virtual_size;
+    let dx = dpdx(texel);
+    let dy = dpdy(texel);
+    let rho = max(dot(dx, dx), dot(dy, dy));
+    let max_mip = u32(vt_params.max_mip);
+    var mip = u32(clamp(floor(0.5 * log2(max(rho, 1e-8))), 0.0, vt_params.max_mip));
+
+    var entry = textureLoad(vt_indirection, vt_tile_coord(uv, mip), i32(mip));
+    while entry.a == 0u && mip < max_mip {
+        mip += 1u;
+        entry = textureLoad(vt_indirection, vt_tile_coord(uv, mip), i32(mip));
+    }
+
+    let tiles = f32(u32(vt_params.tiles_per_side) >> mip);
+    let in_tile = fract(fract(uv) * tiles);
+    let physical_uv = (vec2f(entry.xy) + in_tile) / vt_params.physical_tiles;
+    return textureSampleLevel(vt_physical, vt_sampler, physical_uv, 0.0);
+}
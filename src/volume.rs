@@ -0,0 +1,254 @@
+use cgmath::{ InnerSpace, Matrix4, SquareMatrix, Transform as _, Vector3 };
+use crate::camera::Camera;
+use crate::primitives;
+use crate::transform::Transform;
+
+pub const DENSITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+
+#[derive(Debug, Clone, Copy)]
+pub struct VolumeConfig {
+    pub absorption: f32,
+    pub scattering: f32,
+    // 每条射线在包围盒内的采样次数
+    pub step_count: u32,
+    // 世界空间中指向光源的方向
+    pub light_dir: [f32; 3],
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            absorption: 1.0,
+            scattering: 4.0,
+            step_count: 64,
+            light_dir: [0.3, 1.0, 0.5],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VolumeUniform {
+    model: [[f32; 4]; 4],
+    inverse_model: [[f32; 4]; 4],
+    view_proj: [[f32; 4]; 4],
+    camera_local: [f32; 4],
+    light_dir_local: [f32; 3],
+    absorption: f32,
+    scattering: f32,
+    step_count: u32,
+    _padding: [u32; 2],
+}
+
+// 创建 R32Float 三维密度纹理，data 按 x、y、z 顺序排列
+pub fn create_density_texture(device: &wgpu::Device, queue: &wgpu::Queue, size: [u32; 3], data: &[f32]) -> wgpu::Texture {
+    assert_eq!(data.len(), (size[0] * size[1] * size[2]) as usize, "density data does not match the texture size");
+
+    let extent = wgpu::Extent3d {
+        width: size[0],
+        height: size[1],
+        depth_or_array_layers: size[2],
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Volume Density Texture"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D3,
+        format: DENSITY_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        bytemuck::cast_slice(data),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * size[0]),
+            rows_per_image: Some(size[1]),
+        },
+        extent,
+    );
+    texture
+}
+
+// 用光线步进渲染一个以密度场填充的包围盒（云、烟、火）
+pub struct VolumeRenderer {
+    pub config: VolumeConfig,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+impl VolumeRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        density: &wgpu::Texture,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        config: VolumeConfig,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Volume Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("volume.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("volume_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Volume Uniform Buffer"),
+            size: std::mem::size_of::<VolumeUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &uniform_buffer, density);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Volume Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Volume Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<crate::mesh::Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                // 绘制背面，相机进入包围盒后体积仍然可见
+                cull_mode: Some(wgpu::Face::Front),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        let cube = primitives::cube(0.5);
+        let (vertex_buffer, index_buffer) = cube.upload(device, queue);
+
+        Self {
+            config,
+            pipeline,
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+            vertex_buffer,
+            index_buffer,
+            index_count: cube.index_count(),
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        density: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let view = density.create_view(&wgpu::TextureViewDescriptor::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("volume_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+            ],
+        })
+    }
+
+    pub fn set_density(&mut self, device: &wgpu::Device, density: &wgpu::Texture) {
+        self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.uniform_buffer, density);
+    }
+
+    // transform 决定包围盒在世界中的位置与大小
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, transform: &Transform) {
+        let model = transform.to_matrix();
+        let inverse_model = model.invert().unwrap_or(Matrix4::identity());
+        let camera_local = inverse_model.transform_point(camera.eye);
+        let light_dir_local = inverse_model
+            .transform_vector(Vector3::from(self.config.light_dir))
+            .normalize();
+
+        let uniform = VolumeUniform {
+            model: model.into(),
+            inverse_model: inverse_model.into(),
+            view_proj: camera.build_view_projection_matrix().into(),
+            camera_local: camera_local.to_homogeneous().into(),
+            light_dir_local: light_dir_local.into(),
+            absorption: self.config.absorption,
+            scattering: self.config.scattering,
+            step_count: self.config.step_count,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
@@ -0,0 +1,111 @@
+struct VolumeUniform {
+    model: mat4x4f,
+    inverse_model: mat4x4f,
+    view_proj: mat4x4f,
+    // 局部空间中的相机位置
+    camera_local: vec4f,
+    // 局部空间中指向光源的方向
+    light_dir_local: vec3f,
+    absorption: f32,
+    scattering: f32,
+    step_count: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> volume: VolumeUniform;
+@group(0) @binding(1)
+var t_density: texture_3d<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) local_position: vec3f,
+};
+
+// 包围盒为局部空间 [-0.5, 0.5]^3
+@vertex
+fn vs_main(@location(0) position: vec3f) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = volume.view_proj * volume.model * vec4f(position, 1.0);
+    out.local_position = position;
+    return out;
+}
+
+// R32Float 不可过滤，手动三线性插值
+fn sample_density(uvw: vec3f) -> f32 {
+    let dims = vec3<i32>(textureDimensions(t_density));
+    let p = clamp(uvw, vec3f(0.0), vec3f(1.0)) * vec3f(dims) - 0.5;
+    let base = vec3<i32>(floor(p));
+    let f = fract(p);
+    let max_texel = dims - vec3<i32>(1);
+
+    var result = 0.0;
+    for (var i = 0; i < 8; i++) {
+        let offset = vec3<i32>(i & 1, (i >> 1u) & 1, (i >> 2u) & 1);
+        let texel = clamp(base + offset, vec3<i32>(0), max_texel);
+        let w = mix(1.0 - f, f, vec3f(offset));
+        result += textureLoad(t_density, texel, 0).r * w.x * w.y * w.z;
+    }
+    return result;
+}
+
+// 射线与包围盒求交，返回 (t_near, t_far)
+fn intersect_box(origin: vec3f, direction: vec3f) -> vec2f {
+    let inv_dir = 1.0 / direction;
+    let t0 = (vec3f(-0.5) - origin) * inv_dir;
+    let t1 = (vec3f(0.5) - origin) * inv_dir;
+    let t_min = min(t0, t1);
+    let t_max = max(t0, t1);
+    return vec2f(max(max(t_min.x, t_min.y), t_min.z), min(min(t_max.x, t_max.y), t_max.z));
+}
+
+// 朝光源方向粗略步进，估计到达该点的光照透射率
+fn light_transmittance(position: vec3f) -> f32 {
+    let hit = intersect_box(position, volume.light_dir_local);
+    let step = max(hit.y, 0.0) / 4.0;
+    let extinction = volume.absorption + volume.scattering;
+    var optical_depth = 0.0;
+    for (var i = 0; i < 4; i++) {
+        let p = position + volume.light_dir_local * step * (f32(i) + 0.5);
+        optical_depth += sample_density(p + 0.5) * extinction * step;
+    }
+    return exp(-optical_depth);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let origin = volume.camera_local.xyz;
+    let direction = normalize(in.local_position - origin);
+    let hit = intersect_box(origin, direction);
+    // 相机在包围盒内时从相机位置开始
+    let t_start = max(hit.x, 0.0);
+    let t_end = hit.y;
+    if t_end <= t_start {
+        discard;
+    }
+
+    let step_count = max(volume.step_count, 1u);
+    let dt = (t_end - t_start) / f32(step_count);
+    let extinction = volume.absorption + volume.scattering;
+
+    var transmittance = 1.0;
+    var color = vec3f(0.0);
+    for (var i = 0u; i < step_count; i++) {
+        let position = origin + direction * (t_start + (f32(i) + 0.5) * dt);
+        let density = sample_density(position + 0.5);
+        if density > 0.0 {
+            // Beer-Lambert 衰减
+            let step_transmittance = exp(-density * extinction * dt);
+            // 单次散射，各向同性相函数与光源强度合并为 1
+            let in_scattered = volume.scattering * density * light_transmittance(position);
+            color += transmittance * in_scattered * (1.0 - step_transmittance) / max(density * extinction, 1e-5);
+            transmittance *= step_transmittance;
+            // 几乎不透明时提前退出
+            if 1.0 - transmittance > 0.99 {
+                break;
+            }
+        }
+    }
+
+    // 预乘 alpha
+    return vec4f(color, 1.0 - transmittance);
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use crate::mesh::Vertex;
+
+pub const MAX_DEPTH: u32 = 10;
+
+// 子节点编号为 0 表示空（根节点不会成为子节点）
+const EMPTY: u32 = 0;
+
+// 稀疏体素八叉树，边长为 2^max_depth
+// 内部节点存 8 个子节点下标；最后一层内部节点的子下标指向 voxels
+pub struct SparseVoxelOctree {
+    max_depth: u32,
+    nodes: Vec<[u32; 8]>,
+    voxels: Vec<u32>,
+    // voxels 中与之对应的槽位是否有值
+    occupied: Vec<bool>,
+}
+
+impl SparseVoxelOctree {
+    pub fn new(max_depth: u32) -> Self {
+        assert!((1..=MAX_DEPTH).contains(&max_depth), "octree depth must be in 1..={MAX_DEPTH}");
+        Self {
+            max_depth,
+            nodes: vec![[EMPTY; 8]],
+            voxels: vec![0],
+            occupied: vec![false],
+        }
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    // 每条轴上的体素数
+    pub fn size(&self) -> u32 {
+        1 << self.max_depth
+    }
+
+    fn octant(&self, x: u32, y: u32, z: u32, level: u32) -> usize {
+        let bit = self.max_depth - 1 - level;
+        (((x >> bit) & 1) | (((y >> bit) & 1) << 1) | (((z >> bit) & 1) << 2)) as usize
+    }
+
+    fn in_bounds(&self, x: u32, y: u32, z: u32) -> bool {
+        let size = self.size();
+        x < size && y < size && z < size
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, z: u32, voxel: u32) {
+        assert!(self.in_bounds(x, y, z), "voxel ({x}, {y}, {z}) is outside the octree");
+
+        let mut node = 0;
+        for level in 0..self.max_depth - 1 {
+            let octant = self.octant(x, y, z, level);
+            if self.nodes[node][octant] == EMPTY {
+                self.nodes.push([EMPTY; 8]);
+                self.nodes[node][octant] = (self.nodes.len() - 1) as u32;
+            }
+            node = self.nodes[node][octant] as usize;
+        }
+
+        let octant = self.octant(x, y, z, self.max_depth - 1);
+        let slot = match self.nodes[node][octant] {
+            EMPTY => {
+                self.voxels.push(voxel);
+                self.occupied.push(true);
+                let slot = self.voxels.len() - 1;
+                self.nodes[node][octant] = slot as u32;
+                slot
+            }
+            slot => slot as usize,
+        };
+        self.voxels[slot] = voxel;
+        self.occupied[slot] = true;
+    }
+
+    // 只清空体素，不回收中间节点
+    pub fn remove(&mut self, x: u32, y: u32, z: u32) -> Option<u32> {
+        let slot = self.find_slot(x, y, z)?;
+        self.occupied[slot] = false;
+        Some(self.voxels[slot])
+    }
+
+    pub fn get(&self, x: u32, y: u32, z: u32) -> Option<u32> {
+        self.find_slot(x, y, z).map(|slot| self.voxels[slot])
+    }
+
+    fn find_slot(&self, x: u32, y: u32, z: u32) -> Option<usize> {
+        if !self.in_bounds(x, y, z) {
+            return None;
+        }
+
+        let mut node = 0;
+        for level in 0..self.max_depth - 1 {
+            match self.nodes[node][self.octant(x, y, z, level)] {
+                EMPTY => return None,
+                child => node = child as usize,
+            }
+        }
+        match self.nodes[node][self.octant(x, y, z, self.max_depth - 1)] {
+            EMPTY => None,
+            slot => self.occupied[slot as usize].then_some(slot as usize),
+        }
+    }
+
+    // 深度优先遍历所有体素，返回 (x, y, z, voxel)
+    pub fn iter_leaves(&self) -> impl Iterator<Item = (u32, u32, u32, u32)> + '_ {
+        let mut stack = vec![(0usize, 0u32, [0u32; 3])];
+        let mut pending = Vec::new();
+
+        std::iter::from_fn(move || loop {
+            if let Some(leaf) = pending.pop() {
+                return Some(leaf);
+            }
+            let (node, level, origin) = stack.pop()?;
+            let half = 1 << (self.max_depth - 1 - level);
+
+            for (octant, &child) in self.nodes[node].iter().enumerate() {
+                if child == EMPTY {
+                    continue;
+                }
+                let child_origin = [
+                    origin[0] + (octant as u32 & 1) * half,
+                    origin[1] + ((octant as u32 >> 1) & 1) * half,
+                    origin[2] + ((octant as u32 >> 2) & 1) * half,
+                ];
+                if level + 1 == self.max_depth {
+                    if self.occupied[child as usize] {
+                        pending.push((child_origin[0], child_origin[1], child_origin[2], self.voxels[child as usize]));
+                    }
+                } else {
+                    stack.push((child as usize, level + 1, child_origin));
+                }
+            }
+        })
+    }
+
+    // 贪心网格化：每个切片内把相同体素值的相邻面合并成尽量大的矩形。
+    // 体素边长为 1，索引使用 u32 以支持大场景
+    pub fn greedy_mesh(&self) -> (Vec<Vertex>, Vec<u32>) {
+        let voxels: HashMap<[u32; 3], u32> = self.iter_leaves().map(|(x, y, z, voxel)| ([x, y, z], voxel)).collect();
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for axis in 0..3 {
+            let u_axis = (axis + 1) % 3;
+            let v_axis = (axis + 2) % 3;
+
+            for positive in [true, false] {
+                // 按面所在平面分组：(平面坐标) -> [(u, v, voxel)]
+                let mut slices: HashMap<u32, Vec<(u32, u32, u32)>> = HashMap::new();
+                for (&position, &voxel) in &voxels {
+                    let mut neighbor = position;
+                    let visible = if positive {
+                        neighbor[axis] += 1;
+                        !voxels.contains_key(&neighbor)
+                    } else {
+                        neighbor[axis] = neighbor[axis].wrapping_sub(1);
+                        position[axis] == 0 || !voxels.contains_key(&neighbor)
+                    };
+                    if visible {
+                        let plane = position[axis] + positive as u32;
+                        slices.entry(plane).or_default().push((position[u_axis], position[v_axis], voxel));
+                    }
+                }
+
+                for (plane, faces) in slices {
+                    for (u, v, width, height) in greedy_rectangles(&faces) {
+                        let corner = |du: u32, dv: u32| {
+                            let mut p = [0.0; 3];
+                            p[axis] = plane as f32;
+                            p[u_axis] = (u + du) as f32;
+                            p[v_axis] = (v + dv) as f32;
+                            p
+                        };
+                        let mut normal = [0.0; 3];
+                        normal[axis] = if positive { 1.0 } else { -1.0 };
+
+                        let base = vertices.len() as u32;
+                        // u × v 与 +axis 同向，逆时针顺序即朝向正方向
+                        for (du, dv) in [(0, 0), (width, 0), (width, height), (0, height)] {
+                            vertices.push(Vertex {
+                                position: corner(du, dv),
+                                normal,
+                                tex_coords: [du as f32, dv as f32],
+                                uv1: [du as f32, dv as f32],
+                            });
+                        }
+                        if positive {
+                            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+                        } else {
+                            indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+                        }
+                    }
+                }
+            }
+        }
+        (vertices, indices)
+    }
+
+    // 返回顶点缓冲区与 u32 索引缓冲区，索引数为 index_buffer.size() / 4
+    pub fn build_mesh(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::Buffer, wgpu::Buffer) {
+        let (vertices, indices) = self.greedy_mesh();
+
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Voxel Vertex Buffer"),
+            size: std::mem::size_of_val(vertices.as_slice()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Voxel Index Buffer"),
+            size: std::mem::size_of_val(indices.as_slice()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&index_buffer, 0, bytemuck::cast_slice(&indices));
+
+        (vertex_buffer, index_buffer)
+    }
+}
+
+// 在一个切片的稀疏面集合上做二维贪心合并，返回 (u, v, 宽, 高)
+fn greedy_rectangles(faces: &[(u32, u32, u32)]) -> Vec<(u32, u32, u32, u32)> {
+    let Some(min_u) = faces.iter().map(|f| f.0).min() else {
+        return Vec::new();
+    };
+    let min_v = faces.iter().map(|f| f.1).min().unwrap_or(0);
+    let width = (faces.iter().map(|f| f.0).max().unwrap_or(0) - min_u + 1) as usize;
+    let height = (faces.iter().map(|f| f.1).max().unwrap_or(0) - min_v + 1) as usize;
+
+    let mut mask: Vec<Option<u32>> = vec![None; width * height];
+    for &(u, v, voxel) in faces {
+        mask[(v - min_v) as usize * width + (u - min_u) as usize] = Some(voxel);
+    }
+
+    let mut rectangles = Vec::new();
+    for j in 0..height {
+        let mut i = 0;
+        while i < width {
+            let Some(voxel) = mask[j * width + i] else {
+                i += 1;
+                continue;
+            };
+
+            let mut w = 1;
+            while i + w < width && mask[j * width + i + w] == Some(voxel) {
+                w += 1;
+            }
+            let mut h = 1;
+            while j + h < height && (0..w).all(|k| mask[(j + h) * width + i + k] == Some(voxel)) {
+                h += 1;
+            }
+
+            for row in j..j + h {
+                for cell in &mut mask[row * width + i..row * width + i + w] {
+                    *cell = None;
+                }
+            }
+            rectangles.push((i as u32 + min_u, j as u32 + min_v, w as u32, h as u32));
+            i += w;
+        }
+    }
+    rectangles
+}
@@ -0,0 +1,190 @@
+// 立体渲染的 XR 抽象：VrSession 管理每只眼睛的渲染目标与帧循环，头显运行时通过 XrRuntime 接入
+// 目前只提供桌面调试用的 SimulatedRuntime，OpenXR / WebXR 运行时尚未实现
+use cgmath::{ InnerSpace, Matrix4, Point3, Quaternion, Rotation, SquareMatrix, Vector3 };
+
+use crate::camera::OPENGL_TO_WGPU_MATRIX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+// 与 OpenXR 的 XrFovf 一致：四个半角（弧度），left 与 down 通常为负
+#[derive(Debug, Clone, Copy)]
+pub struct EyeFov {
+    pub left: f32,
+    pub right: f32,
+    pub up: f32,
+    pub down: f32,
+}
+
+impl EyeFov {
+    // 非对称视锥的投影矩阵
+    pub fn projection(&self, znear: f32, zfar: f32) -> Matrix4<f32> {
+        OPENGL_TO_WGPU_MATRIX
+            * cgmath::frustum(
+                self.left.tan() * znear,
+                self.right.tan() * znear,
+                self.down.tan() * znear,
+                self.up.tan() * znear,
+                znear,
+                zfar,
+            )
+    }
+}
+
+// XR 运行时在一帧中给出的单眼位姿，right-handed、y 轴向上
+#[derive(Debug, Clone, Copy)]
+pub struct EyePose {
+    pub position: Point3<f32>,
+    pub orientation: Quaternion<f32>,
+    pub fov: EyeFov,
+}
+
+impl EyePose {
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        let rotation = Matrix4::from(self.orientation);
+        let translation = Matrix4::from_translation(self.position - Point3::new(0.0, 0.0, 0.0));
+        (translation * rotation).invert().unwrap_or_else(Matrix4::identity)
+    }
+}
+
+// 头显运行时的最小接口；接入 OpenXR 会话或 WebXR XRSession 时实现它
+pub trait XrRuntime {
+    // 每只眼睛推荐的渲染分辨率
+    fn recommended_resolution(&self) -> (u32, u32);
+    // 等待下一帧并返回左右眼位姿；运行时不需要渲染这一帧时返回 None
+    fn wait_frame(&mut self) -> Option<[EyePose; 2]>;
+    // 把两只眼睛的图像交给运行时合成
+    fn submit(&mut self, left: &wgpu::Texture, right: &wgpu::Texture);
+}
+
+// 一只眼睛在当前帧的渲染目标与矩阵，为它单独录制一个渲染通道
+pub struct EyeView {
+    pub eye: Eye,
+    pub view: Matrix4<f32>,
+    pub projection: Matrix4<f32>,
+    pub color_view: wgpu::TextureView,
+}
+
+impl EyeView {
+    // 可直接写入 CameraUniform::view_proj
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection * self.view
+    }
+}
+
+pub struct VrSession {
+    runtime: Box<dyn XrRuntime>,
+    format: wgpu::TextureFormat,
+    znear: f32,
+    zfar: f32,
+    images: Option<[wgpu::Texture; 2]>,
+    in_frame: bool,
+}
+
+impl VrSession {
+    pub fn new(runtime: Box<dyn XrRuntime>, format: wgpu::TextureFormat, znear: f32, zfar: f32) -> Self {
+        Self {
+            runtime,
+            format,
+            znear,
+            zfar,
+            images: None,
+            in_frame: false,
+        }
+    }
+
+    pub fn resolution(&self) -> (u32, u32) {
+        self.runtime.recommended_resolution()
+    }
+
+    // 按运行时推荐的分辨率为左右眼各创建一张渲染目标
+    pub fn create_swapchain_images(&mut self, device: &wgpu::Device) -> &[wgpu::Texture; 2] {
+        let (width, height) = self.runtime.recommended_resolution();
+        let image = |label| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: width.max(1),
+                    height: height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        };
+        self.images.insert([image("VR Left Eye"), image("VR Right Eye")])
+    }
+
+    // 返回 (左眼, 右眼)；运行时跳过这一帧时返回 None，此时不要调用 end_frame
+    pub fn begin_frame(&mut self) -> Option<(EyeView, EyeView)> {
+        let images = self.images.as_ref().expect("create_swapchain_images must be called before begin_frame");
+        let [left, right] = self.runtime.wait_frame()?;
+        self.in_frame = true;
+
+        let eye_view = |eye, pose: EyePose, texture: &wgpu::Texture| EyeView {
+            eye,
+            view: pose.view_matrix(),
+            projection: pose.fov.projection(self.znear, self.zfar),
+            color_view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        };
+        Some((eye_view(Eye::Left, left, &images[0]), eye_view(Eye::Right, right, &images[1])))
+    }
+
+    // 两只眼睛的渲染命令提交之后调用
+    pub fn end_frame(&mut self) {
+        if !std::mem::take(&mut self.in_frame) {
+            tracing::warn!("vr: end_frame called without a matching begin_frame");
+            return;
+        }
+        if let Some([left, right]) = &self.images {
+            self.runtime.submit(left, right);
+        }
+    }
+}
+
+// 没有头显时的替代运行时：由一个头部位姿和瞳距推出左右眼，用于在桌面上调试立体渲染
+pub struct SimulatedRuntime {
+    pub head_position: Point3<f32>,
+    pub head_orientation: Quaternion<f32>,
+    // 瞳距，单位为米
+    pub ipd: f32,
+    pub fov: EyeFov,
+    pub resolution: (u32, u32),
+}
+
+impl SimulatedRuntime {
+    pub fn new(resolution: (u32, u32)) -> Self {
+        let half = 45f32.to_radians();
+        Self {
+            head_position: Point3::new(0.0, 1.6, 0.0),
+            head_orientation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            ipd: 0.064,
+            fov: EyeFov { left: -half, right: half, up: half, down: -half },
+            resolution,
+        }
+    }
+}
+
+impl XrRuntime for SimulatedRuntime {
+    fn recommended_resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    fn wait_frame(&mut self) -> Option<[EyePose; 2]> {
+        let orientation = self.head_orientation.normalize();
+        let right = orientation.rotate_vector(Vector3::unit_x()) * (self.ipd * 0.5);
+        let pose = |position| EyePose { position, orientation, fov: self.fov };
+        Some([pose(self.head_position - right), pose(self.head_position + right)])
+    }
+
+    fn submit(&mut self, _left: &wgpu::Texture, _right: &wgpu::Texture) {}
+}
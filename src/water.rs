@@ -0,0 +1,535 @@
+use cgmath::{ Matrix4, SquareMatrix };
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::mesh::Vertex;
+use crate::primitives;
+use crate::texture::Texture;
+
+const SPECTRUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+const FFT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const HEIGHT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const WORKGROUP_SIZE: u32 = 8;
+const FFT_WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub struct WaterConfig {
+    // FFT 网格边长，必须是 2 的幂
+    pub size: u32,
+    // 一块海面在世界中的边长（米），超出部分平铺
+    pub patch_length: f32,
+    // 风速向量（米/秒），决定最大波长与波的朝向
+    pub wind: [f32; 2],
+    // Phillips 频谱的常数 A，默认值使浪高约为 ±1.5 米
+    pub amplitude: f32,
+    pub seed: u32,
+}
+
+impl Default for WaterConfig {
+    fn default() -> Self {
+        Self {
+            size: 256,
+            patch_length: 64.0,
+            wind: [8.0, 4.0],
+            amplitude: 1e-5,
+            seed: 1,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpectrumParams {
+    size: u32,
+    patch_length: f32,
+    wind: [f32; 2],
+    amplitude: f32,
+    seed: u32,
+    time: f32,
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FftParams {
+    span: u32,
+    direction: u32,
+    _padding: [u32; 2],
+}
+
+// Tessendorf 的 FFT 海面：初始 Phillips 频谱只计算一次，
+// 每帧在频域中随时间演化，再用逆 FFT 得到高度图与法线图
+pub struct WaterSimulator {
+    pub config: WaterConfig,
+    spectrum_buffer: wgpu::Buffer,
+    evolve_pipeline: wgpu::ComputePipeline,
+    fft_pipeline: wgpu::ComputePipeline,
+    resolve_pipeline: wgpu::ComputePipeline,
+    evolve_bind_group: wgpu::BindGroup,
+    // 每一级蝶形运算一个绑定组，乒乓读写两张纹理
+    fft_bind_groups: Vec<wgpu::BindGroup>,
+    resolve_bind_group: wgpu::BindGroup,
+    // 波的实部与虚部：h0(k) 与 conj(h0(-k))
+    h0: Texture,
+    h0_conj: Texture,
+    height: Texture,
+    normal: Texture,
+}
+
+impl WaterSimulator {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, config: WaterConfig) -> Self {
+        assert!(config.size.is_power_of_two() && config.size >= 2, "water FFT size must be a power of two");
+        // Rg32Float 不在 WebGPU 核心的存储纹理格式之列
+        assert!(
+            device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES),
+            "WaterSimulator requires TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES for Rg32Float storage textures"
+        );
+        let size = config.size;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Water FFT Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("water_fft.wgsl").into()),
+        });
+
+        let create = |format, label| {
+            Texture::create_render_target(device, size, size, format, wgpu::TextureUsages::STORAGE_BINDING, label)
+        };
+        let h0 = create(SPECTRUM_FORMAT, "Water H0");
+        let h0_conj = create(SPECTRUM_FORMAT, "Water H0 Conjugate");
+        let ping = create(FFT_FORMAT, "Water FFT Ping");
+        let pong = create(FFT_FORMAT, "Water FFT Pong");
+        let height = create(HEIGHT_FORMAT, "Water Height");
+        let normal = create(NORMAL_FORMAT, "Water Normal");
+
+        let spectrum_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Spectrum Params"),
+            contents: bytemuck::cast_slice(&[SpectrumParams {
+                size,
+                patch_length: config.patch_length,
+                wind: config.wind,
+                amplitude: config.amplitude,
+                seed: config.seed,
+                time: 0.0,
+                _padding: 0,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let storage_entry = |binding, format| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        };
+        let create_pipeline = |label, entries: &[wgpu::BindGroupLayoutEntry], entry_point| {
+            let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(label),
+                entries,
+            });
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point,
+            });
+            (pipeline, bind_group_layout)
+        };
+        let bind = |layout: &wgpu::BindGroupLayout, resources: &[wgpu::BindingResource]| {
+            let entries = resources
+                .iter()
+                .enumerate()
+                .map(|(binding, resource)| wgpu::BindGroupEntry { binding: binding as u32, resource: resource.clone() })
+                .collect::<Vec<_>>();
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("water_fft_bind_group"),
+                layout,
+                entries: &entries,
+            })
+        };
+
+        let (spectrum_pipeline, spectrum_layout) = create_pipeline(
+            "Water Initial Spectrum",
+            &[uniform_entry, storage_entry(1, SPECTRUM_FORMAT), storage_entry(2, SPECTRUM_FORMAT)],
+            "cs_initial_spectrum",
+        );
+        let (evolve_pipeline, evolve_layout) = create_pipeline(
+            "Water Evolve",
+            &[uniform_entry, texture_entry(1), texture_entry(2), storage_entry(3, FFT_FORMAT)],
+            "cs_evolve",
+        );
+        let (fft_pipeline, fft_layout) = create_pipeline(
+            "Water FFT",
+            &[uniform_entry, texture_entry(1), storage_entry(2, FFT_FORMAT)],
+            "cs_fft",
+        );
+        let (resolve_pipeline, resolve_layout) = create_pipeline(
+            "Water Resolve",
+            &[uniform_entry, texture_entry(1), storage_entry(2, HEIGHT_FORMAT), storage_entry(3, NORMAL_FORMAT)],
+            "cs_resolve",
+        );
+
+        let spectrum = spectrum_buffer.as_entire_binding();
+        fn view(texture: &Texture) -> wgpu::BindingResource<'_> {
+            wgpu::BindingResource::TextureView(&texture.view)
+        }
+
+        // 演化结果写入 ping，之后每一级在 ping/pong 之间交替：先水平 log2(N) 级，再垂直 log2(N) 级
+        let evolve_bind_group = bind(&evolve_layout, &[spectrum.clone(), view(&h0), view(&h0_conj), view(&ping)]);
+        let stages = size.trailing_zeros();
+        let mut fft_bind_groups = Vec::with_capacity(stages as usize * 2);
+        for direction in 0..2 {
+            for stage in 0..stages {
+                let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Water FFT Params"),
+                    contents: bytemuck::cast_slice(&[FftParams { span: 1 << stage, direction, _padding: [0; 2] }]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                let (input, output) = if fft_bind_groups.len() % 2 == 0 { (&ping, &pong) } else { (&pong, &ping) };
+                fft_bind_groups.push(bind(&fft_layout, &[params.as_entire_binding(), view(input), view(output)]));
+            }
+        }
+        // 级数为偶数，最终结果回到 ping
+        let resolve_bind_group = bind(&resolve_layout, &[spectrum.clone(), view(&ping), view(&height), view(&normal)]);
+
+        let spectrum_bind_group = bind(&spectrum_layout, &[spectrum, view(&h0), view(&h0_conj)]);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Water Initial Spectrum Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Water Initial Spectrum Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&spectrum_pipeline);
+            compute_pass.set_bind_group(0, &spectrum_bind_group, &[]);
+            let workgroups = size.div_ceil(WORKGROUP_SIZE);
+            compute_pass.dispatch_workgroups(workgroups, workgroups, 1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        Self {
+            config,
+            spectrum_buffer,
+            evolve_pipeline,
+            fft_pipeline,
+            resolve_pipeline,
+            evolve_bind_group,
+            fft_bind_groups,
+            resolve_bind_group,
+            h0,
+            h0_conj,
+            height,
+            normal,
+        }
+    }
+
+    // R32Float，每个纹素是海面高度（米）
+    pub fn height_view(&self) -> &wgpu::TextureView {
+        &self.height.view
+    }
+
+    // xyz 为海面法线
+    pub fn normal_view(&self) -> &wgpu::TextureView {
+        &self.normal.view
+    }
+
+    // 初始频谱的实部与虚部
+    pub fn spectrum_views(&self) -> (&wgpu::TextureView, &wgpu::TextureView) {
+        (&self.h0.view, &self.h0_conj.view)
+    }
+
+    // 录制 time 时刻的演化、逆 FFT 与输出，time 以秒为单位
+    pub fn simulate(&self, encoder: &mut wgpu::CommandEncoder, queue: &wgpu::Queue, time: f32) {
+        let params = SpectrumParams {
+            size: self.config.size,
+            patch_length: self.config.patch_length,
+            wind: self.config.wind,
+            amplitude: self.config.amplitude,
+            seed: self.config.seed,
+            time,
+            _padding: 0,
+        };
+        queue.write_buffer(&self.spectrum_buffer, 0, bytemuck::cast_slice(&[params]));
+
+        let size = self.config.size;
+        let workgroups = size.div_ceil(WORKGROUP_SIZE);
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Water Simulation Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.evolve_pipeline);
+        compute_pass.set_bind_group(0, &self.evolve_bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroups, workgroups, 1);
+
+        compute_pass.set_pipeline(&self.fft_pipeline);
+        for bind_group in &self.fft_bind_groups {
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups((size / 2).div_ceil(FFT_WORKGROUP_SIZE), size, 1);
+        }
+
+        compute_pass.set_pipeline(&self.resolve_pipeline);
+        compute_pass.set_bind_group(0, &self.resolve_bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroups, workgroups, 1);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WaterShading {
+    pub sun_direction: [f32; 3],
+    pub deep_color: [f32; 3],
+    // 浪峰逆光时透出的颜色
+    pub scatter_color: [f32; 3],
+    pub height_scale: f32,
+}
+
+impl Default for WaterShading {
+    fn default() -> Self {
+        Self {
+            sun_direction: [0.3, 0.4, -1.0],
+            deep_color: [0.01, 0.05, 0.1],
+            scatter_color: [0.05, 0.4, 0.35],
+            height_scale: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct WaterUniform {
+    model: [[f32; 4]; 4],
+    camera_position: [f32; 3],
+    patch_length: f32,
+    sun_direction: [f32; 3],
+    height_scale: f32,
+    deep_color: [f32; 3],
+    _padding0: f32,
+    scatter_color: [f32; 3],
+    _padding1: f32,
+}
+
+// 细分平面网格，顶点着色器按高度图位移，片元着色器按法线图混合环境反射与次表面散射
+pub struct WaterRenderer {
+    pub shading: WaterShading,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    model: Matrix4<f32>,
+}
+
+impl WaterRenderer {
+    // environment_bind_group_layout 为立方体贴图 + 采样器，与 LightProbeSystem 的布局一致
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        simulator: &WaterSimulator,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        environment_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Water Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("water.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("water_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Water Uniform Buffer"),
+            size: std::mem::size_of::<WaterUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Water Normal Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("water_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(simulator.height_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(simulator.normal_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Water Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &bind_group_layout, environment_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Water Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                // 浪的背面在掠射角下也可能露出来
+                cull_mode: None,
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multiview: None,
+        });
+
+        // 一块海面 128 × 128 格；u16 索引最多容纳 255 × 255 格
+        let half = simulator.config.patch_length * 0.5;
+        let mesh = primitives::plane(half, half, 128);
+        let (vertex_buffer, index_buffer) = mesh.upload(device, queue);
+
+        Self {
+            shading: WaterShading::default(),
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            vertex_buffer,
+            index_buffer,
+            index_count: mesh.index_count(),
+            model: Matrix4::identity(),
+        }
+    }
+
+    pub fn set_model(&mut self, model: Matrix4<f32>) {
+        self.model = model;
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, simulator: &WaterSimulator) {
+        let uniform = WaterUniform {
+            model: self.model.into(),
+            camera_position: camera.eye.into(),
+            patch_length: simulator.config.patch_length,
+            sun_direction: self.shading.sun_direction,
+            height_scale: self.shading.height_scale,
+            deep_color: self.shading.deep_color,
+            _padding0: 0.0,
+            scatter_color: self.shading.scatter_color,
+            _padding1: 0.0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: &'a wgpu::BindGroup,
+        environment_bind_group: &'a wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_bind_group(2, environment_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
@@ -0,0 +1,86 @@
+struct CameraUniform {
+    view_proj: mat4x4f,
+    clip_plane: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct WaterUniform {
+    model: mat4x4f,
+    camera_position: vec3f,
+    patch_length: f32,
+    // 指向太阳
+    sun_direction: vec3f,
+    height_scale: f32,
+    deep_color: vec3f,
+    _padding0: f32,
+    scatter_color: vec3f,
+    _padding1: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> water: WaterUniform;
+@group(1) @binding(1)
+var t_height: texture_2d<f32>;
+@group(1) @binding(2)
+var t_normal: texture_2d<f32>;
+@group(1) @binding(3)
+var s_normal: sampler;
+
+// 环境反射与光照探针共用布局
+@group(2) @binding(0)
+var t_environment: texture_cube<f32>;
+@group(2) @binding(1)
+var s_environment: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) tex_coords: vec2f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) uv: vec2f,
+    @location(2) height: f32,
+};
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var world_position = (water.model * vec4f(model.position, 1.0)).xyz;
+    // 海面按 patch_length 平铺，R32Float 不可过滤，直接按纹素读取
+    let uv = world_position.xz / water.patch_length;
+    let size = vec2<i32>(textureDimensions(t_height));
+    let texel = ((vec2<i32>(floor(fract(uv) * vec2f(size))) % size) + size) % size;
+    let height = textureLoad(t_height, texel, 0).r * water.height_scale;
+    world_position.y += height;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4f(world_position, 1.0);
+    out.world_position = world_position;
+    out.uv = uv;
+    out.height = height;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    var normal = textureSample(t_normal, s_normal, in.uv).xyz;
+    normal = normalize(vec3f(normal.x * water.height_scale, normal.y, normal.z * water.height_scale));
+    let view = normalize(water.camera_position - in.world_position);
+    let sun = normalize(water.sun_direction);
+
+    // Schlick 菲涅耳，水的 F0 约为 0.02
+    let fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(normal, view), 0.0), 5.0);
+    let reflection = textureSample(t_environment, s_environment, reflect(-view, normal)).rgb;
+
+    // 次表面散射：逆光看浪峰时光穿过薄薄的水体
+    let scatter_dir = -normalize(sun + normal * 0.6);
+    let scatter = pow(max(dot(view, scatter_dir), 0.0), 4.0) * max(in.height, 0.0);
+    let refraction = water.deep_color + water.scatter_color * scatter;
+
+    let specular = pow(max(dot(reflect(-sun, normal), view), 0.0), 256.0);
+    let color = mix(refraction, reflection, fresnel) + vec3f(specular);
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,184 @@
+const PI: f32 = 3.14159265;
+const GRAVITY: f32 = 9.81;
+
+struct SpectrumParams {
+    // 网格边长 N（2 的幂）
+    size: u32,
+    // 一块海面在世界中的边长（米）
+    patch_length: f32,
+    wind: vec2f,
+    amplitude: f32,
+    seed: u32,
+    time: f32,
+};
+
+struct FftParams {
+    // 当前蝶形运算的跨度 Ns = 2^stage
+    span: u32,
+    // 0 为水平（沿 x），1 为垂直（沿 y）
+    direction: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> spectrum: SpectrumParams;
+
+// ---- 初始频谱 ----
+
+@group(0) @binding(1)
+var t_h0_out: texture_storage_2d<rg32float, write>;
+@group(0) @binding(2)
+var t_h0_conj_out: texture_storage_2d<rg32float, write>;
+
+fn hash(seed: u32) -> u32 {
+    var x = seed * 747796405u + 2891336453u;
+    x = ((x >> ((x >> 28u) + 4u)) ^ x) * 277803737u;
+    return (x >> 22u) ^ x;
+}
+
+// Box-Muller 变换得到两个独立的标准正态分布随机数
+fn gaussian_pair(texel: vec2u) -> vec2f {
+    let seed = hash(texel.y * spectrum.size + texel.x + hash(spectrum.seed));
+    let u1 = max(f32(hash(seed)) / 4294967296.0, 1e-7);
+    let u2 = f32(hash(seed + 1u)) / 4294967296.0;
+    let radius = sqrt(-2.0 * log(u1));
+    return vec2f(radius * cos(2.0 * PI * u2), radius * sin(2.0 * PI * u2));
+}
+
+fn wave_vector(texel: vec2u) -> vec2f {
+    let n = vec2f(texel) - f32(spectrum.size / 2u);
+    return 2.0 * PI * n / spectrum.patch_length;
+}
+
+// Phillips 频谱，抑制与风向垂直的波与极短的波
+fn phillips(k: vec2f) -> f32 {
+    let k_length = length(k);
+    if k_length < 1e-6 {
+        return 0.0;
+    }
+    let wind_speed = length(spectrum.wind);
+    let largest_wave = wind_speed * wind_speed / GRAVITY;
+    let k2 = k_length * k_length;
+    let alignment = dot(k / k_length, spectrum.wind / max(wind_speed, 1e-6));
+    let smallest_wave = largest_wave / 1000.0;
+    return spectrum.amplitude * exp(-1.0 / (k2 * largest_wave * largest_wave)) / (k2 * k2)
+        * alignment * alignment * exp(-k2 * smallest_wave * smallest_wave);
+}
+
+fn h0(texel: vec2u, k: vec2f) -> vec2f {
+    return gaussian_pair(texel) * sqrt(phillips(k) * 0.5);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_initial_spectrum(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= spectrum.size || id.y >= spectrum.size {
+        return;
+    }
+    let k = wave_vector(id.xy);
+    // -k 对应的纹素，用同一组随机数保证 h(x) 是实数
+    let mirrored = (vec2u(spectrum.size) - id.xy) % spectrum.size;
+    let h0_minus = h0(mirrored, -k);
+
+    textureStore(t_h0_out, vec2<i32>(id.xy), vec4f(h0(id.xy, k), 0.0, 0.0));
+    textureStore(t_h0_conj_out, vec2<i32>(id.xy), vec4f(h0_minus.x, -h0_minus.y, 0.0, 0.0));
+}
+
+// ---- 随时间演化 ----
+
+@group(0) @binding(1)
+var t_h0: texture_2d<f32>;
+@group(0) @binding(2)
+var t_h0_conj: texture_2d<f32>;
+// rg = 高度 + i·x 斜率的频谱，ba = z 斜率的频谱；两个实信号共用一次复数 FFT
+@group(0) @binding(3)
+var t_spectrum_out: texture_storage_2d<rgba32float, write>;
+
+fn complex_mul(a: vec2f, b: vec2f) -> vec2f {
+    return vec2f(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_evolve(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= spectrum.size || id.y >= spectrum.size {
+        return;
+    }
+    let texel = vec2<i32>(id.xy);
+    let k = wave_vector(id.xy);
+    let omega = sqrt(GRAVITY * length(k)) * spectrum.time;
+    let phase = vec2f(cos(omega), sin(omega));
+    let phase_conj = vec2f(phase.x, -phase.y);
+
+    // h(k, t) = h0(k)·e^{iωt} + conj(h0(-k))·e^{-iωt}
+    let h = complex_mul(textureLoad(t_h0, texel, 0).xy, phase) + complex_mul(textureLoad(t_h0_conj, texel, 0).xy, phase_conj);
+    // 斜率的频谱为 i·k·h
+    let slope_x = complex_mul(vec2f(0.0, k.x), h);
+    let slope_z = complex_mul(vec2f(0.0, k.y), h);
+
+    let combined = h + complex_mul(vec2f(0.0, 1.0), slope_x);
+    textureStore(t_spectrum_out, texel, vec4f(combined, slope_z));
+}
+
+// ---- Stockham 基 2 FFT，每次调度完成一级蝶形运算 ----
+
+@group(0) @binding(0)
+var<uniform> fft: FftParams;
+@group(0) @binding(1)
+var t_fft_in: texture_2d<f32>;
+@group(0) @binding(2)
+var t_fft_out: texture_storage_2d<rgba32float, write>;
+
+fn fft_texel(along: u32, across: u32) -> vec2<i32> {
+    if fft.direction == 0u {
+        return vec2<i32>(i32(along), i32(across));
+    }
+    return vec2<i32>(i32(across), i32(along));
+}
+
+// id.x 为 0..N/2 的蝶形序号，id.y 为行（列）号
+@compute @workgroup_size(64, 1, 1)
+fn cs_fft(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(t_fft_in).x;
+    let half_size = size / 2u;
+    if id.x >= half_size || id.y >= size {
+        return;
+    }
+    let j = id.x;
+    let k = j % fft.span;
+
+    // 逆变换使用正号的旋转因子
+    let angle = PI * f32(k) / f32(fft.span);
+    let twiddle = vec2f(cos(angle), sin(angle));
+
+    let a = textureLoad(t_fft_in, fft_texel(j, id.y), 0);
+    let b = textureLoad(t_fft_in, fft_texel(j + half_size, id.y), 0);
+    let b0 = complex_mul(b.xy, twiddle);
+    let b1 = complex_mul(b.zw, twiddle);
+
+    let out_index = (j / fft.span) * fft.span * 2u + k;
+    textureStore(t_fft_out, fft_texel(out_index, id.y), vec4f(a.xy + b0, a.zw + b1));
+    textureStore(t_fft_out, fft_texel(out_index + fft.span, id.y), vec4f(a.xy - b0, a.zw - b1));
+}
+
+// ---- 输出高度图与法线图 ----
+
+@group(0) @binding(1)
+var t_result: texture_2d<f32>;
+@group(0) @binding(2)
+var t_height_out: texture_storage_2d<r32float, write>;
+@group(0) @binding(3)
+var t_normal_out: texture_storage_2d<rgba16float, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_resolve(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= spectrum.size || id.y >= spectrum.size {
+        return;
+    }
+    let texel = vec2<i32>(id.xy);
+    let value = textureLoad(t_result, texel, 0);
+    // 频率下标从 -N/2 开始，空间域结果需要乘以 (-1)^(x+y)
+    let sign = select(1.0, -1.0, ((id.x + id.y) & 1u) == 1u);
+
+    let height = value.x * sign;
+    let slope = vec2f(value.y, value.z) * sign;
+    textureStore(t_height_out, texel, vec4f(height, 0.0, 0.0, 0.0));
+    textureStore(t_normal_out, texel, vec4f(normalize(vec3f(-slope.x, 1.0, -slope.y)), 0.0));
+}
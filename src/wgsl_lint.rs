@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use naga::{ Expression, Handle, Module, ShaderStage };
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintError {
+    // 从 1 开始；无法定位时为 0
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl LintError {
+    fn at(span: naga::Span, source: &str, message: String) -> Self {
+        let (line, column) = if span.is_defined() {
+            let location = span.location(source);
+            (location.line_number, location.line_position)
+        } else {
+            (0, 0)
+        };
+        Self { line, column, message }
+    }
+}
+
+// Rust 端某个绑定组布局中的一项，用于与着色器声明对照
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedBinding {
+    pub group: u32,
+    pub binding: u32,
+}
+
+// 在交给 wgpu 编译之前检查 WGSL，给出带行列号的错误
+pub struct WgslLinter;
+
+impl WgslLinter {
+    pub fn check(source: &str) -> Vec<LintError> {
+        Self::lint(source, None)
+    }
+
+    // 额外检查着色器的 @group/@binding 是否都能在 Rust 端的绑定组布局中找到
+    pub fn check_with_layouts(source: &str, layouts: &[&[wgpu::BindGroupLayoutEntry]]) -> Vec<LintError> {
+        let expected = layouts
+            .iter()
+            .enumerate()
+            .flat_map(|(group, entries)| {
+                entries.iter().map(move |entry| ExpectedBinding { group: group as u32, binding: entry.binding })
+            })
+            .collect::<Vec<_>>();
+        Self::lint(source, Some(&expected))
+    }
+
+    fn lint(source: &str, expected: Option<&[ExpectedBinding]>) -> Vec<LintError> {
+        let module = match naga::front::wgsl::parse_str(source) {
+            Ok(module) => module,
+            Err(e) => {
+                let (line, column) = e
+                    .location(source)
+                    .map(|location| (location.line_number, location.line_position))
+                    .unwrap_or((0, 0));
+                return vec![LintError { line, column, message: e.message().to_string() }];
+            }
+        };
+
+        let mut errors = Vec::new();
+        check_validation(&module, source, &mut errors);
+        check_entry_points(&module, &mut errors);
+        check_unused_bindings(&module, source, &mut errors);
+        if let Some(expected) = expected {
+            check_layout_bindings(&module, source, expected, &mut errors);
+        }
+        errors.sort_by_key(|error| (error.line, error.column));
+        errors
+    }
+}
+
+// naga 校验会发现常量下标越界等问题
+fn check_validation(module: &Module, source: &str, errors: &mut Vec<LintError>) {
+    let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+    if let Err(e) = validator.validate(module) {
+        let (line, column) = e
+            .location(source)
+            .map(|location| (location.line_number, location.line_position))
+            .unwrap_or((0, 0));
+
+        let inner = e.into_inner();
+        let mut message = inner.to_string();
+        let mut cause = std::error::Error::source(&inner);
+        while let Some(inner) = cause {
+            message.push_str(&format!(": {inner}"));
+            cause = inner.source();
+        }
+        errors.push(LintError { line, column, message });
+    }
+}
+
+// 渲染着色器需要同时有 @vertex 与 @fragment，计算着色器不受此限制
+fn check_entry_points(module: &Module, errors: &mut Vec<LintError>) {
+    let has_stage = |stage| module.entry_points.iter().any(|entry_point| entry_point.stage == stage);
+    if has_stage(ShaderStage::Compute) {
+        return;
+    }
+    if !has_stage(ShaderStage::Vertex) {
+        errors.push(LintError { line: 0, column: 0, message: "missing @vertex entry point".to_string() });
+    }
+    if !has_stage(ShaderStage::Fragment) {
+        errors.push(LintError { line: 0, column: 0, message: "missing @fragment entry point".to_string() });
+    }
+}
+
+fn check_unused_bindings(module: &Module, source: &str, errors: &mut Vec<LintError>) {
+    let functions = module
+        .functions
+        .iter()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter().map(|entry_point| &entry_point.function));
+
+    let mut used = HashSet::<Handle<naga::GlobalVariable>>::new();
+    for function in functions {
+        for (_, expression) in function.expressions.iter() {
+            if let Expression::GlobalVariable(handle) = *expression {
+                used.insert(handle);
+            }
+        }
+    }
+
+    for (handle, variable) in module.global_variables.iter() {
+        if let Some(binding) = &variable.binding {
+            if !used.contains(&handle) {
+                errors.push(LintError::at(
+                    module.global_variables.get_span(handle),
+                    source,
+                    format!(
+                        "binding `{}` (@group({}) @binding({})) is never used",
+                        variable.name.as_deref().unwrap_or("<unnamed>"),
+                        binding.group,
+                        binding.binding,
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn check_layout_bindings(module: &Module, source: &str, expected: &[ExpectedBinding], errors: &mut Vec<LintError>) {
+    for (handle, variable) in module.global_variables.iter() {
+        let Some(binding) = &variable.binding else {
+            continue;
+        };
+        let declared = ExpectedBinding { group: binding.group, binding: binding.binding };
+        if !expected.contains(&declared) {
+            errors.push(LintError::at(
+                module.global_variables.get_span(handle),
+                source,
+                format!(
+                    "`{}` uses @group({}) @binding({}), which is not in the Rust bind group layout",
+                    variable.name.as_deref().unwrap_or("<unnamed>"),
+                    binding.group,
+                    binding.binding,
+                ),
+            ));
+        }
+    }
+}
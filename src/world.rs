@@ -0,0 +1,73 @@
+use crate::transform::{ InterpolatedTransform, PreviousTransform, Transform };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity(pub u32);
+
+// 拾取结果中用实体标识被选中的物体
+pub type ObjectId = Entity;
+
+impl Entity {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// 极简的组件存储：每种组件一个 Vec，下标即实体 ID
+#[derive(Default)]
+pub struct World {
+    pub transforms: Vec<Transform>,
+    pub previous_transforms: Vec<PreviousTransform>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, transform: Transform) -> Entity {
+        let entity = Entity(self.transforms.len() as u32);
+        self.transforms.push(transform);
+        self.previous_transforms.push(PreviousTransform(transform));
+        entity
+    }
+
+    pub fn len(&self) -> usize {
+        self.transforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> {
+        (0..self.transforms.len() as u32).map(Entity)
+    }
+
+    pub fn transform(&self, entity: Entity) -> &Transform {
+        &self.transforms[entity.index()]
+    }
+
+    pub fn transform_mut(&mut self, entity: Entity) -> &mut Transform {
+        &mut self.transforms[entity.index()]
+    }
+
+    // 直接设置变换且不做插值，例如编辑器中拖动物体
+    pub fn teleport(&mut self, entity: Entity, transform: Transform) {
+        self.transforms[entity.index()] = transform;
+        self.previous_transforms[entity.index()] = PreviousTransform(transform);
+    }
+
+    // 在每个物理步开始前调用，记录上一步的状态
+    pub fn record_previous_transforms(&mut self) {
+        for (previous, current) in self.previous_transforms.iter_mut().zip(&self.transforms) {
+            previous.0 = *current;
+        }
+    }
+
+    pub fn interpolated_transforms(&self, alpha: f32) -> impl Iterator<Item = InterpolatedTransform> + '_ {
+        self.previous_transforms
+            .iter()
+            .zip(&self.transforms)
+            .map(move |(previous, current)| InterpolatedTransform::blend(previous, current, alpha))
+    }
+}
@@ -0,0 +1,147 @@
+use wgpu::util::DeviceExt;
+
+// wgpu 没有三通道的 Rgb32Float，第四个通道固定为 1
+pub const WORLEY_NOISE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+const WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct WorleyParams {
+    size: u32,
+    grid: u32,
+    _padding: [u32; 2],
+}
+
+// Worley（蜂窝）噪声纹理：num_points 取整为 grid × grid，每个格子随机放一个特征点（抖动网格），
+// 计算着色器为每个纹素只检查周围的格子，输出到最近与次近特征点的距离 (F1, F2, F2 - F1)，
+// 以格子边长为单位。特征点按格子首尾相接，纹理可以无缝重复。
+// Rgba32Float 不可过滤，着色器用 textureLoad 读取
+pub struct WorleyNoiseTexture;
+
+impl WorleyNoiseTexture {
+    // 相同的 seed 生成相同的图案；需要计算着色器
+    pub fn generate(device: &wgpu::Device, queue: &wgpu::Queue, size: u32, num_points: u32, seed: u64) -> wgpu::Texture {
+        let size = size.clamp(1, device.limits().max_texture_dimension_2d);
+        let grid = ((num_points.max(1) as f32).sqrt().round() as u32).clamp(1, size);
+        let seed = hash(seed as u32 ^ hash((seed >> 32) as u32));
+        let points: Vec<[f32; 2]> = (0..grid * grid)
+            .map(|index| {
+                let x = hash(seed ^ hash(index * 2));
+                let y = hash(seed ^ hash(index * 2 + 1));
+                [(x >> 8) as f32 / (1 << 24) as f32, (y >> 8) as f32 / (1 << 24) as f32]
+            })
+            .collect();
+
+        let params = WorleyParams { size, grid, _padding: [0; 2] };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Worley Noise Params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let points_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Worley Noise Points"),
+            contents: bytemuck::cast_slice(&points),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Worley Noise"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: WORLEY_NOISE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("worley_noise_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: WORLEY_NOISE_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("worley_noise_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: points_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&view) },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Worley Noise Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("worley_noise.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Worley Noise Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Worley Noise Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Worley Noise Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Worley Noise Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(size.div_ceil(WORKGROUP_SIZE), size.div_ceil(WORKGROUP_SIZE), 1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        texture
+    }
+}
+
+// 整数哈希（lowbias32）
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
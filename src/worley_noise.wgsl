@@ -0,0 +1,44 @@
+// Worley 噪声（Worley 1996）：每个网格格子一个特征点，纹素只需查找周围的格子
+
+struct WorleyParams {
+    size: u32,
+    // 每行的格子数，共 grid × grid 个特征点
+    grid: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: WorleyParams;
+// 每个格子内特征点的位置，范围 [0, 1)
+@group(0) @binding(1)
+var<storage, read> points: array<vec2f>;
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba32float, write>;
+
+// 查找周围 5 × 5 个格子：特征点可以在格子内任意位置，3 × 3 偶尔会漏掉最近的点。
+// 格子坐标首尾相接，纹理可以无缝重复；距离以格子边长为单位
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= vec2u(params.size)) {
+        return;
+    }
+    let grid = i32(params.grid);
+    let position = (vec2f(id.xy) + 0.5) / f32(params.size) * f32(grid);
+    let cell = vec2i(floor(position));
+    var f1 = 1e9;
+    var f2 = 1e9;
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let neighbor = cell + vec2i(x, y);
+            let wrapped = ((neighbor % grid) + grid) % grid;
+            let point = vec2f(neighbor) + points[wrapped.y * grid + wrapped.x];
+            let distance = length(point - position);
+            if distance < f1 {
+                f2 = f1;
+                f1 = distance;
+            } else if distance < f2 {
+                f2 = distance;
+            }
+        }
+    }
+    textureStore(output, id.xy, vec4f(f1, f2, f2 - f1, 1.0));
+}
@@ -0,0 +1,97 @@
+// Texture::from_ktx2 对块压缩纹理尺寸的检查；没有可用适配器时跳过
+use learn_wgpu::texture::{ Ktx2Error, Texture };
+
+const KTX2_MAGIC: [u8; 12] = [0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a];
+// VK_FORMAT_BC1_RGBA_UNORM_BLOCK
+const BC1_RGBA_UNORM: u32 = 133;
+const BC1_BLOCK_SIZE: u32 = 8;
+
+struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Headless {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .ok()?;
+        Some(Self { device, queue })
+    }
+}
+
+// 只有一级 mip、没有数据格式描述与键值数据的 BC1 文件，每个块都是纯红色
+fn bc1_ktx2(width: u32, height: u32) -> Vec<u8> {
+    let blocks = width.div_ceil(4) * height.div_ceil(4);
+    let level_length = (blocks * BC1_BLOCK_SIZE) as u64;
+    // 头部 80 字节，之后是一级 mip 的 24 字节索引
+    let level_offset = 80 + 24u64;
+
+    let mut bytes = KTX2_MAGIC.to_vec();
+    // vkFormat、typeSize、宽、高、深、层数、面数、mip 级数、超压缩方式
+    for value in [BC1_RGBA_UNORM, 1, width, height, 0, 0, 1, 1, 0] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    // dfd 与 kvd 的偏移和长度
+    bytes.extend_from_slice(&[0; 16]);
+    // sgd 的偏移和长度
+    bytes.extend_from_slice(&[0; 16]);
+    for value in [level_offset, level_length, level_length] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    for _ in 0..blocks {
+        bytes.extend_from_slice(&[0x00, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    }
+    bytes
+}
+
+#[test]
+fn unaligned_block_compressed_size_is_rejected() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping KTX2 test");
+        return;
+    };
+    for (width, height) in [(6, 8), (8, 6), (2, 2)] {
+        let result = Texture::from_ktx2(&bc1_ktx2(width, height), &headless.device, &headless.queue, "KTX2 Test");
+        assert!(
+            matches!(result, Err(Ktx2Error::UnalignedSize { width: w, height: h }) if (w, h) == (width, height)),
+            "{width}x{height} BC1 texture was not rejected: {:?}",
+            result.err(),
+        );
+    }
+}
+
+#[test]
+fn aligned_block_compressed_texture_loads() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping KTX2 test");
+        return;
+    };
+    let texture = Texture::from_ktx2(&bc1_ktx2(8, 4), &headless.device, &headless.queue, "KTX2 Test")
+        .unwrap_or_else(|e| panic!("failed to load an 8x4 BC1 texture: {e}"));
+    assert_eq!((texture.texture.width(), texture.texture.height()), (8, 4));
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
@@ -0,0 +1,243 @@
+// generate_mipmaps 对每种支持的格式生成的各级 mip 与 CPU 上 2x2 平均的结果比较；没有可用适配器时跳过
+use learn_wgpu::mipmap::{ generate_mipmaps, MipmapError };
+
+const SIZE: u32 = 4;
+const LAYERS: u32 = 2;
+const MIP_LEVELS: u32 = 3;
+
+struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Headless {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .ok()?;
+        Some(Self { device, queue })
+    }
+
+    fn create_texture(&self, format: wgpu::TextureFormat, usage: wgpu::TextureUsages) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Mipmap Test Texture"),
+            size: wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: LAYERS,
+            },
+            mip_level_count: MIP_LEVELS,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: usage | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    // 读回某一级 mip 的所有层，按层、行、列顺序返回解码后的纹素
+    fn read_level(&self, texture: &wgpu::Texture, level: u32) -> Vec<[f32; 4]> {
+        let format = texture.format();
+        let texel_size = format.block_size(None).unwrap();
+        let size = SIZE >> level;
+        let unpadded_row = size * texel_size;
+        let padded_row = wgpu::util::align_to(unpadded_row, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mipmap Test Readback"),
+            size: (padded_row * size * LAYERS) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Test Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(size),
+                },
+            },
+            wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: LAYERS,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.expect("failed to map readback buffer"));
+        self.device.poll(wgpu::Maintain::Wait);
+        let data = buffer.slice(..).get_mapped_range();
+        data.chunks_exact(padded_row as usize)
+            .flat_map(|row| row[..unpadded_row as usize].chunks_exact(texel_size as usize))
+            .map(|texel| decode(format, texel))
+            .collect()
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+// 只处理规格化数与零，测试数据不会用到其他情况
+fn f16_to_f32(bits: u16) -> f32 {
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32 / 1024.0;
+    if exponent == 0 { 0.0 } else { (1.0 + mantissa) * 2f32.powi(exponent - 15) }
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    if value == 0.0 {
+        return 0;
+    }
+    let exponent = value.log2().floor() as i32;
+    let mantissa = (value / 2f32.powi(exponent) - 1.0) * 1024.0;
+    (((exponent + 15) as u16) << 10) | mantissa.round() as u16
+}
+
+// 着色器中看到的值：sRGB 的颜色通道已解码到线性空间
+fn decode(format: wgpu::TextureFormat, texel: &[u8]) -> [f32; 4] {
+    std::array::from_fn(|channel| match format {
+        wgpu::TextureFormat::Rgba16Float => f16_to_f32(u16::from_le_bytes([texel[channel * 2], texel[channel * 2 + 1]])),
+        wgpu::TextureFormat::Rgba8UnormSrgb if channel < 3 => srgb_to_linear(texel[channel] as f32 / 255.0),
+        _ => texel[channel] as f32 / 255.0,
+    })
+}
+
+// 换算回存储的数值，便于按一个量化步长比较
+fn stored(format: wgpu::TextureFormat, channel: usize, value: f32) -> f32 {
+    match format {
+        wgpu::TextureFormat::Rgba16Float => value * 1024.0,
+        wgpu::TextureFormat::Rgba8UnormSrgb if channel < 3 => linear_to_srgb(value) * 255.0,
+        _ => value * 255.0,
+    }
+}
+
+// 每个纹素取 0、0.25 … 1 中的一个值，半精度下平均结果也能精确表示
+fn level_zero(format: wgpu::TextureFormat) -> Vec<u8> {
+    let mut state = 0x2545_f491u32;
+    (0..SIZE * SIZE * LAYERS * 4)
+        .flat_map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let value = (state % 5) as f32 * 0.25;
+            match format {
+                wgpu::TextureFormat::Rgba16Float => f32_to_f16(value).to_le_bytes().to_vec(),
+                _ => vec![(value * 255.0).round() as u8],
+            }
+        })
+        .collect()
+}
+
+fn check_format(headless: &Headless, format: wgpu::TextureFormat, usage: wgpu::TextureUsages) {
+    let texture = headless.create_texture(format, usage);
+    let texel_size = format.block_size(None).unwrap();
+    headless.queue.write_texture(
+        texture.as_image_copy(),
+        &level_zero(format),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(SIZE * texel_size),
+            rows_per_image: Some(SIZE),
+        },
+        texture.size(),
+    );
+    generate_mipmaps(&headless.device, &headless.queue, &texture, format, LAYERS)
+        .unwrap_or_else(|e| panic!("generate_mipmaps failed for {format:?}: {e}"));
+
+    // 每一级与读回的上一级做 CPU 平均比较，量化误差不会逐级累积
+    let mut previous = headless.read_level(&texture, 0);
+    for level in 1..MIP_LEVELS {
+        let actual = headless.read_level(&texture, level);
+        let (source_size, size) = ((SIZE >> (level - 1)) as usize, (SIZE >> level) as usize);
+        for layer in 0..LAYERS as usize {
+            for y in 0..size {
+                for x in 0..size {
+                    let got = actual[(layer * size + y) * size + x];
+                    for channel in 0..4 {
+                        let expected = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                            .iter()
+                            .map(|(dx, dy)| previous[(layer * source_size + 2 * y + dy) * source_size + 2 * x + dx][channel])
+                            .sum::<f32>()
+                            / 4.0;
+                        let difference = (stored(format, channel, got[channel]) - stored(format, channel, expected)).abs();
+                        assert!(
+                            difference <= 1.0,
+                            "{format:?} level {level} layer {layer} texel ({x}, {y}) channel {channel}: got {}, expected {expected}",
+                            got[channel],
+                        );
+                    }
+                }
+            }
+        }
+        previous = actual;
+    }
+}
+
+#[test]
+fn mips_average_each_supported_format() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping mipmap test");
+        return;
+    };
+    let storage = wgpu::TextureUsages::STORAGE_BINDING;
+    check_format(&headless, wgpu::TextureFormat::Rgba8Unorm, storage);
+    check_format(&headless, wgpu::TextureFormat::Rgba16Float, storage);
+    check_format(
+        &headless,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureUsages::RENDER_ATTACHMENT,
+    );
+}
+
+#[test]
+fn unsupported_format_is_rejected_before_gpu_work() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping mipmap test");
+        return;
+    };
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+    let texture = headless.create_texture(format, wgpu::TextureUsages::TEXTURE_BINDING);
+    assert!(matches!(
+        generate_mipmaps(&headless.device, &headless.queue, &texture, format, LAYERS),
+        Err(MipmapError::UnsupportedFormat(_))
+    ));
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
@@ -0,0 +1,142 @@
+// GpuPrefixSum 与 CPU 顺序累加的结果比较；没有可用适配器时跳过
+use wgpu::util::DeviceExt;
+
+use learn_wgpu::error_scope::ErrorScope;
+use learn_wgpu::prefix_sum::GpuPrefixSum;
+
+const COUNT: usize = 1 << 20;
+
+struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Headless {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .ok()?;
+        Some(Self { device, queue })
+    }
+
+    // float 为 true 时按 f32 扫描；返回输出缓冲的原始字节
+    fn exclusive_scan(&self, values: &[u8], float: bool) -> Vec<u8> {
+        let device = &self.device;
+        let values_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Prefix Sum Test Values"),
+            contents: values,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Prefix Sum Test Output"),
+            size: values.len() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Prefix Sum Test Readback"),
+            size: values.len() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let error_scope = ErrorScope::new(device, "GpuPrefixSum::new");
+        let mut prefix_sum = GpuPrefixSum::new(device);
+        error_scope.assert_no_errors();
+
+        let error_scope = ErrorScope::new(device, "prefix sum dispatch");
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Prefix Sum Test Encoder"),
+        });
+        let count = (values.len() / 4) as u32;
+        if float {
+            prefix_sum.exclusive_scan_f32(device, &mut encoder, &values_buffer, &output_buffer, count);
+        } else {
+            prefix_sum.exclusive_scan(device, &mut encoder, &values_buffer, &output_buffer, count);
+        }
+        encoder.copy_buffer_to_buffer(&output_buffer, 0, &readback, 0, readback.size());
+        self.queue.submit(std::iter::once(encoder.finish()));
+        error_scope.assert_no_errors();
+
+        let error_scope = ErrorScope::new(device, "prefix sum readback");
+        readback.slice(..).map_async(wgpu::MapMode::Read, |result| result.expect("failed to map readback buffer"));
+        self.device.poll(wgpu::Maintain::Wait);
+        let data = readback.slice(..).get_mapped_range().to_vec();
+        error_scope.assert_no_errors();
+        data
+    }
+}
+
+// xorshift32，固定种子保证每次运行输入相同
+fn random_values(count: usize) -> Vec<u32> {
+    let mut state = 0x9e37_79b9u32;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        })
+        .collect()
+}
+
+#[test]
+fn u32_scan_matches_cpu() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping prefix sum test");
+        return;
+    };
+    // 取低 16 位，总和不会回绕也能覆盖较大的值
+    let values: Vec<u32> = random_values(COUNT).into_iter().map(|value| value & 0xffff).collect();
+    let output = headless.exclusive_scan(bytemuck::cast_slice(&values), false);
+    let output: &[u32] = bytemuck::cast_slice(&output);
+
+    let mut running = 0u32;
+    for (index, (value, got)) in values.iter().zip(output).enumerate() {
+        assert_eq!(*got, running, "prefix sum differs at index {index}");
+        running = running.wrapping_add(*value);
+    }
+}
+
+#[test]
+fn f32_scan_matches_cpu() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping prefix sum test");
+        return;
+    };
+    let values: Vec<f32> = random_values(COUNT).into_iter().map(|value| (value >> 8) as f32 / (1 << 24) as f32).collect();
+    let output = headless.exclusive_scan(bytemuck::cast_slice(&values), true);
+    let output: &[f32] = bytemuck::cast_slice(&output);
+
+    // GPU 按树形顺序求和，与 f64 顺序累加的参考值比较相对误差
+    let mut running = 0f64;
+    for (index, (value, got)) in values.iter().zip(output).enumerate() {
+        let error = (*got as f64 - running).abs();
+        assert!(error <= 1e-4 * running.max(1.0), "prefix sum differs at index {index}: got {got}, expected {running}");
+        running += *value as f64;
+    }
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
@@ -0,0 +1,129 @@
+// GpuRadixSort 与 CPU 稳定排序的结果比较；没有可用适配器时跳过
+use wgpu::util::DeviceExt;
+
+use learn_wgpu::radix_sort::GpuRadixSort;
+
+const COUNT: usize = 100_000;
+
+struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Headless {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .ok()?;
+        Some(Self { device, queue })
+    }
+
+    fn storage_buffer(&self, label: &str, contents: &[u8], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | usage,
+        })
+    }
+
+    fn read_back(&self, buffer: &wgpu::Buffer) -> Vec<u8> {
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Radix Sort Test Readback"),
+            size: buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Radix Sort Test Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, buffer.size());
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        readback.slice(..).map_async(wgpu::MapMode::Read, |result| result.expect("failed to map readback buffer"));
+        self.device.poll(wgpu::Maintain::Wait);
+        let data = readback.slice(..).get_mapped_range().to_vec();
+        data
+    }
+}
+
+// xorshift32，固定种子保证每次运行输入相同；键只取 16 位，保证有大量相等的键以检查稳定性
+fn random_keys(count: usize, seed: u32) -> Vec<[u32; 2]> {
+    let mut state = seed;
+    (0..count as u32)
+        .map(|index| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            [state >> 16, index]
+        })
+        .collect()
+}
+
+#[test]
+fn sort_and_gather_match_cpu() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping radix sort test");
+        return;
+    };
+    let mut radix_sort = GpuRadixSort::new(&headless.device);
+    let draws: Vec<wgpu::util::DrawIndexedIndirect> = (0..COUNT as u32)
+        .map(|index| wgpu::util::DrawIndexedIndirect {
+            vertex_count: 3,
+            instance_count: 1,
+            base_index: 0,
+            vertex_offset: 0,
+            base_instance: index,
+        })
+        .collect();
+    let draws_bytes: Vec<u8> = draws.iter().flat_map(|draw| draw.as_bytes().to_vec()).collect();
+    let draws_buffer = headless.storage_buffer("Radix Sort Test Draws", &draws_bytes, wgpu::BufferUsages::empty());
+    let sorted_draws = headless.storage_buffer("Radix Sort Test Sorted Draws", &draws_bytes, wgpu::BufferUsages::INDIRECT);
+
+    // 同一个键缓冲连续排序两次不同的输入，第二次复用缓存的绑定组
+    let keys_buffer = headless.storage_buffer("Radix Sort Test Keys", &vec![0; COUNT * 8], wgpu::BufferUsages::COPY_DST);
+    for seed in [0x9e37_79b9, 0x2545_f491] {
+        let keys = random_keys(COUNT, seed);
+        headless.queue.write_buffer(&keys_buffer, 0, bytemuck::cast_slice(&keys));
+        let mut encoder = headless.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Radix Sort Test Encoder"),
+        });
+        radix_sort.sort(&headless.device, &headless.queue, &mut encoder, &keys_buffer, COUNT as u32);
+        radix_sort.gather_indirect(&headless.device, &mut encoder, &keys_buffer, &draws_buffer, &sorted_draws);
+        headless.queue.submit(std::iter::once(encoder.finish()));
+
+        let mut expected = keys;
+        expected.sort_by_key(|&[key, _]| key);
+        let sorted = headless.read_back(&keys_buffer);
+        assert!(bytemuck::cast_slice::<u8, [u32; 2]>(&sorted) == expected.as_slice(), "GPU sort differs from a stable CPU sort");
+
+        let gathered = headless.read_back(&sorted_draws);
+        for (position, (draw, [_, index])) in gathered.chunks_exact(20).zip(&expected).enumerate() {
+            let base_instance = u32::from_le_bytes(draw[16..20].try_into().unwrap());
+            assert_eq!(base_instance, *index, "gathered draw {position} is out of order");
+        }
+    }
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
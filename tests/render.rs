@@ -0,0 +1,384 @@
+// 无窗口渲染固定场景并与 tests/golden 下的基准图逐像素比较
+// 运行：cargo test --features render-tests；REGEN=1 时重新生成基准图
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+use std::time::Duration;
+
+use cgmath::Vector3;
+use wgpu::util::DeviceExt;
+
+use learn_wgpu::backend::RenderCapabilities;
+use learn_wgpu::camera::Camera;
+use learn_wgpu::clock::DeterministicClock;
+use learn_wgpu::error_scope::ErrorScope;
+use learn_wgpu::mesh::Vertex;
+use learn_wgpu::primitives;
+use learn_wgpu::scene::Scene;
+use learn_wgpu::texture::Texture;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+// 每个通道允许的最大误差（L∞），可用 RENDER_TEST_TOLERANCE 覆盖
+const DEFAULT_TOLERANCE: u8 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SceneUniform {
+    view_proj: [[f32; 4]; 4],
+    light_dir: [f32; 4],
+}
+
+struct Headless {
+    // Scene 与工作线程共享设备
+    device: Arc<wgpu::Device>,
+    queue: wgpu::Queue,
+    capabilities: RenderCapabilities,
+}
+
+impl Headless {
+    // 没有可用适配器时返回 None，测试跳过而不是失败
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        // 与窗口程序一致：支持时启用间接绘制，透明物体走 GPU 排序路径；传送门的备用管线需要 SHADER_UNUSED_VERTEX_OUTPUT
+        let capabilities = RenderCapabilities::negotiate(
+            &adapter,
+            wgpu::Features::MULTI_DRAW_INDIRECT
+                | wgpu::Features::INDIRECT_FIRST_INSTANCE
+                | wgpu::Features::SHADER_UNUSED_VERTEX_OUTPUT,
+        );
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features: capabilities.features,
+                    limits: capabilities.limits.clone(),
+                },
+                None,
+            )
+            .await
+            .ok()?;
+        Some(Self { device: Arc::new(device), queue, capabilities })
+    }
+
+    fn render_sphere(&self) -> Vec<u8> {
+        let device = &self.device;
+        let camera = Camera {
+            eye: (0.0, 0.0, 3.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: Vector3::unit_y(),
+            aspect: WIDTH as f32 / HEIGHT as f32,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+        };
+        let scene = SceneUniform {
+            view_proj: camera.build_view_projection_matrix().into(),
+            light_dir: [-0.5, 0.8, 0.6, 0.0],
+        };
+        let scene_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Render Test Scene"),
+            contents: bytemuck::cast_slice(&[scene]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("render_test_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("render_test_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: scene_buffer.as_entire_binding(),
+            }],
+        });
+
+        let error_scope = ErrorScope::new(device, "render test pipeline");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Render Test Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("render/lit.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Test Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Test Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        error_scope.assert_no_errors();
+
+        let error_scope = ErrorScope::new(device, "render test draw");
+        let sphere = primitives::sphere(1.0, 32, 48);
+        let (vertex_buffer, index_buffer) = sphere.upload(device, &self.queue);
+        let color = Texture::create_render_target(device, WIDTH, HEIGHT, FORMAT, wgpu::TextureUsages::COPY_SRC, "Render Test Color");
+        let depth = Texture::create_render_target(
+            device,
+            WIDTH,
+            HEIGHT,
+            Texture::DEPTH_FORMAT,
+            wgpu::TextureUsages::empty(),
+            "Render Test Depth",
+        );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Test Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Test Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..sphere.index_count(), 0, 0..1);
+        }
+        error_scope.assert_no_errors();
+
+        read_back(device, &self.queue, encoder, &color.texture)
+    }
+
+    // 用固定步长时钟逐帧渲染演示场景，返回每帧画面的哈希；configure 在第一帧之前修改场景
+    fn scene_frame_hashes(self, frames: usize, configure: impl FnOnce(&mut Scene)) -> Vec<u64> {
+        let color = Texture::create_render_target(&self.device, WIDTH, HEIGHT, FORMAT, wgpu::TextureUsages::COPY_SRC, "Scene Test Color");
+        // 后台管线编译需要 tokio 运行时
+        block_on(async {
+            let error_scope = ErrorScope::new(&self.device, "Scene::new");
+            let mut scene = Scene::new(
+                self.device.clone(),
+                self.queue,
+                self.capabilities,
+                FORMAT,
+                (WIDTH, HEIGHT),
+                Scene::default_assets(),
+                |_| {},
+            );
+            scene.set_clock(DeterministicClock::fixed(Duration::from_secs_f64(1.0 / 60.0)));
+            scene.wait_for_pipelines();
+            configure(&mut scene);
+            error_scope.assert_no_errors();
+
+            (0..frames)
+                .map(|_| {
+                    let error_scope = ErrorScope::new(&self.device, "Scene::step_frame");
+                    scene.step_frame(&color.view, (WIDTH, HEIGHT));
+                    error_scope.assert_no_errors();
+                    let encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Scene Test Readback Encoder"),
+                    });
+                    let pixels = read_back(&self.device, scene.queue(), encoder, &color.texture);
+                    let mut hasher = DefaultHasher::new();
+                    pixels.hash(&mut hasher);
+                    hasher.finish()
+                })
+                .collect()
+        })
+    }
+}
+
+// 绘制命令录制在 encoder 中，录制错误在 finish 时才报告，由这里的作用域捕获
+fn read_back(device: &wgpu::Device, queue: &wgpu::Queue, mut encoder: wgpu::CommandEncoder, texture: &wgpu::Texture) -> Vec<u8> {
+    let error_scope = ErrorScope::new(device, "read_back");
+    let unpadded_row = 4 * WIDTH;
+    let padded_row = wgpu::util::align_to(unpadded_row, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Render Test Readback"),
+        size: (padded_row * HEIGHT) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(HEIGHT),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.expect("failed to map readback buffer"));
+    device.poll(wgpu::Maintain::Wait);
+
+    let data = buffer.slice(..).get_mapped_range();
+    let pixels = data
+        .chunks_exact(padded_row as usize)
+        .flat_map(|row| &row[..unpadded_row as usize])
+        .copied()
+        .collect();
+    error_scope.assert_no_errors();
+    pixels
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.png"))
+}
+
+fn tolerance() -> u8 {
+    std::env::var("RENDER_TEST_TOLERANCE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_TOLERANCE)
+}
+
+// 比较失败时把实际结果写到 target/render-tests 下便于查看
+fn compare_with_golden(name: &str, pixels: Vec<u8>) {
+    let path = golden_path(name);
+    let actual = image::RgbaImage::from_raw(WIDTH, HEIGHT, pixels).expect("readback has the wrong size");
+
+    if std::env::var("REGEN").is_ok_and(|value| value == "1") {
+        actual.save(&path).expect("failed to write golden image");
+        eprintln!("regenerated {}", path.display());
+        return;
+    }
+
+    let golden = image::open(&path)
+        .unwrap_or_else(|e| panic!("missing golden image {} ({e}); run with REGEN=1 to create it", path.display()))
+        .to_rgba8();
+    assert_eq!(golden.dimensions(), actual.dimensions(), "golden image {} has a different size", path.display());
+
+    let tolerance = tolerance();
+    let mut failed = 0;
+    let mut worst = 0;
+    for (expected, got) in golden.pixels().zip(actual.pixels()) {
+        let difference = expected.0.iter().zip(got.0.iter()).map(|(a, b)| a.abs_diff(*b)).max().unwrap_or(0);
+        worst = worst.max(difference);
+        if difference > tolerance {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.actual.png"));
+        let _ = actual.save(&output);
+        panic!(
+            "{failed} pixel(s) of `{name}` differ from the golden image by more than {tolerance}/255 (worst {worst}/255); actual image written to {}",
+            output.display(),
+        );
+    }
+}
+
+#[test]
+fn lit_sphere_matches_golden() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping render test");
+        return;
+    };
+    compare_with_golden("lit_sphere", headless.render_sphere());
+}
+
+// 两次从头渲染 10 帧，每帧画面都应相同；首尾两帧不同说明时钟确实推动了动画
+#[test]
+fn fixed_clock_frames_are_reproducible() {
+    // Scene 接管队列，每次运行使用新设备；GL 后端上同时存在两个实例会互相影响，依次创建
+    let run = || block_on(Headless::new()).map(|headless| headless.scene_frame_hashes(10, |_| {}));
+    let Some(first) = run() else {
+        eprintln!("no GPU adapter available, skipping render test");
+        return;
+    };
+    let second = run().expect("adapter disappeared between runs");
+    assert_eq!(first, second, "frame hashes differ between two runs with the same fixed clock");
+    assert_ne!(first[0], first[9], "the scene did not change over 10 fixed-step frames");
+}
+
+// 材质设置光照贴图后画面应与默认的白色光照贴图不同
+#[test]
+fn lightmapped_material_differs_from_default() {
+    let Some(default) = block_on(Headless::new()).map(|headless| headless.scene_frame_hashes(1, |_| {})) else {
+        eprintln!("no GPU adapter available, skipping render test");
+        return;
+    };
+    let lightmapped = block_on(Headless::new())
+        .expect("adapter disappeared between runs")
+        .scene_frame_hashes(1, |scene| {
+            let lightmap = Texture::from_color(scene.device(), scene.queue(), [64, 0, 0, 255], "Scene Test Lightmap");
+            let handle = scene.add_lightmap(lightmap);
+            scene.set_lightmap(Some(handle));
+        });
+    assert_ne!(default, lightmapped, "setting a lightmap did not change the rendered frame");
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
@@ -0,0 +1,32 @@
+// 渲染测试使用的固定场景：单个方向光 + 环境光的 Lambert 着色
+struct SceneUniform {
+    view_proj: mat4x4f,
+    light_dir: vec4f,
+};
+@group(0) @binding(0)
+var<uniform> scene: SceneUniform;
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) normal: vec3f,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = scene.view_proj * vec4f(in.position, 1.0);
+    out.normal = in.normal;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let diffuse = max(dot(normalize(in.normal), normalize(scene.light_dir.xyz)), 0.0);
+    let color = vec3f(0.8, 0.5, 0.3) * (0.1 + 0.9 * diffuse);
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,174 @@
+// RenderBundleCache 的键要能区分原地重建的资源；没有可用适配器时跳过
+use learn_wgpu::render_bundle_cache::RenderBundleCache;
+use learn_wgpu::render_queue::{ DrawCall, RenderQueue };
+use learn_wgpu::secondary_command_buffer::BundleTarget;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const SHADER: &str = "
+@group(0) @binding(0) var<uniform> color: vec4f;
+
+@vertex
+fn vs_main(@location(0) position: vec2f) -> @builtin(position) vec4f {
+    return vec4f(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return color;
+}
+";
+
+struct Headless {
+    device: wgpu::Device,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl Headless {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let adapter = match instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+        {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await?,
+        };
+        let (device, _queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .ok()?;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bundle Cache Test Shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("bundle_cache_test_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bundle Cache Test Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Bundle Cache Test Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: 8,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        Some(Self { device, pipeline, bind_group_layout })
+    }
+
+    fn buffer(&self, usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bundle Cache Test Buffer"),
+            size: 64,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn bind_group(&self, uniform: &wgpu::Buffer) -> wgpu::BindGroup {
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("bundle_cache_test_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform.as_entire_binding(),
+            }],
+        })
+    }
+
+    fn record(&self, cache: &mut RenderBundleCache, bind_group: &wgpu::BindGroup, vertices: &wgpu::Buffer) {
+        let mut queue = RenderQueue::new();
+        queue.push(
+            DrawCall::new(&self.pipeline)
+                .with_bind_group(0, bind_group)
+                .with_vertex_buffer(0, vertices, ..)
+                .with_elements(0..3),
+        );
+        queue.sort();
+        cache.get_or_record("static", &self.device, &BundleTarget::new(&[FORMAT], None), &queue);
+    }
+}
+
+// 与 resize_render_targets 重建光照绑定组一样，在同一个字段上重新赋值，地址不变
+#[test]
+fn bind_group_replaced_in_place_misses() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping render bundle cache test");
+        return;
+    };
+    let uniform = headless.buffer(wgpu::BufferUsages::UNIFORM);
+    let vertices = headless.buffer(wgpu::BufferUsages::VERTEX);
+    let mut bind_group = headless.bind_group(&uniform);
+    let mut cache = RenderBundleCache::new();
+
+    headless.record(&mut cache, &bind_group, &vertices);
+    headless.record(&mut cache, &bind_group, &vertices);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    bind_group = headless.bind_group(&uniform);
+    headless.record(&mut cache, &bind_group, &vertices);
+    assert_eq!(cache.misses(), 2, "a rebuilt bind group at the same address reused the stale bundle");
+}
+
+// 资源重新加载时以相同大小重新分配缓冲区
+#[test]
+fn buffer_reallocated_in_place_misses() {
+    let Some(headless) = block_on(Headless::new()) else {
+        eprintln!("no GPU adapter available, skipping render bundle cache test");
+        return;
+    };
+    let uniform = headless.buffer(wgpu::BufferUsages::UNIFORM);
+    let bind_group = headless.bind_group(&uniform);
+    let mut vertices = headless.buffer(wgpu::BufferUsages::VERTEX);
+    let mut cache = RenderBundleCache::new();
+
+    headless.record(&mut cache, &bind_group, &vertices);
+    vertices = headless.buffer(wgpu::BufferUsages::VERTEX);
+    headless.record(&mut cache, &bind_group, &vertices);
+    assert_eq!(cache.misses(), 2, "a reallocated vertex buffer of the same size reused the stale bundle");
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}
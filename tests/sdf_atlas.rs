@@ -0,0 +1,111 @@
+// 用 tests/fonts 下的 Cantarell（SIL OFL 1.1）为可打印 ASCII 生成 SDF 图集并检查布局
+use learn_wgpu::error_scope::ErrorScope;
+use learn_wgpu::sdf_atlas::{ SdfAtlas, SdfAtlasBuilder };
+
+const FONT: &[u8] = include_bytes!("fonts/Cantarell-Regular.ttf");
+const ATLAS_SIZE: u32 = 512;
+const SDF_RANGE: u32 = 4;
+// 距离场中轮廓边缘对应的值
+const EDGE_VALUE: u8 = 128;
+
+fn build_atlas() -> SdfAtlas {
+    let glyph_set: String = (' '..='~').collect();
+    SdfAtlasBuilder::from_ttf(FONT, &glyph_set, ATLAS_SIZE, SDF_RANGE).expect("failed to build SDF atlas")
+}
+
+// 按 UV 换算回图集中的像素矩形 [x0, y0, x1, y1)
+fn pixel_rect(uv_min: [f32; 2], uv_max: [f32; 2]) -> [u32; 4] {
+    let to_pixel = |uv: f32| (uv * ATLAS_SIZE as f32).round() as u32;
+    [to_pixel(uv_min[0]), to_pixel(uv_min[1]), to_pixel(uv_max[0]), to_pixel(uv_max[1])]
+}
+
+#[test]
+fn outlined_glyphs_have_metrics_inside_atlas() {
+    let atlas = build_atlas();
+    let face = ttf_parser::Face::parse(FONT, 0).expect("failed to parse font fixture");
+    for c in ' '..='~' {
+        let glyph_id = face.glyph_index(c).unwrap_or_else(|| panic!("font fixture has no glyph for {c:?}"));
+        if face.glyph_bounding_box(glyph_id).is_none() {
+            continue;
+        }
+        let metrics = atlas.glyph_metrics.get(&c).unwrap_or_else(|| panic!("no metrics for {c:?}"));
+        assert!(metrics.size[0] > 0.0 && metrics.size[1] > 0.0, "{c:?} has an outline but an empty quad");
+        for axis in 0..2 {
+            assert!(
+                0.0 <= metrics.uv_min[axis] && metrics.uv_min[axis] < metrics.uv_max[axis] && metrics.uv_max[axis] <= 1.0,
+                "{c:?} has UVs outside the atlas: {:?}..{:?}",
+                metrics.uv_min,
+                metrics.uv_max,
+            );
+        }
+    }
+}
+
+#[test]
+fn glyph_rects_do_not_overlap() {
+    let atlas = build_atlas();
+    let rects: Vec<(char, [u32; 4])> = atlas
+        .glyph_metrics
+        .iter()
+        .filter(|(_, metrics)| metrics.size[0] > 0.0 && metrics.size[1] > 0.0)
+        .map(|(c, metrics)| (*c, pixel_rect(metrics.uv_min, metrics.uv_max)))
+        .collect();
+    for (i, (a, rect_a)) in rects.iter().enumerate() {
+        for (b, rect_b) in &rects[i + 1..] {
+            let overlaps = rect_a[0] < rect_b[2] && rect_b[0] < rect_a[2] && rect_a[1] < rect_b[3] && rect_b[1] < rect_a[3];
+            assert!(!overlaps, "glyph rects for {a:?} {rect_a:?} and {b:?} {rect_b:?} overlap");
+        }
+    }
+}
+
+#[test]
+fn distance_field_spans_edge_value() {
+    let atlas = build_atlas();
+    assert_eq!(atlas.texture_data.len(), (ATLAS_SIZE * ATLAS_SIZE) as usize);
+    assert!(atlas.texture_data.iter().any(|&value| value > EDGE_VALUE), "no texels inside any glyph");
+    assert!(atlas.texture_data.iter().any(|&value| value < EDGE_VALUE), "no texels outside any glyph");
+}
+
+// 上传到 R8 纹理不应产生校验错误；没有可用适配器时跳过
+#[test]
+fn atlas_uploads_without_validation_errors() {
+    let Some((device, queue)) = block_on(request_device()) else {
+        eprintln!("no GPU adapter available, skipping SDF atlas upload test");
+        return;
+    };
+    let atlas = build_atlas();
+    let error_scope = ErrorScope::new(&device, "SdfAtlas::create_texture");
+    let texture = atlas.create_texture(&device, &queue);
+    queue.submit(None);
+    device.poll(wgpu::Maintain::Wait);
+    error_scope.assert_no_errors();
+    assert_eq!(texture.width(), ATLAS_SIZE);
+    assert_eq!(texture.format(), wgpu::TextureFormat::R8Unorm);
+}
+
+async fn request_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let adapter = match instance
+        .request_adapter(&wgpu::RequestAdapterOptions::default())
+        .await
+    {
+        Some(adapter) => adapter,
+        None => instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                force_fallback_adapter: true,
+                ..Default::default()
+            })
+            .await?,
+    };
+    adapter.request_device(&wgpu::DeviceDescriptor::default(), None).await.ok()
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(future)
+}